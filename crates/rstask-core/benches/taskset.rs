@@ -0,0 +1,99 @@
+//! Timings for loading, filtering and rendering a large repository
+//!
+//! Run with `cargo bench -p rstask-core --features testing`, optionally
+//! followed by a substring to pick benchmarks, e.g. `-- load`. The fixture
+//! is generated once per run from a fixed seed, so numbers are comparable
+//! between runs of different commits.
+
+use rstask_core::JsonStyle;
+use rstask_core::query::parse_query;
+use rstask_core::taskset::{LoadOptions, TaskSet};
+use rstask_core::testing::RepoSpec;
+use std::hint::black_box;
+use std::io;
+use std::time::{Duration, Instant};
+
+const TASKS: usize = 10_000;
+const WARMUP: Duration = Duration::from_millis(500);
+const SAMPLES: usize = 20;
+
+/// Runs `f` for a warmup period, then times `SAMPLES` calls of it
+fn bench<T>(filter: Option<&str>, name: &str, mut f: impl FnMut() -> T) {
+    if filter.is_some_and(|filter| !name.contains(filter)) {
+        return;
+    }
+
+    let start = Instant::now();
+    while start.elapsed() < WARMUP {
+        black_box(f());
+    }
+
+    let mut times: Vec<Duration> = (0..SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+
+    let mean = times.iter().sum::<Duration>() / SAMPLES as u32;
+    println!(
+        "{:<28} min {:>10.2?}  median {:>10.2?}  mean {:>10.2?}",
+        name,
+        times[0],
+        times[SAMPLES / 2],
+        mean
+    );
+}
+
+fn main() {
+    // cargo passes --bench; anything else is a name filter
+    let filter = std::env::args().skip(1).find(|a| !a.starts_with("--"));
+    let filter = filter.as_deref();
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let repo = dir.path();
+    let ids_file = repo.join("ids.bin");
+    RepoSpec::new(TASKS).seed(1).write(repo).unwrap();
+    println!("{} generated tasks in {}", TASKS, repo.display());
+
+    bench(filter, "load/all", || {
+        TaskSet::load_with(repo, &ids_file, &LoadOptions::new(true)).unwrap()
+    });
+    bench(filter, "load/open", || {
+        TaskSet::load_with(repo, &ids_file, &LoadOptions::new(false)).unwrap()
+    });
+    bench(filter, "load/open_skip_notes", || {
+        TaskSet::load_with(repo, &ids_file, &LoadOptions::new(false).skip_notes(true)).unwrap()
+    });
+
+    let mut ts = TaskSet::load_with(repo, &ids_file, &LoadOptions::new(true)).unwrap();
+    let query = parse_query(&["+tag3".to_string(), "project:project2".to_string()]).unwrap();
+    let text_query = parse_query(&["release".to_string()]).unwrap();
+
+    bench(filter, "filter/tag_and_project", || {
+        for task in ts.tasks_mut().iter_mut() {
+            task.filtered = false;
+        }
+        ts.filter(&query);
+        ts.tasks().len()
+    });
+    bench(filter, "filter/text", || {
+        for task in ts.tasks_mut().iter_mut() {
+            task.filtered = false;
+        }
+        ts.filter(&text_query);
+        ts.tasks().len()
+    });
+
+    for task in ts.tasks_mut().iter_mut() {
+        task.filtered = false;
+    }
+    bench(filter, "json/pretty", || {
+        ts.write_json(io::sink(), JsonStyle::Pretty).unwrap()
+    });
+    bench(filter, "json/compact", || {
+        ts.write_json(io::sink(), JsonStyle::Compact).unwrap()
+    });
+}
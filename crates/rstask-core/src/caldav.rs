@@ -0,0 +1,836 @@
+//! Two-way synchronisation of open tasks with a CalDAV task list (VTODO)
+//!
+//! Entries are matched on UID, which is the task UUID. The href and etag of
+//! each synced entry are remembered locally, together with a hash of the task
+//! fields that were sent, so each side's changes since the last sync can be
+//! told apart. When both sides changed, the local task wins.
+
+use crate::constants::*;
+use crate::http::{Request, Response};
+use crate::ical::{RemoteTodo, parse_vtodos, priority_from_ical, task_to_vtodo_calendar};
+use crate::task::Task;
+use crate::taskset::TaskSet;
+use crate::util::{fnv1a, is_valid_uuid4_string};
+use crate::{Result, RstaskError};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Statuses that are mirrored to the server
+const SYNCED_STATUSES: &[&str] = &[
+    STATUS_PENDING,
+    STATUS_ACTIVE,
+    STATUS_PAUSED,
+    STATUS_DELEGATED,
+    STATUS_DEFERRED,
+];
+
+/// A VTODO resource on the server
+#[derive(Debug, Clone)]
+pub struct RemoteItem {
+    pub href: String,
+    pub etag: String,
+    pub todo: RemoteTodo,
+}
+
+/// Access to a CalDAV collection
+pub trait CalDavRemote {
+    /// Lists every VTODO in the collection
+    fn list(&self) -> Result<Vec<RemoteItem>>;
+
+    /// Creates or replaces a resource. `etag` guards against overwriting
+    /// concurrent changes; None means the resource must not exist yet.
+    /// Returns the new etag when the server reports one.
+    fn put(&self, href: &str, ics: &str, etag: Option<&str>) -> Result<Option<String>>;
+
+    /// Deletes a resource
+    fn delete(&self, href: &str, etag: &str) -> Result<()>;
+
+    /// Href to create a new resource for a task at
+    fn href_for(&self, uuid: &str) -> String;
+}
+
+/// What was last synced for one task
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncEntry {
+    pub href: String,
+    pub etag: String,
+    pub hash: u64,
+}
+
+/// How `SyncEntry::hash` is worked out. Files without it hold hashes from
+/// std's `DefaultHasher`, whose output can change between Rust releases, and
+/// are compared that way until a sync has rewritten every entry.
+const HASH_FORMAT: u32 = 1;
+
+/// Per-task sync bookkeeping, kept in `.git/rstask` since it is specific to
+/// this checkout
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CalDavState {
+    pub entries: HashMap<String, SyncEntry>,
+    #[serde(default)]
+    pub hash_format: u32,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl CalDavState {
+    pub fn load(repo_path: &Path) -> Self {
+        let path = repo_path.join(".git").join("rstask").join("caldav.json");
+        let mut state: CalDavState = std::fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        state.path = path;
+        state
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Counts of what a sync did
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncReport {
+    pub pushed: usize,
+    pub pulled: usize,
+    pub created_remote: usize,
+    pub created_local: usize,
+    pub deleted_remote: usize,
+    pub resolved_local: usize,
+    pub skipped: usize,
+}
+
+impl std::fmt::Display for SyncReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} pushed, {} pulled, {} uploaded, {} imported, {} deleted on server, {} resolved",
+            self.pushed,
+            self.pulled,
+            self.created_remote,
+            self.created_local,
+            self.deleted_remote,
+            self.resolved_local
+        )?;
+        if self.skipped > 0 {
+            write!(f, ", {} skipped (UID is not a UUID)", self.skipped)?;
+        }
+        Ok(())
+    }
+}
+
+/// Syncs the task set with the remote collection. `ts` must include resolved
+/// tasks so completions on either side are seen.
+pub fn sync_tasks(
+    ts: &mut TaskSet,
+    state: &mut CalDavState,
+    remote: &dyn CalDavRemote,
+) -> Result<SyncReport> {
+    let mut report = SyncReport::default();
+    let mut seen = HashSet::new();
+    let legacy = state.hash_format < HASH_FORMAT && !state.entries.is_empty();
+    let unchanged = |entry: &SyncEntry, task: &Task| {
+        entry.hash == task_hash(task) || (legacy && entry.hash == legacy_task_hash(task))
+    };
+
+    for item in remote.list()? {
+        let uuid = item.todo.uid.to_lowercase();
+        if !is_valid_uuid4_string(&uuid) {
+            report.skipped += 1;
+            continue;
+        }
+        seen.insert(uuid.clone());
+
+        let entry = state.entries.get(&uuid).cloned();
+        let Some(task) = ts.get_by_uuid(&uuid).cloned() else {
+            match entry {
+                // Synced before, then removed locally
+                Some(_) => {
+                    remote.delete(&item.href, &item.etag)?;
+                    state.entries.remove(&uuid);
+                    report.deleted_remote += 1;
+                }
+                None => {
+                    if is_closed(&item.todo.status) {
+                        continue;
+                    }
+                    let mut task = Task::new(item.todo.summary.clone());
+                    task.uuid = uuid.clone();
+                    apply_remote(&mut task, &item.todo);
+                    let task = ts.must_load_task(task)?;
+                    state.entries.insert(
+                        uuid,
+                        SyncEntry {
+                            href: item.href,
+                            etag: item.etag,
+                            hash: task_hash(&task),
+                        },
+                    );
+                    report.created_local += 1;
+                }
+            }
+            continue;
+        };
+
+        let hash = task_hash(&task);
+        let local_changed = entry.as_ref().is_none_or(|e| !unchanged(e, &task));
+        let remote_changed = entry.as_ref().is_none_or(|e| e.etag != item.etag);
+
+        let mut from_remote = task.clone();
+        apply_remote(&mut from_remote, &item.todo);
+
+        if task_hash(&from_remote) == hash {
+            // Already in step
+        } else if remote_changed && !local_changed {
+            ts.update_task(from_remote)?;
+            report.pulled += 1;
+        } else {
+            let etag = remote
+                .put(&item.href, &task_to_vtodo_calendar(&task), Some(&item.etag))?
+                .unwrap_or_default();
+            state.entries.insert(
+                uuid,
+                SyncEntry {
+                    href: item.href,
+                    etag,
+                    hash,
+                },
+            );
+            report.pushed += 1;
+            continue;
+        }
+
+        let hash = task_hash(ts.get_by_uuid(&uuid).unwrap_or(&task));
+        state.entries.insert(
+            uuid,
+            SyncEntry {
+                href: item.href,
+                etag: item.etag,
+                hash,
+            },
+        );
+    }
+
+    let local: Vec<Task> = ts
+        .all_tasks()
+        .iter()
+        .filter(|t| !seen.contains(&t.uuid))
+        .cloned()
+        .collect();
+
+    for mut task in local {
+        let entry = state.entries.get(&task.uuid).cloned();
+        let open = SYNCED_STATUSES.contains(&task.status.as_str());
+
+        match entry {
+            // Removed on the server: treat an untouched task as dealt with
+            Some(entry) if open && unchanged(&entry, &task) => {
+                task.status = STATUS_RESOLVED.to_string();
+                ts.update_task(task.clone())?;
+                state.entries.remove(&task.uuid);
+                report.resolved_local += 1;
+            }
+            Some(_) if !open => {
+                state.entries.remove(&task.uuid);
+            }
+            None if !open => {}
+            _ => {
+                let href = remote.href_for(&task.uuid);
+                let etag = remote
+                    .put(&href, &task_to_vtodo_calendar(&task), None)?
+                    .unwrap_or_default();
+                state.entries.insert(
+                    task.uuid.clone(),
+                    SyncEntry {
+                        href,
+                        etag,
+                        hash: task_hash(&task),
+                    },
+                );
+                report.created_remote += 1;
+            }
+        }
+    }
+
+    // Entries for tasks still on either side were all written above
+    state.hash_format = HASH_FORMAT;
+    Ok(report)
+}
+
+fn is_closed(status: &str) -> bool {
+    status == "COMPLETED" || status == "CANCELLED"
+}
+
+/// Hash of the fields that are mirrored to the server. It is kept between
+/// syncs, so it's FNV-1a of the fields as JSON, which every build agrees on
+fn task_hash(task: &Task) -> u64 {
+    let fields = (
+        &task.summary,
+        &task.notes,
+        &task.tags,
+        &task.priority,
+        task.due.map(|d| d.date_naive()),
+        remote_status(&task.status),
+    );
+    fnv1a(&serde_json::to_vec(&fields).unwrap_or_default())
+}
+
+/// `task_hash` as it was before `HASH_FORMAT`, for entries saved then
+fn legacy_task_hash(task: &Task) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    task.summary.hash(&mut hasher);
+    task.notes.hash(&mut hasher);
+    task.tags.hash(&mut hasher);
+    task.priority.hash(&mut hasher);
+    task.due.map(|d| d.date_naive()).hash(&mut hasher);
+    remote_status(&task.status).hash(&mut hasher);
+    hasher.finish()
+}
+
+fn remote_status(status: &str) -> &'static str {
+    match status {
+        STATUS_RESOLVED => "COMPLETED",
+        STATUS_ACTIVE => "IN-PROCESS",
+        _ => "NEEDS-ACTION",
+    }
+}
+
+/// Copies the mapped VTODO fields onto a task
+fn apply_remote(task: &mut Task, todo: &RemoteTodo) {
+    task.summary = todo.summary.clone();
+    task.notes = todo.description.clone();
+    task.tags = todo.categories.iter().map(|c| c.to_lowercase()).collect();
+    task.tags.sort();
+    task.tags.dedup();
+    task.priority = priority_from_ical(todo.priority).to_string();
+    task.due = todo.due;
+
+    let status = match todo.status.as_str() {
+        "COMPLETED" | "CANCELLED" => STATUS_RESOLVED,
+        "IN-PROCESS" => STATUS_ACTIVE,
+        _ if task.status == STATUS_ACTIVE => STATUS_PAUSED,
+        _ if task.status == STATUS_RESOLVED => STATUS_PENDING,
+        _ => task.status.as_str(),
+    };
+
+    if status != task.status && is_valid_status_transition(&task.status, status) {
+        task.status = status.to_string();
+    }
+}
+
+/// CalDAV client that makes its requests through curl
+pub struct CurlRemote {
+    collection_url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl CurlRemote {
+    pub fn new(collection_url: &str, username: Option<String>, password: Option<String>) -> Self {
+        let mut collection_url = collection_url.to_string();
+        if !collection_url.ends_with('/') {
+            collection_url.push('/');
+        }
+        CurlRemote {
+            collection_url,
+            username,
+            password,
+        }
+    }
+
+    /// Resolves an href from a multistatus response against the collection
+    fn url_for(&self, href: &str) -> String {
+        if href.starts_with("http://") || href.starts_with("https://") {
+            return href.to_string();
+        }
+        if href.starts_with('/') {
+            let scheme_end = self.collection_url.find("://").map_or(0, |i| i + 3);
+            let origin_end = self.collection_url[scheme_end..]
+                .find('/')
+                .map_or(self.collection_url.len(), |i| scheme_end + i);
+            return format!("{}{}", &self.collection_url[..origin_end], href);
+        }
+        format!("{}{}", self.collection_url, href)
+    }
+
+    fn request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[String],
+        body: Option<&str>,
+    ) -> Result<Response> {
+        let mut request = Request::new(method, url);
+        for header in headers {
+            request = request.header(header.as_str());
+        }
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+        if let Some(user) = &self.username {
+            request = request.basic_auth(user, self.password.as_deref().unwrap_or(""));
+        }
+
+        request
+            .send()
+            .map_err(|e| RstaskError::Other(format!("CalDAV request failed: {}", e)))
+    }
+}
+
+impl CalDavRemote for CurlRemote {
+    fn list(&self) -> Result<Vec<RemoteItem>> {
+        let query = r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><d:getetag/><c:calendar-data/></d:prop>
+  <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VTODO"/></c:comp-filter></c:filter>
+</c:calendar-query>"#;
+
+        let response = self.request(
+            "REPORT",
+            &self.collection_url,
+            &[
+                "Depth: 1".to_string(),
+                "Content-Type: application/xml; charset=utf-8".to_string(),
+            ],
+            Some(query),
+        )?;
+        if response.status != 207 {
+            return Err(RstaskError::Other(format!(
+                "CalDAV server returned {} when listing tasks",
+                response.status
+            )));
+        }
+
+        Ok(parse_multistatus(&response.text()))
+    }
+
+    fn put(&self, href: &str, ics: &str, etag: Option<&str>) -> Result<Option<String>> {
+        let precondition = match etag {
+            Some(etag) if !etag.is_empty() => format!("If-Match: {}", etag),
+            Some(_) => "If-Match: *".to_string(),
+            None => "If-None-Match: *".to_string(),
+        };
+        let response = self.request(
+            "PUT",
+            &self.url_for(href),
+            &[
+                "Content-Type: text/calendar; charset=utf-8".to_string(),
+                precondition,
+            ],
+            Some(ics),
+        )?;
+
+        match response.status {
+            200..=299 => Ok(response.header("ETag").map(|e| e.to_string())),
+            412 => Err(RstaskError::Other(format!(
+                "{} changed on the server during sync; run caldav-sync again",
+                href
+            ))),
+            status => Err(RstaskError::Other(format!(
+                "CalDAV server returned {} when saving {}",
+                status, href
+            ))),
+        }
+    }
+
+    fn delete(&self, href: &str, etag: &str) -> Result<()> {
+        let mut headers = Vec::new();
+        if !etag.is_empty() {
+            headers.push(format!("If-Match: {}", etag));
+        }
+        let response = self.request("DELETE", &self.url_for(href), &headers, None)?;
+
+        match response.status {
+            200..=299 | 404 => Ok(()),
+            status => Err(RstaskError::Other(format!(
+                "CalDAV server returned {} when deleting {}",
+                status, href
+            ))),
+        }
+    }
+
+    fn href_for(&self, uuid: &str) -> String {
+        format!("{}{}.ics", self.collection_url, uuid)
+    }
+}
+
+/// Pulls href, etag and VTODO data out of a WebDAV multistatus body
+fn parse_multistatus(xml: &str) -> Vec<RemoteItem> {
+    let mut items = Vec::new();
+
+    for response in xml_elements(xml, "response") {
+        let Some(href) = xml_elements(response, "href")
+            .first()
+            .map(|h| xml_unescape(h))
+        else {
+            continue;
+        };
+        let etag = xml_elements(response, "getetag")
+            .first()
+            .map(|e| xml_unescape(e))
+            .unwrap_or_default();
+        let data = xml_elements(response, "calendar-data")
+            .first()
+            .map(|d| xml_unescape(d))
+            .unwrap_or_default();
+
+        for todo in parse_vtodos(&data) {
+            items.push(RemoteItem {
+                href: href.trim().to_string(),
+                etag: etag.trim().to_string(),
+                todo,
+            });
+        }
+    }
+
+    items
+}
+
+/// Returns the contents of every element with the given local name,
+/// whatever namespace prefix the server chose
+fn xml_elements<'a>(xml: &'a str, local_name: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let tag_end = match rest.find('>') {
+            Some(i) => i,
+            None => break,
+        };
+        let tag = &rest[..tag_end];
+        if tag.starts_with('/') || tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        let local = name.rsplit(':').next().unwrap_or(name);
+        if local != local_name {
+            continue;
+        }
+
+        if tag.ends_with('/') {
+            found.push("");
+            rest = &rest[tag_end + 1..];
+            continue;
+        }
+
+        let content = &rest[tag_end + 1..];
+        let closing = format!("</{}>", name);
+        match content.find(&closing) {
+            Some(end) => {
+                found.push(&content[..end]);
+                rest = &content[end + closing.len()..];
+            }
+            None => break,
+        }
+    }
+
+    found
+}
+
+fn xml_unescape(s: &str) -> String {
+    let s = s.trim();
+    if let Some(cdata) = s
+        .strip_prefix("<![CDATA[")
+        .and_then(|s| s.strip_suffix("]]>"))
+    {
+        return cdata.to_string();
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';') else {
+            break;
+        };
+        let decoded = match &rest[1..semi] {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            entity => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    struct FakeRemote {
+        items: RefCell<HashMap<String, (String, String)>>,
+        next_etag: RefCell<u32>,
+    }
+
+    impl FakeRemote {
+        fn bump_etag(&self) -> String {
+            *self.next_etag.borrow_mut() += 1;
+            format!("\"{}\"", self.next_etag.borrow())
+        }
+
+        fn insert(&self, href: &str, ics: &str) {
+            let etag = self.bump_etag();
+            self.items
+                .borrow_mut()
+                .insert(href.to_string(), (etag, ics.to_string()));
+        }
+
+        fn todo(&self, href: &str) -> Option<RemoteTodo> {
+            self.items
+                .borrow()
+                .get(href)
+                .and_then(|(_, ics)| parse_vtodos(ics).into_iter().next())
+        }
+    }
+
+    impl CalDavRemote for FakeRemote {
+        fn list(&self) -> Result<Vec<RemoteItem>> {
+            let mut items = Vec::new();
+            for (href, (etag, ics)) in self.items.borrow().iter() {
+                for todo in parse_vtodos(ics) {
+                    items.push(RemoteItem {
+                        href: href.clone(),
+                        etag: etag.clone(),
+                        todo,
+                    });
+                }
+            }
+            Ok(items)
+        }
+
+        fn put(&self, href: &str, ics: &str, _etag: Option<&str>) -> Result<Option<String>> {
+            self.insert(href, ics);
+            Ok(self.items.borrow().get(href).map(|(e, _)| e.clone()))
+        }
+
+        fn delete(&self, href: &str, _etag: &str) -> Result<()> {
+            self.items.borrow_mut().remove(href);
+            Ok(())
+        }
+
+        fn href_for(&self, uuid: &str) -> String {
+            format!("/tasks/{}.ics", uuid)
+        }
+    }
+
+    fn setup() -> (tempfile::TempDir, TaskSet, CalDavState) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ts = TaskSet::new(dir.path().to_path_buf(), dir.path().join("ids.bin"));
+        let state = CalDavState::load(dir.path());
+        (dir, ts, state)
+    }
+
+    #[test]
+    fn test_sync_uploads_and_imports() {
+        let (_dir, mut ts, mut state) = setup();
+        let remote = FakeRemote::default();
+
+        let local = ts
+            .must_load_task(Task::new("Local task".to_string()))
+            .unwrap();
+        remote.insert(
+            "/tasks/phone.ics",
+            "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\n\
+             UID:6F9619FF-8B86-4D11-B42D-00C04FC964FF\r\n\
+             SUMMARY:From phone\r\nSTATUS:NEEDS-ACTION\r\nPRIORITY:1\r\n\
+             END:VTODO\r\nEND:VCALENDAR\r\n",
+        );
+        remote.insert(
+            "/tasks/other.ics",
+            "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nUID:not-a-uuid\r\nEND:VTODO\r\nEND:VCALENDAR\r\n",
+        );
+
+        let report = sync_tasks(&mut ts, &mut state, &remote).unwrap();
+        assert_eq!(report.created_remote, 1);
+        assert_eq!(report.created_local, 1);
+        assert_eq!(report.skipped, 1);
+
+        let imported = ts
+            .get_by_uuid("6f9619ff-8b86-4d11-b42d-00c04fc964ff")
+            .unwrap();
+        assert_eq!(imported.summary, "From phone");
+        assert_eq!(imported.priority, PRIORITY_CRITICAL);
+
+        let uploaded = remote.todo(&format!("/tasks/{}.ics", local.uuid)).unwrap();
+        assert_eq!(uploaded.summary, "Local task");
+
+        // A second sync with no changes does nothing
+        let report = sync_tasks(&mut ts, &mut state, &remote).unwrap();
+        assert_eq!(
+            report,
+            SyncReport {
+                skipped: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_task_hash_is_fixed() {
+        let task = Task {
+            summary: "Pay rent".to_string(),
+            tags: vec!["home".to_string()],
+            ..Default::default()
+        };
+        // Saved between syncs, so it must not change with the build
+        assert_eq!(task_hash(&task), 16674671810792028006);
+    }
+
+    #[test]
+    fn test_sync_reads_hashes_saved_before_hash_format() {
+        let (_dir, mut ts, mut state) = setup();
+        let remote = FakeRemote::default();
+
+        let task = ts.must_load_task(Task::new("A".to_string())).unwrap();
+        sync_tasks(&mut ts, &mut state, &remote).unwrap();
+        assert_eq!(state.hash_format, HASH_FORMAT);
+
+        // As an older version saved it
+        state.hash_format = 0;
+        for entry in state.entries.values_mut() {
+            entry.hash = legacy_task_hash(&task);
+        }
+
+        // Edited on the server only, so it's pulled rather than overwritten
+        let href = remote.href_for(&task.uuid);
+        let mut edited = task.clone();
+        edited.summary = "A from phone".to_string();
+        remote.insert(&href, &task_to_vtodo_calendar(&edited));
+
+        let report = sync_tasks(&mut ts, &mut state, &remote).unwrap();
+        assert_eq!(report.pulled, 1);
+        assert_eq!(report.pushed, 0);
+        assert_eq!(ts.get_by_uuid(&task.uuid).unwrap().summary, "A from phone");
+        assert_eq!(state.hash_format, HASH_FORMAT);
+    }
+
+    #[test]
+    fn test_sync_pulls_remote_changes_and_pushes_local_ones() {
+        let (_dir, mut ts, mut state) = setup();
+        let remote = FakeRemote::default();
+
+        let a = ts.must_load_task(Task::new("A".to_string())).unwrap();
+        let b = ts.must_load_task(Task::new("B".to_string())).unwrap();
+        sync_tasks(&mut ts, &mut state, &remote).unwrap();
+
+        // Completed on the phone
+        let href_a = format!("/tasks/{}.ics", a.uuid);
+        let mut done = a.clone();
+        done.status = STATUS_RESOLVED.to_string();
+        done.resolved = Some(chrono::Utc::now());
+        remote.insert(&href_a, &task_to_vtodo_calendar(&done));
+
+        // Edited locally
+        let mut edited = ts.get_by_uuid(&b.uuid).unwrap().clone();
+        edited.summary = "B edited".to_string();
+        ts.update_task(edited).unwrap();
+
+        let report = sync_tasks(&mut ts, &mut state, &remote).unwrap();
+        assert_eq!(report.pulled, 1);
+        assert_eq!(report.pushed, 1);
+        assert_eq!(ts.get_by_uuid(&a.uuid).unwrap().status, STATUS_RESOLVED);
+        assert_eq!(
+            remote
+                .todo(&format!("/tasks/{}.ics", b.uuid))
+                .unwrap()
+                .summary,
+            "B edited"
+        );
+    }
+
+    #[test]
+    fn test_sync_propagates_deletions() {
+        let (_dir, mut ts, mut state) = setup();
+        let remote = FakeRemote::default();
+
+        let a = ts.must_load_task(Task::new("A".to_string())).unwrap();
+        let b = ts.must_load_task(Task::new("B".to_string())).unwrap();
+        ts.save_pending_changes().unwrap();
+        sync_tasks(&mut ts, &mut state, &remote).unwrap();
+
+        ts.delete_task(&a.uuid).unwrap();
+        remote
+            .delete(&format!("/tasks/{}.ics", b.uuid), "")
+            .unwrap();
+
+        let report = sync_tasks(&mut ts, &mut state, &remote).unwrap();
+        assert_eq!(report.deleted_remote, 1);
+        assert_eq!(report.resolved_local, 1);
+        assert!(remote.items.borrow().is_empty());
+        assert_eq!(ts.get_by_uuid(&b.uuid).unwrap().status, STATUS_RESOLVED);
+    }
+
+    #[test]
+    fn test_parse_multistatus() {
+        let xml = r#"<?xml version="1.0"?>
+<D:multistatus xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <D:response>
+    <D:href>/cal/tasks/abc.ics</D:href>
+    <D:propstat>
+      <D:prop>
+        <D:getetag>&quot;42&quot;</D:getetag>
+        <C:calendar-data>BEGIN:VCALENDAR
+BEGIN:VTODO
+UID:abc
+SUMMARY:Fish &amp; chips
+END:VTODO
+END:VCALENDAR
+</C:calendar-data>
+      </D:prop>
+    </D:propstat>
+  </D:response>
+</D:multistatus>"#;
+
+        let items = parse_multistatus(xml);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].href, "/cal/tasks/abc.ics");
+        assert_eq!(items[0].etag, "\"42\"");
+        assert_eq!(items[0].todo.summary, "Fish & chips");
+    }
+
+    #[test]
+    fn test_url_for() {
+        let remote = CurlRemote::new("https://dav.example.com/cal/tasks", None, None);
+        assert_eq!(
+            remote.url_for("/cal/tasks/a.ics"),
+            "https://dav.example.com/cal/tasks/a.ics"
+        );
+        assert_eq!(
+            remote.url_for("a.ics"),
+            "https://dav.example.com/cal/tasks/a.ics"
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::date_util::{format_age, format_minutes, format_relative};
+use crate::preferences::DateStyle;
+use crate::task::Task;
+use crate::{Result, RstaskError};
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// A column that can be shown in task tables
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    Id,
+    Priority,
+    Tags,
+    Due,
+    Project,
+    Summary,
+    Status,
+    Created,
+    Age,
+    Resolved,
+    Urgency,
+    Uuid,
+    Delegated,
+    Estimate,
+    Blocked,
+}
+
+/// Columns shown by next/show-open when no preference is set
+pub const DEFAULT_COLUMNS: &[Column] = &[
+    Column::Id,
+    Column::Priority,
+    Column::Tags,
+    Column::Due,
+    Column::Project,
+    Column::Summary,
+];
+
+/// Columns shown by show-resolved when no preference is set
+pub const DEFAULT_RESOLVED_COLUMNS: &[Column] = &[
+    Column::Resolved,
+    Column::Priority,
+    Column::Tags,
+    Column::Due,
+    Column::Project,
+    Column::Summary,
+];
+
+pub const ALL_COLUMNS: &[Column] = &[
+    Column::Id,
+    Column::Priority,
+    Column::Tags,
+    Column::Due,
+    Column::Project,
+    Column::Summary,
+    Column::Status,
+    Column::Created,
+    Column::Age,
+    Column::Resolved,
+    Column::Urgency,
+    Column::Uuid,
+    Column::Delegated,
+    Column::Estimate,
+    Column::Blocked,
+];
+
+impl Column {
+    /// Name used in preferences and on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::Priority => "priority",
+            Column::Tags => "tags",
+            Column::Due => "due",
+            Column::Project => "project",
+            Column::Summary => "summary",
+            Column::Status => "status",
+            Column::Created => "created",
+            Column::Age => "age",
+            Column::Resolved => "resolved",
+            Column::Urgency => "urgency",
+            Column::Uuid => "uuid",
+            Column::Delegated => "delegated",
+            Column::Estimate => "estimate",
+            Column::Blocked => "blocked",
+        }
+    }
+
+    /// Table header label
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::Id => "ID",
+            Column::Priority => "Priority",
+            Column::Tags => "Tags",
+            Column::Due => "Due",
+            Column::Project => "Project",
+            Column::Summary => "Summary",
+            Column::Status => "Status",
+            Column::Created => "Created",
+            Column::Age => "Age",
+            Column::Resolved => "Resolved",
+            Column::Urgency => "Urgency",
+            Column::Uuid => "UUID",
+            Column::Delegated => "Delegated",
+            Column::Estimate => "Estimate",
+            Column::Blocked => "Blocked by",
+        }
+    }
+
+    /// Renders the cell for a task
+    pub fn cell(&self, task: &Task, date_style: DateStyle) -> String {
+        match self {
+            Column::Id => format!("{:<2}", task.id),
+            Column::Priority => task.priority.clone(),
+            Column::Tags => task.tags.join(" "),
+            Column::Due => task.due_date_str(date_style),
+            Column::Project => task.project.clone(),
+            Column::Summary => task.long_summary(),
+            Column::Status => task.status.clone(),
+            Column::Created => match date_style {
+                DateStyle::Absolute => task.created.format("%a %-d %b %Y").to_string(),
+                DateStyle::Relative => format_relative(task.created),
+            },
+            Column::Age => format_age(task.created),
+            Column::Resolved => match (task.resolved, date_style) {
+                (None, _) => String::new(),
+                (Some(r), DateStyle::Absolute) => r.format("%a %-d").to_string(),
+                (Some(r), DateStyle::Relative) => format_relative(r),
+            },
+            Column::Urgency => format!("{:.1}", task.urgency()),
+            Column::Uuid => task.uuid.clone(),
+            Column::Delegated if task.delegated_to.is_empty() => String::new(),
+            Column::Delegated => format!("@{}", task.delegated_to),
+            Column::Estimate => task.estimate.map(format_minutes).unwrap_or_default(),
+            Column::Blocked => task
+                .blocked_by
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+/// Adds `column` just before the summary, or at the end without one, unless
+/// it's already there
+pub fn insert_before_summary(columns: &mut Vec<Column>, column: Column) {
+    if !columns.contains(&column) {
+        let at = columns
+            .iter()
+            .position(|c| *c == Column::Summary)
+            .unwrap_or(columns.len());
+        columns.insert(at, column);
+    }
+}
+
+impl FromStr for Column {
+    type Err = RstaskError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let lower = s.trim().to_lowercase();
+        ALL_COLUMNS
+            .iter()
+            .find(|c| c.name() == lower)
+            .copied()
+            .ok_or_else(|| {
+                let valid: Vec<&str> = ALL_COLUMNS.iter().map(|c| c.name()).collect();
+                RstaskError::Parse(format!(
+                    "unknown column: {}\nValid columns are: {}",
+                    s,
+                    valid.join(", ")
+                ))
+            })
+    }
+}
+
+/// Parses a comma separated column list such as "id,summary,due"
+pub fn parse_columns(s: &str) -> Result<Vec<Column>> {
+    let columns = s
+        .split(',')
+        .filter(|part| !part.trim().is_empty())
+        .map(Column::from_str)
+        .collect::<Result<Vec<_>>>()?;
+
+    if columns.is_empty() {
+        return Err(RstaskError::Parse("no columns specified".to_string()));
+    }
+
+    Ok(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_columns() {
+        let columns = parse_columns("id,Summary, due").unwrap();
+        assert_eq!(columns, vec![Column::Id, Column::Summary, Column::Due]);
+    }
+
+    #[test]
+    fn test_parse_columns_unknown() {
+        assert!(parse_columns("id,bogus").is_err());
+        assert!(parse_columns("").is_err());
+    }
+
+    #[test]
+    fn test_columns_from_styx() {
+        #[derive(Deserialize)]
+        struct Prefs {
+            columns: Vec<Column>,
+        }
+
+        let prefs: Prefs = serde_styx::from_str("columns (id urgency summary)").unwrap();
+        assert_eq!(
+            prefs.columns,
+            vec![Column::Id, Column::Urgency, Column::Summary]
+        );
+    }
+}
@@ -1,18 +1,94 @@
 use crate::{
+    column::{Column, insert_before_summary},
     config::Config,
     constants::*,
+    date_util::parse_str_to_date,
+    display::{print_brief, write_json_seq},
     error::{Result, RstaskError},
+    events::TaskEvent,
     git::git_commit,
+    ical::{tasks_to_ical, write_repo_export},
     local_state::LocalState,
+    preferences::{JsonStyle, ListOutput, SyncTrigger},
     query::Query,
+    schedule::Schedule,
+    table::{RowStyle, Table},
     task::Task,
-    taskset::TaskSet,
-    util::stdout_is_tty,
+    taskset::{LoadOptions, TaskSet},
+    util::{complete_word, deduplicate_strings, get_term_size, prompt_line, stdout_is_tty},
 };
-use chrono::Utc;
+use chrono::{DateTime, Local, Utc};
+use std::collections::HashMap;
 use std::io::{self, Write};
 use termimad::MadSkin;
 
+/// Commits the working tree, first regenerating the in-repo calendar export
+/// when one is configured so it is committed alongside the tasks
+pub fn commit_changes(conf: &Config, message: &str, quiet: bool) -> Result<String> {
+    if let Some(export_path) = &conf.preferences.ical_export_path {
+        write_repo_export(
+            &conf.repo,
+            &conf.ids_file,
+            export_path,
+            conf.preferences.ical_component,
+            conf.preferences.ical_export_notes && conf.preferences.notes_key_file.is_none(),
+        )?;
+    }
+    git_commit(&conf.repo, message, quiet)
+}
+
+/// Fills the `{{placeholders}}` in a task added from a template, with the
+/// values given by `add --var` and by asking for the rest
+fn fill_placeholders(conf: &Config, task: &mut Task) -> Result<()> {
+    use crate::template::{fill, placeholders};
+
+    let mut names = placeholders(&task.summary);
+    for name in placeholders(&task.notes) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    let mut values: HashMap<String, String> = conf.template_vars.iter().cloned().collect();
+    for (name, _) in &conf.template_vars {
+        if !names.contains(name) {
+            eprintln!("Warning: the template has no {{{{{}}}}}", name);
+        }
+    }
+    for name in names {
+        if values.contains_key(&name) {
+            continue;
+        }
+        let value = prompt_line(&format!("{}: ", name))?.ok_or_else(|| {
+            RstaskError::Parse(format!(
+                "no value for {{{{{}}}}}; give one with --var {}=...",
+                name, name
+            ))
+        })?;
+        values.insert(name, value);
+    }
+
+    task.summary = fill(&task.summary, &values);
+    task.notes = fill(&task.notes, &values);
+    Ok(())
+}
+
+/// A new pending task copied from a template
+fn task_from_template(template: &Task) -> Task {
+    Task {
+        write_pending: true,
+        status: STATUS_PENDING.to_string(),
+        summary: template.summary.clone(),
+        tags: template.tags.clone(),
+        project: template.project.clone(),
+        priority: template.priority.clone(),
+        due: template.due,
+        estimate: template.estimate,
+        notes: template.notes.clone(),
+        ..Default::default()
+    }
+}
+
 /// Add a new task to the task database
 pub fn cmd_add(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
     if query.text.is_empty() && query.template == 0 {
@@ -27,35 +103,27 @@ pub fn cmd_add(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
         ));
     }
 
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    check_dstask_compat(conf, &query.merge(ctx))?;
+
+    let mut ts = TaskSet::load_for_update(conf, false)?;
 
     if query.template > 0 {
         // Create task from template
         let template = ts.must_get_by_id(query.template).clone();
         let merged_query = query.merge(ctx);
+        check_new_taxonomy(conf, &ts, &merged_query)?;
 
-        let task_summary = if !query.text.is_empty() {
-            query.text.clone()
-        } else {
-            template.summary.clone()
-        };
-
-        let mut task = Task {
-            write_pending: true,
-            status: STATUS_PENDING.to_string(),
-            summary: task_summary,
-            tags: template.tags.clone(),
-            project: template.project.clone(),
-            priority: template.priority.clone(),
-            due: template.due,
-            notes: template.notes.clone(),
-            ..Default::default()
-        };
+        let mut task = task_from_template(&template);
+        if !query.text.is_empty() {
+            task.summary = query.text.clone();
+        }
 
+        fill_placeholders(conf, &mut task)?;
         task.modify(&merged_query);
         task = ts.must_load_task(task)?;
         ts.save_pending_changes()?;
-        git_commit(&conf.repo, &format!("Added {}", task.summary), false)?;
+        commit_changes(conf, &format!("Added {}", task.summary), false)?;
+        conf.events.emit(&TaskEvent::TaskAdded(task));
 
         if template.status != STATUS_TEMPLATE {
             println!(
@@ -65,8 +133,14 @@ pub fn cmd_add(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
         }
     } else if !query.text.is_empty() {
         // Create new task from scratch
-        ctx.print_context_description();
-        let merged_query = query.merge(ctx);
+        ctx.print_context_description(conf.preferences.context_verbosity);
+        let mut merged_query = query.merge(ctx);
+        check_new_taxonomy(conf, &ts, &merged_query)?;
+        if merged_query.project.is_empty()
+            && let Some(project) = &conf.preferences.default_project
+        {
+            merged_query.project = project.clone();
+        }
 
         let mut task = Task {
             write_pending: true,
@@ -76,6 +150,7 @@ pub fn cmd_add(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
             project: merged_query.project.clone(),
             priority: merged_query.priority.clone(),
             due: merged_query.due,
+            estimate: merged_query.estimate.filter(|m| *m > 0),
             notes: merged_query.note.clone(),
             ..Default::default()
         };
@@ -86,14 +161,171 @@ pub fn cmd_add(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
         // Print feedback message
         println!("Added {}: {}", task.id, task.summary);
 
-        git_commit(
-            &conf.repo,
-            &format!("Added {}: {}", task.id, task.summary),
-            false,
-        )?;
+        commit_changes(conf, &format!("Added {}: {}", task.id, task.summary), false)?;
+        conf.events.emit(&TaskEvent::TaskAdded(task));
+    }
+
+    auto_sync_if_enabled(conf, SyncTrigger::Modification)?;
+    Ok(())
+}
+
+/// Add a new task by prompting for each field in turn
+///
+/// Values already given on the command line are used as defaults. Projects
+/// and tags are completed against the ones already in use when the typed
+/// prefix is unambiguous.
+pub fn cmd_add_interactive(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
+    let ts = TaskSet::load(conf, false)?;
+    let projects: Vec<String> = ts
+        .get_projects()
+        .into_iter()
+        .map(|p| p.name)
+        .filter(|name| !name.is_empty())
+        .collect();
+    let tags = ts.get_tags();
+
+    let mut q = query.clone();
+
+    let summary = loop {
+        let default = with_default_hint(&query.text);
+        match prompt_line(&format!("Summary{}: ", default))? {
+            None => return Err(RstaskError::Other("Aborted.".to_string())),
+            Some(s) if s.is_empty() && !query.text.is_empty() => break query.text.clone(),
+            Some(s) if s.is_empty() => eprintln!("A summary is required."),
+            Some(s) => break s,
+        }
+    };
+    q.text = summary;
+
+    if !projects.is_empty() {
+        eprintln!("Known projects: {}", projects.join(", "));
+    }
+    if let Some(project) = prompt_line(&format!("Project{}: ", with_default_hint(&query.project)))?
+        && !project.is_empty()
+    {
+        q.project = complete_word(&project.to_lowercase(), &projects);
+    }
+
+    if !tags.is_empty() {
+        eprintln!("Known tags: {}", tags.join(", "));
+    }
+    if let Some(line) = prompt_line(&format!(
+        "Tags (space separated){}: ",
+        with_default_hint(&query.tags.join(" "))
+    ))? && !line.is_empty()
+    {
+        q.tags = line
+            .split_whitespace()
+            .map(|t| complete_word(&t.trim_start_matches('+').to_lowercase(), &tags))
+            .collect();
+        deduplicate_strings(&mut q.tags);
+    }
+
+    loop {
+        let default = if query.priority.is_empty() {
+            PRIORITY_NORMAL
+        } else {
+            query.priority.as_str()
+        };
+        match prompt_line(&format!("Priority (P0-P3) [{}]: ", default))? {
+            Some(p) if !p.is_empty() => {
+                let p = p.to_uppercase();
+                if is_valid_priority(&p) {
+                    q.priority = p;
+                    break;
+                }
+                eprintln!("Priority must be one of P0, P1, P2 or P3.");
+            }
+            _ => break,
+        }
+    }
+
+    loop {
+        match prompt_line("Due (e.g. tomorrow, fri, next-mon, YYYY-MM-DD): ")? {
+            Some(d) if !d.is_empty() => match parse_str_to_date(&d) {
+                Ok(due) => {
+                    q.due = Some(due.with_timezone(&Utc));
+                    q.date_filter.clear();
+                    break;
+                }
+                Err(e) => eprintln!("{}", e),
+            },
+            _ => break,
+        }
+    }
+
+    cmd_add(conf, ctx, &q)
+}
+
+fn with_default_hint(default: &str) -> String {
+    if default.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", default)
+    }
+}
+
+/// Refuses to set fields that dstask's task files have no place for, which
+/// would be dropped on save when `dstask_compat` is on
+fn check_dstask_compat(conf: &Config, query: &Query) -> Result<()> {
+    if !conf.preferences.dstask_compat {
+        return Ok(());
+    }
+    let field = if query.estimate.is_some() {
+        "estimate:"
+    } else if query.schedule.is_some() {
+        "schedule:"
+    } else {
+        return Ok(());
+    };
+    Err(RstaskError::Parse(format!(
+        "{} can't be saved while dstask_compat is on, as dstask's task files have no place for it",
+        field
+    )))
+}
+
+/// Notes projects and tags that no task uses yet, so a typo doesn't silently
+/// fork the taxonomy. Errors instead when `--no-new` was given.
+///
+/// `open` is the set the command already loaded without resolved tasks.
+/// Resolved tasks are only read when it doesn't know something, and then
+/// only their frontmatter.
+fn check_new_taxonomy(conf: &Config, open: &TaskSet, query: &Query) -> Result<()> {
+    if query.project.is_empty() && query.tags.is_empty() {
+        return Ok(());
+    }
+
+    let (mut project, mut tags) = open.unknown_taxonomy(query);
+    if project.is_some() || !tags.is_empty() {
+        let options = LoadOptions::statuses(&[STATUS_RESOLVED])
+            .skip_notes(true)
+            .settings(conf.repo_settings());
+        let resolved = TaskSet::load_with(&conf.repo, &conf.ids_file, &options)?;
+        let unknown = Query {
+            project: project.unwrap_or_default(),
+            tags,
+            ..Default::default()
+        };
+        (project, tags) = resolved.unknown_taxonomy(&unknown);
+    }
+
+    let mut new_items: Vec<String> = project.iter().map(|p| format!("project '{}'", p)).collect();
+    new_items.extend(tags.iter().map(|t| format!("tag '{}'", t)));
+
+    if new_items.is_empty() {
+        return Ok(());
     }
 
-    auto_sync_if_enabled(conf)?;
+    if conf.refuse_new_taxonomy {
+        return Err(RstaskError::Parse(format!(
+            "refusing to create new {} (--no-new)",
+            new_items.join(", ")
+        )));
+    }
+
+    for item in &new_items {
+        eprintln!("note: creating new {}", item);
+    }
     Ok(())
 }
 
@@ -124,7 +356,8 @@ pub fn cmd_done(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
         ));
     }
 
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    let mut ts = TaskSet::load_for_update(conf, false)?;
+    let mut changed = Vec::new();
 
     // iterate over IDs instead of filtering; it's clearer and enables us to
     // test each ID exists, and ignore context/operators
@@ -143,6 +376,7 @@ pub fn cmd_done(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
         task.resolved = Some(Utc::now());
         task.write_pending = true;
 
+        changed.push(task.clone());
         ts.must_update_task(task)?;
     }
 
@@ -153,19 +387,22 @@ pub fn cmd_done(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
     } else {
         "tasks"
     };
-    git_commit(
-        &conf.repo,
+    commit_changes(
+        conf,
         &format!("Resolved {} {}", query.ids.len(), task_word),
         false,
     )?;
+    for task in changed {
+        conf.events.emit(&TaskEvent::TaskResolved(task));
+    }
 
-    auto_sync_if_enabled(conf)?;
+    auto_sync_if_enabled(conf, SyncTrigger::ResolveOrRemove)?;
     Ok(())
 }
 
 /// Edit a task in $EDITOR
 pub fn cmd_edit(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
-    use crate::util::edit_string;
+    use crate::util::{edit_string, make_temp_filename};
 
     if query.ids.len() != 1 {
         return Err(RstaskError::Parse(
@@ -173,32 +410,113 @@ pub fn cmd_edit(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
         ));
     }
 
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
-    let task = ts.must_get_by_id(query.ids[0]);
+    let ts = TaskSet::load(conf, false)?;
+    let task = ts.must_get_by_id(query.ids[0]).clone();
 
     // Serialize task to markdown with frontmatter for editing
-    let markdown_str = crate::frontmatter::task_to_markdown(task)?;
-    let edited = edit_string(&markdown_str)?;
+    let markdown_str = crate::frontmatter::task_to_markdown(&task)?;
+    let original_hash = task_content_hash(&task.status, &markdown_str);
+    let editor = conf.preferences.editor.as_deref();
+    let tmp_filename = make_temp_filename(task.id, &task.summary, "md");
+    let mut edited = edit_string(editor, &markdown_str, &tmp_filename)?;
+
+    // The task may have changed on disk (e.g. by a sync) while the editor was
+    // open, so reload it and check before overwriting anything
+    let mut ts = TaskSet::load_for_update(conf, true)?;
+    let current = ts
+        .get_by_uuid(&task.uuid)
+        .cloned()
+        .ok_or_else(|| RstaskError::Other("task was removed while editing".to_string()))?;
+    let current_str = crate::frontmatter::task_to_markdown(&current)?;
+
+    if task_content_hash(&current.status, &current_str) != original_hash {
+        edited = resolve_concurrent_edit(editor, &markdown_str, &edited, &current_str)?;
+    }
 
-    // Parse edited markdown
-    let edited_task =
-        crate::frontmatter::task_from_markdown(&edited, &task.uuid, &task.status, task.id)?;
+    // Parse edited markdown, keeping the status and ID the task has now
+    let edited_task = crate::frontmatter::task_from_markdown(
+        &edited,
+        &current.uuid,
+        &current.status,
+        current.id,
+    )?;
 
     // Validate UUID hasn't changed (should be guaranteed by task_from_markdown)
-    if edited_task.uuid != task.uuid {
+    if edited_task.uuid != current.uuid {
         return Err(RstaskError::Parse("task ID must not be edited".to_string()));
     }
 
     let mut edited_task = edited_task;
     edited_task.write_pending = true;
-    ts.must_update_task(edited_task)?;
+    ts.must_update_task(edited_task.clone())?;
     ts.save_pending_changes()?;
-    git_commit(&conf.repo, "Edited task", false)?;
+    commit_changes(conf, "Edited task", false)?;
+    conf.events.emit_change(&current, &edited_task);
 
-    auto_sync_if_enabled(conf)?;
+    auto_sync_if_enabled(conf, SyncTrigger::Modification)?;
     Ok(())
 }
 
+/// Hashes the parts of a task that an edit could clobber
+fn task_content_hash(status: &str, markdown: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    status.hash(&mut hasher);
+    markdown.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Decides what to do when a task changed on disk while it was being edited.
+/// Returns the markdown that should be saved.
+fn resolve_concurrent_edit(
+    editor: Option<&str>,
+    original: &str,
+    edited: &str,
+    current: &str,
+) -> Result<String> {
+    use crate::git::git_merge_file;
+    use crate::util::edit_string;
+
+    if !stdout_is_tty() {
+        return Err(RstaskError::Other(
+            "task was modified while it was being edited; aborting".to_string(),
+        ));
+    }
+
+    eprint!(
+        "Task was modified while it was being edited.\n\
+        [m]erge changes, [o]verwrite with your version, or [a]bort? "
+    );
+    io::stderr().flush()?;
+
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+
+    match response.trim().to_lowercase().as_str() {
+        "m" | "merge" => {
+            let (merged, conflicts) = git_merge_file(original, edited, current)?;
+            if !conflicts {
+                return Ok(merged);
+            }
+
+            // Let the user resolve conflict markers by hand
+            let resolved = edit_string(editor, &merged, "rstask-merge.md")?;
+            if resolved
+                .lines()
+                .any(|l| l.starts_with("<<<<<<< ") || l == "=======" || l.starts_with(">>>>>>> "))
+            {
+                return Err(RstaskError::Other(
+                    "unresolved merge conflicts; aborting".to_string(),
+                ));
+            }
+            Ok(resolved)
+        }
+        "o" | "overwrite" => Ok(edited.to_string()),
+        _ => Err(RstaskError::Other("Aborted.".to_string())),
+    }
+}
+
 /// Display help text
 pub fn cmd_help(args: &[String]) {
     let cmd = if args.len() >= 3 {
@@ -216,9 +534,9 @@ pub fn cmd_log(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
         return Err(RstaskError::Parse("task description required".to_string()));
     }
 
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    let mut ts = TaskSet::load_for_update(conf, false)?;
 
-    ctx.print_context_description();
+    ctx.print_context_description(conf.preferences.context_verbosity);
     let merged_query = query.merge(ctx);
 
     let task = Task {
@@ -235,9 +553,10 @@ pub fn cmd_log(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
 
     let task = ts.must_load_task(task)?;
     ts.save_pending_changes()?;
-    git_commit(&conf.repo, &format!("Added {}", task.summary), false)?;
+    commit_changes(conf, &format!("Added {}", task.summary), false)?;
+    conf.events.emit(&TaskEvent::TaskAdded(task));
 
-    auto_sync_if_enabled(conf)?;
+    auto_sync_if_enabled(conf, SyncTrigger::Modification)?;
     Ok(())
 }
 
@@ -250,7 +569,12 @@ pub fn cmd_modify(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
         return Err(RstaskError::Parse("no operations specified".to_string()));
     }
 
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    check_dstask_compat(conf, query)?;
+
+    let mut ts = TaskSet::load_for_update(conf, false)?;
+    check_new_taxonomy(conf, &ts, query)?;
+    // Changes waiting for the single bulk commit before their events are emitted
+    let mut changed = Vec::new();
 
     if query.ids.is_empty() {
         // Apply to all tasks in context
@@ -267,21 +591,25 @@ pub fn cmd_modify(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
         let tasks_to_modify: Vec<_> = ts.tasks().iter().map(|t| (*t).clone()).collect();
         let task_count = tasks_to_modify.len();
 
-        for mut task in tasks_to_modify {
+        for before in tasks_to_modify {
+            let mut task = before.clone();
             task.modify(query);
             task.write_pending = true;
             ts.must_update_task(task.clone())?;
             ts.save_pending_changes()?;
 
             if conf.preferences.bulk_commit_strategy == BulkCommitStrategy::PerTask {
-                git_commit(&conf.repo, &format!("Modified {}", task.summary), false)?;
+                commit_changes(conf, &format!("Modified {}", task.summary), false)?;
+                conf.events.emit_change(&before, &task);
+            } else {
+                changed.push((before, task));
             }
         }
 
         if conf.preferences.bulk_commit_strategy == BulkCommitStrategy::Single && task_count > 0 {
             let task_word = if task_count == 1 { "task" } else { "tasks" };
-            git_commit(
-                &conf.repo,
+            commit_changes(
+                conf,
                 &format!("Modified {} {}", task_count, task_word),
                 false,
             )?;
@@ -291,35 +619,42 @@ pub fn cmd_modify(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
         let task_count = query.ids.len();
 
         for id in &query.ids {
-            let task = ts.must_get_by_id(*id);
-            let mut task = task.clone();
+            let before = ts.must_get_by_id(*id).clone();
+            let mut task = before.clone();
             task.modify(query);
             task.write_pending = true;
             ts.must_update_task(task.clone())?;
             ts.save_pending_changes()?;
 
             if conf.preferences.bulk_commit_strategy == BulkCommitStrategy::PerTask {
-                git_commit(&conf.repo, &format!("Modified {}", task.summary), false)?;
+                commit_changes(conf, &format!("Modified {}", task.summary), false)?;
+                conf.events.emit_change(&before, &task);
+            } else {
+                changed.push((before, task));
             }
         }
 
         if conf.preferences.bulk_commit_strategy == BulkCommitStrategy::Single && task_count > 0 {
             let task_word = if task_count == 1 { "task" } else { "tasks" };
-            git_commit(
-                &conf.repo,
+            commit_changes(
+                conf,
                 &format!("Modified {} {}", task_count, task_word),
                 false,
             )?;
         }
     }
 
-    auto_sync_if_enabled(conf)?;
+    for (before, after) in changed {
+        conf.events.emit_change(&before, &after);
+    }
+
+    auto_sync_if_enabled(conf, SyncTrigger::Modification)?;
     Ok(())
 }
 
 /// Show next/pending tasks (default view)
 pub fn cmd_next(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    let mut ts = TaskSet::load(conf, false)?;
 
     let filter_query = if !query.ids.is_empty() {
         // addressing task by ID, ignores context
@@ -331,18 +666,80 @@ pub fn cmd_next(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
         query.clone()
     } else {
         // apply context
-        query.merge(ctx)
+        let mut merged = query.merge(ctx);
+        if conf.preferences.next_hides_delegated && merged.delegated.is_none() {
+            merged.delegated = Some(false);
+        }
+        merged
     };
 
     ts.filter(&filter_query);
-    ts.display_by_next(ctx, true)?;
+    ts.display_by_next(ctx, true, &conf.preferences)?;
+
+    if stdout_is_tty() && conf.preferences.list_output == ListOutput::Full {
+        warn_over_capacity(conf, &ts)?;
+        if conf.preferences.stale_banner {
+            print_stale_banner(conf, &ts);
+        }
+    }
 
     Ok(())
 }
 
-/// Edit task notes in $EDITOR
-pub fn cmd_note(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
-    use crate::util::edit_string;
+/// Mentions how many open tasks haven't changed for `stale_days`
+fn print_stale_banner(conf: &Config, ts: &TaskSet) {
+    let open = ts
+        .all_tasks()
+        .iter()
+        .filter(|t| !HIDDEN_STATUSES.contains(&t.status.as_str()));
+    let days = conf.preferences.stale_days;
+    let stale = stale_tasks(conf, open, days).len();
+
+    if stale > 0 {
+        let task_word = if stale == 1 { "task" } else { "tasks" };
+        println!(
+            "\x1b[33m{} {} untouched for {}+ days. Run `rstask show-stale` to see them.\x1b[0m",
+            stale, task_word, days
+        );
+    }
+}
+
+/// Warns when the estimates of open tasks due today or earlier, other than
+/// delegated ones, add up to more than `daily_capacity`
+fn warn_over_capacity(conf: &Config, ts: &TaskSet) -> Result<()> {
+    use crate::date_util::{format_minutes, parse_duration_minutes, start_of_day};
+
+    let Some(capacity) = &conf.preferences.daily_capacity else {
+        return Ok(());
+    };
+    let capacity = parse_duration_minutes(capacity)?;
+
+    let tomorrow = start_of_day(Local::now() + chrono::Days::new(1)).with_timezone(&Utc);
+    let due_today: u32 = ts
+        .all_tasks()
+        .iter()
+        .filter(|t| !HIDDEN_STATUSES.contains(&t.status.as_str()) && t.delegated_to.is_empty())
+        .filter(|t| t.due.is_some_and(|due| due < tomorrow))
+        .filter_map(|t| t.estimate)
+        .sum();
+
+    if due_today > capacity {
+        println!(
+            "\x1b[33mTasks due today are estimated at {}, more than your daily capacity of {}.\x1b[0m",
+            format_minutes(due_today),
+            format_minutes(capacity)
+        );
+    }
+
+    Ok(())
+}
+
+/// Edit or append to task notes
+///
+/// Text after the ID is appended as a new line, and `clear` empties the notes
+/// first. Without either, the notes are opened in the editor
+pub fn cmd_note(conf: &Config, query: &Query, args: &[String], clear: bool) -> Result<()> {
+    use crate::util::{edit_string, make_temp_filename};
 
     if query.ids.len() != 1 {
         return Err(RstaskError::Parse(
@@ -350,24 +747,70 @@ pub fn cmd_note(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
         ));
     }
 
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    let mut ts = TaskSet::load_for_update(conf, false)?;
     let task = ts.must_get_by_id(query.ids[0]);
+    let text = note_text(query, args);
 
-    // Edit notes (notes is already a String)
-    let edited = edit_string(&task.notes)?;
-
+    let before = task.clone();
     let mut task = task.clone();
-    task.notes = edited;
+    let message = if clear || !text.is_empty() {
+        if clear {
+            task.notes.clear();
+        }
+        if !text.is_empty() {
+            if !task.notes.is_empty() && !task.notes.ends_with('\n') {
+                task.notes.push('\n');
+            }
+            task.notes.push_str(&text);
+        }
+        if task.notes == before.notes {
+            println!("Task {} has no notes", task.id);
+            return Ok(());
+        }
+        if text.is_empty() {
+            format!("Cleared notes of {}", task.summary)
+        } else {
+            format!("Added note to {}", task.summary)
+        }
+    } else {
+        let tmp_filename = make_temp_filename(task.id, &task.summary, "md");
+        task.notes = edit_string(
+            conf.preferences.editor.as_deref(),
+            &task.notes,
+            &tmp_filename,
+        )?;
+        "Updated task notes".to_string()
+    };
     task.write_pending = true;
 
-    ts.must_update_task(task)?;
+    ts.must_update_task(task.clone())?;
     ts.save_pending_changes()?;
-    git_commit(&conf.repo, "Updated task notes", false)?;
+    commit_changes(conf, &message, false)?;
+    conf.events.emit_change(&before, &task);
 
-    auto_sync_if_enabled(conf)?;
+    auto_sync_if_enabled(conf, SyncTrigger::Modification)?;
     Ok(())
 }
 
+/// The words of `note` after the command and task ID, as typed, so that a
+/// word like +tag stays part of the note
+fn note_text(query: &Query, args: &[String]) -> String {
+    let words: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .skip_while(|arg| {
+            *arg == IGNORE_CONTEXT_KEYWORD
+                || arg.eq_ignore_ascii_case(&query.cmd)
+                || arg.parse::<i32>().is_ok_and(|id| query.ids.contains(&id))
+        })
+        .collect();
+    let words = match words.split_first() {
+        Some((&NOTE_MODE_KEYWORD, rest)) => rest,
+        _ => &words,
+    };
+    words.join(" ")
+}
+
 /// Open/reopen tasks (move from resolved to pending)
 pub fn cmd_open(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
     if query.ids.is_empty() {
@@ -382,14 +825,19 @@ pub fn cmd_open(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
         ));
     }
 
-    let ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    let ts = TaskSet::load(conf, false)?;
 
     for id in &query.ids {
         let task = ts.must_get_by_id(*id);
 
         // Extract URLs from task summary and notes
         let text = format!("{} {}", task.summary, task.notes);
-        let urls = crate::util::extract_urls(&text);
+        let mut urls = crate::util::extract_urls(&text);
+        if let Some(base_url) = &conf.preferences.jira_url
+            && !task.jira.is_empty()
+        {
+            urls.insert(0, crate::jira::issue_url(base_url, &task.jira));
+        }
 
         if urls.is_empty() {
             return Err(RstaskError::Other(format!(
@@ -414,7 +862,7 @@ pub fn cmd_remove(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
         ));
     }
 
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    let mut ts = TaskSet::load_for_update(conf, false)?;
 
     // Print tasks that will be removed (like Go version)
     for id in &query.ids {
@@ -450,54 +898,150 @@ pub fn cmd_remove(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
     } else {
         "tasks"
     };
-    git_commit(
-        &conf.repo,
+    commit_changes(
+        conf,
         &format!("Removed {} {}", query.ids.len(), task_word),
         false,
     )?;
 
-    auto_sync_if_enabled(conf)?;
+    auto_sync_if_enabled(conf, SyncTrigger::ResolveOrRemove)?;
     Ok(())
 }
 
 /// Show active tasks
 pub fn cmd_show_active(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, true)?;
+    let options = LoadOptions::statuses(&[STATUS_ACTIVE]).settings(conf.repo_settings());
+    let mut ts = TaskSet::load_with(&conf.repo, &conf.ids_file, &options)?;
     let merged_query = query.merge(ctx);
 
     ts.filter(&merged_query);
     ts.filter_by_status(STATUS_ACTIVE);
-    ts.display_by_next(ctx, true)?;
+    ts.display_by_next(ctx, true, &conf.preferences)?;
+
+    Ok(())
+}
+
+/// Show tasks delegated to someone, optionally only those delegated to one person
+pub fn cmd_show_delegated(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
+    let mut ts = TaskSet::load(conf, false)?;
+    // The free text names the person rather than searching summaries
+    let mut merged_query = query.merge(ctx);
+    merged_query.text.clear();
+    merged_query.delegated = Some(true);
+
+    ts.filter(&merged_query);
+    if !query.text.is_empty() {
+        ts.filter_delegated_to(&query.text);
+    }
+
+    let mut prefs = conf.preferences.clone();
+    let mut columns = prefs.table_columns().to_vec();
+    insert_before_summary(&mut columns, Column::Delegated);
+    prefs.columns = columns;
+
+    ts.display_by_next(ctx, false, &prefs)?;
+
+    Ok(())
+}
+
+/// Show tasks waiting on another open task, with the IDs of those tasks
+pub fn cmd_show_blocked(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
+    let mut ts = TaskSet::load(conf, false)?;
+    ts.filter(&query.merge(ctx));
+    ts.filter_blocked();
+
+    let mut prefs = conf.preferences.clone();
+    let mut columns = prefs.table_columns().to_vec();
+    insert_before_summary(&mut columns, Column::Blocked);
+    prefs.columns = columns;
+
+    ts.display_by_next(ctx, false, &prefs)?;
 
     Ok(())
 }
 
 /// Show tasks grouped by project
 pub fn cmd_show_projects(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, true)?;
     let merged_query = query.merge(ctx);
+    // Notes are only needed to match a text search
+    let options = LoadOptions::new(true)
+        .skip_notes(merged_query.text.is_empty())
+        .settings(conf.repo_settings());
+    let mut ts = TaskSet::load_with(&conf.repo, &conf.ids_file, &options)?;
 
     ts.filter(&merged_query);
-    ts.display_projects()?;
+    ts.display_projects(&conf.preferences)?;
 
     Ok(())
 }
 
 /// Show open tasks (pending + active + paused)
 pub fn cmd_show_open(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    let mut ts = TaskSet::load(conf, false)?;
     let merged_query = query.merge(ctx);
 
     ts.filter(&merged_query);
     // Don't filter by status - open means not resolved
-    ts.display_by_next(ctx, false)?;
+    ts.display_by_next(ctx, false, &conf.preferences)?;
 
     Ok(())
 }
 
+/// Print tasks with due dates as an iCalendar document
+pub fn cmd_export_ical(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
+    let mut ts = TaskSet::load(conf, false)?;
+    let merged_query = query.merge(ctx);
+
+    ts.filter(&merged_query);
+    let tasks: Vec<&Task> = ts
+        .tasks()
+        .into_iter()
+        .filter(|t| t.status != STATUS_TEMPLATE)
+        .collect();
+
+    print!("{}", tasks_to_ical(&tasks, conf.preferences.ical_component));
+    Ok(())
+}
+
+/// Print open tasks as CSV, one row per task
+pub fn cmd_export_csv(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
+    let mut ts = TaskSet::load(conf, false)?;
+    let merged_query = query.merge(ctx);
+
+    ts.filter(&merged_query);
+    let tasks: Vec<&Task> = ts
+        .tasks()
+        .into_iter()
+        .filter(|t| t.status != STATUS_TEMPLATE)
+        .collect();
+
+    print!("{}", crate::csv::tasks_to_csv(&tasks));
+    Ok(())
+}
+
+/// Print a markdown summary of the period since `since`, a week by default
+pub fn cmd_report(conf: &Config, ctx: &Query, query: &Query, since: Option<&str>) -> Result<()> {
+    let since = crate::date_util::parse_since(since.unwrap_or("1w"))?;
+    let options = LoadOptions::new(true)
+        .resolved_since(since.with_timezone(&Utc))
+        .settings(conf.repo_settings());
+    let mut ts = TaskSet::load_with(&conf.repo, &conf.ids_file, &options)?;
+
+    ts.unhide();
+    ts.filter(&query.merge(ctx));
+    let tasks: Vec<&Task> = ts
+        .tasks()
+        .into_iter()
+        .filter(|t| t.status != STATUS_TEMPLATE && t.status != STATUS_RECURRING)
+        .collect();
+
+    print!("{}", crate::report::markdown_report(&tasks, since, ctx));
+    Ok(())
+}
+
 /// Show a single task with rendered markdown notes
 pub fn cmd_show(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
-    let ts = TaskSet::load(&conf.repo, &conf.ids_file, true)?;
+    let ts = TaskSet::load(conf, true)?;
 
     // Get the task ID from the query
     if query.ids.is_empty() {
@@ -514,49 +1058,130 @@ pub fn cmd_show(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
     // Display task metadata
     task.display();
 
+    // Open dependencies block the task; resolved ones are listed apart
+    let (blockers, done): (Vec<&String>, Vec<&String>) =
+        task.dependencies.iter().partition(|uuid| {
+            ts.get_by_uuid(uuid)
+                .is_some_and(|dep| task.blocked_by.contains(&dep.id))
+        });
+    if !blockers.is_empty() {
+        print_section("Blocked by");
+        for uuid in blockers {
+            println!("{}", related_task_line(ts.get_by_uuid(uuid).unwrap()));
+        }
+    }
+    if !done.is_empty() {
+        print_section("Depends on");
+        for uuid in done {
+            match ts.get_by_uuid(uuid) {
+                Some(dep) => println!("{}", related_task_line(dep)),
+                None => println!("  {} (not found)", uuid),
+            }
+        }
+    }
+
+    let blocked: Vec<&Task> = ts
+        .all_tasks()
+        .iter()
+        .filter(|t| t.dependencies.contains(&task.uuid))
+        .collect();
+    if !blocked.is_empty() {
+        print_section("Blocks");
+        for t in blocked {
+            println!("{}", related_task_line(t));
+        }
+    }
+
+    if !task.subtasks.is_empty() {
+        let done = task.subtasks.iter().filter(|s| s.resolved).count();
+        print_section(&format!("Subtasks ({}/{} done)", done, task.subtasks.len()));
+        for subtask in &task.subtasks {
+            let mark = if subtask.resolved { "x" } else { " " };
+            println!("  [{}] {}", mark, subtask.summary);
+        }
+    }
+
+    let mut links = crate::util::extract_urls(&format!("{}\n{}", task.summary, task.notes));
+    deduplicate_strings(&mut links);
+    if !links.is_empty() {
+        print_section("Links");
+        for link in links {
+            println!("  {}", link);
+        }
+    }
+
     // Render notes with termimad if present
     if !task.notes.is_empty() {
-        println!("\nNotes:");
-        println!("{}", "─".repeat(80));
+        print_section("Notes");
 
         let skin = MadSkin::default();
         skin.print_text(&task.notes);
+    }
 
-        println!("{}", "─".repeat(80));
+    let history = crate::git::git_task_history(&conf.repo, &task.uuid, SHOW_HISTORY_LIMIT)?;
+    if !history.is_empty() {
+        print_section("History");
+        for line in history {
+            println!("  {}", line);
+        }
     }
 
+    println!("{}", "─".repeat(80));
     Ok(())
 }
 
+/// How many commits `show` lists for a task
+const SHOW_HISTORY_LIMIT: usize = 5;
+
+/// Prints a heading for one part of the `show` output
+fn print_section(title: &str) {
+    println!("\n{}:", title);
+    println!("{}", "─".repeat(80));
+}
+
+/// A task mentioned by another one in `show`
+fn related_task_line(task: &Task) -> String {
+    if task.id > 0 {
+        format!("  {:>3} {:<8} {}", task.id, task.status, task.summary)
+    } else {
+        format!("  {:>3} {:<8} {}", "-", task.status, task.summary)
+    }
+}
+
 /// Show paused tasks
 pub fn cmd_show_paused(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, true)?;
+    let options = LoadOptions::statuses(&[STATUS_PAUSED]).settings(conf.repo_settings());
+    let mut ts = TaskSet::load_with(&conf.repo, &conf.ids_file, &options)?;
     let merged_query = query.merge(ctx);
 
     ts.filter(&merged_query);
     ts.filter_by_status(STATUS_PAUSED);
-    ts.display_by_next(ctx, true)?;
+    ts.display_by_next(ctx, true, &conf.preferences)?;
 
     Ok(())
 }
 
 /// Show resolved tasks
 pub fn cmd_show_resolved(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, true)?;
+    let mut ts = TaskSet::load(conf, true)?;
     let merged_query = query.merge(ctx);
 
     ts.unhide();
     ts.filter(&merged_query);
     ts.filter_by_status(STATUS_RESOLVED);
-    ts.display_by_week()?;
+    ts.display_by_week(&conf.preferences)?;
 
     Ok(())
 }
 
 /// Show all tags in use
 pub fn cmd_show_tags(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, true)?;
     let merged_query = query.merge(ctx);
+    // Notes are only needed to match a text search
+    let options = LoadOptions::new(true)
+        .skip_notes(merged_query.text.is_empty())
+        .settings(conf.repo_settings());
+    let mut ts = TaskSet::load_with(&conf.repo, &conf.ids_file, &options)?;
 
     ts.filter(&merged_query);
 
@@ -582,14 +1207,14 @@ pub fn cmd_show_tags(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
 
 /// Show template tasks
 pub fn cmd_show_templates(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    let mut ts = TaskSet::load(conf, false)?;
 
     ts.unhide();
     ts.filter_by_status(STATUS_TEMPLATE);
 
     let merged_query = query.merge(ctx);
     ts.filter(&merged_query);
-    ts.display_by_next(ctx, true)?;
+    ts.display_by_next(ctx, true, &conf.preferences)?;
 
     Ok(())
 }
@@ -603,83 +1228,493 @@ pub fn cmd_show_unorganised(conf: &Config, ctx: &Query, query: &Query) -> Result
         ));
     }
 
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    let mut ts = TaskSet::load(conf, false)?;
 
     // Don't filter by query or context - show ALL unorganised tasks
     ts.filter_unorganised();
-    ts.display_by_next(ctx, true)?;
+    ts.display_by_next(ctx, true, &conf.preferences)?;
 
     Ok(())
 }
 
-/// Start/activate a task
-pub fn cmd_start(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
-    if query.ids.is_empty() {
-        return Err(RstaskError::Parse(
-            "at least one task ID required".to_string(),
-        ));
-    }
+/// Tasks that haven't changed for `days`, oldest first, with when they last
+/// changed. Tasks saved before `modified` was tracked fall back to the git
+/// history, then to their creation
+fn stale_tasks<'a>(
+    conf: &Config,
+    tasks: impl IntoIterator<Item = &'a Task>,
+    days: u32,
+) -> Vec<(&'a Task, DateTime<Utc>)> {
+    let stale_before = Utc::now() - chrono::Duration::days(days as i64);
+    let tasks: Vec<&Task> = tasks.into_iter().collect();
+    let committed = if tasks.iter().any(|t| t.modified.is_none()) {
+        crate::git::last_modified(&conf.repo)
+    } else {
+        HashMap::new()
+    };
 
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    let mut stale: Vec<(&Task, DateTime<Utc>)> = tasks
+        .into_iter()
+        .map(|t| {
+            let changed = t
+                .modified
+                .or_else(|| committed.get(&t.uuid).copied())
+                .unwrap_or(t.created);
+            (t, changed)
+        })
+        .filter(|(_, changed)| *changed < stale_before)
+        .collect();
+    stale.sort_by_key(|(_, changed)| *changed);
+    stale
+}
 
-    for id in &query.ids {
-        let task = ts.must_get_by_id(*id);
+/// Show open tasks that haven't changed for a while, oldest first
+pub fn cmd_show_stale(conf: &Config, ctx: &Query, query: &Query, days: Option<u32>) -> Result<()> {
+    use crate::date_util::format_age;
 
-        if task.status != STATUS_PENDING && task.status != STATUS_PAUSED {
-            return Err(RstaskError::InvalidStatusTransition(
-                task.status.clone(),
-                STATUS_ACTIVE.to_string(),
-            ));
-        }
+    let mut ts = TaskSet::load(conf, false)?;
+    ts.filter(&query.merge(ctx));
 
-        let mut task = task.clone();
-        task.status = STATUS_ACTIVE.to_string();
-        task.write_pending = true;
+    let days = days.unwrap_or(conf.preferences.stale_days);
+    let mut stale = stale_tasks(conf, ts.tasks(), days);
+    if let Some(limit) = conf.preferences.limit {
+        stale.truncate(limit);
+    }
+    let stale_refs: Vec<&Task> = stale.iter().map(|(t, _)| *t).collect();
+    if print_brief(&stale_refs, conf.preferences.list_output) {
+        return Ok(());
+    }
 
-        ts.must_update_task(task)?;
+    if !stdout_is_tty() {
+        let mut out = io::stdout().lock();
+        write_json_seq(
+            &mut out,
+            stale.iter().map(|(t, _)| t.to_json_ref()),
+            conf.preferences.json_style,
+        )?;
+        writeln!(out)?;
+        return Ok(());
     }
 
-    ts.save_pending_changes()?;
+    ctx.print_context_description(conf.preferences.context_verbosity);
+    if stale.is_empty() {
+        println!("No tasks untouched for {} days or more.", days);
+        return Ok(());
+    }
 
-    let task_word = if query.ids.len() == 1 {
-        "task"
-    } else {
-        "tasks"
-    };
-    git_commit(
-        &conf.repo,
-        &format!("Started {} {}", query.ids.len(), task_word),
-        false,
-    )?;
+    let (w, _) = get_term_size();
+    let mut table = Table::new(
+        w,
+        vec![
+            "ID".to_string(),
+            "Priority".to_string(),
+            "Unchanged".to_string(),
+            "Project".to_string(),
+            "Summary".to_string(),
+        ],
+    );
+    for (task, changed) in &stale {
+        table.add_row(
+            vec![
+                task.id.to_string(),
+                task.priority.clone(),
+                format_age(*changed),
+                task.project.clone(),
+                task.long_summary(),
+            ],
+            task.style(),
+        );
+    }
+    table.render();
 
-    auto_sync_if_enabled(conf)?;
+    println!("\n{} tasks untouched for {}+ days.", stale.len(), days);
     Ok(())
 }
 
-/// Stop/pause an active task
-pub fn cmd_stop(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
-    if query.ids.is_empty() {
-        return Err(RstaskError::Parse(
-            "at least one task ID required".to_string(),
-        ));
+/// Weekly review: overdue tasks, tasks nobody has touched for a while and
+/// tasks without tags or a project, in one report
+pub fn cmd_review(
+    conf: &Config,
+    ctx: &Query,
+    query: &Query,
+    stale_days: Option<u32>,
+) -> Result<()> {
+    use crate::date_util::{format_age, start_of_day};
+    use crate::task::TaskJsonRef;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct Review<'a> {
+        overdue: Vec<TaskJsonRef<'a>>,
+        stale: Vec<TaskJsonRef<'a>>,
+        unorganised: Vec<TaskJsonRef<'a>>,
     }
 
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    let mut ts = TaskSet::load(conf, false)?;
+    // Unorganised tasks can't match a context, so they are gathered first
+    let mut unorganised: Vec<Task> = ts
+        .tasks()
+        .into_iter()
+        .filter(|t| t.tags.is_empty() && t.project.is_empty())
+        .cloned()
+        .collect();
+    unorganised.sort_by_key(|t| t.id);
+    ts.filter(&query.merge(ctx));
+
+    let today = start_of_day(Local::now()).with_timezone(&Utc);
+    let mut overdue: Vec<&Task> = ts
+        .tasks()
+        .into_iter()
+        .filter(|t| t.due.is_some_and(|due| due < today))
+        .collect();
+    overdue.sort_by_key(|t| t.due);
+
+    let stale_days = stale_days.unwrap_or(conf.preferences.review_stale_days);
+    let stale = stale_tasks(conf, ts.tasks(), stale_days);
+
+    if !stdout_is_tty() {
+        let review = Review {
+            overdue: overdue.iter().map(|t| t.to_json_ref()).collect(),
+            stale: stale.iter().map(|(t, _)| t.to_json_ref()).collect(),
+            unorganised: unorganised.iter().map(|t| t.to_json_ref()).collect(),
+        };
+        let json = match conf.preferences.json_style {
+            JsonStyle::Pretty => serde_json::to_string_pretty(&review)?,
+            JsonStyle::Compact => serde_json::to_string(&review)?,
+        };
+        println!("{}", json);
+        return Ok(());
+    }
 
-    for id in &query.ids {
-        let task = ts.must_get_by_id(*id);
+    ctx.print_context_description(conf.preferences.context_verbosity);
+    let date_style = conf.preferences.date_style;
 
-        if task.status != STATUS_ACTIVE {
-            return Err(RstaskError::InvalidStatusTransition(
+    println!("\x1b[1mOverdue ({})\x1b[0m", overdue.len());
+    for task in &overdue {
+        println!(
+            "{}  due {}",
+            related_task_line(task),
+            task.due_date_str(date_style)
+        );
+    }
+    if overdue.is_empty() {
+        println!("  Nothing overdue");
+    }
+
+    println!(
+        "\n\x1b[1mNot changed for {} days or more ({})\x1b[0m",
+        stale_days,
+        stale.len()
+    );
+    for (task, changed) in &stale {
+        println!(
+            "{}  last changed {} ago",
+            related_task_line(task),
+            format_age(*changed)
+        );
+    }
+    if stale.is_empty() {
+        println!("  Nothing stale");
+    }
+
+    println!("\n\x1b[1mUnorganised ({})\x1b[0m", unorganised.len());
+    for task in &unorganised {
+        println!("{}", related_task_line(task));
+    }
+    if unorganised.is_empty() {
+        println!("  Everything has a tag or project");
+    }
+
+    if !overdue.is_empty() || !stale.is_empty() || !unorganised.is_empty() {
+        println!(
+            "\nResolve what's finished with `rstask done <id>`, give overdue tasks a new \
+             date with `rstask modify <id> due:<date>`, and file the rest with \
+             `rstask modify <id> +<tag> project:<name>` or drop them with `rstask remove <id>`."
+        );
+    }
+
+    Ok(())
+}
+
+/// Help shown at the start of `triage` and for `?`
+const TRIAGE_HELP: &str = "Type changes such as \"project:web +bug P1 due:fri\"; known projects \
+and tags are completed from an unambiguous prefix. Or a single key: Enter skips, d marks the \
+task done, s starts it, q quits, ? shows this again.";
+
+/// Walks through unorganised tasks, and with `stale` the ones that haven't
+/// changed for `days`, asking what to do with each. Every answer is saved
+/// and committed straight away, so quitting part way loses nothing
+pub fn cmd_triage(conf: &Config, stale: bool, days: Option<u32>) -> Result<()> {
+    use crate::date_util::format_age;
+
+    let mut ts = TaskSet::load_for_update(conf, false)?;
+    let projects: Vec<String> = ts
+        .get_projects()
+        .into_iter()
+        .map(|p| p.name)
+        .filter(|name| !name.is_empty())
+        .collect();
+    let tags = ts.get_tags();
+
+    let mut queue: Vec<(i32, String)> = ts
+        .tasks()
+        .into_iter()
+        .filter(|t| t.tags.is_empty() && t.project.is_empty())
+        .map(|t| (t.id, "unorganised".to_string()))
+        .collect();
+    queue.sort_by_key(|(id, _)| *id);
+    if stale {
+        let days = days.unwrap_or(conf.preferences.stale_days);
+        for (task, changed) in stale_tasks(conf, ts.tasks(), days) {
+            if !queue.iter().any(|(id, _)| *id == task.id) {
+                queue.push((task.id, format!("unchanged for {}", format_age(changed))));
+            }
+        }
+    }
+
+    if queue.is_empty() {
+        println!("Nothing to triage.");
+        return Ok(());
+    }
+    eprintln!("{} tasks to triage. {}", queue.len(), TRIAGE_HELP);
+
+    let total = queue.len();
+    let mut triaged = 0;
+    'tasks: for (n, (id, reason)) in queue.into_iter().enumerate() {
+        let before = ts.must_get_by_id(id).clone();
+        eprintln!(
+            "\n[{}/{}] {}: {} ({}, {})",
+            n + 1,
+            total,
+            id,
+            before.summary,
+            before.priority,
+            reason
+        );
+        if let Some(line) = before.notes.lines().find(|l| !l.trim().is_empty()) {
+            eprintln!("    {}", line);
+        }
+
+        let mut task = before.clone();
+        let message = loop {
+            let Some(line) = prompt_line("> ")? else {
+                break 'tasks;
+            };
+            match line.as_str() {
+                "" => continue 'tasks,
+                "q" => break 'tasks,
+                "?" => eprintln!("{}", TRIAGE_HELP),
+                "d" => {
+                    task.status = STATUS_RESOLVED.to_string();
+                    task.resolved = Some(Utc::now());
+                    break format!("Resolved {}", task.summary);
+                }
+                "s" if task.status == STATUS_ACTIVE => eprintln!("Already started."),
+                "s" => {
+                    task.status = STATUS_ACTIVE.to_string();
+                    break format!("Started {}", task.summary);
+                }
+                _ => match triage_query(&line, &projects, &tags)
+                    .and_then(|query| check_dstask_compat(conf, &query).map(|()| query))
+                {
+                    Ok(query) => {
+                        check_new_taxonomy(conf, &ts, &query)?;
+                        task.modify(&query);
+                        break format!("Modified {}", task.summary);
+                    }
+                    Err(e) => eprintln!("{}", e),
+                },
+            }
+        };
+
+        task.write_pending = true;
+        ts.must_update_task(task.clone())?;
+        let paused = if conf.preferences.single_active_task && task.status == STATUS_ACTIVE {
+            ts.pause_active_except(&[&task.uuid])?
+        } else {
+            Vec::new()
+        };
+        ts.save_pending_changes()?;
+        commit_changes(conf, &message, true)?;
+        conf.events.emit_change(&before, &task);
+        for (before, after) in paused {
+            eprintln!("Paused {}: {}", after.id, after.summary);
+            conf.events.emit_change(&before, &after);
+        }
+        triaged += 1;
+    }
+
+    println!("Triaged {} of {} tasks", triaged, total);
+    if triaged > 0 {
+        auto_sync_if_enabled(conf, SyncTrigger::Modification)?;
+    }
+    Ok(())
+}
+
+/// Parses a line of changes typed during `triage`, completing projects and
+/// tags and accepting lowercase priorities
+fn triage_query(line: &str, projects: &[String], tags: &[String]) -> Result<Query> {
+    let mut words = vec![CMD_MODIFY.to_string()];
+    for word in line.split_whitespace() {
+        let lower = word.to_lowercase();
+        words.push(if let Some(project) = lower.strip_prefix("project:") {
+            format!("project:{}", complete_word(project, projects))
+        } else if let Some(tag) = lower.strip_prefix('+') {
+            format!("+{}", complete_word(tag, tags))
+        } else if is_valid_priority(&word.to_uppercase()) {
+            word.to_uppercase()
+        } else {
+            word.to_string()
+        });
+    }
+
+    let query = crate::query::parse_query(&words)?;
+    if !query.text.is_empty() {
+        return Err(RstaskError::Parse(format!(
+            "not understood: {}. Type ? for help",
+            query.text
+        )));
+    }
+    if !query.has_operators() {
+        return Err(RstaskError::Parse("nothing to change".to_string()));
+    }
+    Ok(query)
+}
+
+/// Start/activate a task
+pub fn cmd_start(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
+    if query.ids.is_empty() {
+        return Err(RstaskError::Parse(
+            "at least one task ID required".to_string(),
+        ));
+    }
+
+    if conf.preferences.single_active_task && query.ids.len() > 1 {
+        return Err(RstaskError::Parse(
+            "only one task can be started at a time, as single_active_task is set".to_string(),
+        ));
+    }
+
+    let mut ts = TaskSet::load_for_update(conf, false)?;
+    let mut changed = Vec::new();
+
+    for id in &query.ids {
+        let task = ts.must_get_by_id(*id);
+
+        if task.status != STATUS_PENDING && task.status != STATUS_PAUSED {
+            return Err(RstaskError::InvalidStatusTransition(
+                task.status.clone(),
+                STATUS_ACTIVE.to_string(),
+            ));
+        }
+
+        let mut task = task.clone();
+        task.status = STATUS_ACTIVE.to_string();
+        task.write_pending = true;
+
+        changed.push(task.clone());
+        ts.must_update_task(task)?;
+    }
+
+    let paused = if conf.preferences.single_active_task {
+        let keep: Vec<&str> = changed.iter().map(|t| t.uuid.as_str()).collect();
+        ts.pause_active_except(&keep)?
+    } else {
+        Vec::new()
+    };
+    for (_, task) in &paused {
+        println!("Paused {}: {}", task.id, task.summary);
+    }
+
+    ts.save_pending_changes()?;
+
+    let task_word = if query.ids.len() == 1 {
+        "task"
+    } else {
+        "tasks"
+    };
+    commit_changes(
+        conf,
+        &format!("Started {} {}", query.ids.len(), task_word),
+        false,
+    )?;
+    for task in changed {
+        conf.events.emit(&TaskEvent::TaskStarted(task));
+    }
+    for (before, after) in paused {
+        conf.events.emit_change(&before, &after);
+    }
+
+    auto_sync_if_enabled(conf, SyncTrigger::Modification)?;
+    Ok(())
+}
+
+/// Delegate tasks to someone, or clear who they're delegated to
+pub fn cmd_delegate(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
+    if query.ids.is_empty() {
+        return Err(RstaskError::Parse(
+            "at least one task ID required".to_string(),
+        ));
+    }
+
+    let person = query.text.trim().trim_start_matches('@').to_string();
+    let mut ts = TaskSet::load_for_update(conf, false)?;
+    let mut changed = Vec::new();
+
+    for id in &query.ids {
+        let before = ts.must_get_by_id(*id).clone();
+        let mut task = before.clone();
+        task.delegated_to = person.clone();
+        task.write_pending = true;
+
+        ts.must_update_task(task.clone())?;
+        changed.push((before, task));
+    }
+
+    ts.save_pending_changes()?;
+
+    let message = match (changed.as_slice(), person.is_empty()) {
+        ([(_, task)], true) => format!("Cleared delegate of {}", task.summary),
+        ([(_, task)], false) => format!("Delegated {} to {}", task.summary, person),
+        (_, true) => format!("Cleared delegate of {} tasks", changed.len()),
+        (_, false) => format!("Delegated {} tasks to {}", changed.len(), person),
+    };
+    commit_changes(conf, &message, false)?;
+    for (before, after) in changed {
+        conf.events.emit_change(&before, &after);
+    }
+
+    auto_sync_if_enabled(conf, SyncTrigger::Modification)?;
+    Ok(())
+}
+
+/// Stop/pause an active task
+pub fn cmd_stop(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
+    if query.ids.is_empty() {
+        return Err(RstaskError::Parse(
+            "at least one task ID required".to_string(),
+        ));
+    }
+
+    let mut ts = TaskSet::load_for_update(conf, false)?;
+    let mut changed = Vec::new();
+
+    for id in &query.ids {
+        let task = ts.must_get_by_id(*id);
+
+        if task.status != STATUS_ACTIVE {
+            return Err(RstaskError::InvalidStatusTransition(
                 task.status.clone(),
                 STATUS_PAUSED.to_string(),
             ));
         }
 
+        let before = task.clone();
         let mut task = task.clone();
         task.status = STATUS_PAUSED.to_string();
         task.write_pending = true;
 
+        changed.push((before, task.clone()));
         ts.must_update_task(task)?;
     }
 
@@ -690,20 +1725,25 @@ pub fn cmd_stop(conf: &Config, _ctx: &Query, query: &Query) -> Result<()> {
     } else {
         "tasks"
     };
-    git_commit(
-        &conf.repo,
+    commit_changes(
+        conf,
         &format!("Stopped {} {}", query.ids.len(), task_word),
         false,
     )?;
+    for (before, after) in changed {
+        conf.events.emit_change(&before, &after);
+    }
 
-    auto_sync_if_enabled(conf)?;
+    auto_sync_if_enabled(conf, SyncTrigger::Modification)?;
     Ok(())
 }
 
-/// Sync repository with git remote
-pub fn cmd_sync(repo_path: &str, quiet: bool) -> Result<String> {
+/// Sync repository with git remote, waiting up to `lock_timeout` for another
+/// rstask process to finish first
+pub fn cmd_sync(repo_path: &str, lock_timeout: std::time::Duration, quiet: bool) -> Result<String> {
     use crate::git::{git_pull, git_push};
 
+    let _lock = crate::lock::RepoLock::acquire(std::path::Path::new(repo_path), lock_timeout)?;
     // Pull with fast-forward, creating merge commits if needed
     let pull_summary = git_pull(repo_path, quiet)?;
 
@@ -713,12 +1753,677 @@ pub fn cmd_sync(repo_path: &str, quiet: bool) -> Result<String> {
     Ok(format!("{}, {}", pull_summary, push_summary))
 }
 
-/// Automatically sync if configured to do so
-fn auto_sync_if_enabled(conf: &Config) -> Result<()> {
-    use crate::preferences::SyncFrequency;
+/// Two-way sync of open tasks with the configured CalDAV task list
+pub fn cmd_caldav_sync(conf: &Config) -> Result<()> {
+    use crate::caldav::{CalDavState, CurlRemote, sync_tasks};
+
+    let url = conf.preferences.caldav_url.as_deref().ok_or_else(|| {
+        RstaskError::Other("caldav_url is not set in the config file".to_string())
+    })?;
+    let password = std::env::var("RSTASK_CALDAV_PASSWORD").ok();
+    let remote = CurlRemote::new(url, conf.preferences.caldav_username.clone(), password);
 
-    if conf.preferences.sync_frequency == SyncFrequency::AfterEveryModification {
-        cmd_sync(conf.repo.to_str().unwrap(), false).map(|_| ())?;
+    let mut ts = TaskSet::load_for_update(conf, true)?;
+    let mut state = CalDavState::load(&conf.repo);
+
+    let report = sync_tasks(&mut ts, &mut state, &remote)?;
+    ts.save_pending_changes()?;
+    state.save()?;
+
+    if report.pulled + report.created_local + report.resolved_local > 0 {
+        commit_changes(conf, "CalDAV sync", false)?;
+    }
+    println!("CalDAV sync: {}", report);
+
+    Ok(())
+}
+
+/// Serve task operations to MCP clients over stdin and stdout
+pub fn cmd_mcp(conf: &Config) -> Result<()> {
+    crate::mcp::serve(conf, io::stdin().lock(), io::stdout().lock())
+}
+
+/// Replace this binary with the latest release, or just report it
+#[cfg(feature = "self-update")]
+pub fn cmd_self_update(check_only: bool) -> Result<()> {
+    crate::update::self_update(check_only)
+}
+
+/// Install or run a git hook in the task repository
+pub fn cmd_hook(conf: &Config, args: &[String]) -> Result<()> {
+    match (
+        args.get(1).map(String::as_str),
+        args.get(2).map(String::as_str),
+    ) {
+        (Some("install"), Some(name)) => {
+            let path = crate::hook::install_hook(&conf.repo, name)?;
+            println!("Installed {}", path.display());
+            Ok(())
+        }
+        (Some("run"), Some(name)) => crate::hook::run_hook(&conf.repo, name),
+        _ => Err(RstaskError::Parse(
+            "usage: rstask hook install|run pre-commit".to_string(),
+        )),
+    }
+}
+
+/// Import Jira issues matching a JQL search, or link a task to an issue
+pub fn cmd_jira(conf: &Config, args: &[String]) -> Result<()> {
+    use crate::jira::{JiraClient, is_issue_key, issue_to_task};
+
+    if conf.preferences.dstask_compat
+        && matches!(args.get(1).map(String::as_str), Some("import" | "link"))
+    {
+        return Err(RstaskError::Parse(
+            "Jira links can't be saved while dstask_compat is on, as dstask's task files have \
+             no place for them"
+                .to_string(),
+        ));
+    }
+
+    match args.get(1).map(String::as_str) {
+        Some("import") if args.len() > 2 => {
+            let jql = args[2..].join(" ");
+            let issues = JiraClient::from_preferences(&conf.preferences)?.search(&jql)?;
+
+            let mut ts = TaskSet::load_for_update(conf, true)?;
+            let linked: std::collections::HashSet<String> = ts
+                .all_tasks()
+                .iter()
+                .map(|t| t.jira.clone())
+                .filter(|key| !key.is_empty())
+                .collect();
+
+            let mut imported = Vec::new();
+            for issue in issues.iter().filter(|i| !linked.contains(&i.key)) {
+                imported.push(ts.must_load_task(issue_to_task(issue))?);
+            }
+            if imported.is_empty() {
+                println!("No new issues; {} already linked to tasks", issues.len());
+                return Ok(());
+            }
+            ts.save_pending_changes()?;
+
+            let message = match imported.as_slice() {
+                [task] => format!("Imported {} from Jira", task.jira),
+                _ => format!("Imported {} issues from Jira", imported.len()),
+            };
+            commit_changes(conf, &message, false)?;
+            for task in &imported {
+                println!("Added {}: {} ({})", task.id, task.summary, task.jira);
+            }
+            if issues.len() > imported.len() {
+                println!(
+                    "Skipped {} already linked to tasks",
+                    issues.len() - imported.len()
+                );
+            }
+            Ok(())
+        }
+        Some("link") if args.len() == 4 => {
+            let id: i32 = args[2]
+                .parse()
+                .map_err(|_| RstaskError::Parse(format!("{} isn't a task ID", args[2])))?;
+            let key = args[3].to_uppercase();
+            if !is_issue_key(&key) {
+                return Err(RstaskError::Parse(format!(
+                    "{} isn't a Jira issue key such as PROJ-123",
+                    args[3]
+                )));
+            }
+
+            let mut ts = TaskSet::load_for_update(conf, false)?;
+            let before = ts
+                .get_by_id(id)
+                .ok_or_else(|| RstaskError::TaskNotFound(format!("Task with ID {} not found", id)))?
+                .clone();
+            let mut task = before.clone();
+            task.jira = key.clone();
+            task.write_pending = true;
+            ts.must_update_task(task.clone())?;
+            ts.save_pending_changes()?;
+
+            commit_changes(conf, &format!("Linked {} to {}", task.summary, key), false)?;
+            conf.events.emit_change(&before, &task);
+            Ok(())
+        }
+        _ => Err(RstaskError::Parse(
+            "usage: rstask jira import <JQL> | rstask jira link <id> <issue-key>".to_string(),
+        )),
+    }
+}
+
+/// Rewrite every task file in the given format, and keep writing that format
+pub fn cmd_migrate_format(conf: &Config, args: &[String]) -> Result<()> {
+    use crate::preferences::{Preferences, TaskFormat};
+    use crate::util::must_get_repo_path;
+
+    let format: TaskFormat = args
+        .get(1)
+        .ok_or_else(|| RstaskError::Parse("usage: rstask migrate-format md|yml".to_string()))?
+        .parse()?;
+    let other = match format {
+        TaskFormat::Md => TaskFormat::Yml,
+        TaskFormat::Yml => TaskFormat::Md,
+    };
+    let write_format = crate::task::WriteFormat {
+        format,
+        ..conf.repo_settings().write_format
+    };
+
+    let _lock = crate::lock::RepoLock::acquire(&conf.repo, conf.repo_settings().lock_timeout)?;
+    let mut ts = TaskSet::load_for_update(conf, true)?;
+    let mut converted = 0;
+    for task in ts.tasks_mut() {
+        let path_as = |format: TaskFormat| {
+            must_get_repo_path(
+                &conf.repo,
+                &task.status,
+                &format!("{}.{}", task.uuid, format.as_str()),
+            )
+        };
+        if path_as(format).exists() && !path_as(other).exists() {
+            continue;
+        }
+        // Not through save_pending_changes, which would mark every task modified
+        task.save_to_disk(&conf.repo, write_format)?;
+        converted += 1;
+    }
+
+    let file_word = if converted == 1 { "file" } else { "files" };
+    if converted > 0 {
+        commit_changes(
+            conf,
+            &format!(
+                "Converted {} task {} to {}",
+                converted,
+                file_word,
+                format.as_str()
+            ),
+            true,
+        )?;
+    }
+    println!(
+        "Converted {} task {} to {}",
+        converted,
+        file_word,
+        format.as_str()
+    );
+
+    if conf.preferences.task_format != format
+        && let Err(e) = Preferences::save_setting("task_format", format.as_str())
+    {
+        eprintln!(
+            "Warning: couldn't save the format to the config ({}); set \"task_format {}\" \
+             there so that changes keep being written as {}",
+            e,
+            format.as_str(),
+            format.as_str()
+        );
+    }
+
+    Ok(())
+}
+
+/// Import a dstask repository: its tasks, the IDs dstask showed for them and
+/// its context. Pointed at the rstask repository itself, it converts it in place
+pub fn cmd_migrate_from_dstask(conf: &Config, args: &[String]) -> Result<()> {
+    use crate::dstask;
+    use crate::local_state::{load_ids, save_ids};
+    use crate::maintenance::{duplicate_files, task_files};
+    use crate::task::parse_task_file;
+    use std::collections::HashSet;
+    use std::path::{Path, PathBuf};
+
+    let source = match args.get(1) {
+        Some(path) => PathBuf::from(path),
+        None => std::env::var("DSTASK_GIT_REPO")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| home::home_dir().unwrap_or_default().join(".dstask")),
+    };
+    if !source.join(".git").is_dir() {
+        return Err(RstaskError::Other(format!(
+            "{} isn't a git repository, so it isn't a dstask repository",
+            source.display()
+        )));
+    }
+    if !ALL_STATUSES
+        .iter()
+        .any(|status| source.join(status).is_dir())
+    {
+        return Err(RstaskError::Other(format!(
+            "{} has no task directories, so it isn't a dstask repository",
+            source.display()
+        )));
+    }
+
+    // Read everything before changing anything, so that a repository with a
+    // broken file imports nothing
+    let files = task_files(&source)?;
+    let duplicates: HashSet<&Path> = duplicate_files(&files)
+        .into_iter()
+        .map(|f| f.path.as_path())
+        .collect();
+    let mut tasks = Vec::new();
+    let mut broken = Vec::new();
+    for file in files
+        .iter()
+        .filter(|f| !duplicates.contains(f.path.as_path()))
+    {
+        let filename = file.path.file_name().unwrap_or_default().to_string_lossy();
+        match std::fs::read_to_string(&file.path)
+            .map_err(RstaskError::from)
+            .and_then(|data| parse_task_file(&filename, &data, file.status, 0))
+        {
+            Ok(task) => tasks.push(task),
+            Err(e) => broken.push(format!("  {}: {}", file.path.display(), e)),
+        }
+    }
+    if !broken.is_empty() {
+        return Err(RstaskError::Other(format!(
+            "{} task file(s) in {} can't be read, so nothing was imported:\n{}",
+            broken.len(),
+            source.display(),
+            broken.join("\n")
+        )));
+    }
+
+    let dstask_ids = dstask::read_ids(&source).unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: couldn't read dstask's task IDs, so new ones are given: {}",
+            e
+        );
+        Default::default()
+    });
+    let context = dstask::read_context(&source).unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: couldn't read dstask's context; set it again with \"rstask context\": {}",
+            e
+        );
+        None
+    });
+
+    let _lock = crate::lock::RepoLock::acquire(&conf.repo, conf.repo_settings().lock_timeout)?;
+    let in_place = source.canonicalize()? == conf.repo.canonicalize()?;
+    let existing: HashSet<String> = if in_place {
+        HashSet::new()
+    } else {
+        TaskSet::load(conf, true)?
+            .all_tasks()
+            .iter()
+            .map(|t| t.uuid.clone())
+            .collect()
+    };
+
+    // Written in the configured format, which converts them when in place
+    let mut imported = HashSet::new();
+    let mut skipped = 0;
+    for mut task in tasks {
+        if existing.contains(&task.uuid) {
+            skipped += 1;
+            continue;
+        }
+        task.save_to_disk(&conf.repo, conf.repo_settings().write_format)?;
+        imported.insert(task.uuid);
+    }
+
+    // dstask's IDs replace any rstask gave the same tasks before
+    let mut ids = load_ids(&conf.ids_file);
+    ids.retain(|uuid, _| !imported.contains(uuid));
+    let mut taken: HashSet<i32> = ids.values().copied().collect();
+    let mut kept_ids = 0;
+    for (uuid, id) in dstask_ids {
+        if imported.contains(&uuid) && taken.insert(id) {
+            ids.insert(uuid, id);
+            kept_ids += 1;
+        }
+    }
+    save_ids(&conf.ids_file, &ids)?;
+
+    let task_word = if imported.len() == 1 { "task" } else { "tasks" };
+    if !imported.is_empty() {
+        commit_changes(
+            conf,
+            &format!("Imported {} {} from dstask", imported.len(), task_word),
+            true,
+        )?;
+    }
+    println!(
+        "Imported {} {} from {}, {} keeping their dstask IDs",
+        imported.len(),
+        task_word,
+        source.display(),
+        kept_ids
+    );
+    if skipped > 0 {
+        println!("Skipped {} already in {}", skipped, conf.repo.display());
+    }
+
+    if let Some(context) = context {
+        let mut state = LocalState::load(&conf.state_file);
+        if *state.get_context() == Query::default() {
+            println!("Context set to {}, as in dstask", context);
+            state.set_context(context)?;
+            state.save()?;
+        } else {
+            println!("Kept the current context; dstask's was {}", context);
+        }
+    }
+
+    if in_place && !conf.preferences.dstask_compat {
+        println!(
+            "To keep using dstask on this repository too, set \"dstask_compat true\" in the config"
+        );
+    }
+
+    Ok(())
+}
+
+/// Add and update tasks from a CSV file, or stdin with `-`. Rows with the
+/// UUID of an existing task update it, and the rest are added. Nothing is
+/// written unless every row can be applied
+pub fn cmd_import_csv(conf: &Config, args: &[String]) -> Result<()> {
+    use std::io::Read;
+
+    let Some(path) = args.get(1) else {
+        return Err(RstaskError::Parse(
+            "import-csv needs a file to read, or - for stdin".to_string(),
+        ));
+    };
+    let data = if path == "-" {
+        let mut data = String::new();
+        io::stdin().read_to_string(&mut data)?;
+        data
+    } else {
+        std::fs::read_to_string(path)
+            .map_err(|e| RstaskError::Other(format!("couldn't read {}: {}", path, e)))?
+    };
+
+    let (rows, unknown) = crate::csv::parse_tasks(&data)?;
+    if !unknown.is_empty() {
+        eprintln!(
+            "Ignoring column(s) rstask doesn't have: {}",
+            unknown.join(", ")
+        );
+    }
+
+    let mut ts = TaskSet::load_for_update(conf, true)?;
+    let mut added = 0;
+    let mut updated = 0;
+    for row in &rows {
+        let line_error = |e: RstaskError| RstaskError::Other(format!("line {}: {}", row.line, e));
+        match row.uuid.as_deref().and_then(|uuid| ts.get_by_uuid(uuid)) {
+            Some(old) => {
+                let mut task = old.clone();
+                row.apply(&mut task)?;
+                // Rows exported and imported again without changes
+                if serde_json::to_value(&task)? == serde_json::to_value(old)? {
+                    continue;
+                }
+                ts.update_task(task).map_err(line_error)?;
+                updated += 1;
+            }
+            None => {
+                let mut task = Task::new(String::new());
+                if let Some(uuid) = &row.uuid {
+                    task.uuid = uuid.clone();
+                }
+                row.apply(&mut task)?;
+                if task.summary.is_empty() {
+                    return Err(RstaskError::Parse(format!(
+                        "line {}: a new task needs a summary",
+                        row.line
+                    )));
+                }
+                ts.load_task(task).map_err(line_error)?;
+                added += 1;
+            }
+        }
+    }
+
+    if added + updated == 0 {
+        println!("Nothing to import, every task is already up to date");
+        return Ok(());
+    }
+    ts.save_pending_changes()?;
+
+    let summary = format!("{} added, {} updated", added, updated);
+    commit_changes(conf, &format!("Imported CSV: {}", summary), true)?;
+    println!("Imported {}: {}", path, summary);
+    Ok(())
+}
+
+/// Check every task file and report problems, fixing what can be fixed when
+/// asked. Fails while problems remain, so scripts can tell
+pub fn cmd_doctor(conf: &Config, fix: bool) -> Result<()> {
+    use crate::doctor::{apply_fixes, diagnose};
+
+    let mut problems = diagnose(&conf.repo)?;
+    if fix && problems.iter().any(|p| p.fix.is_some()) {
+        let _lock = crate::lock::RepoLock::acquire(&conf.repo, conf.repo_settings().lock_timeout)?;
+        let fixed = apply_fixes(&conf.repo, &problems, conf.repo_settings().write_format)?;
+        let problem_word = if fixed == 1 { "problem" } else { "problems" };
+        commit_changes(
+            conf,
+            &format!("Fixed {} {} found by doctor", fixed, problem_word),
+            true,
+        )?;
+        println!("Fixed {} {}", fixed, problem_word);
+        problems = diagnose(&conf.repo)?;
+    }
+
+    if problems.is_empty() {
+        println!("No problems found");
+        return Ok(());
+    }
+
+    for problem in &problems {
+        let path = problem
+            .path
+            .strip_prefix(&conf.repo)
+            .unwrap_or(&problem.path);
+        let fixable = if problem.fix.is_some() {
+            " (fixable)"
+        } else {
+            ""
+        };
+        println!("{}: {}{}", path.display(), problem.message, fixable);
+    }
+
+    let fixable = problems.iter().filter(|p| p.fix.is_some()).count();
+    Err(RstaskError::Other(if fixable > 0 {
+        format!(
+            "{} problem(s) found; {} can be fixed with \"rstask doctor --fix\"",
+            problems.len(),
+            fixable
+        )
+    } else {
+        format!(
+            "{} problem(s) found that need fixing by hand",
+            problems.len()
+        )
+    }))
+}
+
+/// Tidy the repository: remove duplicate task files, prune the IDs file and
+/// run `git gc`, then report how big things have grown
+pub fn cmd_maintenance(conf: &Config) -> Result<()> {
+    use crate::local_state::{load_ids, save_ids};
+    use crate::maintenance::{dir_size, duplicate_files, format_bytes, task_files};
+
+    let _lock = crate::lock::RepoLock::acquire(&conf.repo, conf.repo_settings().lock_timeout)?;
+    let files = task_files(&conf.repo)?;
+    let duplicates = duplicate_files(&files);
+    for file in &duplicates {
+        std::fs::remove_file(&file.path)?;
+    }
+    if !duplicates.is_empty() {
+        let file_word = if duplicates.len() == 1 {
+            "file"
+        } else {
+            "files"
+        };
+        commit_changes(
+            conf,
+            &format!("Removed {} duplicate task {}", duplicates.len(), file_word),
+            true,
+        )?;
+    }
+    println!("Duplicate task files removed: {}", duplicates.len());
+
+    let ts = TaskSet::load(conf, true)?;
+    let open: std::collections::HashSet<&str> = ts
+        .all_tasks()
+        .iter()
+        .filter(|t| t.status != STATUS_RESOLVED)
+        .map(|t| t.uuid.as_str())
+        .collect();
+    let mut ids = load_ids(&conf.ids_file);
+    let before = ids.len();
+    ids.retain(|uuid, _| open.contains(uuid.as_str()));
+    if ids.len() < before {
+        save_ids(&conf.ids_file, &ids)?;
+    }
+    println!("Stale IDs file entries pruned: {}", before - ids.len());
+
+    let git_dir = conf.repo.join(".git");
+    let git_before = dir_size(&git_dir);
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(&conf.repo)
+        .args(["gc", "--quiet"])
+        .output()?;
+    if !output.status.success() {
+        return Err(RstaskError::Other(format!(
+            "git gc failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    println!(
+        "Git directory after gc: {} (was {})",
+        format_bytes(dir_size(&git_dir)),
+        format_bytes(git_before)
+    );
+
+    let files = task_files(&conf.repo)?;
+    println!(
+        "\n{} task files, {}",
+        files.len(),
+        format_bytes(files.iter().map(|f| f.size).sum())
+    );
+    for &status in ALL_STATUSES {
+        let count = files.iter().filter(|f| f.status == status).count();
+        if count > 0 {
+            println!("  {:<10} {}", status, count);
+        }
+    }
+
+    let mut with_notes: Vec<&Task> = ts
+        .all_tasks()
+        .iter()
+        .filter(|t| !t.notes.is_empty())
+        .collect();
+    if !with_notes.is_empty() {
+        with_notes.sort_by_key(|t| std::cmp::Reverse(t.notes.len()));
+        println!("\nLargest notes:");
+        for task in with_notes.iter().take(5) {
+            println!(
+                "  {:>9}  {}  {}",
+                format_bytes(task.notes.len() as u64),
+                &task.uuid[..8],
+                task.summary
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// How often `remind --daemon` checks for due tasks
+const REMIND_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Notify about tasks that are overdue or within their reminder lead time
+///
+/// Once notified, a task is snoozed for `remind_snooze_minutes` so that cron
+/// jobs and the daemon don't repeat themselves every minute.
+pub fn cmd_remind(conf: &Config, daemon: bool) -> Result<()> {
+    if !daemon {
+        let sent = send_reminders(conf)?;
+        if sent == 0 && stdout_is_tty() {
+            println!("No tasks due soon");
+        }
+        return Ok(());
+    }
+
+    loop {
+        if let Err(e) = send_reminders(conf) {
+            eprintln!("Error: {}", e);
+        }
+        std::thread::sleep(REMIND_POLL_INTERVAL);
+    }
+}
+
+/// Sends one round of reminders, returning how many tasks were notified
+fn send_reminders(conf: &Config) -> Result<usize> {
+    use crate::date_util::format_relative;
+    use crate::notify::{Notification, notify_all};
+    use chrono::Duration;
+
+    let ts = TaskSet::load(conf, false)?;
+    let mut state = LocalState::load(&conf.state_file);
+    let now = Utc::now();
+    let snooze = Duration::minutes(conf.preferences.remind_snooze_minutes as i64);
+
+    state.prune_snoozes(now);
+
+    let mut sent = 0;
+    for task in ts.tasks() {
+        let Some(due) = task.due else { continue };
+        if !task.is_due_soon(&conf.preferences, now)
+            || task.status == STATUS_TEMPLATE
+            || state.is_snoozed(&task.uuid, now)
+        {
+            continue;
+        }
+
+        let title = if due < now {
+            format!("Overdue ({})", format_relative(due))
+        } else {
+            format!("Due {}", format_relative(due))
+        };
+        let notification = Notification {
+            task_uuid: Some(task.uuid.clone()),
+            ..Notification::new(&title, &format!("{}: {}", task.id, task.summary))
+        };
+
+        state.snooze(&task.uuid, now + snooze);
+        if let Err(e) = notify_all(&conf.preferences, &notification) {
+            state.save()?;
+            return Err(e);
+        }
+        sent += 1;
+    }
+
+    state.save()?;
+    Ok(sent)
+}
+
+/// Automatically sync if the configured sync frequency calls for it
+pub fn auto_sync_if_enabled(conf: &Config, trigger: SyncTrigger) -> Result<()> {
+    let mut state = LocalState::load(&conf.state_file);
+
+    if conf.preferences.sync_frequency.should_sync(
+        trigger,
+        state.last_sync,
+        conf.preferences.sync_interval_minutes,
+    ) {
+        cmd_sync(
+            conf.repo.to_str().unwrap(),
+            conf.repo_settings().lock_timeout,
+            false,
+        )?;
+        state.mark_synced()?;
+        conf.events.emit(&TaskEvent::Synced);
     }
 
     Ok(())
@@ -728,7 +2433,9 @@ fn auto_sync_if_enabled(conf: &Config) -> Result<()> {
 pub fn cmd_template(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
     use crate::preferences::BulkCommitStrategy;
 
-    let mut ts = TaskSet::load(&conf.repo, &conf.ids_file, false)?;
+    check_dstask_compat(conf, &query.merge(ctx))?;
+
+    let mut ts = TaskSet::load_for_update(conf, false)?;
 
     if !query.ids.is_empty() {
         // Convert existing task(s) to template(s)
@@ -738,12 +2445,18 @@ pub fn cmd_template(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
             let task = ts.must_get_by_id(*id);
             let mut task = task.clone();
             task.status = STATUS_TEMPLATE.to_string();
+            if query.schedule.is_some() {
+                task.modify(&Query {
+                    schedule: query.schedule.clone(),
+                    ..Query::default()
+                });
+            }
             task.write_pending = true;
             ts.must_update_task(task.clone())?;
 
             if conf.preferences.bulk_commit_strategy == BulkCommitStrategy::PerTask {
-                git_commit(
-                    &conf.repo,
+                commit_changes(
+                    conf,
                     &format!("Changed {} to Template", task.summary),
                     false,
                 )?;
@@ -753,8 +2466,8 @@ pub fn cmd_template(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
 
         if conf.preferences.bulk_commit_strategy == BulkCommitStrategy::Single && task_count > 0 {
             let task_word = if task_count == 1 { "task" } else { "tasks" };
-            git_commit(
-                &conf.repo,
+            commit_changes(
+                conf,
                 &format!("Changed {} {} to Template", task_count, task_word),
                 false,
             )?;
@@ -774,21 +2487,106 @@ pub fn cmd_template(conf: &Config, ctx: &Query, query: &Query) -> Result<()> {
             notes: merged_query.note.clone(),
             ..Default::default()
         };
+        if let Some(schedule) = &merged_query.schedule {
+            task.schedule = schedule.clone();
+            task.last_instantiated = Some(Utc::now());
+        }
 
         task = ts.must_load_task(task)?;
         ts.save_pending_changes()?;
-        git_commit(
-            &conf.repo,
-            &format!("Created template: {}", task.summary),
-            false,
-        )?;
+        commit_changes(conf, &format!("Created template: {}", task.summary), false)?;
     } else {
         return Err(RstaskError::Parse(
             "task ID or description required for template".to_string(),
         ));
     }
 
-    auto_sync_if_enabled(conf)?;
+    auto_sync_if_enabled(conf, SyncTrigger::Modification)?;
+    Ok(())
+}
+
+/// Copies a task from each template whose schedule came round since a task
+/// was last copied from it, returning the tasks added. A template only gets
+/// one task however many times its schedule came round, and the time is
+/// recorded on the template so that other machines sharing the repository
+/// don't copy it again.
+///
+/// Does nothing while `dstask_compat` is on: recording the copy would save
+/// the template in dstask's format, which drops its schedule.
+pub fn tick(conf: &Config) -> Result<Vec<Task>> {
+    if conf.preferences.dstask_compat {
+        return Ok(Vec::new());
+    }
+
+    let mut ts = TaskSet::load_for_update(conf, false)?;
+    let templates: Vec<Task> = ts
+        .all_tasks()
+        .iter()
+        .filter(|t| t.status == STATUS_TEMPLATE && !t.schedule.is_empty())
+        .cloned()
+        .collect();
+    if templates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let now = Local::now();
+    let mut added = Vec::new();
+    for mut template in templates {
+        let schedule = match Schedule::parse(&template.schedule) {
+            Ok(schedule) => schedule,
+            Err(e) => {
+                eprintln!("Warning: template {}: {}", template.id, e);
+                continue;
+            }
+        };
+        let since = template
+            .last_instantiated
+            .unwrap_or(template.created)
+            .with_timezone(&Local);
+        let Some(when) = schedule.last_between(since, now) else {
+            continue;
+        };
+
+        added.push(ts.must_load_task(task_from_template(&template))?);
+        template.last_instantiated = Some(when.with_timezone(&Utc));
+        template.write_pending = true;
+        ts.must_update_task(template)?;
+    }
+    if added.is_empty() {
+        return Ok(added);
+    }
+
+    ts.save_pending_changes()?;
+    let message = match added.as_slice() {
+        [task] => format!("Added {} from its schedule", task.summary),
+        _ => format!("Added {} tasks from schedules", added.len()),
+    };
+    // Quiet, as this also runs before `next` whose output may be JSON
+    commit_changes(conf, &message, true)?;
+    for task in &added {
+        conf.events.emit(&TaskEvent::TaskAdded(task.clone()));
+    }
+    auto_sync_if_enabled(conf, SyncTrigger::Modification)?;
+    Ok(added)
+}
+
+/// Adds the tasks due from scheduled templates
+pub fn cmd_tick(conf: &Config) -> Result<()> {
+    if conf.preferences.dstask_compat {
+        return Err(RstaskError::Parse(
+            "tick doesn't run while dstask_compat is on, as saving a template in dstask's \
+             format would drop its schedule"
+                .to_string(),
+        ));
+    }
+
+    let added = tick(conf)?;
+    if added.is_empty() {
+        println!("No scheduled templates are due");
+    }
+    for task in added {
+        println!("Added {} {}", task.id, task.summary);
+    }
     Ok(())
 }
 
@@ -808,10 +2606,113 @@ pub fn cmd_undo(conf: &Config, args: &[String]) -> Result<()> {
     }
 
     println!("Undone {} commit(s)", count);
-    auto_sync_if_enabled(conf)?;
+    auto_sync_if_enabled(conf, SyncTrigger::Modification)?;
     Ok(())
 }
 
+/// Show the command that `again` would repeat
+pub fn cmd_last(state: &LocalState) -> Result<()> {
+    match &state.last_command {
+        Some(last) => println!("{}", format_command_line(&last.args)),
+        None => println!("No command recorded yet"),
+    }
+    Ok(())
+}
+
+/// One line of the `usage` report as JSON
+#[derive(serde::Serialize)]
+struct UsageEntry<'a> {
+    command: &'a str,
+    count: u64,
+    last_used: Option<DateTime<Utc>>,
+}
+
+/// Report how often each command was run on this machine, or clear the
+/// counts with `usage reset`
+pub fn cmd_usage(conf: &Config, state: &mut LocalState, args: &[String]) -> Result<()> {
+    if args.get(1).is_some_and(|a| a == "reset") {
+        state.usage = None;
+        state.save()?;
+        println!("Usage statistics cleared");
+        return Ok(());
+    }
+
+    let Some(usage) = state.usage.as_ref().filter(|u| !u.commands.is_empty()) else {
+        if conf.preferences.usage_stats {
+            println!("No commands counted yet");
+        } else {
+            println!(
+                "Usage statistics are off. Set \"usage_stats true\" in the config to count \
+                 commands; the counts stay on this machine."
+            );
+        }
+        return Ok(());
+    };
+
+    let commands = usage.by_count();
+    if !stdout_is_tty() {
+        let entries = commands.iter().map(|(name, c)| UsageEntry {
+            command: name,
+            count: c.count,
+            last_used: DateTime::from_timestamp(c.last_used, 0),
+        });
+        let mut out = io::stdout().lock();
+        write_json_seq(&mut out, entries, conf.preferences.json_style)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    let total = usage.total();
+    let (w, _) = get_term_size();
+    let mut table = Table::new(
+        w,
+        vec![
+            "Command".to_string(),
+            "Runs".to_string(),
+            "Share".to_string(),
+            "Last used".to_string(),
+        ],
+    );
+    for (name, c) in &commands {
+        let last_used = DateTime::from_timestamp(c.last_used, 0)
+            .map(|t| t.with_timezone(&Local).format("%a %-d %b %Y").to_string())
+            .unwrap_or_default();
+        table.add_row(
+            vec![
+                name.to_string(),
+                c.count.to_string(),
+                format!("{:.0}%", c.count as f64 * 100.0 / total as f64),
+                last_used,
+            ],
+            RowStyle::default(),
+        );
+    }
+    table.render();
+
+    if let Some(since) = DateTime::from_timestamp(usage.since, 0) {
+        println!(
+            "\n{} commands since {}",
+            total,
+            since.with_timezone(&Local).format("%a %-d %b %Y")
+        );
+    }
+    Ok(())
+}
+
+/// The command line as it could be typed again, quoting words that need it
+pub fn format_command_line(args: &[String]) -> String {
+    let mut line = String::from("rstask");
+    for arg in args {
+        line.push(' ');
+        if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || "'\"$`\\".contains(c)) {
+            line.push_str(&format!("'{}'", arg.replace('\'', "'\\''")));
+        } else {
+            line.push_str(arg);
+        }
+    }
+    line
+}
+
 /// Display version information
 pub fn cmd_version() {
     println!("rstask {}", env!("CARGO_PKG_VERSION"));
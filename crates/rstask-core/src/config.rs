@@ -1,6 +1,10 @@
+use crate::Result;
+use crate::events::EventBus;
 use crate::preferences::Preferences;
+use crate::taskset::RepoSettings;
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 /// Configuration for the rstask application
 #[derive(Debug, Clone)]
@@ -15,39 +19,197 @@ pub struct Config {
     pub ctx_from_env_var: Option<String>,
     /// User preferences
     pub preferences: Preferences,
+    /// Refuse to introduce projects or tags that no task uses yet
+    pub refuse_new_taxonomy: bool,
+    /// Values for template placeholders, given with `add --var`
+    pub template_vars: Vec<(String, String)>,
+    /// Subscribers to events emitted by commands
+    pub events: EventBus,
 }
 
 impl Config {
     /// Creates a new Config from environment variables
-    pub fn new() -> Self {
+    pub fn new() -> Result<Self> {
         let ctx_from_env_var = env::var("RSTASK_CONTEXT").ok();
 
-        let home = home::home_dir()
-            .or_else(|| env::var("HOME").ok().map(PathBuf::from))
-            .expect("Could not determine home directory");
-
-        let default_repo = home.join(".rstask");
+        let default_repo = home_dir().join(".rstask");
         let repo = env::var("RSTASK_GIT_REPO")
             .map(PathBuf::from)
             .unwrap_or(default_repo);
 
-        let state_file = repo.join(".git").join("rstask").join("state.bin");
-        let ids_file = repo.join(".git").join("rstask").join("ids.bin");
+        // Earlier versions kept these inside the repository's .git directory
+        let legacy_dir = repo.join(".git").join("rstask");
+        let state_dir = repo_state_dir(&state_home(), &repo);
+        let state_file = migrate(&legacy_dir.join("state.bin"), &state_dir.join("state.bin"));
+        migrate(
+            &legacy_dir.join("ids.bin.bak"),
+            &state_dir.join("ids.bin.bak"),
+        );
+        let ids_file = migrate(&legacy_dir.join("ids.bin"), &state_dir.join("ids.bin"));
 
-        let preferences = Preferences::load();
+        let mut preferences = Preferences::load()?;
+        preferences.apply_repo_file(&repo);
+        let events = EventBus::from_preferences(&preferences);
 
-        Config {
+        Ok(Config {
             repo,
             state_file,
             ids_file,
             ctx_from_env_var,
             preferences,
+            refuse_new_taxonomy: false,
+            template_vars: Vec::new(),
+            events,
+        })
+    }
+
+    /// How task sets read and write the repository
+    pub fn repo_settings(&self) -> RepoSettings {
+        RepoSettings::from_preferences(&self.preferences)
+    }
+
+    /// Socket on which a running TUI accepts commands from other programs
+    pub fn tui_socket(&self) -> PathBuf {
+        self.repo.join(".git").join("rstask").join("tui.sock")
+    }
+}
+
+fn home_dir() -> PathBuf {
+    home::home_dir()
+        .or_else(|| env::var_os("HOME").map(PathBuf::from))
+        .or_else(|| env::var_os("USERPROFILE").map(PathBuf::from))
+        .expect("Could not determine home directory")
+}
+
+/// An XDG base directory variable, which only counts when it is absolute
+fn xdg_dir(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+/// `$XDG_CONFIG_HOME/rstask`, or `~/.config/rstask` when it isn't set. On
+/// Windows the fallback is the roaming application data folder
+pub fn config_dir() -> PathBuf {
+    let config_home = xdg_dir("XDG_CONFIG_HOME").unwrap_or_else(|| {
+        if cfg!(windows) {
+            dirs::config_dir().unwrap_or_else(|| home_dir().join("AppData").join("Roaming"))
+        } else {
+            home_dir().join(".config")
         }
+    });
+    config_home.join("rstask")
+}
+
+/// Where earlier versions looked for the config file when `XDG_CONFIG_HOME`
+/// isn't set, if that differs from [`config_dir`], as it does on macOS
+pub fn legacy_config_dir() -> Option<PathBuf> {
+    if xdg_dir("XDG_CONFIG_HOME").is_some() {
+        return None;
     }
+    dirs::config_dir()
+        .map(|dir| dir.join("rstask"))
+        .filter(|dir| *dir != config_dir())
+}
+
+/// `$XDG_STATE_HOME`, or `~/.local/state` when it isn't set. On Windows
+/// the fallback is the local application data folder
+fn state_home() -> PathBuf {
+    xdg_dir("XDG_STATE_HOME").unwrap_or_else(|| {
+        if cfg!(windows) {
+            dirs::data_local_dir().unwrap_or_else(|| home_dir().join("AppData").join("Local"))
+        } else {
+            home_dir().join(".local").join("state")
+        }
+    })
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self::new()
+/// The directory holding the state and IDs of one task repository, named
+/// after the repository's absolute path, such as
+/// `~/.local/state/rstask/%home%me%.rstask`
+pub fn repo_state_dir(state_home: &Path, repo: &Path) -> PathBuf {
+    let repo = std::path::absolute(repo).unwrap_or_else(|_| repo.to_path_buf());
+    let name: String = repo
+        .to_string_lossy()
+        .chars()
+        .map(|c| {
+            if matches!(c, '/' | '\\' | ':') {
+                '%'
+            } else {
+                c
+            }
+        })
+        .collect();
+    state_home.join("rstask").join(name)
+}
+
+/// The IDs file of the repository at `repo`
+pub fn ids_file(repo: &Path) -> PathBuf {
+    let legacy = repo.join(".git").join("rstask").join("ids.bin");
+    let current = repo_state_dir(&state_home(), repo).join("ids.bin");
+    if !current.exists() && legacy.exists() {
+        legacy
+    } else {
+        current
+    }
+}
+
+/// Moves a file an earlier version left at `old` to `new`, unless there
+/// already is one. Returns the path to use, which stays `old` if the move
+/// fails
+pub fn migrate(old: &Path, new: &Path) -> PathBuf {
+    if new.exists() || !old.exists() {
+        return new.to_path_buf();
+    }
+
+    let moved = new
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| {
+            // Renaming fails across file systems
+            fs::rename(old, new).or_else(|_| fs::copy(old, new).and_then(|_| fs::remove_file(old)))
+        });
+    match moved {
+        Ok(()) => new.to_path_buf(),
+        Err(e) => {
+            eprintln!(
+                "Warning: could not move {} to {}: {}",
+                old.display(),
+                new.display(),
+                e
+            );
+            old.to_path_buf()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_state_dir() {
+        assert_eq!(
+            repo_state_dir(Path::new("/state"), Path::new("/home/me/.rstask")),
+            Path::new("/state/rstask/%home%me%.rstask")
+        );
+    }
+
+    #[test]
+    fn test_migrate() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("repo/.git/rstask/ids.bin");
+        let new = dir.path().join("state/rstask/repo/ids.bin");
+        fs::create_dir_all(old.parent().unwrap()).unwrap();
+        fs::write(&old, b"ids").unwrap();
+
+        assert_eq!(migrate(&old, &new), new);
+        assert!(!old.exists());
+        assert_eq!(fs::read(&new).unwrap(), b"ids");
+
+        // Only once: a file at the new path wins
+        fs::write(&old, b"stale").unwrap();
+        assert_eq!(migrate(&old, &new), new);
+        assert_eq!(fs::read(&new).unwrap(), b"ids");
     }
 }
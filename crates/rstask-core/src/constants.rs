@@ -44,14 +44,38 @@ pub const CMD_MODIFY: &str = "modify";
 pub const CMD_EDIT: &str = "edit";
 pub const CMD_UNDO: &str = "undo";
 pub const CMD_SYNC: &str = "sync";
+pub const CMD_CALDAV_SYNC: &str = "caldav-sync";
 pub const CMD_OPEN: &str = "open";
 pub const CMD_SHOW: &str = "show";
 pub const CMD_GIT: &str = "git";
+pub const CMD_EXPORT_ICAL: &str = "export-ical";
+pub const CMD_EXPORT_CSV: &str = "export-csv";
+pub const CMD_IMPORT_CSV: &str = "import-csv";
+pub const CMD_JIRA: &str = "jira";
+pub const CMD_REMIND: &str = "remind";
+pub const CMD_REVIEW: &str = "review";
+pub const CMD_REPORT: &str = "report";
+pub const CMD_TICK: &str = "tick";
+pub const CMD_TRIAGE: &str = "triage";
+pub const CMD_MCP: &str = "mcp";
+pub const CMD_SELF_UPDATE: &str = "self-update";
+pub const CMD_HOOK: &str = "hook";
+pub const CMD_MAINTENANCE: &str = "maintenance";
+pub const CMD_DOCTOR: &str = "doctor";
+pub const CMD_MIGRATE_FORMAT: &str = "migrate-format";
+pub const CMD_MIGRATE_FROM_DSTASK: &str = "migrate-from-dstask";
+pub const CMD_AGAIN: &str = "again";
+pub const CMD_LAST: &str = "last";
+pub const CMD_USAGE: &str = "usage";
+pub const CMD_DELEGATE: &str = "delegate";
 pub const CMD_SHOW_NEXT: &str = "show-next";
 pub const CMD_SHOW_PROJECTS: &str = "show-projects";
 pub const CMD_SHOW_TAGS: &str = "show-tags";
 pub const CMD_SHOW_ACTIVE: &str = "show-active";
 pub const CMD_SHOW_PAUSED: &str = "show-paused";
+pub const CMD_SHOW_DELEGATED: &str = "show-delegated";
+pub const CMD_SHOW_BLOCKED: &str = "show-blocked";
+pub const CMD_SHOW_STALE: &str = "show-stale";
 pub const CMD_SHOW_OPEN: &str = "show-open";
 pub const CMD_SHOW_RESOLVED: &str = "show-resolved";
 pub const CMD_SHOW_TEMPLATES: &str = "show-templates";
@@ -72,10 +96,15 @@ pub const PRIORITY_LOW: &str = "P3";
 // Other constants
 pub const MAX_TASKS_OPEN: usize = 10000;
 pub const TASK_FILENAME_LEN: usize = 40;
+
+/// Settings committed with a task repository, overriding the user's config
+pub const REPO_PREFERENCES_FILE: &str = ".rstask.toml";
 pub const MIN_TASKS_SHOWN: usize = 8;
 pub const TERMINAL_HEIGHT_MARGIN: usize = 9;
 pub const IGNORE_CONTEXT_KEYWORD: &str = "--";
 pub const NOTE_MODE_KEYWORD: &str = "/";
+/// Used as +delegated/-delegated to filter on whether a task is delegated
+pub const DELEGATED_KEYWORD: &str = "delegated";
 
 // Theme constants (based on taskwarrior dark-256 theme)
 pub const TABLE_MAX_WIDTH: usize = 160;
@@ -109,6 +138,33 @@ pub const ALL_STATUSES: &[&str] = &[
     STATUS_TEMPLATE,
 ];
 
+/// Commands that `again` won't repeat: those about history, syncing or
+/// state rather than tasks, and the long-running ones
+pub const NON_REPEATABLE_CMDS: &[&str] = &[
+    CMD_AGAIN,
+    CMD_LAST,
+    CMD_UNDO,
+    CMD_SYNC,
+    CMD_CALDAV_SYNC,
+    CMD_GIT,
+    CMD_CONTEXT,
+    CMD_REMIND,
+    CMD_MCP,
+    CMD_SELF_UPDATE,
+    CMD_HOOK,
+    CMD_MAINTENANCE,
+    CMD_DOCTOR,
+    CMD_MIGRATE_FORMAT,
+    CMD_MIGRATE_FROM_DSTASK,
+    CMD_IMPORT_CSV,
+    CMD_JIRA,
+    CMD_TICK,
+    CMD_TRIAGE,
+    CMD_USAGE,
+    CMD_HELP,
+    CMD_VERSION,
+];
+
 pub const HIDDEN_STATUSES: &[&str] = &[STATUS_RECURRING, STATUS_RESOLVED, STATUS_TEMPLATE];
 
 pub const NON_RESOLVED_STATUSES: &[&str] = &[
@@ -154,14 +210,38 @@ pub const ALL_CMDS: &[&str] = &[
     CMD_EDIT,
     CMD_UNDO,
     CMD_SYNC,
+    CMD_CALDAV_SYNC,
     CMD_OPEN,
     CMD_SHOW,
     CMD_GIT,
+    CMD_EXPORT_ICAL,
+    CMD_EXPORT_CSV,
+    CMD_IMPORT_CSV,
+    CMD_JIRA,
+    CMD_REMIND,
+    CMD_REVIEW,
+    CMD_REPORT,
+    CMD_TICK,
+    CMD_TRIAGE,
+    CMD_MCP,
+    CMD_SELF_UPDATE,
+    CMD_HOOK,
+    CMD_MAINTENANCE,
+    CMD_DOCTOR,
+    CMD_MIGRATE_FORMAT,
+    CMD_MIGRATE_FROM_DSTASK,
+    CMD_AGAIN,
+    CMD_LAST,
+    CMD_USAGE,
+    CMD_DELEGATE,
     CMD_SHOW_NEXT,
     CMD_SHOW_PROJECTS,
     CMD_SHOW_TAGS,
     CMD_SHOW_ACTIVE,
     CMD_SHOW_PAUSED,
+    CMD_SHOW_DELEGATED,
+    CMD_SHOW_BLOCKED,
+    CMD_SHOW_STALE,
     CMD_SHOW_OPEN,
     CMD_SHOW_RESOLVED,
     CMD_SHOW_TEMPLATES,
@@ -0,0 +1,333 @@
+//! CSV export and import, for triage in a spreadsheet and for moving tasks
+//! between rstask and other tools
+//!
+//! The header row names the columns, so they can come in any order and
+//! columns rstask doesn't know are left out. Dates are RFC 3339, tags are
+//! separated by spaces and estimates are in minutes, as in the JSON output.
+
+use crate::constants::{ALL_STATUSES, STATUS_RESOLVED};
+use crate::date_util::{parse_duration_minutes, parse_str_to_date};
+use crate::task::Task;
+use crate::util::is_valid_uuid4_string;
+use crate::{Result, RstaskError};
+use chrono::{DateTime, Utc};
+
+/// Columns written by `export-csv`, in order
+pub const COLUMNS: &[&str] = &[
+    "uuid",
+    "id",
+    "status",
+    "summary",
+    "project",
+    "priority",
+    "tags",
+    "due",
+    "created",
+    "resolved",
+    "estimate",
+    "delegatedto",
+    "notes",
+];
+
+/// Header names other tools use, and the column they mean
+const ALIASES: &[(&str, &str)] = &[
+    ("title", "summary"),
+    ("name", "summary"),
+    ("description", "notes"),
+    ("labels", "tags"),
+    ("due date", "due"),
+    ("delegated to", "delegatedto"),
+];
+
+/// One row per task, after a header row
+pub fn tasks_to_csv(tasks: &[&Task]) -> String {
+    let mut out = String::new();
+    write_row(&mut out, COLUMNS.iter().map(|c| c.to_string()));
+
+    for task in tasks {
+        let date = |dt: Option<DateTime<Utc>>| dt.map(|d| d.to_rfc3339()).unwrap_or_default();
+        write_row(
+            &mut out,
+            [
+                task.uuid.clone(),
+                if task.id > 0 {
+                    task.id.to_string()
+                } else {
+                    String::new()
+                },
+                task.status.clone(),
+                task.summary.clone(),
+                task.project.clone(),
+                task.priority.clone(),
+                task.tags.join(" "),
+                date(task.due),
+                task.created.to_rfc3339(),
+                date(task.resolved),
+                task.estimate.map(|m| m.to_string()).unwrap_or_default(),
+                task.delegated_to.clone(),
+                task.notes.clone(),
+            ],
+        );
+    }
+
+    out
+}
+
+/// Quotes fields that need it, as RFC 4180 describes
+fn write_row(out: &mut String, fields: impl IntoIterator<Item = String>) {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(&field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+/// Splits CSV into rows of fields, along with the line each row starts on.
+/// Quoted fields can hold commas, quotes and line breaks
+fn parse_rows(data: &str) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = data.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if quoted {
+        return Err(RstaskError::Parse(format!(
+            "line {}: quoted field is never closed",
+            row_line
+        )));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((row_line, row));
+    }
+
+    // Blank lines, which spreadsheets leave at the end
+    rows.retain(|(_, fields)| fields.iter().any(|f| !f.is_empty()));
+    Ok(rows)
+}
+
+/// A row to import: a new task, or changes to the task with its UUID
+#[derive(Debug, Clone)]
+pub struct CsvTask {
+    pub line: usize,
+    pub uuid: Option<String>,
+    /// Column and value, for the columns in the file
+    values: Vec<(&'static str, String)>,
+}
+
+/// Reads the rows of a CSV file with a header row. Unknown columns are
+/// returned so they can be reported
+pub fn parse_tasks(data: &str) -> Result<(Vec<CsvTask>, Vec<String>)> {
+    let mut rows = parse_rows(data)?.into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Err(RstaskError::Parse("the file is empty".to_string()));
+    };
+
+    let mut unknown = Vec::new();
+    let columns: Vec<Option<&'static str>> = header
+        .iter()
+        .map(|name| {
+            let name = name.trim().to_lowercase();
+            let column = COLUMNS
+                .iter()
+                .copied()
+                .find(|c| *c == name)
+                .or_else(|| ALIASES.iter().find(|(a, _)| *a == name).map(|(_, c)| *c));
+            if column.is_none() {
+                unknown.push(name);
+            }
+            column
+        })
+        .collect();
+    if !columns.contains(&Some("summary")) && !columns.contains(&Some("uuid")) {
+        return Err(RstaskError::Parse(
+            "the header row has no summary column, so there is nothing to import".to_string(),
+        ));
+    }
+
+    let tasks = rows
+        .map(|(line, fields)| {
+            let mut uuid = None;
+            let mut values = Vec::new();
+            for (column, value) in columns.iter().zip(fields) {
+                match column {
+                    Some("uuid") if !value.trim().is_empty() => {
+                        uuid = Some(value.trim().to_string())
+                    }
+                    // IDs are given by rstask
+                    Some("uuid") | Some("id") | None => {}
+                    Some(column) => values.push((*column, value)),
+                }
+            }
+            CsvTask { line, uuid, values }
+        })
+        .collect();
+
+    Ok((tasks, unknown))
+}
+
+impl CsvTask {
+    /// Sets the task's fields from the row's columns. An empty cell clears
+    /// the field, except the summary, status and created time
+    pub fn apply(&self, task: &mut Task) -> Result<()> {
+        let error =
+            |message: String| RstaskError::Parse(format!("line {}: {}", self.line, message));
+
+        if let Some(uuid) = &self.uuid
+            && !is_valid_uuid4_string(uuid)
+        {
+            return Err(error(format!("{} isn't a valid UUID", uuid)));
+        }
+
+        for (column, value) in &self.values {
+            let value = value.trim();
+            match *column {
+                "summary" if value.is_empty() => return Err(error("summary is empty".to_string())),
+                "summary" => task.summary = value.to_string(),
+                "status" if value.is_empty() => {}
+                "status" if ALL_STATUSES.contains(&value) => task.status = value.to_string(),
+                "status" => return Err(error(format!("unknown status {}", value))),
+                "project" => task.project = value.to_string(),
+                "priority" if !value.is_empty() => task.priority = value.to_uppercase(),
+                "priority" => {}
+                "tags" => {
+                    task.tags = value
+                        .split([' ', ','])
+                        .map(|tag| tag.trim_start_matches('+'))
+                        .filter(|tag| !tag.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                }
+                "due" => task.due = parse_date(value).map_err(error)?,
+                "resolved" => task.resolved = parse_date(value).map_err(error)?,
+                "created" => {
+                    if let Some(created) = parse_date(value).map_err(error)? {
+                        task.created = created;
+                    }
+                }
+                "estimate" if value.is_empty() => task.estimate = None,
+                "estimate" => {
+                    let minutes = value
+                        .parse()
+                        .or_else(|_| parse_duration_minutes(value))
+                        .map_err(|e| error(e.to_string()))?;
+                    task.estimate = Some(minutes);
+                }
+                "delegatedto" => task.delegated_to = value.to_string(),
+                "notes" => task.notes = value.to_string(),
+                _ => {}
+            }
+        }
+
+        if task.status == STATUS_RESOLVED && task.resolved.is_none() {
+            task.resolved = Some(Utc::now());
+        }
+        Ok(())
+    }
+}
+
+/// RFC 3339, or anything `due:` accepts, such as 2025-06-01
+fn parse_date(value: &str) -> std::result::Result<Option<DateTime<Utc>>, String> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(Some(dt.with_timezone(&Utc)));
+    }
+    parse_str_to_date(value)
+        .map(|dt| Some(dt.with_timezone(&Utc)))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut task = Task::new("Call \"Sam\", then email".to_string());
+        task.tags = vec!["admin".to_string(), "phone".to_string()];
+        task.notes = "Number is on the fridge\nAsk about Friday".to_string();
+        task.estimate = Some(90);
+
+        let csv = tasks_to_csv(&[&task]);
+        let (rows, unknown) = parse_tasks(&csv).unwrap();
+        assert!(unknown.is_empty());
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].uuid.as_deref(), Some(task.uuid.as_str()));
+
+        let mut imported = Task::new(String::new());
+        rows[0].apply(&mut imported).unwrap();
+        assert_eq!(imported.summary, task.summary);
+        assert_eq!(imported.tags, task.tags);
+        assert_eq!(imported.notes, task.notes);
+        assert_eq!(imported.estimate, Some(90));
+        assert_eq!(imported.created, task.created);
+    }
+
+    #[test]
+    fn test_other_tools_columns() {
+        let csv = "Title,Labels,Due Date,Owner\n\
+                   Renew passport,admin home,2025-06-01,Sam\n\
+                   \n\
+                   \"Book dentist\",,,\n";
+        let (rows, unknown) = parse_tasks(csv).unwrap();
+        assert_eq!(unknown, vec!["owner"]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].line, 4);
+
+        let mut task = Task::new(String::new());
+        rows[0].apply(&mut task).unwrap();
+        assert_eq!(task.summary, "Renew passport");
+        assert_eq!(task.tags, vec!["admin", "home"]);
+        assert!(task.due.is_some());
+        assert!(rows[0].uuid.is_none());
+    }
+
+    #[test]
+    fn test_errors_name_the_line() {
+        let (rows, _) = parse_tasks("summary,status\nFix the build,finished\n").unwrap();
+        let err = rows[0].apply(&mut Task::new(String::new())).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parse error: line 2: unknown status finished"
+        );
+
+        assert!(parse_tasks("summary\n\"unclosed\n").is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::Result;
-use chrono::{Datelike, Days, Local, NaiveDate, TimeZone, Weekday};
+use chrono::{Datelike, Days, Local, Months, NaiveDate, TimeZone, Weekday};
 
 /// Returns the start of day (midnight) for a given time
 pub fn start_of_day(t: chrono::DateTime<Local>) -> chrono::DateTime<Local> {
@@ -8,9 +8,28 @@ pub fn start_of_day(t: chrono::DateTime<Local>) -> chrono::DateTime<Local> {
         .unwrap()
 }
 
-/// Parses weekday strings (full names and abbreviations)
-fn weekday_str_to_time(date_str: &str, selector: &str) -> Option<chrono::DateTime<Local>> {
-    let weekday = match date_str.to_lowercase().as_str() {
+/// Midnight at the start of a local date, which is how due dates are stored
+pub fn local_midnight(date: NaiveDate) -> chrono::DateTime<Local> {
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+        .unwrap()
+}
+
+/// The Sunday that ends the week `today` is in
+pub(crate) fn end_of_week(today: NaiveDate) -> NaiveDate {
+    let days = 6 - today.weekday().num_days_from_monday();
+    today + Days::new(days as u64)
+}
+
+/// The last day of the month `today` is in
+pub(crate) fn end_of_month(today: NaiveDate) -> NaiveDate {
+    let first = today.with_day(1).unwrap();
+    (first + Months::new(1)).pred_opt().unwrap()
+}
+
+/// Parses weekday names and abbreviations
+pub(crate) fn parse_weekday(day: &str) -> Option<Weekday> {
+    let weekday = match day.to_lowercase().as_str() {
         "sun" | "sunday" => Weekday::Sun,
         "mon" | "monday" => Weekday::Mon,
         "tue" | "tues" | "tuesday" => Weekday::Tue,
@@ -20,29 +39,84 @@ fn weekday_str_to_time(date_str: &str, selector: &str) -> Option<chrono::DateTim
         "sat" | "saturday" => Weekday::Sat,
         _ => return None,
     };
+    Some(weekday)
+}
 
-    let now = Local::now();
-    let now_weekday = now.weekday();
+/// Words `due:` understands, for shell completions. Counts such as `3d`
+/// and dates are left out
+pub fn due_keywords() -> Vec<String> {
+    const DAYS: [&str; 7] = [
+        "monday",
+        "tuesday",
+        "wednesday",
+        "thursday",
+        "friday",
+        "saturday",
+        "sunday",
+    ];
+    let mut words: Vec<String> = ["today", "tomorrow", "yesterday", "eow", "eom", "overdue"]
+        .iter()
+        .map(|w| w.to_string())
+        .collect();
+    words.extend(DAYS.iter().map(|d| d.to_string()));
+    words.extend(DAYS.iter().map(|d| format!("next-{}", d)));
+    words
+}
+
+/// The date of a weekday relative to `today`: the next one to come for
+/// "this" or no selector, and the one in the following week for "next"
+pub(crate) fn weekday_date(
+    today: NaiveDate,
+    weekday: Weekday,
+    selector: &str,
+) -> Option<NaiveDate> {
     let days_difference =
-        weekday.num_days_from_monday() as i64 - now_weekday.num_days_from_monday() as i64;
-
-    let target_date = match selector {
-        "next" => start_of_day(now + Days::new((days_difference + 7) as u64)),
-        "this" | "" => {
-            if days_difference <= 0 {
-                start_of_day(now + Days::new((days_difference + 7) as u64))
-            } else {
-                start_of_day(now + Days::new(days_difference as u64))
-            }
-        }
+        weekday.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64;
+
+    let days = match selector {
+        "next" => days_difference + 7,
+        "this" | "" if days_difference <= 0 => days_difference + 7,
+        "this" | "" => days_difference,
         _ => return None,
     };
 
-    Some(target_date)
+    today.checked_add_days(Days::new(days as u64))
+}
+
+/// Parses weekday strings (full names and abbreviations)
+fn weekday_str_to_time(date_str: &str, selector: &str) -> Option<chrono::DateTime<Local>> {
+    let weekday = parse_weekday(date_str)?;
+    let today = Local::now().date_naive();
+    weekday_date(today, weekday, selector).map(local_midnight)
+}
+
+/// Parses durations from now such as "3d", "2w" or "1m" (days, weeks, months),
+/// counting forwards or, for `back`, backwards
+fn duration_str_to_time(date_str: &str, back: bool) -> Option<chrono::DateTime<Local>> {
+    let unit_at = date_str.len().checked_sub(1)?;
+    let (count, unit) = date_str.split_at(unit_at);
+    if count.is_empty() || !count.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let count: u32 = count.parse().ok()?;
+
+    let now = Local::now();
+    let target = match (unit, back) {
+        ("d", false) => now.checked_add_days(Days::new(count as u64))?,
+        ("d", true) => now.checked_sub_days(Days::new(count as u64))?,
+        ("w", false) => now.checked_add_days(Days::new(count as u64 * 7))?,
+        ("w", true) => now.checked_sub_days(Days::new(count as u64 * 7))?,
+        ("m", false) => now.checked_add_months(Months::new(count))?,
+        ("m", true) => now.checked_sub_months(Months::new(count))?,
+        _ => return None,
+    };
+
+    Some(start_of_day(target))
 }
 
 /// Parses a date string into a DateTime
-/// Supports: "today", "tomorrow", "yesterday", "[next-]monday", "YYYY-MM-DD", "MM-DD", "DD"
+/// Supports: "today", "tomorrow", "yesterday", "eow", "eom", "[next-]monday",
+/// "3d", "2w", "1m", "YYYY-MM-DD", "MM-DD", "DD"
 pub fn parse_str_to_date(date_str: &str) -> Result<chrono::DateTime<Local>> {
     let now = Local::now();
     let lower = date_str.trim().to_lowercase();
@@ -51,6 +125,8 @@ pub fn parse_str_to_date(date_str: &str) -> Result<chrono::DateTime<Local>> {
         "today" => return Ok(start_of_day(now)),
         "tomorrow" => return Ok(start_of_day(now + Days::new(1))),
         "yesterday" => return Ok(start_of_day(now - Days::new(1))),
+        "eow" => return Ok(local_midnight(end_of_week(now.date_naive()))),
+        "eom" => return Ok(local_midnight(end_of_month(now.date_naive()))),
         _ => {}
     }
 
@@ -66,22 +142,24 @@ pub fn parse_str_to_date(date_str: &str) -> Result<chrono::DateTime<Local>> {
         return Ok(date);
     }
 
+    // Check for [n]d, [n]w, [n]m
+    if let Some(date) = duration_str_to_time(&lower, false) {
+        return Ok(date);
+    }
+
     // Try YYYY-MM-DD
     if let Ok(naive_date) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-        return Ok(Local
-            .from_local_datetime(&naive_date.and_hms_opt(0, 0, 0).unwrap())
-            .unwrap());
+        return Ok(local_midnight(naive_date));
     }
 
     // Try MM-DD
     if date_str.contains('-') && date_str.split('-').count() == 2 {
         let parts: Vec<&str> = date_str.split('-').collect();
         if let (Ok(month), Ok(day)) = (parts[0].parse::<u32>(), parts[1].parse::<u32>())
-            && let Some(naive_date) = NaiveDate::from_ymd_opt(now.year(), month, day) {
-                return Ok(Local
-                    .from_local_datetime(&naive_date.and_hms_opt(0, 0, 0).unwrap())
-                    .unwrap());
-            }
+            && let Some(naive_date) = NaiveDate::from_ymd_opt(now.year(), month, day)
+        {
+            return Ok(local_midnight(naive_date));
+        }
     }
 
     // Try DD (day of month)
@@ -89,13 +167,45 @@ pub fn parse_str_to_date(date_str: &str) -> Result<chrono::DateTime<Local>> {
         && (1..=31).contains(&day)
         && let Some(naive_date) = NaiveDate::from_ymd_opt(now.year(), now.month(), day)
     {
-        return Ok(Local
-            .from_local_datetime(&naive_date.and_hms_opt(0, 0, 0).unwrap())
-            .unwrap());
+        return Ok(local_midnight(naive_date));
+    }
+
+    parse_words_to_date(date_str, now)
+}
+
+/// Parses the start of a period that ends today: "1w" is a week ago, "3d"
+/// three days ago and "1m" a month ago. Anything else is read as a date, as
+/// for due dates
+pub fn parse_since(text: &str) -> Result<chrono::DateTime<Local>> {
+    match duration_str_to_time(&text.trim().to_lowercase(), true) {
+        Some(date) => Ok(date),
+        None => parse_str_to_date(text),
     }
+}
+
+/// Parses dates written in words, such as "next friday" or "eom"
+#[cfg(feature = "natural-dates")]
+fn parse_words_to_date(
+    date_str: &str,
+    now: chrono::DateTime<Local>,
+) -> Result<chrono::DateTime<Local>> {
+    crate::natural_date::parse_natural_date(date_str, now.date_naive())
+        .map(local_midnight)
+        .map_err(|word| {
+            crate::RstaskError::Parse(format!(
+                "Invalid due date: {}\nCouldn't make sense of '{}'. Try a date like 2025-12-25, 'next friday', 'in 2 weeks', 'eom' or '3d'.",
+                date_str, word
+            ))
+        })
+}
 
+#[cfg(not(feature = "natural-dates"))]
+fn parse_words_to_date(
+    date_str: &str,
+    _now: chrono::DateTime<Local>,
+) -> Result<chrono::DateTime<Local>> {
     Err(crate::RstaskError::Parse(format!(
-        "Invalid due date format: {}\nExpected format: YYYY-MM-DD, MM-DD or DD, relative date like 'next-monday', 'today', etc.",
+        "Invalid due date format: {}\nExpected format: YYYY-MM-DD, MM-DD or DD, relative date like 'next-monday', 'today', 'eom', '3d', '2w', '1m', etc.",
         date_str
     )))
 }
@@ -105,7 +215,7 @@ pub fn parse_due_date_arg(due_str: &str) -> Result<(String, chrono::DateTime<Loc
     let parts: Vec<&str> = due_str.splitn(2, ':').collect();
     if parts.len() != 2 {
         return Err(crate::RstaskError::Parse(format!(
-            "Invalid due query format: {}\nExpected format: due:YYYY-MM-DD, due:MM-DD, due:DD, due:next-monday, due:today, etc.",
+            "Invalid due query format: {}\nExpected format: due:YYYY-MM-DD, due:MM-DD, due:DD, due:next-monday, due:today, due:3d, etc.",
             due_str
         )));
     }
@@ -172,6 +282,78 @@ pub fn format_due_date(due: chrono::DateTime<Local>) -> String {
     }
 }
 
+/// Parses an effort estimate such as "45m", "2h" or "1h30m" into minutes
+pub fn parse_duration_minutes(text: &str) -> Result<u32> {
+    let invalid = || {
+        crate::RstaskError::Parse(format!(
+            "Invalid duration: {}\nExpected hours and minutes such as 45m, 2h or 1h30m",
+            text
+        ))
+    };
+
+    let mut minutes: u32 = 0;
+    let mut digits = String::new();
+    for c in text.trim().to_lowercase().chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            'h' | 'm' if !digits.is_empty() => {
+                let n: u32 = digits.parse().map_err(|_| invalid())?;
+                let n = if c == 'h' { n.checked_mul(60) } else { Some(n) };
+                minutes = n.and_then(|n| minutes.checked_add(n)).ok_or_else(invalid)?;
+                digits.clear();
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    if !digits.is_empty() || text.trim().is_empty() {
+        return Err(invalid());
+    }
+    Ok(minutes)
+}
+
+/// Formats minutes the way estimates are written, e.g. "45m", "2h" or "1h30m"
+pub fn format_minutes(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h{}m", h, m),
+    }
+}
+
+/// Formats a duration compactly, e.g. "45min", "3d" or "2w"
+fn format_duration_short(duration: chrono::Duration) -> String {
+    let minutes = duration.num_minutes().abs();
+
+    match minutes {
+        0..60 => format!("{}min", minutes),
+        60..1440 => format!("{}h", minutes / 60),
+        1440..20160 => format!("{}d", minutes / 1440),
+        20160..86400 => format!("{}w", minutes / 10080),
+        86400..525600 => format!("{}mo", minutes / 43200),
+        _ => format!("{}y", minutes / 525600),
+    }
+}
+
+/// Formats the time elapsed since a timestamp compactly, e.g. "3d" or "2w"
+pub fn format_age(since: chrono::DateTime<chrono::Utc>) -> String {
+    let elapsed = chrono::Utc::now() - since;
+    format_duration_short(elapsed.max(chrono::Duration::zero()))
+}
+
+/// Formats a timestamp relative to now, e.g. "in 3d" or "2w ago"
+pub fn format_relative(dt: chrono::DateTime<chrono::Utc>) -> String {
+    let delta = dt - chrono::Utc::now();
+
+    if delta.num_minutes().abs() < 1 {
+        "now".to_string()
+    } else if delta > chrono::Duration::zero() {
+        format!("in {}", format_duration_short(delta))
+    } else {
+        format!("{} ago", format_duration_short(delta))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -195,6 +377,74 @@ mod tests {
         assert_eq!(date.day(), 25);
     }
 
+    #[test]
+    fn test_parse_durations() {
+        let now = Local::now();
+
+        assert_eq!(
+            parse_str_to_date("3d").unwrap(),
+            start_of_day(now + Days::new(3))
+        );
+        assert_eq!(
+            parse_str_to_date("2W").unwrap(),
+            start_of_day(now + Days::new(14))
+        );
+        assert_eq!(
+            parse_str_to_date("1m").unwrap(),
+            start_of_day(now.checked_add_months(Months::new(1)).unwrap())
+        );
+        assert!(parse_str_to_date("d").is_err());
+        assert!(parse_str_to_date("3y").is_err());
+        assert!(parse_str_to_date("-3d").is_err());
+    }
+
+    #[test]
+    fn test_end_of_week_and_month() {
+        let wednesday = NaiveDate::from_ymd_opt(2026, 2, 18).unwrap();
+        let sunday = NaiveDate::from_ymd_opt(2026, 2, 22).unwrap();
+
+        assert_eq!(end_of_week(wednesday), sunday);
+        assert_eq!(end_of_week(sunday), sunday);
+        assert_eq!(
+            end_of_month(wednesday),
+            NaiveDate::from_ymd_opt(2026, 2, 28).unwrap()
+        );
+        assert!(parse_str_to_date("eom").is_ok());
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let today = start_of_day(Local::now());
+        assert_eq!(
+            parse_since("1w").unwrap(),
+            start_of_day(today - Days::new(7))
+        );
+        assert_eq!(
+            parse_since("3D").unwrap(),
+            start_of_day(today - Days::new(3))
+        );
+        assert_eq!(
+            parse_since("2025-06-01").unwrap().date_naive(),
+            NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()
+        );
+        assert!(parse_since("1y").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_minutes() {
+        assert_eq!(parse_duration_minutes("45m").unwrap(), 45);
+        assert_eq!(parse_duration_minutes("2H").unwrap(), 120);
+        assert_eq!(parse_duration_minutes("1h30m").unwrap(), 90);
+        assert!(parse_duration_minutes("90").is_err());
+        assert!(parse_duration_minutes("h").is_err());
+        assert!(parse_duration_minutes("2d").is_err());
+        assert!(parse_duration_minutes("").is_err());
+
+        assert_eq!(format_minutes(45), "45m");
+        assert_eq!(format_minutes(120), "2h");
+        assert_eq!(format_minutes(90), "1h30m");
+    }
+
     #[test]
     fn test_parse_weekdays() {
         // These tests will pass as long as the weekday parsing works
@@ -214,4 +464,25 @@ mod tests {
         let (filter, _date) = parse_due_date_arg("due:overdue").unwrap();
         assert_eq!(filter, "before");
     }
+
+    #[test]
+    fn test_format_age() {
+        let now = chrono::Utc::now();
+        assert_eq!(format_age(now), "0min");
+        assert_eq!(format_age(now - chrono::Duration::hours(5)), "5h");
+        assert_eq!(format_age(now - chrono::Duration::days(3)), "3d");
+        assert_eq!(format_age(now - chrono::Duration::days(21)), "3w");
+        assert_eq!(format_age(now - chrono::Duration::days(400)), "1y");
+    }
+
+    #[test]
+    fn test_format_relative() {
+        let now = chrono::Utc::now();
+        assert_eq!(format_relative(now), "now");
+        assert_eq!(
+            format_relative(now + chrono::Duration::days(3) + chrono::Duration::minutes(1)),
+            "in 3d"
+        );
+        assert_eq!(format_relative(now - chrono::Duration::days(14)), "2w ago");
+    }
 }
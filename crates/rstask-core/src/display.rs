@@ -1,11 +1,16 @@
 use crate::Result;
+use crate::column::{Column, insert_before_summary};
 use crate::constants::*;
+use crate::date_util::format_minutes;
+use crate::preferences::{ContextVerbosity, DateStyle, JsonStyle, ListOutput, Preferences};
 use crate::query::Query;
 use crate::table::{RowStyle, Table};
 use crate::task::Task;
 use crate::taskset::TaskSet;
 use crate::util::{get_term_size, stdout_is_tty};
 use chrono::{Datelike, Utc};
+use serde::{Serialize, Serializer};
+use std::io::{BufWriter, Write};
 
 impl Task {
     /// Returns the row style for this task
@@ -96,26 +101,55 @@ impl Task {
             );
         }
 
+        if !self.blocked_by.is_empty() {
+            table.add_row(
+                vec![
+                    "Blocked by".to_string(),
+                    Column::Blocked.cell(self, DateStyle::Absolute),
+                ],
+                RowStyle::default(),
+            );
+        }
+
         table.render();
     }
 }
 
 impl TaskSet {
     /// Displays tasks in "next" view (by priority and creation date)
-    pub fn display_by_next(&mut self, ctx: &Query, truncate: bool) -> Result<()> {
-        self.sort_by_created_ascending();
-        self.sort_by_priority_ascending();
+    pub fn display_by_next(
+        &mut self,
+        ctx: &Query,
+        truncate: bool,
+        prefs: &Preferences,
+    ) -> Result<()> {
+        self.sort(prefs.sort);
+        // Critical tasks cut off by the limit are still in this context
+        let critical_in_view = self
+            .tasks()
+            .iter()
+            .filter(|t| t.priority == PRIORITY_CRITICAL)
+            .count();
+        if let Some(limit) = prefs.limit {
+            self.limit(limit);
+        }
+        if print_brief(&self.tasks(), prefs.list_output) {
+            return Ok(());
+        }
 
         if stdout_is_tty() {
-            ctx.print_context_description();
-            self.render_table(truncate)?;
-
-            // Count critical tasks
-            let critical_in_view = self
-                .tasks()
-                .iter()
-                .filter(|t| t.priority == PRIORITY_CRITICAL)
-                .count();
+            self.print_context_details(ctx, prefs.context_verbosity);
+            self.render_table(truncate, prefs)?;
+            self.print_estimate_total();
+
+            if prefs.context_verbosity == ContextVerbosity::Verbose
+                && self.tasks().is_empty()
+                && ctx.has_operators()
+            {
+                println!(
+                    "\x1b[33mNothing to show in this context. Use `rstask -- <cmd>` or `rstask -n <cmd>` to bypass it.\x1b[0m"
+                );
+            }
 
             let total_critical = self
                 .all_tasks()
@@ -135,22 +169,64 @@ impl TaskSet {
 
             Ok(())
         } else {
-            self.render_json()
+            self.render_json(prefs.json_style)
+        }
+    }
+
+    /// Prints the total estimated effort of the tasks in view, if any have one
+    fn print_estimate_total(&self) {
+        let estimates: Vec<u32> = self.tasks().iter().filter_map(|t| t.estimate).collect();
+        if estimates.is_empty() {
+            return;
+        }
+
+        let total: u32 = estimates.iter().sum();
+        let task_word = if estimates.len() == 1 {
+            "task"
+        } else {
+            "tasks"
+        };
+        println!(
+            "Estimated effort: {} ({} {} with an estimate)",
+            format_minutes(total),
+            estimates.len(),
+            task_word
+        );
+    }
+
+    /// Prints the context description, plus how many tasks it hides when verbose
+    pub fn print_context_details(&self, ctx: &Query, verbosity: ContextVerbosity) {
+        ctx.print_context_description(verbosity);
+
+        if verbosity != ContextVerbosity::Verbose || !ctx.has_operators() {
+            return;
+        }
+
+        let hidden = self.num_hidden_by_context(ctx);
+        if hidden > 0 {
+            println!("\x1b[33m  {} task(s) hidden by this context\x1b[0m", hidden);
         }
     }
 
     /// Renders tasks as JSON
-    pub fn render_json(&self) -> Result<()> {
-        let tasks: Vec<_> = self.tasks().iter().map(|t| t.to_json()).collect();
-        let json = serde_json::to_string_pretty(&tasks)?;
-        println!("{}", json);
-        Ok(())
+    pub fn render_json(&self, style: JsonStyle) -> Result<()> {
+        print_json_seq(self.tasks().iter().map(|t| t.to_json_ref()), style)
+    }
+
+    /// Writes tasks as a JSON array, serializing one task at a time
+    pub fn write_json(&self, out: impl Write, style: JsonStyle) -> Result<()> {
+        write_json_seq(out, self.tasks().iter().map(|t| t.to_json_ref()), style)
     }
 
     /// Renders tasks as a table
-    pub fn render_table(&self, truncate: bool) -> Result<()> {
+    pub fn render_table(&self, truncate: bool, prefs: &Preferences) -> Result<()> {
+        let mut columns = prefs.table_columns().to_vec();
         let tasks = self.tasks();
         let total = tasks.len();
+        // The default columns gain "Blocked by" when it has something to say
+        if prefs.columns.is_empty() && tasks.iter().any(|t| !t.blocked_by.is_empty()) {
+            insert_before_summary(&mut columns, Column::Blocked);
+        }
 
         if tasks.is_empty() {
             println!("No tasks found. Run `rstask help` for instructions.");
@@ -181,31 +257,10 @@ impl TaskSet {
             &tasks[..]
         };
 
-        let mut table = Table::new(
-            w,
-            vec![
-                "ID".to_string(),
-                "Priority".to_string(),
-                "Tags".to_string(),
-                "Due".to_string(),
-                "Project".to_string(),
-                "Summary".to_string(),
-            ],
-        );
+        let mut table = Table::new(w, column_headers(&columns));
 
         for task in display_tasks {
-            let style = task.style();
-            table.add_row(
-                vec![
-                    format!("{:<2}", task.id),
-                    task.priority.clone(),
-                    task.tags.join(" "),
-                    task.parse_due_date_to_str(),
-                    task.project.clone(),
-                    task.long_summary(),
-                ],
-                style,
-            );
+            table.add_row(column_cells(&columns, task, prefs.date_style), task.style());
         }
 
         table.render();
@@ -220,8 +275,17 @@ impl TaskSet {
     }
 
     /// Displays tasks grouped by week (for show-resolved)
-    pub fn display_by_week(&mut self) -> Result<()> {
+    pub fn display_by_week(&mut self, prefs: &Preferences) -> Result<()> {
+        // A limit keeps the most recently resolved
+        if let Some(limit) = prefs.limit {
+            self.sort_by_resolved_descending();
+            self.limit(limit);
+        }
         self.sort_by_resolved_ascending();
+        if print_brief(&self.tasks(), prefs.list_output) {
+            return Ok(());
+        }
+        let columns = prefs.resolved_table_columns();
 
         if stdout_is_tty() {
             let (w, _) = get_term_size();
@@ -247,31 +311,11 @@ impl TaskSet {
                             resolved.format("%a %-d %b %Y")
                         );
 
-                        table = Some(Table::new(
-                            w,
-                            vec![
-                                "Resolved".to_string(),
-                                "Priority".to_string(),
-                                "Tags".to_string(),
-                                "Due".to_string(),
-                                "Project".to_string(),
-                                "Summary".to_string(),
-                            ],
-                        ));
+                        table = Some(Table::new(w, column_headers(columns)));
                     }
 
                     if let Some(ref mut t) = table {
-                        t.add_row(
-                            vec![
-                                resolved.format("%a %-d").to_string(),
-                                task.priority.clone(),
-                                task.tags.join(" "),
-                                task.parse_due_date_to_str(),
-                                task.project.clone(),
-                                task.long_summary(),
-                            ],
-                            task.style(),
-                        );
+                        t.add_row(column_cells(columns, task, prefs.date_style), task.style());
                     }
 
                     last_week = week;
@@ -285,26 +329,19 @@ impl TaskSet {
             println!("{} tasks.", tasks.len());
             Ok(())
         } else {
-            self.render_json()
+            self.render_json(prefs.json_style)
         }
     }
 
     /// Displays projects
-    pub fn display_projects(&self) -> Result<()> {
+    pub fn display_projects(&self, prefs: &Preferences) -> Result<()> {
         if stdout_is_tty() {
             self.render_projects_table()
         } else {
-            self.render_projects_json()
+            print_json_seq(self.get_projects(), prefs.json_style)
         }
     }
 
-    fn render_projects_json(&self) -> Result<()> {
-        let projects = self.get_projects();
-        let json = serde_json::to_string_pretty(&projects)?;
-        println!("{}", json);
-        Ok(())
-    }
-
     fn render_projects_table(&self) -> Result<()> {
         let projects = self.get_projects();
         let (w, _) = get_term_size();
@@ -313,6 +350,7 @@ impl TaskSet {
             vec![
                 "Name".to_string(),
                 "Progress".to_string(),
+                "Estimate".to_string(),
                 "Created".to_string(),
             ],
         );
@@ -323,6 +361,11 @@ impl TaskSet {
                     vec![
                         project.name.clone(),
                         format!("{}/{}", project.tasks_resolved, project.tasks),
+                        if project.estimate > 0 {
+                            format_minutes(project.estimate)
+                        } else {
+                            String::new()
+                        },
                         project.created.format("%a %-d %b %Y").to_string(),
                     ],
                     project.style(),
@@ -334,3 +377,51 @@ impl TaskSet {
         Ok(())
     }
 }
+
+/// Writes items as a JSON array without collecting them first
+pub fn write_json_seq<T: Serialize>(
+    out: impl Write,
+    items: impl IntoIterator<Item = T>,
+    style: JsonStyle,
+) -> Result<()> {
+    match style {
+        JsonStyle::Pretty => serde_json::Serializer::pretty(out).collect_seq(items)?,
+        JsonStyle::Compact => serde_json::Serializer::new(out).collect_seq(items)?,
+    }
+    Ok(())
+}
+
+/// Streams a JSON array to stdout, followed by a newline
+fn print_json_seq<T: Serialize>(
+    items: impl IntoIterator<Item = T>,
+    style: JsonStyle,
+) -> Result<()> {
+    let mut out = BufWriter::new(std::io::stdout().lock());
+    write_json_seq(&mut out, items, style)?;
+    writeln!(out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Prints tasks for `--count` or `--quiet`, returning false when the usual
+/// output is wanted instead
+pub fn print_brief(tasks: &[&Task], output: ListOutput) -> bool {
+    match output {
+        ListOutput::Full => return false,
+        ListOutput::Count => println!("{}", tasks.len()),
+        ListOutput::Quiet => {
+            for task in tasks {
+                println!("{} {}", task.id, task.summary);
+            }
+        }
+    }
+    true
+}
+
+fn column_headers(columns: &[Column]) -> Vec<String> {
+    columns.iter().map(|c| c.header().to_string()).collect()
+}
+
+fn column_cells(columns: &[Column], task: &Task, date_style: DateStyle) -> Vec<String> {
+    columns.iter().map(|c| c.cell(task, date_style)).collect()
+}
@@ -0,0 +1,263 @@
+//! Repository checks for `rstask doctor`
+//!
+//! The loader skips task files it can't read with a one-line warning and
+//! quietly ignores inconsistencies, so a bad hand edit or sync conflict can go
+//! unnoticed for a long time. Doctor reads every file and says what is wrong
+//! with it, and repairs what can be repaired without guessing.
+
+use crate::constants::{ALL_STATUSES, STATUS_RESOLVED};
+use crate::maintenance::{duplicate_files, task_files};
+use crate::task::{Task, WriteFormat, parse_task_file};
+use crate::util::is_valid_uuid4_string;
+use crate::{Result, RstaskError};
+use chrono::{Datelike, Utc};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// How `doctor --fix` repairs a problem
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fix {
+    /// Delete a copy of a task that the loader never reads
+    RemoveFile,
+    /// Give a resolved task its last change as the resolved time
+    SetResolved,
+    /// Clear the resolved time of a task that isn't resolved
+    ClearResolved,
+    /// Drop a dependency on a task that doesn't exist
+    DropDependency(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub path: PathBuf,
+    pub message: String,
+    /// None when it needs a person to look at it
+    pub fix: Option<Fix>,
+}
+
+/// Checks every file in the status directories
+pub fn diagnose(repo_path: &Path) -> Result<Vec<Problem>> {
+    let mut problems = Vec::new();
+    let problem = |path: &Path, message: String, fix: Option<Fix>| Problem {
+        path: path.to_path_buf(),
+        message,
+        fix,
+    };
+
+    for &status in ALL_STATUSES {
+        let Ok(entries) = std::fs::read_dir(repo_path.join(status)) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with('.') && !name.ends_with(".md") && !name.ends_with(".yml") {
+                problems.push(problem(&entry.path(), "not a task file".to_string(), None));
+            }
+        }
+    }
+
+    let files = task_files(repo_path)?;
+    let duplicates: HashSet<&Path> = duplicate_files(&files)
+        .into_iter()
+        .map(|f| f.path.as_path())
+        .collect();
+    let mut loaded_from = HashMap::new();
+    let mut tasks = Vec::new();
+
+    for file in &files {
+        if duplicates.contains(file.path.as_path()) {
+            problems.push(problem(
+                &file.path,
+                format!(
+                    "duplicate of the task in {}, which is the copy rstask reads",
+                    loaded_from.get(&file.uuid).copied().unwrap_or_default()
+                ),
+                Some(Fix::RemoveFile),
+            ));
+            continue;
+        }
+        loaded_from.insert(file.uuid.clone(), file.status);
+
+        let filename = file.path.file_name().unwrap_or_default().to_string_lossy();
+        let parsed = std::fs::read_to_string(&file.path)
+            .map_err(RstaskError::from)
+            .and_then(|data| parse_task_file(&filename, &data, file.status, 0));
+        match parsed {
+            Ok(mut task) => {
+                task.normalise();
+                tasks.push((&file.path, task));
+            }
+            Err(e) => problems.push(problem(&file.path, e.to_string(), None)),
+        }
+    }
+
+    let known: HashSet<&str> = tasks.iter().map(|(_, t)| t.uuid.as_str()).collect();
+    for (path, task) in &tasks {
+        // Dependencies are checked below, and can be fixed
+        let mut without_dependencies = task.clone();
+        without_dependencies.dependencies.clear();
+        if let Err(e) = without_dependencies.validate() {
+            problems.push(problem(path, e.to_string(), None));
+        }
+
+        for dependency in &task.dependencies {
+            if !known.contains(dependency.as_str()) {
+                let message = if is_valid_uuid4_string(dependency) {
+                    format!("depends on {}, which isn't a task", dependency)
+                } else {
+                    format!("depends on {}, which isn't a UUID", dependency)
+                };
+                problems.push(problem(
+                    path,
+                    message,
+                    Some(Fix::DropDependency(dependency.clone())),
+                ));
+            }
+        }
+
+        problems.extend(
+            date_problems(task)
+                .into_iter()
+                .map(|(message, fix)| problem(path, message, fix)),
+        );
+    }
+
+    Ok(problems)
+}
+
+/// Resolved times that don't fit the status, and dates that can't be right
+fn date_problems(task: &Task) -> Vec<(String, Option<Fix>)> {
+    let mut problems = Vec::new();
+
+    match (task.status == STATUS_RESOLVED, task.resolved) {
+        (true, None) => problems.push((
+            "resolved but has no resolved time".to_string(),
+            Some(Fix::SetResolved),
+        )),
+        (false, Some(_)) => problems.push((
+            format!("{} but has a resolved time", task.status),
+            Some(Fix::ClearResolved),
+        )),
+        _ => {}
+    }
+
+    if let Some(resolved) = task.resolved {
+        if resolved < task.created {
+            problems.push(("resolved before it was created".to_string(), None));
+        }
+        if resolved > Utc::now() {
+            problems.push(("resolved in the future".to_string(), None));
+        }
+    }
+
+    if let Some(due) = task.due
+        && !(2000..=2100).contains(&due.year())
+    {
+        problems.push((
+            format!("due {}, which looks like a typo", due.format("%Y-%m-%d")),
+            None,
+        ));
+    }
+
+    problems
+}
+
+/// Applies every fix in `problems`, returning how many were applied
+pub fn apply_fixes(
+    repo_path: &Path,
+    problems: &[Problem],
+    write_format: WriteFormat,
+) -> Result<usize> {
+    let mut by_path: HashMap<&Path, Vec<&Fix>> = HashMap::new();
+    for problem in problems {
+        if let Some(fix) = &problem.fix {
+            by_path.entry(&problem.path).or_default().push(fix);
+        }
+    }
+
+    let mut fixed = 0;
+    for (path, fixes) in by_path {
+        if fixes.contains(&&Fix::RemoveFile) {
+            std::fs::remove_file(path)?;
+            fixed += 1;
+            continue;
+        }
+
+        let status = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .unwrap_or_default()
+            .to_string_lossy();
+        let filename = path.file_name().unwrap_or_default().to_string_lossy();
+        let mut task = parse_task_file(&filename, &std::fs::read_to_string(path)?, &status, 0)?;
+
+        for fix in &fixes {
+            match fix {
+                Fix::RemoveFile => {}
+                Fix::SetResolved => task.resolved = Some(task.last_modified()),
+                Fix::ClearResolved => task.resolved = None,
+                Fix::DropDependency(uuid) => task.dependencies.retain(|d| d != uuid),
+            }
+        }
+        task.modified = Some(Utc::now());
+        // Also removes the old file if the write format changed it
+        task.save_to_disk(repo_path, write_format)?;
+        fixed += fixes.len();
+    }
+
+    Ok(fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::STATUS_PENDING;
+
+    fn write(repo: &Path, task: &mut Task) {
+        task.save_to_disk(repo, WriteFormat::default()).unwrap();
+    }
+
+    #[test]
+    fn test_diagnose_and_fix() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+
+        let mut blocker = Task::new("Order parts".to_string());
+        write(repo, &mut blocker);
+
+        let mut task = Task::new("Fix the bike".to_string());
+        task.dependencies = vec![
+            blocker.uuid.clone(),
+            "9a8b7c6d-0000-4000-8000-000000000000".to_string(),
+        ];
+        task.resolved = Some(Utc::now());
+        write(repo, &mut task);
+
+        let mut done = Task::new("Pump tyres".to_string());
+        done.status = STATUS_RESOLVED.to_string();
+        write(repo, &mut done);
+
+        std::fs::write(repo.join(STATUS_PENDING).join("notes.txt"), "").unwrap();
+
+        let problems = diagnose(repo).unwrap();
+        let mut messages: Vec<&str> = problems.iter().map(|p| p.message.as_str()).collect();
+        messages.sort();
+        assert_eq!(
+            messages,
+            vec![
+                "depends on 9a8b7c6d-0000-4000-8000-000000000000, which isn't a task",
+                "not a task file",
+                "pending but has a resolved time",
+                "resolved but has no resolved time",
+            ]
+        );
+
+        assert_eq!(
+            apply_fixes(repo, &problems, WriteFormat::default()).unwrap(),
+            3
+        );
+        let remaining = diagnose(repo).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].fix, None);
+    }
+}
@@ -0,0 +1,456 @@
+//! Compatibility with dstask repositories
+//!
+//! rstask reads dstask's task files as they are. Its IDs and context live in
+//! `.git/dstask` as Go gob streams, which `rstask migrate-from-dstask` decodes
+//! so that tasks keep the IDs people know them by. With `dstask_compat` set,
+//! tasks are written the way dstask writes them, so both tools can work on one
+//! repository while moving over.
+
+use crate::local_state::IdsMap;
+use crate::query::Query;
+use crate::task::{SubTask, Task};
+use crate::{Result, RstaskError};
+use chrono::{DateTime, Timelike, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Where dstask keeps the IDs it shows for open tasks
+pub fn ids_path(repo_path: &Path) -> PathBuf {
+    repo_path.join(".git").join("dstask").join("ids.bin")
+}
+
+/// Where dstask keeps its context
+pub fn state_path(repo_path: &Path) -> PathBuf {
+    repo_path.join(".git").join("dstask").join("state.bin")
+}
+
+/// Task UUIDs mapped to the IDs dstask showed for them. A repository dstask
+/// never listed has no IDs file, and gives an empty map
+pub fn read_ids(repo_path: &Path) -> Result<IdsMap> {
+    let path = ids_path(repo_path);
+    if !path.exists() {
+        return Ok(IdsMap::new());
+    }
+
+    let unexpected = || RstaskError::Parse(format!("{} doesn't hold task IDs", path.display()));
+    let gob::Value::Map(entries) = gob::decode(&std::fs::read(&path)?)? else {
+        return Err(unexpected());
+    };
+    entries
+        .into_iter()
+        .map(|entry| match entry {
+            (gob::Value::String(uuid), gob::Value::Int(id)) => Ok((uuid, id as i32)),
+            _ => Err(unexpected()),
+        })
+        .collect()
+}
+
+/// The context dstask has set, or None if there isn't one
+pub fn read_context(repo_path: &Path) -> Result<Option<Query>> {
+    let path = state_path(repo_path);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let state = gob::decode(&std::fs::read(&path)?)?;
+    let Some(gob::Value::Struct(context)) = state.field("Context") else {
+        return Ok(None);
+    };
+    let string = |name: &str| match context.get(name) {
+        Some(gob::Value::String(s)) => s.clone(),
+        _ => String::new(),
+    };
+    let strings = |name: &str| match context.get(name) {
+        Some(gob::Value::Slice(items)) => items
+            .iter()
+            .filter_map(|item| match item {
+                gob::Value::String(s) => Some(s.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let query = Query {
+        tags: strings("Tags"),
+        anti_tags: strings("AntiTags"),
+        project: string("Project"),
+        anti_projects: strings("AntiProjects"),
+        priority: string("Priority"),
+        ..Query::default()
+    };
+    Ok((query != Query::default()).then_some(query))
+}
+
+/// A task laid out the way dstask writes it: in dstask's field order, without
+/// the status, which is the directory, or fields dstask doesn't know, and
+/// with unset dates as Go's zero time
+#[derive(Serialize)]
+struct DstaskTask<'a> {
+    summary: &'a str,
+    notes: &'a str,
+    tags: &'a [String],
+    project: &'a str,
+    priority: &'a str,
+    delegatedto: &'a str,
+    subtasks: &'a [SubTask],
+    dependencies: &'a [String],
+    created: String,
+    resolved: String,
+    due: String,
+}
+
+/// The task file dstask would write for `task`
+pub fn task_to_yaml(task: &Task) -> Result<String> {
+    let yaml = serde_yaml::to_string(&DstaskTask {
+        summary: &task.summary,
+        notes: &task.notes,
+        tags: &task.tags,
+        project: &task.project,
+        priority: &task.priority,
+        delegatedto: &task.delegated_to,
+        subtasks: &task.subtasks,
+        dependencies: &task.dependencies,
+        created: go_time(Some(task.created)),
+        resolved: go_time(task.resolved),
+        due: go_time(task.due),
+    })?;
+
+    // Go quotes empty strings with double quotes. Only keys start a line, as
+    // notes are indented
+    Ok(yaml
+        .lines()
+        .map(|line| match line.strip_suffix(": ''") {
+            Some(key) if key.chars().all(|c| c.is_ascii_lowercase()) || key == "- summary" => {
+                format!("{}: \"\"\n", key)
+            }
+            _ => format!("{}\n", line),
+        })
+        .collect())
+}
+
+/// Go's RFC 3339 format, with trailing zeros dropped from the fraction
+fn go_time(dt: Option<DateTime<Utc>>) -> String {
+    let Some(dt) = dt else {
+        return "0001-01-01T00:00:00Z".to_string();
+    };
+
+    let mut formatted = dt.format("%Y-%m-%dT%H:%M:%S").to_string();
+    if dt.nanosecond() > 0 {
+        formatted.push_str(format!(".{:09}", dt.nanosecond()).trim_end_matches('0'));
+    }
+    formatted.push('Z');
+    formatted
+}
+
+/// Just enough of Go's gob encoding to read dstask's state: the type
+/// definitions a stream carries, and the values they describe
+mod gob {
+    use crate::{Result, RstaskError};
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Value {
+        Bool(bool),
+        Int(i64),
+        Uint(u64),
+        Float(f64),
+        Bytes(Vec<u8>),
+        String(String),
+        Slice(Vec<Value>),
+        Map(Vec<(Value, Value)>),
+        Struct(HashMap<String, Value>),
+    }
+
+    impl Value {
+        pub fn field(&self, name: &str) -> Option<&Value> {
+            match self {
+                Value::Struct(fields) => fields.get(name),
+                _ => None,
+            }
+        }
+    }
+
+    enum Type {
+        Bool,
+        Int,
+        Uint,
+        Float,
+        Bytes,
+        String,
+        /// Arrays are sent like slices
+        Slice(i64),
+        Map(i64, i64),
+        Struct(Vec<(String, i64)>),
+        /// Types with their own encoding, such as time.Time, sent as bytes
+        Opaque,
+    }
+
+    fn invalid() -> RstaskError {
+        RstaskError::Parse("not a gob stream dstask wrote".to_string())
+    }
+
+    struct Reader<'a> {
+        data: &'a [u8],
+    }
+
+    impl<'a> Reader<'a> {
+        fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+            if n > self.data.len() {
+                return Err(invalid());
+            }
+            let (taken, rest) = self.data.split_at(n);
+            self.data = rest;
+            Ok(taken)
+        }
+
+        /// Below 128 a single byte, otherwise the negated byte count followed
+        /// by the bytes, big-endian
+        fn uint(&mut self) -> Result<u64> {
+            let first = self.take(1)?[0];
+            if first < 0x80 {
+                return Ok(first as u64);
+            }
+            let len = first.wrapping_neg() as usize;
+            if len > 8 {
+                return Err(invalid());
+            }
+            Ok(self
+                .take(len)?
+                .iter()
+                .fold(0, |n, &byte| (n << 8) | byte as u64))
+        }
+
+        /// The sign is in the lowest bit, and the rest complemented if negative
+        fn int(&mut self) -> Result<i64> {
+            let n = self.uint()?;
+            Ok(if n & 1 == 1 {
+                !(n >> 1) as i64
+            } else {
+                (n >> 1) as i64
+            })
+        }
+
+        fn bytes(&mut self) -> Result<&'a [u8]> {
+            let len = self.uint()? as usize;
+            self.take(len)
+        }
+
+        fn string(&mut self) -> Result<String> {
+            Ok(String::from_utf8_lossy(self.bytes()?).into_owned())
+        }
+
+        /// Struct fields are sent as the difference from the previous field
+        /// number, ending with 0. `read` gets each field's index
+        fn fields(&mut self, mut read: impl FnMut(&mut Self, u64) -> Result<()>) -> Result<()> {
+            let mut field = 0;
+            loop {
+                match self.uint()? {
+                    0 => return Ok(()),
+                    delta => field += delta,
+                }
+                read(self, field - 1)?;
+            }
+        }
+    }
+
+    /// Decodes the first value in a stream, after the types it needs
+    pub fn decode(data: &[u8]) -> Result<Value> {
+        let mut types: HashMap<i64, Type> = HashMap::from([
+            (1, Type::Bool),
+            (2, Type::Int),
+            (3, Type::Uint),
+            (4, Type::Float),
+            (5, Type::Bytes),
+            (6, Type::String),
+        ]);
+
+        let mut stream = Reader { data };
+        loop {
+            let len = stream.uint()? as usize;
+            let mut message = Reader {
+                data: stream.take(len)?,
+            };
+            let id = message.int()?;
+            if id < 0 {
+                let definition = wire_type(&mut message)?;
+                types.insert(-id, definition);
+                continue;
+            }
+
+            // Anything but a struct comes after a field number of 0
+            if !matches!(types.get(&id), Some(Type::Struct(_))) {
+                message.uint()?;
+            }
+            return value(&mut message, &types, id, 0);
+        }
+    }
+
+    /// Reads a wireType, which says what a new type is made of
+    fn wire_type(r: &mut Reader) -> Result<Type> {
+        let mut definition = None;
+        r.fields(|r, kind| {
+            let mut elems = Vec::new();
+            let mut struct_fields = Vec::new();
+            r.fields(|r, field| {
+                match (kind, field) {
+                    // CommonType, with the type's name and ID
+                    (_, 0) => r.fields(|r, common| match common {
+                        0 => r.bytes().map(drop),
+                        _ => r.int().map(drop),
+                    })?,
+                    (2, 1) => {
+                        for _ in 0..r.uint()? {
+                            let mut name = String::new();
+                            let mut id = 0;
+                            r.fields(|r, f| {
+                                match f {
+                                    0 => name = r.string()?,
+                                    _ => id = r.int()?,
+                                }
+                                Ok(())
+                            })?;
+                            struct_fields.push((name, id));
+                        }
+                    }
+                    // Element, key and array length IDs
+                    _ => elems.push(r.int()?),
+                }
+                Ok(())
+            })?;
+
+            definition = Some(match (kind, elems.as_slice()) {
+                (0 | 1, [elem, ..]) => Type::Slice(*elem),
+                (2, _) => Type::Struct(std::mem::take(&mut struct_fields)),
+                (3, [key, elem]) => Type::Map(*key, *elem),
+                (4..=6, _) => Type::Opaque,
+                _ => return Err(invalid()),
+            });
+            Ok(())
+        })?;
+
+        definition.ok_or_else(invalid)
+    }
+
+    fn value(r: &mut Reader, types: &HashMap<i64, Type>, id: i64, depth: usize) -> Result<Value> {
+        // Types can refer to themselves, but the data is never this deep
+        if depth > 64 {
+            return Err(invalid());
+        }
+        let depth = depth + 1;
+
+        Ok(match types.get(&id).ok_or_else(invalid)? {
+            Type::Bool => Value::Bool(r.uint()? != 0),
+            Type::Int => Value::Int(r.int()?),
+            Type::Uint => Value::Uint(r.uint()?),
+            Type::Float => Value::Float(f64::from_bits(r.uint()?.swap_bytes())),
+            Type::Bytes | Type::Opaque => Value::Bytes(r.bytes()?.to_vec()),
+            Type::String => Value::String(r.string()?),
+            Type::Slice(elem) => Value::Slice(
+                (0..r.uint()?)
+                    .map(|_| value(r, types, *elem, depth))
+                    .collect::<Result<_>>()?,
+            ),
+            Type::Map(key, elem) => Value::Map(
+                (0..r.uint()?)
+                    .map(|_| {
+                        Ok((
+                            value(r, types, *key, depth)?,
+                            value(r, types, *elem, depth)?,
+                        ))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            Type::Struct(fields) => {
+                let mut values = HashMap::new();
+                r.fields(|r, field| {
+                    let (name, field_id) = fields.get(field as usize).ok_or_else(invalid)?;
+                    values.insert(name.clone(), value(r, types, *field_id, depth)?);
+                    Ok(())
+                })?;
+                Value::Struct(values)
+            }
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_decode_struct() {
+            // The example in Go's encoding/gob documentation:
+            // Point{X: 22, Y: 33}
+            let data = [
+                0x1f, 0xff, 0x81, 0x03, 0x01, 0x01, 0x05, 0x50, 0x6f, 0x69, 0x6e, 0x74, 0x01, 0xff,
+                0x82, 0x00, 0x01, 0x02, 0x01, 0x01, 0x58, 0x01, 0x04, 0x00, 0x01, 0x01, 0x59, 0x01,
+                0x04, 0x00, 0x00, 0x00, 0x07, 0xff, 0x82, 0x01, 0x2c, 0x01, 0x42, 0x00,
+            ];
+            let point = decode(&data).unwrap();
+            assert_eq!(point.field("X"), Some(&Value::Int(22)));
+            assert_eq!(point.field("Y"), Some(&Value::Int(33)));
+        }
+
+        #[test]
+        fn test_decode_map() {
+            // map[string]int{"ab": 3, "c": 300}
+            let data = [
+                0x0e, 0xff, 0x81, 0x04, 0x01, 0x02, 0xff, 0x82, 0x00, 0x01, 0x0c, 0x01, 0x04, 0x00,
+                0x00, 0x0d, 0xff, 0x82, 0x00, 0x02, 0x02, 0x61, 0x62, 0x06, 0x01, 0x63, 0xfe, 0x02,
+                0x58,
+            ];
+            assert_eq!(
+                decode(&data).unwrap(),
+                Value::Map(vec![
+                    (Value::String("ab".to_string()), Value::Int(3)),
+                    (Value::String("c".to_string()), Value::Int(300)),
+                ])
+            );
+            assert!(decode(&data[..20]).is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_task_to_yaml_matches_dstask() {
+        let mut task = Task::new("Renew passport".to_string());
+        task.tags = vec!["admin".to_string()];
+        task.created = Utc
+            .with_ymd_and_hms(2025, 3, 1, 9, 30, 0)
+            .unwrap()
+            .with_nanosecond(120_000_000)
+            .unwrap();
+        task.estimate = Some(30);
+
+        assert_eq!(
+            task_to_yaml(&task).unwrap(),
+            "summary: Renew passport\n\
+             notes: \"\"\n\
+             tags:\n\
+             - admin\n\
+             project: \"\"\n\
+             priority: P2\n\
+             delegatedto: \"\"\n\
+             subtasks: []\n\
+             dependencies: []\n\
+             created: 2025-03-01T09:30:00.12Z\n\
+             resolved: 0001-01-01T00:00:00Z\n\
+             due: 0001-01-01T00:00:00Z\n"
+        );
+
+        let parsed = crate::task::parse_task_file(
+            &format!("{}.yml", task.uuid),
+            &task_to_yaml(&task).unwrap(),
+            "pending",
+            0,
+        )
+        .unwrap();
+        assert_eq!(parsed.created, task.created);
+        assert_eq!(parsed.resolved, None);
+    }
+}
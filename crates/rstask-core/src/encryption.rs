@@ -0,0 +1,135 @@
+//! Encryption of task notes at rest
+//!
+//! With `notes_key_file` set, notes are written as ASCII-armored age
+//! ciphertext, so a repository hosted on a third-party forge shows summaries,
+//! tags and dates but not what was written in the notes. Notes are decrypted
+//! as tasks load, so the rest of rstask only sees plain text. Needs the
+//! `encryption` feature.
+
+use crate::{Result, RstaskError};
+use std::path::{Path, PathBuf};
+
+const ARMOR_BEGIN: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+
+/// The key notes are encrypted to and decrypted with, set from `notes_key_file`
+#[cfg(feature = "encryption")]
+static KEY: std::sync::RwLock<Option<age::x25519::Identity>> = std::sync::RwLock::new(None);
+
+/// Whether the notes are age ciphertext rather than plain text
+pub fn is_encrypted(notes: &str) -> bool {
+    notes.trim_start().starts_with(ARMOR_BEGIN)
+}
+
+/// Reads the key in `notes_key_file`, if set. A leading `~/` is the home
+/// directory
+pub fn load_key(notes_key_file: Option<&str>) -> Result<()> {
+    let Some(path) = notes_key_file else {
+        return Ok(());
+    };
+    let path = match (path.strip_prefix("~/"), home::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    };
+    set_key_file(&path)
+}
+
+/// Reads an age identity file, such as one written by `age-keygen`
+#[cfg(feature = "encryption")]
+pub fn set_key_file(path: &Path) -> Result<()> {
+    let data = std::fs::read_to_string(path).map_err(|e| {
+        RstaskError::Other(format!(
+            "couldn't read the notes key {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let identity = data
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .unwrap_or_default()
+        .parse::<age::x25519::Identity>()
+        .map_err(|e| RstaskError::Parse(format!("{} isn't an age key: {}", path.display(), e)))?;
+
+    *KEY.write().unwrap() = Some(identity);
+    Ok(())
+}
+
+#[cfg(not(feature = "encryption"))]
+pub fn set_key_file(_path: &Path) -> Result<()> {
+    Err(RstaskError::Other(
+        "notes_key_file is set, but this build of rstask can't encrypt notes; \
+         build it with the \"encryption\" feature"
+            .to_string(),
+    ))
+}
+
+/// Encrypts the notes if a key is set. Empty notes stay empty, so tasks
+/// without notes don't grow an age header
+pub fn encrypt_notes(notes: &str) -> Result<String> {
+    #[cfg(feature = "encryption")]
+    if let Some(identity) = KEY.read().unwrap().as_ref() {
+        return encrypt_with(identity, notes);
+    }
+
+    Ok(notes.to_string())
+}
+
+/// Decrypts encrypted notes. Without a key they stay encrypted, and are
+/// written back as they are
+pub fn decrypt_notes(notes: &str) -> Result<String> {
+    #[cfg(feature = "encryption")]
+    if let Some(identity) = KEY.read().unwrap().as_ref() {
+        return decrypt_with(identity, notes);
+    }
+
+    Ok(notes.to_string())
+}
+
+#[cfg(feature = "encryption")]
+fn encrypt_with(identity: &age::x25519::Identity, notes: &str) -> Result<String> {
+    if notes.is_empty() || is_encrypted(notes) {
+        return Ok(notes.to_string());
+    }
+
+    age::encrypt_and_armor(&identity.to_public(), notes.as_bytes())
+        .map_err(|e| RstaskError::Other(format!("couldn't encrypt notes: {}", e)))
+}
+
+#[cfg(feature = "encryption")]
+fn decrypt_with(identity: &age::x25519::Identity, notes: &str) -> Result<String> {
+    if !is_encrypted(notes) {
+        return Ok(notes.to_string());
+    }
+
+    let plain = age::decrypt(identity, notes.trim().as_bytes()).map_err(|e| {
+        RstaskError::Other(format!(
+            "couldn't decrypt notes, check notes_key_file: {}",
+            e
+        ))
+    })?;
+    String::from_utf8(plain)
+        .map_err(|_| RstaskError::Other("decrypted notes aren't UTF-8".to_string()))
+}
+
+#[cfg(all(test, feature = "encryption"))]
+mod tests {
+    use super::*;
+    use age::x25519::Identity;
+
+    #[test]
+    fn test_notes_round_trip() {
+        let identity = Identity::generate();
+        let notes = "Door code is 4821\n\nAsk for Sam at reception";
+
+        let encrypted = encrypt_with(&identity, notes).unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.contains("4821"));
+        // Markdown bodies lose surrounding whitespace
+        assert_eq!(decrypt_with(&identity, encrypted.trim()).unwrap(), notes);
+
+        assert_eq!(encrypt_with(&identity, "").unwrap(), "");
+        assert_eq!(encrypt_with(&identity, &encrypted).unwrap(), encrypted);
+        assert!(decrypt_with(&Identity::generate(), &encrypted).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+//! Typed events emitted after a change is committed
+//!
+//! Commands and the TUI describe what they did as a [`TaskEvent`] and hand it
+//! to the [`EventBus`] in the config. Side effects such as webhooks subscribe
+//! to the bus instead of being called from each command.
+
+use crate::constants::{STATUS_ACTIVE, STATUS_RESOLVED};
+use crate::jira::{JiraClient, JiraSubscriber};
+use crate::preferences::Preferences;
+use crate::task::Task;
+use crate::webhook::WebhookSubscriber;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub enum TaskEvent {
+    TaskAdded(Task),
+    /// A task changed without being started or resolved
+    TaskModified {
+        task: Task,
+        /// Names of the changed fields, as in the task frontmatter
+        fields: Vec<&'static str>,
+    },
+    TaskStarted(Task),
+    TaskResolved(Task),
+    /// The repository was pulled and pushed
+    Synced,
+}
+
+impl TaskEvent {
+    /// Describes the change from `before` to `after`, if there is one
+    pub fn for_change(before: &Task, after: &Task) -> Option<TaskEvent> {
+        if after.status != before.status {
+            match after.status.as_str() {
+                STATUS_ACTIVE => return Some(TaskEvent::TaskStarted(after.clone())),
+                STATUS_RESOLVED => {
+                    return Some(TaskEvent::TaskResolved(after.clone()));
+                }
+                _ => {}
+            }
+        }
+
+        let fields = changed_fields(before, after);
+        if fields.is_empty() {
+            None
+        } else {
+            Some(TaskEvent::TaskModified {
+                task: after.clone(),
+                fields,
+            })
+        }
+    }
+}
+
+/// Lists the user-visible fields that differ between two versions of a task
+pub fn changed_fields(before: &Task, after: &Task) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if before.summary != after.summary {
+        fields.push("summary");
+    }
+    if before.status != after.status {
+        fields.push("status");
+    }
+    if before.project != after.project {
+        fields.push("project");
+    }
+    if before.tags != after.tags {
+        fields.push("tags");
+    }
+    if before.priority != after.priority {
+        fields.push("priority");
+    }
+    if before.due != after.due {
+        fields.push("due");
+    }
+    if before.notes != after.notes {
+        fields.push("notes");
+    }
+    if before.delegated_to != after.delegated_to {
+        fields.push("delegatedto");
+    }
+    if before.subtasks != after.subtasks {
+        fields.push("subtasks");
+    }
+    if before.dependencies != after.dependencies {
+        fields.push("dependencies");
+    }
+    if before.estimate != after.estimate {
+        fields.push("estimate");
+    }
+    if before.jira != after.jira {
+        fields.push("jira");
+    }
+    if before.schedule != after.schedule {
+        fields.push("schedule");
+    }
+    fields
+}
+
+/// How long the process waits at exit for subscribers' background work,
+/// such as webhook deliveries
+pub const FINISH_LIMIT: Duration = Duration::from_secs(5);
+
+/// Something that reacts to task events
+pub trait Subscriber {
+    fn on_event(&self, event: &TaskEvent);
+
+    /// Finishes work still running in the background before the process
+    /// exits, giving up at `deadline`
+    fn finish(&self, _deadline: Instant) {}
+}
+
+/// Delivers events to every registered subscriber, in registration order
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Vec<Arc<dyn Subscriber>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a bus with the subscribers configured in preferences
+    pub fn from_preferences(prefs: &Preferences) -> Self {
+        let mut bus = Self::new();
+        if !prefs.webhooks.is_empty() {
+            bus.subscribe(WebhookSubscriber::new(prefs.webhooks.clone()));
+        }
+        if let (Ok(client), Some(transition)) = (
+            JiraClient::from_preferences(prefs),
+            prefs.jira_done_transition.clone(),
+        ) {
+            bus.subscribe(JiraSubscriber::new(client, transition));
+        }
+        bus
+    }
+
+    pub fn subscribe(&mut self, subscriber: impl Subscriber + 'static) {
+        self.subscribers.push(Arc::new(subscriber));
+    }
+
+    pub fn emit(&self, event: &TaskEvent) {
+        for subscriber in &self.subscribers {
+            subscriber.on_event(event);
+        }
+    }
+
+    /// Lets subscribers finish their background work, waiting at most
+    /// `limit` in total
+    pub fn finish(&self, limit: Duration) {
+        let deadline = Instant::now() + limit;
+        for subscriber in &self.subscribers {
+            subscriber.finish(deadline);
+        }
+    }
+
+    /// Emits the event describing an update of a task, if anything changed
+    pub fn emit_change(&self, before: &Task, after: &Task) {
+        if let Some(event) = TaskEvent::for_change(before, after) {
+            self.emit(&event);
+        }
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Recorder(Rc<RefCell<Vec<TaskEvent>>>);
+
+    impl Subscriber for Recorder {
+        fn on_event(&self, event: &TaskEvent) {
+            self.0.borrow_mut().push(event.clone());
+        }
+    }
+
+    fn task(status: &str) -> Task {
+        Task {
+            summary: "Pay rent".to_string(),
+            status: status.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_for_change() {
+        let pending = task(STATUS_PENDING);
+
+        assert!(matches!(
+            TaskEvent::for_change(&pending, &task(STATUS_ACTIVE)),
+            Some(TaskEvent::TaskStarted(t)) if t.status == STATUS_ACTIVE
+        ));
+        assert!(matches!(
+            TaskEvent::for_change(&pending, &task(STATUS_RESOLVED)),
+            Some(TaskEvent::TaskResolved(_))
+        ));
+
+        let mut modified = pending.clone();
+        modified.project = "home".to_string();
+        modified.priority = PRIORITY_HIGH.to_string();
+        assert!(matches!(
+            TaskEvent::for_change(&pending, &modified),
+            Some(TaskEvent::TaskModified { fields, .. }) if fields == vec!["project", "priority"]
+        ));
+
+        assert!(TaskEvent::for_change(&pending, &pending).is_none());
+    }
+
+    #[test]
+    fn test_bus_delivers_to_subscribers() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let mut bus = EventBus::new();
+        bus.subscribe(Recorder(received.clone()));
+
+        bus.emit(&TaskEvent::Synced);
+        bus.emit_change(&task(STATUS_PENDING), &task(STATUS_PENDING));
+        bus.emit_change(&task(STATUS_ACTIVE), &task(STATUS_PAUSED));
+
+        let received = received.borrow();
+        assert_eq!(received.len(), 2);
+        assert!(matches!(received[0], TaskEvent::Synced));
+        assert!(matches!(
+            &received[1],
+            TaskEvent::TaskModified { fields, .. } if fields == &vec!["status"]
+        ));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::Result;
 use crate::RstaskError;
+use crate::date_util::{format_minutes, parse_duration_minutes};
 use crate::task::Task;
 use serde::{Deserialize, Serialize};
 
@@ -39,7 +40,12 @@ pub fn task_to_markdown(task: &Task) -> Result<String> {
         } else {
             Some(task.dependencies.clone())
         },
+        estimate: task.estimate.map(format_minutes),
+        jira: Some(task.jira.clone()).filter(|key| !key.is_empty()),
+        schedule: Some(task.schedule.clone()).filter(|s| !s.is_empty()),
+        last_instantiated: task.last_instantiated,
         created: task.created,
+        modified: task.modified,
         resolved: task.resolved,
         due: task.due,
     };
@@ -97,6 +103,12 @@ pub fn task_from_markdown(content: &str, uuid: &str, status: &str, id: i32) -> R
     let frontmatter: TaskFrontmatter =
         serde_yaml::from_str(&frontmatter_str).map_err(RstaskError::Yaml)?;
 
+    let estimate = frontmatter
+        .estimate
+        .as_deref()
+        .map(parse_duration_minutes)
+        .transpose()?;
+
     // Construct the task
     let task = Task {
         uuid: uuid.to_string(),
@@ -112,10 +124,16 @@ pub fn task_from_markdown(content: &str, uuid: &str, status: &str, id: i32) -> R
         delegated_to: frontmatter.delegatedto.unwrap_or_default(),
         subtasks: frontmatter.subtasks.unwrap_or_default(),
         dependencies: frontmatter.dependencies.unwrap_or_default(),
+        estimate,
+        jira: frontmatter.jira.unwrap_or_default(),
+        schedule: frontmatter.schedule.unwrap_or_default(),
+        last_instantiated: frontmatter.last_instantiated,
         created: frontmatter.created,
+        modified: frontmatter.modified,
         resolved: frontmatter.resolved,
         due: frontmatter.due,
         filtered: false,
+        blocked_by: Vec::new(),
     };
 
     Ok(task)
@@ -144,9 +162,33 @@ struct TaskFrontmatter {
     #[serde(skip_serializing_if = "Option::is_none")]
     dependencies: Option<Vec<String>>,
 
+    /// Written like "1h30m"
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    estimate: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    jira: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    schedule: Option<String>,
+
+    #[serde(
+        with = "crate::task::optional_datetime_rfc3339",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    last_instantiated: Option<chrono::DateTime<chrono::Utc>>,
+
     #[serde(with = "crate::task::datetime_rfc3339")]
     created: chrono::DateTime<chrono::Utc>,
 
+    #[serde(
+        with = "crate::task::optional_datetime_rfc3339",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    modified: Option<chrono::DateTime<chrono::Utc>>,
+
     #[serde(
         with = "crate::task::optional_datetime_rfc3339",
         skip_serializing_if = "Option::is_none",
@@ -183,10 +225,16 @@ mod tests {
             delegated_to: String::new(),
             subtasks: vec![],
             dependencies: vec![],
+            estimate: None,
+            jira: String::new(),
+            schedule: String::new(),
+            last_instantiated: None,
             created: Utc::now(),
+            modified: None,
             resolved: None,
             due: None,
             filtered: false,
+            blocked_by: Vec::new(),
         };
 
         let md = task_to_markdown(&task).unwrap();
@@ -236,13 +284,20 @@ With multiple lines"#;
             delegated_to: String::new(),
             subtasks: vec![],
             dependencies: vec![],
+            estimate: Some(90),
+            jira: "OPS-7".to_string(),
+            schedule: "every:monday".to_string(),
+            last_instantiated: Some(Utc::now()),
             created: Utc::now(),
+            modified: Some(Utc::now()),
             resolved: None,
             due: None,
             filtered: false,
+            blocked_by: Vec::new(),
         };
 
         let md = task_to_markdown(&original).unwrap();
+        assert!(md.contains("estimate: 1h30m\n"));
         let restored = task_from_markdown(&md, "test-uuid", "pending", 1).unwrap();
 
         assert_eq!(original.summary, restored.summary);
@@ -250,5 +305,9 @@ With multiple lines"#;
         assert_eq!(original.tags, restored.tags);
         assert_eq!(original.project, restored.project);
         assert_eq!(original.priority, restored.priority);
+        assert_eq!(original.estimate, restored.estimate);
+        assert_eq!(original.jira, restored.jira);
+        assert_eq!(original.schedule, restored.schedule);
+        assert_eq!(original.modified, restored.modified);
     }
 }
@@ -1,10 +1,11 @@
 use crate::Result;
 use git2::Repository;
-use std::io::{self, Write};
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
 use std::path::Path;
 
 fn is_stdout_tty() -> bool {
-    atty::is(atty::Stream::Stdout)
+    io::stdout().is_terminal()
 }
 
 fn confirm_or_abort(message: &str) -> Result<()> {
@@ -50,12 +51,176 @@ pub fn ensure_repo_exists(repo_path: &Path) -> Result<bool> {
         // Return true to indicate repo was just created
         return Ok(true);
     }
+
+    if let Some(warning) = head_warning(repo_path) {
+        eprintln!("Warning: {}", warning);
+    }
     Ok(false)
 }
 
+/// Where HEAD points, as far as committing and syncing are concerned
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeadState {
+    /// On a branch, including the unborn branch of a new repository
+    Branch(String),
+    Detached,
+}
+
+pub fn head_state(repo: &Repository) -> Result<HeadState> {
+    if repo.head_detached()? {
+        return Ok(HeadState::Detached);
+    }
+
+    let head = repo.find_reference("HEAD")?;
+    let target = head.symbolic_target().unwrap_or_default();
+    Ok(HeadState::Branch(
+        target.trim_start_matches("refs/heads/").to_string(),
+    ))
+}
+
+/// The branch tasks are normally committed to: the sync remote's default branch,
+/// else init.defaultBranch, main or master if it exists, else the only branch
+pub fn default_branch(repo: &Repository) -> Option<String> {
+    if let Some(remote) = sync_remote(repo, None)
+        && let Ok(remote_head) = repo.find_reference(&format!("refs/remotes/{}/HEAD", remote))
+        && let Some(target) = remote_head.symbolic_target()
+    {
+        return Some(
+            target
+                .trim_start_matches(&format!("refs/remotes/{}/", remote))
+                .to_string(),
+        );
+    }
+
+    let local: Vec<String> = repo
+        .branches(Some(git2::BranchType::Local))
+        .ok()?
+        .filter_map(|b| b.ok()?.0.name().ok()?.map(str::to_string))
+        .collect();
+
+    let configured = repo
+        .config()
+        .ok()
+        .and_then(|c| c.get_string("init.defaultBranch").ok());
+    let candidates = configured
+        .iter()
+        .map(String::as_str)
+        .chain(["main", "master"]);
+    for candidate in candidates {
+        if local.iter().any(|b| b == candidate) {
+            return Some(candidate.to_string());
+        }
+    }
+
+    match local.as_slice() {
+        [only] => Some(only.clone()),
+        _ => None,
+    }
+}
+
+/// How many commits the current branch is ahead of and behind its
+/// upstream, as of the last fetch. None without a branch or an upstream.
+pub fn ahead_behind(repo_path: &Path) -> Option<(usize, usize)> {
+    let repo = Repository::open(repo_path).ok()?;
+    let HeadState::Branch(name) = head_state(&repo).ok()? else {
+        return None;
+    };
+    let branch = repo.find_branch(&name, git2::BranchType::Local).ok()?;
+    let local = branch.get().target()?;
+    let upstream = branch.upstream().ok()?.get().target()?;
+    repo.graph_ahead_behind(local, upstream).ok()
+}
+
+/// Explains what's wrong if HEAD is detached or on a branch other than the
+/// default one, since commits and syncs would then go somewhere unexpected
+pub fn head_warning(repo_path: &Path) -> Option<String> {
+    let repo = Repository::open(repo_path).ok()?;
+    match head_state(&repo).ok()? {
+        HeadState::Detached => Some(
+            "the task repository is on a detached HEAD, so changes can't be committed. \
+             Run \"rstask git checkout-default\" to go back to the default branch."
+                .to_string(),
+        ),
+        HeadState::Branch(branch) => {
+            let default = default_branch(&repo)?;
+            (branch != default).then(|| {
+                format!(
+                    "the task repository is on branch '{}' rather than '{}'. \
+                     Run \"rstask git checkout-default\" to switch back.",
+                    branch, default
+                )
+            })
+        }
+    }
+}
+
+/// Checks out the default branch. Commits only reachable from a detached
+/// HEAD are kept on a new branch so that they aren't lost.
+pub fn git_checkout_default(repo_path: &Path) -> Result<String> {
+    use std::process::Command;
+
+    let repo = Repository::open(repo_path)?;
+    let default = default_branch(&repo).ok_or_else(|| {
+        crate::RstaskError::Other(
+            "can't tell which branch is the default; check one out with \
+             \"rstask git checkout <branch>\""
+                .to_string(),
+        )
+    })?;
+
+    let mut kept = None;
+    if head_state(&repo)? == HeadState::Detached {
+        let head = repo.head()?.peel_to_commit()?;
+        let on_default = repo
+            .revparse_single(&default)
+            .and_then(|o| o.peel_to_commit())
+            .is_ok_and(|c| {
+                c.id() == head.id() || repo.graph_descendant_of(c.id(), head.id()).unwrap_or(false)
+            });
+        if !on_default {
+            let name = format!("rstask-detached-{}", &head.id().to_string()[..7]);
+            repo.branch(&name, &head, false)?;
+            kept = Some(name);
+        }
+    }
+
+    let output = Command::new("git")
+        .args(["-C", &repo_path.to_string_lossy(), "checkout", &default])
+        .output()?;
+    if !output.status.success() {
+        return Err(crate::RstaskError::Other(format!(
+            "git checkout {} failed: {}",
+            default,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(match kept {
+        Some(name) => format!(
+            "Switched to '{}'. Commits from the detached HEAD are kept on '{}'; \
+             merge them with \"rstask git merge {}\".",
+            default, name, name
+        ),
+        None => format!("Switched to '{}'", default),
+    })
+}
+
 pub fn git_commit(repo_path: &Path, message: &str, quiet: bool) -> Result<String> {
     use std::process::{Command, Stdio};
 
+    // Commits on a detached HEAD end up on no branch and are easily lost.
+    // Leaving the change uncommitted means the next commit picks it up.
+    if let Ok(repo) = Repository::open(repo_path)
+        && head_state(&repo)? == HeadState::Detached
+    {
+        return Err(crate::RstaskError::Other(
+            "not committing on a detached HEAD; the change is saved but uncommitted. \
+             Run \"rstask git checkout-default\", then any command that changes a task \
+             will commit it."
+                .to_string(),
+        ));
+    }
+
     // Check if repo is brand new (needed before diff-index to avoid missing HEAD error)
     let objects_dir = repo_path.join(".git/objects");
     let brand_new = if let Ok(entries) = std::fs::read_dir(&objects_dir) {
@@ -158,124 +323,219 @@ pub fn git_commit(repo_path: &Path, message: &str, quiet: bool) -> Result<String
     }
 }
 
-fn get_current_branch(repo_path: &str) -> Result<String> {
-    use std::process::Command;
+/// The branch HEAD is on, which may not have any commits yet
+fn current_branch(repo: &Repository) -> Result<String> {
+    match head_state(repo)? {
+        HeadState::Branch(branch) => Ok(branch),
+        HeadState::Detached => Err(crate::RstaskError::Other(
+            "not on a branch (detached HEAD); run \"rstask git checkout-default\" first"
+                .to_string(),
+        )),
+    }
+}
 
-    let output = Command::new("git")
-        .args(["-C", repo_path, "branch", "--show-current"])
-        .output()?;
+/// The remote that `branch` syncs with: the branch's own remote, else
+/// remote.pushDefault, else origin, else the only remote there is
+fn sync_remote(repo: &Repository, branch: Option<&str>) -> Option<String> {
+    let remotes: Vec<String> = repo
+        .remotes()
+        .ok()?
+        .iter()
+        .flatten()
+        .map(str::to_string)
+        .collect();
+    let config = repo.config().ok()?;
 
-    if !output.status.success() {
-        return Err(crate::RstaskError::Other(
-            "failed to get current branch".to_string(),
-        ));
+    let configured = branch
+        .and_then(|b| config.get_string(&format!("branch.{}.remote", b)).ok())
+        .into_iter()
+        .chain(config.get_string("remote.pushDefault").ok())
+        .chain(["origin".to_string()]);
+    for candidate in configured {
+        if remotes.contains(&candidate) {
+            return Some(candidate);
+        }
     }
 
-    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    match remotes.as_slice() {
+        [only] => Some(only.clone()),
+        _ => None,
+    }
+}
 
-    if branch.is_empty() {
-        return Err(crate::RstaskError::Other("not on a branch".to_string()));
+fn require_sync_remote(repo: &Repository, branch: &str) -> Result<String> {
+    if let Some(remote) = sync_remote(repo, Some(branch)) {
+        return Ok(remote);
+    }
+    if repo.remotes()?.is_empty() {
+        Err(crate::RstaskError::Other(
+            "No remote configured. Add a remote with: rstask git remote add origin <url>"
+                .to_string(),
+        ))
+    } else {
+        Err(crate::RstaskError::Other(
+            "several remotes are configured and none is chosen; pick one with: \
+             rstask git config remote.pushDefault <name>"
+                .to_string(),
+        ))
     }
+}
 
-    Ok(branch)
+/// The remote branch that `branch` pulls from and pushes to, if it tracks one
+fn upstream_branch(repo: &Repository, branch: &str) -> Option<String> {
+    let merge = repo
+        .config()
+        .ok()?
+        .get_string(&format!("branch.{}.merge", branch))
+        .ok()?;
+    Some(merge.trim_start_matches("refs/heads/").to_string())
 }
 
-fn has_upstream_branch(repo_path: &str, branch: &str) -> Result<bool> {
-    use std::process::Command;
+fn set_upstream(repo: &Repository, branch: &str, remote: &str, upstream: &str) -> Result<()> {
+    let mut config = repo.config()?;
+    config.set_str(&format!("branch.{}.remote", branch), remote)?;
+    config.set_str(
+        &format!("branch.{}.merge", branch),
+        &format!("refs/heads/{}", upstream),
+    )?;
+    Ok(())
+}
 
-    let output = Command::new("git")
-        .args([
-            "-C",
-            repo_path,
-            "rev-parse",
-            "--abbrev-ref",
-            &format!("{}@{{upstream}}", branch),
-        ])
-        .output()?;
+/// Branches of `remote` as of the last fetch, without the remote's name
+fn remote_branches(repo: &Repository, remote: &str) -> Vec<String> {
+    let prefix = format!("{}/", remote);
+    let Ok(branches) = repo.branches(Some(git2::BranchType::Remote)) else {
+        return Vec::new();
+    };
+    branches
+        .filter_map(|b| b.ok()?.0.name().ok()?.map(str::to_string))
+        .filter_map(|name| name.strip_prefix(&prefix).map(str::to_string))
+        .filter(|name| name != "HEAD")
+        .collect()
+}
 
-    Ok(output.status.success())
+/// Picks the remote branch to pull into `branch` after a fetch. A branch
+/// that tracks nothing pulls the remote branch of the same name. A branch
+/// whose upstream is gone, or one without commits yet, follows the remote's
+/// default branch instead, which is what happens when the remote renamed
+/// master to main.
+fn pull_target(repo: &Repository, remote: &str, branch: &str) -> Option<String> {
+    let available = remote_branches(repo, remote);
+    let upstream = upstream_branch(repo, branch);
+
+    let wanted = upstream.as_deref().unwrap_or(branch);
+    if available.iter().any(|b| b == wanted) {
+        return Some(wanted.to_string());
+    }
+    if upstream.is_none() && repo.head().is_ok() {
+        return None;
+    }
+
+    let remote_head = repo
+        .find_reference(&format!("refs/remotes/{}/HEAD", remote))
+        .ok()
+        .and_then(|r| r.symbolic_target().map(str::to_string))
+        .map(|t| {
+            t.trim_start_matches(&format!("refs/remotes/{}/", remote))
+                .to_string()
+        });
+    let candidates = remote_head
+        .into_iter()
+        .chain(["main".to_string(), "master".to_string()]);
+    for candidate in candidates {
+        if available.contains(&candidate) {
+            return Some(candidate);
+        }
+    }
+    match available.as_slice() {
+        [only] => Some(only.clone()),
+        _ => None,
+    }
 }
 
-fn has_remote(repo_path: &str) -> Result<bool> {
+/// Runs git in the repository. Quiet runs capture stdout and stderr and
+/// return them; otherwise git writes straight to the terminal.
+fn run_git(repo_path: &str, args: &[&str], quiet: bool) -> Result<(String, String)> {
     use std::process::Command;
 
-    let output = Command::new("git")
-        .args(["-C", repo_path, "remote"])
-        .output()?;
+    let mut cmd = Command::new("git");
+    cmd.args(["-C", repo_path]).args(args);
 
-    if !output.status.success() {
-        return Ok(false);
+    if quiet {
+        let output = cmd.output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(crate::RstaskError::Other(format!(
+                "git {} failed: {}",
+                args[0],
+                stderr.trim()
+            )));
+        }
+        Ok((
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ))
+    } else {
+        if !cmd.status()?.success() {
+            return Err(crate::RstaskError::Other(format!("git {} failed", args[0])));
+        }
+        Ok((String::new(), String::new()))
     }
-
-    let remotes = String::from_utf8_lossy(&output.stdout);
-    Ok(!remotes.trim().is_empty())
 }
 
 pub fn git_pull(repo_path: &str, quiet: bool) -> Result<String> {
-    use std::process::{Command, Stdio};
-
-    // Check if a remote is configured
-    if !has_remote(repo_path)? {
-        return Err(crate::RstaskError::Other(
-            "No remote configured. Add a remote with: rstask git remote add origin <url>"
-                .to_string(),
-        ));
-    }
+    let repo = Repository::open(repo_path)?;
+    let branch = current_branch(&repo)?;
+    let remote = require_sync_remote(&repo, &branch)?;
 
-    // Get current branch name
-    let branch = get_current_branch(repo_path)?;
+    run_git(repo_path, &["fetch", "--prune", &remote], quiet).map_err(|e| {
+        crate::RstaskError::Other(format!(
+            "{}. Make sure the remote is set up correctly with: rstask git remote add {} <url>",
+            e, remote
+        ))
+    })?;
 
-    // Check if upstream is set
-    let has_upstream = has_upstream_branch(repo_path, &branch)?;
+    // Nothing to pull from an empty remote, or from one that doesn't have
+    // this branch yet; pushing creates it
+    let Some(target) = pull_target(&repo, &remote, &branch) else {
+        return Ok("nothing to pull".to_string());
+    };
+    let previous = upstream_branch(&repo, &branch);
+    if previous.as_deref() != Some(target.as_str()) {
+        if let Some(previous) = &previous
+            && !quiet
+        {
+            eprintln!(
+                "'{}/{}' no longer exists; following '{}/{}' instead",
+                remote, previous, remote, target
+            );
+        }
+        set_upstream(&repo, &branch, &remote, &target)?;
+    }
 
-    let mut cmd = if has_upstream {
-        let mut c = Command::new("git");
-        c.args([
-            "-C",
-            repo_path,
-            "pull",
-            "--ff",
-            "--no-rebase",
-            "--no-edit",
-            "--commit",
-            "--allow-unrelated-histories",
-        ]);
-        c
-    } else {
-        let mut c = Command::new("git");
-        c.args([
-            "-C",
-            repo_path,
-            "pull",
-            "--set-upstream",
-            "origin",
-            &branch,
+    let tracking = format!("refs/remotes/{}/{}", remote, target);
+    let (stdout, _) = run_git(
+        repo_path,
+        &[
+            // A status directory emptied by moving its only task elsewhere
+            // must not look like a renamed directory to the merge
+            "-c",
+            "merge.directoryRenames=false",
+            "merge",
             "--ff",
-            "--no-rebase",
             "--no-edit",
             "--commit",
             "--allow-unrelated-histories",
-        ]);
-        c
-    };
+            &tracking,
+        ],
+        quiet,
+    )?;
 
-    if quiet {
-        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    if !quiet {
+        return Ok("pulled".to_string());
     }
-
-    if quiet {
-        let output = cmd.output()?;
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(crate::RstaskError::Other(format!(
-                "git pull failed: {}",
-                stderr.trim()
-            )));
-        }
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let summary = if stdout.trim() == "Already up to date."
-            || stdout.trim() == "Already up-to-date."
-        {
+    let summary =
+        if stdout.trim() == "Already up to date." || stdout.trim() == "Already up-to-date." {
             "up to date".to_string()
         } else {
             let file_count = stdout.lines().filter(|l| l.contains('|')).count();
@@ -285,73 +545,165 @@ pub fn git_pull(repo_path: &str, quiet: bool) -> Result<String> {
                 "pulled".to_string()
             }
         };
-        Ok(summary)
-    } else {
-        let status = cmd.status()?;
-        if !status.success() {
-            return Err(crate::RstaskError::Other(
-                "git pull failed. Make sure the remote is set up correctly with: rstask git remote add origin <url>".to_string()
-            ));
-        }
-        Ok("pulled".to_string())
-    }
+    Ok(summary)
 }
 
 pub fn git_push(repo_path: &str, quiet: bool) -> Result<String> {
-    use std::process::{Command, Stdio};
+    let repo = Repository::open(repo_path)?;
+    let branch = current_branch(&repo)?;
+    let remote = require_sync_remote(&repo, &branch)?;
 
-    // Check if a remote is configured
-    if !has_remote(repo_path)? {
-        return Err(crate::RstaskError::Other(
-            "No remote configured. Add a remote with: rstask git remote add origin <url>"
-                .to_string(),
-        ));
+    // A new repository has no commits to push until the first task is added
+    if repo.head().is_err() {
+        return Ok("nothing to push".to_string());
     }
 
-    // Get current branch name
-    let branch = get_current_branch(repo_path)?;
-
-    // Check if upstream is set
-    let has_upstream = has_upstream_branch(repo_path, &branch)?;
+    // The upstream may be named differently from the local branch after a
+    // rename on the remote, so push to it by name
+    let (_, stderr) = match upstream_branch(&repo, &branch) {
+        Some(upstream) => {
+            let refspec = format!("HEAD:refs/heads/{}", upstream);
+            run_git(repo_path, &["push", &remote, &refspec], quiet)?
+        }
+        None => run_git(repo_path, &["push", "-u", &remote, &branch], quiet)?,
+    };
 
-    let mut cmd = if has_upstream {
-        let mut c = Command::new("git");
-        c.args(["-C", repo_path, "push"]);
-        c
+    // git push output goes to stderr
+    let summary = if quiet && stderr.contains("Everything up-to-date") {
+        "already pushed".to_string()
     } else {
-        let mut c = Command::new("git");
-        c.args(["-C", repo_path, "push", "-u", "origin", &branch]);
-        c
+        "pushed".to_string()
     };
+    Ok(summary)
+}
 
-    if quiet {
-        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+/// The latest `limit` commits that touched a task's file in any status
+/// directory, newest first, as "<short hash> <date> <subject>"
+pub fn git_task_history(repo_path: &Path, uuid: &str, limit: usize) -> Result<Vec<String>> {
+    use std::process::Command;
+
+    let output = Command::new("git")
+        .args([
+            "-C",
+            &repo_path.to_string_lossy(),
+            "log",
+            &format!("--max-count={}", limit),
+            "--format=%h %ad %s",
+            "--date=short",
+            "--",
+            &format!(":(glob)*/{}.md", uuid),
+            &format!(":(glob)*/{}.yml", uuid),
+        ])
+        .output()?;
+
+    // A repository without commits has no history to show
+    if !output.status.success() {
+        return Ok(Vec::new());
     }
 
-    if quiet {
-        let output = cmd.output()?;
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(crate::RstaskError::Other(format!(
-                "git push failed: {}",
-                stderr.trim()
-            )));
-        }
-        // git push output goes to stderr
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let summary = if stderr.contains("Everything up-to-date") {
-            "already pushed".to_string()
-        } else {
-            "pushed".to_string()
-        };
-        Ok(summary)
-    } else {
-        let status = cmd.status()?;
-        if !status.success() {
-            return Err(crate::RstaskError::Other("git push failed".to_string()));
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// Commits after `since`, or all of them without it, up to HEAD, newest
+/// first, as "<short hash> <date> <subject>", at most `limit` of them
+pub fn git_log_since(repo_path: &Path, since: Option<&str>, limit: usize) -> Result<Vec<String>> {
+    use std::process::Command;
+
+    let range = match since {
+        Some(commit) => format!("{}..HEAD", commit),
+        None => "HEAD".to_string(),
+    };
+    let output = Command::new("git")
+        .args([
+            "-C",
+            &repo_path.to_string_lossy(),
+            "log",
+            &format!("--max-count={}", limit),
+            "--format=%h %ad %s",
+            "--date=format-local:%m-%d %H:%M",
+            &range,
+        ])
+        .output()?;
+
+    // A repository without commits has no history to show
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// When each task file was last committed, keyed by task UUID. Tasks
+/// without a commit yet are left out
+pub fn last_modified(repo_path: &Path) -> HashMap<String, chrono::DateTime<chrono::Utc>> {
+    use std::process::Command;
+
+    let mut modified = HashMap::new();
+    let Ok(output) = Command::new("git")
+        .args([
+            "-C",
+            &repo_path.to_string_lossy(),
+            "log",
+            "--no-renames",
+            "--format=@%ct",
+            "--name-only",
+        ])
+        .output()
+    else {
+        return modified;
+    };
+
+    // Newest first, so the first time a file shows up is its last change
+    let mut time = None;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(timestamp) = line.strip_prefix('@') {
+            time = timestamp
+                .parse()
+                .ok()
+                .and_then(|t| chrono::DateTime::from_timestamp(t, 0));
+        } else if let Some(time) = time
+            && let Some(uuid) = Path::new(line).file_stem().and_then(|s| s.to_str())
+        {
+            modified.entry(uuid.to_string()).or_insert(time);
         }
-        Ok("pushed".to_string())
     }
+
+    modified
+}
+
+/// When a task last moved into the active directory, going by the commit
+/// that added its file there. None if that isn't committed yet.
+pub fn active_since(repo_path: &Path, uuid: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    use std::process::Command;
+
+    let output = Command::new("git")
+        .args([
+            "-C",
+            &repo_path.to_string_lossy(),
+            "log",
+            "--max-count=1",
+            // A move from pending/ is an addition here, not a rename
+            "--no-renames",
+            "--diff-filter=A",
+            "--format=%ct",
+            "--",
+            &format!("{}/{}.md", crate::constants::STATUS_ACTIVE, uuid),
+            &format!("{}/{}.yml", crate::constants::STATUS_ACTIVE, uuid),
+        ])
+        .output()
+        .ok()?;
+
+    let timestamp = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    chrono::DateTime::from_timestamp(timestamp, 0)
 }
 
 pub fn git_reset(repo_path: &Path) -> Result<()> {
@@ -369,4 +721,307 @@ pub fn git_reset(repo_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// The hash of the commit HEAD points at, or None before the first commit
+pub fn head_commit(repo_path: &Path) -> Option<String> {
+    let repo = Repository::open(repo_path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    Some(commit.id().to_string())
+}
+
+/// Reverses `commit` with a new commit on top, so nothing is lost and the
+/// reversal can itself be reverted. Leaves the repository untouched if later
+/// commits changed the same tasks and the change no longer applies cleanly.
+/// Returns the hash of the new commit.
+pub fn git_revert(repo_path: &Path, commit: &str, message: &str) -> Result<String> {
+    let repo_arg = repo_path.to_string_lossy();
+    let output = std::process::Command::new("git")
+        .args(["-C", &repo_arg, "revert", "--no-commit", commit])
+        .output()?;
+    if !output.status.success() {
+        let _ = std::process::Command::new("git")
+            .args(["-C", &repo_arg, "revert", "--abort"])
+            .output();
+        return Err(crate::RstaskError::Other(format!(
+            "can't reverse {} cleanly, later changes touch the same tasks: {}",
+            &commit[..commit.len().min(7)],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    git_commit(repo_path, message, true)?;
+    head_commit(repo_path)
+        .ok_or_else(|| crate::RstaskError::Other("no commit after reverting".to_string()))
+}
+
+/// Three-way merges `ours` and `theirs` against their common `base` using
+/// `git merge-file`. Returns the merged text and whether it contains conflicts.
+pub fn git_merge_file(base: &str, ours: &str, theirs: &str) -> Result<(String, bool)> {
+    use std::process::Command;
+
+    let dir = tempfile::tempdir()?;
+    let base_path = dir.path().join("base.md");
+    let ours_path = dir.path().join("ours.md");
+    let theirs_path = dir.path().join("theirs.md");
+    std::fs::write(&base_path, base)?;
+    std::fs::write(&ours_path, ours)?;
+    std::fs::write(&theirs_path, theirs)?;
+
+    let output = Command::new("git")
+        .arg("merge-file")
+        .arg("-p")
+        .args(["-L", "edited", "-L", "original", "-L", "current"])
+        .arg(&ours_path)
+        .arg(&base_path)
+        .arg(&theirs_path)
+        .output()?;
+
+    // Exit code is the number of conflicts; negative values signal an error
+    match output.status.code() {
+        Some(code) if code >= 0 => Ok((
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            code > 0,
+        )),
+        _ => Err(crate::RstaskError::Other(format!(
+            "git merge-file failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::WriteFormat;
+
+    #[test]
+    fn test_git_merge_file_clean() {
+        let base = "a\nb\nc\n";
+        let ours = "A\nb\nc\n";
+        let theirs = "a\nb\nC\n";
+
+        let (merged, conflicts) = git_merge_file(base, ours, theirs).unwrap();
+        assert!(!conflicts);
+        assert_eq!(merged, "A\nb\nC\n");
+    }
+
+    #[test]
+    fn test_git_merge_file_conflict() {
+        let (merged, conflicts) = git_merge_file("a\n", "b\n", "c\n").unwrap();
+        assert!(conflicts);
+        assert!(merged.contains("<<<<<<< edited"));
+        assert!(merged.contains(">>>>>>> current"));
+    }
+
+    fn git(repo: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn repo_with_commit() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "--initial-branch=main"]);
+        git(dir.path(), &["commit", "--allow-empty", "-m", "first"]);
+        dir
+    }
+
+    #[test]
+    fn test_head_warning() {
+        let dir = repo_with_commit();
+        assert!(head_warning(dir.path()).is_none());
 
+        git(dir.path(), &["checkout", "-b", "experiment"]);
+        let warning = head_warning(dir.path()).unwrap();
+        assert!(warning.contains("'experiment' rather than 'main'"));
+
+        git(dir.path(), &["checkout", "--detach"]);
+        assert!(head_warning(dir.path()).unwrap().contains("detached HEAD"));
+        assert!(git_commit(dir.path(), "nope", true).is_err());
+    }
+
+    #[test]
+    fn test_revert_and_redo() {
+        let dir = repo_with_commit();
+        git(dir.path(), &["config", "user.name", "Test"]);
+        git(dir.path(), &["config", "user.email", "test@example.com"]);
+        let task = dir.path().join("task.md");
+        std::fs::write(&task, "one\n").unwrap();
+        git_commit(dir.path(), "Added task", true).unwrap();
+        let added = head_commit(dir.path()).unwrap();
+
+        let undo = git_revert(dir.path(), &added, "Undo: Added task").unwrap();
+        assert!(!task.exists());
+        git_revert(dir.path(), &undo, "Redo: Added task").unwrap();
+        assert_eq!(std::fs::read_to_string(&task).unwrap(), "one\n");
+
+        // A later change to the same lines blocks the revert, and nothing
+        // is left half-done
+        std::fs::write(&task, "two\n").unwrap();
+        git_commit(dir.path(), "Edited task", true).unwrap();
+        let before = head_commit(dir.path()).unwrap();
+        assert!(git_revert(dir.path(), &added, "Undo: Added task").is_err());
+        assert_eq!(head_commit(dir.path()).unwrap(), before);
+        assert_eq!(std::fs::read_to_string(&task).unwrap(), "two\n");
+        assert!(!dir.path().join(".git/REVERT_HEAD").exists());
+    }
+
+    #[test]
+    fn test_active_since() {
+        let dir = repo_with_commit();
+        git(dir.path(), &["config", "user.name", "Test"]);
+        git(dir.path(), &["config", "user.email", "test@example.com"]);
+        let mut task = crate::task::Task::new("Write the report".to_string());
+        task.save_to_disk(dir.path(), WriteFormat::default())
+            .unwrap();
+        git_commit(dir.path(), "Added task", true).unwrap();
+        assert_eq!(active_since(dir.path(), &task.uuid), None);
+
+        let before = chrono::Utc::now() - chrono::Duration::seconds(1);
+        task.status = crate::constants::STATUS_ACTIVE.to_string();
+        task.save_to_disk(dir.path(), WriteFormat::default())
+            .unwrap();
+        git_commit(dir.path(), "Started task", true).unwrap();
+        let since = active_since(dir.path(), &task.uuid).unwrap();
+        assert!(since >= before && since <= chrono::Utc::now());
+    }
+
+    #[test]
+    fn test_git_log_since() {
+        let dir = repo_with_commit();
+        let start = head_commit(dir.path()).unwrap();
+        assert!(
+            git_log_since(dir.path(), Some(&start), 10)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(git_log_since(dir.path(), None, 10).unwrap().len(), 1);
+
+        git(dir.path(), &["commit", "--allow-empty", "-m", "Added one"]);
+        git(dir.path(), &["commit", "--allow-empty", "-m", "Added two"]);
+        let log = git_log_since(dir.path(), Some(&start), 10).unwrap();
+        assert_eq!(log.len(), 2);
+        assert!(log[0].ends_with(" Added two"));
+        assert!(log[1].ends_with(" Added one"));
+        assert_eq!(git_log_since(dir.path(), Some(&start), 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_last_modified() {
+        let dir = repo_with_commit();
+        let commit_at = |date: &str, message: &str| {
+            git(dir.path(), &["add", "-A"]);
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(["commit", "-m", message])
+                .env("GIT_COMMITTER_DATE", date)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+
+        std::fs::create_dir(dir.path().join("pending")).unwrap();
+        std::fs::write(dir.path().join("pending/one.md"), "one\n").unwrap();
+        std::fs::write(dir.path().join("pending/two.md"), "two\n").unwrap();
+        commit_at("2026-01-01T12:00:00Z", "Added tasks");
+
+        std::fs::create_dir(dir.path().join("active")).unwrap();
+        std::fs::rename(
+            dir.path().join("pending/two.md"),
+            dir.path().join("active/two.md"),
+        )
+        .unwrap();
+        commit_at("2026-02-01T12:00:00Z", "Started two");
+
+        let modified = last_modified(dir.path());
+        assert_eq!(modified["one"].to_rfc3339(), "2026-01-01T12:00:00+00:00");
+        assert_eq!(modified["two"].to_rfc3339(), "2026-02-01T12:00:00+00:00");
+    }
+
+    #[test]
+    fn test_checkout_default_keeps_detached_commits() {
+        let dir = repo_with_commit();
+        git(dir.path(), &["checkout", "--detach"]);
+        git(dir.path(), &["commit", "--allow-empty", "-m", "orphan"]);
+
+        let message = git_checkout_default(dir.path()).unwrap();
+        assert!(message.contains("rstask-detached-"));
+
+        let repo = Repository::open(dir.path()).unwrap();
+        assert_eq!(
+            head_state(&repo).unwrap(),
+            HeadState::Branch("main".to_string())
+        );
+        let kept = repo
+            .branches(Some(git2::BranchType::Local))
+            .unwrap()
+            .filter_map(|b| b.ok())
+            .filter(|(b, _)| b.name().unwrap().unwrap().starts_with("rstask-detached-"))
+            .count();
+        assert_eq!(kept, 1);
+    }
+
+    #[test]
+    fn test_ahead_behind() {
+        let origin = repo_with_commit();
+        assert_eq!(ahead_behind(origin.path()), None);
+
+        let clone = tempfile::tempdir().unwrap();
+        git(
+            clone.path(),
+            &["clone", "--quiet", origin.path().to_str().unwrap(), "."],
+        );
+        assert_eq!(ahead_behind(clone.path()), Some((0, 0)));
+
+        git(clone.path(), &["commit", "--allow-empty", "-m", "local"]);
+        git(
+            origin.path(),
+            &["commit", "--allow-empty", "-m", "remote 1"],
+        );
+        git(
+            origin.path(),
+            &["commit", "--allow-empty", "-m", "remote 2"],
+        );
+        // Nothing is known about the remote's commits until a fetch
+        assert_eq!(ahead_behind(clone.path()), Some((1, 0)));
+        git(clone.path(), &["fetch", "--quiet"]);
+        assert_eq!(ahead_behind(clone.path()), Some((1, 2)));
+    }
+
+    #[test]
+    fn test_sync_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "--initial-branch=main"]);
+        let repo = Repository::open(dir.path()).unwrap();
+        assert_eq!(sync_remote(&repo, Some("main")), None);
+        assert_eq!(current_branch(&repo).unwrap(), "main");
+
+        git(dir.path(), &["remote", "add", "upstream", "/nowhere"]);
+        assert_eq!(
+            sync_remote(&repo, Some("main")).as_deref(),
+            Some("upstream")
+        );
+
+        git(dir.path(), &["remote", "add", "backup", "/nowhere"]);
+        assert_eq!(sync_remote(&repo, Some("main")), None);
+        assert!(require_sync_remote(&repo, "main").is_err());
+
+        git(dir.path(), &["config", "remote.pushDefault", "backup"]);
+        assert_eq!(sync_remote(&repo, Some("main")).as_deref(), Some("backup"));
+
+        // The branch's own remote wins
+        git(dir.path(), &["config", "branch.main.remote", "upstream"]);
+        assert_eq!(
+            sync_remote(&repo, Some("main")).as_deref(),
+            Some("upstream")
+        );
+        assert_eq!(sync_remote(&repo, None).as_deref(), Some("backup"));
+    }
+}
@@ -16,8 +16,9 @@ Bypass the current context with --.
         }
 
         CMD_ADD => {
-            r#"Usage: rstask add [template:<id>] [task summary] [--]
+            r#"Usage: rstask add [-i] [template:<id> [--var name=value]...] [task summary] [--]
 Example: rstask add Fix main web page 500 error +bug P1 project:website
+Example: rstask add -i
 
 Add a task, returning the git commit output which contains the task ID, used
 later to reference the task.
@@ -28,7 +29,14 @@ Add -- to ignore the current context. / can be used when adding tasks to note
 any words after.
 
 A copy of an existing task can be made by including "template:<id>". See
-"rstask help template" for more information on templates.
+"rstask help template" for more information on templates. --var fills a
+{{name}} placeholder in the template; the others are asked for.
+
+With -i, prompt for summary, project, tags, priority and due date in turn.
+Known projects and tags are completed from an unambiguous prefix.
+
+A note is printed when the task introduces a project or tag that no other task
+uses yet. With --no-new, the task is refused instead.
 
 "#
         }
@@ -55,6 +63,13 @@ To create a new task from a template use the command:
 The template task <id> remains unchanged, but a new task is created as a copy
 with any modifications made in the task summary.
 
+With schedule:<when>, such as schedule:every:monday, a task is copied from the
+template each time the schedule comes round. See "rstask help tick".
+
+The summary and notes can contain placeholders such as {{version}}. Adding a
+task from the template asks for a value for each one, unless given with
+--var. Example: rstask add template:12 --var version=2.1
+
 Github-style task lists (checklists) are recommended for templates, useful for
 performing procedures. Example:
 
@@ -107,10 +122,14 @@ Add -- to ignore the current context.
 
         CMD_NOTE | CMD_NOTES => {
             r#"Usage: rstask note <id>
-Usage: rstask note <id> <text>
-Example task 13 note problem is faulty hardware
+Usage: rstask note <id> [--clear] [text]
+Example: rstask note 13 problem is faulty hardware
+Example: rstask note 13 --clear
 
 Edit or append text to the markdown notes attached to a particular task.
+Without text the notes open in $EDITOR. Text is appended as a new line, as
+typed, without opening the editor, so scripts can add notes. --clear empties
+the notes first.
 "#
         }
 
@@ -124,6 +143,52 @@ may be added, which will be appended to the note.
 "#
         }
 
+        CMD_DELEGATE => {
+            r#"Usage: rstask <id...> delegate [person]
+Example: rstask 15 delegate alice
+Example: rstask delegate 15 16 @bob
+
+Record who a task is waiting on. Leave out the person to clear it.
+
+Delegated tasks are left out of "next" unless the filter includes +delegated;
+set "next_hides_delegated false" in the config to keep them. -delegated
+filters them out of any other command. See "show-delegated".
+"#
+        }
+
+        CMD_SHOW_DELEGATED => {
+            r#"Usage: rstask show-delegated [person] [filter] [--]
+Example: rstask show-delegated
+Example: rstask show-delegated alice +work
+
+Show open tasks that are delegated to someone, with who they are delegated
+to. Give a name to only show that person's.
+"#
+        }
+
+        CMD_SHOW_BLOCKED => {
+            r#"Usage: rstask show-blocked [filter] [--]
+Example: rstask show-blocked
+Example: rstask show-blocked +work
+
+Show open tasks that depend on another open task, with the IDs of the tasks
+they are waiting on. "next" adds the same "Blocked by" column when a task it
+lists is blocked, and "show" lists what a task is blocked by and what it
+blocks.
+"#
+        }
+
+        CMD_SHOW_STALE => {
+            r#"Usage: rstask show-stale [--days N] [filter] [--]
+Example: rstask show-stale
+Example: rstask show-stale --days 60 +work
+
+Show open tasks that haven't changed for N days (stale_days in the config,
+30 by default), oldest first. Set "stale_banner true" in the config
+for "next" to say how many there are.
+"#
+        }
+
         CMD_RESOLVE | CMD_DONE => {
             r#"Usage: rstask <id...> done [closing note]
 Example: rstask 15 done
@@ -161,6 +226,9 @@ the operation will be performed to all tasks in the current context subject to
 confirmation.
 
 Modifiable attributes: tags, project and priority.
+
+As with add, a note is printed for projects or tags that no task uses yet, and
+--no-new refuses them.
 "#
         }
 
@@ -168,6 +236,9 @@ Modifiable attributes: tags, project and priority.
             r#"Usage: rstask <id...> edit
 
 Edit a task in your text editor.
+
+If the task is changed by something else (such as a sync) while the editor is
+open, you will be asked to merge the changes, overwrite them or abort.
 "#
         }
 
@@ -185,12 +256,60 @@ is at ~/.rstask by default.
 "#
         }
 
+        CMD_AGAIN => {
+            r#"Usage: rstask again
+
+Run the last command again, e.g. to add several similar tasks or to redo a
+filtered listing after a sync. The command is repeated as it was first parsed,
+so relative dates such as due:tomorrow keep the date they had then. It is
+printed on stderr before it runs.
+
+Commands that don't work on tasks (undo, sync, git, context, help and the
+like) are not recorded.
+"#
+        }
+
+        CMD_LAST => {
+            r#"Usage: rstask last
+
+Show the command that "rstask again" would repeat.
+"#
+        }
+
+        CMD_USAGE => {
+            r#"Usage: rstask usage [reset]
+Example: rstask usage
+
+Show how often each command was run on this machine, most used first, to help
+decide which aliases or key bindings are worth having. "rstask usage reset"
+clears the counts.
+
+Nothing is counted until "usage_stats true" is set in the config. The counts
+live in the local state file and are never sent anywhere.
+"#
+        }
+
         CMD_SYNC => {
             r#"Usage: rstask sync
 
 Synchronise with the remote git server. Runs git pull then git push. If there
 are conflicts that cannot be automatically resolved, it is necessary to
 manually resolve them in  ~/.rstask or with the "task git" command.
+
+The remote is the branch's own, else remote.pushDefault, else origin, else the
+only one. If the tracked branch was renamed on the remote, sync follows the
+remote's default branch.
+"#
+        }
+
+        CMD_CALDAV_SYNC => {
+            r#"Usage: rstask caldav-sync
+
+Two-way sync of open tasks with a CalDAV task list. Set caldav_url and
+caldav_username in the config file and the password in the
+RSTASK_CALDAV_PASSWORD environment variable. Summary, notes, tags, priority,
+due date and status are kept in step; when both sides changed a task, the
+local version wins.
 "#
         }
 
@@ -199,6 +318,10 @@ manually resolve them in  ~/.rstask or with the "task git" command.
 Example: rstask git status
 
 Run the given git command inside ~/.rstask
+
+"rstask git checkout-default" switches back to the default branch after the
+repository was left on another branch or a detached HEAD. Commits that only
+exist on the detached HEAD are kept on a new rstask-detached-* branch.
 "#
         }
 
@@ -222,7 +345,26 @@ Bypass the current context with --"#
 
 Open all URLs found within the task summary and notes. If you commonly have
 dozens of tabs open to later action, convert them into tasks to open later with
-this command.
+this command. A task linked to a Jira issue opens the issue too.
+"#
+        }
+
+        CMD_JIRA => {
+            r#"Usage: rstask jira import <JQL>
+Usage: rstask jira link <id> <issue-key>
+Example: rstask jira import "assignee = currentUser() AND resolution = Unresolved"
+Example: rstask jira link 12 OPS-42
+
+import adds a task for every issue the JQL search finds, with the summary,
+description, labels, due date and priority, and links it to the issue. Issues
+already linked to a task are skipped, so the same search can be run again.
+link records the issue a task belongs to. Linked issues open with
+"rstask open".
+
+Set "jira_url" in the config and the API token in RSTASK_JIRA_TOKEN. For Jira
+Cloud also set "jira_username" to your account's email address. With
+"jira_done_transition" set, resolving a linked task applies that transition
+(or the one to that status) to the issue.
 "#
         }
 
@@ -231,7 +373,246 @@ this command.
 Example: rstask show 15
 
 Display a single task with full details. If the task has notes (markdown content),
-they will be rendered with formatting to the terminal.
+they will be rendered with formatting to the terminal. The tasks it depends on
+and those depending on it, subtask progress, links and the last few commits
+that changed it follow in their own sections.
+"#
+        }
+
+        CMD_EXPORT_ICAL => {
+            r#"Usage: rstask export-ical [--events] [filter] [--]
+Example: rstask export-ical +work > work.ics
+
+Print open tasks with a due date as an iCalendar document. Entries use the task
+UUID as their UID. With --events, all-day VEVENT entries are written instead of
+VTODO entries.
+
+Set "ical_export_path" in the config to regenerate the export inside the
+repository on every commit, ready for static hosting.
+"#
+        }
+
+        CMD_EXPORT_CSV => {
+            r#"Usage: rstask export-csv [filter] [--]
+Example: rstask export-csv project:garden > garden.csv
+
+Print open tasks as CSV, with a header row naming the columns: uuid, id,
+status, summary, project, priority, tags, due, created, resolved, estimate,
+delegatedto and notes. Tags are separated by spaces, dates are RFC 3339 and
+estimates are in minutes. Edit the file in a spreadsheet and bring the changes
+back with import-csv.
+"#
+        }
+
+        CMD_IMPORT_CSV => {
+            r#"Usage: rstask import-csv <file|->
+Example: rstask import-csv garden.csv
+
+Add and update tasks from CSV with a header row, read from stdin with "-".
+Columns are matched by name, in any order, using the names export-csv writes;
+title, name, description, labels, "due date" and "delegated to" are also
+understood. Other columns are ignored, and so is id.
+
+A row whose uuid is an existing task updates that task, and an empty cell
+clears the field. Other rows are added as new tasks and need a summary; they
+keep the uuid if one is given. Dates can also be written as due: accepts them.
+Nothing is written if any row has an error, and the import is committed.
+"#
+        }
+
+        CMD_REPORT => {
+            r#"Usage: rstask report md [--since <period>] [filter] [--]
+Example: rstask report md
+Example: rstask report md --since 2w +work > status.md
+
+Print a markdown summary for a status update: tasks resolved in the period,
+grouped by project, open tasks that are active, P0/P1 or due within a week,
+and the open tasks added in the period. The period starts a week ago unless
+--since gives a span such as 3d, 2w or 1m, or a date such as 2025-06-01. The
+filter and context apply.
+"#
+        }
+
+        CMD_TRIAGE => {
+            r#"Usage: rstask triage [--stale [--days N]]
+Example: rstask triage
+Example: rstask triage --stale --days 60
+
+Go through the tasks without tags or a project one at a time, for a quick
+inbox zero. With --stale, also go through tasks that haven't changed for N
+days (stale_days in the config, 30 by default).
+
+For each task, type changes as for "modify", such as
+"project:web +bug P1 due:fri". Known projects and tags are completed from an
+unambiguous prefix and priorities can be lowercase. Or press a single key:
+Enter skips the task, d marks it done, s starts it and q quits. Each answer is
+saved and committed straight away.
+"#
+        }
+
+        CMD_TICK => {
+            r#"Usage: rstask tick
+Example: rstask template Water plants +home schedule:every:monday
+Example: rstask template Standup notes schedule:"30 9 * * 1-5"
+
+Add a task copied from each template whose schedule came round since a task
+was last copied from it. A template gets one task however many times its
+schedule came round meanwhile. It doesn't run while dstask_compat is on, as
+saving a template in dstask's format would drop its schedule.
+
+A schedule is one of every:day, every:weekday, every:week, every:month or
+every:<day of the week>, which fall at midnight, or five cron fields (minute,
+hour, day of month, month, day of week). Set it with schedule:<when> on
+"template" or "modify", and remove it with schedule:none.
+
+The last time each template was copied is kept in the template, so once that
+is synced other machines don't copy it again. Run it from cron, or let "next"
+run it first, which it does unless tick_before_next is false in the config.
+"#
+        }
+
+        CMD_REVIEW => {
+            r#"Usage: rstask review [--days N] [filter] [--]
+Example: rstask review
+Example: rstask review --days 30 +work
+
+List what needs attention in a weekly review: overdue tasks, tasks that
+haven't changed for N days (review_stale_days in the config, 14 by default) and tasks with no tags and no project, with hints on what to do
+about each. The filter and context apply to the first two; unorganised tasks
+are always all shown. Prints JSON when stdout isn't a terminal.
+
+Pair it with due:eow or due:eom to plan the week or month ahead.
+"#
+        }
+
+        CMD_REMIND => {
+            r#"Usage: rstask remind [--daemon]
+Example: rstask remind
+
+Send a notification for each open task that is overdue or due within
+remind_horizon_minutes (default 60). Set remind_lead_days, e.g. {P0 3, P2 0},
+to remind some priorities a number of days ahead instead. A notified task is snoozed for
+remind_snooze_minutes (default 120) so it isn't repeated on every run.
+
+Without --daemon, checks once and exits, which suits cron. With --daemon, keeps
+running and checks every minute. Notifications go through the backends set in
+notification_backends.
+"#
+        }
+
+        CMD_MCP => {
+            r#"Usage: rstask mcp
+
+Run a Model Context Protocol server on stdin and stdout, so AI assistants can
+work with your tasks. It offers these tools:
+
+  list_tasks    List open tasks, optionally with an rstask filter
+  add_task      Add a task with summary, project, tags, priority, due and notes
+  resolve_task  Mark a task as done
+  annotate      Append a line to a task's notes
+
+Each change is committed like a normal command and can be reverted with
+"rstask undo". No tool removes tasks.
+"#
+        }
+
+        CMD_SELF_UPDATE => {
+            r#"Usage: rstask self-update [--check]
+
+Download the latest release for this platform from GitHub, check it against the
+SHA-256 checksum published with it, and replace the running binary. With
+--check, only report whether a newer version exists. The checksum catches a
+corrupted download; it is not a signature.
+
+Only available in builds with the "self-update" feature. Separately, set
+"update_check true" in the config to have rstask look for a new release once a
+week and mention it after a command.
+"#
+        }
+
+        CMD_HOOK => {
+            r#"Usage: rstask hook install pre-commit
+Example: rstask hook install pre-commit
+
+Install a pre-commit hook in the task repository that checks staged task files
+the way rstask loads them, and refuses the commit if any would fail to load,
+such as a hand edit with broken YAML or an unknown priority. Each problem is
+printed with its file. "git commit --no-verify" skips the check.
+
+An existing hook that rstask didn't write is left alone. "rstask hook run
+pre-commit" runs the check by hand, or from a hook of your own.
+"#
+        }
+
+        CMD_MAINTENANCE => {
+            r#"Usage: rstask maintenance
+Example: rstask maintenance
+
+Tidy the task repository:
+
+- remove task files that repeat a task already in another status directory,
+  as sync conflicts and hand edits can leave behind. The copy rstask shows is
+  kept, and the removal is committed
+- drop IDs file entries for tasks that are no longer open
+- run "git gc"
+
+Then report the number and size of task files by status, the size of the git
+directory and the tasks with the largest notes.
+"#
+        }
+
+        CMD_DOCTOR => {
+            r#"Usage: rstask doctor [--fix]
+Example: rstask doctor
+Example: rstask doctor --fix
+
+Check every file in the task repository and list the problems found, each
+with its path:
+
+- files that aren't tasks, or whose names aren't the task's UUID
+- frontmatter that doesn't parse, or an unknown status or priority
+- copies of a task in more than one status directory
+- dependencies on tasks that don't exist
+- resolved tasks with no resolved time, and open tasks with one
+- resolved times before creation or in the future, and due dates that look
+  mistyped
+
+Problems marked (fixable) are repaired by --fix, which commits the result:
+copies are removed, dependencies dropped and resolved times set or cleared.
+Exits with an error while problems remain.
+"#
+        }
+
+        CMD_MIGRATE_FORMAT => {
+            r#"Usage: rstask migrate-format md|yml
+Example: rstask migrate-format md
+
+Rewrite every task file, resolved ones included, in the given format and
+commit the result. "md" is Markdown with YAML frontmatter and the notes as the
+body, the default; "yml" is the plain YAML that dstask and older versions of
+rstask write. Both are always read.
+
+The format is also saved as "task_format" in the config, so that tasks keep
+being written that way.
+"#
+        }
+
+        CMD_MIGRATE_FROM_DSTASK => {
+            r#"Usage: rstask migrate-from-dstask [path]
+Example: rstask migrate-from-dstask
+Example: rstask migrate-from-dstask ~/notes/dstask
+
+Import the tasks in a dstask repository, ~/.dstask or $DSTASK_GIT_REPO unless
+a path is given, and commit them. Open tasks keep the IDs dstask showed for
+them, and dstask's context becomes rstask's if none is set. Tasks already in
+the rstask repository are skipped, and nothing is imported if any task file
+can't be read.
+
+Run with RSTASK_GIT_REPO pointing at the dstask repository, it converts the
+repository in place. To keep using dstask on it while moving over, set
+"dstask_compat true" in the config first, so tasks are written as dstask
+writes them. estimate:, schedule: and Jira links are refused in that mode,
+as dstask's files have no place for them.
 "#
         }
 
@@ -268,20 +649,44 @@ log               : Log a task (already resolved)
 start             : Change task status to active
 note              : Append to or edit note for a task
 stop              : Change task status to pending
+delegate          : Record who a task is waiting on
 done              : Resolve a task
 context           : Set global context for task list and new tasks (use "none" to set no context)
 modify            : Change task attributes specified on command line
 edit              : Edit task with text editor
 undo              : Undo last n commits
+again             : Repeat the last command
+last              : Show the command that again would repeat
+usage             : Show how often each command was run (opt-in, local only)
 sync              : Pull then push to git repository, automatic merge commit.
+caldav-sync       : Two-way sync of open tasks with a CalDAV task list
 open              : Open all URLs found in summary/annotations
 show              : Display a single task with rendered markdown notes
 git               : Pass a command to git in the repository. Used for push/pull.
+export-ical       : Print tasks with due dates as iCalendar
+export-csv        : Print open tasks as CSV
+import-csv        : Add and update tasks from CSV
+jira              : Import Jira issues, or link a task to one
+remind            : Notify about tasks that are overdue or due soon
+review            : List overdue, stale and unorganised tasks for a weekly review
+report            : Print a markdown status report for a period
+tick              : Add the tasks due from scheduled templates
+triage            : Sort out unorganised tasks one at a time
+mcp               : Serve tasks to AI assistants over the Model Context Protocol
+self-update       : Install the latest release of rstask
+hook              : Install a pre-commit hook that checks task files
+maintenance       : Remove duplicate task files, prune IDs and run git gc
+doctor            : Check task files for problems, and fix some of them
+migrate-format    : Rewrite every task file as Markdown or YAML
+migrate-from-dstask : Import a dstask repository
 remove            : Remove a task (use to remove tasks added by mistake)
 show-projects     : List projects with completion status
 show-tags         : List tags in use
 show-active       : Show tasks that have been started
 show-paused       : Show tasks that have been started then stopped
+show-delegated    : Show tasks delegated to someone, optionally one person
+show-blocked      : Show tasks waiting on another open task
+show-stale        : Show tasks that haven't changed for a while
 show-open         : Show all non-resolved tasks (without truncation)
 show-resolved     : Show resolved tasks
 show-templates    : Show task templates
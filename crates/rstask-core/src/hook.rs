@@ -0,0 +1,173 @@
+//! Git hooks installed into the task repository
+//!
+//! The pre-commit hook checks staged task files the same way the loader reads
+//! them, so a hand edit or another tool can't commit a file that other
+//! machines would then fail to load.
+
+use crate::constants::ALL_STATUSES;
+use crate::task::parse_task_file;
+use crate::{Result, RstaskError};
+use git2::{Delta, Index, Repository};
+use std::path::{Path, PathBuf};
+
+pub const HOOK_PRE_COMMIT: &str = "pre-commit";
+
+/// First line after the shebang of hooks that rstask wrote, so that they can
+/// be replaced while hooks written by someone else are left alone
+const HOOK_MARKER: &str = "# Installed by rstask";
+
+/// Checks a task file given its path relative to the repository root.
+/// Files outside the status directories aren't tasks and always pass.
+pub fn check_task_file(rel_path: &str, data: &str) -> Result<()> {
+    let Some((status, filename)) = rel_path.split_once('/') else {
+        return Ok(());
+    };
+    // The loader skips hidden files too
+    if !ALL_STATUSES.contains(&status) || filename.contains('/') || filename.starts_with('.') {
+        return Ok(());
+    }
+
+    parse_task_file(filename, data, status, 0)?.validate()
+}
+
+/// Returns a "path: problem" line for every staged task file that doesn't
+/// load, comparing the index against HEAD
+pub fn staged_problems(repo_path: &Path) -> Result<Vec<String>> {
+    let repo = Repository::open(repo_path)?;
+    let head_tree = match repo.head() {
+        Ok(head) => Some(head.peel_to_tree()?),
+        // Nothing committed yet, so everything in the index is new
+        Err(_) => None,
+    };
+
+    // `git commit -a` and `git commit <paths>` point hooks at a temporary index
+    let index = match std::env::var_os("GIT_INDEX_FILE") {
+        Some(path) => Index::open(&repo_path.join(path))?,
+        None => repo.index()?,
+    };
+    let diff = repo.diff_tree_to_index(head_tree.as_ref(), Some(&index), None)?;
+
+    let mut problems = Vec::new();
+    for delta in diff.deltas() {
+        if !matches!(
+            delta.status(),
+            Delta::Added | Delta::Modified | Delta::Renamed | Delta::Copied
+        ) {
+            continue;
+        }
+        let file = delta.new_file();
+        let Some(path) = file.path() else {
+            continue;
+        };
+        let rel_path = path.to_string_lossy().replace('\\', "/");
+
+        let blob = repo.find_blob(file.id())?;
+        let result = match std::str::from_utf8(blob.content()) {
+            Ok(data) => check_task_file(&rel_path, data),
+            Err(_) => Err(RstaskError::Parse("not valid UTF-8".to_string())),
+        };
+        if let Err(e) = result {
+            problems.push(format!("{}: {}", rel_path, e));
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Where git looks for the named hook, honouring `core.hooksPath`
+fn hook_path(repo: &Repository, name: &str) -> Result<PathBuf> {
+    let dir = match repo.config()?.get_path("core.hooksPath") {
+        Ok(dir) if dir.is_absolute() => dir,
+        Ok(dir) => repo.workdir().unwrap_or(repo.path()).join(dir),
+        Err(_) => repo.path().join("hooks"),
+    };
+    Ok(dir.join(name))
+}
+
+/// Writes a hook that runs `rstask hook run <name>` with this binary,
+/// replacing an earlier rstask hook but never someone else's
+pub fn install_hook(repo_path: &Path, name: &str) -> Result<PathBuf> {
+    if name != HOOK_PRE_COMMIT {
+        return Err(RstaskError::Parse(format!("unknown hook: {}", name)));
+    }
+
+    let repo = Repository::open(repo_path)?;
+    let path = hook_path(&repo, name)?;
+    if let Ok(existing) = std::fs::read_to_string(&path)
+        && !existing.contains(HOOK_MARKER)
+    {
+        return Err(RstaskError::Other(format!(
+            "{} already exists; remove it, or add \"rstask hook run {}\" to it",
+            path.display(),
+            name
+        )));
+    }
+
+    let exe = std::env::current_exe()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "rstask".to_string());
+    // Git runs hooks from the top of the work tree, which is the task repo
+    let script = format!(
+        "#!/bin/sh\n{}\nRSTASK_GIT_REPO=\"$(pwd)\" exec '{}' hook run {}\n",
+        HOOK_MARKER,
+        exe.replace('\'', r"'\''"),
+        name
+    );
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, script)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(path)
+}
+
+/// Runs the named hook's checks, printing each problem to stderr
+pub fn run_hook(repo_path: &Path, name: &str) -> Result<()> {
+    if name != HOOK_PRE_COMMIT {
+        return Err(RstaskError::Parse(format!("unknown hook: {}", name)));
+    }
+
+    let problems = staged_problems(repo_path)?;
+    if problems.is_empty() {
+        return Ok(());
+    }
+
+    for problem in &problems {
+        eprintln!("{}", problem);
+    }
+    Err(RstaskError::Other(format!(
+        "{} staged task file(s) would fail to load; fix them or commit with --no-verify",
+        problems.len()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UUID: &str = "9a8b7c6d-0000-4000-8000-000000000000";
+
+    #[test]
+    fn test_check_task_file() {
+        let good = "---\nsummary: Pay rent\npriority: P2\ncreated: 2024-01-01T00:00:00Z\n---\n";
+        assert!(check_task_file(&format!("pending/{}.md", UUID), good).is_ok());
+
+        // Broken YAML, an unknown priority and a misnamed file all fail
+        let broken = "---\nsummary: [unclosed\n---\n";
+        assert!(check_task_file(&format!("pending/{}.md", UUID), broken).is_err());
+        let bad_priority = good.replace("P2", "P9");
+        assert!(check_task_file(&format!("pending/{}.md", UUID), &bad_priority).is_err());
+        assert!(check_task_file("pending/not-a-uuid.md", good).is_err());
+
+        // Files the loader doesn't read are left alone
+        assert!(check_task_file("README.md", "anything").is_ok());
+        assert!(check_task_file("notes/todo.md", "anything").is_ok());
+        assert!(check_task_file("pending/.gitkeep", "").is_ok());
+    }
+}
@@ -0,0 +1,241 @@
+//! HTTP requests, made by shelling out to curl as git operations shell out
+//! to git
+//!
+//! CalDAV, Jira, webhooks, ntfy and the update check all go through
+//! [`Request`]. Everything but the body is handed to curl as a config file on
+//! stdin, so credentials don't show up in `ps`. Values containing a line break
+//! or other control character are refused rather than written into that file,
+//! where they would start a curl option of their own.
+
+use crate::{Result, RstaskError};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// A request to send with [`Request::send`]
+#[derive(Debug, Clone)]
+pub struct Request {
+    method: String,
+    url: String,
+    headers: Vec<String>,
+    body: Option<Vec<u8>>,
+    user: Option<String>,
+    max_time: Option<u32>,
+    follow_redirects: bool,
+}
+
+/// The final response to a request, after any redirects curl followed
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn new(method: &str, url: &str) -> Self {
+        Request {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: None,
+            user: None,
+            max_time: None,
+            follow_redirects: false,
+        }
+    }
+
+    /// Adds a header, written as `Name: value`
+    pub fn header(mut self, header: impl Into<String>) -> Self {
+        self.headers.push(header.into());
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    pub fn basic_auth(mut self, user: &str, password: &str) -> Self {
+        self.user = Some(format!("{}:{}", user, password));
+        self
+    }
+
+    /// Gives up on the whole request after this many seconds
+    pub fn max_time(mut self, seconds: u32) -> Self {
+        self.max_time = Some(seconds);
+        self
+    }
+
+    pub fn follow_redirects(mut self) -> Self {
+        self.follow_redirects = true;
+        self
+    }
+
+    /// Runs curl. Only failing to get a response at all is an error; the
+    /// caller decides what to make of the status.
+    pub fn send(&self) -> Result<Response> {
+        // The body goes in a file rather than on stdin, which holds the config
+        let body_file = tempfile::NamedTempFile::new()?;
+        let config = self.curl_config(body_file.path())?;
+        if let Some(body) = &self.body {
+            std::fs::write(body_file.path(), body)?;
+        }
+
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--include", "--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| RstaskError::Other(format!("failed to run curl: {}", e)))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(config.as_bytes())?;
+        }
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(RstaskError::Other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+
+        parse_response(&output.stdout)
+    }
+
+    fn curl_config(&self, body_path: &std::path::Path) -> Result<String> {
+        let mut config = String::new();
+        let mut option = |name: &str, value: &str, what: &str| -> Result<()> {
+            config.push_str(&format!("{} = {}\n", name, quote(value, what)?));
+            Ok(())
+        };
+
+        option("url", &self.url, "a URL")?;
+        option("request", &self.method, "a request method")?;
+        // Don't wait for a 100 Continue that some servers never send
+        option("header", "Expect:", "a header")?;
+        for header in &self.headers {
+            option("header", header, "a header")?;
+        }
+        if let Some(user) = &self.user {
+            option("user", user, "a username or password")?;
+        }
+        if self.body.is_some() {
+            let path = format!("@{}", body_path.display());
+            option("data-binary", &path, "a file name")?;
+        }
+        if let Some(seconds) = self.max_time {
+            option("max-time", &seconds.to_string(), "a timeout")?;
+        }
+        if self.follow_redirects {
+            config.push_str("location\n");
+        }
+        Ok(config)
+    }
+}
+
+impl Response {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// Quotes a value for a curl config file
+fn quote(value: &str, what: &str) -> Result<String> {
+    if value.chars().any(|c| c.is_control() && c != '\t') {
+        return Err(RstaskError::Other(format!(
+            "refusing to send {} containing a line break or other control character",
+            what
+        )));
+    }
+    Ok(format!(
+        "\"{}\"",
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    ))
+}
+
+/// Splits curl's `--include` output into the final response's status,
+/// headers and body
+fn parse_response(raw: &[u8]) -> Result<Response> {
+    let mut rest = raw;
+    loop {
+        let (head, body) = split_head(rest);
+        let head = String::from_utf8_lossy(head);
+        let mut lines = head.lines();
+        let status: u16 = lines
+            .next()
+            .and_then(|l| l.split_whitespace().nth(1))
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| RstaskError::Other("malformed HTTP response".to_string()))?;
+
+        // Interim responses and followed redirects come before the final one
+        if (status < 200 || (300..400).contains(&status)) && body.starts_with(b"HTTP/") {
+            rest = body;
+            continue;
+        }
+
+        let headers = lines
+            .filter_map(|l| l.split_once(':'))
+            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+            .collect();
+        return Ok(Response {
+            status,
+            headers,
+            body: body.to_vec(),
+        });
+    }
+}
+
+fn split_head(raw: &[u8]) -> (&[u8], &[u8]) {
+    for separator in [&b"\r\n\r\n"[..], &b"\n\n"[..]] {
+        if let Some(at) = raw.windows(separator.len()).position(|w| w == separator) {
+            return (&raw[..at], &raw[at + separator.len()..]);
+        }
+    }
+    (raw, &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let raw = b"HTTP/1.1 201 Created\r\nETag: \"7\"\r\nContent-Length: 0\r\n\r\n";
+        let response = parse_response(raw).unwrap();
+        assert_eq!(response.status, 201);
+        assert_eq!(response.header("etag"), Some("\"7\""));
+    }
+
+    #[test]
+    fn test_parse_response_after_redirect() {
+        let raw = b"HTTP/1.1 302 Found\r\nLocation: /b\r\n\r\nHTTP/1.1 200 OK\r\n\r\nhello";
+        let response = parse_response(raw).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.text(), "hello");
+    }
+
+    #[test]
+    fn test_control_characters_are_refused() {
+        let request = Request::new("GET", "https://example.com/")
+            .basic_auth("me", "secret\"\nurl = \"https://attacker.example/");
+        assert!(request.curl_config(std::path::Path::new("/body")).is_err());
+
+        let config = Request::new("GET", "https://example.com/")
+            .basic_auth("me", "back\\slash \"quoted\"")
+            .curl_config(std::path::Path::new("/body"))
+            .unwrap();
+        assert!(config.contains(r#"user = "me:back\\slash \"quoted\"""#));
+    }
+}
@@ -0,0 +1,522 @@
+use crate::constants::*;
+use crate::preferences::IcalComponent;
+use crate::task::Task;
+use crate::taskset::{LoadOptions, TaskSet};
+use crate::{Result, RstaskError};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::path::{Component, Path};
+
+/// Builds an iCalendar document with one entry per task that has a due date
+///
+/// Each entry uses the task UUID as its UID so calendar clients update
+/// existing entries rather than duplicating them on refresh.
+pub fn tasks_to_ical(tasks: &[&Task], component: IcalComponent) -> String {
+    let mut lines = calendar_header();
+
+    for task in tasks.iter().filter(|t| t.due.is_some()) {
+        push_entry(&mut lines, task, component);
+    }
+
+    finish_calendar(lines)
+}
+
+/// Builds a calendar holding a single VTODO for the task, as stored on CalDAV servers
+pub fn task_to_vtodo_calendar(task: &Task) -> String {
+    let mut lines = calendar_header();
+    push_entry(&mut lines, task, IcalComponent::Todo);
+    finish_calendar(lines)
+}
+
+fn calendar_header() -> Vec<String> {
+    vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//rstask//rstask {}//EN", env!("CARGO_PKG_VERSION")),
+        "CALSCALE:GREGORIAN".to_string(),
+    ]
+}
+
+fn finish_calendar(mut lines: Vec<String>) -> String {
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&fold_line(&line));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Appends the entry for one task. Events need a due date to anchor them.
+fn push_entry(lines: &mut Vec<String>, task: &Task, component: IcalComponent) {
+    let due_date = task
+        .due
+        .map(|due| due.with_timezone(&Local).format("%Y%m%d").to_string());
+
+    let kind = match component {
+        IcalComponent::Todo => "VTODO",
+        IcalComponent::Event => "VEVENT",
+    };
+
+    lines.push(format!("BEGIN:{}", kind));
+    lines.push(format!("UID:{}", task.uuid));
+    // Derived from the task rather than the clock so regenerating an
+    // unchanged task set produces an identical file
+    let created = format_timestamp(task.created);
+    lines.push(format!("DTSTAMP:{}", created));
+    lines.push(format!("CREATED:{}", created));
+    lines.push(format!("SUMMARY:{}", escape_text(&task.summary)));
+
+    match component {
+        IcalComponent::Todo => {
+            if let Some(due_date) = &due_date {
+                lines.push(format!("DUE;VALUE=DATE:{}", due_date));
+            }
+            lines.push(format!("STATUS:{}", todo_status(task)));
+            if let Some(resolved) = task.resolved
+                && task.status == STATUS_RESOLVED
+            {
+                lines.push(format!("COMPLETED:{}", format_timestamp(resolved)));
+            }
+        }
+        IcalComponent::Event => {
+            if let Some(due_date) = &due_date {
+                lines.push(format!("DTSTART;VALUE=DATE:{}", due_date));
+            }
+            lines.push("TRANSP:TRANSPARENT".to_string());
+        }
+    }
+
+    if let Some(priority) = ical_priority(&task.priority) {
+        lines.push(format!("PRIORITY:{}", priority));
+    }
+    if !task.tags.is_empty() {
+        let tags: Vec<String> = task.tags.iter().map(|t| escape_text(t)).collect();
+        lines.push(format!("CATEGORIES:{}", tags.join(",")));
+    }
+    if !task.notes.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape_text(&task.notes)));
+    }
+
+    lines.push(format!("END:{}", kind));
+}
+
+/// The parts of a VTODO that map onto task fields
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RemoteTodo {
+    pub uid: String,
+    pub summary: String,
+    pub description: String,
+    pub status: String,
+    pub priority: Option<u8>,
+    pub due: Option<DateTime<Utc>>,
+    pub categories: Vec<String>,
+}
+
+/// Extracts the VTODO components from an iCalendar document
+pub fn parse_vtodos(ics: &str) -> Vec<RemoteTodo> {
+    let mut todos = Vec::new();
+    let mut current: Option<RemoteTodo> = None;
+    // Nested components such as VALARM have their own properties
+    let mut nested = 0;
+
+    for line in unfold_lines(ics) {
+        let Some((name_params, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, params) = match name_params.split_once(';') {
+            Some((name, params)) => (name, params),
+            None => (name_params, ""),
+        };
+        let name = name.to_ascii_uppercase();
+
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VTODO") => {
+                current = Some(RemoteTodo::default());
+            }
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VTODO") => {
+                todos.extend(current.take());
+            }
+            (_, Some(_)) if nested > 0 => {}
+            ("UID", Some(todo)) => todo.uid = value.trim().to_string(),
+            ("SUMMARY", Some(todo)) => todo.summary = unescape_text(value),
+            ("DESCRIPTION", Some(todo)) => todo.description = unescape_text(value),
+            ("STATUS", Some(todo)) => todo.status = value.trim().to_ascii_uppercase(),
+            ("PRIORITY", Some(todo)) => todo.priority = value.trim().parse().ok(),
+            ("DUE", Some(todo)) => todo.due = parse_ical_date(value.trim(), params),
+            ("CATEGORIES", Some(todo)) => {
+                todo.categories.extend(
+                    split_unescaped_commas(value)
+                        .iter()
+                        .map(|c| unescape_text(c))
+                        .filter(|c| !c.is_empty()),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    todos
+}
+
+/// Maps an iCalendar priority (1 highest to 9 lowest, 0 undefined) onto P0-P3
+pub fn priority_from_ical(priority: Option<u8>) -> &'static str {
+    match priority {
+        Some(1) => PRIORITY_CRITICAL,
+        Some(2..=4) => PRIORITY_HIGH,
+        Some(6..=9) => PRIORITY_LOW,
+        _ => PRIORITY_NORMAL,
+    }
+}
+
+fn unfold_lines(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in ics.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        if let Some(rest) = raw.strip_prefix([' ', '\t'])
+            && let Some(last) = lines.last_mut()
+        {
+            last.push_str(rest);
+        } else if !raw.is_empty() {
+            lines.push(raw.to_string());
+        }
+    }
+    lines
+}
+
+/// Parses DATE and DATE-TIME values. Floating and TZID times are read as local time.
+fn parse_ical_date(value: &str, params: &str) -> Option<DateTime<Utc>> {
+    let is_date = params.to_ascii_uppercase().contains("VALUE=DATE")
+        && !params.to_ascii_uppercase().contains("VALUE=DATE-TIME");
+
+    if is_date || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+            .single()
+            .map(|dt| dt.with_timezone(&Utc));
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&naive));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .single()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn split_unescaped_commas(value: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut escaped = false;
+    for c in value.chars() {
+        match c {
+            ',' if !escaped => parts.push(String::new()),
+            _ => {
+                escaped = c == '\\' && !escaped;
+                parts.last_mut().unwrap().push(c);
+            }
+        }
+    }
+    parts
+}
+
+fn unescape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Regenerates the calendar file kept inside the repository, if configured
+///
+/// Called by `commands::commit_changes` before each commit so the file is
+/// always in step with the tasks and can be served straight from a static host.
+/// Without `include_notes` the notes are left out, as the file is meant to be
+/// published. `export_path` must stay inside the repository.
+pub fn write_repo_export(
+    repo_path: &Path,
+    ids_file: &Path,
+    export_path: &str,
+    component: IcalComponent,
+    include_notes: bool,
+) -> Result<()> {
+    let relative = Path::new(export_path);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(RstaskError::Other(format!(
+            "ical_export_path must be a relative path inside the repository, not {}",
+            export_path
+        )));
+    }
+
+    let options = LoadOptions::new(false).skip_notes(!include_notes);
+    let ts = TaskSet::load_with(repo_path, ids_file, &options)?;
+    let tasks: Vec<Task> = ts
+        .tasks()
+        .into_iter()
+        .filter(|t| t.status != STATUS_TEMPLATE)
+        .map(|t| {
+            let mut task = t.clone();
+            if !include_notes {
+                task.notes.clear();
+            }
+            task
+        })
+        .collect();
+    let tasks: Vec<&Task> = tasks.iter().collect();
+
+    let target = repo_path.join(export_path);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(target, tasks_to_ical(&tasks, component))?;
+    Ok(())
+}
+
+fn todo_status(task: &Task) -> &'static str {
+    match task.status.as_str() {
+        STATUS_RESOLVED => "COMPLETED",
+        STATUS_ACTIVE => "IN-PROCESS",
+        _ => "NEEDS-ACTION",
+    }
+}
+
+/// Maps P0-P3 onto the 1 (highest) to 9 (lowest) iCalendar scale
+fn ical_priority(priority: &str) -> Option<u8> {
+    match priority {
+        PRIORITY_CRITICAL => Some(1),
+        PRIORITY_HIGH => Some(3),
+        PRIORITY_NORMAL => Some(5),
+        PRIORITY_LOW => Some(9),
+        _ => None,
+    }
+}
+
+fn format_timestamp(dt: DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a TEXT value as described in RFC 5545 section 3.3.11
+fn escape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Folds a content line so no physical line exceeds 75 octets
+fn fold_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut len = 0;
+
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::WriteFormat;
+
+    fn task_with_due() -> Task {
+        Task {
+            uuid: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            status: STATUS_PENDING.to_string(),
+            summary: "Pay rent, water; gas".to_string(),
+            tags: vec!["home".to_string()],
+            priority: PRIORITY_HIGH.to_string(),
+            due: Some(Utc::now()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tasks_to_ical_todo() {
+        let task = task_with_due();
+        let no_due = Task {
+            summary: "Someday".to_string(),
+            ..Default::default()
+        };
+
+        let ical = tasks_to_ical(&[&task, &no_due], IcalComponent::Todo);
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ical.matches("BEGIN:VTODO").count(), 1);
+        assert!(ical.contains("UID:550e8400-e29b-41d4-a716-446655440000\r\n"));
+        assert!(ical.contains("SUMMARY:Pay rent\\, water\\; gas\r\n"));
+        assert!(ical.contains("STATUS:NEEDS-ACTION\r\n"));
+        assert!(ical.contains("PRIORITY:3\r\n"));
+        assert!(ical.contains("CATEGORIES:home\r\n"));
+        assert!(!ical.contains("Someday"));
+    }
+
+    #[test]
+    fn test_tasks_to_ical_event() {
+        let task = task_with_due();
+        let ical = tasks_to_ical(&[&task], IcalComponent::Event);
+        assert!(ical.contains("BEGIN:VEVENT\r\n"));
+        assert!(ical.contains("DTSTART;VALUE=DATE:"));
+        assert!(!ical.contains("VTODO"));
+    }
+
+    #[test]
+    fn test_parse_vtodos() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+            VERSION:2.0\r\n\
+            BEGIN:VTODO\r\n\
+            UID:550E8400-E29B-41D4-A716-446655440000\r\n\
+            SUMMARY:Pay rent\\, water\r\n\
+            DESCRIPTION:first line\\nsecond \r\n line\r\n\
+            STATUS:IN-PROCESS\r\n\
+            PRIORITY:1\r\n\
+            DUE;VALUE=DATE:20261019\r\n\
+            CATEGORIES:home,bills\r\n\
+            BEGIN:VALARM\r\n\
+            DESCRIPTION:Reminder\r\n\
+            END:VALARM\r\n\
+            END:VTODO\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:event\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let todos = parse_vtodos(ics);
+        assert_eq!(todos.len(), 1);
+        let todo = &todos[0];
+        assert_eq!(todo.uid, "550E8400-E29B-41D4-A716-446655440000");
+        assert_eq!(todo.summary, "Pay rent, water");
+        assert_eq!(todo.description, "first line\nsecond line");
+        assert_eq!(todo.status, "IN-PROCESS");
+        assert_eq!(priority_from_ical(todo.priority), PRIORITY_CRITICAL);
+        assert_eq!(todo.categories, vec!["home", "bills"]);
+        assert_eq!(
+            todo.due
+                .unwrap()
+                .with_timezone(&Local)
+                .format("%Y-%m-%d")
+                .to_string(),
+            "2026-10-19"
+        );
+    }
+
+    #[test]
+    fn test_vtodo_round_trip() {
+        let mut task = task_with_due();
+        task.notes = "line one\nline two, with comma".to_string();
+
+        let todos = parse_vtodos(&task_to_vtodo_calendar(&task));
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0].uid, task.uuid);
+        assert_eq!(todos[0].summary, task.summary);
+        assert_eq!(todos[0].description, task.notes);
+        assert_eq!(priority_from_ical(todos[0].priority), task.priority);
+        assert_eq!(todos[0].categories, task.tags);
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line = format!("DESCRIPTION:{}", "x".repeat(100));
+        let folded = fold_line(&line);
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].len(), 75);
+        assert!(parts[1].starts_with(' '));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+
+    #[test]
+    fn test_repo_export_without_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut task = task_with_due();
+        task.notes = "door code 1234".to_string();
+        task.save_to_disk(dir.path(), WriteFormat::default())
+            .unwrap();
+        let ids_file = dir.path().join("ids.bin");
+
+        write_repo_export(
+            dir.path(),
+            &ids_file,
+            "tasks.ics",
+            IcalComponent::Todo,
+            false,
+        )
+        .unwrap();
+        let ical = std::fs::read_to_string(dir.path().join("tasks.ics")).unwrap();
+        assert!(ical.contains("SUMMARY:"));
+        assert!(!ical.contains("door code"));
+
+        write_repo_export(
+            dir.path(),
+            &ids_file,
+            "tasks.ics",
+            IcalComponent::Todo,
+            true,
+        )
+        .unwrap();
+        let ical = std::fs::read_to_string(dir.path().join("tasks.ics")).unwrap();
+        assert!(ical.contains("DESCRIPTION:door code 1234"));
+    }
+
+    #[test]
+    fn test_repo_export_stays_inside_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        let ids_file = repo.join("ids.bin");
+        let outside = dir.path().join("outside.ics");
+
+        for path in [
+            outside.to_str().unwrap(),
+            "../outside.ics",
+            "public/../../outside.ics",
+        ] {
+            let result = write_repo_export(&repo, &ids_file, path, IcalComponent::Todo, false);
+            assert!(result.is_err(), "{} was accepted", path);
+        }
+        assert!(!outside.exists());
+
+        write_repo_export(
+            &repo,
+            &ids_file,
+            "./public/tasks.ics",
+            IcalComponent::Todo,
+            false,
+        )
+        .unwrap();
+        assert!(repo.join("public").join("tasks.ics").exists());
+    }
+}
@@ -0,0 +1,369 @@
+//! Jira issues as tasks: importing the results of a JQL search, linking
+//! tasks to issues, and moving linked issues on when their tasks are resolved
+//!
+//! Requests shell out to curl, as for CalDAV. The API token is read from
+//! `RSTASK_JIRA_TOKEN`. With `jira_username` set it is sent with basic auth,
+//! as Jira Cloud expects; otherwise it is sent as a bearer token, which is how
+//! personal access tokens work on Jira Server and Data Center.
+
+use crate::constants::*;
+use crate::date_util::local_midnight;
+use crate::events::{Subscriber, TaskEvent};
+use crate::http::Request;
+use crate::preferences::Preferences;
+use crate::task::Task;
+use crate::{Result, RstaskError};
+use chrono::{NaiveDate, Utc};
+use serde::Deserialize;
+
+pub const TOKEN_VAR: &str = "RSTASK_JIRA_TOKEN";
+
+/// Issues asked for per search request
+const PAGE_SIZE: usize = 100;
+
+/// Fields read from each issue
+const FIELDS: &str = "summary,description,priority,duedate,labels";
+
+#[derive(Debug, Deserialize)]
+pub struct Issue {
+    pub key: String,
+    #[serde(default)]
+    pub fields: IssueFields,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct IssueFields {
+    #[serde(default)]
+    pub summary: String,
+    /// Plain text in version 2 of the API
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub priority: Option<Named>,
+    #[serde(default)]
+    pub duedate: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Named {
+    #[serde(default)]
+    pub name: String,
+}
+
+/// One page of search results. Jira Cloud pages with a token, Server and
+/// Data Center with an offset
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchPage {
+    #[serde(default)]
+    issues: Vec<Issue>,
+    next_page_token: Option<String>,
+    is_last: Option<bool>,
+    total: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Transitions {
+    #[serde(default)]
+    transitions: Vec<Transition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Transition {
+    id: String,
+    name: String,
+    #[serde(default)]
+    to: Named,
+}
+
+/// Whether `key` looks like an issue key, such as PROJ-123
+pub fn is_issue_key(key: &str) -> bool {
+    let Some((project, number)) = key.rsplit_once('-') else {
+        return false;
+    };
+    project.starts_with(|c: char| c.is_ascii_uppercase())
+        && project
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The page for an issue in the Jira web interface
+pub fn issue_url(base_url: &str, key: &str) -> String {
+    format!("{}/browse/{}", base_url.trim_end_matches('/'), key)
+}
+
+/// A new task for an issue, linked to it
+pub fn issue_to_task(issue: &Issue) -> Task {
+    let fields = &issue.fields;
+    let mut task = Task::new(fields.summary.trim().to_string());
+    task.jira = issue.key.clone();
+    task.notes = fields
+        .description
+        .as_deref()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    task.tags = fields.labels.iter().map(|l| l.to_lowercase()).collect();
+    task.due = fields
+        .duedate
+        .as_deref()
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .map(|d| local_midnight(d).with_timezone(&Utc));
+    task.priority = match fields.priority.as_ref().map(|p| p.name.to_lowercase()) {
+        Some(p) if matches!(p.as_str(), "highest" | "blocker" | "critical") => PRIORITY_CRITICAL,
+        Some(p) if matches!(p.as_str(), "high" | "major") => PRIORITY_HIGH,
+        Some(p) if matches!(p.as_str(), "low" | "lowest" | "minor" | "trivial") => PRIORITY_LOW,
+        _ => PRIORITY_NORMAL,
+    }
+    .to_string();
+    task
+}
+
+/// Jira REST API client that makes its requests through curl
+pub struct JiraClient {
+    base_url: String,
+    username: Option<String>,
+    token: Option<String>,
+}
+
+impl JiraClient {
+    pub fn new(base_url: &str, username: Option<String>, token: Option<String>) -> Self {
+        JiraClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            username,
+            token,
+        }
+    }
+
+    /// A client for `jira_url`, with the token from the environment
+    pub fn from_preferences(prefs: &Preferences) -> Result<Self> {
+        let url = prefs.jira_url.as_deref().ok_or_else(|| {
+            RstaskError::Other("set jira_url in the config to use Jira".to_string())
+        })?;
+        Ok(Self::new(
+            url,
+            prefs.jira_username.clone(),
+            std::env::var(TOKEN_VAR).ok(),
+        ))
+    }
+
+    /// Every issue matching the JQL
+    pub fn search(&self, jql: &str) -> Result<Vec<Issue>> {
+        let mut issues = Vec::new();
+        let mut next_page_token: Option<String> = None;
+
+        // Jira Cloud only has the newer search/jql endpoint, and Server and
+        // Data Center only the older one
+        let mut legacy = false;
+        loop {
+            let mut query = format!(
+                "jql={}&fields={}&maxResults={}",
+                encode(jql),
+                FIELDS,
+                PAGE_SIZE
+            );
+            if legacy {
+                query.push_str(&format!("&startAt={}", issues.len()));
+            } else if let Some(token) = &next_page_token {
+                query.push_str(&format!("&nextPageToken={}", encode(token)));
+            }
+            let endpoint = if legacy { "search" } else { "search/jql" };
+            let (status, body) = self.request("GET", &format!("{}?{}", endpoint, query), None)?;
+
+            if status == 404 && !legacy && issues.is_empty() {
+                legacy = true;
+                continue;
+            }
+            if status != 200 {
+                return Err(api_error(status, &body, "searching"));
+            }
+
+            let page: SearchPage = serde_json::from_str(&body)?;
+            let got = page.issues.len();
+            issues.extend(page.issues);
+            let done = if legacy {
+                got == 0 || page.total.is_none_or(|total| issues.len() >= total)
+            } else {
+                next_page_token = page.next_page_token;
+                page.is_last.unwrap_or(true) || next_page_token.is_none()
+            };
+            if done {
+                return Ok(issues);
+            }
+        }
+    }
+
+    /// Applies the transition called `name`, or the one leading to a status
+    /// called `name`
+    pub fn transition(&self, key: &str, name: &str) -> Result<()> {
+        let path = format!("issue/{}/transitions", key);
+        let (status, body) = self.request("GET", &path, None)?;
+        if status != 200 {
+            return Err(api_error(status, &body, key));
+        }
+
+        let transitions: Transitions = serde_json::from_str(&body)?;
+        let transition = transitions
+            .transitions
+            .iter()
+            .find(|t| t.name.eq_ignore_ascii_case(name) || t.to.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                RstaskError::Other(format!(
+                    "{} can't be moved to {} from its current status",
+                    key, name
+                ))
+            })?;
+
+        let body = format!(r#"{{"transition":{{"id":"{}"}}}}"#, transition.id);
+        let (status, response) = self.request("POST", &path, Some(&body))?;
+        match status {
+            200..=299 => Ok(()),
+            _ => Err(api_error(status, &response, key)),
+        }
+    }
+
+    /// Sends a request to the REST API, returning the status and body
+    fn request(&self, method: &str, path: &str, body: Option<&str>) -> Result<(u16, String)> {
+        let url = format!("{}/rest/api/2/{}", self.base_url, path);
+        let mut request = Request::new(method, &url).header("Accept: application/json");
+        if let Some(body) = body {
+            request = request.header("Content-Type: application/json").body(body);
+        }
+        request = match (&self.username, &self.token) {
+            (Some(user), token) => request.basic_auth(user, token.as_deref().unwrap_or("")),
+            (None, Some(token)) => request.header(format!("Authorization: Bearer {}", token)),
+            (None, None) => request,
+        };
+
+        let response = request
+            .send()
+            .map_err(|e| RstaskError::Other(format!("Jira request failed: {}", e)))?;
+        Ok((response.status, response.text()))
+    }
+}
+
+/// The error for a failed request, with Jira's own messages when it sent some
+fn api_error(status: u16, body: &str, doing: &str) -> RstaskError {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Errors {
+        #[serde(default)]
+        error_messages: Vec<String>,
+    }
+
+    let hint = match status {
+        401 | 403 => format!(", check {} and jira_username", TOKEN_VAR),
+        _ => String::new(),
+    };
+    let messages = serde_json::from_str::<Errors>(body)
+        .map(|e| e.error_messages.join("; "))
+        .unwrap_or_default();
+    if messages.is_empty() {
+        RstaskError::Other(format!("Jira returned {} for {}{}", status, doing, hint))
+    } else {
+        RstaskError::Other(format!(
+            "Jira returned {} for {}: {}{}",
+            status, doing, messages, hint
+        ))
+    }
+}
+
+/// Percent-encodes a query string value
+fn encode(value: &str) -> String {
+    let mut out = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Event subscriber that moves linked issues on with `jira_done_transition`
+/// when their tasks are resolved
+///
+/// Failures are reported on stderr, since the task is already resolved.
+pub struct JiraSubscriber {
+    client: JiraClient,
+    transition: String,
+}
+
+impl JiraSubscriber {
+    pub fn new(client: JiraClient, transition: String) -> Self {
+        JiraSubscriber { client, transition }
+    }
+}
+
+impl Subscriber for JiraSubscriber {
+    fn on_event(&self, event: &TaskEvent) {
+        let TaskEvent::TaskResolved(task) = event else {
+            return;
+        };
+        if task.jira.is_empty() {
+            return;
+        }
+
+        match self.client.transition(&task.jira, &self.transition) {
+            Ok(()) => println!("Moved {} to {}", task.jira, self.transition),
+            Err(e) => eprintln!("warning: couldn't move {} on Jira: {}", task.jira, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_issue_key() {
+        assert!(is_issue_key("PROJ-123"));
+        assert!(is_issue_key("AB2_X-1"));
+        assert!(!is_issue_key("proj-123"));
+        assert!(!is_issue_key("PROJ-"));
+        assert!(!is_issue_key("PROJ123"));
+        assert!(!is_issue_key("2PROJ-1"));
+    }
+
+    #[test]
+    fn test_issue_to_task() {
+        let issue: Issue = serde_json::from_str(
+            r#"{"key": "OPS-42", "fields": {
+                "summary": "Rotate TLS certificates ",
+                "description": "Before they expire\n",
+                "priority": {"id": "2", "name": "High"},
+                "duedate": "2030-06-01",
+                "labels": ["Security"]
+            }}"#,
+        )
+        .unwrap();
+
+        let task = issue_to_task(&issue);
+        assert_eq!(task.summary, "Rotate TLS certificates");
+        assert_eq!(task.jira, "OPS-42");
+        assert_eq!(task.notes, "Before they expire");
+        assert_eq!(task.priority, PRIORITY_HIGH);
+        assert_eq!(task.tags, vec!["security"]);
+        assert!(task.due.is_some());
+
+        let bare: Issue = serde_json::from_str(
+            r#"{"key": "OPS-43", "fields": {"summary": "x", "priority": null}}"#,
+        )
+        .unwrap();
+        assert_eq!(issue_to_task(&bare).priority, PRIORITY_NORMAL);
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(
+            encode("project = OPS AND assignee = currentUser()"),
+            "project%20%3D%20OPS%20AND%20assignee%20%3D%20currentUser%28%29"
+        );
+    }
+}
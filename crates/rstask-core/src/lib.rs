@@ -1,23 +1,52 @@
+pub mod caldav;
+pub mod column;
 pub mod commands;
 pub mod config;
 pub mod constants;
+pub mod csv;
 pub mod date_util;
 pub mod display;
+pub mod doctor;
+pub mod dstask;
+pub mod encryption;
 pub mod error;
+pub mod events;
 pub mod frontmatter;
 pub mod git;
 pub mod help;
+pub mod hook;
+pub mod http;
+pub mod ical;
+pub mod jira;
 pub mod local_state;
+pub mod lock;
+pub mod maintenance;
+pub mod mcp;
+#[cfg(feature = "natural-dates")]
+pub mod natural_date;
+pub mod notify;
 pub mod preferences;
 pub mod query;
+pub mod report;
+pub mod schedule;
 pub mod table;
 pub mod task;
 pub mod taskset;
+pub mod template;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod update;
 pub mod util;
+pub mod webhook;
 
+pub use column::Column;
 pub use config::Config;
 pub use error::{Result, RstaskError};
-pub use preferences::{BulkCommitStrategy, Preferences, SyncFrequency};
+pub use events::{EventBus, Subscriber, TaskEvent};
+pub use preferences::{
+    BulkCommitStrategy, ContextVerbosity, DateStyle, IcalComponent, JsonStyle, NotificationBackend,
+    Preferences, SyncFrequency, SyncTrigger, TuiTheme, Webhook, WebhookEvent,
+};
 pub use query::Query;
 pub use task::{SubTask, Task};
-pub use taskset::TaskSet;
+pub use taskset::{LoadOptions, TaskSet};
@@ -2,29 +2,308 @@
 use crate::Result;
 use crate::error::RstaskError;
 use crate::query::Query;
+use crate::util::fnv1a;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 pub type IdsMap = HashMap<String, i32>;
 
+/// IDs that were given up recently, with the Unix time each was, kept out of
+/// use for the `id_grace_minutes` preference
+pub type ReleasedIds = HashMap<i32, i64>;
+
 /// Local state including context
 #[derive(Debug, Clone)]
 pub struct LocalState {
     pub context: Query,
+    /// When the repository was last synced, shared by all auto-sync triggers
+    pub last_sync: Option<DateTime<Utc>>,
+    /// Task UUIDs mapped to when reminders for them may be sent again
+    pub snoozed_until: HashMap<String, DateTime<Utc>>,
+    /// The command that `again` repeats
+    pub last_command: Option<LastCommand>,
+    /// Commands counted while `usage_stats` is on
+    pub usage: Option<UsageStats>,
     state_file: PathBuf,
 }
 
+/// How often each command was run, kept only on this machine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// When counting started, as a Unix timestamp
+    pub since: i64,
+    pub commands: HashMap<String, CommandUsage>,
+}
+
+impl UsageStats {
+    /// Commands with their counts, most used first
+    pub fn by_count(&self) -> Vec<(&str, &CommandUsage)> {
+        let mut commands: Vec<_> = self
+            .commands
+            .iter()
+            .map(|(name, usage)| (name.as_str(), usage))
+            .collect();
+        commands.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.0.cmp(b.0)));
+        commands
+    }
+
+    pub fn total(&self) -> u64 {
+        self.commands.values().map(|c| c.count).sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandUsage {
+    pub count: u64,
+    /// Unix timestamp of the latest run
+    pub last_used: i64,
+}
+
+/// A command recorded for `again` and `last`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LastCommand {
+    /// The command line as typed, without the program name
+    pub args: Vec<String>,
+    pub query: Query,
+}
+
+/// On-disk layout of the state file. It's JSON, so fields can be added
+/// without making the files older versions wrote unreadable
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct StateFile {
+    context: Query,
+    last_sync: Option<i64>,
+    snoozed_until: HashMap<String, i64>,
+    last_command: Option<LastCommand>,
+    usage: Option<UsageStats>,
+}
+
+/// The state file as earlier versions wrote it, in bincode. bincode doesn't
+/// record which fields it holds, so each layout the file has had is tried,
+/// newest first, and must account for every byte. `Query` gained fields over
+/// the same versions, so each layout is tried with each of its layouts too.
+mod legacy {
+    use super::{LastCommand, StateFile, UsageStats};
+    use crate::query::Query;
+    use bincode::Options;
+    use chrono::{DateTime, Utc};
+    use serde::Deserialize;
+    use serde::de::DeserializeOwned;
+    use std::collections::HashMap;
+
+    /// `Query` before any fields were added to it
+    #[derive(Deserialize)]
+    pub(super) struct QueryV0 {
+        cmd: String,
+        ids: Vec<i32>,
+        tags: Vec<String>,
+        anti_tags: Vec<String>,
+        project: String,
+        anti_projects: Vec<String>,
+        #[serde(with = "chrono::serde::ts_seconds_option")]
+        due: Option<DateTime<Utc>>,
+        date_filter: String,
+        priority: String,
+        template: i32,
+        text: String,
+        ignore_context: bool,
+        note: String,
+    }
+
+    // Then `delegated`, `estimate`, `schedule` and `anti_text`, each added at
+    // the end. bincode writes a tuple just as it writes a struct's fields.
+    type QueryV1 = (QueryV0, Option<bool>);
+    type QueryV2 = (QueryV1, Option<u32>);
+    type QueryV3 = (QueryV2, Option<String>);
+    type QueryV4 = (QueryV3, Vec<String>);
+
+    trait OldQuery: DeserializeOwned {
+        fn upgrade(self) -> Query;
+    }
+
+    impl OldQuery for QueryV0 {
+        fn upgrade(self) -> Query {
+            Query {
+                cmd: self.cmd,
+                ids: self.ids,
+                tags: self.tags,
+                anti_tags: self.anti_tags,
+                project: self.project,
+                anti_projects: self.anti_projects,
+                due: self.due,
+                date_filter: self.date_filter,
+                priority: self.priority,
+                template: self.template,
+                text: self.text,
+                ignore_context: self.ignore_context,
+                note: self.note,
+                ..Default::default()
+            }
+        }
+    }
+
+    impl OldQuery for QueryV1 {
+        fn upgrade(self) -> Query {
+            Query {
+                delegated: self.1,
+                ..self.0.upgrade()
+            }
+        }
+    }
+
+    impl OldQuery for QueryV2 {
+        fn upgrade(self) -> Query {
+            Query {
+                estimate: self.1,
+                ..self.0.upgrade()
+            }
+        }
+    }
+
+    impl OldQuery for QueryV3 {
+        fn upgrade(self) -> Query {
+            Query {
+                schedule: self.1,
+                ..self.0.upgrade()
+            }
+        }
+    }
+
+    impl OldQuery for QueryV4 {
+        fn upgrade(self) -> Query {
+            Query {
+                anti_text: self.1,
+                ..self.0.upgrade()
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct WithUsage<Q> {
+        context: Q,
+        last_sync: Option<i64>,
+        snoozed_until: HashMap<String, i64>,
+        last_command: Option<(Vec<String>, Q)>,
+        usage: Option<UsageStats>,
+    }
+
+    /// Before usage statistics
+    #[derive(Deserialize)]
+    struct WithLastCommand<Q> {
+        context: Q,
+        last_sync: Option<i64>,
+        snoozed_until: HashMap<String, i64>,
+        last_command: Option<(Vec<String>, Q)>,
+    }
+
+    /// Before commands were recorded
+    #[derive(Deserialize)]
+    struct WithSnoozes<Q> {
+        context: Q,
+        last_sync: Option<i64>,
+        snoozed_until: HashMap<String, i64>,
+    }
+
+    /// Before reminders were snoozed. Earlier still the file held only the
+    /// context.
+    #[derive(Deserialize)]
+    struct WithLastSync<Q> {
+        context: Q,
+        last_sync: Option<i64>,
+    }
+
+    pub(super) fn decode(data: &[u8]) -> Option<StateFile> {
+        decode_with::<QueryV4>(data)
+            .or_else(|| decode_with::<QueryV3>(data))
+            .or_else(|| decode_with::<QueryV2>(data))
+            .or_else(|| decode_with::<QueryV1>(data))
+            .or_else(|| decode_with::<QueryV0>(data))
+    }
+
+    fn decode_with<Q: OldQuery>(data: &[u8]) -> Option<StateFile> {
+        let command = |command: Option<(Vec<String>, Q)>| {
+            command.map(|(args, query)| LastCommand {
+                args,
+                query: query.upgrade(),
+            })
+        };
+
+        if let Ok(old) = options().deserialize::<WithUsage<Q>>(data) {
+            return Some(StateFile {
+                context: old.context.upgrade(),
+                last_sync: old.last_sync,
+                snoozed_until: old.snoozed_until,
+                last_command: command(old.last_command),
+                usage: old.usage,
+            });
+        }
+        if let Ok(old) = options().deserialize::<WithLastCommand<Q>>(data) {
+            return Some(StateFile {
+                context: old.context.upgrade(),
+                last_sync: old.last_sync,
+                snoozed_until: old.snoozed_until,
+                last_command: command(old.last_command),
+                usage: None,
+            });
+        }
+        if let Ok(old) = options().deserialize::<WithSnoozes<Q>>(data) {
+            return Some(StateFile {
+                context: old.context.upgrade(),
+                last_sync: old.last_sync,
+                snoozed_until: old.snoozed_until,
+                ..Default::default()
+            });
+        }
+        if let Ok(old) = options().deserialize::<WithLastSync<Q>>(data) {
+            return Some(StateFile {
+                context: old.context.upgrade(),
+                last_sync: old.last_sync,
+                ..Default::default()
+            });
+        }
+        let context = options().deserialize::<Q>(data).ok()?;
+        Some(StateFile {
+            context: context.upgrade(),
+            ..Default::default()
+        })
+    }
+
+    /// What `bincode::deserialize` uses, except that a layout that leaves
+    /// bytes over doesn't match
+    fn options() -> impl Options {
+        bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .reject_trailing_bytes()
+    }
+}
+
+fn from_timestamp(ts: i64) -> Option<DateTime<Utc>> {
+    DateTime::<Utc>::from_timestamp(ts, 0)
+}
+
 impl LocalState {
     /// Load state from file or create default
     pub fn load(state_file: &Path) -> Self {
-        let context = if let Ok(data) = std::fs::read(state_file) {
-            bincode::deserialize(&data).unwrap_or_default()
-        } else {
-            Query::default()
-        };
+        let data = std::fs::read(state_file).unwrap_or_default();
+
+        let file = serde_json::from_slice::<StateFile>(&data)
+            .ok()
+            .or_else(|| legacy::decode(&data))
+            .unwrap_or_default();
 
         LocalState {
-            context,
+            context: file.context,
+            last_sync: file.last_sync.and_then(from_timestamp),
+            snoozed_until: file
+                .snoozed_until
+                .into_iter()
+                .filter_map(|(uuid, ts)| Some((uuid, from_timestamp(ts)?)))
+                .collect(),
+            last_command: file.last_command,
+            usage: file.usage,
             state_file: state_file.to_path_buf(),
         }
     }
@@ -55,26 +334,206 @@ impl LocalState {
         if let Some(parent) = self.state_file.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let data = bincode::serialize(&self.context)?;
+        let data = serde_json::to_vec(&StateFile {
+            context: self.context.clone(),
+            last_sync: self.last_sync.map(|t| t.timestamp()),
+            snoozed_until: self
+                .snoozed_until
+                .iter()
+                .map(|(uuid, until)| (uuid.clone(), until.timestamp()))
+                .collect(),
+            last_command: self.last_command.clone(),
+            usage: self.usage.clone(),
+        })?;
         std::fs::write(&self.state_file, data)?;
         Ok(())
     }
+
+    /// Records a sync that just happened and saves the state
+    pub fn mark_synced(&mut self) -> Result<()> {
+        self.last_sync = Some(Utc::now());
+        self.save()
+    }
+
+    /// Whether reminders for a task are held back at the given time
+    pub fn is_snoozed(&self, uuid: &str, now: DateTime<Utc>) -> bool {
+        self.snoozed_until
+            .get(uuid)
+            .is_some_and(|until| *until > now)
+    }
+
+    /// Holds back reminders for a task until the given time
+    pub fn snooze(&mut self, uuid: &str, until: DateTime<Utc>) {
+        self.snoozed_until.insert(uuid.to_string(), until);
+    }
+
+    /// Counts a run of `cmd`, starting the statistics if need be
+    pub fn record_usage(&mut self, cmd: &str, now: DateTime<Utc>) {
+        let usage = self.usage.get_or_insert_with(|| UsageStats {
+            since: now.timestamp(),
+            commands: HashMap::new(),
+        });
+        let entry = usage
+            .commands
+            .entry(cmd.to_string())
+            .or_insert(CommandUsage {
+                count: 0,
+                last_used: 0,
+            });
+        entry.count += 1;
+        entry.last_used = now.timestamp();
+    }
+
+    /// Forgets snoozes that have run out
+    pub fn prune_snoozes(&mut self, now: DateTime<Utc>) {
+        self.snoozed_until.retain(|_, until| *until > now);
+    }
 }
 
-pub fn load_ids(ids_file: &Path) -> IdsMap {
-    if let Ok(data) = std::fs::read(ids_file) {
-        bincode::deserialize(&data).unwrap_or_default()
+/// Start of an ids file written with a checksum. Older files are bare
+/// bincode and are still read, without the check.
+const IDS_MAGIC: &[u8; 4] = b"RSID";
+
+/// Start of an ids file that also lists released IDs. Only written while
+/// there are some, so files stay readable by versions without them
+const IDS_RELEASED_MAGIC: &[u8; 4] = b"RSIR";
+
+enum IdsFile {
+    Missing,
+    Damaged,
+    Valid(IdsMap, ReleasedIds),
+}
+
+fn read_ids_file(path: &Path) -> IdsFile {
+    match std::fs::read(path) {
+        Ok(data) => decode_ids(&data).map_or(IdsFile::Damaged, |(ids, released)| {
+            IdsFile::Valid(ids, released)
+        }),
+        Err(_) => IdsFile::Missing,
+    }
+}
+
+fn decode_ids(data: &[u8]) -> Option<(IdsMap, ReleasedIds)> {
+    let (with_released, rest) = if let Some(rest) = data.strip_prefix(IDS_RELEASED_MAGIC) {
+        (true, rest)
+    } else if let Some(rest) = data.strip_prefix(IDS_MAGIC) {
+        (false, rest)
+    } else {
+        return Some((bincode::deserialize(data).ok()?, ReleasedIds::new()));
+    };
+    let (checksum, payload) = rest.split_at_checked(8)?;
+    if u64::from_le_bytes(checksum.try_into().ok()?) != fnv1a(payload) {
+        return None;
+    }
+    if with_released {
+        bincode::deserialize(payload).ok()
     } else {
-        HashMap::new()
+        Some((bincode::deserialize(payload).ok()?, ReleasedIds::new()))
     }
 }
 
+/// The copy of the ids file kept next to it
+pub fn ids_backup_path(ids_file: &Path) -> PathBuf {
+    ids_file.with_extension("bin.bak")
+}
+
+/// Moves a damaged file aside, so it can be inspected and isn't reported again
+fn set_aside(path: &Path) {
+    let _ = std::fs::rename(path, corrupt_path(path));
+}
+
+fn corrupt_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".corrupt");
+    path.with_file_name(name)
+}
+
+/// Loads the task ID map. A missing or damaged file is restored from its
+/// backup; if that fails too, IDs are reassigned from scratch with a warning
+/// rather than silently.
+pub fn load_ids(ids_file: &Path) -> IdsMap {
+    load_ids_and_released(ids_file).0
+}
+
+/// Loads the task ID map and the IDs released recently, like [`load_ids`]
+pub fn load_ids_and_released(ids_file: &Path) -> (IdsMap, ReleasedIds) {
+    let main = match read_ids_file(ids_file) {
+        IdsFile::Valid(ids, released) => return (ids, released),
+        other => other,
+    };
+
+    let backup_path = ids_backup_path(ids_file);
+    let backup = read_ids_file(&backup_path);
+    if let IdsFile::Damaged = main {
+        set_aside(ids_file);
+    }
+    if let IdsFile::Damaged = backup {
+        set_aside(&backup_path);
+    }
+
+    match (main, backup) {
+        (main, IdsFile::Valid(ids, released)) => {
+            let problem = if let IdsFile::Damaged = main {
+                "is damaged"
+            } else {
+                "is missing"
+            };
+            eprintln!(
+                "Warning: the task ID file {} {}; restored IDs from its backup",
+                ids_file.display(),
+                problem
+            );
+            if let Err(e) = save_ids_and_released(ids_file, &ids, &released) {
+                eprintln!("Warning: failed to rewrite the task ID file: {}", e);
+            }
+            (ids, released)
+        }
+        // A new repository or clone
+        (IdsFile::Missing, IdsFile::Missing) => Default::default(),
+        _ => {
+            eprintln!(
+                "Warning: the task ID file {} and its backup are damaged. Task IDs have \
+                 been reassigned and may not match the ones you saw before; check them \
+                 with \"rstask show-open\" before acting on a task by ID. The damaged \
+                 files were kept with .corrupt added to their names.",
+                ids_file.display()
+            );
+            Default::default()
+        }
+    }
+}
+
+/// Saves the task ID map with a checksum, along with a backup copy. IDs
+/// released earlier stay released
 pub fn save_ids(ids_file: &Path, ids: &IdsMap) -> Result<()> {
+    let released = match read_ids_file(ids_file) {
+        IdsFile::Valid(_, released) => released,
+        _ => ReleasedIds::new(),
+    };
+    save_ids_and_released(ids_file, ids, &released)
+}
+
+/// Saves the task ID map and the IDs released recently, like [`save_ids`]
+pub fn save_ids_and_released(ids_file: &Path, ids: &IdsMap, released: &ReleasedIds) -> Result<()> {
     if let Some(parent) = ids_file.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    let data = bincode::serialize(ids)?;
-    std::fs::write(ids_file, data)?;
+    let (magic, payload) = if released.is_empty() {
+        (IDS_MAGIC, bincode::serialize(ids)?)
+    } else {
+        (IDS_RELEASED_MAGIC, bincode::serialize(&(ids, released))?)
+    };
+    let mut data = Vec::with_capacity(magic.len() + 8 + payload.len());
+    data.extend_from_slice(magic);
+    data.extend_from_slice(&fnv1a(&payload).to_le_bytes());
+    data.extend_from_slice(&payload);
+
+    // Each file is replaced whole, so an interrupted save damages at most one
+    for path in [ids_backup_path(ids_file), ids_file.to_path_buf()] {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, &data)?;
+        std::fs::rename(&tmp, &path)?;
+    }
     Ok(())
 }
 
@@ -94,3 +553,318 @@ pub fn save_state(state_file: &Path, query: &Query) -> Result<()> {
     std::fs::write(state_file, data)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.bin");
+
+        let mut state = LocalState::load(&path);
+        assert!(state.last_sync.is_none());
+        state.context.tags = vec!["work".to_string()];
+        state.mark_synced().unwrap();
+
+        let loaded = LocalState::load(&path);
+        assert_eq!(loaded.context.tags, vec!["work".to_string()]);
+        assert!(loaded.last_sync.is_some());
+    }
+
+    #[test]
+    fn test_loads_context_only_state_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.bin");
+
+        let context = Query {
+            project: "website".to_string(),
+            ..Default::default()
+        };
+        save_state(&path, &context).unwrap();
+
+        let loaded = LocalState::load(&path);
+        assert_eq!(loaded.context.project, "website");
+        assert!(loaded.last_sync.is_none());
+    }
+
+    #[test]
+    fn test_loads_baseline_state_file() {
+        // What the first release wrote for `rstask context +home`: its
+        // Query's fields in order, from cmd to note, and nothing else
+        let baseline = (
+            String::new(),
+            Vec::<i32>::new(),
+            vec!["home".to_string()],
+            Vec::<String>::new(),
+            String::new(),
+            Vec::<String>::new(),
+            None::<i64>,
+            String::new(),
+            String::new(),
+            0i32,
+            String::new(),
+            false,
+            String::new(),
+        );
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.bin");
+        std::fs::write(&path, bincode::serialize(&baseline).unwrap()).unwrap();
+
+        let mut state = LocalState::load(&path);
+        assert_eq!(state.context.tags, vec!["home".to_string()]);
+
+        // Saved again in the current format, which still reads back
+        state.mark_synced().unwrap();
+        let loaded = LocalState::load(&path);
+        assert_eq!(loaded.context.tags, vec!["home".to_string()]);
+        assert!(loaded.last_sync.is_some());
+    }
+
+    #[test]
+    fn test_loads_state_file_written_after_each_query_field() {
+        #[derive(Serialize)]
+        struct Old<Q> {
+            context: Q,
+            last_sync: Option<i64>,
+            snoozed_until: HashMap<String, i64>,
+            last_command: Option<(Vec<String>, Q)>,
+            usage: Option<UsageStats>,
+        }
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.bin");
+        // The baseline Query with delegated:true and estimate:None appended
+        let query = (
+            (
+                (
+                    String::new(),
+                    Vec::<i32>::new(),
+                    Vec::<String>::new(),
+                    Vec::<String>::new(),
+                    "website".to_string(),
+                    Vec::<String>::new(),
+                    None::<i64>,
+                    String::new(),
+                    String::new(),
+                    0i32,
+                    String::new(),
+                    false,
+                    String::new(),
+                ),
+                Some(true),
+            ),
+            None::<u32>,
+        );
+        let old = Old {
+            context: query.clone(),
+            last_sync: Some(1_700_000_000),
+            snoozed_until: HashMap::from([("a".to_string(), 1_800_000_000)]),
+            last_command: Some((vec!["next".to_string()], query)),
+            usage: None,
+        };
+        std::fs::write(&path, bincode::serialize(&old).unwrap()).unwrap();
+
+        let loaded = LocalState::load(&path);
+        assert_eq!(loaded.context.project, "website");
+        assert_eq!(loaded.context.delegated, Some(true));
+        assert_eq!(loaded.snoozed_until.len(), 1);
+        assert_eq!(loaded.last_command.unwrap().args, vec!["next".to_string()]);
+    }
+
+    #[test]
+    fn test_loads_state_file_without_snoozes() {
+        #[derive(Serialize)]
+        struct Old {
+            context: Query,
+            last_sync: Option<i64>,
+        }
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.bin");
+        let old = Old {
+            context: Query {
+                project: "website".to_string(),
+                ..Default::default()
+            },
+            last_sync: Some(1_700_000_000),
+        };
+        std::fs::write(&path, bincode::serialize(&old).unwrap()).unwrap();
+
+        let loaded = LocalState::load(&path);
+        assert_eq!(loaded.context.project, "website");
+        assert_eq!(loaded.last_sync.unwrap().timestamp(), 1_700_000_000);
+        assert!(loaded.snoozed_until.is_empty());
+    }
+
+    #[test]
+    fn test_last_command_round_trip() {
+        #[derive(Serialize)]
+        struct Old {
+            context: Query,
+            last_sync: Option<i64>,
+            snoozed_until: HashMap<String, i64>,
+        }
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.bin");
+        let old = Old {
+            context: Query::default(),
+            last_sync: Some(1_700_000_000),
+            snoozed_until: HashMap::from([("a".to_string(), 1_800_000_000)]),
+        };
+        std::fs::write(&path, bincode::serialize(&old).unwrap()).unwrap();
+
+        let mut state = LocalState::load(&path);
+        assert_eq!(state.snoozed_until.len(), 1);
+        assert!(state.last_command.is_none());
+
+        let args = vec![
+            "add".to_string(),
+            "Pay rent".to_string(),
+            "+home".to_string(),
+        ];
+        let command = LastCommand {
+            query: crate::query::parse_query(&args).unwrap(),
+            args,
+        };
+        state.last_command = Some(command.clone());
+        state.save().unwrap();
+
+        let loaded = LocalState::load(&path);
+        assert_eq!(loaded.last_command, Some(command));
+        assert_eq!(loaded.last_sync.unwrap().timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    fn test_ids_round_trip_and_legacy_format() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("ids.bin");
+        let ids = IdsMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+
+        save_ids(&path, &ids).unwrap();
+        assert_eq!(load_ids(&path), ids);
+        assert_eq!(load_ids(&ids_backup_path(&path)), ids);
+
+        // Files from before the checksum still load
+        std::fs::write(&path, bincode::serialize(&ids).unwrap()).unwrap();
+        assert_eq!(load_ids(&path), ids);
+    }
+
+    #[test]
+    fn test_released_ids_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("ids.bin");
+        let ids = IdsMap::from([("a".to_string(), 1)]);
+        let released = ReleasedIds::from([(2, 1_700_000_000)]);
+
+        save_ids_and_released(&path, &ids, &released).unwrap();
+        assert_eq!(
+            load_ids_and_released(&path),
+            (ids.clone(), released.clone())
+        );
+
+        // Kept by saves that only change the assigned IDs
+        save_ids(&path, &IdsMap::new()).unwrap();
+        assert_eq!(load_ids_and_released(&path), (IdsMap::new(), released));
+    }
+
+    #[test]
+    fn test_damaged_ids_file_is_restored_from_backup() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("ids.bin");
+        let ids = IdsMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+        save_ids(&path, &ids).unwrap();
+
+        // A flipped byte fails the checksum
+        let mut data = std::fs::read(&path).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        std::fs::write(&path, data).unwrap();
+        assert_eq!(load_ids(&path), ids);
+        assert!(corrupt_path(&path).exists());
+        // The main file was rewritten
+        assert!(matches!(read_ids_file(&path), IdsFile::Valid(..)));
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(load_ids(&path), ids);
+    }
+
+    #[test]
+    fn test_damaged_ids_file_and_backup_start_over() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("ids.bin");
+        save_ids(&path, &IdsMap::from([("a".to_string(), 1)])).unwrap();
+        std::fs::write(&path, b"RSID garbage").unwrap();
+        std::fs::write(ids_backup_path(&path), b"").unwrap();
+
+        assert!(load_ids(&path).is_empty());
+        assert!(corrupt_path(&path).exists());
+        assert!(corrupt_path(&ids_backup_path(&path)).exists());
+
+        // Reported once; afterwards it looks like a fresh repository
+        assert!(matches!(read_ids_file(&path), IdsFile::Missing));
+    }
+
+    #[test]
+    fn test_usage_round_trip() {
+        #[derive(Serialize)]
+        struct Old {
+            context: Query,
+            last_sync: Option<i64>,
+            snoozed_until: HashMap<String, i64>,
+            last_command: Option<LastCommand>,
+        }
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.bin");
+        let old = Old {
+            context: Query {
+                project: "website".to_string(),
+                ..Default::default()
+            },
+            last_sync: None,
+            snoozed_until: HashMap::new(),
+            last_command: None,
+        };
+        std::fs::write(&path, bincode::serialize(&old).unwrap()).unwrap();
+
+        let mut state = LocalState::load(&path);
+        assert_eq!(state.context.project, "website");
+        assert!(state.usage.is_none());
+
+        let now = Utc::now();
+        state.record_usage("next", now);
+        state.record_usage("next", now);
+        state.record_usage("add", now);
+        state.save().unwrap();
+
+        let usage = LocalState::load(&path).usage.unwrap();
+        assert_eq!(usage.since, now.timestamp());
+        assert_eq!(usage.commands["next"].count, 2);
+        assert_eq!(usage.commands["add"].count, 1);
+    }
+
+    #[test]
+    fn test_snooze() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.bin");
+        let now = Utc::now();
+
+        let mut state = LocalState::load(&path);
+        state.snooze("a", now + chrono::Duration::hours(1));
+        state.snooze("b", now - chrono::Duration::hours(1));
+        assert!(state.is_snoozed("a", now));
+        assert!(!state.is_snoozed("b", now));
+        assert!(!state.is_snoozed("c", now));
+
+        state.prune_snoozes(now);
+        state.save().unwrap();
+
+        let loaded = LocalState::load(&path);
+        assert!(loaded.is_snoozed("a", now));
+        assert_eq!(loaded.snoozed_until.len(), 1);
+    }
+}
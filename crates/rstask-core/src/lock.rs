@@ -0,0 +1,121 @@
+//! Advisory lock on the task repository
+//!
+//! Two rstask processes saving at once, such as the TUI and a cron job
+//! syncing, can interleave ID assignment and git commits. Whatever saves
+//! tasks, commits or syncs takes this lock first and waits for the other
+//! process to finish. The operating system releases it if rstask crashes.
+
+use crate::{Result, RstaskError};
+use std::cell::Cell;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long to wait for another process, unless `lock_timeout_seconds` says
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+thread_local! {
+    /// Locks this thread already holds, so that nested saves don't wait on
+    /// themselves
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The lock file, in `.git/rstask`
+pub fn lock_path(repo_path: &Path) -> PathBuf {
+    repo_path.join(".git").join("rstask").join("lock")
+}
+
+/// Held until dropped
+#[derive(Debug)]
+pub struct RepoLock {
+    /// None when an outer lock on this thread already covers it
+    file: Option<File>,
+}
+
+impl RepoLock {
+    /// Takes the lock, waiting up to `timeout` for another process to
+    /// release it
+    pub fn acquire(repo_path: &Path, timeout: Duration) -> Result<Self> {
+        if DEPTH.get() > 0 {
+            DEPTH.set(DEPTH.get() + 1);
+            return Ok(RepoLock { file: None });
+        }
+
+        let path = lock_path(repo_path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+
+        let started = Instant::now();
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) if started.elapsed() < timeout => {
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                Err(TryLockError::WouldBlock) => return Err(busy_error(&mut file, timeout)),
+                Err(TryLockError::Error(e)) => return Err(e.into()),
+            }
+        }
+
+        // Only for the message another process shows while waiting
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{}", std::process::id())?;
+
+        DEPTH.set(1);
+        Ok(RepoLock { file: Some(file) })
+    }
+}
+
+impl Drop for RepoLock {
+    fn drop(&mut self) {
+        DEPTH.set(DEPTH.get().saturating_sub(1));
+        if let Some(file) = &self.file {
+            let _ = file.unlock();
+        }
+    }
+}
+
+fn busy_error(file: &mut File, timeout: Duration) -> RstaskError {
+    let mut pid = String::new();
+    let _ = file.read_to_string(&mut pid);
+    let holder = match pid.trim() {
+        "" => String::new(),
+        pid => format!(" (pid {})", pid),
+    };
+
+    RstaskError::Other(format!(
+        "another rstask instance{} is running and didn't finish within {}s; \
+         try again once it has",
+        holder,
+        timeout.as_secs()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_locks_on_one_thread() {
+        let dir = tempfile::tempdir().unwrap();
+        let outer = RepoLock::acquire(dir.path(), DEFAULT_TIMEOUT).unwrap();
+        let inner = RepoLock::acquire(dir.path(), DEFAULT_TIMEOUT).unwrap();
+        drop(inner);
+        drop(outer);
+
+        // Released, so a fresh handle can take it
+        let file = File::open(lock_path(dir.path())).unwrap();
+        assert!(file.try_lock().is_ok());
+    }
+}
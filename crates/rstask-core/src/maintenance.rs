@@ -0,0 +1,136 @@
+//! Housekeeping for `rstask maintenance`
+//!
+//! Sync conflicts and hand edits can leave the same task in two status
+//! directories, and tasks resolved or removed on another machine leave their
+//! entries behind in the IDs file. Neither breaks anything, but both build up.
+
+use crate::Result;
+use crate::constants::ALL_STATUSES;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// A task file found in one of the status directories
+#[derive(Debug, Clone)]
+pub struct TaskFile {
+    pub path: PathBuf,
+    pub status: &'static str,
+    pub uuid: String,
+    pub size: u64,
+}
+
+/// Every task file in the repository, in the order the loader reads them:
+/// by status, then Markdown before legacy YAML
+pub fn task_files(repo_path: &Path) -> Result<Vec<TaskFile>> {
+    let mut files = Vec::new();
+
+    for &status in ALL_STATUSES {
+        let dir = repo_path.join(status);
+        if !dir.exists() {
+            continue;
+        }
+
+        let mut in_status = Vec::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some(uuid) = name
+                .strip_suffix(".md")
+                .or_else(|| name.strip_suffix(".yml"))
+            else {
+                continue;
+            };
+            if name.starts_with('.') {
+                continue;
+            }
+
+            in_status.push(TaskFile {
+                path: entry.path(),
+                status,
+                uuid: uuid.to_string(),
+                size: entry.metadata()?.len(),
+            });
+        }
+        // Markdown first, as false sorts before true
+        in_status.sort_by_key(|f| (f.path.extension().is_none_or(|e| e != "md"), f.uuid.clone()));
+        files.extend(in_status);
+    }
+
+    Ok(files)
+}
+
+/// Files holding a task that an earlier file already holds. The loader only
+/// reads the first copy, so removing the rest leaves what rstask shows as it is
+pub fn duplicate_files(files: &[TaskFile]) -> Vec<&TaskFile> {
+    let mut seen = HashSet::new();
+    files
+        .iter()
+        .filter(|f| !seen.insert(f.uuid.as_str()))
+        .collect()
+}
+
+/// Total size in bytes of the files under `path`
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Sizes such as "340 B", "12.5 KiB" or "3.1 MiB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{Task, WriteFormat};
+
+    #[test]
+    fn test_duplicate_files_keeps_the_loaded_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut task = Task::new("Fix the build".to_string());
+        task.save_to_disk(dir.path(), WriteFormat::default())
+            .unwrap();
+
+        // A stale copy left behind in another status directory
+        let copy = dir
+            .path()
+            .join(crate::constants::STATUS_RESOLVED)
+            .join(format!("{}.md", task.uuid));
+        std::fs::create_dir_all(copy.parent().unwrap()).unwrap();
+        std::fs::copy(task.file_path(dir.path()).unwrap(), &copy).unwrap();
+
+        let files = task_files(dir.path()).unwrap();
+        assert_eq!(files.len(), 2);
+        let duplicates = duplicate_files(&files);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].path, copy);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(340), "340 B");
+        assert_eq!(format_bytes(12_800), "12.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
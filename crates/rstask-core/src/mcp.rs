@@ -0,0 +1,378 @@
+//! Model Context Protocol server over stdio
+//!
+//! `rstask mcp` reads newline-delimited JSON-RPC requests on stdin and answers
+//! on stdout, exposing a small set of task operations as MCP tools. Changes go
+//! through the same TaskSet and git commit path as the CLI, so anything an
+//! assistant does shows up in the history and can be reverted with
+//! `rstask undo`. No tool deletes tasks.
+
+use crate::commands::commit_changes;
+use crate::config::Config;
+use crate::constants::*;
+use crate::date_util::parse_str_to_date;
+use crate::events::TaskEvent;
+use crate::query::parse_query;
+use crate::task::Task;
+use crate::taskset::TaskSet;
+use crate::{Result, RstaskError};
+use chrono::Utc;
+use serde_json::{Value, json};
+use std::io::{BufRead, Write};
+
+/// Protocol revisions this server speaks, newest first
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Answers requests from `input` until it is closed
+pub fn serve(conf: &Config, input: impl BufRead, mut output: impl Write) -> Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle_message(conf, &message),
+            Err(e) => Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+
+        if let Some(response) = response {
+            serde_json::to_writer(&mut output, &response)?;
+            writeln!(output)?;
+            output.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles one JSON-RPC message, returning the response for requests and
+/// nothing for notifications
+pub fn handle_message(conf: &Config, message: &Value) -> Option<Value> {
+    let id = message.get("id").cloned()?;
+    let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => Ok(initialize(&params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+        "tools/call" => call_tool(conf, &params),
+        _ => Err((METHOD_NOT_FOUND, format!("unknown method '{}'", method))),
+    };
+
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error_response(id, code, &message),
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn initialize(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|v| PROTOCOL_VERSIONS.contains(v))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {} },
+        "serverInfo": { "name": "rstask", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "list_tasks",
+            "description": "List open tasks as JSON. The optional filter uses rstask syntax, \
+                e.g. \"+work project:website P1 due.before:friday\".",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "filter": { "type": "string", "description": "rstask filter" },
+                },
+            },
+        },
+        {
+            "name": "add_task",
+            "description": "Add a new pending task.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "summary": { "type": "string" },
+                    "project": { "type": "string" },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "priority": { "type": "string", "enum": ["P0", "P1", "P2", "P3"] },
+                    "due": { "type": "string", "description": "e.g. \"tomorrow\" or \"2025-03-01\"" },
+                    "notes": { "type": "string" },
+                },
+                "required": ["summary"],
+            },
+        },
+        {
+            "name": "resolve_task",
+            "description": "Mark an open task as done.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "id": { "type": "integer" } },
+                "required": ["id"],
+            },
+        },
+        {
+            "name": "annotate",
+            "description": "Append a line to the notes of an open task.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer" },
+                    "note": { "type": "string" },
+                },
+                "required": ["id", "note"],
+            },
+        },
+    ])
+}
+
+/// Runs a tool. Failures of the tool itself are reported in the result, as
+/// MCP asks, so the assistant can see and correct them.
+fn call_tool(conf: &Config, params: &Value) -> std::result::Result<Value, (i64, String)> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or((INVALID_PARAMS, "missing tool name".to_string()))?;
+    let args = params.get("arguments").cloned().unwrap_or(json!({}));
+
+    let outcome = match name {
+        "list_tasks" => list_tasks(conf, &args),
+        "add_task" => add_task(conf, &args),
+        "resolve_task" => resolve_task(conf, &args),
+        "annotate" => annotate(conf, &args),
+        _ => return Err((INVALID_PARAMS, format!("unknown tool '{}'", name))),
+    };
+
+    let (text, is_error) = match outcome {
+        Ok(text) => (text, false),
+        Err(e) => (e.to_string(), true),
+    };
+
+    Ok(json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    }))
+}
+
+fn str_arg<'a>(args: &'a Value, name: &str) -> Option<&'a str> {
+    args.get(name).and_then(Value::as_str)
+}
+
+fn id_arg(args: &Value) -> Result<i32> {
+    args.get("id")
+        .and_then(Value::as_i64)
+        .and_then(|id| i32::try_from(id).ok())
+        .ok_or_else(|| RstaskError::Parse("an integer task id is required".to_string()))
+}
+
+fn list_tasks(conf: &Config, args: &Value) -> Result<String> {
+    let tokens: Vec<String> = str_arg(args, "filter")
+        .unwrap_or("")
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    let query = parse_query(&tokens)?;
+
+    let mut ts = TaskSet::load(conf, false)?;
+    ts.filter(&query);
+    ts.sort_by_created_ascending();
+
+    let tasks: Vec<_> = ts.tasks().iter().map(|t| t.to_json_ref()).collect();
+    Ok(serde_json::to_string_pretty(&tasks)?)
+}
+
+fn add_task(conf: &Config, args: &Value) -> Result<String> {
+    let summary = str_arg(args, "summary")
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| RstaskError::Parse("summary is required".to_string()))?;
+
+    let due = match str_arg(args, "due") {
+        Some(due) => Some(parse_str_to_date(due)?.with_timezone(&Utc)),
+        None => None,
+    };
+
+    let task = Task {
+        write_pending: true,
+        status: STATUS_PENDING.to_string(),
+        summary: summary.to_string(),
+        project: str_arg(args, "project").unwrap_or("").to_string(),
+        tags: args
+            .get("tags")
+            .and_then(Value::as_array)
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        priority: str_arg(args, "priority").unwrap_or("").to_string(),
+        due,
+        notes: str_arg(args, "notes").unwrap_or("").to_string(),
+        ..Default::default()
+    };
+
+    let mut ts = TaskSet::load_for_update(conf, false)?;
+    let task = ts.must_load_task(task)?;
+    ts.save_pending_changes()?;
+    commit_changes(conf, &format!("Added {}: {}", task.id, task.summary), true)?;
+
+    let message = format!("Added {}: {}", task.id, task.summary);
+    conf.events.emit(&TaskEvent::TaskAdded(task));
+    Ok(message)
+}
+
+fn resolve_task(conf: &Config, args: &Value) -> Result<String> {
+    let id = id_arg(args)?;
+    let mut ts = TaskSet::load_for_update(conf, false)?;
+    let before = open_task(&ts, id)?.clone();
+
+    let mut task = before.clone();
+    task.status = STATUS_RESOLVED.to_string();
+    task.resolved = Some(Utc::now());
+    task.write_pending = true;
+
+    ts.must_update_task(task.clone())?;
+    ts.save_pending_changes()?;
+    commit_changes(conf, &format!("Resolved {}", task.summary), true)?;
+
+    conf.events.emit_change(&before, &task);
+    Ok(format!("Resolved {}: {}", id, task.summary))
+}
+
+fn annotate(conf: &Config, args: &Value) -> Result<String> {
+    let id = id_arg(args)?;
+    let note = str_arg(args, "note")
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .ok_or_else(|| RstaskError::Parse("note is required".to_string()))?;
+
+    let mut ts = TaskSet::load_for_update(conf, false)?;
+    let before = open_task(&ts, id)?.clone();
+
+    let mut task = before.clone();
+    if !task.notes.is_empty() {
+        task.notes.push('\n');
+    }
+    task.notes.push_str(note);
+    task.write_pending = true;
+
+    ts.must_update_task(task.clone())?;
+    ts.save_pending_changes()?;
+    commit_changes(conf, &format!("Updated notes for {}", task.summary), true)?;
+
+    conf.events.emit_change(&before, &task);
+    Ok(format!("Annotated {}: {}", id, task.summary))
+}
+
+/// Finds an open task by ID, without the CLI's exit-on-missing behaviour
+fn open_task(ts: &TaskSet, id: i32) -> Result<&Task> {
+    ts.get_by_id(id)
+        .filter(|t| t.status != STATUS_TEMPLATE)
+        .ok_or_else(|| RstaskError::TaskNotFound(id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> Config {
+        Config {
+            repo: "/nonexistent".into(),
+            state_file: "/nonexistent/state.bin".into(),
+            ids_file: "/nonexistent/ids.bin".into(),
+            ctx_from_env_var: None,
+            preferences: Default::default(),
+            refuse_new_taxonomy: false,
+            template_vars: Vec::new(),
+            events: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_initialize_negotiates_version() {
+        let conf = test_config();
+        let response = handle_message(
+            &conf,
+            &json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "protocolVersion": "2024-11-05" },
+            }),
+        )
+        .unwrap();
+        assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(response["result"]["serverInfo"]["name"], "rstask");
+
+        let response = handle_message(
+            &conf,
+            &json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "initialize",
+                "params": { "protocolVersion": "1999-01-01" },
+            }),
+        )
+        .unwrap();
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+    }
+
+    #[test]
+    fn test_notifications_and_unknown_methods() {
+        let conf = test_config();
+        assert!(
+            handle_message(
+                &conf,
+                &json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })
+            )
+            .is_none()
+        );
+
+        let response = handle_message(
+            &conf,
+            &json!({ "jsonrpc": "2.0", "id": 3, "method": "nope" }),
+        )
+        .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_tools_list() {
+        let conf = test_config();
+        let response = handle_message(
+            &conf,
+            &json!({ "jsonrpc": "2.0", "id": 4, "method": "tools/list" }),
+        )
+        .unwrap();
+        let names: Vec<&str> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["list_tasks", "add_task", "resolve_task", "annotate"]
+        );
+    }
+}
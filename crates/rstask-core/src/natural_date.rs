@@ -0,0 +1,237 @@
+//! Due dates written in words, such as "next friday afternoon", "in 2 weeks",
+//! "eom" or "25th of december"
+//!
+//! Due dates are stored as days, so times of day ("afternoon", "noon") are
+//! accepted and dropped.
+
+use crate::date_util::{end_of_month, end_of_week, parse_weekday, weekday_date};
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+
+/// Words that don't change the date
+const FILLER_WORDS: &[&str] = &[
+    "on",
+    "by",
+    "at",
+    "the",
+    "of",
+    "morning",
+    "afternoon",
+    "evening",
+    "night",
+    "noon",
+    "midday",
+];
+
+/// Parses `text` relative to `today`. On failure, returns the first word
+/// that wasn't understood
+pub fn parse_natural_date(text: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '-')
+        .filter(|w| !w.is_empty() && !FILLER_WORDS.contains(w))
+        .collect();
+
+    match_words(&words, today).ok_or_else(|| {
+        words
+            .iter()
+            .find(|w| !is_known_word(w))
+            .map_or_else(|| text.trim().to_string(), |w| w.to_string())
+    })
+}
+
+fn match_words(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    match words {
+        // Only a time of day, such as "afternoon"
+        [] | ["today" | "tonight" | "eod"] => Some(today),
+        ["tomorrow"] => today.succ_opt(),
+        ["yesterday"] => today.pred_opt(),
+        ["day", "after", "tomorrow"] => today.checked_add_days(Days::new(2)),
+        ["eow"] | ["end", "week"] => Some(end_of_week(today)),
+        ["eom"] | ["end", "month"] => Some(end_of_month(today)),
+        ["eoy"] | ["end", "year"] => NaiveDate::from_ymd_opt(today.year(), 12, 31),
+        ["next", "week"] => weekday_date(today, Weekday::Mon, "next"),
+        ["next", "month"] => end_of_month(today).succ_opt(),
+        ["next", "year"] => NaiveDate::from_ymd_opt(today.year() + 1, 1, 1),
+        [selector @ ("this" | "next"), day] => weekday_date(today, parse_weekday(day)?, selector),
+        [day] if parse_weekday(day).is_some() => weekday_date(today, parse_weekday(day)?, ""),
+        ["in", count, unit] | [count, unit] if parse_count(count).is_some() => {
+            after(today, parse_count(count)?, unit)
+        }
+        [a, b] => month_day(today, a, b, None),
+        [a, b, year] => month_day(today, a, b, Some(year.parse().ok()?)),
+        _ => None,
+    }
+}
+
+fn after(today: NaiveDate, count: u32, unit: &str) -> Option<NaiveDate> {
+    match unit.trim_end_matches('s') {
+        "day" => today.checked_add_days(Days::new(count as u64)),
+        "week" => today.checked_add_days(Days::new(count as u64 * 7)),
+        "month" => today.checked_add_months(Months::new(count)),
+        "year" => today.checked_add_months(Months::new(count * 12)),
+        _ => None,
+    }
+}
+
+fn is_unit(word: &str) -> bool {
+    matches!(
+        word.trim_end_matches('s'),
+        "day" | "week" | "month" | "year"
+    )
+}
+
+fn parse_count(word: &str) -> Option<u32> {
+    let count = match word {
+        "a" | "an" | "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        _ => return word.parse().ok(),
+    };
+    Some(count)
+}
+
+/// "dec 25" or "25th december", in the given year or else the next one to come
+fn month_day(today: NaiveDate, a: &str, b: &str, year: Option<i32>) -> Option<NaiveDate> {
+    let (month, day) = match (parse_month(a), parse_month(b)) {
+        (Some(month), None) => (month, parse_day(b)?),
+        (None, Some(month)) => (month, parse_day(a)?),
+        _ => return None,
+    };
+
+    match year {
+        Some(year) => NaiveDate::from_ymd_opt(year, month, day),
+        None => {
+            let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+            if date < today {
+                NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+            } else {
+                Some(date)
+            }
+        }
+    }
+}
+
+fn parse_month(word: &str) -> Option<u32> {
+    const MONTHS: &[&str] = &[
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+
+    if word.len() < 3 {
+        return None;
+    }
+    let word = if word == "sept" { "sep" } else { word };
+    MONTHS
+        .iter()
+        .position(|m| m.starts_with(word))
+        .map(|i| i as u32 + 1)
+}
+
+fn parse_day(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &word[digits.len()..];
+    if !matches!(suffix, "" | "st" | "nd" | "rd" | "th") {
+        return None;
+    }
+    digits.parse().ok().filter(|d| (1..=31).contains(d))
+}
+
+fn is_known_word(word: &str) -> bool {
+    const WORDS: &[&str] = &[
+        "today",
+        "tonight",
+        "eod",
+        "tomorrow",
+        "yesterday",
+        "day",
+        "after",
+        "eow",
+        "eom",
+        "eoy",
+        "end",
+        "week",
+        "month",
+        "year",
+        "this",
+        "next",
+        "in",
+    ];
+
+    WORDS.contains(&word)
+        || parse_weekday(word).is_some()
+        || parse_count(word).is_some()
+        || is_unit(word)
+        || parse_month(word).is_some()
+        || parse_day(word).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_natural_date() {
+        // A Wednesday
+        let today = date(2026, 3, 18);
+        let parse = |text| parse_natural_date(text, today);
+
+        assert_eq!(parse("next friday afternoon"), Ok(date(2026, 3, 27)));
+        assert_eq!(parse("friday"), Ok(date(2026, 3, 20)));
+        assert_eq!(parse("day after tomorrow"), Ok(date(2026, 3, 20)));
+        assert_eq!(parse("eow"), Ok(date(2026, 3, 22)));
+        assert_eq!(parse("eom"), Ok(date(2026, 3, 31)));
+        assert_eq!(parse("end of the year"), Ok(date(2026, 12, 31)));
+        assert_eq!(parse("next week"), Ok(date(2026, 3, 23)));
+        assert_eq!(parse("next month"), Ok(date(2026, 4, 1)));
+        assert_eq!(parse("in 2 weeks"), Ok(date(2026, 4, 1)));
+        assert_eq!(parse("in a month"), Ok(date(2026, 4, 18)));
+        assert_eq!(parse("25th of December"), Ok(date(2026, 12, 25)));
+        assert_eq!(parse("jan 5"), Ok(date(2027, 1, 5)));
+        assert_eq!(parse("jan 5 2028"), Ok(date(2028, 1, 5)));
+    }
+
+    #[test]
+    fn test_parse_natural_date_errors() {
+        let today = date(2026, 3, 18);
+
+        assert_eq!(
+            parse_natural_date("next fridya", today),
+            Err("fridya".to_string())
+        );
+        assert_eq!(
+            parse_natural_date("feb 30", today),
+            Err("feb 30".to_string())
+        );
+    }
+
+    #[test]
+    fn test_end_of_week_on_sunday_is_today() {
+        let sunday = date(2026, 3, 22);
+        assert_eq!(parse_natural_date("eow", sunday), Ok(sunday));
+        assert_eq!(
+            parse_natural_date("next week", sunday),
+            Ok(date(2026, 3, 23))
+        );
+    }
+}
@@ -0,0 +1,331 @@
+//! Delivery of notifications
+//!
+//! Everything that tells someone about a task builds a [`Notification`] and
+//! gives it to a [`Notifier`]. Reminders and the TUI's timer use the
+//! backends chosen in preferences through [`notify_all`]; webhooks post
+//! theirs with [`crate::webhook::WebhookNotifier`] through
+//! [`BackgroundDeliveries`], so that a slow endpoint doesn't hold up the
+//! command that caused them.
+
+use crate::http::Request;
+use crate::preferences::{NotificationBackend, Preferences};
+use crate::util::shell_command;
+use crate::{Result, RstaskError};
+use std::io::Write;
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// A message to deliver
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    /// UUID of the task the notification is about, if any
+    pub task_uuid: Option<String>,
+    /// The whole event as JSON, for backends that post it
+    pub json: Option<String>,
+}
+
+impl Notification {
+    pub fn new(title: &str, body: &str) -> Self {
+        Notification {
+            title: title.to_string(),
+            body: body.to_string(),
+            task_uuid: None,
+            json: None,
+        }
+    }
+}
+
+/// A way of delivering notifications
+pub trait Notifier {
+    /// Name used in preferences and error messages
+    fn name(&self) -> &'static str;
+
+    fn notify(&self, notification: &Notification) -> Result<()>;
+}
+
+/// Desktop notification through the platform's notification service: D-Bus
+/// on Linux and the BSDs, Notification Center on macOS and toasts on Windows
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn notify(&self, notification: &Notification) -> Result<()> {
+        notify_rust::Notification::new()
+            .appname("rstask")
+            .summary(&notification.title)
+            .body(&notification.body)
+            .show()
+            .map(|_| ())
+            .map_err(|e| RstaskError::Other(format!("desktop notification failed: {}", e)))
+    }
+}
+
+/// Rings the terminal bell and prints the message on stderr
+pub struct BellNotifier;
+
+impl Notifier for BellNotifier {
+    fn name(&self) -> &'static str {
+        "bell"
+    }
+
+    fn notify(&self, notification: &Notification) -> Result<()> {
+        let mut stderr = std::io::stderr();
+        write!(stderr, "\x07{}", format_bell(notification))?;
+        stderr.flush()?;
+        Ok(())
+    }
+}
+
+/// Publishes to an ntfy topic URL such as https://ntfy.sh/my-tasks
+pub struct NtfyNotifier {
+    pub url: String,
+}
+
+impl Notifier for NtfyNotifier {
+    fn name(&self) -> &'static str {
+        "ntfy"
+    }
+
+    fn notify(&self, notification: &Notification) -> Result<()> {
+        let response = Request::new("POST", &self.url)
+            .header(format!("Title: {}", notification.title))
+            .header("Tags: rstask")
+            .body(notification.body.as_str())
+            .send()
+            .map_err(|e| RstaskError::Other(format!("ntfy notification failed: {}", e)))?;
+        if !response.is_success() {
+            return Err(RstaskError::Other(format!(
+                "ntfy notification failed: the server returned {}",
+                response.status
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Runs a shell command with the notification in environment variables
+pub struct CommandNotifier {
+    pub command: String,
+}
+
+impl Notifier for CommandNotifier {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn notify(&self, notification: &Notification) -> Result<()> {
+        let status = shell_command(&self.command)
+            .env("RSTASK_NOTIFY_TITLE", &notification.title)
+            .env("RSTASK_NOTIFY_BODY", &notification.body)
+            .env(
+                "RSTASK_NOTIFY_TASK",
+                notification.task_uuid.as_deref().unwrap_or(""),
+            )
+            .status()?;
+
+        if !status.success() {
+            return Err(RstaskError::Other(format!(
+                "notify command failed with {}",
+                status
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Builds the notifiers selected in preferences
+pub fn notifiers_from_preferences(prefs: &Preferences) -> Result<Vec<Box<dyn Notifier>>> {
+    prefs
+        .notification_backends()
+        .iter()
+        .map(|backend| -> Result<Box<dyn Notifier>> {
+            Ok(match backend {
+                NotificationBackend::Desktop => Box::new(DesktopNotifier),
+                NotificationBackend::Bell => Box::new(BellNotifier),
+                NotificationBackend::Ntfy => Box::new(NtfyNotifier {
+                    url: prefs.ntfy_url.clone().ok_or_else(|| {
+                        RstaskError::Other("ntfy backend needs ntfy_url to be set".to_string())
+                    })?,
+                }),
+                NotificationBackend::Command => Box::new(CommandNotifier {
+                    command: prefs.notify_command.clone().ok_or_else(|| {
+                        RstaskError::Other(
+                            "command backend needs notify_command to be set".to_string(),
+                        )
+                    })?,
+                }),
+            })
+        })
+        .collect()
+}
+
+/// Sends a notification through every configured backend
+///
+/// A failing backend doesn't stop the others; their errors are combined.
+pub fn notify_all(prefs: &Preferences, notification: &Notification) -> Result<()> {
+    let errors: Vec<String> = notifiers_from_preferences(prefs)?
+        .iter()
+        .filter_map(|n| {
+            n.notify(notification)
+                .err()
+                .map(|e| format!("{}: {}", n.name(), e))
+        })
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(RstaskError::Other(errors.join("; ")))
+    }
+}
+
+/// Deliveries running on threads of their own. The process waits for them
+/// with [`BackgroundDeliveries::wait`] before exiting, for a limited time.
+#[derive(Default)]
+pub struct BackgroundDeliveries {
+    threads: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl BackgroundDeliveries {
+    /// Sends a notification on a new thread, reporting failure on stderr
+    pub fn send(&self, notifier: Box<dyn Notifier + Send>, notification: Notification) {
+        let thread = thread::spawn(move || {
+            if let Err(e) = notifier.notify(&notification) {
+                eprintln!("warning: {} failed: {}", notifier.name(), e);
+            }
+        });
+        if let Ok(mut threads) = self.threads.lock() {
+            // Long-running loops such as the MCP server send many
+            threads.retain(|t| !t.is_finished());
+            threads.push(thread);
+        }
+    }
+
+    /// Waits until every delivery has finished or `deadline` has passed,
+    /// returning how many were still running
+    pub fn wait(&self, deadline: Instant) -> usize {
+        let Ok(mut threads) = self.threads.lock() else {
+            return 0;
+        };
+        while threads.iter().any(|t| !t.is_finished()) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(20));
+        }
+        let (finished, running): (Vec<_>, Vec<_>) =
+            threads.drain(..).partition(|t| t.is_finished());
+        for thread in finished {
+            let _ = thread.join();
+        }
+        running.len()
+    }
+}
+
+fn format_bell(notification: &Notification) -> String {
+    if notification.body.is_empty() {
+        format!("{}\n", notification.title)
+    } else {
+        format!("{}: {}\n", notification.title, notification.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_command_notifier() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let out = dir.path().join("out.txt");
+        let notifier = CommandNotifier {
+            command: format!(
+                "printf '%s|%s' \"$RSTASK_NOTIFY_TITLE\" \"$RSTASK_NOTIFY_BODY\" > {}",
+                out.display()
+            ),
+        };
+
+        notifier
+            .notify(&Notification::new("Due today", "Pay rent"))
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(out).unwrap(), "Due today|Pay rent");
+
+        let failing = CommandNotifier {
+            command: "exit 3".to_string(),
+        };
+        assert!(failing.notify(&Notification::default()).is_err());
+    }
+
+    #[test]
+    fn test_notifiers_from_preferences() {
+        let prefs = Preferences::default();
+        let names: Vec<&str> = notifiers_from_preferences(&prefs)
+            .unwrap()
+            .iter()
+            .map(|n| n.name())
+            .collect();
+        assert_eq!(names, vec!["bell"]);
+
+        let prefs = Preferences {
+            notification_backends: vec![NotificationBackend::Ntfy],
+            ..Default::default()
+        };
+        assert!(notifiers_from_preferences(&prefs).is_err());
+
+        let prefs = Preferences {
+            notification_backends: vec![NotificationBackend::Desktop, NotificationBackend::Ntfy],
+            ntfy_url: Some("https://ntfy.sh/tasks".to_string()),
+            ..Default::default()
+        };
+        let names: Vec<&str> = notifiers_from_preferences(&prefs)
+            .unwrap()
+            .iter()
+            .map(|n| n.name())
+            .collect();
+        assert_eq!(names, vec!["desktop", "ntfy"]);
+    }
+
+    #[test]
+    fn test_background_deliveries_are_waited_for() {
+        struct Slow(Duration);
+        impl Notifier for Slow {
+            fn name(&self) -> &'static str {
+                "slow"
+            }
+            fn notify(&self, _: &Notification) -> Result<()> {
+                thread::sleep(self.0);
+                Ok(())
+            }
+        }
+
+        let deliveries = BackgroundDeliveries::default();
+        let start = Instant::now();
+        deliveries.send(
+            Box::new(Slow(Duration::from_millis(50))),
+            Notification::default(),
+        );
+        deliveries.send(
+            Box::new(Slow(Duration::from_secs(10))),
+            Notification::default(),
+        );
+        // Sending doesn't wait
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        // The quick one finishes; the other is given up on at the deadline
+        assert_eq!(deliveries.wait(start + Duration::from_millis(300)), 1);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_format_bell() {
+        assert_eq!(
+            format_bell(&Notification::new("Overdue", "Pay rent")),
+            "Overdue: Pay rent\n"
+        );
+        assert_eq!(format_bell(&Notification::new("Synced", "")), "Synced\n");
+    }
+}
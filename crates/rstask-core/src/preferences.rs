@@ -1,12 +1,54 @@
+use crate::column::{Column, DEFAULT_COLUMNS, DEFAULT_RESOLVED_COLUMNS};
+use crate::constants::REPO_PREFERENCES_FILE;
+use crate::{Result, RstaskError};
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum SyncFrequency {
     Never,
     AfterEveryModification,
+    /// Only after tasks are resolved or removed
+    AfterResolveOrRemove,
+    /// After a change, at most once every `sync_interval_minutes`
+    Batched,
+    /// When the TUI exits
+    OnTuiExit,
+}
+
+/// What prompted an automatic sync
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyncTrigger {
+    /// Tasks were added or changed
+    Modification,
+    /// Tasks were resolved or removed
+    ResolveOrRemove,
+    /// The TUI is exiting
+    TuiExit,
+}
+
+impl SyncFrequency {
+    /// Decides whether a trigger should sync, given when the last sync happened
+    pub fn should_sync(
+        &self,
+        trigger: SyncTrigger,
+        last_sync: Option<DateTime<Utc>>,
+        interval_minutes: u64,
+    ) -> bool {
+        match self {
+            SyncFrequency::Never => false,
+            SyncFrequency::AfterEveryModification => trigger != SyncTrigger::TuiExit,
+            SyncFrequency::AfterResolveOrRemove => trigger == SyncTrigger::ResolveOrRemove,
+            SyncFrequency::OnTuiExit => trigger == SyncTrigger::TuiExit,
+            SyncFrequency::Batched => last_sync.is_none_or(|last| {
+                Utc::now() - last >= chrono::Duration::minutes(interval_minutes as i64)
+            }),
+        }
+    }
 }
 
 #[allow(clippy::derivable_impls)]
@@ -30,40 +72,959 @@ impl Default for BulkCommitStrategy {
     }
 }
 
+/// How much detail to print about the active context
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextVerbosity {
+    /// Never mention the context
+    Quiet,
+    /// A single line naming the active context
+    Normal,
+    /// Also count tasks hidden by the context and hint how to bypass it
+    Verbose,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for ContextVerbosity {
+    fn default() -> Self {
+        ContextVerbosity::Normal
+    }
+}
+
+/// How dates are shown in task lists
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DateStyle {
+    /// Calendar dates, e.g. "Mon 3" or "12 Jan"
+    Absolute,
+    /// Humanized offsets from now, e.g. "in 3d" or "2w ago"
+    Relative,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for DateStyle {
+    fn default() -> Self {
+        DateStyle::Absolute
+    }
+}
+
+/// Layout of JSON output when stdout isn't a terminal
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonStyle {
+    /// Indented, one field per line
+    Pretty,
+    /// Everything on a single line
+    Compact,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for JsonStyle {
+    fn default() -> Self {
+        JsonStyle::Pretty
+    }
+}
+
+/// How task lists are printed
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ListOutput {
+    /// A table on a terminal, JSON otherwise
+    #[default]
+    Full,
+    /// Just the number of tasks
+    Count,
+    /// One line per task with its ID and summary
+    Quiet,
+}
+
+/// Field that `next` and the other open task lists are ordered by. The
+/// descriptions give the ascending order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortField {
+    /// Highest priority first, oldest first within a priority
+    Priority,
+    /// Soonest due first. Tasks without a due date come last either way
+    Due,
+    /// Oldest first
+    Created,
+    /// Least recently changed first
+    Modified,
+    /// Least urgent first
+    Urgency,
+}
+
+/// Order of tasks in `next` and the other open task lists, written as the
+/// field with an optional `:asc` or `:desc`, e.g. `due:desc`
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(try_from = "String")]
+pub struct TaskSort {
+    pub field: SortField,
+    /// Largest first. Priority P0 counts as the smallest
+    pub descending: bool,
+}
+
+impl TaskSort {
+    pub const PRIORITY: TaskSort = TaskSort {
+        field: SortField::Priority,
+        descending: false,
+    };
+}
+
+impl Default for TaskSort {
+    fn default() -> Self {
+        TaskSort::PRIORITY
+    }
+}
+
+impl std::str::FromStr for TaskSort {
+    type Err = RstaskError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let lower = s.trim().to_lowercase();
+        let (field, direction) = match lower.split_once(':') {
+            Some((field, direction)) => (field, Some(direction)),
+            None => (lower.as_str(), None),
+        };
+        let field = match field {
+            "priority" => SortField::Priority,
+            "due" => SortField::Due,
+            "created" => SortField::Created,
+            "modified" => SortField::Modified,
+            "urgency" => SortField::Urgency,
+            _ => {
+                return Err(RstaskError::Parse(format!(
+                    "unknown sort: {}\nValid sorts are: priority, due, created, modified, urgency, optionally followed by :asc or :desc",
+                    s
+                )));
+            }
+        };
+        // Urgency is the one field whose usual order is descending
+        let descending = match direction {
+            None => field == SortField::Urgency,
+            Some("asc") => false,
+            Some("desc") => true,
+            Some(other) => {
+                return Err(RstaskError::Parse(format!(
+                    "unknown sort direction: {}\nUse asc or desc",
+                    other
+                )));
+            }
+        };
+        Ok(TaskSort { field, descending })
+    }
+}
+
+impl TryFrom<String> for TaskSort {
+    type Error = RstaskError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+/// File format task files are written in. Both are always read
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskFormat {
+    /// YAML frontmatter with the notes as the Markdown body
+    Md,
+    /// Plain YAML, as written by dstask and older versions of rstask
+    Yml,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for TaskFormat {
+    fn default() -> Self {
+        TaskFormat::Md
+    }
+}
+
+impl TaskFormat {
+    /// File extension, and name as written in the config file
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskFormat::Md => "md",
+            TaskFormat::Yml => "yml",
+        }
+    }
+}
+
+impl std::str::FromStr for TaskFormat {
+    type Err = RstaskError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "md" => Ok(TaskFormat::Md),
+            "yml" | "yaml" => Ok(TaskFormat::Yml),
+            _ => Err(RstaskError::Parse(format!(
+                "unknown task format: {}\nValid formats are: md, yml",
+                s
+            ))),
+        }
+    }
+}
+
+/// Colour scheme of the interactive TUI
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TuiTheme {
+    /// The regular colours
+    Default,
+    /// Darker colours that stay readable on a light terminal background
+    Light,
+    /// Bright colours only, with the selection shown in reverse video
+    HighContrast,
+    /// No colours at all; state is shown with labels and reverse video
+    Monochrome,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for TuiTheme {
+    fn default() -> Self {
+        TuiTheme::Default
+    }
+}
+
+impl TuiTheme {
+    /// Name as written in the config file
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TuiTheme::Default => "default",
+            TuiTheme::Light => "light",
+            TuiTheme::HighContrast => "high_contrast",
+            TuiTheme::Monochrome => "monochrome",
+        }
+    }
+
+    /// The theme after this one, for cycling through them
+    pub fn next(&self) -> Self {
+        match self {
+            TuiTheme::Default => TuiTheme::Light,
+            TuiTheme::Light => TuiTheme::HighContrast,
+            TuiTheme::HighContrast => TuiTheme::Monochrome,
+            TuiTheme::Monochrome => TuiTheme::Default,
+        }
+    }
+}
+
+/// Colours that replace the TUI's built-in ones. Each is a colour name such
+/// as `dark_gray`, a 256-colour index or `#rrggbb`; unset ones keep the
+/// theme's colour
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct TuiColors {
+    /// Background of the selected row
+    pub selection: Option<String>,
+    /// Borders of the task list, sidebar and detail panes
+    pub border: Option<String>,
+    pub p0: Option<String>,
+    pub p1: Option<String>,
+    pub p2: Option<String>,
+    pub p3: Option<String>,
+    pub active: Option<String>,
+    pub paused: Option<String>,
+    pub pending: Option<String>,
+    pub resolved: Option<String>,
+    /// Background of text matching the filter
+    pub search_match: Option<String>,
+}
+
+/// Calendar component used when exporting tasks to iCalendar
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IcalComponent {
+    /// VTODO entries, shown by clients with task support
+    Todo,
+    /// All-day VEVENT entries on the due date, shown by any calendar
+    Event,
+}
+
+#[allow(clippy::derivable_impls)]
+impl Default for IcalComponent {
+    fn default() -> Self {
+        IcalComponent::Todo
+    }
+}
+
+/// A way of delivering reminders and other notifications
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationBackend {
+    /// Desktop notification through the platform's notification service
+    Desktop,
+    /// Terminal bell plus a message on stderr
+    Bell,
+    /// POST to the ntfy topic in `ntfy_url`
+    Ntfy,
+    /// Run `notify_command` with the message in environment variables
+    Command,
+}
+
+/// Something that happened to a task which webhooks can subscribe to
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    Added,
+    Resolved,
+    Started,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::Added => "added",
+            WebhookEvent::Resolved => "resolved",
+            WebhookEvent::Started => "started",
+        }
+    }
+}
+
+/// A URL that task events are POSTed to as JSON
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Webhook {
+    pub url: String,
+    /// Events to send. Empty means all of them
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+impl Webhook {
+    pub fn wants(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Preferences {
     #[serde(default)]
     pub sync_frequency: SyncFrequency,
+    /// Minimum gap between syncs for the batched sync frequency
+    #[serde(default = "default_sync_interval_minutes")]
+    pub sync_interval_minutes: u64,
     #[serde(default)]
     pub bulk_commit_strategy: BulkCommitStrategy,
+    /// Columns shown in task tables, in order. Empty means the built-in defaults
+    #[serde(default)]
+    pub columns: Vec<Column>,
+    /// Columns shown by `show-resolved`, which has a resolved date where
+    /// other tables have an ID. Empty means the built-in defaults
+    #[serde(default)]
+    pub resolved_columns: Vec<Column>,
+    #[serde(default)]
+    pub context_verbosity: ContextVerbosity,
+    #[serde(default)]
+    pub date_style: DateStyle,
+    #[serde(default)]
+    pub json_style: JsonStyle,
+    #[serde(default)]
+    pub sort: TaskSort,
+    /// Most tasks `next` and the show commands list
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Set by `--count` and `--quiet` for one command, never read from the
+    /// config
+    #[serde(skip)]
+    pub list_output: ListOutput,
+    #[serde(default)]
+    pub task_format: TaskFormat,
+    #[serde(default)]
+    pub tui_theme: TuiTheme,
+    #[serde(default)]
+    pub tui_colors: TuiColors,
+    /// How long the TUI shows a message before clearing it; 0 keeps it
+    /// until the next one
+    #[serde(default = "default_tui_status_seconds")]
+    pub tui_status_seconds: u64,
+    /// The same for errors, which stay up longer by default
+    #[serde(default = "default_tui_error_seconds")]
+    pub tui_error_seconds: u64,
+    /// How many days back the TUI's Resolved tab goes. Unset shows every
+    /// resolved task
+    #[serde(default)]
+    pub tui_resolved_days: Option<u32>,
+    /// Length of the TUI's pomodoro timer
+    #[serde(default = "default_tui_pomodoro_minutes")]
+    pub tui_pomodoro_minutes: u64,
+    /// Filters that `tui --dashboard` cycles through, e.g.
+    /// `("+team P0" "project:website")`. Empty means all open tasks
+    #[serde(default)]
+    pub dashboard_views: Vec<String>,
+    /// How long each dashboard view stays up
+    #[serde(default = "default_dashboard_cycle_seconds")]
+    pub dashboard_cycle_seconds: u64,
+    /// How often the dashboard syncs with the remote
+    #[serde(default = "default_dashboard_sync_minutes")]
+    pub dashboard_sync_minutes: u64,
+    #[serde(default)]
+    pub ical_component: IcalComponent,
+    /// Path inside the repository to regenerate an iCalendar export at on every commit
+    #[serde(default)]
+    pub ical_export_path: Option<String>,
+    /// Include notes in that export. Never done while notes are encrypted.
+    #[serde(default)]
+    pub ical_export_notes: bool,
+    /// CalDAV collection that `caldav-sync` mirrors open tasks to
+    #[serde(default)]
+    pub caldav_url: Option<String>,
+    #[serde(default)]
+    pub caldav_username: Option<String>,
+    /// Jira site used by `jira import`, such as https://example.atlassian.net
+    #[serde(default)]
+    pub jira_url: Option<String>,
+    /// Account for basic auth on Jira Cloud; without it the token is sent as
+    /// a bearer token
+    #[serde(default)]
+    pub jira_username: Option<String>,
+    /// Transition, or status, applied to the linked issue when a task is resolved
+    #[serde(default)]
+    pub jira_done_transition: Option<String>,
+    /// Notification backends, in order. Empty means the terminal bell
+    #[serde(default)]
+    pub notification_backends: Vec<NotificationBackend>,
+    #[serde(default)]
+    pub ntfy_url: Option<String>,
+    #[serde(default)]
+    pub notify_command: Option<String>,
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+    /// How far ahead `remind` looks for due tasks
+    #[serde(default = "default_remind_horizon_minutes")]
+    pub remind_horizon_minutes: u64,
+    /// How long `remind` waits before notifying about the same task again
+    #[serde(default = "default_remind_snooze_minutes")]
+    pub remind_snooze_minutes: u64,
+    /// Days before the due date that a task counts as due soon, by priority,
+    /// e.g. `{P0 3, P2 0}`. Priorities left out use `remind_horizon_minutes`
+    #[serde(default)]
+    pub remind_lead_days: HashMap<String, u32>,
+    /// Look for a new release once a week and mention it after a command.
+    /// Off unless asked for, as the check can hold a command up for a few
+    /// seconds on a slow network
+    #[serde(default)]
+    pub update_check: bool,
+    /// Count how often each command is run, for `rstask usage`. The counts
+    /// stay in the local state file
+    #[serde(default)]
+    pub usage_stats: bool,
+    /// Leave tasks delegated to someone else out of `next` unless the
+    /// query asks for them with `+delegated`
+    #[serde(default = "default_next_hides_delegated")]
+    pub next_hides_delegated: bool,
+    /// Add the tasks due from scheduled templates before `next`, as
+    /// `rstask tick` does
+    #[serde(default = "default_tick_before_next")]
+    pub tick_before_next: bool,
+    /// How long a task can go unchanged before `review` calls it stale
+    #[serde(default = "default_review_stale_days")]
+    pub review_stale_days: u32,
+    /// Effort you can get through in a day, such as "6h". `next` warns
+    /// when the estimates of tasks due today add up to more
+    #[serde(default)]
+    pub daily_capacity: Option<String>,
+    /// How long a task can go unchanged before `show-stale` lists it
+    #[serde(default = "default_stale_days")]
+    pub stale_days: u32,
+    /// Mention in `next` how many tasks have gone `stale_days` unchanged
+    #[serde(default)]
+    pub stale_banner: bool,
+    /// Fail commands when a task file can't be read, instead of skipping it
+    #[serde(default)]
+    pub strict_load: bool,
+    /// How long to wait for another rstask process to finish saving
+    #[serde(default = "default_lock_timeout_seconds")]
+    pub lock_timeout_seconds: u64,
+    /// age identity file that notes are encrypted with
+    pub notes_key_file: Option<String>,
+    /// Write tasks exactly as dstask does, for sharing a repository with it
+    #[serde(default)]
+    pub dstask_compat: bool,
+    /// Commands of your own, each a command followed by query words, e.g.
+    /// `{inbox show-unorganised, today "next due:today"}`
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+    /// Project given to new tasks that don't name one
+    pub default_project: Option<String>,
+    /// How long the ID of a resolved or deleted task is kept from other
+    /// tasks. 0 reuses IDs straight away
+    #[serde(default)]
+    pub id_grace_minutes: u64,
+    /// Starting a task pauses any other active task
+    #[serde(default)]
+    pub single_active_task: bool,
+    /// Editor command line, such as `code --wait {file}`. Overrides $VISUAL
+    /// and $EDITOR
+    pub editor: Option<String>,
+}
+
+/// Whether a config that didn't parse has a `notes_key_file` line, leaving
+/// out comments that mention it
+fn sets_notes_key(config: &str) -> bool {
+    config.lines().any(|line| {
+        line.trim_start()
+            .strip_prefix("notes_key_file")
+            .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+    })
+}
+
+/// Settings a task repository overrides for everyone using it, read from
+/// `.rstask.toml` at its root
+#[derive(Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RepoPreferences {
+    pub bulk_commit_strategy: Option<BulkCommitStrategy>,
+    pub default_project: Option<String>,
+    pub columns: Option<Vec<Column>>,
+    pub resolved_columns: Option<Vec<Column>>,
+}
+
+impl RepoPreferences {
+    pub fn parse(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| RstaskError::Parse(e.message().to_string()))
+    }
+}
+
+fn default_sync_interval_minutes() -> u64 {
+    15
+}
+
+fn default_tui_status_seconds() -> u64 {
+    5
+}
+
+fn default_tui_error_seconds() -> u64 {
+    15
+}
+
+fn default_tui_pomodoro_minutes() -> u64 {
+    25
+}
+
+fn default_dashboard_cycle_seconds() -> u64 {
+    30
+}
+
+fn default_dashboard_sync_minutes() -> u64 {
+    5
+}
+
+fn default_remind_horizon_minutes() -> u64 {
+    60
+}
+
+fn default_remind_snooze_minutes() -> u64 {
+    120
+}
+
+fn default_next_hides_delegated() -> bool {
+    true
+}
+
+fn default_tick_before_next() -> bool {
+    true
+}
+
+fn default_review_stale_days() -> u32 {
+    14
+}
+
+fn default_stale_days() -> u32 {
+    30
+}
+
+fn default_lock_timeout_seconds() -> u64 {
+    10
 }
 
 impl Default for Preferences {
     fn default() -> Self {
         Preferences {
             sync_frequency: SyncFrequency::Never,
+            sync_interval_minutes: default_sync_interval_minutes(),
             bulk_commit_strategy: BulkCommitStrategy::PerTask,
+            columns: Vec::new(),
+            resolved_columns: Vec::new(),
+            context_verbosity: ContextVerbosity::Normal,
+            date_style: DateStyle::Absolute,
+            json_style: JsonStyle::Pretty,
+            sort: TaskSort::PRIORITY,
+            limit: None,
+            list_output: ListOutput::Full,
+            task_format: TaskFormat::Md,
+            tui_theme: TuiTheme::Default,
+            tui_colors: TuiColors::default(),
+            tui_status_seconds: default_tui_status_seconds(),
+            tui_error_seconds: default_tui_error_seconds(),
+            tui_resolved_days: None,
+            tui_pomodoro_minutes: default_tui_pomodoro_minutes(),
+            dashboard_views: Vec::new(),
+            dashboard_cycle_seconds: default_dashboard_cycle_seconds(),
+            dashboard_sync_minutes: default_dashboard_sync_minutes(),
+            ical_component: IcalComponent::Todo,
+            ical_export_path: None,
+            ical_export_notes: false,
+            caldav_url: None,
+            caldav_username: None,
+            jira_url: None,
+            jira_username: None,
+            jira_done_transition: None,
+            notification_backends: Vec::new(),
+            ntfy_url: None,
+            notify_command: None,
+            webhooks: Vec::new(),
+            remind_horizon_minutes: default_remind_horizon_minutes(),
+            remind_snooze_minutes: default_remind_snooze_minutes(),
+            remind_lead_days: HashMap::new(),
+            update_check: false,
+            usage_stats: false,
+            next_hides_delegated: default_next_hides_delegated(),
+            tick_before_next: default_tick_before_next(),
+            review_stale_days: default_review_stale_days(),
+            daily_capacity: None,
+            stale_days: default_stale_days(),
+            stale_banner: false,
+            strict_load: false,
+            lock_timeout_seconds: default_lock_timeout_seconds(),
+            notes_key_file: None,
+            dstask_compat: false,
+            aliases: HashMap::new(),
+            default_project: None,
+            id_grace_minutes: 0,
+            single_active_task: false,
+            editor: None,
         }
     }
 }
 
 impl Preferences {
     pub fn config_path() -> Option<PathBuf> {
-        dirs::config_dir().map(|config_dir| config_dir.join("rstask").join("config.styx"))
+        let path = crate::config::config_dir().join("config.styx");
+        match crate::config::legacy_config_dir() {
+            Some(legacy) => Some(crate::config::migrate(&legacy.join("config.styx"), &path)),
+            None => Some(path),
+        }
+    }
+
+    /// Columns for task tables, falling back to the defaults
+    pub fn table_columns(&self) -> &[Column] {
+        if self.columns.is_empty() {
+            DEFAULT_COLUMNS
+        } else {
+            &self.columns
+        }
+    }
+
+    /// Columns for the resolved tasks report, falling back to the defaults
+    pub fn resolved_table_columns(&self) -> &[Column] {
+        if self.resolved_columns.is_empty() {
+            DEFAULT_RESOLVED_COLUMNS
+        } else {
+            &self.resolved_columns
+        }
+    }
+
+    /// When a task with the given priority and due date starts counting as
+    /// due soon, for reminders and highlighting
+    pub fn due_soon_from(&self, priority: &str, due: DateTime<Utc>) -> DateTime<Utc> {
+        match self.remind_lead_days.get(&priority.to_uppercase()) {
+            Some(&days) => {
+                let day = crate::date_util::start_of_day(due.with_timezone(&chrono::Local))
+                    - chrono::Days::new(days as u64);
+                crate::date_util::start_of_day(day).with_timezone(&Utc)
+            }
+            None => due - chrono::Duration::minutes(self.remind_horizon_minutes as i64),
+        }
+    }
+
+    /// The words an alias stands for, if `name` is one
+    pub fn alias(&self, name: &str) -> Option<Vec<String>> {
+        let words: Vec<String> = self
+            .aliases
+            .get(name)?
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        (!words.is_empty()).then_some(words)
+    }
+
+    /// Notification backends, falling back to the terminal bell
+    pub fn notification_backends(&self) -> &[NotificationBackend] {
+        if self.notification_backends.is_empty() {
+            &[NotificationBackend::Bell]
+        } else {
+            &self.notification_backends
+        }
     }
 
     /// Load preferences from config file, or return default if file doesn't exist
-    pub fn load() -> Self {
+    ///
+    /// A config that doesn't parse falls back to the defaults, unless it sets
+    /// `notes_key_file`: carrying on without the key would write notes in
+    /// plain text, so that is an error.
+    pub fn load() -> Result<Self> {
         let config_path = match Self::config_path() {
             Some(path) => path,
-            None => return Self::default(),
+            None => return Ok(Self::default()),
         };
 
-        let config_content = match fs::read_to_string(config_path) {
+        let config_content = match fs::read_to_string(&config_path) {
             Ok(content) => content,
-            Err(_) => return Self::default(),
+            Err(_) => return Ok(Self::default()),
+        };
+
+        match serde_styx::from_str(&config_content) {
+            Ok(prefs) => Ok(prefs),
+            Err(e) if sets_notes_key(&config_content) => Err(RstaskError::Parse(format!(
+                "couldn't read {}, which sets notes_key_file: {}",
+                config_path.display(),
+                e
+            ))),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Applies the overrides in the repository's `.rstask.toml`, if it has
+    /// one. A file that can't be read is reported and ignored
+    pub fn apply_repo_file(&mut self, repo: &Path) {
+        let path = repo.join(REPO_PREFERENCES_FILE);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return;
+        };
+        match RepoPreferences::parse(&content) {
+            Ok(repo_prefs) => self.apply_repo(repo_prefs),
+            Err(e) => eprintln!("Warning: ignoring {}: {}", path.display(), e),
+        }
+    }
+
+    fn apply_repo(&mut self, repo_prefs: RepoPreferences) {
+        if let Some(strategy) = repo_prefs.bulk_commit_strategy {
+            self.bulk_commit_strategy = strategy;
+        }
+        if let Some(project) = repo_prefs.default_project {
+            self.default_project = Some(project);
+        }
+        if let Some(columns) = repo_prefs.columns {
+            self.columns = columns;
+        }
+        if let Some(columns) = repo_prefs.resolved_columns {
+            self.resolved_columns = columns;
+        }
+    }
+
+    /// Writes a single top-level setting to the config file, replacing any
+    /// existing value and leaving the rest of the file untouched
+    pub fn save_setting(key: &str, value: &str) -> Result<()> {
+        let config_path = Self::config_path()
+            .ok_or_else(|| RstaskError::Other("no config directory found".to_string()))?;
+        let content = fs::read_to_string(&config_path).unwrap_or_default();
+
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&config_path, set_setting(&content, key, value))?;
+        Ok(())
+    }
+}
+
+/// Replaces the `key value` line in styx config text, or appends one
+fn set_setting(content: &str, key: &str, value: &str) -> String {
+    let line = format!("{} {}", key, value);
+    let mut found = false;
+    let mut lines: Vec<String> = content
+        .lines()
+        .map(|l| {
+            let is_key =
+                l.split_whitespace().next() == Some(key) && !l.starts_with(char::is_whitespace);
+            if is_key && !found {
+                found = true;
+                line.clone()
+            } else {
+                l.to_string()
+            }
+        })
+        .collect();
+
+    if !found {
+        lines.push(line);
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_sync() {
+        use SyncTrigger::*;
+
+        let recent = Some(Utc::now() - chrono::Duration::minutes(5));
+        let stale = Some(Utc::now() - chrono::Duration::minutes(30));
+
+        assert!(!SyncFrequency::Never.should_sync(ResolveOrRemove, None, 15));
+        assert!(SyncFrequency::AfterEveryModification.should_sync(Modification, recent, 15));
+        assert!(!SyncFrequency::AfterEveryModification.should_sync(TuiExit, None, 15));
+        assert!(!SyncFrequency::AfterResolveOrRemove.should_sync(Modification, None, 15));
+        assert!(SyncFrequency::AfterResolveOrRemove.should_sync(ResolveOrRemove, recent, 15));
+        assert!(SyncFrequency::OnTuiExit.should_sync(TuiExit, recent, 15));
+        assert!(!SyncFrequency::OnTuiExit.should_sync(Modification, None, 15));
+        assert!(SyncFrequency::Batched.should_sync(Modification, None, 15));
+        assert!(!SyncFrequency::Batched.should_sync(Modification, recent, 15));
+        assert!(SyncFrequency::Batched.should_sync(TuiExit, stale, 15));
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        let sort: TaskSort = "Due:desc".parse().unwrap();
+        assert_eq!(sort.field, SortField::Due);
+        assert!(sort.descending);
+        let sort: TaskSort = "urgency".parse().unwrap();
+        assert!(sort.descending);
+        let sort: TaskSort = "urgency:asc".parse().unwrap();
+        assert!(!sort.descending);
+        assert!("due:sideways".parse::<TaskSort>().is_err());
+
+        let prefs: Preferences = serde_styx::from_str("sort created:desc\nlimit 10").unwrap();
+        assert_eq!(prefs.sort.field, SortField::Created);
+        assert!(prefs.sort.descending);
+        assert_eq!(prefs.limit, Some(10));
+    }
+
+    #[test]
+    fn test_sync_preferences_from_styx() {
+        let prefs: Preferences =
+            serde_styx::from_str("sync_frequency batched\nsync_interval_minutes 30").unwrap();
+        assert_eq!(prefs.sync_frequency, SyncFrequency::Batched);
+        assert_eq!(prefs.sync_interval_minutes, 30);
+
+        let prefs: Preferences = serde_styx::from_str("sync_frequency on_tui_exit").unwrap();
+        assert_eq!(prefs.sync_interval_minutes, 15);
+    }
+
+    #[test]
+    fn test_due_soon_from() {
+        use chrono::{Local, TimeZone};
+
+        let prefs: Preferences =
+            serde_styx::from_str("remind_lead_days {P0 3, P2 0}\nremind_horizon_minutes 90")
+                .unwrap();
+        let due = Local
+            .with_ymd_and_hms(2025, 3, 10, 0, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            prefs.due_soon_from("P0", due),
+            Local
+                .with_ymd_and_hms(2025, 3, 7, 0, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        );
+        assert_eq!(prefs.due_soon_from("P2", due), due);
+        assert_eq!(
+            prefs.due_soon_from("P1", due),
+            due - chrono::Duration::minutes(90)
+        );
+    }
+
+    #[test]
+    fn test_repo_preferences() {
+        let mut prefs = Preferences::default();
+        let repo_prefs = RepoPreferences::parse(
+            "bulk_commit_strategy = \"single\"\ndefault_project = \"website\"\ncolumns = [\"id\", \"summary\"]\n",
+        )
+        .unwrap();
+        prefs.apply_repo(repo_prefs);
+        assert_eq!(prefs.bulk_commit_strategy, BulkCommitStrategy::Single);
+        assert_eq!(prefs.default_project.as_deref(), Some("website"));
+        assert_eq!(prefs.columns, vec![Column::Id, Column::Summary]);
+
+        // Only the settings a repository may override
+        assert!(RepoPreferences::parse("sync_frequency = \"never\"").is_err());
+    }
+
+    #[test]
+    fn test_resolved_columns_are_separate() {
+        let mut prefs = Preferences {
+            columns: vec![Column::Id, Column::Summary],
+            ..Default::default()
         };
+        assert_eq!(prefs.table_columns(), &[Column::Id, Column::Summary]);
+        assert_eq!(prefs.resolved_table_columns(), DEFAULT_RESOLVED_COLUMNS);
+
+        prefs.resolved_columns = vec![Column::Resolved, Column::Summary];
+        assert_eq!(
+            prefs.resolved_table_columns(),
+            &[Column::Resolved, Column::Summary]
+        );
+    }
+
+    #[test]
+    fn test_aliases_from_styx() {
+        let prefs: Preferences =
+            serde_styx::from_str("aliases {inbox show-unorganised, today \"next  due:today\"}")
+                .unwrap();
+        assert_eq!(prefs.alias("inbox").unwrap(), vec!["show-unorganised"]);
+        assert_eq!(prefs.alias("today").unwrap(), vec!["next", "due:today"]);
+        assert_eq!(prefs.alias("next"), None);
+    }
+
+    #[test]
+    fn test_webhooks_from_styx() {
+        let prefs: Preferences = serde_styx::from_str(
+            "webhooks (\n  {url https://example.com/a, events (added resolved)}\n  {url https://example.com/b}\n)",
+        )
+        .unwrap();
+        assert_eq!(prefs.webhooks.len(), 2);
+        assert!(prefs.webhooks[0].wants(WebhookEvent::Resolved));
+        assert!(!prefs.webhooks[0].wants(WebhookEvent::Started));
+        assert!(prefs.webhooks[1].wants(WebhookEvent::Started));
+    }
+
+    #[test]
+    fn test_dashboard_views_from_styx() {
+        let prefs: Preferences =
+            serde_styx::from_str("dashboard_views (\"+team P0\" project:website)").unwrap();
+        assert_eq!(prefs.dashboard_views, vec!["+team P0", "project:website"]);
+        assert_eq!(prefs.dashboard_cycle_seconds, 30);
+    }
+
+    #[test]
+    fn test_tui_colors_from_styx() {
+        let prefs: Preferences = serde_styx::from_str(
+            "tui_theme light\ntui_colors {\n  selection \"#d0d0d0\"\n  p0 red\n}",
+        )
+        .unwrap();
+        assert_eq!(prefs.tui_theme, TuiTheme::Light);
+        assert_eq!(prefs.tui_colors.selection.as_deref(), Some("#d0d0d0"));
+        assert_eq!(prefs.tui_colors.p0.as_deref(), Some("red"));
+        assert_eq!(prefs.tui_colors.border, None);
+    }
+
+    #[test]
+    fn test_set_setting() {
+        assert_eq!(
+            set_setting("", "tui_theme", "monochrome"),
+            "tui_theme monochrome\n"
+        );
+        assert_eq!(
+            set_setting(
+                "date_style relative\ntui_theme default\n",
+                "tui_theme",
+                "high_contrast"
+            ),
+            "date_style relative\ntui_theme high_contrast\n"
+        );
+        // Nested keys with the same name are left alone
+        assert_eq!(
+            set_setting("webhooks (\n  tui_theme x\n)", "tui_theme", "monochrome"),
+            "webhooks (\n  tui_theme x\n)\ntui_theme monochrome\n"
+        );
 
-        serde_styx::from_str(&config_content).unwrap_or_default()
+        let prefs: Preferences =
+            serde_styx::from_str(&set_setting("", "tui_theme", "high_contrast")).unwrap();
+        assert_eq!(prefs.tui_theme, TuiTheme::HighContrast);
     }
 }
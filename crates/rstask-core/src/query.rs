@@ -1,12 +1,15 @@
 use crate::Result;
 use crate::constants::*;
-use crate::date_util::parse_due_date_arg;
+use crate::date_util::{format_minutes, parse_due_date_arg, parse_duration_minutes};
+use crate::preferences::ContextVerbosity;
+use crate::schedule::Schedule;
 use crate::util::slice_contains;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Query {
     pub cmd: String,
     pub ids: Vec<i32>,
@@ -15,7 +18,6 @@ pub struct Query {
     pub project: String,
     pub anti_projects: Vec<String>,
     #[serde(with = "chrono::serde::ts_seconds_option")]
-    #[serde(default)]
     pub due: Option<DateTime<Utc>>,
     pub date_filter: String,
     pub priority: String,
@@ -23,6 +25,16 @@ pub struct Query {
     pub text: String,
     pub ignore_context: bool,
     pub note: String,
+    /// Set by +delegated (true) or -delegated (false)
+    pub delegated: Option<bool>,
+    /// Effort in minutes from estimate:<duration>, where 0 removes it
+    pub estimate: Option<u32>,
+    /// Template schedule from schedule:<when>, where an empty string from
+    /// schedule:none removes it
+    pub schedule: Option<String>,
+    /// Text the summary and notes must not contain, from not:<text> or
+    /// -"<text>"
+    pub anti_text: Vec<String>,
 }
 
 impl Query {
@@ -32,8 +44,12 @@ impl Query {
     }
 
     /// Prints context description with color
-    pub fn print_context_description(&self) {
-        let env_var_notification = if std::env::var("RSTASK_CONTEXT").is_ok() {
+    pub fn print_context_description(&self, verbosity: ContextVerbosity) {
+        if verbosity == ContextVerbosity::Quiet {
+            return;
+        }
+
+        let env_var_notification = if std::env::var("RSTASK_CONTEXT").is_ok_and(|v| !v.is_empty()) {
             " (set by RSTASK_CONTEXT)"
         } else {
             ""
@@ -58,6 +74,9 @@ impl Query {
             || !self.date_filter.is_empty()
             || !self.priority.is_empty()
             || self.template > 0
+            || self.delegated.is_some()
+            || self.estimate.is_some()
+            || self.schedule.is_some()
     }
 
     /// Merges another query into this one, used for applying context
@@ -83,6 +102,12 @@ impl Query {
             q.project = q2.project.clone();
         }
 
+        for text in &q2.anti_text {
+            if !q.anti_text.contains(text) {
+                q.anti_text.push(text.clone());
+            }
+        }
+
         if q2.due.is_some() {
             if q.due.is_some() && q.due != q2.due {
                 panic!("Could not apply context, date filter conflict");
@@ -98,10 +123,33 @@ impl Query {
             q.priority = q2.priority.clone();
         }
 
+        if q.delegated.is_none() {
+            q.delegated = q2.delegated;
+        }
+
         q
     }
 }
 
+/// Expands a list of IDs and ID ranges, such as `1,4,9` or `3-7`. Returns
+/// None if `item` isn't one, as with `2025-06-18` or a backwards range
+fn parse_id_list(item: &str) -> Option<Vec<i32>> {
+    if !item.contains([',', '-']) {
+        return None;
+    }
+
+    let mut ids = Vec::new();
+    for part in item.split(',') {
+        let (from, to) = part.split_once('-').unwrap_or((part, part));
+        let (from, to) = (from.parse::<i32>().ok()?, to.parse::<i32>().ok()?);
+        if from < 1 || to < from || (to - from) as usize >= MAX_TASKS_OPEN {
+            return None;
+        }
+        ids.extend(from..=to);
+    }
+    Some(ids)
+}
+
 /// Parses command line arguments into a Query
 pub fn parse_query(args: &[String]) -> Result<Query> {
     let mut query = Query::new();
@@ -130,6 +178,10 @@ pub fn parse_query(args: &[String]) -> Result<Query> {
             query.ids.push(id);
             continue;
         }
+        if !ids_exhausted && let Some(ids) = parse_id_list(item) {
+            query.ids.extend(ids);
+            continue;
+        }
 
         // Check for special keywords
         if item == IGNORE_CONTEXT_KEYWORD {
@@ -156,10 +208,31 @@ pub fn parse_query(args: &[String]) -> Result<Query> {
             query.date_filter = date_filter;
             query.due = Some(due_date.with_timezone(&Utc));
             due_date_set = true;
+        } else if let Some(estimate) = lc_item.strip_prefix("estimate:") {
+            query.estimate = Some(match estimate {
+                "0" => 0,
+                _ => parse_duration_minutes(estimate)?,
+            });
+        } else if let Some(schedule) = lc_item.strip_prefix("schedule:") {
+            query.schedule = Some(match schedule {
+                "none" => String::new(),
+                _ => Schedule::parse(schedule)?.to_string(),
+            });
         } else if let Some(template_str) = lc_item.strip_prefix("template:") {
             if let Ok(template_id) = template_str.parse::<i32>() {
                 query.template = template_id;
             }
+        } else if let Some(text) = lc_item
+            .strip_prefix("not:")
+            .or_else(|| lc_item.strip_prefix("-\"")?.strip_suffix('"'))
+        {
+            if !text.is_empty() {
+                query.anti_text.push(text.to_string());
+            }
+        } else if lc_item.strip_prefix('+') == Some(DELEGATED_KEYWORD) {
+            query.delegated = Some(true);
+        } else if lc_item.strip_prefix('-') == Some(DELEGATED_KEYWORD) {
+            query.delegated = Some(false);
         } else if let Some(tag) = lc_item.strip_prefix('+') {
             if !tag.is_empty() {
                 query.tags.push(tag.to_string());
@@ -200,6 +273,12 @@ impl fmt::Display for Query {
             args.push(format!("-{}", tag));
         }
 
+        match self.delegated {
+            Some(true) => args.push(format!("+{}", DELEGATED_KEYWORD)),
+            Some(false) => args.push(format!("-{}", DELEGATED_KEYWORD)),
+            None => {}
+        }
+
         if !self.project.is_empty() {
             args.push(format!("project:{}", self.project));
         }
@@ -227,6 +306,27 @@ impl fmt::Display for Query {
             args.push(format!("template:{}", self.template));
         }
 
+        if let Some(estimate) = self.estimate {
+            args.push(format!("estimate:{}", format_minutes(estimate)));
+        }
+
+        match self.schedule.as_deref() {
+            Some("") => args.push("schedule:none".to_string()),
+            Some(schedule) if schedule.contains(' ') => {
+                args.push(format!("\"schedule:{}\"", schedule))
+            }
+            Some(schedule) => args.push(format!("schedule:{}", schedule)),
+            None => {}
+        }
+
+        for text in &self.anti_text {
+            if text.contains(' ') {
+                args.push(format!("\"not:{}\"", text));
+            } else {
+                args.push(format!("not:{}", text));
+            }
+        }
+
         if !self.text.is_empty() {
             args.push(format!("\"{}\"", self.text));
         }
@@ -273,6 +373,60 @@ mod tests {
         assert_eq!(query.text, "have an adventure");
     }
 
+    #[test]
+    fn test_parse_query_delegated() {
+        let args = vec![
+            "next".to_string(),
+            "+delegated".to_string(),
+            "+work".to_string(),
+        ];
+        let query = parse_query(&args).unwrap();
+
+        assert_eq!(query.delegated, Some(true));
+        assert_eq!(query.tags, vec!["work"]);
+        assert_eq!(query.to_string(), "+work +delegated");
+
+        let query = parse_query(&["next".to_string(), "-Delegated".to_string()]).unwrap();
+        assert_eq!(query.delegated, Some(false));
+        assert!(query.anti_tags.is_empty());
+    }
+
+    #[test]
+    fn test_parse_query_schedule() {
+        let args = vec![
+            "template".to_string(),
+            "Standup".to_string(),
+            "schedule:30 9 * * 1-5".to_string(),
+        ];
+        let query = parse_query(&args).unwrap();
+        assert_eq!(query.schedule.as_deref(), Some("30 9 * * 1-5"));
+        assert_eq!(query.to_string(), "\"schedule:30 9 * * 1-5\" \"Standup\"");
+
+        let query = parse_query(&["modify".to_string(), "schedule:none".to_string()]).unwrap();
+        assert_eq!(query.schedule.as_deref(), Some(""));
+
+        let bad = vec!["modify".to_string(), "schedule:sometimes".to_string()];
+        assert!(parse_query(&bad).is_err());
+    }
+
+    #[test]
+    fn test_parse_query_estimate() {
+        let args = vec![
+            "add".to_string(),
+            "Write".to_string(),
+            "report".to_string(),
+            "estimate:1H30m".to_string(),
+        ];
+        let query = parse_query(&args).unwrap();
+
+        assert_eq!(query.estimate, Some(90));
+        assert_eq!(query.text, "Write report");
+        assert_eq!(query.to_string(), "estimate:1h30m \"Write report\"");
+
+        let bad = vec!["add".to_string(), "estimate:soon".to_string()];
+        assert!(parse_query(&bad).is_err());
+    }
+
     #[test]
     fn test_parse_query_with_note() {
         let args = vec![
@@ -311,6 +465,40 @@ mod tests {
         assert_eq!(query.anti_tags, vec!["fun".to_string()]);
     }
 
+    #[test]
+    fn test_parse_query_id_ranges_and_lists() {
+        let args: Vec<String> = ["3-5", "9,1", "modify", "+urgent", "1-2"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let query = parse_query(&args).unwrap();
+        assert_eq!(query.ids, vec![3, 4, 5, 9, 1]);
+        assert_eq!(query.tags, vec!["urgent"]);
+        assert_eq!(query.text, "1-2");
+
+        // Not IDs at all
+        for word in ["7-3", "2025-06-18", "a-b", "1,"] {
+            let query = parse_query(&[word.to_string()]).unwrap();
+            assert!(query.ids.is_empty());
+            assert_eq!(query.text, word);
+        }
+    }
+
+    #[test]
+    fn test_parse_query_anti_text() {
+        let args: Vec<String> = ["next", "Not:Meeting", "-\"weekly sync\"", "report"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let query = parse_query(&args).unwrap();
+        assert_eq!(query.anti_text, vec!["meeting", "weekly sync"]);
+        assert_eq!(query.text, "report");
+        assert_eq!(
+            query.to_string(),
+            "not:meeting \"not:weekly sync\" \"report\""
+        );
+    }
+
     #[test]
     fn test_parse_query_ignore_context() {
         let args = vec!["--".to_string(), "show-resolved".to_string()];
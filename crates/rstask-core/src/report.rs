@@ -0,0 +1,176 @@
+//! Markdown status reports, for pasting into a weekly update
+//!
+//! A report covers the period from a start date to now: what was resolved,
+//! grouped by project, the open tasks worth mentioning and the tasks that
+//! came in.
+
+use crate::constants::*;
+use crate::date_util::{format_due_date, start_of_day};
+use crate::query::Query;
+use crate::task::Task;
+use chrono::{DateTime, Days, Local, Utc};
+use std::collections::BTreeMap;
+
+/// Open tasks due within this many days are highlighted
+const DUE_SOON_DAYS: u64 = 7;
+
+/// Builds the report from the tasks matching the filter and context,
+/// resolved ones included
+pub fn markdown_report(tasks: &[&Task], since: DateTime<Local>, ctx: &Query) -> String {
+    let now = Local::now();
+    let since_utc = since.with_timezone(&Utc);
+    let mut out = format!(
+        "# Status report: {} to {}\n",
+        since.format("%-d %b %Y"),
+        now.format("%-d %b %Y")
+    );
+    if *ctx != Query::default() {
+        out.push_str(&format!("\nContext: `{}`\n", ctx));
+    }
+
+    let mut resolved: Vec<&Task> = tasks
+        .iter()
+        .copied()
+        .filter(|t| t.status == STATUS_RESOLVED && t.resolved.is_some_and(|r| r >= since_utc))
+        .collect();
+    resolved.sort_by_key(|t| t.resolved);
+    let mut by_project: BTreeMap<&str, Vec<&Task>> = BTreeMap::new();
+    for task in &resolved {
+        by_project.entry(&task.project).or_default().push(task);
+    }
+
+    out.push_str(&format!("\n## Done ({})\n", resolved.len()));
+    if resolved.is_empty() {
+        out.push_str("\nNothing resolved.\n");
+    }
+    // Tasks without a project come last
+    let mut groups: Vec<(&str, Vec<&Task>)> = by_project.into_iter().collect();
+    let without_project = groups.iter().take_while(|(p, _)| p.is_empty()).count();
+    groups.rotate_left(without_project);
+    for (project, tasks) in groups {
+        let heading = if project.is_empty() {
+            "No project"
+        } else {
+            project
+        };
+        out.push_str(&format!("\n### {}\n\n", heading));
+        for task in tasks {
+            out.push_str(&format!("- {}\n", task.summary));
+        }
+    }
+
+    let open: Vec<&Task> = tasks
+        .iter()
+        .copied()
+        .filter(|t| !HIDDEN_STATUSES.contains(&t.status.as_str()))
+        .collect();
+
+    let due_soon = start_of_day(now + Days::new(DUE_SOON_DAYS)).with_timezone(&Utc);
+    let mut highlights: Vec<&Task> = open
+        .iter()
+        .copied()
+        .filter(|t| {
+            t.status == STATUS_ACTIVE
+                || t.priority == PRIORITY_CRITICAL
+                || t.priority == PRIORITY_HIGH
+                || t.due.is_some_and(|due| due < due_soon)
+        })
+        .collect();
+    highlights.sort_by(|a, b| {
+        a.priority
+            .cmp(&b.priority)
+            .then_with(|| a.due.is_none().cmp(&b.due.is_none()))
+            .then_with(|| a.due.cmp(&b.due))
+    });
+    out.push_str(&format!("\n## Still open ({})\n\n", highlights.len()));
+    if highlights.is_empty() {
+        out.push_str("Nothing in progress, urgent or due soon.\n");
+    }
+    for task in highlights {
+        out.push_str(&format!("- {}{}\n", task.summary, details(task)));
+    }
+
+    let mut new: Vec<&Task> = open
+        .iter()
+        .copied()
+        .filter(|t| t.created >= since_utc)
+        .collect();
+    new.sort_by_key(|t| t.created);
+    out.push_str(&format!("\n## New ({})\n\n", new.len()));
+    if new.is_empty() {
+        out.push_str("No new tasks.\n");
+    }
+    for task in new {
+        out.push_str(&format!("- {}{}\n", task.summary, details(task)));
+    }
+
+    out
+}
+
+/// What makes an open task worth a mention, such as " (active, P1, due 3 Jun)"
+fn details(task: &Task) -> String {
+    let mut parts = Vec::new();
+    if task.status == STATUS_ACTIVE {
+        parts.push("active".to_string());
+    }
+    if task.priority == PRIORITY_CRITICAL || task.priority == PRIORITY_HIGH {
+        parts.push(task.priority.clone());
+    }
+    if let Some(due) = task.due {
+        parts.push(format!(
+            "due {}",
+            format_due_date(due.with_timezone(&Local))
+        ));
+    }
+    if !task.project.is_empty() {
+        parts.push(task.project.clone());
+    }
+
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(summary: &str, status: &str, project: &str) -> Task {
+        let mut task = Task::new(summary.to_string());
+        task.status = status.to_string();
+        task.project = project.to_string();
+        task
+    }
+
+    #[test]
+    fn test_markdown_report() {
+        let week_ago = Local::now() - Days::new(7);
+        let mut fence = task("Mend fence", STATUS_RESOLVED, "garden");
+        fence.resolved = Some(Utc::now());
+        let mut taxes = task("File taxes", STATUS_RESOLVED, "");
+        taxes.resolved = Some(Utc::now());
+        let mut old = task("Old news", STATUS_RESOLVED, "garden");
+        old.resolved = Some((week_ago - Days::new(1)).with_timezone(&Utc));
+        let mut release = task("Ship release", STATUS_ACTIVE, "work");
+        release.priority = PRIORITY_HIGH.to_string();
+        release.created = (week_ago - Days::new(30)).with_timezone(&Utc);
+        let mut quiet = task("Someday", STATUS_PENDING, "");
+        quiet.created = (week_ago - Days::new(30)).with_timezone(&Utc);
+        let plants = task("Water plants", STATUS_PENDING, "");
+
+        let tasks = [&fence, &taxes, &old, &release, &quiet, &plants];
+        let report = markdown_report(&tasks, week_ago, &Query::default());
+
+        assert!(report.starts_with("# Status report: "));
+        assert!(!report.contains("Context:"));
+        assert!(report.contains(
+            "## Done (2)\n\n### garden\n\n- Mend fence\n\n### No project\n\n- File taxes\n"
+        ));
+        assert!(!report.contains("Old news"));
+        assert!(report.contains("## Still open (1)\n\n- Ship release (active, P1, work)\n"));
+        assert!(report.contains("## New (1)\n\n- Water plants\n"));
+        assert!(!report.contains("Someday"));
+    }
+}
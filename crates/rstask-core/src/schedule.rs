@@ -0,0 +1,230 @@
+//! Schedules for templates
+//!
+//! A template with a schedule gets a task copied from it each time the
+//! schedule comes round, when `rstask tick` runs. A schedule is either a five
+//! field cron expression such as `0 9 * * 1-5`, or one of the shorthands
+//! `every:day`, `every:weekday`, `every:week`, `every:month` and
+//! `every:<day of the week>`, which fall at midnight.
+
+use crate::{Result, RstaskError};
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, TimeZone};
+use std::fmt;
+
+/// How far back to look for the last time a schedule came round. Enough for
+/// a schedule on the 29th of February
+const MAX_DAYS_BACK: u64 = 5 * 366;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    /// As written, lowercased with single spaces
+    text: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day of the month and of the week were restricted. Like
+    /// cron, a day matches either of them when both are
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Schedule {
+    /// Parses a cron expression or an `every:` shorthand
+    pub fn parse(text: &str) -> Result<Schedule> {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let text = text.to_lowercase();
+        let cron = match text.strip_prefix("every:") {
+            Some(every) => every_to_cron(every)
+                .ok_or_else(|| invalid(&text, "use day, weekday, week, month or a day name"))?
+                .to_string(),
+            None => text.clone(),
+        };
+
+        let fields: Vec<&str> = cron.split(' ').collect();
+        if fields.len() != 5 {
+            return Err(invalid(
+                &text,
+                "expected every:<when> or five cron fields, such as \"0 9 * * 1\"",
+            ));
+        }
+        let field = |i: usize, min: u32, max: u32| {
+            parse_field(fields[i], min, max).ok_or_else(|| invalid(&text, fields[i]))
+        };
+
+        let mut weekdays = field(4, 0, 7)?;
+        // Both 0 and 7 are Sunday
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Schedule {
+            minutes: field(0, 0, 59)?,
+            hours: field(1, 0, 23)?,
+            days: field(2, 1, 31)?,
+            months: field(3, 1, 12)?,
+            weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+            text,
+        })
+    }
+
+    /// The last time the schedule came round after `after`, up to and
+    /// including `now`
+    pub fn last_between(
+        &self,
+        after: DateTime<Local>,
+        now: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        let mut day = now.date_naive();
+        for _ in 0..MAX_DAYS_BACK {
+            if day < after.date_naive() {
+                return None;
+            }
+            if self.matches_day(day) {
+                for hour in (0..24).rev().filter(|h| self.hours & (1 << h) != 0) {
+                    for minute in (0..60).rev().filter(|m| self.minutes & (1 << m) != 0) {
+                        // Times skipped by a daylight saving change don't count
+                        let Some(time) = day
+                            .and_hms_opt(hour, minute, 0)
+                            .and_then(|t| Local.from_local_datetime(&t).earliest())
+                        else {
+                            continue;
+                        };
+                        if time <= after {
+                            return None;
+                        }
+                        if time <= now {
+                            return Some(time);
+                        }
+                    }
+                }
+            }
+            day = day.checked_sub_days(Days::new(1))?;
+        }
+        None
+    }
+
+    fn matches_day(&self, day: NaiveDate) -> bool {
+        if self.months & (1 << day.month()) == 0 {
+            return false;
+        }
+        let by_day = self.days & (1 << day.day()) != 0;
+        let by_weekday = self.weekdays & (1 << day.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => by_day || by_weekday,
+            _ => by_day && by_weekday,
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+fn invalid(text: &str, reason: &str) -> RstaskError {
+    RstaskError::Parse(format!("invalid schedule {}: {}", text, reason))
+}
+
+fn every_to_cron(every: &str) -> Option<&'static str> {
+    Some(match every {
+        "day" | "daily" => "0 0 * * *",
+        "weekday" => "0 0 * * 1-5",
+        "week" | "weekly" => "0 0 * * 1",
+        "month" | "monthly" => "0 0 1 * *",
+        "sun" | "sunday" => "0 0 * * 0",
+        "mon" | "monday" => "0 0 * * 1",
+        "tue" | "tuesday" => "0 0 * * 2",
+        "wed" | "wednesday" => "0 0 * * 3",
+        "thu" | "thursday" => "0 0 * * 4",
+        "fri" | "friday" => "0 0 * * 5",
+        "sat" | "saturday" => "0 0 * * 6",
+        _ => return None,
+    })
+}
+
+/// A cron field as a bit set, from lists of `*`, `n` and `a-b`, each
+/// optionally followed by `/step`
+fn parse_field(field: &str, min: u32, max: u32) -> Option<u64> {
+    let mut bits = 0;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse().ok().filter(|s| *s > 0)?),
+            None => (item, 1),
+        };
+        let (from, to) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((from, to)) => (from.parse().ok()?, to.parse().ok()?),
+                None => {
+                    let n = range.parse().ok()?;
+                    // "5/15" runs from 5 to the end, like cron
+                    (n, if item.contains('/') { max } else { n })
+                }
+            },
+        };
+        if from < min || to > max || from > to {
+            return None;
+        }
+        for n in (from..=to).step_by(step) {
+            bits |= 1 << n;
+        }
+    }
+    Some(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Schedule::parse("Every:Monday").unwrap().to_string(),
+            "every:monday"
+        );
+        assert_eq!(
+            Schedule::parse("0  9 * * 1-5").unwrap().to_string(),
+            "0 9 * * 1-5"
+        );
+        assert!(Schedule::parse("every:fortnight").is_err());
+        assert!(Schedule::parse("0 9 * *").is_err());
+        assert!(Schedule::parse("60 9 * * *").is_err());
+        assert!(Schedule::parse("*/0 * * * *").is_err());
+    }
+
+    #[test]
+    fn test_last_between() {
+        // 2026-03-02 is a Monday
+        let monday = Schedule::parse("every:monday").unwrap();
+        let now = local(2026, 3, 11, 15, 0);
+        assert_eq!(
+            monday.last_between(local(2026, 3, 1, 12, 0), now),
+            Some(local(2026, 3, 9, 0, 0))
+        );
+        assert_eq!(monday.last_between(local(2026, 3, 9, 0, 0), now), None);
+
+        let weekdays = Schedule::parse("30 9,17 * * 1-5").unwrap();
+        assert_eq!(
+            weekdays.last_between(local(2026, 3, 1, 0, 0), local(2026, 3, 8, 12, 0)),
+            Some(local(2026, 3, 6, 17, 30))
+        );
+
+        // With both days restricted, either one matches
+        let either = Schedule::parse("0 0 1 * 3").unwrap();
+        assert_eq!(
+            either.last_between(local(2026, 2, 20, 0, 0), local(2026, 3, 3, 12, 0)),
+            Some(local(2026, 3, 1, 0, 0))
+        );
+        assert_eq!(
+            either.last_between(local(2026, 2, 20, 0, 0), local(2026, 3, 4, 12, 0)),
+            Some(local(2026, 3, 4, 0, 0))
+        );
+    }
+}
@@ -72,17 +72,7 @@ impl Table {
             .width
             .saturating_sub(TABLE_COL_GAP * (self.header.len() - 1));
 
-        // Iteratively reduce widths to fit budget
-        while widths.iter().sum::<usize>() > width_budget {
-            // Find max width column
-            let (max_idx, &max_width) = widths.iter().enumerate().max_by_key(|(_, w)| *w).unwrap();
-
-            if max_width == 0 {
-                break;
-            }
-
-            widths[max_idx] -= 1;
-        }
+        fit_widths(&mut widths, width_budget);
 
         // Combine header and rows
         let mut all_rows = vec![self.header.clone()];
@@ -132,6 +122,21 @@ impl Table {
     }
 }
 
+/// Narrows column widths until they add up to no more than `budget`,
+/// taking from the widest column each time
+pub fn fit_widths(widths: &mut [usize], budget: usize) {
+    while widths.iter().sum::<usize>() > budget {
+        // Find max width column
+        let (max_idx, &max_width) = widths.iter().enumerate().max_by_key(|(_, w)| *w).unwrap();
+
+        if max_width == 0 {
+            break;
+        }
+
+        widths[max_idx] -= 1;
+    }
+}
+
 /// Fixes a string to a specific width, truncating or padding as needed
 pub fn fix_str(text: &str, width: usize) -> String {
     // Remove anything after newline
@@ -198,6 +203,17 @@ mod tests {
         assert!(result.ends_with(' '));
     }
 
+    #[test]
+    fn test_fit_widths() {
+        let mut widths = [3, 40, 10];
+        fit_widths(&mut widths, 30);
+        assert_eq!(widths, [3, 17, 10]);
+
+        // The widest give way until all are equal
+        fit_widths(&mut widths, 15);
+        assert_eq!(widths, [3, 6, 6]);
+    }
+
     #[test]
     fn test_fix_str_newline() {
         assert_eq!(fix_str("hello\nworld", 10), "hello     ");
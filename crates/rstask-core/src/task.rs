@@ -1,10 +1,11 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 use crate::constants::*;
-use crate::date_util::format_due_date;
+use crate::date_util::{format_due_date, format_relative};
+use crate::preferences::{DateStyle, TaskFormat};
 use crate::query::Query;
 use crate::util::{is_valid_uuid4_string, must_get_repo_path};
 use crate::{Result, RstaskError};
@@ -76,11 +77,71 @@ pub struct TaskJson {
     pub tags: Vec<String>,
     pub project: String,
     pub priority: String,
+    /// Only written for delegated tasks, keeping other output as before
+    #[serde(rename = "delegatedto", skip_serializing_if = "String::is_empty")]
+    pub delegated_to: String,
+    /// Minutes, only written for tasks with an estimate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<u32>,
+    /// Only written for tasks linked to a Jira issue
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub jira: String,
+    /// Only written for templates with a schedule
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub schedule: String,
     pub created: String,
+    pub modified: String,
     pub resolved: String,
     pub due: String,
 }
 
+/// Borrowed form of [`TaskJson`], serialized without copying the task
+#[derive(Debug, Serialize)]
+pub struct TaskJsonRef<'a> {
+    uuid: &'a str,
+    status: &'a str,
+    id: i32,
+    summary: &'a str,
+    notes: &'a str,
+    tags: &'a [String],
+    project: &'a str,
+    priority: &'a str,
+    #[serde(rename = "delegatedto", skip_serializing_if = "str::is_empty")]
+    delegated_to: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    estimate: Option<u32>,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    jira: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    schedule: &'a str,
+    #[serde(serialize_with = "serialize_rfc3339")]
+    created: DateTime<Utc>,
+    #[serde(serialize_with = "serialize_rfc3339")]
+    modified: DateTime<Utc>,
+    #[serde(serialize_with = "serialize_rfc3339_or_zero")]
+    resolved: Option<DateTime<Utc>>,
+    #[serde(serialize_with = "serialize_rfc3339_or_zero")]
+    due: Option<DateTime<Utc>>,
+}
+
+/// Same output as `to_rfc3339`, written straight to the serializer
+fn serialize_rfc3339<S: serde::Serializer>(
+    dt: &DateTime<Utc>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(&dt.format("%Y-%m-%dT%H:%M:%S%.f%:z"))
+}
+
+fn serialize_rfc3339_or_zero<S: serde::Serializer>(
+    dt: &Option<DateTime<Utc>>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match dt {
+        Some(dt) => serialize_rfc3339(dt, serializer),
+        None => serializer.serialize_str("0001-01-01T00:00:00Z"),
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SubTask {
     pub summary: String,
@@ -127,9 +188,39 @@ pub struct Task {
     #[serde(default)]
     pub dependencies: Vec<String>,
 
+    /// Estimated effort in minutes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<u32>,
+
+    /// Key of the linked Jira issue, such as PROJ-123
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub jira: String,
+
+    /// When a template gets a new task copied from it, such as every:monday
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub schedule: String,
+
+    /// The last time the schedule came round and a task was copied from the
+    /// template. Kept in the repository so other machines don't copy it again
+    #[serde(
+        with = "optional_datetime_rfc3339",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_instantiated: Option<DateTime<Utc>>,
+
     #[serde(with = "datetime_rfc3339")]
     pub created: DateTime<Utc>,
 
+    /// When the task was last saved. Files written before this was tracked
+    /// don't have it
+    #[serde(
+        with = "optional_datetime_rfc3339",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub modified: Option<DateTime<Utc>>,
+
     #[serde(with = "optional_datetime_rfc3339", default)]
     pub resolved: Option<DateTime<Utc>>,
 
@@ -138,6 +229,11 @@ pub struct Task {
 
     #[serde(skip)]
     pub filtered: bool,
+
+    /// IDs of the open tasks this one depends on, worked out when the task
+    /// set is loaded
+    #[serde(skip)]
+    pub blocked_by: Vec<i32>,
 }
 
 impl Task {
@@ -157,13 +253,24 @@ impl Task {
             delegated_to: String::new(),
             subtasks: Vec::new(),
             dependencies: Vec::new(),
+            estimate: None,
+            jira: String::new(),
+            schedule: String::new(),
+            last_instantiated: None,
             created: Utc::now(),
+            modified: None,
             resolved: None,
             due: None,
             filtered: false,
+            blocked_by: Vec::new(),
         }
     }
 
+    /// When the task was last saved, or created if that isn't known
+    pub fn last_modified(&self) -> DateTime<Utc> {
+        self.modified.unwrap_or(self.created)
+    }
+
     /// Converts task to JSON representation (matches Go version)
     pub fn to_json(&self) -> TaskJson {
         TaskJson {
@@ -175,7 +282,12 @@ impl Task {
             tags: self.tags.clone(),
             project: self.project.clone(),
             priority: self.priority.clone(),
+            delegated_to: self.delegated_to.clone(),
+            estimate: self.estimate,
+            jira: self.jira.clone(),
+            schedule: self.schedule.clone(),
             created: self.created.to_rfc3339(),
+            modified: self.last_modified().to_rfc3339(),
             resolved: self
                 .resolved
                 .map(|r| r.to_rfc3339())
@@ -187,6 +299,34 @@ impl Task {
         }
     }
 
+    /// Whether the task is overdue or inside its reminder lead time
+    pub fn is_due_soon(&self, prefs: &crate::preferences::Preferences, now: DateTime<Utc>) -> bool {
+        self.due
+            .is_some_and(|due| now >= prefs.due_soon_from(&self.priority, due))
+    }
+
+    /// Borrows the task as its JSON representation, for streaming output
+    pub fn to_json_ref(&self) -> TaskJsonRef<'_> {
+        TaskJsonRef {
+            uuid: &self.uuid,
+            status: &self.status,
+            id: self.id,
+            summary: &self.summary,
+            notes: &self.notes,
+            tags: &self.tags,
+            project: &self.project,
+            priority: &self.priority,
+            delegated_to: &self.delegated_to,
+            estimate: self.estimate,
+            jira: &self.jira,
+            schedule: &self.schedule,
+            created: self.created,
+            modified: self.last_modified(),
+            resolved: self.resolved,
+            due: self.due,
+        }
+    }
+
     /// Checks equality of core properties (ignores ephemeral fields)
     pub fn equals(&self, other: &Task) -> bool {
         self.uuid == other.uuid
@@ -199,6 +339,10 @@ impl Task {
             && self.delegated_to == other.delegated_to
             && self.subtasks == other.subtasks
             && self.dependencies == other.dependencies
+            && self.estimate == other.estimate
+            && self.jira == other.jira
+            && self.schedule == other.schedule
+            && self.last_instantiated == other.last_instantiated
             && self.created == other.created
             && self.resolved == other.resolved
             && self.due == other.due
@@ -225,6 +369,13 @@ impl Task {
             }
         }
 
+        // Must be delegated, or not, if +delegated/-delegated was given
+        if let Some(delegated) = query.delegated
+            && self.delegated_to.is_empty() == delegated
+        {
+            return false;
+        }
+
         // Must not be in anti-projects
         if query.anti_projects.contains(&self.project) {
             return false;
@@ -264,6 +415,19 @@ impl Task {
             }
         }
 
+        // Must not contain any anti-text
+        if !query.anti_text.is_empty() {
+            let summary_lower = self.summary.to_lowercase();
+            let notes_lower = self.notes.to_lowercase();
+            if query
+                .anti_text
+                .iter()
+                .any(|text| summary_lower.contains(text) || notes_lower.contains(text))
+            {
+                return false;
+            }
+        }
+
         true
     }
 
@@ -359,6 +523,19 @@ impl Task {
             self.due = Some(due);
         }
 
+        // Set estimate, where 0 removes it
+        if let Some(estimate) = query.estimate {
+            self.estimate = Some(estimate).filter(|m| *m > 0);
+        }
+
+        // Set schedule, counted from now, where none removes it
+        if let Some(schedule) = &query.schedule
+            && *schedule != self.schedule
+        {
+            self.schedule = schedule.clone();
+            self.last_instantiated = Some(Utc::now()).filter(|_| !schedule.is_empty());
+        }
+
         // Append note
         if !query.note.is_empty() {
             if !self.notes.is_empty() {
@@ -370,94 +547,127 @@ impl Task {
         self.write_pending = true;
     }
 
-    /// Saves task to disk
-    pub fn save_to_disk(&mut self, repo_path: &Path) -> Result<()> {
+    /// Saves task to disk in the given format, or deletes it if it's marked
+    /// deleted
+    pub fn save_to_disk(&mut self, repo_path: &Path, write_format: WriteFormat) -> Result<()> {
         self.write_pending = false;
 
-        let filepath = must_get_repo_path(repo_path, &self.status, &format!("{}.md", self.uuid));
+        let format = write_format.file_format();
+        let filepath = must_get_repo_path(
+            repo_path,
+            &self.status,
+            &format!("{}.{}", self.uuid, format.as_str()),
+        );
 
-        if self.deleted {
-            // Delete the task file
-            if filepath.exists() {
-                std::fs::remove_file(&filepath)?;
-            }
-        } else {
-            // Save task to disk using markdown with frontmatter
-            let markdown_data = crate::frontmatter::task_to_markdown(self)?;
+        if !self.deleted {
+            // Swapped back before returning, so the task keeps plain notes
+            let encrypted = crate::encryption::encrypt_notes(&self.notes)?;
+            let notes = std::mem::replace(&mut self.notes, encrypted);
+            let data = match format {
+                TaskFormat::Md => crate::frontmatter::task_to_markdown(self),
+                TaskFormat::Yml if write_format.dstask_compat => crate::dstask::task_to_yaml(self),
+                TaskFormat::Yml => serde_yaml::to_string(self).map_err(RstaskError::from),
+            };
+            self.notes = notes;
+            let data = data?;
 
             // Ensure directory exists
             if let Some(parent) = filepath.parent() {
                 std::fs::create_dir_all(parent)?;
             }
 
-            std::fs::write(&filepath, markdown_data)?;
+            std::fs::write(&filepath, data)?;
         }
 
-        // Delete task from other status directories (both .md and legacy .yml)
-        for status in ALL_STATUSES {
-            if *status == self.status {
-                continue;
-            }
+        // The other format in this status directory, and both in the rest
+        let keep = if self.deleted { None } else { Some(filepath) };
+        self.remove_copies(repo_path, keep.as_deref())?;
 
-            // Delete .md file
-            let other_filepath =
-                must_get_repo_path(repo_path, status, &format!("{}.md", self.uuid));
-            if other_filepath.exists() {
-                std::fs::remove_file(&other_filepath)?;
-            }
+        Ok(())
+    }
 
-            // Delete legacy .yml file if it exists
-            let legacy_filepath =
-                must_get_repo_path(repo_path, status, &format!("{}.yml", self.uuid));
-            if legacy_filepath.exists() {
-                std::fs::remove_file(&legacy_filepath)?;
+    /// The file the task is stored in, or None if it hasn't been saved.
+    /// Markdown is preferred if both formats exist.
+    pub fn file_path(&self, repo_path: &Path) -> Option<PathBuf> {
+        ["md", "yml"]
+            .iter()
+            .map(|ext| {
+                must_get_repo_path(repo_path, &self.status, &format!("{}.{}", self.uuid, ext))
+            })
+            .find(|path| path.exists())
+    }
+
+    /// Deletes task from disk, in either format and any status directory
+    pub fn delete_from_disk(&self, repo_path: &Path) -> Result<()> {
+        self.remove_copies(repo_path, None)
+    }
+
+    /// Removes every file holding this task except `keep`
+    fn remove_copies(&self, repo_path: &Path, keep: Option<&Path>) -> Result<()> {
+        for status in ALL_STATUSES {
+            for ext in ["md", "yml"] {
+                let path = must_get_repo_path(repo_path, status, &format!("{}.{}", self.uuid, ext));
+                if keep != Some(path.as_path()) && path.exists() {
+                    std::fs::remove_file(&path)?;
+                }
             }
         }
 
         Ok(())
     }
 
-    /// Deletes task from disk
-    pub fn delete_from_disk(&self, repo_path: &Path) -> Result<()> {
-        // Delete both .yml and .md files from current status directory
-        let yml_filepath =
-            must_get_repo_path(repo_path, &self.status, &format!("{}.yml", self.uuid));
-        if yml_filepath.exists() {
-            std::fs::remove_file(&yml_filepath)?;
+    /// Returns an urgency score used to rank tasks; higher is more urgent
+    pub fn urgency(&self) -> f64 {
+        let mut urgency = match self.priority.as_str() {
+            PRIORITY_CRITICAL => 6.0,
+            PRIORITY_HIGH => 3.9,
+            PRIORITY_NORMAL => 1.8,
+            _ => 0.0,
+        };
+
+        if self.status == STATUS_ACTIVE {
+            urgency += 4.0;
         }
 
-        let md_filepath = must_get_repo_path(repo_path, &self.status, &format!("{}.md", self.uuid));
-        if md_filepath.exists() {
-            std::fs::remove_file(&md_filepath)?;
+        // Due dates ramp up over the two weeks before they fall due, and
+        // overdue tasks get the maximum boost
+        if let Some(due) = self.due {
+            let days_left = (due - Utc::now()).num_hours() as f64 / 24.0;
+            urgency += if days_left < 0.0 {
+                12.0
+            } else if days_left < 14.0 {
+                12.0 * (1.0 - days_left / 14.0)
+            } else {
+                0.0
+            };
         }
 
-        // Also check other status directories
-        for status in ALL_STATUSES {
-            if *status == self.status {
-                continue;
-            }
+        // Older tasks slowly gain urgency, capped at one year
+        let age_days = (Utc::now() - self.created).num_days().clamp(0, 365) as f64;
+        urgency += 2.0 * age_days / 365.0;
 
-            let other_yml_filepath =
-                must_get_repo_path(repo_path, status, &format!("{}.yml", self.uuid));
-            if other_yml_filepath.exists() {
-                std::fs::remove_file(&other_yml_filepath)?;
-            }
+        if !self.project.is_empty() {
+            urgency += 1.0;
+        }
 
-            let other_md_filepath =
-                must_get_repo_path(repo_path, status, &format!("{}.md", self.uuid));
-            if other_md_filepath.exists() {
-                std::fs::remove_file(&other_md_filepath)?;
-            }
+        if !self.tags.is_empty() {
+            urgency += 1.0;
         }
 
-        Ok(())
+        urgency
     }
 
     /// Parses due date to a display string
     pub fn parse_due_date_to_str(&self) -> String {
-        match self.due {
-            Some(due) => format_due_date(due.with_timezone(&chrono::Local)),
-            None => String::new(),
+        self.due_date_str(DateStyle::Absolute)
+    }
+
+    /// Formats the due date in the given style, empty if there is none
+    pub fn due_date_str(&self, style: DateStyle) -> String {
+        match (self.due, style) {
+            (None, _) => String::new(),
+            (Some(due), DateStyle::Absolute) => format_due_date(due.with_timezone(&chrono::Local)),
+            (Some(due), DateStyle::Relative) => format_relative(due),
         }
     }
 }
@@ -472,13 +682,74 @@ impl std::fmt::Display for Task {
     }
 }
 
-/// Unmarshals a task from disk
+/// How [`Task::save_to_disk`] writes task files, from the `task_format` and
+/// `dstask_compat` preferences
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WriteFormat {
+    pub format: TaskFormat,
+    /// Write tasks the way dstask does
+    pub dstask_compat: bool,
+}
+
+impl WriteFormat {
+    fn file_format(&self) -> TaskFormat {
+        // dstask only reads YAML
+        if self.dstask_compat {
+            TaskFormat::Yml
+        } else {
+            self.format
+        }
+    }
+}
+
+/// Unmarshals a task from disk. With `skip_notes`, markdown files are only
+/// read up to the end of the frontmatter and the task's notes are left empty.
 pub fn unmarshal_task(
     path: &Path,
     filename: &str,
     ids: &std::collections::HashMap<String, i32>,
     status: &str,
+    skip_notes: bool,
 ) -> Result<Task> {
+    let uuid = task_file_uuid(filename)?;
+    let id = ids.get(uuid).copied().unwrap_or(0);
+    let data = if skip_notes && filename.ends_with(".md") {
+        read_frontmatter(path)?
+    } else {
+        std::fs::read_to_string(path)?
+    };
+
+    let mut task = parse_task_file(filename, &data, status, id)?;
+    if skip_notes {
+        task.notes.clear();
+    }
+
+    Ok(task)
+}
+
+/// Parses the contents of a task file named `filename` in the `status`
+/// directory
+pub fn parse_task_file(filename: &str, data: &str, status: &str, id: i32) -> Result<Task> {
+    let uuid = task_file_uuid(filename)?;
+
+    let mut task = if filename.ends_with(".md") {
+        // Parse markdown with frontmatter
+        crate::frontmatter::task_from_markdown(data, uuid, status, id)?
+    } else {
+        // Parse plain YAML, as dstask writes
+        let mut task: Task = serde_yaml::from_str(data)?;
+        task.uuid = uuid.to_string();
+        task.status = status.to_string();
+        task.id = id;
+        task
+    };
+    task.notes = crate::encryption::decrypt_notes(&task.notes)?;
+
+    Ok(task)
+}
+
+/// Returns the UUID encoded in a task filename
+fn task_file_uuid(filename: &str) -> Result<&str> {
     // Support both .md (new format) and .yml (legacy format)
     let is_markdown = filename.ends_with(".md");
     let is_yaml = filename.ends_with(".yml");
@@ -506,28 +777,68 @@ pub fn unmarshal_task(
         )));
     }
 
-    let id = ids.get(uuid).copied().unwrap_or(0);
-    let data = std::fs::read_to_string(path)?;
+    Ok(uuid)
+}
 
-    let task = if is_markdown {
-        // Parse markdown with frontmatter
-        crate::frontmatter::task_from_markdown(&data, uuid, status, id)?
-    } else {
-        // Parse legacy YAML format
-        let mut task: Task = serde_yaml::from_str(&data)?;
-        task.uuid = uuid.to_string();
-        task.status = status.to_string();
-        task.id = id;
-        task
-    };
+/// Reads a markdown task file up to and including the closing frontmatter
+/// delimiter
+fn read_frontmatter(path: &Path) -> Result<String> {
+    use std::io::BufRead;
 
-    Ok(task)
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut frontmatter = String::new();
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        frontmatter.push_str(&line);
+        frontmatter.push('\n');
+        if i > 0 && line == "---" {
+            break;
+        }
+    }
+
+    Ok(frontmatter)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_json_ref_matches_json() {
+        let task = Task {
+            uuid: "9a8b7c6d-0000-4000-8000-000000000000".to_string(),
+            status: STATUS_RESOLVED.to_string(),
+            summary: "Pay \"rent\"".to_string(),
+            tags: vec!["home".to_string()],
+            created: DateTime::from_timestamp(1_700_000_000, 123_000_000).unwrap(),
+            resolved: DateTime::from_timestamp(1_700_086_400, 0),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::to_string(&task.to_json_ref()).unwrap(),
+            serde_json::to_string(&task.to_json()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_file_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut task = Task::new("Fix the build".to_string());
+        assert_eq!(task.file_path(dir.path()), None);
+
+        task.save_to_disk(dir.path(), WriteFormat::default())
+            .unwrap();
+        let path = task.file_path(dir.path()).unwrap();
+        assert_eq!(
+            path,
+            dir.path()
+                .join(STATUS_PENDING)
+                .join(format!("{}.md", task.uuid))
+        );
+    }
+
     #[test]
     fn test_yaml_serialization_format() {
         let task = Task {
@@ -539,6 +850,7 @@ mod tests {
             delegated_to: String::new(),
             subtasks: Vec::new(),
             dependencies: Vec::new(),
+            estimate: None,
             created: Utc::now(),
             resolved: None,
             due: None,
@@ -637,6 +949,19 @@ due: 0001-01-01T00:00:00Z
         assert_eq!(task.project, "");
     }
 
+    #[test]
+    fn test_task_urgency() {
+        let mut task = Task::new("Test".to_string());
+        let normal = task.urgency();
+
+        task.priority = PRIORITY_CRITICAL.to_string();
+        assert!(task.urgency() > normal);
+
+        let critical = task.urgency();
+        task.due = Some(Utc::now() - chrono::Duration::days(1));
+        assert!(task.urgency() > critical);
+    }
+
     #[test]
     fn test_task_normalise() {
         let mut task = Task::new("Test".to_string());
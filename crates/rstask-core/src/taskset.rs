@@ -1,14 +1,60 @@
 // TaskSet - collection of tasks with filtering and loading capabilities
 use crate::Result;
+use crate::config::Config;
 use crate::constants::*;
-use crate::local_state::{load_ids, save_ids};
+use crate::local_state::{
+    IdsMap, ReleasedIds, load_ids, load_ids_and_released, save_ids, save_ids_and_released,
+};
+use crate::lock::{self, RepoLock};
+use crate::preferences::{Preferences, SortField, TaskSort};
 use crate::query::Query;
 use crate::table::RowStyle;
-use crate::task::{Task, unmarshal_task};
+use crate::task::{Task, WriteFormat, unmarshal_task};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How a task set reads and writes the repository, from the preferences
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RepoSettings {
+    /// Fail to load when a task file can't be read, rather than skipping it
+    /// with a warning. A bad merge can otherwise hide tasks unnoticed.
+    pub strict: bool,
+    /// Keep the ID of a task that was resolved or deleted from being given to
+    /// another task for this many minutes, so a number doesn't change meaning
+    /// right after you last saw it
+    pub id_grace_minutes: u64,
+    /// How long to wait for another process holding the repository lock
+    pub lock_timeout: Duration,
+    pub write_format: WriteFormat,
+}
+
+impl RepoSettings {
+    pub fn from_preferences(prefs: &Preferences) -> Self {
+        RepoSettings {
+            strict: prefs.strict_load,
+            id_grace_minutes: prefs.id_grace_minutes,
+            lock_timeout: Duration::from_secs(prefs.lock_timeout_seconds),
+            write_format: WriteFormat {
+                format: prefs.task_format,
+                dstask_compat: prefs.dstask_compat,
+            },
+        }
+    }
+}
+
+impl Default for RepoSettings {
+    fn default() -> Self {
+        RepoSettings {
+            strict: false,
+            id_grace_minutes: 0,
+            lock_timeout: lock::DEFAULT_TIMEOUT,
+            write_format: WriteFormat::default(),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
@@ -21,6 +67,13 @@ pub struct Project {
     #[serde(with = "chrono::serde::ts_seconds")]
     pub resolved: DateTime<Utc>,
     pub priority: String,
+    /// Minutes estimated for the project's open tasks
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub estimate: u32,
+}
+
+fn is_zero(minutes: &u32) -> bool {
+    *minutes == 0
 }
 
 impl Project {
@@ -42,12 +95,96 @@ impl Project {
     }
 }
 
+/// Which tasks [`TaskSet::load_with`] reads, and how much of each
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    /// Status directories to read
+    pub statuses: Vec<&'static str>,
+    /// Skip resolved tasks that were resolved before this time
+    pub resolved_since: Option<DateTime<Utc>>,
+    /// Leave notes empty, for views that never show or search them
+    pub skip_notes: bool,
+    /// Take the repository lock before reading anything, for a set that
+    /// will be saved
+    pub for_update: bool,
+    pub settings: RepoSettings,
+}
+
+impl LoadOptions {
+    /// Every open task, plus resolved tasks if asked for
+    pub fn new(include_resolved: bool) -> Self {
+        let statuses = if include_resolved {
+            ALL_STATUSES
+        } else {
+            NON_RESOLVED_STATUSES
+        };
+        Self::statuses(statuses)
+    }
+
+    /// Only the tasks with the given statuses
+    pub fn statuses(statuses: &[&'static str]) -> Self {
+        LoadOptions {
+            statuses: statuses.to_vec(),
+            resolved_since: None,
+            skip_notes: false,
+            for_update: false,
+            settings: RepoSettings::default(),
+        }
+    }
+
+    pub fn resolved_since(mut self, since: DateTime<Utc>) -> Self {
+        self.resolved_since = Some(since);
+        self
+    }
+
+    pub fn skip_notes(mut self, skip: bool) -> Self {
+        self.skip_notes = skip;
+        self
+    }
+
+    pub fn for_update(mut self) -> Self {
+        self.for_update = true;
+        self
+    }
+
+    pub fn settings(mut self, settings: RepoSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Whether tasks loaded this way can't be written back safely: open
+    /// tasks left out would lose their IDs, and notes would be wiped
+    fn is_partial(&self) -> bool {
+        self.skip_notes
+            || !NON_RESOLVED_STATUSES
+                .iter()
+                .all(|status| self.statuses.contains(status))
+    }
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
 pub struct TaskSet {
     tasks: Vec<Task>,
     tasks_by_id: HashMap<i32, usize>,
     tasks_by_uuid: HashMap<String, usize>,
     ids_file_path: PathBuf,
     repo_path: PathBuf,
+    /// IDs as they were in the ids file when the set was loaded
+    loaded_ids: IdsMap,
+    /// IDs released within the grace period, which new tasks don't get
+    /// unless every other ID is taken
+    released_ids: ReleasedIds,
+    /// Loaded with options that make saving unsafe
+    partial: bool,
+    settings: RepoSettings,
+    /// Taken before loading for an update, or else on the first save, and
+    /// kept until the set is dropped so the commit that follows is covered too
+    lock: Option<RepoLock>,
 }
 
 impl TaskSet {
@@ -58,21 +195,56 @@ impl TaskSet {
             tasks_by_uuid: HashMap::new(),
             ids_file_path,
             repo_path,
+            loaded_ids: IdsMap::new(),
+            released_ids: ReleasedIds::new(),
+            partial: false,
+            settings: RepoSettings::default(),
+            lock: None,
         }
     }
 
-    /// Loads tasks from the repository
-    pub fn load(repo_path: &Path, ids_file_path: &Path, include_resolved: bool) -> Result<Self> {
-        let mut ts = TaskSet::new(repo_path.to_path_buf(), ids_file_path.to_path_buf());
-        let ids = load_ids(ids_file_path);
+    /// Loads tasks from the configured repository
+    pub fn load(conf: &Config, include_resolved: bool) -> Result<Self> {
+        Self::load_with(
+            &conf.repo,
+            &conf.ids_file,
+            &LoadOptions::new(include_resolved).settings(conf.repo_settings()),
+        )
+    }
 
-        let statuses = if include_resolved {
-            ALL_STATUSES
-        } else {
-            NON_RESOLVED_STATUSES
-        };
+    /// Loads tasks from the configured repository to change them. The
+    /// repository lock is held from before the IDs are read until the set is
+    /// dropped, so another process can't hand out the same IDs in between.
+    pub fn load_for_update(conf: &Config, include_resolved: bool) -> Result<Self> {
+        Self::load_with(
+            &conf.repo,
+            &conf.ids_file,
+            &LoadOptions::new(include_resolved)
+                .for_update()
+                .settings(conf.repo_settings()),
+        )
+    }
 
-        for status in statuses {
+    /// Loads only the tasks, and parts of tasks, that `options` asks for
+    pub fn load_with(
+        repo_path: &Path,
+        ids_file_path: &Path,
+        options: &LoadOptions,
+    ) -> Result<Self> {
+        let mut ts = TaskSet::new(repo_path.to_path_buf(), ids_file_path.to_path_buf());
+        ts.partial = options.is_partial();
+        ts.settings = options.settings;
+        if options.for_update {
+            ts.lock_repo()?;
+        }
+        let (ids, mut released) = load_ids_and_released(ids_file_path);
+        let grace = ts.settings.id_grace_minutes as i64 * 60;
+        let now = Utc::now().timestamp();
+        released.retain(|_, at| now - *at < grace);
+        ts.released_ids = released;
+        let mut broken = Vec::new();
+
+        for &status in &options.statuses {
             let dir = repo_path.join(status);
 
             if !dir.exists() {
@@ -108,17 +280,41 @@ impl TaskSet {
                 }
 
                 let path = entry.path();
-                match unmarshal_task(&path, &filename_str, &ids, status) {
+                match unmarshal_task(&path, &filename_str, &ids, status, options.skip_notes) {
                     Ok(task) => {
-                        ts.load_task(task)?;
+                        let too_old = options
+                            .resolved_since
+                            .is_some_and(|since| task.resolved.is_some_and(|r| r < since));
+                        if !too_old {
+                            ts.load_task(task)?;
+                        }
                     }
                     Err(e) => {
-                        eprintln!("Warning: error loading task: {}", e);
+                        let rel_path = path.strip_prefix(repo_path).unwrap_or(&path);
+                        broken.push(format!("{}: {}", rel_path.display(), e));
                     }
                 }
             }
         }
 
+        if !broken.is_empty() {
+            if ts.settings.strict {
+                return Err(crate::RstaskError::Other(format!(
+                    "{} task file(s) failed to load:\n{}",
+                    broken.len(),
+                    broken.join("\n")
+                )));
+            }
+            for problem in &broken {
+                eprintln!("Warning: error loading task {}", problem);
+            }
+            eprintln!(
+                "Warning: skipped {} task file(s) that failed to load; \"rstask doctor\" lists \
+                 every problem",
+                broken.len()
+            );
+        }
+
         // hide some tasks by default. This is useful for things like templates and
         // recurring tasks which are shown either directly or with show- commands
         for task in &mut ts.tasks {
@@ -126,10 +322,64 @@ impl TaskSet {
                 task.filtered = true;
             }
         }
+        ts.mark_blocked(&ids);
+        ts.loaded_ids = ids;
 
         Ok(ts)
     }
 
+    /// Sets `blocked_by` on each task from the open tasks it depends on.
+    /// A load of only some statuses looks for dependencies it didn't load in
+    /// the other open status directories, so `show-active` still sees a
+    /// pending blocker
+    fn mark_blocked(&mut self, ids: &IdsMap) {
+        let loaded: HashSet<&str> = self.tasks.iter().map(|t| t.uuid.as_str()).collect();
+        let mut open: HashMap<&str, i32> = self
+            .tasks
+            .iter()
+            .filter(|t| t.id > 0 && !HIDDEN_STATUSES.contains(&t.status.as_str()))
+            .map(|t| (t.uuid.as_str(), t.id))
+            .collect();
+        for task in &self.tasks {
+            for uuid in &task.dependencies {
+                if loaded.contains(uuid.as_str()) || open.contains_key(uuid.as_str()) {
+                    continue;
+                }
+                if let Some(&id) = ids.get(uuid)
+                    && self.is_open_on_disk(uuid)
+                {
+                    open.insert(uuid.as_str(), id);
+                }
+            }
+        }
+        let blocked_by: Vec<Vec<i32>> = self
+            .tasks
+            .iter()
+            .map(|t| {
+                t.dependencies
+                    .iter()
+                    .filter_map(|uuid| open.get(uuid.as_str()).copied())
+                    .collect()
+            })
+            .collect();
+        for (task, ids) in self.tasks.iter_mut().zip(blocked_by) {
+            task.blocked_by = ids;
+        }
+    }
+
+    /// Whether a task file for `uuid` is in a status directory that counts
+    /// as open
+    fn is_open_on_disk(&self, uuid: &str) -> bool {
+        NON_RESOLVED_STATUSES
+            .iter()
+            .filter(|status| !HIDDEN_STATUSES.contains(status))
+            .any(|status| {
+                let dir = self.repo_path.join(status);
+                dir.join(format!("{}.md", uuid)).exists()
+                    || dir.join(format!("{}.yml", uuid)).exists()
+            })
+    }
+
     /// Loads a task into the set
     pub fn load_task(&mut self, mut task: Task) -> Result<()> {
         task.normalise();
@@ -151,13 +401,11 @@ impl TaskSet {
         }
 
         // Assign ID if needed (for non-resolved tasks)
-        if task.id == 0 && task.status != STATUS_RESOLVED {
-            for id in 1..=MAX_TASKS_OPEN as i32 {
-                if !self.tasks_by_id.contains_key(&id) {
-                    task.id = id;
-                    break;
-                }
-            }
+        if task.id == 0
+            && task.status != STATUS_RESOLVED
+            && let Some(id) = self.free_id()
+        {
+            task.id = id;
         }
 
         // Set created time if not set
@@ -175,6 +423,15 @@ impl TaskSet {
         Ok(())
     }
 
+    /// The lowest ID no open task has, passing over IDs released within the
+    /// grace period while there are others
+    fn free_id(&self) -> Option<i32> {
+        let mut free = (1..=MAX_TASKS_OPEN as i32).filter(|id| !self.tasks_by_id.contains_key(id));
+        free.clone()
+            .find(|id| !self.released_ids.contains_key(id))
+            .or_else(|| free.next())
+    }
+
     /// Assigns IDs to tasks
     pub fn assign_ids(&mut self) -> Result<()> {
         let mut ids = load_ids(&self.ids_file_path);
@@ -198,6 +455,15 @@ impl TaskSet {
         Ok(())
     }
 
+    /// Keeps only tasks waiting on another open task
+    pub fn filter_blocked(&mut self) {
+        for task in &mut self.tasks {
+            if task.blocked_by.is_empty() {
+                task.filtered = true;
+            }
+        }
+    }
+
     /// Filters tasks by a query
     pub fn filter(&mut self, query: &Query) {
         for task in &mut self.tasks {
@@ -224,11 +490,20 @@ impl TaskSet {
 
     /// Saves all pending changes
     pub fn save_pending_changes(&mut self) -> Result<()> {
+        if self.partial {
+            return Err(crate::RstaskError::Other(
+                "cannot save a partially loaded task set".to_string(),
+            ));
+        }
+
+        self.lock_repo()?;
+
         let mut ids = std::collections::HashMap::new();
 
         for task in &mut self.tasks {
             if task.write_pending {
-                task.save_to_disk(&self.repo_path)?;
+                task.modified = Some(Utc::now());
+                task.save_to_disk(&self.repo_path, self.settings.write_format)?;
             }
 
             // Build IDs map for all tasks with IDs
@@ -237,8 +512,32 @@ impl TaskSet {
             }
         }
 
+        // IDs that no task has any more are held back for the grace period
+        if self.settings.id_grace_minutes > 0 {
+            let now = Utc::now().timestamp();
+            let in_use: HashSet<i32> = ids.values().copied().collect();
+            for id in self.loaded_ids.values() {
+                if !in_use.contains(id) {
+                    self.released_ids.entry(*id).or_insert(now);
+                }
+            }
+            self.released_ids.retain(|id, _| !in_use.contains(id));
+            self.loaded_ids = ids.clone();
+        }
+
         // Save IDs map to disk
-        save_ids(&self.ids_file_path, &ids)?;
+        save_ids_and_released(&self.ids_file_path, &ids, &self.released_ids)?;
+        Ok(())
+    }
+
+    /// Takes the repository lock, unless the set already holds it
+    fn lock_repo(&mut self) -> Result<()> {
+        if self.lock.is_none() {
+            self.lock = Some(RepoLock::acquire(
+                &self.repo_path,
+                self.settings.lock_timeout,
+            )?);
+        }
         Ok(())
     }
 
@@ -298,14 +597,13 @@ impl TaskSet {
         }
 
         // Assign a new ID when un-resolving (resolved -> non-resolved)
-        if old.status == STATUS_RESOLVED && task.status != STATUS_RESOLVED && task.id == 0 {
-            for id in 1..=MAX_TASKS_OPEN as i32 {
-                if let std::collections::hash_map::Entry::Vacant(e) = self.tasks_by_id.entry(id) {
-                    task.id = id;
-                    e.insert(idx);
-                    break;
-                }
-            }
+        if old.status == STATUS_RESOLVED
+            && task.status != STATUS_RESOLVED
+            && task.id == 0
+            && let Some(id) = self.free_id()
+        {
+            task.id = id;
+            self.tasks_by_id.insert(id, idx);
         }
 
         // Set resolved time
@@ -331,7 +629,62 @@ impl TaskSet {
     }
 
     pub fn sort_by_created_descending(&mut self) {
-        self.tasks.sort_by(|a, b| b.created.cmp(&a.created));
+        self.tasks.sort_by_key(|t| std::cmp::Reverse(t.created));
+    }
+
+    /// Sorts tasks by when they were last saved, least recent first
+    pub fn sort_by_modified_ascending(&mut self) {
+        self.tasks.sort_by(|a, b| {
+            a.last_modified()
+                .cmp(&b.last_modified())
+                .then_with(|| a.id.cmp(&b.id))
+        });
+    }
+
+    /// Sorts tasks by due date, soonest first and tasks without one last
+    pub fn sort_by_due_ascending(&mut self) {
+        self.tasks.sort_by(|a, b| {
+            (a.due.is_none(), a.due)
+                .cmp(&(b.due.is_none(), b.due))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+    }
+
+    /// Sorts tasks by urgency score, least urgent first
+    pub fn sort_by_urgency_ascending(&mut self) {
+        self.tasks.sort_by(|a, b| {
+            a.urgency()
+                .total_cmp(&b.urgency())
+                .then_with(|| a.id.cmp(&b.id))
+        });
+    }
+
+    /// Sorts tasks in the given order. Tasks without a due date stay last
+    /// when sorting by due date in either direction
+    pub fn sort(&mut self, sort: TaskSort) {
+        match sort.field {
+            SortField::Priority => {
+                self.sort_by_created_ascending();
+                self.sort_by_priority_ascending();
+            }
+            SortField::Due => self.sort_by_due_ascending(),
+            SortField::Created => self.sort_by_created_ascending(),
+            SortField::Modified => self.sort_by_modified_ascending(),
+            SortField::Urgency => self.sort_by_urgency_ascending(),
+        }
+        if sort.descending {
+            self.tasks.reverse();
+            if sort.field == SortField::Due {
+                self.tasks.sort_by_key(|t| t.due.is_none());
+            }
+        }
+    }
+
+    /// Filters out all but the first `limit` tasks, in their current order
+    pub fn limit(&mut self, limit: usize) {
+        for task in self.tasks.iter_mut().filter(|t| !t.filtered).skip(limit) {
+            task.filtered = true;
+        }
     }
 
     /// Sorts tasks by priority (P0 > P1 > P2 > P3)
@@ -371,6 +724,17 @@ impl TaskSet {
         }
     }
 
+    /// Filters to show only tasks delegated to the given person, ignoring case
+    /// and a leading @
+    pub fn filter_delegated_to(&mut self, person: &str) {
+        let person = person.trim_start_matches('@');
+        for task in &mut self.tasks {
+            if !task.delegated_to.eq_ignore_ascii_case(person) {
+                task.filtered = true;
+            }
+        }
+    }
+
     /// Filters to show only organized tasks (with tags or project)
     pub fn filter_organised(&mut self) {
         for task in &mut self.tasks {
@@ -432,6 +796,7 @@ impl TaskSet {
                     created: Utc::now(),
                     resolved: DateTime::<Utc>::from_timestamp(0, 0).unwrap(),
                     priority: PRIORITY_LOW.to_string(),
+                    estimate: 0,
                 });
 
             project.tasks += 1;
@@ -459,6 +824,10 @@ impl TaskSet {
             if task.status != STATUS_RESOLVED && task.priority < project.priority {
                 project.priority = task.priority.clone();
             }
+
+            if task.status != STATUS_RESOLVED {
+                project.estimate += task.estimate.unwrap_or(0);
+            }
         }
 
         let mut names: Vec<String> = projects_map.keys().cloned().collect();
@@ -470,6 +839,34 @@ impl TaskSet {
             .collect()
     }
 
+    /// Counts visible-status tasks that the given context filters out
+    pub fn num_hidden_by_context(&self, ctx: &Query) -> usize {
+        self.tasks
+            .iter()
+            .filter(|t| !HIDDEN_STATUSES.contains(&t.status.as_str()) && !t.matches_filter(ctx))
+            .count()
+    }
+
+    /// Returns the project and tags of a query that no loaded task uses yet
+    pub fn unknown_taxonomy(&self, query: &Query) -> (Option<String>, Vec<String>) {
+        let project = if !query.project.is_empty()
+            && !self.tasks.iter().any(|t| t.project == query.project)
+        {
+            Some(query.project.clone())
+        } else {
+            None
+        };
+
+        let tags = query
+            .tags
+            .iter()
+            .filter(|tag| !self.tasks.iter().any(|t| t.tags.contains(tag)))
+            .cloned()
+            .collect();
+
+        (project, tags)
+    }
+
     /// Returns the total number of tasks
     pub fn num_total(&self) -> usize {
         self.tasks.len()
@@ -505,6 +902,28 @@ impl TaskSet {
         self.update_task(task)
     }
 
+    /// Pauses every active task except those with the given UUIDs, for the
+    /// `single_active_task` preference. Returns each paused task as it was
+    /// before and after
+    pub fn pause_active_except(&mut self, keep: &[&str]) -> Result<Vec<(Task, Task)>> {
+        let active: Vec<Task> = self
+            .tasks
+            .iter()
+            .filter(|t| t.status == STATUS_ACTIVE && !keep.contains(&t.uuid.as_str()))
+            .cloned()
+            .collect();
+
+        let mut paused = Vec::new();
+        for before in active {
+            let mut task = before.clone();
+            task.status = STATUS_PAUSED.to_string();
+            task.write_pending = true;
+            self.update_task(task.clone())?;
+            paused.push((before, task));
+        }
+        Ok(paused)
+    }
+
     /// Apply modifications from a query to filtered tasks
     pub fn apply_modifications(&mut self, query: &Query) -> Result<()> {
         for task in &mut self.tasks {
@@ -522,6 +941,7 @@ impl TaskSet {
             .get(uuid)
             .ok_or_else(|| crate::RstaskError::TaskNotFound(uuid.to_string()))?;
 
+        self.lock_repo()?;
         let task = &self.tasks[idx];
 
         // Delete from disk
@@ -554,3 +974,101 @@ impl TaskSet {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save(repo: &Path, summary: &str, status: &str, resolved: Option<DateTime<Utc>>) {
+        let mut task = Task {
+            uuid: crate::util::must_get_uuid4_string(),
+            summary: summary.to_string(),
+            status: status.to_string(),
+            notes: format!("notes for {}", summary),
+            created: Utc::now(),
+            resolved,
+            ..Default::default()
+        };
+        task.save_to_disk(repo, WriteFormat::default()).unwrap();
+    }
+
+    fn summaries(ts: &TaskSet) -> Vec<String> {
+        let mut summaries: Vec<String> = ts.all_tasks().iter().map(|t| t.summary.clone()).collect();
+        summaries.sort();
+        summaries
+    }
+
+    #[test]
+    fn test_load_options() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = dir.path();
+        let ids = repo.join("ids.bin");
+        let now = Utc::now();
+
+        save(repo, "pending", STATUS_PENDING, None);
+        save(repo, "active", STATUS_ACTIVE, None);
+        save(
+            repo,
+            "old",
+            STATUS_RESOLVED,
+            Some(now - chrono::Duration::days(400)),
+        );
+        save(
+            repo,
+            "recent",
+            STATUS_RESOLVED,
+            Some(now - chrono::Duration::days(2)),
+        );
+
+        let ts = TaskSet::load_with(repo, &ids, &LoadOptions::new(false)).unwrap();
+        assert_eq!(summaries(&ts), vec!["active", "pending"]);
+
+        let options = LoadOptions::new(true).resolved_since(now - chrono::Duration::days(30));
+        let ts = TaskSet::load_with(repo, &ids, &options).unwrap();
+        assert_eq!(summaries(&ts), vec!["active", "pending", "recent"]);
+
+        let options = LoadOptions::statuses(&[STATUS_ACTIVE]).skip_notes(true);
+        let mut ts = TaskSet::load_with(repo, &ids, &options).unwrap();
+        assert_eq!(summaries(&ts), vec!["active"]);
+        assert!(ts.all_tasks()[0].notes.is_empty());
+        assert!(ts.save_pending_changes().is_err());
+
+        let mut ts = TaskSet::load_with(repo, &ids, &LoadOptions::new(true)).unwrap();
+        assert!(
+            ts.all_tasks()
+                .iter()
+                .all(|t| t.notes.starts_with("notes for"))
+        );
+        assert!(ts.save_pending_changes().is_ok());
+    }
+
+    #[test]
+    fn test_partial_load_sees_blockers_it_did_not_load() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = dir.path();
+        let ids = repo.join("ids.bin");
+
+        let mut blocker = Task::new("blocker".to_string());
+        blocker.save_to_disk(repo, WriteFormat::default()).unwrap();
+        let mut done = Task {
+            status: STATUS_RESOLVED.to_string(),
+            resolved: Some(Utc::now()),
+            ..Task::new("done".to_string())
+        };
+        done.save_to_disk(repo, WriteFormat::default()).unwrap();
+        let mut started = Task {
+            status: STATUS_ACTIVE.to_string(),
+            dependencies: vec![blocker.uuid.clone(), done.uuid.clone()],
+            ..Task::new("started".to_string())
+        };
+        started.save_to_disk(repo, WriteFormat::default()).unwrap();
+
+        // Hand out IDs
+        let mut ts = TaskSet::load_with(repo, &ids, &LoadOptions::new(false)).unwrap();
+        ts.save_pending_changes().unwrap();
+        let blocker_id = ts.get_by_uuid(&blocker.uuid).unwrap().id;
+
+        let ts = TaskSet::load_with(repo, &ids, &LoadOptions::statuses(&[STATUS_ACTIVE])).unwrap();
+        assert_eq!(ts.all_tasks()[0].blocked_by, vec![blocker_id]);
+    }
+}
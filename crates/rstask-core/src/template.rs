@@ -0,0 +1,75 @@
+//! Placeholders in templates
+//!
+//! A template's summary and notes can hold placeholders such as
+//! `{{version}}`, which are filled in when a task is added from it.
+
+use std::collections::HashMap;
+
+/// Names of the placeholders in `text`, in the order they first appear
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some((_, name, after)) = next_placeholder(rest) {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = after;
+    }
+    names
+}
+
+/// Replaces the placeholders that have a value, leaving the others as they are
+pub fn fill(text: &str, values: &HashMap<String, String>) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some((before, name, after)) = next_placeholder(rest) {
+        out.push_str(before);
+        match values.get(name) {
+            Some(value) => out.push_str(value),
+            None => out.push_str(&rest[before.len()..rest.len() - after.len()]),
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The text before the first placeholder in `text`, its name and the text
+/// after it. Spaces inside the braces are ignored, so `{{ version }}` is
+/// `version`
+fn next_placeholder(text: &str) -> Option<(&str, &str, &str)> {
+    let mut from = 0;
+    loop {
+        let start = text[from..].find("{{")? + from;
+        let end = text[start + 2..].find("}}")? + start + 2;
+        let name = text[start + 2..end].trim();
+        if !name.is_empty() && !name.contains(['{', '}']) {
+            return Some((&text[..start], name, &text[end + 2..]));
+        }
+        from = start + 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(
+            placeholders("Prepare release {{version}} for {{ team }}, then tag {{version}}"),
+            vec!["version", "team"]
+        );
+        assert!(placeholders("No placeholders, {{}} or {single}").is_empty());
+    }
+
+    #[test]
+    fn test_fill() {
+        let values = HashMap::from([("version".to_string(), "2.1".to_string())]);
+        assert_eq!(
+            fill("Prepare release {{ version }} for {{team}}", &values),
+            "Prepare release 2.1 for {{team}}"
+        );
+        assert_eq!(fill("{{}} and {{version}}", &values), "{{}} and 2.1");
+    }
+}
@@ -0,0 +1,288 @@
+//! Synthetic tasks and repositories for tests and benchmarks
+//!
+//! Built with the `testing` feature. Everything is driven by a seed, so a
+//! failing case can be reproduced by rerunning with the seed it reports.
+
+use crate::Result;
+use crate::constants::*;
+use crate::task::{SubTask, Task, WriteFormat};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use fastrand::Rng;
+use std::path::Path;
+
+/// Statuses that generated tasks are spread across, with their weights
+const STATUS_WEIGHTS: &[(&str, u32)] = &[
+    (STATUS_PENDING, 40),
+    (STATUS_ACTIVE, 5),
+    (STATUS_PAUSED, 5),
+    (STATUS_DELEGATED, 3),
+    (STATUS_DEFERRED, 3),
+    (STATUS_RESOLVED, 44),
+];
+
+const PRIORITIES: &[&str] = &[
+    PRIORITY_CRITICAL,
+    PRIORITY_HIGH,
+    PRIORITY_NORMAL,
+    PRIORITY_LOW,
+];
+
+/// Words that summaries and notes are built from. Some of them need quoting
+/// in YAML, which is what the round-trip tests are after.
+const WORDS: &[&str] = &[
+    "fix",
+    "write",
+    "review",
+    "the",
+    "release",
+    "notes",
+    "for",
+    "café",
+    "日本語",
+    "yes",
+    "null",
+    "42",
+    "3.14",
+    "~",
+    "key: value",
+    "#hash",
+    "- dash",
+    "'quoted'",
+    "\"double\"",
+    "[brackets]",
+    "{braces}",
+    "&anchor",
+    "*alias",
+    "!tag",
+    "%percent",
+    "@at",
+    "`tick`",
+    "back\\slash",
+    "a|b",
+    "> fold",
+    "---",
+];
+
+/// Describes a synthetic repository
+#[derive(Debug, Clone)]
+pub struct RepoSpec {
+    pub tasks: usize,
+    pub projects: usize,
+    pub tags: usize,
+    pub seed: u64,
+}
+
+impl RepoSpec {
+    pub fn new(tasks: usize) -> Self {
+        RepoSpec {
+            tasks,
+            projects: 10,
+            tags: 20,
+            seed: 0,
+        }
+    }
+
+    pub fn projects(mut self, projects: usize) -> Self {
+        self.projects = projects;
+        self
+    }
+
+    pub fn tags(mut self, tags: usize) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Generates the tasks without writing them anywhere
+    pub fn build(&self) -> Vec<Task> {
+        let mut rng = Rng::with_seed(self.seed);
+        (0..self.tasks)
+            .map(|_| random_task(&mut rng, self.projects, self.tags))
+            .collect()
+    }
+
+    /// Writes the tasks as files under `repo_path`, in the status directories
+    /// the loader reads. Nothing is committed.
+    pub fn write(&self, repo_path: &Path) -> Result<Vec<Task>> {
+        let mut tasks = self.build();
+        for task in &mut tasks {
+            task.save_to_disk(repo_path, WriteFormat::default())?;
+        }
+        Ok(tasks)
+    }
+}
+
+/// Generates one valid, normalised task
+pub fn random_task(rng: &mut Rng, projects: usize, tags: usize) -> Task {
+    let status = random_status(rng);
+    let created = random_time(rng);
+
+    let mut task = Task {
+        uuid: random_uuid(rng),
+        status: status.to_string(),
+        summary: random_words(rng, 1..8),
+        notes: random_notes(rng),
+        tags: (0..rng.usize(0..=3.min(tags)))
+            .map(|_| format!("tag{}", rng.usize(0..tags)))
+            .collect(),
+        project: if projects > 0 && rng.u8(0..4) > 0 {
+            format!("project{}", rng.usize(0..projects))
+        } else {
+            String::new()
+        },
+        priority: PRIORITIES[rng.usize(0..PRIORITIES.len())].to_string(),
+        delegated_to: if status == STATUS_DELEGATED {
+            random_words(rng, 1..2)
+        } else {
+            String::new()
+        },
+        subtasks: (0..rng.usize(0..3))
+            .map(|_| SubTask {
+                summary: random_words(rng, 1..4),
+                resolved: rng.bool(),
+            })
+            .collect(),
+        dependencies: (0..rng.usize(0..2)).map(|_| random_uuid(rng)).collect(),
+        created,
+        resolved: (status == STATUS_RESOLVED)
+            .then(|| created + Duration::seconds(rng.i64(0..90 * 86400))),
+        due: rng
+            .bool()
+            .then(|| created + Duration::seconds(rng.i64(-30 * 86400..120 * 86400))),
+        ..Default::default()
+    };
+    task.normalise();
+    task
+}
+
+fn random_status(rng: &mut Rng) -> &'static str {
+    let total: u32 = STATUS_WEIGHTS.iter().map(|(_, w)| w).sum();
+    let mut pick = rng.u32(0..total);
+    for &(status, weight) in STATUS_WEIGHTS {
+        if pick < weight {
+            return status;
+        }
+        pick -= weight;
+    }
+    STATUS_PENDING
+}
+
+fn random_uuid(rng: &mut Rng) -> String {
+    let mut bytes = [0u8; 16];
+    rng.fill(&mut bytes);
+    uuid::Builder::from_random_bytes(bytes)
+        .into_uuid()
+        .to_string()
+}
+
+/// A time in 2020-2030, to the second as stored in task files
+fn random_time(rng: &mut Rng) -> DateTime<Utc> {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    start + Duration::seconds(rng.i64(0..10 * 365 * 86400))
+}
+
+fn random_words(rng: &mut Rng, count: std::ops::Range<usize>) -> String {
+    (0..rng.usize(count))
+        .map(|_| WORDS[rng.usize(0..WORDS.len())])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Markdown notes, possibly several paragraphs and list items, without the
+/// leading or trailing blank lines that the file format doesn't keep
+fn random_notes(rng: &mut Rng) -> String {
+    if rng.bool() {
+        return String::new();
+    }
+
+    let mut lines = Vec::new();
+    for _ in 0..rng.usize(1..6) {
+        match rng.u8(0..4) {
+            0 => lines.push(format!("- [ ] {}", random_words(rng, 1..5))),
+            1 if !lines.is_empty() => lines.push(String::new()),
+            _ => lines.push(random_words(rng, 1..10)),
+        }
+    }
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontmatter::{task_from_markdown, task_to_markdown};
+    use crate::taskset::{LoadOptions, TaskSet};
+
+    const CASES: u64 = 500;
+
+    #[test]
+    fn test_generated_tasks_are_valid() {
+        for task in RepoSpec::new(CASES as usize).build() {
+            task.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_build_is_deterministic() {
+        let a = RepoSpec::new(20).seed(7).build();
+        let b = RepoSpec::new(20).seed(7).build();
+        assert!(a.iter().zip(&b).all(|(a, b)| a.equals(b)));
+
+        let c = RepoSpec::new(20).seed(8).build();
+        assert!(!a.iter().zip(&c).all(|(a, c)| a.equals(c)));
+    }
+
+    #[test]
+    fn test_markdown_round_trip() {
+        for seed in 0..CASES {
+            let mut rng = Rng::with_seed(seed);
+            let task = random_task(&mut rng, 5, 5);
+
+            let md = task_to_markdown(&task).unwrap();
+            let parsed = task_from_markdown(&md, &task.uuid, &task.status, task.id)
+                .unwrap_or_else(|e| panic!("seed {}: {}\n{}", seed, e, md));
+            assert!(
+                parsed.equals(&task),
+                "seed {}: round trip changed the task\n{}\n{:#?}",
+                seed,
+                md,
+                parsed
+            );
+
+            // Serialising again gives the same file
+            assert_eq!(task_to_markdown(&parsed).unwrap(), md, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_loader_reads_generated_repo() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let tasks = RepoSpec::new(300).seed(3).write(dir.path()).unwrap();
+
+        let ts = TaskSet::load_with(
+            dir.path(),
+            &dir.path().join("ids.bin"),
+            &LoadOptions::new(true),
+        )
+        .unwrap();
+        assert_eq!(ts.all_tasks().len(), tasks.len());
+        for task in &tasks {
+            let loaded = ts.get_by_uuid(&task.uuid).unwrap();
+            assert!(loaded.equals(task), "{:#?}\n{:#?}", loaded, task);
+        }
+
+        let open = TaskSet::load_with(
+            dir.path(),
+            &dir.path().join("ids.bin"),
+            &LoadOptions::new(false),
+        )
+        .unwrap();
+        assert_eq!(
+            open.all_tasks().len(),
+            tasks.iter().filter(|t| t.status != STATUS_RESOLVED).count()
+        );
+    }
+}
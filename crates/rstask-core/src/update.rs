@@ -0,0 +1,481 @@
+//! Checking for new releases, and replacing the binary with one
+//!
+//! Release information comes from the GitHub API through `curl`, like the
+//! other network features. Checking is always available and is what the
+//! weekly notice uses; downloading and installing needs the `self-update`
+//! feature.
+//!
+//! Releases are tagged `rstask-v<version>` by the release workflow, next to
+//! `rstask-core-v<version>` for the library, and the binaries workflow adds
+//! a `rstask-<target>.tar.gz` archive and its `.sha256` to each.
+
+use crate::config::Config;
+use crate::http::Request;
+use crate::{Result, RstaskError};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+const REPOSITORY: &str = env!("CARGO_PKG_REPOSITORY");
+pub const RELEASES_PAGE: &str = concat!(env!("CARGO_PKG_REPOSITORY"), "/releases");
+/// Prefix of the tags of rstask's releases, as opposed to rstask-core's
+const TAG_PREFIX: &str = "rstask-v";
+
+/// How often the passive check runs
+const CHECK_INTERVAL: Duration = Duration::days(7);
+/// The passive check must not hold up the command it follows for long
+const CHECK_TIMEOUT_SECS: u32 = 3;
+
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    /// The version from the tag, without its `rstask-v` or `v`
+    pub fn version(&self) -> &str {
+        self.tag_name
+            .strip_prefix(TAG_PREFIX)
+            .unwrap_or(self.tag_name.trim_start_matches('v'))
+    }
+
+    /// Whether this is a published release of the rstask binary
+    fn is_rstask_release(&self) -> bool {
+        let plain_version = self
+            .tag_name
+            .strip_prefix('v')
+            .is_some_and(|v| v.starts_with(|c: char| c.is_ascii_digit()));
+        !self.draft && !self.prerelease && (self.tag_name.starts_with(TAG_PREFIX) || plain_version)
+    }
+
+    /// The binary or archive built for this platform, judged by its name
+    pub fn asset_for_platform(&self) -> Option<&Asset> {
+        let os = match std::env::consts::OS {
+            "macos" => &["apple", "darwin", "macos"][..],
+            "windows" => &["windows"][..],
+            other => &[other][..],
+        };
+        self.assets.iter().find(|a| {
+            let name = a.name.to_lowercase();
+            name.contains(std::env::consts::ARCH)
+                && os.iter().any(|o| name.contains(o))
+                && !is_checksum_name(&name)
+        })
+    }
+
+    /// The checksum file published for an asset: either `<asset>.sha256` or a
+    /// combined `SHA256SUMS`-style file
+    pub fn checksum_asset(&self, asset: &Asset) -> Option<&Asset> {
+        let own = format!("{}.sha256", asset.name);
+        self.assets.iter().find(|a| a.name == own).or_else(|| {
+            self.assets
+                .iter()
+                .find(|a| a.name.to_lowercase().starts_with("sha256sums"))
+        })
+    }
+}
+
+fn is_checksum_name(name: &str) -> bool {
+    name.ends_with(".sha256") || name.starts_with("sha256sums")
+}
+
+/// Parses "1.2.3" or "v1.2.3", ignoring any pre-release suffix
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    Some((
+        parts.next()??,
+        parts.next()??,
+        parts.next().unwrap_or(Some(0))?,
+    ))
+}
+
+/// Whether `latest` is a newer version than `current`
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Downloads a URL
+pub fn fetch(url: &str, timeout_secs: u32) -> Result<Vec<u8>> {
+    let response = Request::new("GET", url)
+        .header("Accept: application/vnd.github+json")
+        .header(format!("User-Agent: rstask/{}", CURRENT_VERSION))
+        .max_time(timeout_secs)
+        .follow_redirects()
+        .send()
+        .map_err(|e| RstaskError::Other(format!("failed to fetch {}: {}", url, e)))?;
+
+    if !response.is_success() {
+        return Err(RstaskError::Other(format!(
+            "failed to fetch {}: the server returned {}",
+            url, response.status
+        )));
+    }
+    Ok(response.body)
+}
+
+/// The newest release of rstask. GitHub's "latest release" may be one of
+/// rstask-core's, so recent releases are listed instead
+pub fn latest_release(timeout_secs: u32) -> Result<Release> {
+    let body = fetch(&releases_api_url(), timeout_secs)?;
+    let releases: Vec<Release> = serde_json::from_slice(&body)?;
+    releases
+        .into_iter()
+        .find(Release::is_rstask_release)
+        .ok_or_else(|| RstaskError::Other(format!("no rstask release found at {}", RELEASES_PAGE)))
+}
+
+/// The GitHub API's list of releases for the repository in Cargo.toml
+fn releases_api_url() -> String {
+    let repo = REPOSITORY
+        .trim_start_matches("https://github.com/")
+        .trim_end_matches('/')
+        .trim_end_matches(".git");
+    format!("https://api.github.com/repos/{}/releases?per_page=30", repo)
+}
+
+/// Where the time and result of the last passive check are kept
+fn check_file(conf: &Config) -> PathBuf {
+    conf.state_file.with_file_name("update_check")
+}
+
+/// Reads "<rfc3339 time> <latest version>"
+fn read_last_check(path: &Path) -> Option<(DateTime<Utc>, String)> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let (time, version) = content.trim().split_once(' ')?;
+    let time = DateTime::parse_from_rfc3339(time).ok()?.with_timezone(&Utc);
+    Some((time, version.to_string()))
+}
+
+/// Prints a notice on stderr when a newer release is out
+///
+/// Looks at most once a week, when `update_check` is on. Failures are
+/// ignored; being offline shouldn't make every command noisy.
+pub fn notify_if_outdated(conf: &Config) {
+    if !conf.preferences.update_check {
+        return;
+    }
+
+    let path = check_file(conf);
+    let now = Utc::now();
+    if read_last_check(&path).is_some_and(|(checked, _)| now - checked < CHECK_INTERVAL) {
+        return;
+    }
+
+    // Record the attempt even if it fails, so an offline machine waits a week too
+    let latest = latest_release(CHECK_TIMEOUT_SECS)
+        .map(|r| r.version().to_string())
+        .unwrap_or_default();
+    let _ = std::fs::write(&path, format!("{} {}\n", now.to_rfc3339(), latest));
+
+    if is_newer(&latest, CURRENT_VERSION) {
+        let how = if cfg!(feature = "self-update") {
+            "run \"rstask self-update\" to install it".to_string()
+        } else {
+            format!("see {}", RELEASES_PAGE)
+        };
+        eprintln!(
+            "\nrstask {} is available (you have {}); {}",
+            latest, CURRENT_VERSION, how
+        );
+    }
+}
+
+/// Hex SHA-256 expected for `asset_name`, from a checksum file holding
+/// either a bare hash or `<hash>  <name>` lines
+#[cfg(feature = "self-update")]
+fn expected_checksum(checksums: &str, asset_name: &str) -> Option<String> {
+    let lines: Vec<&str> = checksums.lines().filter(|l| !l.trim().is_empty()).collect();
+    let hash = match lines.as_slice() {
+        [single] if single.split_whitespace().count() == 1 => single.trim(),
+        _ => lines.iter().find_map(|line| {
+            let mut parts = line.split_whitespace();
+            let hash = parts.next()?;
+            let name = parts.next()?.trim_start_matches('*');
+            (name == asset_name).then_some(hash)
+        })?,
+    };
+    Some(hash.to_lowercase())
+}
+
+#[cfg(feature = "self-update")]
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Downloads the latest release for this platform, verifies its checksum
+/// and replaces the running binary with it
+///
+/// The checksum only catches a corrupted download. It is fetched from the
+/// same release as the binary, so it is no defence against a tampered release.
+///
+/// With `check_only`, just reports whether there is a newer version.
+#[cfg(feature = "self-update")]
+pub fn self_update(check_only: bool) -> Result<()> {
+    let release = latest_release(30)?;
+    let latest = release.version();
+
+    if !is_newer(latest, CURRENT_VERSION) {
+        println!("rstask {} is up to date", CURRENT_VERSION);
+        return Ok(());
+    }
+    if check_only {
+        println!(
+            "rstask {} is available (you have {})",
+            latest, CURRENT_VERSION
+        );
+        return Ok(());
+    }
+
+    let asset = release.asset_for_platform().ok_or_else(|| {
+        RstaskError::Other(format!(
+            "release {} has no build for {}-{}; see {}",
+            latest,
+            std::env::consts::ARCH,
+            std::env::consts::OS,
+            RELEASES_PAGE
+        ))
+    })?;
+    let checksum_asset = release.checksum_asset(asset).ok_or_else(|| {
+        RstaskError::Other(format!(
+            "release {} publishes no checksum for {}, refusing to install it",
+            latest, asset.name
+        ))
+    })?;
+
+    println!("Downloading {}...", asset.name);
+    let data = fetch(&asset.browser_download_url, 300)?;
+    let checksums = fetch(&checksum_asset.browser_download_url, 30)?;
+    let expected = expected_checksum(&String::from_utf8_lossy(&checksums), &asset.name)
+        .ok_or_else(|| RstaskError::Other(format!("no checksum listed for {}", asset.name)))?;
+    let actual = sha256_hex(&data);
+    if actual != expected {
+        return Err(RstaskError::Other(format!(
+            "checksum mismatch for {}: expected {}, got {}",
+            asset.name, expected, actual
+        )));
+    }
+
+    let current_exe = std::env::current_exe()?;
+    let dir = current_exe
+        .parent()
+        .ok_or_else(|| RstaskError::Other("cannot locate the rstask binary".to_string()))?;
+    let staging = tempfile::TempDir::new_in(dir)?;
+    let binary = unpack(&asset.name, &data, staging.path())?;
+    install(&binary, &current_exe)?;
+
+    println!("Updated rstask {} -> {}", CURRENT_VERSION, latest);
+    Ok(())
+}
+
+/// Writes the downloaded asset out, extracting it first if it's an archive,
+/// and returns the path of the binary
+#[cfg(feature = "self-update")]
+fn unpack(name: &str, data: &[u8], dir: &Path) -> Result<PathBuf> {
+    let download = dir.join(name);
+    std::fs::write(&download, data)?;
+
+    let is_zip = name.ends_with(".zip");
+    let is_archive = is_zip
+        || [".tar.gz", ".tgz", ".tar.xz"]
+            .iter()
+            .any(|ext| name.ends_with(ext));
+    if !is_archive {
+        return Ok(download);
+    }
+
+    // GNU tar can't read zip files, but the bsdtar Windows ships can
+    let mut extract = if is_zip && !cfg!(windows) {
+        let mut unzip = std::process::Command::new("unzip");
+        unzip.arg("-q").arg(&download).arg("-d").arg(dir);
+        unzip
+    } else {
+        let mut tar = std::process::Command::new("tar");
+        tar.arg("-xf").arg(&download).arg("-C").arg(dir);
+        tar
+    };
+    let status = extract.status().map_err(|e| {
+        RstaskError::Other(format!(
+            "failed to run {}: {}",
+            extract.get_program().to_string_lossy(),
+            e
+        ))
+    })?;
+    if !status.success() {
+        return Err(RstaskError::Other(format!("failed to extract {}", name)));
+    }
+
+    let binary_name = if cfg!(windows) {
+        "rstask.exe"
+    } else {
+        "rstask"
+    };
+    find_file(dir, binary_name)?
+        .ok_or_else(|| RstaskError::Other(format!("{} contains no {}", name, binary_name)))
+}
+
+#[cfg(feature = "self-update")]
+fn find_file(dir: &Path, name: &str) -> Result<Option<PathBuf>> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if let Some(found) = find_file(&path, name)? {
+                return Ok(Some(found));
+            }
+        } else if path.file_name().is_some_and(|n| n == name) {
+            return Ok(Some(path));
+        }
+    }
+    Ok(None)
+}
+
+/// Moves the new binary into place. The rename is atomic, so a failure
+/// leaves the old binary working. On Windows the old binary is moved aside
+/// first, and moved back if the new one can't take its place.
+#[cfg(feature = "self-update")]
+fn install(binary: &Path, current_exe: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(binary, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    // Windows won't replace a running executable, but it will rename one
+    if cfg!(windows) {
+        let old = current_exe.with_extension("old.exe");
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(current_exe, &old)?;
+
+        if let Err(e) = std::fs::rename(binary, current_exe) {
+            return Err(match std::fs::rename(&old, current_exe) {
+                Ok(()) => e.into(),
+                Err(restore) => RstaskError::Other(format!(
+                    "failed to install the new binary ({}) and to put the old one back \
+                     ({}); it is at {}",
+                    e,
+                    restore,
+                    old.display()
+                )),
+            });
+        }
+        return Ok(());
+    }
+
+    std::fs::rename(binary, current_exe)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("0.4.0", "0.3.0"));
+        assert!(is_newer("v0.3.1", "0.3.0"));
+        assert!(is_newer("1.0", "0.9.9"));
+        assert!(!is_newer("0.3.0", "0.3.0"));
+        assert!(!is_newer("0.2.9", "0.3.0"));
+        assert!(!is_newer("0.3.0-rc.1", "0.3.0"));
+        assert!(!is_newer("", "0.3.0"));
+        assert!(!is_newer("nightly", "0.3.0"));
+    }
+
+    #[test]
+    fn test_release_assets() {
+        let arch = std::env::consts::ARCH;
+        let os = match std::env::consts::OS {
+            "macos" => "apple-darwin",
+            other => other,
+        };
+        let asset_name = format!("rstask-{}-{}.tar.gz", arch, os);
+        let release: Release = serde_json::from_value(serde_json::json!({
+            "tag_name": "rstask-v0.4.0",
+            "assets": [
+                { "name": "rstask-sparc-plan9.tar.gz", "browser_download_url": "https://x/1" },
+                { "name": format!("{}.sha256", asset_name), "browser_download_url": "https://x/2" },
+                { "name": asset_name, "browser_download_url": "https://x/3" },
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(release.version(), "0.4.0");
+        let asset = release.asset_for_platform().unwrap();
+        assert_eq!(asset.name, asset_name);
+        assert_eq!(
+            release.checksum_asset(asset).unwrap().browser_download_url,
+            "https://x/2"
+        );
+    }
+
+    #[test]
+    fn test_rstask_releases() {
+        let release = |tag: &str, prerelease: bool| Release {
+            tag_name: tag.to_string(),
+            draft: false,
+            prerelease,
+            assets: Vec::new(),
+        };
+
+        assert!(release("rstask-v0.4.0", false).is_rstask_release());
+        assert!(release("v0.4.0", false).is_rstask_release());
+        assert_eq!(release("v0.4.0", false).version(), "0.4.0");
+        assert!(!release("rstask-core-v0.4.0", false).is_rstask_release());
+        assert!(!release("rstask-v0.5.0-rc.1", true).is_rstask_release());
+        assert!(releases_api_url().starts_with("https://api.github.com/repos/"));
+        assert!(releases_api_url().ends_with("/rstask/releases?per_page=30"));
+    }
+
+    #[test]
+    fn test_read_last_check() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("update_check");
+        assert!(read_last_check(&path).is_none());
+
+        std::fs::write(&path, "2025-03-01T10:00:00+00:00 0.4.0\n").unwrap();
+        let (time, version) = read_last_check(&path).unwrap();
+        assert_eq!(time.to_rfc3339(), "2025-03-01T10:00:00+00:00");
+        assert_eq!(version, "0.4.0");
+    }
+
+    #[cfg(feature = "self-update")]
+    #[test]
+    fn test_checksums() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            expected_checksum("ABCDEF\n", "rstask.tar.gz").as_deref(),
+            Some("abcdef")
+        );
+        assert_eq!(
+            expected_checksum("111  other.tar.gz\n222 *rstask.tar.gz\n", "rstask.tar.gz")
+                .as_deref(),
+            Some("222")
+        );
+        assert!(expected_checksum("111  other.tar.gz\n", "rstask.tar.gz").is_none());
+    }
+}
@@ -1,7 +1,8 @@
 use crate::Result;
 use crate::constants::*;
 use linkify::{LinkFinder, LinkKind};
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+use std::ops::Range;
 use std::process::{Command, Stdio};
 use uuid::Uuid;
 
@@ -56,7 +57,9 @@ pub fn run_cmd(name: &str, args: &[&str]) -> Result<()> {
     Ok(())
 }
 
-/// Creates a temporary filename for editing
+/// Creates a temporary filename for editing, such as
+/// `rstask-12-fix-the-header.md`. Only ASCII letters, digits and hyphens are
+/// kept from the summary, so the name is valid on every platform
 pub fn make_temp_filename(id: i32, summary: &str, ext: &str) -> String {
     let mut truncated = String::new();
     let mut prev_was_hyphen = true; // Start true to skip leading hyphens
@@ -86,57 +89,175 @@ pub fn make_temp_filename(id: i32, summary: &str, ext: &str) -> String {
     }
 
     let lowered = truncated.to_lowercase();
-    format!("rstask.*.{}-{}.{}", id, lowered, ext)
+    format!("rstask-{}-{}.{}", id, lowered, ext)
 }
 
-/// Opens an editor to edit bytes, returns the edited content
-pub fn must_edit_bytes(data: &[u8], tmp_filename: &str) -> Result<Vec<u8>> {
-    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
-    let editor_parts: Vec<&str> = editor.split_whitespace().collect();
+/// Opens an editor to edit bytes, returns the edited content. The file is
+/// named `tmp_filename` inside a new temporary directory, so editors see
+/// its extension and show the name in their title
+pub fn must_edit_bytes(editor: Option<&str>, data: &[u8], tmp_filename: &str) -> Result<Vec<u8>> {
+    let dir = tempfile::Builder::new().prefix("rstask-").tempdir()?;
+    let path = dir.path().join(tmp_filename);
+    std::fs::write(&path, data)?;
 
-    if editor_parts.is_empty() {
-        return Err(crate::RstaskError::Other("EDITOR is empty".to_string()));
-    }
+    edit_file(editor, &path)?;
 
-    let mut tmpfile = tempfile::Builder::new()
-        .prefix("")
-        .suffix(tmp_filename)
-        .tempfile()?;
+    let edited = std::fs::read(&path)?;
+    Ok(edited)
+}
 
-    tmpfile.write_all(data)?;
-    tmpfile.flush()?;
+/// Editor used when neither the `editor` preference, $VISUAL nor $EDITOR is
+/// set
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vim";
+
+/// The editor command line: the `editor` preference if given, otherwise
+/// $VISUAL, then $EDITOR
+pub fn editor_command(configured: Option<&str>) -> String {
+    configured
+        .map(str::to_string)
+        .or_else(|| std::env::var("VISUAL").ok())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string())
+}
 
-    let path = tmpfile.path().to_path_buf();
+/// Splits an editor command line into the program and its arguments, putting
+/// the file where `{file}` appears, or at the end if it doesn't. Double or
+/// single quotes keep an argument with spaces together, as in
+/// `"C:\Program Files\Vim\gvim.exe" -f`
+pub fn editor_args(command: &str, file: &std::path::Path) -> Vec<std::ffi::OsString> {
+    let file = file.as_os_str();
+    let mut args: Vec<std::ffi::OsString> = Vec::new();
+    let mut placed = false;
+    for word in split_quoted(command) {
+        if word.contains("{file}") {
+            let (before, after) = word.split_once("{file}").unwrap_or_default();
+            let mut arg = std::ffi::OsString::from(before);
+            arg.push(file);
+            arg.push(after);
+            args.push(arg);
+            placed = true;
+        } else {
+            args.push(word.into());
+        }
+    }
+    if !placed {
+        args.push(file.to_os_string());
+    }
+    args
+}
 
-    let mut cmd = Command::new(editor_parts[0]);
-    if editor_parts.len() > 1 {
-        cmd.args(&editor_parts[1..]);
+/// Splits on whitespace outside of double or single quotes, dropping the
+/// quotes
+fn split_quoted(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for c in text.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => word.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            None => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
     }
-    cmd.arg(&path);
+    words
+}
+
+/// Opens a file in the editor and waits for it to exit
+pub fn edit_file(editor: Option<&str>, path: &std::path::Path) -> Result<()> {
+    let command = editor_command(editor);
+    let args = editor_args(&command, path);
+    let Some((program, args)) = args.split_first() else {
+        return Err(crate::RstaskError::Other(
+            "editor command is empty".to_string(),
+        ));
+    };
 
-    let status = cmd
+    let status = Command::new(program)
+        .args(args)
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
-        .status()?;
+        .status()
+        .map_err(|e| {
+            crate::RstaskError::Other(format!(
+                "failed to start editor {}: {}",
+                program.to_string_lossy(),
+                e
+            ))
+        })?;
 
     if !status.success() {
-        return Err(crate::RstaskError::Other(
-            "Failed to run $EDITOR".to_string(),
-        ));
+        return Err(crate::RstaskError::Other(format!(
+            "editor {} exited with {}",
+            program.to_string_lossy(),
+            status
+        )));
     }
-
-    let edited = std::fs::read(&path)?;
-    Ok(edited)
+    Ok(())
 }
 
-/// Opens an editor to edit a string, returns the edited content
-pub fn edit_string(content: &str) -> Result<String> {
-    let bytes = must_edit_bytes(content.as_bytes(), "rstask-edit.md")?;
+/// Opens an editor to edit a string, returns the edited content.
+/// `tmp_filename` names the file the editor sees
+pub fn edit_string(editor: Option<&str>, content: &str, tmp_filename: &str) -> Result<String> {
+    let bytes = must_edit_bytes(editor, content.as_bytes(), tmp_filename)?;
     Ok(String::from_utf8_lossy(&bytes).to_string())
 }
 
+/// Prints a prompt on stderr and reads one line, returns None on end of input
+pub fn prompt_line(msg: &str) -> Result<Option<String>> {
+    eprint!("{}", msg);
+    io::stderr().flush()?;
+
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(input.trim().to_string()))
+}
+
+/// Completes a word against known candidates when exactly one starts with it
+pub fn complete_word(word: &str, candidates: &[String]) -> String {
+    if word.is_empty() || candidates.iter().any(|c| c == word) {
+        return word.to_string();
+    }
+
+    let mut matches = candidates.iter().filter(|c| c.starts_with(word));
+    match (matches.next(), matches.next()) {
+        (Some(only), None) => only.clone(),
+        _ => word.to_string(),
+    }
+}
+
 /// Checks if a slice contains an item
+/// FNV-1a, which is plenty to notice truncated or overwritten files and
+/// gives the same result on every build, unlike std's `DefaultHasher`
+pub fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
 pub fn slice_contains<T: PartialEq>(haystack: &[T], needle: &T) -> bool {
     haystack.contains(needle)
 }
@@ -154,34 +275,43 @@ pub fn deduplicate_strings(strings: &mut Vec<String>) {
 
 /// Extracts URLs from text using linkify (similar to Go's xurls.Relaxed)
 pub fn extract_urls(text: &str) -> Vec<String> {
+    url_spans(text)
+        .into_iter()
+        .map(|span| text[span].to_string())
+        .collect()
+}
+
+/// Byte ranges of the URLs in text, as found by extract_urls
+pub fn url_spans(text: &str) -> Vec<Range<usize>> {
     let mut finder = LinkFinder::new();
     finder.kinds(&[LinkKind::Url]);
     finder
         .links(text)
-        .map(|link| link.as_str().to_string())
+        .map(|link| link.start()..link.end())
         .collect()
 }
 
 /// Opens a URL in the default browser
 pub fn open_browser(url: &str) -> Result<()> {
-    #[cfg(target_os = "linux")]
-    let cmd = "xdg-open";
-
-    #[cfg(target_os = "windows")]
-    let cmd = "cmd";
+    #[cfg(windows)]
+    let mut cmd = {
+        // `start` takes the first quoted word as a window title, hence the
+        // empty one
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", "start", "", &cmd_escape(url)]);
+        cmd
+    };
 
     #[cfg(target_os = "macos")]
-    let cmd = "open";
+    let mut cmd = Command::new("open");
 
-    #[cfg(target_os = "windows")]
-    let args = ["/c", "start", "", url];
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let mut cmd = Command::new("xdg-open");
 
-    #[cfg(not(target_os = "windows"))]
-    let args = [url];
+    #[cfg(not(windows))]
+    cmd.arg(url);
 
-    Command::new(cmd)
-        .args(args)
-        .stdin(Stdio::null())
+    cmd.stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .spawn()
@@ -190,6 +320,37 @@ pub fn open_browser(url: &str) -> Result<()> {
     Ok(())
 }
 
+/// Escapes the characters cmd.exe treats specially, such as the `&` between
+/// URL query parameters
+#[cfg(any(windows, test))]
+fn cmd_escape(arg: &str) -> String {
+    let mut escaped = String::new();
+    for c in arg.chars() {
+        if matches!(c, '^' | '&' | '|' | '<' | '>' | '(' | ')') {
+            escaped.push('^');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A command that runs `command` through the system shell: `sh -c` on
+/// Unix and `cmd /C` on Windows
+pub fn shell_command(command: &str) -> Command {
+    #[cfg(windows)]
+    {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
 /// Gets terminal size (width, height)
 pub fn get_term_size() -> (usize, usize) {
     terminal_size::terminal_size()
@@ -199,7 +360,7 @@ pub fn get_term_size() -> (usize, usize) {
 
 /// Checks if stdout is a TTY
 pub fn stdout_is_tty() -> bool {
-    *FAKE_PTY || termion::is_tty(&std::io::stdout())
+    *FAKE_PTY || io::stdout().is_terminal()
 }
 
 /// Gets the repository path for a given status
@@ -231,18 +392,47 @@ mod tests {
 
     #[test]
     fn test_make_temp_filename() {
-        assert_eq!(make_temp_filename(1, "& &", "md"), "rstask.*.1-.md");
+        assert_eq!(make_temp_filename(1, "& &", "md"), "rstask-1-.md");
         assert_eq!(
             make_temp_filename(99, "A simple summary!", "md"),
-            "rstask.*.99-a-simple-summary.md"
+            "rstask-99-a-simple-summary.md"
         );
         assert_eq!(
             make_temp_filename(1, "& that's that.", "md"),
-            "rstask.*.1-thats-that.md"
+            "rstask-1-thats-that.md"
         );
         assert_eq!(
             make_temp_filename(2147483647, "J's $100, != €100", "md"),
-            "rstask.*.2147483647-js-100-100.md"
+            "rstask-2147483647-js-100-100.md"
+        );
+    }
+
+    #[test]
+    fn test_editor_args() {
+        let file = std::path::Path::new("/tmp/rstask-1-x.md");
+        assert_eq!(
+            editor_args("code --wait", file),
+            vec!["code", "--wait", "/tmp/rstask-1-x.md"]
+        );
+        assert_eq!(
+            editor_args("vim +{file}:1 -c 'set ft=markdown'", file),
+            vec!["vim", "+/tmp/rstask-1-x.md:1", "-c", "set ft=markdown"]
+        );
+        assert_eq!(
+            editor_args("\"C:\\Program Files\\Vim\\gvim.exe\" -f {file}", file),
+            vec![
+                "C:\\Program Files\\Vim\\gvim.exe",
+                "-f",
+                "/tmp/rstask-1-x.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cmd_escape() {
+        assert_eq!(
+            cmd_escape("https://example.com/?a=1&b=(2)"),
+            "https://example.com/?a=1^&b=^(2^)"
         );
     }
 
@@ -288,6 +478,19 @@ mod tests {
         assert_eq!(vec, vec!["a".to_string(), "b".to_string(), "c".to_string()]);
     }
 
+    #[test]
+    fn test_complete_word() {
+        let candidates = vec![
+            "website".to_string(),
+            "work".to_string(),
+            "home".to_string(),
+        ];
+        assert_eq!(complete_word("web", &candidates), "website");
+        assert_eq!(complete_word("w", &candidates), "w");
+        assert_eq!(complete_word("garden", &candidates), "garden");
+        assert_eq!(complete_word("", &candidates), "");
+    }
+
     #[test]
     fn test_extract_urls() {
         let text = "Check out https://example.com and http://test.org for more info";
@@ -0,0 +1,274 @@
+//! Posting task events to the webhooks listed in preferences
+//!
+//! Each event is sent as a JSON object holding the action and the task, in
+//! the same shape as the JSON output of `rstask next`. Events become a
+//! [`Notification`] like any other, and [`WebhookNotifier`] delivers it.
+//! Deliveries run in the background while the command carries on, and are
+//! retried a couple of times with a growing delay. A webhook that still
+//! fails only produces a warning, since the change has already been
+//! committed.
+
+use crate::events::{Subscriber, TaskEvent};
+use crate::http::Request;
+use crate::notify::{BackgroundDeliveries, Notification, Notifier};
+use crate::preferences::{Webhook, WebhookEvent};
+use crate::task::{Task, TaskJson};
+use crate::{Result, RstaskError};
+use serde::Serialize;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const ATTEMPTS: u32 = 3;
+const FIRST_RETRY_DELAY: Duration = Duration::from_millis(500);
+/// Seconds each attempt may take
+const MAX_TIME: u32 = 5;
+
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    action: &'a str,
+    task: TaskJson,
+}
+
+/// Builds the JSON body sent for an event
+pub fn payload(event: WebhookEvent, task: &Task) -> String {
+    serde_json::to_string(&Payload {
+        action: event.as_str(),
+        task: task.to_json(),
+    })
+    .expect("task JSON is always serializable")
+}
+
+/// Builds the notification sent for an event, carrying the payload
+pub fn notification(event: WebhookEvent, task: &Task) -> Notification {
+    Notification {
+        task_uuid: Some(task.uuid.clone()),
+        json: Some(payload(event, task)),
+        ..Notification::new(&format!("Task {}", event.as_str()), &task.summary)
+    }
+}
+
+/// Posts notifications to a webhook as JSON
+pub struct WebhookNotifier {
+    webhook: Webhook,
+}
+
+impl WebhookNotifier {
+    pub fn new(webhook: Webhook) -> Self {
+        WebhookNotifier { webhook }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    /// Posts the notification's JSON, or its title and body when it has none
+    fn notify(&self, notification: &Notification) -> Result<()> {
+        let body = match &notification.json {
+            Some(json) => json.clone(),
+            None => serde_json::json!({
+                "title": notification.title,
+                "body": notification.body,
+            })
+            .to_string(),
+        };
+        deliver(&self.webhook, &body, ATTEMPTS, FIRST_RETRY_DELAY)
+            .map_err(|e| RstaskError::Other(format!("{}: {}", self.webhook.url, e)))
+    }
+}
+
+/// Event subscriber that sends added, started and resolved tasks to webhooks
+///
+/// Failures are reported on stderr rather than returned.
+pub struct WebhookSubscriber {
+    webhooks: Vec<Webhook>,
+    deliveries: BackgroundDeliveries,
+}
+
+impl WebhookSubscriber {
+    pub fn new(webhooks: Vec<Webhook>) -> Self {
+        WebhookSubscriber {
+            webhooks,
+            deliveries: BackgroundDeliveries::default(),
+        }
+    }
+}
+
+impl Subscriber for WebhookSubscriber {
+    fn on_event(&self, event: &TaskEvent) {
+        let (event, task) = match event {
+            TaskEvent::TaskAdded(task) => (WebhookEvent::Added, task),
+            TaskEvent::TaskStarted(task) => (WebhookEvent::Started, task),
+            TaskEvent::TaskResolved(task) => (WebhookEvent::Resolved, task),
+            TaskEvent::TaskModified { .. } | TaskEvent::Synced => return,
+        };
+
+        let notification = notification(event, task);
+        for webhook in self.webhooks.iter().filter(|w| w.wants(event)) {
+            self.deliveries.send(
+                Box::new(WebhookNotifier::new(webhook.clone())),
+                notification.clone(),
+            );
+        }
+    }
+
+    fn finish(&self, deadline: Instant) {
+        let abandoned = self.deliveries.wait(deadline);
+        if abandoned > 0 {
+            eprintln!(
+                "warning: gave up waiting for {} webhook deliveries",
+                abandoned
+            );
+        }
+    }
+}
+
+/// POSTs a body, retrying up to `attempts` times and doubling the delay
+/// between tries
+pub fn deliver(webhook: &Webhook, body: &str, attempts: u32, first_delay: Duration) -> Result<()> {
+    let mut delay = first_delay;
+    let mut last_error = RstaskError::Other("no attempts made".to_string());
+
+    for attempt in 1..=attempts {
+        match post_json(&webhook.url, body) {
+            Ok(()) => return Ok(()),
+            Err(e) => last_error = e,
+        }
+        if attempt < attempts {
+            thread::sleep(delay);
+            delay *= 2;
+        }
+    }
+
+    Err(last_error)
+}
+
+fn post_json(url: &str, body: &str) -> Result<()> {
+    let response = Request::new("POST", url)
+        .header("Content-Type: application/json")
+        .body(body)
+        .max_time(MAX_TIME)
+        .send()?;
+    if !response.is_success() {
+        return Err(RstaskError::Other(format!(
+            "the server returned {}",
+            response.status
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    /// Answers each connection with the next status code and returns the bodies received
+    fn serve(statuses: Vec<u16>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        length = v.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                bodies.push(String::from_utf8(body).unwrap());
+
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+            }
+            bodies
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn test_payload() {
+        let task = Task {
+            uuid: "a1b2".to_string(),
+            summary: "Pay rent".to_string(),
+            status: "pending".to_string(),
+            ..Default::default()
+        };
+        let json: serde_json::Value =
+            serde_json::from_str(&payload(WebhookEvent::Added, &task)).unwrap();
+        assert_eq!(json["action"], "added");
+        assert_eq!(json["task"]["uuid"], "a1b2");
+        assert_eq!(json["task"]["summary"], "Pay rent");
+    }
+
+    #[test]
+    fn test_notifier_posts_the_payload() {
+        let task = Task {
+            uuid: "a1b2".to_string(),
+            summary: "Pay rent".to_string(),
+            ..Default::default()
+        };
+        let (url, server) = serve(vec![200, 200]);
+        let notifier = WebhookNotifier::new(Webhook {
+            url,
+            events: Vec::new(),
+        });
+
+        notifier
+            .notify(&notification(WebhookEvent::Resolved, &task))
+            .unwrap();
+        // Notifications from elsewhere have no payload of their own
+        notifier
+            .notify(&Notification::new("Pomodoro finished", "Pay rent"))
+            .unwrap();
+
+        let bodies = server.join().unwrap();
+        assert_eq!(bodies[0], payload(WebhookEvent::Resolved, &task));
+        let json: serde_json::Value = serde_json::from_str(&bodies[1]).unwrap();
+        assert_eq!(json["title"], "Pomodoro finished");
+        assert_eq!(json["body"], "Pay rent");
+    }
+
+    #[test]
+    fn test_deliver_retries_until_success() {
+        let (url, server) = serve(vec![500, 200]);
+        let webhook = Webhook {
+            url,
+            events: Vec::new(),
+        };
+
+        deliver(&webhook, "{\"a\":1}", 3, Duration::from_millis(10)).unwrap();
+        assert_eq!(server.join().unwrap(), vec!["{\"a\":1}", "{\"a\":1}"]);
+    }
+
+    #[test]
+    fn test_deliver_gives_up() {
+        let (url, server) = serve(vec![503, 503]);
+        let webhook = Webhook {
+            url,
+            events: Vec::new(),
+        };
+
+        assert!(deliver(&webhook, "{}", 2, Duration::from_millis(10)).is_err());
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+}
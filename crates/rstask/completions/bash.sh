@@ -7,16 +7,24 @@ _rstask() {
 
     # Basic command completion
     if [[ ${COMP_CWORD} -eq 1 ]] ; then
-        opts="next add remove template log start stop done context modify edit note undo sync git show open show-open show-active show-paused show-resolved show-templates show-unorganised show-projects show-tags completions help"
+        opts="next add remove template log start stop delegate done context modify edit note undo sync git show open review triage report tick maintenance doctor migrate-format migrate-from-dstask export-csv import-csv jira show-open show-active show-paused show-delegated show-blocked show-stale show-resolved show-templates show-unorganised show-projects show-tags completions help"
+        opts="${opts} $(rstask _completions aliases 2>/dev/null)"
         COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
         return 0
     fi
 
-    # Dynamic completions for specific contexts
+    # Bash splits words at colons, so work on the whole word being typed
+    local line="${COMP_LINE:0:COMP_POINT}"
+    local word="${line##*[[:space:]]}"
+
+    # Task IDs come first, with the filters and changes after them
     case "${cmd}" in
-        done|resolve|show|edit|open|stop|start|modify|note|notes)
-            # Task ID completion
-            if [[ "${cur}" =~ ^[0-9] ]] || [[ ${COMP_CWORD} -eq 2 ]]; then
+        done|resolve|show|edit|open|stop|start|delegate|modify|note|notes|remove|rm)
+            local only_ids=1
+            for ((i = 2; i < COMP_CWORD; i++)); do
+                [[ "${COMP_WORDS[i]}" =~ ^[0-9][0-9,-]*$ ]] || only_ids=0
+            done
+            if [[ ${only_ids} -eq 1 ]] && [[ -z "${cur}" || "${cur}" =~ ^[0-9]+$ ]]; then
                 local ids=$(rstask _completions ids 2>/dev/null)
                 COMPREPLY=( $(compgen -W "${ids}" -- "${cur}") )
                 return 0
@@ -25,41 +33,30 @@ _rstask() {
     esac
 
     # Project completion after project: prefix
-    if [[ "${cur}" == project:* ]]; then
-        local prefix="project:"
-        local search="${cur#project:}"
+    if [[ "${word}" == project:* ]]; then
         local projects=$(rstask _completions projects 2>/dev/null)
-        local suggestions=()
-        for proj in ${projects}; do
-            suggestions+=("${prefix}${proj}")
-        done
-        COMPREPLY=( $(compgen -W "${suggestions[*]}" -- "${cur}") )
+        _rstask_prefixed "project:" "${projects}" "${word}"
+        return 0
+    fi
+
+    # Due date keywords after due: or due.before: and the like
+    if [[ "${word}" =~ ^due(\.[a-z]+)?: ]]; then
+        local keywords=$(rstask _completions due 2>/dev/null)
+        _rstask_prefixed "${word%%:*}:" "${keywords}" "${word}"
         return 0
     fi
 
     # Tag completion after + prefix
     if [[ "${cur}" == +* ]]; then
-        local prefix="+"
-        local search="${cur#+}"
         local tags=$(rstask _completions tags 2>/dev/null)
-        local suggestions=()
-        for tag in ${tags}; do
-            suggestions+=("${prefix}${tag}")
-        done
-        COMPREPLY=( $(compgen -W "${suggestions[*]}" -- "${cur}") )
+        _rstask_prefixed "+" "${tags}" "${cur}"
         return 0
     fi
 
     # Anti-tag completion after - prefix
     if [[ "${cur}" == -* ]] && [[ ! "${cur}" =~ ^--[a-z] ]]; then
-        local prefix="-"
-        local search="${cur#-}"
         local tags=$(rstask _completions tags 2>/dev/null)
-        local suggestions=()
-        for tag in ${tags}; do
-            suggestions+=("${prefix}${tag}")
-        done
-        COMPREPLY=( $(compgen -W "${suggestions[*]}" -- "${cur}") )
+        _rstask_prefixed "-" "${tags}" "${cur}"
         return 0
     fi
 
@@ -73,4 +70,19 @@ _rstask() {
     return 0
 }
 
+# Completes "prefix" followed by each of the given words, leaving out the part
+# of the word before its last colon, which bash keeps as a separate word
+_rstask_prefixed() {
+    local prefix="$1" words="$2" word="$3"
+    local suggestions=()
+    for w in ${words}; do
+        suggestions+=("${prefix}${w}")
+    done
+    COMPREPLY=( $(compgen -W "${suggestions[*]}" -- "${word}") )
+    if [[ "${word}" == *:* && "${COMP_WORDBREAKS}" == *:* ]]; then
+        local colon_prefix="${word%"${word##*:}"}"
+        COMPREPLY=( "${COMPREPLY[@]#"${colon_prefix}"}" )
+    fi
+}
+
 complete -F _rstask rstask
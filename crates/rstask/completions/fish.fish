@@ -8,6 +8,7 @@ complete -c rstask -f -n "__fish_use_subcommand" -a "template" -d "Create or man
 complete -c rstask -f -n "__fish_use_subcommand" -a "log" -d "Log an already completed task"
 complete -c rstask -f -n "__fish_use_subcommand" -a "start" -d "Start working on a task"
 complete -c rstask -f -n "__fish_use_subcommand" -a "stop" -d "Stop working on a task"
+complete -c rstask -f -n "__fish_use_subcommand" -a "delegate" -d "Record who a task is waiting on"
 complete -c rstask -f -n "__fish_use_subcommand" -a "done" -d "Mark a task as done"
 complete -c rstask -f -n "__fish_use_subcommand" -a "context" -d "Set or view context"
 complete -c rstask -f -n "__fish_use_subcommand" -a "modify" -d "Modify task attributes"
@@ -18,31 +19,52 @@ complete -c rstask -f -n "__fish_use_subcommand" -a "sync" -d "Sync with remote"
 complete -c rstask -f -n "__fish_use_subcommand" -a "git" -d "Run git commands"
 complete -c rstask -f -n "__fish_use_subcommand" -a "show" -d "Display a single task"
 complete -c rstask -f -n "__fish_use_subcommand" -a "open" -d "Open URLs in task"
+complete -c rstask -f -n "__fish_use_subcommand" -a "review" -d "Review overdue, stale and unorganised tasks"
+complete -c rstask -f -n "__fish_use_subcommand" -a "report" -d "Print a markdown status report"
+complete -c rstask -f -n "__fish_use_subcommand" -a "triage" -d "Sort out unorganised tasks one at a time"
+complete -c rstask -f -n "__fish_use_subcommand" -a "tick" -d "Add the tasks due from scheduled templates"
+complete -c rstask -f -n "__fish_use_subcommand" -a "maintenance" -d "Remove duplicate task files and run git gc"
+complete -c rstask -f -n "__fish_use_subcommand" -a "doctor" -d "Check task files for problems"
+complete -c rstask -f -n "__fish_use_subcommand" -a "migrate-format" -d "Rewrite every task file as Markdown or YAML"
+complete -c rstask -f -n "__fish_use_subcommand" -a "migrate-from-dstask" -d "Import a dstask repository"
+complete -c rstask -f -n "__fish_use_subcommand" -a "export-csv" -d "Print open tasks as CSV"
+complete -c rstask -f -n "__fish_use_subcommand" -a "import-csv" -d "Add and update tasks from CSV"
+complete -c rstask -f -n "__fish_use_subcommand" -a "jira" -d "Import Jira issues, or link a task to one"
 complete -c rstask -f -n "__fish_use_subcommand" -a "show-open" -d "Show all non-resolved tasks"
 complete -c rstask -f -n "__fish_use_subcommand" -a "show-active" -d "Show active tasks"
 complete -c rstask -f -n "__fish_use_subcommand" -a "show-paused" -d "Show paused tasks"
+complete -c rstask -f -n "__fish_use_subcommand" -a "show-delegated" -d "Show delegated tasks"
+complete -c rstask -f -n "__fish_use_subcommand" -a "show-blocked" -d "Show tasks waiting on another open task"
+complete -c rstask -f -n "__fish_use_subcommand" -a "show-stale" -d "Show tasks that have not changed for a while"
 complete -c rstask -f -n "__fish_use_subcommand" -a "show-resolved" -d "Show resolved tasks"
 complete -c rstask -f -n "__fish_use_subcommand" -a "show-templates" -d "Show task templates"
 complete -c rstask -f -n "__fish_use_subcommand" -a "show-unorganised" -d "Show unorganised tasks"
 complete -c rstask -f -n "__fish_use_subcommand" -a "show-projects" -d "List all projects"
 complete -c rstask -f -n "__fish_use_subcommand" -a "show-tags" -d "List all tags"
 complete -c rstask -f -n "__fish_use_subcommand" -a "completions" -d "Generate shell completions"
+complete -c rstask -f -n "__fish_use_subcommand" -a "(rstask _completions aliases 2>/dev/null)" -d "Alias from your config"
 
 # Global options
 complete -c rstask -s n -l no-context -d "Ignore the current context filter"
 complete -c rstask -s h -l help -d "Print help"
 complete -c rstask -s V -l version -d "Print version"
 
-# Dynamic task ID completions for commands that take IDs
+# Dynamic task ID completions for commands that take IDs. IDs come first, with
+# the filters and changes after them, and the summaries show as descriptions
 function __fish_rstask_task_ids
     set -l token (commandline -t)
     # Only complete IDs if the token is empty or starts with a digit
-    if test -z "$token"; or string match -qr '^\d' -- "$token"
-        rstask _completions ids 2>/dev/null
+    if test -n "$token"; and not string match -qr '^\d' -- "$token"
+        return
+    end
+    set -l words (commandline -opc)
+    for i in (seq 3 (count $words))
+        string match -qr '^\d[\d,-]*$' -- "$words[$i]"; or return
     end
+    rstask _completions tasks 2>/dev/null
 end
 
-complete -c rstask -f -n "__fish_seen_subcommand_from done resolve show edit open stop start modify note notes" -a "(__fish_rstask_task_ids)"
+complete -c rstask -f -n "__fish_seen_subcommand_from done resolve show edit open stop start delegate modify note notes remove rm" -a "(__fish_rstask_task_ids)"
 
 # Dynamic argument completions that check token prefix inside the function
 function __fish_rstask_dynamic_args
@@ -50,7 +72,8 @@ function __fish_rstask_dynamic_args
 
     # Only complete if we've seen a relevant subcommand
     set -l cmd (commandline -opc)[2]
-    if not contains -- $cmd next add log template modify context start done resolve show edit open stop note notes
+    if not contains -- $cmd next add log template modify context start done resolve show edit open stop note notes remove rm delegate triage report
+        and not string match -q -- "show-*" $cmd
         return
     end
 
@@ -63,6 +86,15 @@ function __fish_rstask_dynamic_args
         return
     end
 
+    # Due date keywords (only when token starts with "due:" or "due.<filter>:")
+    if string match -qr -- '^due(\.[a-z]+)?:' $token
+        set -l prefix (string replace -r ':.*' ':' -- $token)
+        for keyword in (rstask _completions due 2>/dev/null)
+            echo "$prefix$keyword"
+        end
+        return
+    end
+
     # Tag completion (only when token starts with "+")
     if string match -q -- "+*" $token
         set -l tags (rstask _completions tags 2>/dev/null)
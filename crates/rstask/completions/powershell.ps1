@@ -0,0 +1,122 @@
+# PowerShell completion for rstask
+#
+# Add the output of `rstask completions powershell` to your $PROFILE, or dot
+# source a file holding it.
+
+Register-ArgumentCompleter -Native -CommandName rstask -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $commands = [ordered]@{
+        'next' = 'Show most important tasks'
+        'add' = 'Add a new task'
+        'remove' = 'Remove a task'
+        'template' = 'Create or manage task templates'
+        'log' = 'Log an already completed task'
+        'start' = 'Start working on a task'
+        'stop' = 'Stop working on a task'
+        'delegate' = 'Record who a task is waiting on'
+        'done' = 'Mark a task as done'
+        'context' = 'Set or view the current context'
+        'modify' = 'Modify task attributes'
+        'edit' = 'Edit a task in your text editor'
+        'note' = 'Add or edit notes for a task'
+        'undo' = 'Undo last n commits'
+        'sync' = 'Synchronize with remote git repository'
+        'git' = 'Run git commands in the task repository'
+        'show' = 'Display a single task with full details'
+        'open' = 'Open URLs found in task summary and notes'
+        'review' = 'Review overdue, stale and unorganised tasks'
+        'report' = 'Print a markdown status report'
+        'triage' = 'Sort out unorganised tasks one at a time'
+        'tick' = 'Add the tasks due from scheduled templates'
+        'maintenance' = 'Remove duplicate task files and run git gc'
+        'doctor' = 'Check task files for problems'
+        'migrate-format' = 'Rewrite every task file as Markdown or YAML'
+        'migrate-from-dstask' = 'Import a dstask repository'
+        'export-csv' = 'Print open tasks as CSV'
+        'import-csv' = 'Add and update tasks from CSV'
+        'jira' = 'Import Jira issues, or link a task to one'
+        'show-open' = 'Show all non-resolved tasks'
+        'show-active' = 'Show active tasks'
+        'show-paused' = 'Show paused tasks'
+        'show-delegated' = 'Show delegated tasks'
+        'show-blocked' = 'Show tasks waiting on another open task'
+        'show-stale' = 'Show tasks that have not changed for a while'
+        'show-resolved' = 'Show resolved tasks'
+        'show-templates' = 'Show task templates'
+        'show-unorganised' = 'Show unorganised tasks'
+        'show-projects' = 'List all projects'
+        'show-tags' = 'List all tags in use'
+        'completions' = 'Generate shell completions'
+    }
+    $idCommands = @('done', 'resolve', 'show', 'edit', 'open', 'stop', 'start', 'delegate', 'modify', 'note', 'notes', 'remove', 'rm')
+
+    function Result([string]$text, [string]$tooltip = $text) {
+        [System.Management.Automation.CompletionResult]::new($text, $text, 'ParameterValue', $tooltip)
+    }
+
+    # The words before the one being completed, leaving out the global flags
+    $words = @($commandAst.CommandElements |
+        Where-Object { $_.Extent.EndOffset -lt $cursorPosition } |
+        Select-Object -Skip 1 |
+        ForEach-Object { $_.Extent.Text } |
+        Where-Object { $_ -notlike '--*' -and $_ -cnotin '-n', '-q' })
+
+    # Command completion, with the aliases from the config
+    if ($words.Count -eq 0) {
+        foreach ($alias in rstask _completions aliases 2>$null) {
+            $commands[$alias] = 'Alias from your config'
+        }
+        foreach ($command in $commands.GetEnumerator()) {
+            if ($command.Key -like "$wordToComplete*") {
+                Result $command.Key $command.Value
+            }
+        }
+        return
+    }
+    $cmd = $words[0]
+
+    # Task IDs come first, with the filters and changes after them
+    $onlyIds = @($words | Select-Object -Skip 1 | Where-Object { $_ -notmatch '^\d[\d,-]*$' }).Count -eq 0
+    if ($idCommands -contains $cmd -and $onlyIds -and $wordToComplete -match '^\d*$') {
+        foreach ($line in rstask _completions tasks 2>$null) {
+            $id, $summary = $line -split "`t", 2
+            if ($id -like "$wordToComplete*") {
+                Result $id $summary
+            }
+        }
+        return
+    }
+
+    if ($wordToComplete -like 'project:*') {
+        foreach ($project in rstask _completions projects 2>$null) {
+            $text = "project:$project"
+            if ($text -like "$wordToComplete*") { Result $text }
+        }
+        return
+    }
+
+    if ($wordToComplete -match '^(due(\.[a-z]+)?:)') {
+        $prefix = $Matches[1]
+        foreach ($keyword in rstask _completions due 2>$null) {
+            $text = "$prefix$keyword"
+            if ($text -like "$wordToComplete*") { Result $text }
+        }
+        return
+    }
+
+    if ($wordToComplete -match '^([+-])' -and $wordToComplete -notlike '--*') {
+        $sign = $Matches[1]
+        foreach ($tag in rstask _completions tags 2>$null) {
+            $text = "$sign$tag"
+            if ($text -like "$wordToComplete*") { Result $text }
+        }
+        return
+    }
+
+    if ($wordToComplete -clike 'P*') {
+        foreach ($priority in 'P0', 'P1', 'P2', 'P3') {
+            if ($priority -like "$wordToComplete*") { Result $priority }
+        }
+    }
+}
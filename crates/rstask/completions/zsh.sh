@@ -11,14 +11,14 @@ _rstask() {
     case $state in
         args)
             case $line[1] in
-                done|resolve|show|edit|open|stop|start|modify|note|notes)
-                    _rstask_task_ids
+                done|resolve|show|edit|open|stop|start|delegate|modify|note|notes|remove|rm)
+                    # Task IDs come first, with the filters and changes after them
+                    if _rstask_only_ids; then
+                        _rstask_task_ids
+                    fi
                     _rstask_filters
                     ;;
-                next|add|log|template)
-                    _rstask_filters
-                    ;;
-                context)
+                next|add|log|template|context|triage|report|show-*)
                     _rstask_filters
                     ;;
             esac
@@ -36,6 +36,7 @@ _rstask_commands() {
         'log:Log an already completed task'
         'start:Start working on a task'
         'stop:Stop working on a task'
+        'delegate:Record who a task is waiting on'
         'done:Mark a task as done'
         'context:Set or view the current context'
         'modify:Modify task attributes'
@@ -46,9 +47,23 @@ _rstask_commands() {
         'git:Run git commands in the task repository'
         'show:Display a single task with full details'
         'open:Open URLs found in task summary and notes'
+        'review:Review overdue, stale and unorganised tasks'
+        'report:Print a markdown status report'
+        'triage:Sort out unorganised tasks one at a time'
+        'tick:Add the tasks due from scheduled templates'
+        'maintenance:Remove duplicate task files and run git gc'
+        'doctor:Check task files for problems'
+        'migrate-format:Rewrite every task file as Markdown or YAML'
+        'migrate-from-dstask:Import a dstask repository'
+        'export-csv:Print open tasks as CSV'
+        'import-csv:Add and update tasks from CSV'
+        'jira:Import Jira issues, or link a task to one'
         'show-open:Show all non-resolved tasks'
         'show-active:Show active tasks'
         'show-paused:Show paused tasks'
+        'show-delegated:Show delegated tasks'
+        'show-blocked:Show tasks waiting on another open task'
+        'show-stale:Show tasks that have not changed for a while'
         'show-resolved:Show resolved tasks'
         'show-templates:Show task templates'
         'show-unorganised:Show unorganised tasks'
@@ -56,13 +71,31 @@ _rstask_commands() {
         'show-tags:List all tags in use'
         'completions:Generate shell completions'
     )
+    local name
+    for name in ${(f)"$(rstask _completions aliases 2>/dev/null)"}; do
+        commands+=("${name}:Alias from your config")
+    done
     _describe -t commands 'rstask commands' commands
 }
 
+# Whether every word between the command and the cursor is a task ID, or
+# a range or list of them
+_rstask_only_ids() {
+    local i
+    for (( i = 2; i < CURRENT; i++ )); do
+        [[ $words[i] =~ '^[0-9][0-9,-]*$' ]] || return 1
+    done
+    [[ -z $PREFIX || $PREFIX == <-> ]]
+}
+
 _rstask_task_ids() {
-    local ids
-    ids=(${(f)"$(rstask _completions ids 2>/dev/null)"})
-    _describe -t ids 'task IDs' ids
+    local tasks task
+    tasks=()
+    # Each line is the ID and summary separated by a tab
+    for task in ${(f)"$(rstask _completions tasks 2>/dev/null)"}; do
+        tasks+=("${task%%$'\t'*}:${task#*$'\t'}")
+    done
+    _describe -t ids 'task IDs' tasks
 }
 
 _rstask_filters() {
@@ -78,6 +111,15 @@ _rstask_filters() {
         return
     fi
 
+    # Due date keywords (due:, due.before: and the like)
+    if [[ $PREFIX == due(.[a-z]##|):* ]]; then
+        local prefix=${PREFIX%%:*}:
+        local keywords
+        keywords=(${(f)"$(rstask _completions due 2>/dev/null)"})
+        compadd -P "$prefix" -a keywords
+        return
+    fi
+
     # Tag completion (with +)
     if [[ $PREFIX == +* ]]; then
         local tags
@@ -1,5 +1,7 @@
-use clap::{Parser, Subcommand};
-use clap_complete::Shell;
+use crate::completions::CompletionShell;
+use clap::{CommandFactory, Parser, Subcommand};
+use rstask_core::preferences::{ListOutput, Preferences};
+use std::ffi::OsString;
 
 #[derive(Parser, Debug)]
 #[command(name = "rstask")]
@@ -10,6 +12,35 @@ pub struct Cli {
     #[arg(long = "no-context", short = 'n', global = true)]
     pub no_context: bool,
 
+    /// Comma separated list of table columns, e.g. id,summary,due
+    #[arg(long = "columns", global = true, value_name = "COLUMNS")]
+    pub columns: Option<String>,
+
+    /// Print JSON output on a single line instead of indented
+    #[arg(long = "compact", global = true)]
+    pub compact: bool,
+
+    /// Fail if any task file can't be read, instead of skipping it
+    #[arg(long = "strict", global = true)]
+    pub strict: bool,
+
+    /// Order of task lists: priority, due, created, modified or urgency,
+    /// optionally followed by :asc or :desc
+    #[arg(long = "sort", global = true, value_name = "FIELD")]
+    pub sort: Option<String>,
+
+    /// Show at most this many tasks
+    #[arg(long = "limit", global = true, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Print only the number of matching tasks
+    #[arg(long = "count", global = true, conflicts_with = "quiet")]
+    pub count: bool,
+
+    /// Print only the ID and summary of each matching task
+    #[arg(long = "quiet", short = 'q', global = true)]
+    pub quiet: bool,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -42,7 +73,20 @@ pub enum Commands {
     ///   rstask add Fix bug +urgent P1 project:web
     ///   rstask add Buy milk / at the store
     ///   rstask add template:5 New task from template
+    ///   rstask add -i    # Prompt for each field
     Add {
+        /// Prompt for summary, project, tags, priority and due date
+        #[arg(short = 'i', long = "interactive")]
+        interactive: bool,
+
+        /// Refuse to create projects or tags that no task uses yet
+        #[arg(long = "no-new")]
+        no_new: bool,
+
+        /// Value for a {{placeholder}} in the template, instead of being asked
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_var)]
+        vars: Vec<(String, String)>,
+
         /// Task description and attributes
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
@@ -68,6 +112,7 @@ pub enum Commands {
     /// Examples:
     ///   rstask template Weekly review / checklist items
     ///   rstask template 34 project:home
+    ///   rstask template Pay rent schedule:"0 9 1 * *"
     Template {
         /// Task ID or template description
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
@@ -132,7 +177,12 @@ pub enum Commands {
     /// Examples:
     ///   rstask modify 15 +urgent -later P1
     ///   rstask modify 15 project:website
+    ///   rstask modify --no-new 15 +urgnet    # Fails instead of creating a new tag
     Modify {
+        /// Refuse to create projects or tags that no task uses yet
+        #[arg(long = "no-new")]
+        no_new: bool,
+
         /// Task IDs and attribute modifications
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
@@ -152,13 +202,55 @@ pub enum Commands {
     /// Examples:
     ///   rstask note 15
     ///   rstask note 15 This is a note
+    ///   rstask note 15 --clear
     #[command(visible_alias = "notes")]
     Note {
+        /// Empty the notes, before appending any text
+        #[arg(long = "clear")]
+        clear: bool,
+
         /// Task ID and optional note text
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
 
+    /// Record who a task is waiting on
+    ///
+    /// Leave out the person to clear it. Delegated tasks are left out of
+    /// next unless the filter includes +delegated.
+    ///
+    /// Examples:
+    ///   rstask delegate 15 alice
+    ///   rstask delegate 15
+    Delegate {
+        /// Task IDs and the person
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Repeat the last command
+    ///
+    /// Runs the last task command again exactly as it was parsed, in the
+    /// current context. `rstask last` shows what that is.
+    Again,
+
+    /// Show the command that `again` would repeat
+    Last,
+
+    /// Show how often each command was run on this machine
+    ///
+    /// Counting is off until usage_stats is set in the config file. The
+    /// counts are kept in the local state file and never sent anywhere.
+    ///
+    /// Examples:
+    ///   rstask usage
+    ///   rstask usage reset
+    Usage {
+        /// Clear the counts
+        #[arg(value_parser = ["reset"])]
+        action: Option<String>,
+    },
+
     /// Undo last n commits
     Undo {
         /// Number of commits to undo (default: 1)
@@ -169,6 +261,171 @@ pub enum Commands {
     /// Synchronize with remote git repository
     Sync,
 
+    /// Two-way sync of open tasks with a CalDAV task list
+    ///
+    /// Uses caldav_url and caldav_username from the config file, and the
+    /// password from the RSTASK_CALDAV_PASSWORD environment variable.
+    #[command(name = "caldav-sync")]
+    CaldavSync,
+
+    /// Notify about tasks that are overdue or due soon
+    ///
+    /// Runs once by default, which suits cron. Notifications go through the
+    /// backends in notification_backends.
+    ///
+    /// Examples:
+    ///   rstask remind
+    ///   rstask remind --daemon
+    Remind {
+        /// Keep running and check every minute
+        #[arg(long = "daemon")]
+        daemon: bool,
+    },
+
+    /// Weekly review of overdue, stale and unorganised tasks
+    ///
+    /// Stale tasks are those that haven't changed for review_stale_days
+    /// (14 by default).
+    ///
+    /// Examples:
+    ///   rstask review
+    ///   rstask review --days 30 +work
+    Review {
+        /// Days without a change before a task counts as stale
+        #[arg(long = "days")]
+        days: Option<u32>,
+
+        /// Task filters
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Write a status report of resolved, open and new tasks
+    ///
+    /// Resolved tasks are grouped by project, followed by open tasks that are
+    /// active, high priority or due within a week, and tasks added in the
+    /// period. The context applies.
+    ///
+    /// Examples:
+    ///   rstask report md
+    ///   rstask report md --since 2w +work
+    Report {
+        /// Report format
+        #[arg(value_parser = ["md"])]
+        format: String,
+
+        /// Start of the period, such as 1w, 3d or 2025-06-01
+        #[arg(long = "since")]
+        since: Option<String>,
+
+        /// Task filters
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Sort out unorganised tasks one at a time
+    ///
+    /// Shows each task without tags or a project, and with --stale each task
+    /// that hasn't changed for a while, and asks for a project, tags,
+    /// priority or due date. Each answer is saved straight away.
+    ///
+    /// Examples:
+    ///   rstask triage
+    ///   rstask triage --stale --days 60
+    Triage {
+        /// Also go through tasks that haven't changed for stale_days
+        #[arg(long = "stale")]
+        stale: bool,
+
+        /// Days without a change before a task counts as stale
+        #[arg(long = "days", requires = "stale")]
+        days: Option<u32>,
+    },
+
+    /// Add the tasks due from scheduled templates
+    ///
+    /// A template with schedule:<when> gets a task copied from it once each
+    /// time the schedule comes round. Suitable for cron; `next` also does
+    /// this first unless tick_before_next is false.
+    ///
+    /// Examples:
+    ///   rstask template Water plants schedule:every:monday
+    ///   rstask tick
+    Tick,
+
+    /// Serve tasks to AI assistants over the Model Context Protocol
+    ///
+    /// Speaks MCP on stdin and stdout, offering the list_tasks, add_task,
+    /// resolve_task and annotate tools. Every change is a normal commit.
+    Mcp,
+
+    /// Install the latest release of rstask
+    ///
+    /// Downloads the build for this platform from GitHub, verifies its
+    /// checksum and replaces the running binary.
+    #[cfg(feature = "self-update")]
+    #[command(name = "self-update")]
+    SelfUpdate {
+        /// Only report whether a newer version exists
+        #[arg(long = "check")]
+        check: bool,
+    },
+
+    /// Install or run git hooks in the task repository
+    ///
+    /// The pre-commit hook refuses commits whose task files rstask couldn't
+    /// load, such as hand edits with broken YAML.
+    ///
+    /// Examples:
+    ///   rstask hook install pre-commit
+    Hook {
+        /// install writes the hook; run is what the hook itself calls
+        #[arg(value_parser = ["install", "run"])]
+        action: String,
+
+        /// Hook to install or run
+        #[arg(value_parser = ["pre-commit"])]
+        hook: String,
+    },
+
+    /// Remove duplicate task files, prune the IDs file and run git gc
+    ///
+    /// Reports file counts, repository size and the largest notes afterwards.
+    Maintenance,
+
+    /// Check every task file for problems
+    ///
+    /// Examples:
+    ///   rstask doctor
+    ///   rstask doctor --fix
+    Doctor {
+        /// Repair the problems that can be repaired, and commit
+        #[arg(long = "fix")]
+        fix: bool,
+    },
+
+    /// Rewrite every task file as Markdown (md) or plain YAML (yml)
+    ///
+    /// Examples:
+    ///   rstask migrate-format md
+    #[command(name = "migrate-format")]
+    MigrateFormat {
+        /// Format to write
+        #[arg(value_parser = ["md", "yml"])]
+        format: String,
+    },
+
+    /// Import a dstask repository, keeping task IDs and the context
+    ///
+    /// Examples:
+    ///   rstask migrate-from-dstask
+    ///   rstask migrate-from-dstask ~/notes/dstask
+    #[command(name = "migrate-from-dstask")]
+    MigrateFromDstask {
+        /// dstask repository, ~/.dstask by default
+        path: Option<String>,
+    },
+
     /// Run git commands in the task repository
     Git {
         /// Git command and arguments
@@ -176,6 +433,72 @@ pub enum Commands {
         args: Vec<String>,
     },
 
+    /// Export tasks with due dates as iCalendar
+    ///
+    /// Writes a VCALENDAR document to stdout with one entry per task that has
+    /// a due date, using the task UUID as the entry UID.
+    ///
+    /// Examples:
+    ///   rstask export-ical > tasks.ics
+    ///   rstask export-ical --events +work > work.ics
+    #[command(name = "export-ical")]
+    ExportIcal {
+        /// Emit all-day VEVENT entries instead of VTODO
+        #[arg(long = "events")]
+        events: bool,
+
+        /// Task filters
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Export open tasks as CSV
+    ///
+    /// Writes a header row and one row per task to stdout, for editing in a
+    /// spreadsheet and importing again with import-csv.
+    ///
+    /// Examples:
+    ///   rstask export-csv > tasks.csv
+    ///   rstask export-csv project:garden > garden.csv
+    #[command(name = "export-csv")]
+    ExportCsv {
+        /// Task filters
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Add and update tasks from a CSV file
+    ///
+    /// Columns are matched by the names in the header row. Rows with the
+    /// UUID of an existing task update it; the others are added.
+    ///
+    /// Examples:
+    ///   rstask import-csv tasks.csv
+    ///   other-tool export | rstask import-csv -
+    #[command(name = "import-csv")]
+    ImportCsv {
+        /// CSV file, or - for stdin
+        path: String,
+    },
+
+    /// Import Jira issues as tasks, or link a task to an issue
+    ///
+    /// Needs jira_url in the config and the API token in RSTASK_JIRA_TOKEN.
+    /// Linked issues open with `rstask open`.
+    ///
+    /// Examples:
+    ///   rstask jira import "assignee = currentUser() AND resolution = Unresolved"
+    ///   rstask jira link 12 OPS-42
+    Jira {
+        /// import runs a JQL search; link sets the issue of a task
+        #[arg(value_parser = ["import", "link"])]
+        action: String,
+
+        /// JQL for import, or the task ID and issue key for link
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
     /// Display a single task with full details and rendered markdown notes
     ///
     /// Examples:
@@ -220,6 +543,38 @@ pub enum Commands {
         args: Vec<String>,
     },
 
+    /// Show tasks delegated to someone
+    #[command(name = "show-delegated")]
+    ShowDelegated {
+        /// Optional person, then task filters
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Show tasks waiting on another open task
+    #[command(name = "show-blocked")]
+    ShowBlocked {
+        /// Task filters
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Show open tasks that haven't changed for a while, oldest first
+    ///
+    /// Examples:
+    ///   rstask show-stale
+    ///   rstask show-stale --days 60 +work
+    #[command(name = "show-stale")]
+    ShowStale {
+        /// Days without a change before a task counts as stale
+        #[arg(long = "days")]
+        days: Option<u32>,
+
+        /// Task filters
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
     /// Show resolved tasks
     #[command(name = "show-resolved")]
     ShowResolved {
@@ -263,33 +618,102 @@ pub enum Commands {
     /// Launch interactive TUI mode
     ///
     /// Opens a full-screen terminal interface for browsing, filtering,
-    /// and acting on tasks interactively.
+    /// and acting on tasks interactively. While it runs, other programs can
+    /// send it reload, filter, select and sync commands through the socket
+    /// at <repo>/.git/rstask/tui.sock.
+    ///
+    /// With --dashboard it becomes a read-only display for a shared screen:
+    /// keys that change tasks do nothing, and it syncs and cycles through the
+    /// filters in dashboard_views on its own.
     ///
     /// Examples:
     ///   rstask tui
-    Tui,
+    ///   rstask tui --dashboard
+    Tui {
+        /// Read-only mode that syncs and cycles views on a timer
+        #[arg(long = "dashboard")]
+        dashboard: bool,
+    },
 
     /// Generate shell completions
     #[command(name = "completions")]
     Completions {
         /// Shell to generate completions for
         #[arg(value_enum)]
-        shell: Shell,
+        shell: CompletionShell,
     },
 
     /// Internal command for dynamic completions (hidden)
     #[command(name = "_completions", hide = true)]
     Complete {
-        /// Completion type: projects, tags, or ids
-        #[arg(value_parser = ["projects", "tags", "ids"])]
+        /// Completion type: projects, tags, ids, tasks (IDs with summaries),
+        /// due or aliases
+        #[arg(value_parser = ["projects", "tags", "ids", "tasks", "due", "aliases"])]
         completion_type: String,
     },
 }
 
+/// Flags parsed by clap rather than being part of the query
+#[derive(Debug, Default)]
+pub struct GlobalOptions {
+    /// Overrides the `columns` preference for table output
+    pub columns: Option<String>,
+    /// Overrides the `json_style` preference with compact output
+    pub compact: bool,
+    /// Overrides the `sort` preference
+    pub sort: Option<String>,
+    /// Overrides the `limit` preference
+    pub limit: Option<usize>,
+    /// Set by `--count` and `--quiet`
+    pub list_output: ListOutput,
+    /// Overrides the `strict_load` preference
+    pub strict: bool,
+    /// Set by `add -i` to prompt for task fields
+    pub interactive: bool,
+    /// Set by `add --no-new` and `modify --no-new`
+    pub no_new: bool,
+    /// Set by `add --var`
+    pub vars: Vec<(String, String)>,
+    /// Set by `note --clear`
+    pub clear: bool,
+    /// Set by `export-ical --events`
+    pub ical_events: bool,
+    /// Set by `remind --daemon`
+    pub daemon: bool,
+    /// Set by `tui --dashboard`
+    pub dashboard: bool,
+    /// Set by `doctor --fix`
+    pub fix: bool,
+    /// Set by `review --days`, `show-stale --days` and `triage --days`
+    pub days: Option<u32>,
+    /// Set by `triage --stale`
+    pub stale: bool,
+    /// Set by `report --since`
+    pub since: Option<String>,
+    /// Set by `self-update --check`
+    #[cfg(feature = "self-update")]
+    pub check_only: bool,
+}
+
 impl Cli {
-    /// Parse command line arguments and return the command name and args
-    pub fn parse_to_command_and_args() -> (String, Vec<String>) {
-        let cli = Cli::parse();
+    /// Parse command line arguments and return the command name, args and global options
+    pub fn parse_to_command_and_args() -> (String, Vec<String>, GlobalOptions) {
+        let cli = Cli::parse_from(expand_alias(std::env::args_os().collect()));
+        let mut options = GlobalOptions {
+            columns: cli.columns.clone(),
+            compact: cli.compact,
+            sort: cli.sort.clone(),
+            limit: cli.limit,
+            list_output: if cli.count {
+                ListOutput::Count
+            } else if cli.quiet {
+                ListOutput::Quiet
+            } else {
+                ListOutput::Full
+            },
+            strict: cli.strict,
+            ..Default::default()
+        };
 
         // Helper to prepend "--" if no-context flag is set
         let maybe_add_context_bypass = |mut args: Vec<String>| -> Vec<String> {
@@ -299,9 +723,21 @@ impl Cli {
             args
         };
 
-        match cli.command {
+        let (cmd, args) = match cli.command {
             Some(Commands::Next { args }) => ("next".to_string(), maybe_add_context_bypass(args)),
-            Some(Commands::Add { args }) => ("add".to_string(), maybe_add_context_bypass(args)),
+            Some(Commands::Add {
+                interactive,
+                no_new,
+                vars,
+                args,
+            }) => {
+                options.interactive = interactive;
+                options.no_new = no_new;
+                let mut args = args;
+                options.vars = vars;
+                options.vars.extend(take_vars(&mut args));
+                ("add".to_string(), maybe_add_context_bypass(args))
+            }
             Some(Commands::Remove { args }) => {
                 ("remove".to_string(), maybe_add_context_bypass(args))
             }
@@ -313,18 +749,82 @@ impl Cli {
             Some(Commands::Stop { args }) => ("stop".to_string(), maybe_add_context_bypass(args)),
             Some(Commands::Done { args }) => ("done".to_string(), maybe_add_context_bypass(args)),
             Some(Commands::Context { args }) => ("context".to_string(), args),
-            Some(Commands::Modify { args }) => {
+            Some(Commands::Modify { no_new, args }) => {
+                options.no_new = no_new;
                 ("modify".to_string(), maybe_add_context_bypass(args))
             }
             Some(Commands::Edit { args }) => ("edit".to_string(), maybe_add_context_bypass(args)),
-            Some(Commands::Note { args }) => ("note".to_string(), maybe_add_context_bypass(args)),
+            Some(Commands::Note { clear, mut args }) => {
+                // After the ID, clap leaves --clear among the words
+                let before = args.len();
+                args.retain(|arg| arg != "--clear");
+                options.clear = clear || args.len() != before;
+                ("note".to_string(), maybe_add_context_bypass(args))
+            }
+            Some(Commands::Delegate { args }) => {
+                ("delegate".to_string(), maybe_add_context_bypass(args))
+            }
             Some(Commands::Undo { args }) => ("undo".to_string(), args),
+            Some(Commands::Again) => ("again".to_string(), vec![]),
+            Some(Commands::Last) => ("last".to_string(), vec![]),
+            Some(Commands::Usage { action }) => ("usage".to_string(), action.into_iter().collect()),
             Some(Commands::Sync) => ("sync".to_string(), vec![]),
-            Some(Commands::Git { args }) => {
-                let mut full_args = vec!["git".to_string()];
-                full_args.extend(args);
-                ("git".to_string(), full_args)
+            Some(Commands::CaldavSync) => ("caldav-sync".to_string(), vec![]),
+            Some(Commands::Mcp) => ("mcp".to_string(), vec![]),
+            #[cfg(feature = "self-update")]
+            Some(Commands::SelfUpdate { check }) => {
+                options.check_only = check;
+                ("self-update".to_string(), vec![])
             }
+            Some(Commands::Remind { daemon }) => {
+                options.daemon = daemon;
+                ("remind".to_string(), vec![])
+            }
+            Some(Commands::Review { days, args }) => {
+                options.days = days;
+                ("review".to_string(), maybe_add_context_bypass(args))
+            }
+            // Markdown is the only format so far
+            Some(Commands::Report {
+                format: _,
+                since,
+                args,
+            }) => {
+                options.since = since;
+                ("report".to_string(), maybe_add_context_bypass(args))
+            }
+            Some(Commands::Triage { stale, days }) => {
+                options.stale = stale;
+                options.days = days;
+                ("triage".to_string(), vec![])
+            }
+            Some(Commands::Tick) => ("tick".to_string(), vec![]),
+            Some(Commands::Hook { action, hook }) => ("hook".to_string(), vec![action, hook]),
+            Some(Commands::Maintenance) => ("maintenance".to_string(), vec![]),
+            Some(Commands::MigrateFormat { format }) => {
+                ("migrate-format".to_string(), vec![format])
+            }
+            Some(Commands::MigrateFromDstask { path }) => (
+                "migrate-from-dstask".to_string(),
+                path.into_iter().collect(),
+            ),
+            Some(Commands::Doctor { fix }) => {
+                options.fix = fix;
+                ("doctor".to_string(), vec![])
+            }
+            Some(Commands::Git { args }) => ("git".to_string(), args),
+            Some(Commands::ExportIcal { events, args }) => {
+                options.ical_events = events;
+                ("export-ical".to_string(), maybe_add_context_bypass(args))
+            }
+            Some(Commands::ExportCsv { args }) => {
+                ("export-csv".to_string(), maybe_add_context_bypass(args))
+            }
+            Some(Commands::ImportCsv { path }) => ("import-csv".to_string(), vec![path]),
+            Some(Commands::Jira { action, args }) => (
+                "jira".to_string(),
+                std::iter::once(action).chain(args).collect(),
+            ),
             Some(Commands::Show { args }) => ("show".to_string(), maybe_add_context_bypass(args)),
             Some(Commands::Open { args }) => ("open".to_string(), maybe_add_context_bypass(args)),
             Some(Commands::ShowOpen { args }) => {
@@ -336,6 +836,16 @@ impl Cli {
             Some(Commands::ShowPaused { args }) => {
                 ("show-paused".to_string(), maybe_add_context_bypass(args))
             }
+            Some(Commands::ShowDelegated { args }) => {
+                ("show-delegated".to_string(), maybe_add_context_bypass(args))
+            }
+            Some(Commands::ShowBlocked { args }) => {
+                ("show-blocked".to_string(), maybe_add_context_bypass(args))
+            }
+            Some(Commands::ShowStale { days, args }) => {
+                options.days = days;
+                ("show-stale".to_string(), maybe_add_context_bypass(args))
+            }
             Some(Commands::ShowResolved { args }) => {
                 ("show-resolved".to_string(), maybe_add_context_bypass(args))
             }
@@ -352,7 +862,10 @@ impl Cli {
             Some(Commands::ShowTags { args }) => {
                 ("show-tags".to_string(), maybe_add_context_bypass(args))
             }
-            Some(Commands::Tui) => ("tui".to_string(), vec![]),
+            Some(Commands::Tui { dashboard }) => {
+                options.dashboard = dashboard;
+                ("tui".to_string(), vec![])
+            }
             Some(Commands::Completions { shell }) => {
                 // Generate enhanced completions with dynamic data
                 crate::completions::generate_completions(shell, &mut std::io::stdout());
@@ -365,6 +878,78 @@ impl Cli {
                 // No subcommand provided - default to "next" command
                 ("next".to_string(), vec![])
             }
+        };
+
+        (cmd, args, options)
+    }
+}
+
+/// Replaces an alias from the config with the words it stands for. The
+/// alias is the first word after the global flags, and can't be the name of
+/// a command, so the config is only read when that word is not one.
+fn expand_alias(mut args: Vec<OsString>) -> Vec<OsString> {
+    let command = Cli::command();
+    let mut i = 1;
+    while let Some(arg) = args.get(i).and_then(|a| a.to_str()) {
+        let Some(flag) = arg.strip_prefix("--") else {
+            if arg.starts_with('-') {
+                i += 1;
+                continue;
+            }
+            break;
+        };
+        let takes_value = command
+            .get_arguments()
+            .find(|a| a.get_long() == Some(flag))
+            .is_some_and(|a| a.get_action().takes_values());
+        i += if takes_value { 2 } else { 1 };
+    }
+
+    let Some(name) = args.get(i).and_then(|a| a.to_str()) else {
+        return args;
+    };
+    if command.find_subcommand(name).is_some() {
+        return args;
+    }
+    if let Some(words) = Preferences::load().unwrap_or_default().alias(name) {
+        args.splice(i..=i, words.into_iter().map(OsString::from));
+    }
+    args
+}
+
+/// Parses `add --var name=value`
+fn parse_var(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected NAME=VALUE, got {}", arg)),
+    }
+}
+
+/// Removes `--var name=value` from the words of `add`, where clap leaves it
+/// when it follows `template:<id>`. Words after `/` are notes and kept as is.
+fn take_vars(args: &mut Vec<String>) -> Vec<(String, String)> {
+    let mut vars = Vec::new();
+    let mut i = 0;
+    while i < args.len() && args[i] != "/" {
+        let value = if args[i] == "--var" && i + 1 < args.len() {
+            args.remove(i);
+            args.remove(i)
+        } else if let Some(value) = args[i].strip_prefix("--var=") {
+            let value = value.to_string();
+            args.remove(i);
+            value
+        } else {
+            i += 1;
+            continue;
+        };
+        match parse_var(&value) {
+            Ok(var) => vars.push(var),
+            Err(e) => Cli::command()
+                .error(clap::error::ErrorKind::InvalidValue, e)
+                .exit(),
         }
     }
+    vars
 }
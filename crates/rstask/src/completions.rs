@@ -1,28 +1,36 @@
-use clap::CommandFactory;
+use clap::{CommandFactory, ValueEnum};
 use clap_complete::{Shell, generate};
 use std::io::Write;
 
 use crate::cli::Cli;
 
+/// Shells that `rstask completions` writes scripts for
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    Elvish,
+    #[value(alias = "pwsh")]
+    Powershell,
+    #[value(alias = "nu")]
+    Nushell,
+}
+
 /// Generate enhanced shell completions with dynamic task data
-pub fn generate_completions<W: Write>(shell: Shell, buf: &mut W) {
-    match shell {
-        Shell::Bash => {
-            let script = include_str!("../completions/bash.sh");
-            let _ = buf.write_all(script.as_bytes());
-        }
-        Shell::Zsh => {
-            let script = include_str!("../completions/zsh.sh");
-            let _ = buf.write_all(script.as_bytes());
-        }
-        Shell::Fish => {
-            let script = include_str!("../completions/fish.fish");
-            let _ = buf.write_all(script.as_bytes());
-        }
-        _ => {
+pub fn generate_completions<W: Write>(shell: CompletionShell, buf: &mut W) {
+    let script = match shell {
+        CompletionShell::Bash => include_str!("../completions/bash.sh"),
+        CompletionShell::Zsh => include_str!("../completions/zsh.sh"),
+        CompletionShell::Fish => include_str!("../completions/fish.fish"),
+        CompletionShell::Powershell => include_str!("../completions/powershell.ps1"),
+        CompletionShell::Nushell => include_str!("../completions/nushell.nu"),
+        CompletionShell::Elvish => {
             // Fall back to basic clap completions for other shells
             let mut cmd = Cli::command();
-            generate(shell, &mut cmd, "rstask", buf);
+            generate(Shell::Elvish, &mut cmd, "rstask", buf);
+            return;
         }
-    }
+    };
+    let _ = buf.write_all(script.as_bytes());
 }
@@ -0,0 +1,163 @@
+//! Unix socket through which other programs drive a running TUI
+//!
+//! Clients send one command per line and get one line back for each, starting
+//! with "ok" or "error". The TUI polls the socket between key presses, so a
+//! command takes effect within a tick.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long a client may take to send its commands once connected
+const READ_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// A request from a client
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    Reload,
+    /// Replace the filter; an empty query clears it
+    Filter(String),
+    /// Select a task by UUID or ID
+    Select(String),
+    Sync,
+}
+
+impl ControlCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (word, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        match word {
+            "reload" => Ok(ControlCommand::Reload),
+            "filter" => Ok(ControlCommand::Filter(rest.to_string())),
+            "select" if !rest.is_empty() => Ok(ControlCommand::Select(rest.to_string())),
+            "select" => Err("select needs a task UUID or ID".to_string()),
+            "sync" => Ok(ControlCommand::Sync),
+            "" => Err("empty command".to_string()),
+            other => Err(format!("unknown command '{}'", other)),
+        }
+    }
+}
+
+/// Listens on the socket and removes it when dropped
+pub struct ControlSocket {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl ControlSocket {
+    /// Binds the socket, replacing a stale one left by a TUI that crashed.
+    /// Fails if another TUI is already listening on it.
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    "another TUI is already listening",
+                ));
+            }
+            std::fs::remove_file(path)?;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        Ok(ControlSocket {
+            listener,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Runs `handle` for every command waiting on the socket, writing its
+    /// reply back to the client. Never blocks for longer than a client's
+    /// read timeout.
+    pub fn poll(&self, mut handle: impl FnMut(ControlCommand) -> Result<String, String>) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            // A client that misbehaves only loses its own connection
+            let _ = serve_client(stream, &mut handle);
+        }
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn serve_client(
+    stream: UnixStream,
+    handle: &mut impl FnMut(ControlCommand) -> Result<String, String>,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match ControlCommand::parse(&line).and_then(&mut *handle) {
+            Ok(message) => format!("ok {}", message),
+            Err(message) => format!("error {}", message),
+        };
+        writeln!(writer, "{}", reply.trim_end())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(ControlCommand::parse("reload"), Ok(ControlCommand::Reload));
+        assert_eq!(
+            ControlCommand::parse("filter +work P1\n"),
+            Ok(ControlCommand::Filter("+work P1".to_string()))
+        );
+        assert_eq!(
+            ControlCommand::parse("filter"),
+            Ok(ControlCommand::Filter(String::new()))
+        );
+        assert_eq!(
+            ControlCommand::parse("select 12"),
+            Ok(ControlCommand::Select("12".to_string()))
+        );
+        assert!(ControlCommand::parse("select").is_err());
+        assert!(ControlCommand::parse("quit").is_err());
+    }
+
+    #[test]
+    fn test_socket_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("tui.sock");
+        let socket = ControlSocket::bind(&path).unwrap();
+
+        // A second TUI can't take over the socket
+        assert!(ControlSocket::bind(&path).is_err());
+
+        let mut client = UnixStream::connect(&path).unwrap();
+        client.write_all(b"reload\nbogus\n").unwrap();
+        client.shutdown(std::net::Shutdown::Write).unwrap();
+
+        let mut received = Vec::new();
+        socket.poll(|cmd| {
+            received.push(cmd);
+            Ok("done".to_string())
+        });
+        assert_eq!(received, vec![ControlCommand::Reload]);
+
+        let replies: Vec<String> = BufReader::new(client).lines().map(|l| l.unwrap()).collect();
+        assert_eq!(replies, vec!["ok done", "error unknown command 'bogus'"]);
+
+        drop(socket);
+        assert!(!path.exists());
+    }
+}
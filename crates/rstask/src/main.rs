@@ -1,25 +1,34 @@
 mod cli;
 mod completions;
+#[cfg(unix)]
+mod control;
 mod tui;
+mod watch;
 
-use cli::Cli;
+use chrono::Utc;
+use cli::{Cli, GlobalOptions};
+use rstask_core::column::parse_columns;
 use rstask_core::commands::*;
 use rstask_core::config::Config;
 use rstask_core::constants::*;
-use rstask_core::git::ensure_repo_exists;
-use rstask_core::local_state::LocalState;
+use rstask_core::date_util::due_keywords;
+use rstask_core::encryption;
+use rstask_core::events::{FINISH_LIMIT, TaskEvent};
+use rstask_core::git::{ensure_repo_exists, git_checkout_default};
+use rstask_core::local_state::{LastCommand, LocalState};
 use rstask_core::query::{Query, parse_query};
-use rstask_core::taskset::TaskSet;
+use rstask_core::taskset::{LoadOptions, TaskSet};
+use rstask_core::{IcalComponent, JsonStyle};
 use std::env;
 use std::process;
 
 fn main() {
     // Parse CLI arguments using clap
-    let (cmd_name, cmd_args) = Cli::parse_to_command_and_args();
+    let (cmd_name, cmd_args, options) = Cli::parse_to_command_and_args();
 
     // Handle TUI command early - it doesn't use the query system
     if cmd_name == "tui" {
-        let conf = Config::new();
+        let conf = load_config(&options);
         match ensure_repo_exists(&conf.repo) {
             Ok(_) => {}
             Err(e) => {
@@ -27,7 +36,7 @@ fn main() {
                 process::exit(1);
             }
         }
-        if let Err(e) = tui::run_tui(conf) {
+        if let Err(e) = tui::run_tui(conf, options.dashboard) {
             eprintln!("TUI error: {}", e);
             process::exit(1);
         }
@@ -42,7 +51,7 @@ fn main() {
     args.extend(cmd_args);
 
     // Parse the query using the existing query parser
-    let query = match parse_query(&args) {
+    let mut query = match parse_query(&args) {
         Ok(q) => q,
         Err(e) => {
             eprintln!("Error parsing command: {}", e);
@@ -52,7 +61,26 @@ fn main() {
 
     // Handle _completions command for dynamic completions
     if query.cmd == "_completions" {
-        let conf = Config::new();
+        let Ok(conf) = Config::new() else {
+            return;
+        };
+        match args.get(1).map(String::as_str) {
+            Some("aliases") => {
+                let mut aliases: Vec<&String> = conf.preferences.aliases.keys().collect();
+                aliases.sort();
+                for alias in aliases {
+                    println!("{}", alias);
+                }
+                return;
+            }
+            Some("due") => {
+                for word in due_keywords() {
+                    println!("{}", word);
+                }
+                return;
+            }
+            _ => {}
+        }
         if ensure_repo_exists(&conf.repo).is_err() {
             // If repo doesn't exist, just exit silently
             return;
@@ -64,9 +92,14 @@ fn main() {
             return;
         };
 
+        // Completions never look at notes
+        let completion_load = LoadOptions::default()
+            .skip_notes(true)
+            .settings(conf.repo_settings());
+
         match completion_type.as_str() {
             "projects" => {
-                if let Ok(ts) = TaskSet::load(&conf.repo, &conf.ids_file, false) {
+                if let Ok(ts) = TaskSet::load_with(&conf.repo, &conf.ids_file, &completion_load) {
                     let projects = ts.get_projects();
                     for project in projects {
                         if !project.name.is_empty() {
@@ -76,7 +109,7 @@ fn main() {
                 }
             }
             "tags" => {
-                if let Ok(ts) = TaskSet::load(&conf.repo, &conf.ids_file, false) {
+                if let Ok(ts) = TaskSet::load_with(&conf.repo, &conf.ids_file, &completion_load) {
                     let tags = ts.get_tags();
                     for tag in tags {
                         println!("{}", tag);
@@ -84,7 +117,7 @@ fn main() {
                 }
             }
             "ids" => {
-                if let Ok(ts) = TaskSet::load(&conf.repo, &conf.ids_file, false) {
+                if let Ok(ts) = TaskSet::load_with(&conf.repo, &conf.ids_file, &completion_load) {
                     let mut ids: Vec<i32> = ts.tasks().iter().map(|t| t.id).collect();
                     ids.sort();
                     for id in ids {
@@ -92,13 +125,48 @@ fn main() {
                     }
                 }
             }
+            "tasks" => {
+                if let Ok(ts) = TaskSet::load_with(&conf.repo, &conf.ids_file, &completion_load) {
+                    let mut tasks = ts.tasks();
+                    tasks.sort_by_key(|t| t.id);
+                    // Tab separated, as fish shows descriptions
+                    for task in tasks {
+                        println!("{}\t{}", task.id, task.summary);
+                    }
+                }
+            }
             _ => {}
         }
         return;
     }
 
     // Initialize config and ensure repo exists
-    let conf = Config::new();
+    let mut conf = load_config(&options);
+
+    if let Some(columns) = &options.columns {
+        let columns = match parse_columns(columns) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        };
+        conf.preferences.resolved_columns = columns.clone();
+        conf.preferences.columns = columns;
+    }
+    conf.preferences.list_output = options.list_output;
+    if options.limit.is_some() {
+        conf.preferences.limit = options.limit;
+    }
+    if let Some(sort) = &options.sort {
+        conf.preferences.sort = match sort.parse() {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        };
+    }
     let repo_was_created = match ensure_repo_exists(&conf.repo) {
         Ok(created) => created,
         Err(e) => {
@@ -109,6 +177,21 @@ fn main() {
 
     // Load state for context
     let mut state = LocalState::load(&conf.state_file);
+
+    // `again` runs the recorded command as if it had been typed
+    if query.cmd == CMD_AGAIN {
+        match &state.last_command {
+            Some(last) => {
+                eprintln!("{}", format_command_line(&last.args));
+                args = last.args.clone();
+                query = last.query.clone();
+            }
+            None => {
+                eprintln!("Error: no command to repeat yet");
+                process::exit(1);
+            }
+        }
+    }
     let mut ctx = state.context.clone();
 
     // Check for context override from environment variable
@@ -140,21 +223,63 @@ fn main() {
 
     // Execute the command
     let result = match query.cmd.as_str() {
-        "" | CMD_NEXT | CMD_SHOW_NEXT => cmd_next(&conf, &ctx, &query),
+        "" | CMD_NEXT | CMD_SHOW_NEXT => {
+            if conf.preferences.tick_before_next {
+                // Output goes to stderr, leaving stdout to the tasks for --json
+                match tick(&conf) {
+                    Ok(added) => {
+                        for task in added {
+                            eprintln!("Added {} {} from its schedule", task.id, task.summary);
+                        }
+                    }
+                    Err(e) => eprintln!("Warning: failed to add scheduled tasks: {}", e),
+                }
+            }
+            cmd_next(&conf, &ctx, &query)
+        }
         CMD_SHOW_OPEN => cmd_show_open(&conf, &ctx, &query),
+        CMD_ADD if options.interactive => cmd_add_interactive(&conf, &ctx, &query),
         CMD_ADD => cmd_add(&conf, &ctx, &query),
         CMD_RM | CMD_REMOVE => cmd_remove(&conf, &ctx, &query),
         CMD_TEMPLATE => cmd_template(&conf, &ctx, &query),
         CMD_LOG => cmd_log(&conf, &ctx, &query),
         CMD_START => cmd_start(&conf, &ctx, &query),
         CMD_STOP => cmd_stop(&conf, &ctx, &query),
+        CMD_DELEGATE => cmd_delegate(&conf, &ctx, &query),
         CMD_DONE | CMD_RESOLVE => cmd_done(&conf, &ctx, &query),
         CMD_CONTEXT => cmd_context(&mut state, &ctx, &query, &args),
         CMD_MODIFY => cmd_modify(&conf, &ctx, &query),
         CMD_EDIT => cmd_edit(&conf, &ctx, &query),
-        CMD_NOTE | CMD_NOTES => cmd_note(&conf, &ctx, &query),
+        CMD_NOTE | CMD_NOTES => cmd_note(&conf, &query, &args, options.clear),
         CMD_UNDO => cmd_undo(&conf, &args),
-        CMD_SYNC => cmd_sync(conf.repo.to_str().unwrap(), false).map(|_| ()),
+        CMD_SYNC => cmd_sync(
+            conf.repo.to_str().unwrap(),
+            conf.repo_settings().lock_timeout,
+            false,
+        )
+        .and_then(|_| state.mark_synced())
+        .map(|()| conf.events.emit(&TaskEvent::Synced)),
+        CMD_CALDAV_SYNC => cmd_caldav_sync(&conf),
+        CMD_REMIND => cmd_remind(&conf, options.daemon),
+        CMD_REVIEW => cmd_review(&conf, &ctx, &query, options.days),
+        CMD_TICK => cmd_tick(&conf),
+        CMD_TRIAGE => cmd_triage(&conf, options.stale, options.days),
+        CMD_REPORT => cmd_report(&conf, &ctx, &query, options.since.as_deref()),
+        CMD_MCP => cmd_mcp(&conf),
+        CMD_LAST => cmd_last(&state),
+        CMD_USAGE => cmd_usage(&conf, &mut state, &args),
+        CMD_HOOK => cmd_hook(&conf, &args),
+        CMD_MAINTENANCE => cmd_maintenance(&conf),
+        CMD_DOCTOR => cmd_doctor(&conf, options.fix),
+        CMD_MIGRATE_FORMAT => cmd_migrate_format(&conf, &args),
+        CMD_MIGRATE_FROM_DSTASK => cmd_migrate_from_dstask(&conf, &args),
+        CMD_IMPORT_CSV => cmd_import_csv(&conf, &args),
+        CMD_JIRA => cmd_jira(&conf, &args),
+        #[cfg(feature = "self-update")]
+        CMD_SELF_UPDATE => cmd_self_update(options.check_only),
+        CMD_GIT if args.get(1).is_some_and(|a| a == "checkout-default") => {
+            git_checkout_default(&conf.repo).map(|msg| println!("{}", msg))
+        }
         CMD_GIT => {
             // Git command - run git directly in the repo
             if args.len() < 2 {
@@ -169,8 +294,13 @@ fn main() {
         }
         CMD_SHOW_ACTIVE => cmd_show_active(&conf, &ctx, &query),
         CMD_SHOW_PAUSED => cmd_show_paused(&conf, &ctx, &query),
+        CMD_SHOW_DELEGATED => cmd_show_delegated(&conf, &ctx, &query),
+        CMD_SHOW_BLOCKED => cmd_show_blocked(&conf, &ctx, &query),
+        CMD_SHOW_STALE => cmd_show_stale(&conf, &ctx, &query, options.days),
         CMD_OPEN => cmd_open(&conf, &ctx, &query),
         CMD_SHOW => cmd_show(&conf, &ctx, &query),
+        CMD_EXPORT_ICAL => cmd_export_ical(&conf, &ctx, &query),
+        CMD_EXPORT_CSV => cmd_export_csv(&conf, &ctx, &query),
         CMD_SHOW_PROJECTS => cmd_show_projects(&conf, &ctx, &query),
         CMD_SHOW_TAGS => cmd_show_tags(&conf, &ctx, &query),
         CMD_SHOW_TEMPLATES => cmd_show_templates(&conf, &ctx, &query),
@@ -182,11 +312,41 @@ fn main() {
         }
     };
 
+    conf.events.finish(FINISH_LIMIT);
     if let Err(e) = result {
         eprintln!("Error: {}", e);
         process::exit(1);
     }
 
+    // Reload rather than reuse `state`, which commands may have saved over
+    let repeatable = !NON_REPEATABLE_CMDS.contains(&query.cmd.as_str());
+    let counted = conf.preferences.usage_stats && query.cmd != CMD_USAGE;
+    if repeatable || counted {
+        let mut state = LocalState::load(&conf.state_file);
+        if repeatable {
+            state.last_command = Some(LastCommand {
+                args: args.clone(),
+                query: query.clone(),
+            });
+        }
+        if counted {
+            let name = if query.cmd.is_empty() {
+                CMD_NEXT
+            } else {
+                &query.cmd
+            };
+            state.record_usage(name, Utc::now());
+        }
+        if let Err(e) = state.save() {
+            eprintln!("Warning: failed to record command: {}", e);
+        }
+    }
+
+    // Only interrupt people at a terminal, not scripts or the MCP/remind loops
+    if query.cmd != CMD_SELF_UPDATE && rstask_core::util::stdout_is_tty() {
+        rstask_core::update::notify_if_outdated(&conf);
+    }
+
     // Print remote help message if repo was just created and this wasn't a git remote command
     let is_git_remote_command = query.cmd == CMD_GIT && args.len() >= 2 && args[1] == "remote";
     if repo_was_created && !is_git_remote_command {
@@ -195,3 +355,32 @@ fn main() {
         println!();
     }
 }
+
+/// Reads the config and applies the command line's overrides to it, exiting
+/// if it can't be used
+fn load_config(options: &GlobalOptions) -> Config {
+    let mut conf = match Config::new() {
+        Ok(conf) => conf,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    };
+
+    conf.refuse_new_taxonomy = options.no_new;
+    conf.template_vars = options.vars.clone();
+    if options.ical_events {
+        conf.preferences.ical_component = IcalComponent::Event;
+    }
+    if options.compact {
+        conf.preferences.json_style = JsonStyle::Compact;
+    }
+    if options.strict {
+        conf.preferences.strict_load = true;
+    }
+    if let Err(e) = encryption::load_key(conf.preferences.notes_key_file.as_deref()) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+    conf
+}
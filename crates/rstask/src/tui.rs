@@ -1,30 +1,61 @@
+#[cfg(unix)]
+use crate::control::{ControlCommand, ControlSocket};
+use crate::watch::RepoWatcher;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    event::{
+        self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyModifiers,
+    },
     execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    terminal::{
+        EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode, size,
+    },
 };
 use ratatui::{
     Frame, Terminal,
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Sparkline, Wrap,
+    },
 };
-use rstask_core::commands::cmd_sync;
+use rstask_core::commands::{auto_sync_if_enabled, cmd_sync, commit_changes};
 use rstask_core::config::Config;
 use rstask_core::constants::*;
+use rstask_core::date_util::{format_minutes, local_midnight};
+use rstask_core::events::{FINISH_LIMIT, TaskEvent};
 use rstask_core::frontmatter::{task_from_markdown, task_to_markdown};
-use rstask_core::git::{git_commit, git_reset};
+use rstask_core::git::{
+    active_since, ahead_behind, git_log_since, git_revert, head_commit, head_warning,
+};
+use rstask_core::hook::check_task_file;
 use rstask_core::local_state::LocalState;
+use rstask_core::notify::{Notification, notify_all};
+use rstask_core::preferences::{
+    NotificationBackend, Preferences, SyncTrigger, TuiColors, TuiTheme,
+};
 use rstask_core::query::{Query, parse_query};
+use rstask_core::table::fit_widths;
 use rstask_core::task::Task;
-use rstask_core::taskset::TaskSet;
-use rstask_core::util::{edit_string, extract_urls, open_browser};
-use std::{fmt::Display, io};
+use rstask_core::taskset::{LoadOptions, TaskSet};
+use rstask_core::util::{edit_file, edit_string, make_temp_filename, open_browser, url_spans};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    io::{self, Write},
+    ops::Range,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    time::{Duration, Instant},
+};
 
-use chrono::Utc;
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate, Utc};
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
 use mdfrier::MdFrier;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 /// Which view the TUI is currently showing
 #[derive(Debug, Clone, PartialEq)]
@@ -35,6 +66,8 @@ enum View {
     Detail,
     /// Editing the note of a task
     EditNote,
+    /// Counts and trends for a weekly review
+    Stats,
 }
 
 /// Which status filter tab is active
@@ -44,7 +77,11 @@ enum StatusTab {
     Pending,
     Active,
     Paused,
+    /// Open tasks handed to someone else
+    Delegated,
     Resolved,
+    /// Templates and recurring tasks
+    Templates,
 }
 
 impl StatusTab {
@@ -54,7 +91,9 @@ impl StatusTab {
             StatusTab::Pending => "Pending",
             StatusTab::Active => "Active",
             StatusTab::Paused => "Paused",
+            StatusTab::Delegated => "Delegated",
             StatusTab::Resolved => "Resolved",
+            StatusTab::Templates => "Templates",
         }
     }
 
@@ -63,26 +102,142 @@ impl StatusTab {
             StatusTab::All => StatusTab::Pending,
             StatusTab::Pending => StatusTab::Active,
             StatusTab::Active => StatusTab::Paused,
-            StatusTab::Paused => StatusTab::Resolved,
-            StatusTab::Resolved => StatusTab::All,
+            StatusTab::Paused => StatusTab::Delegated,
+            StatusTab::Delegated => StatusTab::Resolved,
+            StatusTab::Resolved => StatusTab::Templates,
+            StatusTab::Templates => StatusTab::All,
         }
     }
 
     fn prev(&self) -> Self {
         match self {
-            StatusTab::All => StatusTab::Resolved,
+            StatusTab::All => StatusTab::Templates,
             StatusTab::Pending => StatusTab::All,
             StatusTab::Active => StatusTab::Pending,
             StatusTab::Paused => StatusTab::Active,
-            StatusTab::Resolved => StatusTab::Paused,
+            StatusTab::Delegated => StatusTab::Paused,
+            StatusTab::Resolved => StatusTab::Delegated,
+            StatusTab::Templates => StatusTab::Resolved,
         }
     }
 }
 
+/// Colours the config can change, with `tui_colors` applied over the
+/// built-in ones
+struct ThemeColors {
+    selection: Color,
+    border: Color,
+    /// P0 to P3
+    priorities: [Color; 4],
+    active: Color,
+    paused: Color,
+    pending: Color,
+    resolved: Color,
+    search_match: Color,
+}
+
+impl ThemeColors {
+    /// Also returns the settings that aren't colours, which keep the
+    /// built-in colour
+    fn new(colors: &TuiColors) -> (Self, Vec<String>) {
+        let mut invalid = Vec::new();
+        let mut pick = |name: &str, value: &Option<String>, default: Color| match value {
+            Some(value) => value.parse().unwrap_or_else(|_| {
+                invalid.push(format!("{} \"{}\"", name, value));
+                default
+            }),
+            None => default,
+        };
+        let theme = ThemeColors {
+            selection: pick("selection", &colors.selection, Color::Indexed(236)),
+            border: pick("border", &colors.border, Color::Reset),
+            priorities: [
+                pick("p0", &colors.p0, Color::Red),
+                pick("p1", &colors.p1, Color::Yellow),
+                pick("p2", &colors.p2, Color::White),
+                pick("p3", &colors.p3, Color::DarkGray),
+            ],
+            active: pick("active", &colors.active, Color::Green),
+            paused: pick("paused", &colors.paused, Color::Yellow),
+            pending: pick("pending", &colors.pending, Color::Blue),
+            resolved: pick("resolved", &colors.resolved, Color::DarkGray),
+            search_match: pick("search_match", &colors.search_match, Color::Yellow),
+        };
+        (theme, invalid)
+    }
+}
+
+/// Frames of the spinner shown while a sync runs
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// A sync running on a background thread
+struct SyncJob {
+    result: Receiver<Result<String, String>>,
+    started: Instant,
+}
+
+/// How long a transient status message stays up
+const TRANSIENT_STATUS: Duration = Duration::from_secs(3);
+
+/// How long a message stays in the status bar, None if until the next one
+fn status_lifetime(prefs: &Preferences, is_error: bool) -> Option<Duration> {
+    let seconds = if is_error {
+        prefs.tui_error_seconds
+    } else {
+        prefs.tui_status_seconds
+    };
+    (seconds > 0).then(|| Duration::from_secs(seconds))
+}
+
 /// A status message shown temporarily at the bottom
 struct StatusMessage {
     text: String,
     is_error: bool,
+    /// When the message goes away by itself, if it does
+    expires: Option<Instant>,
+}
+
+/// Marks each URL in text with its number among `urls`, adding the ones not
+/// seen yet. The number goes after the closing parenthesis of a Markdown
+/// link so the link still renders.
+fn number_links(text: &str, urls: &mut Vec<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    for span in url_spans(text) {
+        let url = &text[span.clone()];
+        let number = match urls.iter().position(|u| u == url) {
+            Some(i) => i + 1,
+            None => {
+                urls.push(url.to_string());
+                urls.len()
+            }
+        };
+        let before = &text[..span.start];
+        let close = if before.ends_with("](") {
+            Some(')')
+        } else if before.ends_with('<') {
+            Some('>')
+        } else {
+            None
+        };
+        let end = close
+            .and_then(|c| text[span.end..].find(c))
+            .map_or(span.end, |i| span.end + i + 1);
+        out.push_str(&text[copied..end]);
+        out.push_str(&format!(" [{}]", number));
+        copied = end;
+    }
+    out.push_str(&text[copied..]);
+    out
+}
+
+/// The URLs in a task's summary and notes, in the order the detail view
+/// numbers them
+fn task_links(task: &Task) -> Vec<String> {
+    let mut urls = Vec::new();
+    number_links(&task.summary, &mut urls);
+    number_links(&task.notes, &mut urls);
+    urls
 }
 
 /// State for the URL selection popup
@@ -140,10 +295,97 @@ struct NoteEditor {
     cursor_row: usize,
     /// Cursor column (byte offset within the line)
     cursor_col: usize,
-    /// Scroll offset for the editor view
+    /// Scroll offset for the editor view, in visual rows
     scroll: usize,
+    /// Columns lines wrap at, as of the last draw; 0 doesn't wrap
+    wrap_width: usize,
     /// UUID of the task being edited
     task_uuid: String,
+    /// States to go back to with Ctrl+Z, oldest first
+    undo: Vec<EditorSnapshot>,
+    /// States undone, to go forward to again with Ctrl+Y
+    redo: Vec<EditorSnapshot>,
+    /// The kind of the last change, while the cursor hasn't moved since
+    last_edit: Option<EditKind>,
+}
+
+/// Most changes the note editor can undo
+const NOTE_UNDO_LIMIT: usize = 200;
+
+/// Text and cursor of the note editor before a change
+#[derive(Debug, Clone, PartialEq)]
+struct EditorSnapshot {
+    lines: Vec<String>,
+    cursor_row: usize,
+    cursor_col: usize,
+}
+
+/// Changes of the same kind in a row are undone together, so a paste, which
+/// arrives as a run of keys, goes away with one Ctrl+Z
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EditKind {
+    Insert,
+    Delete,
+}
+
+/// A line, or the part of one, shown on one row of the editor
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct VisualRow {
+    line: usize,
+    /// Byte range within the line
+    start: usize,
+    end: usize,
+}
+
+/// Byte offsets where each row of `line` starts when soft wrapped at `width`
+/// columns. Lines break after a space where they can; spaces at the break
+/// stay at the end of the row above. A word longer than a row is split.
+fn wrap_points(line: &str, width: usize) -> Vec<usize> {
+    let mut starts = vec![0];
+    if width == 0 {
+        return starts;
+    }
+    let mut row_start = 0;
+    let mut row_width = 0;
+    let mut last_break = None;
+    for (i, c) in line.char_indices() {
+        let w = c.width().unwrap_or(0);
+        if c == ' ' {
+            row_width += w;
+            last_break = Some(i + 1);
+            continue;
+        }
+        if row_width + w > width && i > row_start {
+            let start = match last_break {
+                Some(b) if b > row_start => b,
+                _ => i,
+            };
+            starts.push(start);
+            row_start = start;
+            row_width = line[start..i].width();
+            last_break = None;
+        }
+        row_width += w;
+    }
+    starts
+}
+
+/// Where the word after `col` starts, or the end of the line
+fn next_word(line: &str, col: usize) -> usize {
+    let rest = &line[col..];
+    let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let gap = rest[word_end..]
+        .find(|c: char| !c.is_whitespace())
+        .unwrap_or(rest.len() - word_end);
+    col + word_end + gap
+}
+
+/// Where the word before `col` starts
+fn previous_word(line: &str, col: usize) -> usize {
+    let before = line[..col].trim_end();
+    before.rfind(char::is_whitespace).map_or(0, |i| {
+        i + before[i..].chars().next().map_or(1, char::len_utf8)
+    })
 }
 
 impl NoteEditor {
@@ -168,15 +410,85 @@ impl NoteEditor {
             cursor_row: 0,
             cursor_col: 0,
             scroll: 0,
+            wrap_width: 0,
             task_uuid: task_uuid.to_string(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_edit: None,
+        }
+    }
+
+    fn snapshot(&self) -> EditorSnapshot {
+        EditorSnapshot {
+            lines: self.lines.clone(),
+            cursor_row: self.cursor_row,
+            cursor_col: self.cursor_col,
+        }
+    }
+
+    fn restore(&mut self, snapshot: EditorSnapshot) {
+        self.lines = snapshot.lines;
+        self.cursor_row = snapshot.cursor_row;
+        self.cursor_col = snapshot.cursor_col;
+        self.last_edit = None;
+    }
+
+    /// Call before changing the text. Starts a new undo step unless this
+    /// continues the last change.
+    fn record(&mut self, kind: EditKind) {
+        if self.last_edit != Some(kind) {
+            self.undo.push(self.snapshot());
+            if self.undo.len() > NOTE_UNDO_LIMIT {
+                self.undo.remove(0);
+            }
         }
+        self.redo.clear();
+        self.last_edit = Some(kind);
     }
 
-    /// Ensure cursor_col is valid for current line
-    fn clamp_cursor_col(&mut self) {
-        let line_len = self.lines[self.cursor_row].len();
-        if self.cursor_col > line_len {
-            self.cursor_col = line_len;
+    /// Inserts pasted text at the cursor as one change, keeping its lines
+    fn paste(&mut self, pasted: &str) {
+        let pasted = pasted
+            .replace("\r\n", "\n")
+            .replace('\r', "\n")
+            .replace('\t', "    ");
+        self.record(EditKind::Insert);
+        self.last_edit = None;
+
+        let tail = self.lines[self.cursor_row].split_off(self.cursor_col);
+        let mut pieces = pasted.split('\n');
+        if let Some(first) = pieces.next() {
+            self.lines[self.cursor_row].push_str(first);
+        }
+        for piece in pieces {
+            self.cursor_row += 1;
+            self.lines.insert(self.cursor_row, piece.to_string());
+        }
+        self.cursor_col = self.lines[self.cursor_row].len();
+        self.lines[self.cursor_row].push_str(&tail);
+    }
+
+    /// Goes back a step. Returns false when there's nothing to undo.
+    fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(snapshot) => {
+                self.redo.push(self.snapshot());
+                self.restore(snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Goes forward a step undone. Returns false when there's nothing to redo.
+    fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(snapshot) => {
+                self.undo.push(self.snapshot());
+                self.restore(snapshot);
+                true
+            }
+            None => false,
         }
     }
 
@@ -185,11 +497,115 @@ impl NoteEditor {
         if visible_height == 0 {
             return;
         }
-        if self.cursor_row < self.scroll {
-            self.scroll = self.cursor_row;
+        let row = self.cursor_visual_row(&self.visual_rows());
+        if row < self.scroll {
+            self.scroll = row;
+        }
+        if row >= self.scroll + visible_height {
+            self.scroll = row - visible_height + 1;
+        }
+    }
+
+    /// Every line split into the rows it takes up once wrapped
+    fn visual_rows(&self) -> Vec<VisualRow> {
+        let mut rows = Vec::new();
+        for (line, text) in self.lines.iter().enumerate() {
+            let starts = wrap_points(text, self.wrap_width);
+            for (i, &start) in starts.iter().enumerate() {
+                let end = starts.get(i + 1).copied().unwrap_or(text.len());
+                rows.push(VisualRow { line, start, end });
+            }
+        }
+        rows
+    }
+
+    /// The row the cursor is on. At a wrap point it belongs to the row below.
+    fn cursor_visual_row(&self, rows: &[VisualRow]) -> usize {
+        rows.iter()
+            .rposition(|r| r.line == self.cursor_row && r.start <= self.cursor_col)
+            .unwrap_or(0)
+    }
+
+    /// Whether `row` is followed by more of the same line
+    fn continues(rows: &[VisualRow], row: usize) -> bool {
+        rows.get(row + 1)
+            .is_some_and(|next| next.line == rows[row].line)
+    }
+
+    /// The last place the cursor can be on `row` without moving to the next
+    fn row_end(&self, rows: &[VisualRow], row: usize) -> usize {
+        let r = rows[row];
+        if Self::continues(rows, row) {
+            self.lines[r.line][..r.end]
+                .char_indices()
+                .last()
+                .map_or(r.start, |(i, _)| i.max(r.start))
+        } else {
+            r.end
+        }
+    }
+
+    /// Moves up or down a row as shown, keeping to the same column where
+    /// the row is long enough
+    fn move_vertical(&mut self, down: bool) {
+        let rows = self.visual_rows();
+        let current = self.cursor_visual_row(&rows);
+        let target = if down {
+            current + 1
+        } else {
+            match current.checked_sub(1) {
+                Some(t) => t,
+                None => return,
+            }
+        };
+        if target >= rows.len() {
+            return;
+        }
+
+        let r = rows[current];
+        let x = self.lines[r.line][r.start..self.cursor_col].width();
+        let t = rows[target];
+        let text = &self.lines[t.line];
+        let mut col = t.start;
+        let mut used = 0;
+        for (i, c) in text[t.start..t.end].char_indices() {
+            let w = c.width().unwrap_or(0);
+            if used + w > x {
+                break;
+            }
+            used += w;
+            col = t.start + i + c.len_utf8();
         }
-        if self.cursor_row >= self.scroll + visible_height {
-            self.scroll = self.cursor_row - visible_height + 1;
+        self.cursor_row = t.line;
+        self.cursor_col = col.min(self.row_end(&rows, target));
+    }
+
+    /// Home and End, on the row as shown
+    fn move_to_row_edge(&mut self, end: bool) {
+        let rows = self.visual_rows();
+        let row = self.cursor_visual_row(&rows);
+        self.cursor_col = if end {
+            self.row_end(&rows, row)
+        } else {
+            rows[row].start
+        };
+    }
+
+    /// Ctrl+arrow: to the next or previous word, across lines
+    fn move_word(&mut self, forward: bool) {
+        let line = &self.lines[self.cursor_row];
+        if forward {
+            if self.cursor_col < line.len() {
+                self.cursor_col = next_word(line, self.cursor_col);
+            } else if self.cursor_row + 1 < self.lines.len() {
+                self.cursor_row += 1;
+                self.cursor_col = 0;
+            }
+        } else if self.cursor_col > 0 {
+            self.cursor_col = previous_word(line, self.cursor_col);
+        } else if self.cursor_row > 0 {
+            self.cursor_row -= 1;
+            self.cursor_col = self.lines[self.cursor_row].len();
         }
     }
 }
@@ -205,8 +621,6 @@ impl Display for NoteEditor {
 enum ConfirmAction {
     /// Remove a task (stores uuid and summary for display)
     RemoveTask { uuid: String, summary: String },
-    /// Undo last git commit
-    Undo,
 }
 
 /// State for confirmation popup
@@ -221,185 +635,1577 @@ impl ConfirmPopup {
             ConfirmAction::RemoveTask { summary, .. } => {
                 format!("Remove task \"{}\"?", summary)
             }
-            ConfirmAction::Undo => "Undo last commit? This cannot be reversed.".to_string(),
         };
         ConfirmPopup { action, message }
     }
 }
 
-/// State for the add-task input mode
-struct AddTaskInput {
-    /// Raw input text (summary + inline tags/project/priority)
-    text: String,
-    /// Cursor position (byte offset)
-    cursor: usize,
-    /// Whether to immediately resolve the task (log mode)
-    resolve_immediately: bool,
+/// A change committed from this TUI session, which the undo popup can
+/// reverse and reapply
+#[derive(Debug, Clone)]
+struct SessionChange {
+    description: String,
+    /// The commit that makes the change: the original one, or the latest redo
+    applied: String,
+    /// The commit that reversed it, while it's undone
+    undone_by: Option<String>,
 }
 
-impl AddTaskInput {
-    fn new() -> Self {
-        AddTaskInput {
-            text: String::new(),
-            cursor: 0,
-            resolve_immediately: false,
+impl SessionChange {
+    /// The commit to revert next, and what doing so amounts to
+    fn next_revert(&self) -> (&str, &'static str) {
+        match &self.undone_by {
+            None => (&self.applied, "Undo"),
+            Some(undo) => (undo, "Redo"),
+        }
+    }
+
+    /// Records the commit that just reverted `next_revert()`
+    fn reverted(&mut self, commit: String) {
+        match self.undone_by.take() {
+            None => self.undone_by = Some(commit),
+            Some(_) => self.applied = commit,
         }
     }
 }
 
-/// State for the context management popup
-struct ContextPopup {
-    /// Input text for setting a new context
-    text: String,
-    /// Cursor position
-    cursor: usize,
+/// State for the undo popup, which lists the session's changes newest first
+struct UndoPopup {
+    selected: usize,
 }
 
-impl ContextPopup {
-    fn new(current_context: &Query) -> Self {
-        // Pre-fill with the current context as text
-        let text = context_to_display_string(current_context);
-        let cursor = text.len();
-        ContextPopup { text, cursor }
-    }
+/// A pomodoro countdown started from the TUI
+struct Pomodoro {
+    /// The task worked on, as "12 Summary"
+    label: String,
+    ends: Instant,
 }
 
-/// Convert a Query context to a display string
-fn context_to_display_string(q: &Query) -> String {
-    let mut parts = Vec::new();
-    for tag in &q.tags {
-        parts.push(format!("+{}", tag));
-    }
-    for tag in &q.anti_tags {
-        parts.push(format!("-{}", tag));
+/// "1:02:03", or "2:03" under an hour
+fn format_elapsed(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let (hours, minutes, secs) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
     }
-    if !q.project.is_empty() {
-        parts.push(format!("project:{}", q.project));
+}
+
+/// Tells the user a pomodoro is over. The bell is rung directly, since the
+/// bell backend also prints a line that would land in the middle of the TUI.
+fn notify_pomodoro(prefs: &Preferences, label: &str) -> rstask_core::Result<()> {
+    let backends = prefs.notification_backends();
+    if backends.contains(&NotificationBackend::Bell) {
+        let mut stdout = io::stdout();
+        write!(stdout, "\x07")?;
+        stdout.flush()?;
     }
-    if !q.priority.is_empty() {
-        parts.push(q.priority.clone());
+
+    let others: Vec<_> = backends
+        .iter()
+        .copied()
+        .filter(|b| *b != NotificationBackend::Bell)
+        .collect();
+    if others.is_empty() {
+        return Ok(());
     }
-    parts.join(" ")
+    let prefs = Preferences {
+        notification_backends: others,
+        ..prefs.clone()
+    };
+    notify_all(&prefs, &Notification::new("Pomodoro finished", label))
 }
 
-/// Application state
-struct App {
-    conf: Config,
-    /// All non-resolved tasks (unfiltered)
-    all_tasks: Vec<Task>,
-    /// Indices into all_tasks that pass the current filter
-    filtered_indices: Vec<usize>,
-    /// List widget state (selection)
-    list_state: ListState,
-    /// Current view
-    view: View,
-    /// Search/filter input string
-    filter_text: String,
-    /// Whether the filter input is focused
-    filter_active: bool,
-    /// Status tab filter
-    status_tab: StatusTab,
-    /// Status bar message
-    status_message: Option<StatusMessage>,
-    /// Should the app quit?
-    should_quit: bool,
-    /// Show the help popup
-    show_help: bool,
-    /// Note editor state (active when view == EditNote)
-    note_editor: Option<NoteEditor>,
-    /// URL selection popup state
-    url_popup: Option<UrlPopup>,
-    /// Confirmation popup state
-    confirm_popup: Option<ConfirmPopup>,
-    /// Add task input state
-    add_input: Option<AddTaskInput>,
-    /// Context management popup state
-    context_popup: Option<ContextPopup>,
-    /// Local state for context persistence
-    local_state: LocalState,
-    /// Whether we need to suspend/resume TUI for external editor
-    editor_request: Option<String>,
-    /// Cached mdfrier parser for markdown rendering
-    frier: MdFrier,
+/// A line of the dependency tree popup
+#[derive(Debug)]
+struct DepLine {
+    /// Tree drawing in front of the task, like "│   └── "
+    prefix: String,
+    /// The task on this line; None for headings and tasks that weren't found
+    uuid: Option<String>,
+    text: String,
+    resolved: bool,
 }
 
-impl App {
-    fn new(conf: Config) -> Result<Self, rstask_core::error::RstaskError> {
-        let local_state = LocalState::load(&conf.state_file);
-        let mut app = App {
-            conf,
-            all_tasks: Vec::new(),
-            filtered_indices: Vec::new(),
-            list_state: ListState::default(),
-            view: View::List,
-            filter_text: String::new(),
-            filter_active: false,
-            status_tab: StatusTab::All,
-            status_message: None,
-            should_quit: false,
-            show_help: false,
-            note_editor: None,
-            url_popup: None,
-            confirm_popup: None,
-            add_input: None,
-            context_popup: None,
-            local_state,
-            editor_request: None,
-            frier: MdFrier::new().expect("failed to initialize markdown parser"),
+/// Gives the UUIDs of the tasks a task is related to in one direction
+type RelatedTasks<'a> = &'a dyn Fn(&Task) -> Vec<String>;
+
+/// Builds the tree of the tasks `task` waits for and those waiting for it,
+/// following dependencies of dependencies. A task already in a tree isn't
+/// expanded again, which also ends cycles.
+fn dependency_tree(task: &Task, tasks: &[Task]) -> Vec<DepLine> {
+    let mut lines = vec![DepLine {
+        prefix: String::new(),
+        uuid: Some(task.uuid.clone()),
+        text: task_label(task),
+        resolved: task.status == STATUS_RESOLVED,
+    }];
+    let blockers = |t: &Task| t.dependencies.clone();
+    let dependents = |t: &Task| {
+        tasks
+            .iter()
+            .filter(|other| other.dependencies.contains(&t.uuid))
+            .map(|other| other.uuid.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let sections: [(&str, RelatedTasks); 2] = [("Blocked by", &blockers), ("Blocks", &dependents)];
+    for (heading, next) in sections {
+        let children = next(task);
+        if children.is_empty() {
+            continue;
+        }
+        lines.push(DepLine {
+            prefix: String::new(),
+            uuid: None,
+            text: heading.to_string(),
+            resolved: false,
+        });
+        let mut seen = vec![task.uuid.clone()];
+        push_dep_branch(&mut lines, tasks, &children, "", &mut seen, next);
+    }
+    lines
+}
+
+fn push_dep_branch(
+    lines: &mut Vec<DepLine>,
+    tasks: &[Task],
+    uuids: &[String],
+    prefix: &str,
+    seen: &mut Vec<String>,
+    next: RelatedTasks,
+) {
+    for (i, uuid) in uuids.iter().enumerate() {
+        let last = i + 1 == uuids.len();
+        let branch = format!("{}{}", prefix, if last { "└── " } else { "├── " });
+        let Some(task) = tasks.iter().find(|t| &t.uuid == uuid) else {
+            lines.push(DepLine {
+                prefix: branch,
+                uuid: None,
+                text: format!("{} (not found)", uuid),
+                resolved: false,
+            });
+            continue;
         };
-        app.reload_tasks()?;
-        Ok(app)
+
+        let repeated = seen.contains(uuid);
+        lines.push(DepLine {
+            prefix: branch,
+            uuid: Some(uuid.clone()),
+            text: if repeated {
+                format!("{} (see above)", task_label(task))
+            } else {
+                task_label(task)
+            },
+            resolved: task.status == STATUS_RESOLVED,
+        });
+        if !repeated {
+            seen.push(uuid.clone());
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            push_dep_branch(lines, tasks, &next(task), &child_prefix, seen, next);
+        }
     }
+}
 
-    /// Load tasks from disk
-    fn reload_tasks(&mut self) -> Result<(), rstask_core::error::RstaskError> {
-        let mut ts = TaskSet::load(&self.conf.repo, &self.conf.ids_file, true)?;
-        ts.sort_by_created_ascending();
-        ts.sort_by_priority_ascending();
+/// "12 Summary", or just the summary for tasks without an ID
+fn task_label(task: &Task) -> String {
+    if task.id > 0 {
+        format!("{} {}", task.id, task.summary)
+    } else {
+        task.summary.clone()
+    }
+}
 
-        // Collect all tasks except templates and recurring
-        self.all_tasks = ts
-            .all_tasks()
-            .iter()
-            .filter(|t| t.status != STATUS_TEMPLATE && t.status != STATUS_RECURRING)
-            .cloned()
-            .collect();
+/// State for the dependency tree popup
+struct DepsPopup {
+    lines: Vec<DepLine>,
+    /// Line under the cursor, always one with a task
+    selected: usize,
+}
 
-        self.apply_filter();
-        Ok(())
+impl DepsPopup {
+    fn new(lines: Vec<DepLine>) -> Self {
+        // Start on the first task after the selected one itself
+        let selected = (1..lines.len())
+            .find(|&i| lines[i].uuid.is_some())
+            .unwrap_or(0);
+        DepsPopup { lines, selected }
     }
 
-    /// Recompute filtered_indices from all_tasks based on filter_text + status_tab
-    fn apply_filter(&mut self) {
-        // Parse filter text using the same query parser as the CLI
-        let filter_query = if self.filter_text.is_empty() {
-            None
+    fn move_selection(&mut self, down: bool) {
+        let next = if down {
+            (self.selected + 1..self.lines.len()).find(|&i| self.lines[i].uuid.is_some())
         } else {
-            let tokens: Vec<String> = self
-                .filter_text
-                .split_whitespace()
-                .map(|s| s.to_string())
-                .collect();
-            parse_query(&tokens).ok()
+            (0..self.selected)
+                .rev()
+                .find(|&i| self.lines[i].uuid.is_some())
         };
+        if let Some(i) = next {
+            self.selected = i;
+        }
+    }
+}
 
-        self.filtered_indices = self
-            .all_tasks
-            .iter()
-            .enumerate()
-            .filter(|(_, task)| {
-                // Status tab filter
-                let status_ok = match self.status_tab {
-                    StatusTab::All => task.status != STATUS_RESOLVED,
+/// Fields of the add-task form, in the order Tab moves through them
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AddField {
+    Summary,
+    Project,
+    Tags,
+    Priority,
+    Due,
+    Notes,
+    Start,
+    Log,
+}
+
+const ADD_FIELDS: &[AddField] = &[
+    AddField::Summary,
+    AddField::Project,
+    AddField::Tags,
+    AddField::Priority,
+    AddField::Due,
+    AddField::Notes,
+    AddField::Start,
+    AddField::Log,
+];
+
+const PRIORITIES: &[&str] = &[
+    PRIORITY_CRITICAL,
+    PRIORITY_HIGH,
+    PRIORITY_NORMAL,
+    PRIORITY_LOW,
+];
+
+/// State for the add-task form
+struct AddTaskForm {
+    summary: String,
+    project: String,
+    /// Space-separated, with or without leading `+`
+    tags: String,
+    notes: String,
+    /// Index into PRIORITIES
+    priority: usize,
+    due: Option<NaiveDate>,
+    /// Whether to immediately resolve the task (log mode)
+    resolve_immediately: bool,
+    /// Whether to add the task already active
+    start_immediately: bool,
+    field: AddField,
+    /// Cursor position (byte offset) in the focused text field
+    cursor: usize,
+    /// Projects and tags already in use, for completion
+    known_projects: Vec<String>,
+    known_tags: Vec<String>,
+}
+
+impl AddTaskForm {
+    /// An empty form with the project, tags and priority of the context
+    fn new(ctx: &Query, known_projects: Vec<String>, known_tags: Vec<String>) -> Self {
+        AddTaskForm {
+            summary: String::new(),
+            project: ctx.project.clone(),
+            tags: ctx.tags.join(" "),
+            notes: String::new(),
+            priority: PRIORITIES
+                .iter()
+                .position(|&p| p == ctx.priority)
+                .unwrap_or(2),
+            due: None,
+            resolve_immediately: false,
+            start_immediately: false,
+            field: AddField::Summary,
+            cursor: 0,
+            known_projects,
+            known_tags,
+        }
+    }
+
+    /// The focused field's text, if it's a text field
+    fn text(&self) -> Option<&str> {
+        match self.field {
+            AddField::Summary => Some(&self.summary),
+            AddField::Project => Some(&self.project),
+            AddField::Tags => Some(&self.tags),
+            AddField::Notes => Some(&self.notes),
+            _ => None,
+        }
+    }
+
+    fn text_mut(&mut self) -> Option<&mut String> {
+        match self.field {
+            AddField::Summary => Some(&mut self.summary),
+            AddField::Project => Some(&mut self.project),
+            AddField::Tags => Some(&mut self.tags),
+            AddField::Notes => Some(&mut self.notes),
+            _ => None,
+        }
+    }
+
+    fn focus(&mut self, step: isize) {
+        let current = ADD_FIELDS
+            .iter()
+            .position(|&f| f == self.field)
+            .unwrap_or(0);
+        let next = (current as isize + step).rem_euclid(ADD_FIELDS.len() as isize);
+        self.field = ADD_FIELDS[next as usize];
+        self.cursor = self.text().map_or(0, str::len);
+    }
+
+    /// Names that the word being typed in the project or tags field could
+    /// be completed to
+    fn completions(&self) -> Vec<&str> {
+        let (word, candidates) = match self.field {
+            AddField::Project => (self.project.as_str(), &self.known_projects),
+            AddField::Tags => (
+                self.tags
+                    .rsplit(' ')
+                    .next()
+                    .unwrap_or("")
+                    .trim_start_matches('+'),
+                &self.known_tags,
+            ),
+            _ => return Vec::new(),
+        };
+        if word.is_empty() {
+            return Vec::new();
+        }
+        candidates
+            .iter()
+            .map(String::as_str)
+            .filter(|c| c.starts_with(word) && *c != word)
+            .collect()
+    }
+
+    /// Fills in the word being typed if only one name fits
+    fn complete(&mut self) -> bool {
+        let completed = match self.completions().as_slice() {
+            [only] => only.to_string(),
+            _ => return false,
+        };
+        match self.field {
+            AddField::Project => self.project = completed,
+            AddField::Tags => {
+                let start = self.tags.rfind(' ').map_or(0, |i| i + 1);
+                let plus = if self.tags[start..].starts_with('+') {
+                    "+"
+                } else {
+                    ""
+                };
+                self.tags.truncate(start);
+                self.tags.push_str(plus);
+                self.tags.push_str(&completed);
+            }
+            _ => return false,
+        }
+        self.cursor = self.text().map_or(0, str::len);
+        true
+    }
+
+    fn tag_list(&self) -> Vec<String> {
+        self.tags
+            .split_whitespace()
+            .map(|t| t.trim_start_matches('+').to_string())
+            .filter(|t| !t.is_empty())
+            .collect()
+    }
+
+    fn move_due(&mut self, days: i64) {
+        let today = Local::now().date_naive();
+        let from = self.due.unwrap_or(today);
+        self.due = if days >= 0 {
+            from.checked_add_days(Days::new(days as u64))
+        } else {
+            from.checked_sub_days(Days::new(days.unsigned_abs()))
+        }
+        .or(self.due);
+    }
+}
+
+/// State for the popup setting who a task is delegated to
+struct DelegateInput {
+    text: String,
+    cursor: usize,
+    task_uuid: String,
+}
+
+/// State for the modify popup
+struct ModifyInput {
+    /// The task's `+tags project:x P1 due:...`, edited in place
+    text: String,
+    /// Cursor position (byte offset)
+    cursor: usize,
+    /// The text as first shown, parsed, to tell what was removed or left alone
+    original: Query,
+    task_uuid: String,
+    completion: Option<CompletionMenu>,
+}
+
+impl ModifyInput {
+    fn new(task: &Task) -> Self {
+        let current = Query {
+            tags: task.tags.clone(),
+            project: task.project.clone(),
+            priority: task.priority.clone(),
+            due: task.due,
+            ..Default::default()
+        };
+        let text = format!("{} ", current);
+        ModifyInput {
+            cursor: text.len(),
+            original: parse_query(&split_words(&text)).unwrap_or(current),
+            text,
+            task_uuid: task.uuid.clone(),
+            completion: None,
+        }
+    }
+
+    /// Turns the edited text into the query for Task::modify. Whatever was
+    /// deleted from the text is removed from the task. Returns whether the
+    /// due date was deleted, which a query can't express.
+    fn to_modification(&self) -> Result<(Query, bool), String> {
+        let mut query = parse_query(&split_words(&self.text)).map_err(|e| e.to_string())?;
+        if !query.text.is_empty() || !query.ids.is_empty() || !query.note.is_empty() {
+            return Err("only +tags, project:, P0-P3 and due: can be changed here".to_string());
+        }
+
+        for tag in &self.original.tags {
+            if !query.tags.contains(tag) && !query.anti_tags.contains(tag) {
+                query.anti_tags.push(tag.clone());
+            }
+        }
+        if !self.original.project.is_empty() && query.project.is_empty() {
+            query.anti_projects.push(self.original.project.clone());
+        }
+
+        let clear_due = self.original.due.is_some() && query.due.is_none();
+        // An untouched date would move a due time to midnight
+        if query.due == self.original.due {
+            query.due = None;
+        }
+        Ok((query, clear_due))
+    }
+}
+
+/// A markdown block describing a task for pasting elsewhere. The UUID is
+/// written as a `urn:uuid:` link so it stays unique outside this repository.
+fn task_markdown(task: &Task) -> String {
+    let mut md = format!("### {}\n\n", task.summary);
+    md.push_str(&format!("- UUID: <urn:uuid:{}>\n", task.uuid));
+    md.push_str(&format!("- Status: {}\n", task.status));
+    if !task.project.is_empty() {
+        md.push_str(&format!("- Project: {}\n", task.project));
+    }
+    if !task.tags.is_empty() {
+        md.push_str(&format!("- Tags: {}\n", task.tags.join(", ")));
+    }
+    md.push_str(&format!("- Priority: {}\n", task.priority));
+    if let Some(due) = task.due {
+        md.push_str(&format!(
+            "- Due: {}\n",
+            due.with_timezone(&Local).format("%Y-%m-%d")
+        ));
+    }
+    let notes = task.notes.trim();
+    if !notes.is_empty() {
+        md.push_str(&format!("\n{}\n", notes));
+    }
+    md
+}
+
+/// Splits typed filters and modifications into words, keeping double-quoted
+/// spans such as `due:"next friday"` together without their quotes
+fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    let mut started = false;
+
+    for c in text.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    words.push(std::mem::take(&mut word));
+                }
+                started = false;
+            }
+            c => {
+                word.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        words.push(word);
+    }
+    words
+}
+
+/// Applies an editing key to a single-line input, returning false for keys
+/// it doesn't handle
+fn edit_line(text: &mut String, cursor: &mut usize, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Backspace if *cursor > 0 => {
+            let prev = text[..*cursor]
+                .char_indices()
+                .last()
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+            text.remove(prev);
+            *cursor = prev;
+        }
+        KeyCode::Left if *cursor > 0 => {
+            *cursor = text[..*cursor]
+                .char_indices()
+                .last()
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+        }
+        KeyCode::Right if *cursor < text.len() => {
+            let rest = &text[*cursor..];
+            let next_len = rest.chars().next().map(|c| c.len_utf8()).unwrap_or(0);
+            *cursor += next_len;
+        }
+        KeyCode::Home => {
+            *cursor = 0;
+        }
+        KeyCode::End => {
+            *cursor = text.len();
+        }
+        KeyCode::Char(c) => {
+            text.insert(*cursor, c);
+            *cursor += c.len_utf8();
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Inserts pasted text into a single-line input, with line breaks turned
+/// into spaces so a paste can't submit the input halfway
+fn paste_line(text: &mut String, cursor: &mut usize, pasted: &str) {
+    let line = pasted
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    text.insert_str(*cursor, &line);
+    *cursor += line.len();
+}
+
+/// Candidates for the `+tag` or `project:` word under the cursor of a
+/// query input, opened with Tab
+struct CompletionMenu {
+    /// Byte offset where the name being completed starts, after the prefix
+    start: usize,
+    candidates: Vec<String>,
+    selected: usize,
+}
+
+impl CompletionMenu {
+    /// Builds the menu for the word ending at `cursor`, if it is a tag or
+    /// project and some known name fits
+    fn new(text: &str, cursor: usize, tags: &[String], projects: &[String]) -> Option<Self> {
+        let word_start = text[..cursor].rfind(' ').map_or(0, |i| i + 1);
+        let word = &text[word_start..cursor];
+        let (prefix_len, names) = if let Some(rest) = word.strip_prefix("-project:") {
+            (word.len() - rest.len(), projects)
+        } else if let Some(rest) = word.strip_prefix("project:") {
+            (word.len() - rest.len(), projects)
+        } else if word.starts_with('+') || word.starts_with('-') {
+            (1, tags)
+        } else {
+            return None;
+        };
+
+        let start = word_start + prefix_len;
+        let partial = &text[start..cursor];
+        let candidates: Vec<String> = names
+            .iter()
+            .filter(|n| n.starts_with(partial))
+            .cloned()
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        Some(CompletionMenu {
+            start,
+            candidates,
+            selected: 0,
+        })
+    }
+
+    fn move_selection(&mut self, step: isize) {
+        let len = self.candidates.len() as isize;
+        self.selected = (self.selected as isize + step).rem_euclid(len) as usize;
+    }
+
+    /// Replaces the partial name before the cursor with the selected one
+    fn apply(&self, text: &mut String, cursor: &mut usize) {
+        let name = &self.candidates[self.selected];
+        text.replace_range(self.start..*cursor, name);
+        *cursor = self.start + name.len();
+        if !text[*cursor..].starts_with(' ') {
+            text.insert(*cursor, ' ');
+        }
+        *cursor += 1;
+    }
+}
+
+/// Handles Tab completion for a query input. Tab opens the menu, or fills
+/// in the name straight away if only one fits; while it's open, arrows
+/// move through it and Enter or Tab pick. Returns false for keys left to
+/// the input, which also close the menu.
+fn handle_completion(
+    menu: &mut Option<CompletionMenu>,
+    text: &mut String,
+    cursor: &mut usize,
+    key: KeyEvent,
+    tags: &[String],
+    projects: &[String],
+) -> bool {
+    if let Some(open) = menu.as_mut() {
+        match key.code {
+            KeyCode::Down => open.move_selection(1),
+            KeyCode::Up | KeyCode::BackTab => open.move_selection(-1),
+            KeyCode::Enter | KeyCode::Tab => {
+                open.apply(text, cursor);
+                *menu = None;
+            }
+            KeyCode::Esc => *menu = None,
+            _ => {
+                *menu = None;
+                return false;
+            }
+        }
+        return true;
+    }
+
+    if key.code != KeyCode::Tab {
+        return false;
+    }
+    if let Some(found) = CompletionMenu::new(text, *cursor, tags, projects) {
+        if found.candidates.len() == 1 {
+            found.apply(text, cursor);
+        } else {
+            *menu = Some(found);
+        }
+    }
+    true
+}
+
+/// What to open in $EDITOR once the TUI is suspended
+enum EditorRequest {
+    /// The task with this UUID, as markdown in a temporary file
+    Task(String),
+    /// A task file itself, as stored in the repository
+    File {
+        path: std::path::PathBuf,
+        summary: String,
+    },
+}
+
+/// State for the due date picker
+struct DuePicker {
+    task_uuid: String,
+    /// Day under the cursor
+    cursor: NaiveDate,
+    /// The task's due date when the picker opened
+    current: Option<NaiveDate>,
+}
+
+impl DuePicker {
+    fn new(task: &Task) -> Self {
+        let current = task.due.map(|d| d.with_timezone(&Local).date_naive());
+        DuePicker {
+            task_uuid: task.uuid.clone(),
+            cursor: current.unwrap_or_else(|| Local::now().date_naive()),
+            current,
+        }
+    }
+
+    fn move_days(&mut self, days: i64) {
+        let moved = if days >= 0 {
+            self.cursor.checked_add_days(Days::new(days as u64))
+        } else {
+            self.cursor.checked_sub_days(Days::new(days.unsigned_abs()))
+        };
+        self.cursor = moved.unwrap_or(self.cursor);
+    }
+
+    fn move_months(&mut self, months: i32) {
+        let moved = if months >= 0 {
+            self.cursor.checked_add_months(Months::new(months as u32))
+        } else {
+            self.cursor
+                .checked_sub_months(Months::new(months.unsigned_abs()))
+        };
+        self.cursor = moved.unwrap_or(self.cursor);
+    }
+}
+
+/// The weeks of the month containing `date`, Monday first, with `None` for
+/// days outside the month
+fn month_grid(date: NaiveDate) -> Vec<[Option<NaiveDate>; 7]> {
+    let first = date.with_day(1).unwrap();
+    let offset = first.weekday().num_days_from_monday() as usize;
+
+    let mut weeks = Vec::new();
+    let mut week = [None; 7];
+    let mut day = Some(first);
+    let mut col = offset;
+    while let Some(d) = day.filter(|d| d.month() == first.month()) {
+        week[col] = Some(d);
+        col += 1;
+        if col == 7 {
+            weeks.push(week);
+            week = [None; 7];
+            col = 0;
+        }
+        day = d.succ_opt();
+    }
+    if col > 0 {
+        weeks.push(week);
+    }
+    weeks
+}
+
+/// Something the command palette can run
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PaletteAction {
+    AddTask,
+    ShowDetail,
+    ModifyTask,
+    PickDue,
+    StartTask,
+    PauseTask,
+    DoneTask,
+    CyclePriority,
+    RemoveTask,
+    EditNotes,
+    EditInEditor,
+    OpenUrls,
+    ShowDependencies,
+    Delegate,
+    GoToId,
+    YankSummary,
+    YankMarkdown,
+    Filter,
+    Find,
+    ClearFilter,
+    NextStatusTab,
+    ShowResolved,
+    ShowDelegated,
+    ShowTemplates,
+    ShowStats,
+    Pomodoro,
+    AddFromTemplate,
+    ConvertToTemplate,
+    ToggleSidebar,
+    ToggleSplitView,
+    ToggleActivity,
+    SetContext,
+    ClearContext,
+    Reload,
+    Sync,
+    Undo,
+    CycleTheme,
+    Help,
+    Quit,
+}
+
+struct PaletteEntry {
+    action: PaletteAction,
+    /// Key bound to the action, empty if it has none
+    key: &'static str,
+    description: &'static str,
+}
+
+const PALETTE_ENTRIES: &[PaletteEntry] = &[
+    PaletteEntry {
+        action: PaletteAction::AddTask,
+        key: "a",
+        description: "Add a task",
+    },
+    PaletteEntry {
+        action: PaletteAction::ShowDetail,
+        key: "Enter",
+        description: "Show task details",
+    },
+    PaletteEntry {
+        action: PaletteAction::ModifyTask,
+        key: "m",
+        description: "Modify tags, project, priority and due date",
+    },
+    PaletteEntry {
+        action: PaletteAction::PickDue,
+        key: "t",
+        description: "Pick a due date",
+    },
+    PaletteEntry {
+        action: PaletteAction::StartTask,
+        key: "s",
+        description: "Start task",
+    },
+    PaletteEntry {
+        action: PaletteAction::PauseTask,
+        key: "p",
+        description: "Pause task",
+    },
+    PaletteEntry {
+        action: PaletteAction::DoneTask,
+        key: "d",
+        description: "Mark task done",
+    },
+    PaletteEntry {
+        action: PaletteAction::CyclePriority,
+        key: "P",
+        description: "Cycle priority",
+    },
+    PaletteEntry {
+        action: PaletteAction::RemoveTask,
+        key: "x",
+        description: "Remove task",
+    },
+    PaletteEntry {
+        action: PaletteAction::EditNotes,
+        key: "e",
+        description: "Edit notes",
+    },
+    PaletteEntry {
+        action: PaletteAction::EditInEditor,
+        key: "E",
+        description: "Edit task in $EDITOR",
+    },
+    PaletteEntry {
+        action: PaletteAction::OpenUrls,
+        key: "o",
+        description: "Open URLs in browser",
+    },
+    PaletteEntry {
+        action: PaletteAction::ShowDependencies,
+        key: "D",
+        description: "Show dependency tree",
+    },
+    PaletteEntry {
+        action: PaletteAction::YankSummary,
+        key: "y",
+        description: "Copy summary to clipboard",
+    },
+    PaletteEntry {
+        action: PaletteAction::YankMarkdown,
+        key: "Y",
+        description: "Copy task as markdown to clipboard",
+    },
+    PaletteEntry {
+        action: PaletteAction::Filter,
+        key: "/",
+        description: "Filter tasks",
+    },
+    PaletteEntry {
+        action: PaletteAction::Find,
+        key: "Ctrl+F",
+        description: "Find tasks, projects and tags",
+    },
+    PaletteEntry {
+        action: PaletteAction::Delegate,
+        key: "@",
+        description: "Delegate task to someone, or take it back",
+    },
+    PaletteEntry {
+        action: PaletteAction::ShowDelegated,
+        key: "",
+        description: "Show delegated tasks",
+    },
+    PaletteEntry {
+        action: PaletteAction::GoToId,
+        key: "#",
+        description: "Go to task by ID",
+    },
+    PaletteEntry {
+        action: PaletteAction::ClearFilter,
+        key: "c",
+        description: "Clear filter",
+    },
+    PaletteEntry {
+        action: PaletteAction::NextStatusTab,
+        key: "Tab",
+        description: "Next status tab",
+    },
+    PaletteEntry {
+        action: PaletteAction::ShowResolved,
+        key: "",
+        description: "Show resolved tasks",
+    },
+    PaletteEntry {
+        action: PaletteAction::ShowTemplates,
+        key: "",
+        description: "Show templates",
+    },
+    PaletteEntry {
+        action: PaletteAction::ShowStats,
+        key: "w",
+        description: "Show stats for a weekly review",
+    },
+    PaletteEntry {
+        action: PaletteAction::Pomodoro,
+        key: "z",
+        description: "Start or stop a pomodoro timer",
+    },
+    PaletteEntry {
+        action: PaletteAction::AddFromTemplate,
+        key: "Enter",
+        description: "Add a task from the selected template",
+    },
+    PaletteEntry {
+        action: PaletteAction::ConvertToTemplate,
+        key: "M",
+        description: "Convert task to template",
+    },
+    PaletteEntry {
+        action: PaletteAction::ToggleSidebar,
+        key: "b",
+        description: "Toggle project sidebar",
+    },
+    PaletteEntry {
+        action: PaletteAction::ToggleSplitView,
+        key: "v",
+        description: "Toggle the detail view beside the list",
+    },
+    PaletteEntry {
+        action: PaletteAction::ToggleActivity,
+        key: "L",
+        description: "Toggle the log of commits made this session",
+    },
+    PaletteEntry {
+        action: PaletteAction::SetContext,
+        key: "C",
+        description: "Set context",
+    },
+    PaletteEntry {
+        action: PaletteAction::ClearContext,
+        key: "",
+        description: "Clear context",
+    },
+    PaletteEntry {
+        action: PaletteAction::Reload,
+        key: "r",
+        description: "Reload tasks",
+    },
+    PaletteEntry {
+        action: PaletteAction::Sync,
+        key: "S",
+        description: "Sync with remote",
+    },
+    PaletteEntry {
+        action: PaletteAction::Undo,
+        key: "u",
+        description: "Undo or redo changes from this session",
+    },
+    PaletteEntry {
+        action: PaletteAction::CycleTheme,
+        key: "T",
+        description: "Cycle theme",
+    },
+    PaletteEntry {
+        action: PaletteAction::Help,
+        key: "?",
+        description: "Show key bindings",
+    },
+    PaletteEntry {
+        action: PaletteAction::Quit,
+        key: "q",
+        description: "Quit",
+    },
+];
+
+/// Scores how well `query` matches `text` as a subsequence, ignoring case,
+/// or None if it doesn't match. Matches at word starts and runs of adjacent
+/// characters score higher, so "st" ranks "Start task" above "Pause task".
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut prev = None;
+
+    for qc in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (pos..text.len()).find(|&i| text[i] == qc)?;
+        score += 1;
+        if found > 0 && prev == Some(found - 1) {
+            score += 4;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - pos).min(3) as i32;
+        prev = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+/// State for the command palette
+struct CommandPalette {
+    text: String,
+    cursor: usize,
+    /// Index into the current matches
+    selected: usize,
+}
+
+impl CommandPalette {
+    fn new() -> Self {
+        CommandPalette {
+            text: String::new(),
+            cursor: 0,
+            selected: 0,
+        }
+    }
+
+    /// Entries matching the search, best first. An empty search lists them all
+    fn matches(&self) -> Vec<&'static PaletteEntry> {
+        let mut scored: Vec<(i32, &PaletteEntry)> = PALETTE_ENTRIES
+            .iter()
+            .filter_map(|e| fuzzy_score(&self.text, e.description).map(|s| (s, e)))
+            .collect();
+        // Stable, so ties keep the list order
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(_, e)| e).collect()
+    }
+}
+
+/// Where the fuzzy finder goes when an entry is picked
+#[derive(Debug, Clone, PartialEq)]
+enum FindTarget {
+    /// Select the task with this uuid
+    Task(String),
+    /// Show only this project's tasks
+    Project(String),
+    /// Filter by this tag
+    Tag(String),
+}
+
+struct FindEntry {
+    target: FindTarget,
+    /// Text shown and matched: a task's summary with its project and tags,
+    /// or a project or tag name
+    label: String,
+}
+
+/// Ctrl+F popup that fuzzy-matches across task summaries, projects and tags
+struct FuzzyFinder {
+    text: String,
+    cursor: usize,
+    /// Index into the current matches
+    selected: usize,
+    entries: Vec<FindEntry>,
+}
+
+impl FuzzyFinder {
+    /// Entries for the given tasks, followed by their projects and tags
+    fn new(tasks: &[Task]) -> Self {
+        let mut entries: Vec<FindEntry> = tasks
+            .iter()
+            .map(|task| {
+                let mut label = task.summary.clone();
+                if !task.project.is_empty() {
+                    label.push_str(&format!("  project:{}", task.project));
+                }
+                for tag in &task.tags {
+                    label.push_str(&format!("  +{}", tag));
+                }
+                FindEntry {
+                    target: FindTarget::Task(task.uuid.clone()),
+                    label,
+                }
+            })
+            .collect();
+
+        let mut projects: Vec<&str> = tasks
+            .iter()
+            .map(|t| t.project.as_str())
+            .filter(|p| !p.is_empty())
+            .collect();
+        projects.sort_unstable();
+        projects.dedup();
+        entries.extend(projects.into_iter().map(|project| FindEntry {
+            target: FindTarget::Project(project.to_string()),
+            label: format!("project:{}", project),
+        }));
+
+        let mut tags: Vec<&str> = tasks
+            .iter()
+            .flat_map(|t| t.tags.iter().map(String::as_str))
+            .collect();
+        tags.sort_unstable();
+        tags.dedup();
+        entries.extend(tags.into_iter().map(|tag| FindEntry {
+            target: FindTarget::Tag(tag.to_string()),
+            label: format!("+{}", tag),
+        }));
+
+        FuzzyFinder {
+            text: String::new(),
+            cursor: 0,
+            selected: 0,
+            entries,
+        }
+    }
+
+    /// Entries matching the search, best first. An empty search lists them all
+    fn matches(&self) -> Vec<&FindEntry> {
+        if self.text.is_empty() {
+            return self.entries.iter().collect();
+        }
+        let matcher = SkimMatcherV2::default().ignore_case();
+        let mut scored: Vec<(i64, &FindEntry)> = self
+            .entries
+            .iter()
+            .filter_map(|e| matcher.fuzzy_match(&e.label, &self.text).map(|s| (s, e)))
+            .collect();
+        // Stable, so ties keep tasks ahead of projects and tags
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(_, e)| e).collect()
+    }
+}
+
+/// Read-only mode for a shared screen, which syncs and cycles through
+/// filters on its own
+struct Dashboard {
+    /// Filters to cycle through; an empty one shows all open tasks
+    views: Vec<String>,
+    current: usize,
+    cycle: Duration,
+    sync_every: Duration,
+    shown_at: Instant,
+    synced_at: Instant,
+}
+
+impl Dashboard {
+    fn new(prefs: &Preferences, now: Instant) -> Self {
+        let views = if prefs.dashboard_views.is_empty() {
+            vec![String::new()]
+        } else {
+            prefs.dashboard_views.clone()
+        };
+        Dashboard {
+            views,
+            current: 0,
+            cycle: Duration::from_secs(prefs.dashboard_cycle_seconds.max(1)),
+            sync_every: Duration::from_secs(prefs.dashboard_sync_minutes.max(1) * 60),
+            shown_at: now,
+            synced_at: now,
+        }
+    }
+
+    fn query(&self) -> &str {
+        &self.views[self.current]
+    }
+
+    /// Moves `step` views forward or back, wrapping around
+    fn advance(&mut self, step: isize, now: Instant) {
+        let len = self.views.len() as isize;
+        self.current = (self.current as isize + step).rem_euclid(len) as usize;
+        self.shown_at = now;
+    }
+
+    /// Whether the next view is due, and whether a sync is due
+    fn due(&self, now: Instant) -> (bool, bool) {
+        (
+            self.views.len() > 1 && now.duration_since(self.shown_at) >= self.cycle,
+            now.duration_since(self.synced_at) >= self.sync_every,
+        )
+    }
+}
+
+/// State for the context management popup
+struct ContextPopup {
+    /// Input text for setting a new context
+    text: String,
+    /// Cursor position
+    cursor: usize,
+    completion: Option<CompletionMenu>,
+}
+
+impl ContextPopup {
+    fn new(current_context: &Query) -> Self {
+        // Pre-fill with the current context as text
+        let text = context_to_display_string(current_context);
+        let cursor = text.len();
+        ContextPopup {
+            text,
+            cursor,
+            completion: None,
+        }
+    }
+}
+
+/// Convert a Query context to a display string
+fn context_to_display_string(q: &Query) -> String {
+    let mut parts = Vec::new();
+    for tag in &q.tags {
+        parts.push(format!("+{}", tag));
+    }
+    for tag in &q.anti_tags {
+        parts.push(format!("-{}", tag));
+    }
+    for text in &q.anti_text {
+        if text.contains(' ') {
+            parts.push(format!("\"not:{}\"", text));
+        } else {
+            parts.push(format!("not:{}", text));
+        }
+    }
+    if !q.project.is_empty() {
+        parts.push(format!("project:{}", q.project));
+    }
+    if !q.priority.is_empty() {
+        parts.push(q.priority.clone());
+    }
+    parts.join(" ")
+}
+
+/// A row of the project sidebar
+struct ProjectEntry {
+    name: String,
+    /// Tasks that aren't resolved
+    open: usize,
+    active: usize,
+}
+
+/// Application state
+struct App {
+    conf: Config,
+    /// All non-resolved tasks (unfiltered)
+    all_tasks: Vec<Task>,
+    /// Indices into all_tasks that pass the current filter
+    filtered_indices: Vec<usize>,
+    /// List widget state (selection)
+    list_state: ListState,
+    /// Current view
+    view: View,
+    /// Search/filter input string
+    filter_text: String,
+    /// The free text of the filter, highlighted where tasks match it
+    search_text: String,
+    /// Whether the filter input is focused
+    filter_active: bool,
+    /// Status tab filter
+    status_tab: StatusTab,
+    /// Whether resolved tasks are loaded. They're only read once the
+    /// Resolved tab is first opened, since there can be many of them
+    resolved_loaded: bool,
+    /// Projects with their counts, shown in the sidebar
+    projects: Vec<ProjectEntry>,
+    /// Every tag in use, for completion
+    tags: Vec<String>,
+    /// Only show tasks of this project
+    project_filter: Option<String>,
+    /// Whether the project sidebar is shown
+    show_sidebar: bool,
+    /// Whether the list shows the selected task's detail beside it, on
+    /// terminals wide enough
+    split_view: bool,
+    /// HEAD when the TUI started, to tell which commits it has seen made
+    session_start: Option<String>,
+    /// Commits since session_start, newest first, while the activity pane
+    /// is shown
+    activity: Option<Vec<String>>,
+    /// Whether keys go to the sidebar rather than the task list
+    sidebar_focused: bool,
+    /// Sidebar selection; row 0 is "All projects"
+    sidebar_state: ListState,
+    /// Status bar message
+    status_message: Option<StatusMessage>,
+    /// Should the app quit?
+    should_quit: bool,
+    /// Show the help popup
+    show_help: bool,
+    /// Note editor state (active when view == EditNote)
+    note_editor: Option<NoteEditor>,
+    /// URL selection popup state
+    url_popup: Option<UrlPopup>,
+    /// Confirmation popup state
+    confirm_popup: Option<ConfirmPopup>,
+    /// Add task input state
+    add_input: Option<AddTaskForm>,
+    /// Context management popup state
+    context_popup: Option<ContextPopup>,
+    /// Modify popup state
+    modify_input: Option<ModifyInput>,
+    /// Due date picker state
+    due_picker: Option<DuePicker>,
+    /// Command palette state
+    palette: Option<CommandPalette>,
+    /// Fuzzy finder state
+    finder: Option<FuzzyFinder>,
+    /// Task ID typed into the `#` prompt
+    jump_input: Option<String>,
+    /// Delegate popup state
+    delegate_input: Option<DelegateInput>,
+    /// Undo popup state
+    undo_popup: Option<UndoPopup>,
+    /// Dependency tree popup state
+    deps_popup: Option<DepsPopup>,
+    /// Changes committed from this session, oldest first
+    changes: Vec<SessionChange>,
+    /// HEAD as of the last reload, to tell whether a change made a commit
+    known_head: Option<String>,
+    /// When each active task was started, from the git history
+    active_since: HashMap<String, DateTime<Utc>>,
+    /// The running pomodoro timer, if any
+    pomodoro: Option<Pomodoro>,
+    /// First line of the notes shown in the detail view
+    notes_scroll: usize,
+    /// Lines of notes that fit in the detail view, as of the last draw
+    notes_height: usize,
+    /// Local state for context persistence
+    local_state: LocalState,
+    /// Whether we need to suspend/resume TUI for external editor
+    editor_request: Option<EditorRequest>,
+    /// Spots changes made to the repository outside the TUI
+    watcher: RepoWatcher,
+    /// Set in dashboard mode, where nothing can be changed
+    dashboard: Option<Dashboard>,
+    /// The sync in flight, if any
+    sync_job: Option<SyncJob>,
+    /// Commits ahead of and behind the upstream as of the last reload
+    ahead_behind: Option<(usize, usize)>,
+    /// Colours after the `tui_colors` overrides
+    colors: ThemeColors,
+    /// Cached mdfrier parser for markdown rendering
+    frier: MdFrier,
+    /// Opened on the first yank and kept, since on X11 the copied text
+    /// is only available while the clipboard that set it is alive
+    clipboard: Option<arboard::Clipboard>,
+}
+
+impl App {
+    fn new(conf: Config, dashboard: bool) -> Result<Self, rstask_core::error::RstaskError> {
+        let local_state = LocalState::load(&conf.state_file);
+        let watcher = RepoWatcher::new(&conf.repo);
+        let dashboard = dashboard.then(|| Dashboard::new(&conf.preferences, Instant::now()));
+        let (colors, invalid_colors) = ThemeColors::new(&conf.preferences.tui_colors);
+        let mut app = App {
+            conf,
+            all_tasks: Vec::new(),
+            filtered_indices: Vec::new(),
+            list_state: ListState::default(),
+            view: View::List,
+            filter_text: String::new(),
+            search_text: String::new(),
+            filter_active: false,
+            status_tab: StatusTab::All,
+            resolved_loaded: false,
+            projects: Vec::new(),
+            tags: Vec::new(),
+            project_filter: None,
+            show_sidebar: false,
+            split_view: false,
+            session_start: None,
+            activity: None,
+            sidebar_focused: false,
+            sidebar_state: ListState::default(),
+            status_message: None,
+            should_quit: false,
+            show_help: false,
+            note_editor: None,
+            url_popup: None,
+            confirm_popup: None,
+            add_input: None,
+            context_popup: None,
+            modify_input: None,
+            due_picker: None,
+            palette: None,
+            finder: None,
+            jump_input: None,
+            delegate_input: None,
+            undo_popup: None,
+            deps_popup: None,
+            changes: Vec::new(),
+            known_head: None,
+            active_since: HashMap::new(),
+            pomodoro: None,
+            notes_scroll: 0,
+            notes_height: 0,
+            local_state,
+            editor_request: None,
+            watcher,
+            dashboard,
+            sync_job: None,
+            ahead_behind: None,
+            colors,
+            frier: MdFrier::new().expect("failed to initialize markdown parser"),
+            clipboard: None,
+        };
+        app.reload_tasks()?;
+        app.session_start = app.known_head.clone();
+        app.show_dashboard_view();
+        if let Some(warning) = head_warning(&app.conf.repo) {
+            app.set_status(&format!("Warning: {}", warning), true);
+        } else if !invalid_colors.is_empty() {
+            app.set_status(
+                &format!(
+                    "Warning: not colours in tui_colors: {}",
+                    invalid_colors.join(", ")
+                ),
+                true,
+            );
+        }
+        Ok(app)
+    }
+
+    /// Load tasks from disk
+    fn reload_tasks(&mut self) -> Result<(), rstask_core::error::RstaskError> {
+        let mut options =
+            LoadOptions::new(self.resolved_loaded).settings(self.conf.repo_settings());
+        if self.resolved_loaded
+            && let Some(days) = self.conf.preferences.tui_resolved_days
+        {
+            options = options.resolved_since(Utc::now() - chrono::Duration::days(days.into()));
+        }
+        let mut ts = TaskSet::load_with(&self.conf.repo, &self.conf.ids_file, &options)?;
+        ts.sort_by_created_ascending();
+        ts.sort_by_priority_ascending();
+
+        self.projects = ts
+            .get_projects()
+            .into_iter()
+            .map(|project| ProjectEntry {
+                active: ts
+                    .all_tasks()
+                    .iter()
+                    .filter(|t| t.project == project.name && t.status == STATUS_ACTIVE)
+                    .count(),
+                open: project.tasks - project.tasks_resolved,
+                name: project.name,
+            })
+            .collect();
+        self.tags = ts.get_tags();
+
+        // Templates and recurring tasks only show in their own tab
+        self.all_tasks = ts.all_tasks().to_vec();
+
+        self.apply_filter();
+        self.ahead_behind = ahead_behind(&self.conf.repo);
+        self.known_head = head_commit(&self.conf.repo);
+        if self.activity.is_some() {
+            self.activity = Some(self.session_log());
+        }
+        self.active_since = self
+            .all_tasks
+            .iter()
+            .filter(|t| t.status == STATUS_ACTIVE)
+            .filter_map(|t| Some((t.uuid.clone(), active_since(&self.conf.repo, &t.uuid)?)))
+            .collect();
+        // Whatever changed, including the TUI's own commits, is now shown
+        self.watcher.mark_seen();
+        Ok(())
+    }
+
+    /// Reloads after a change made outside the TUI, keeping the selected
+    /// task selected if it's still listed
+    fn reload_external(&mut self) {
+        let selected = self.selected_task().map(|t| t.uuid.clone());
+        if let Err(e) = self.reload_tasks() {
+            self.set_status(&format!("Reload error: {}", e), true);
+            return;
+        }
+
+        if let Some(uuid) = selected
+            && let Some(pos) = self
+                .filtered_indices
+                .iter()
+                .position(|&i| self.all_tasks[i].uuid == uuid)
+        {
+            self.list_state.select(Some(pos));
+        }
+        // Don't hide an error the user hasn't read yet
+        if !self.status_message.as_ref().is_some_and(|m| m.is_error) {
+            self.set_transient_status("Reloaded (external change)");
+        }
+    }
+
+    /// Selects the task at `idx` in all_tasks, first clearing the filters
+    /// and switching tabs if it isn't in the list
+    fn select_task(&mut self, idx: usize) {
+        if !self.filtered_indices.contains(&idx) {
+            self.filter_text.clear();
+            self.project_filter = None;
+            self.status_tab = if self.all_tasks[idx].status == STATUS_RESOLVED {
+                StatusTab::Resolved
+            } else if is_template(&self.all_tasks[idx]) {
+                StatusTab::Templates
+            } else {
+                StatusTab::All
+            };
+            self.apply_filter();
+        }
+        let pos = self.filtered_indices.iter().position(|&i| i == idx);
+        self.list_state.select(pos);
+        let summary = self.all_tasks[idx].summary.clone();
+        self.set_status(&format!("Selected: {}", summary), false);
+    }
+
+    /// Switches the list to another status tab, loading resolved tasks the
+    /// first time they're needed
+    fn set_status_tab(&mut self, tab: StatusTab) {
+        self.status_tab = tab;
+        if tab == StatusTab::Resolved && !self.resolved_loaded {
+            self.resolved_loaded = true;
+            if let Err(e) = self.reload_tasks() {
+                self.set_status(&format!("Error: {}", e), true);
+            }
+        }
+        self.apply_filter();
+    }
+
+    /// Recompute filtered_indices from all_tasks based on filter_text + status_tab
+    fn apply_filter(&mut self) {
+        // Parse filter text using the same query parser as the CLI
+        let filter_query = if self.filter_text.is_empty() {
+            None
+        } else {
+            parse_query(&split_words(&self.filter_text)).ok()
+        };
+        self.search_text = filter_query
+            .as_ref()
+            .map(|q| q.text.clone())
+            .unwrap_or_default();
+
+        self.filtered_indices = self
+            .all_tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| {
+                // Status tab filter
+                let status_ok = match self.status_tab {
+                    StatusTab::All => !HIDDEN_STATUSES.contains(&task.status.as_str()),
                     StatusTab::Pending => task.status == STATUS_PENDING,
                     StatusTab::Active => task.status == STATUS_ACTIVE,
                     StatusTab::Paused => task.status == STATUS_PAUSED,
+                    StatusTab::Delegated => {
+                        !task.delegated_to.is_empty()
+                            && !HIDDEN_STATUSES.contains(&task.status.as_str())
+                    }
                     StatusTab::Resolved => task.status == STATUS_RESOLVED,
+                    StatusTab::Templates => is_template(task),
                 };
                 if !status_ok {
                     return false;
                 }
 
+                if let Some(project) = &self.project_filter
+                    && task.project != *project
+                {
+                    return false;
+                }
+
                 // Query-based filter
                 match &filter_query {
                     Some(q) => task.matches_filter(q),
@@ -409,6 +2215,13 @@ impl App {
             .map(|(i, _)| i)
             .collect();
 
+        // Resolved tasks are grouped by week, most recent first
+        if self.status_tab == StatusTab::Resolved {
+            let tasks = &self.all_tasks;
+            self.filtered_indices
+                .sort_by_key(|&i| std::cmp::Reverse(tasks[i].resolved));
+        }
+
         // Fix selection
         if self.filtered_indices.is_empty() {
             self.list_state.select(None);
@@ -423,6 +2236,101 @@ impl App {
         }
     }
 
+    /// Show or hide the project sidebar, focusing it when shown
+    /// The commits made since the TUI started, by it or anything else
+    fn session_log(&self) -> Vec<String> {
+        git_log_since(
+            &self.conf.repo,
+            self.session_start.as_deref(),
+            ACTIVITY_LIMIT,
+        )
+        .unwrap_or_default()
+    }
+
+    fn toggle_activity(&mut self) {
+        self.activity = match self.activity {
+            Some(_) => None,
+            None => Some(self.session_log()),
+        };
+    }
+
+    fn toggle_split_view(&mut self) {
+        self.split_view = !self.split_view;
+        self.notes_scroll = 0;
+        if self.split_view && size().is_ok_and(|(width, _)| width < SPLIT_MIN_WIDTH) {
+            self.set_status(
+                &format!(
+                    "The split view shows at {} columns or wider",
+                    SPLIT_MIN_WIDTH
+                ),
+                false,
+            );
+        }
+    }
+
+    fn toggle_sidebar(&mut self) {
+        self.show_sidebar = !self.show_sidebar;
+        self.sidebar_focused = self.show_sidebar;
+        if self.show_sidebar {
+            let row = self
+                .project_filter
+                .as_ref()
+                .and_then(|name| self.projects.iter().position(|p| p.name == *name))
+                .map_or(0, |i| i + 1);
+            self.sidebar_state.select(Some(row));
+        }
+    }
+
+    /// Filter the list by the project under the sidebar cursor
+    fn select_sidebar_project(&mut self) {
+        self.project_filter = match self.sidebar_state.selected() {
+            Some(row) if row > 0 => self.projects.get(row - 1).map(|p| p.name.clone()),
+            _ => None,
+        };
+        self.list_state.select(Some(0));
+        self.apply_filter();
+        self.sidebar_focused = false;
+        match &self.project_filter {
+            Some(name) => self.set_status(&format!("Project: {}", name), false),
+            None => self.set_status("All projects", false),
+        }
+    }
+
+    fn handle_sidebar_input(&mut self, key: KeyEvent) {
+        let rows = self.projects.len() + 1;
+        let current = self.sidebar_state.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('q') => {
+                self.should_quit = true;
+            }
+            KeyCode::Char('?') => {
+                self.show_help = true;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.sidebar_state.select(Some((current + 1).min(rows - 1)));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.sidebar_state.select(Some(current.saturating_sub(1)));
+            }
+            KeyCode::Char('g') | KeyCode::Home => {
+                self.sidebar_state.select(Some(0));
+            }
+            KeyCode::Char('G') | KeyCode::End => {
+                self.sidebar_state.select(Some(rows - 1));
+            }
+            KeyCode::Enter => {
+                self.select_sidebar_project();
+            }
+            KeyCode::Char('b') => {
+                self.toggle_sidebar();
+            }
+            KeyCode::Esc | KeyCode::Char('l') | KeyCode::Right => {
+                self.sidebar_focused = false;
+            }
+            _ => {}
+        }
+    }
+
     /// Get the currently selected task (if any)
     fn selected_task(&self) -> Option<&Task> {
         self.list_state
@@ -431,15 +2339,101 @@ impl App {
             .map(|&idx| &self.all_tasks[idx])
     }
 
+    /// Whether the repository is free to change. A commit during a sync
+    /// would race its pull and push, so changes wait until it's done.
+    fn check_not_syncing(&mut self) -> bool {
+        if self.sync_job.is_some() {
+            self.set_status("Wait for the sync to finish", true);
+            return false;
+        }
+        true
+    }
+
     fn set_status(&mut self, msg: &str, is_error: bool) {
         self.status_message = Some(StatusMessage {
             text: msg.to_string(),
             is_error,
+            expires: status_lifetime(&self.conf.preferences, is_error)
+                .map(|lifetime| Instant::now() + lifetime),
+        });
+    }
+
+    /// Shows a message that clears itself after a few seconds
+    fn set_transient_status(&mut self, msg: &str) {
+        self.status_message = Some(StatusMessage {
+            text: msg.to_string(),
+            is_error: false,
+            expires: Some(Instant::now() + TRANSIENT_STATUS),
+        });
+    }
+
+    /// The active task started most recently, with when it was started,
+    /// and how many other tasks are active
+    fn current_work(&self) -> Option<(&Task, DateTime<Utc>, usize)> {
+        let (uuid, since) = self.active_since.iter().max_by_key(|(_, since)| **since)?;
+        let task = self.all_tasks.iter().find(|t| &t.uuid == uuid)?;
+        Some((task, *since, self.active_since.len() - 1))
+    }
+
+    /// Starts a pomodoro for the selected task, or whichever is active if
+    /// nothing is selected, or stops the one running
+    fn toggle_pomodoro(&mut self) {
+        if let Some(pomodoro) = self.pomodoro.take() {
+            self.set_status(&format!("Pomodoro stopped: {}", pomodoro.label), false);
+            return;
+        }
+        let task = match self
+            .selected_task()
+            .or_else(|| self.current_work().map(|(t, _, _)| t))
+        {
+            Some(t) => t,
+            None => {
+                self.set_status("No task selected", true);
+                return;
+            }
+        };
+
+        let minutes = self.conf.preferences.tui_pomodoro_minutes.max(1);
+        let label = task_label(task);
+        self.set_status(
+            &format!("Pomodoro started: {} minutes on {}", minutes, label),
+            false,
+        );
+        self.pomodoro = Some(Pomodoro {
+            label,
+            ends: Instant::now() + Duration::from_secs(minutes * 60),
         });
     }
 
+    fn pomodoro_tick(&mut self, now: Instant) {
+        if self.pomodoro.as_ref().is_none_or(|p| now < p.ends) {
+            return;
+        }
+        let Some(pomodoro) = self.pomodoro.take() else {
+            return;
+        };
+        match notify_pomodoro(&self.conf.preferences, &pomodoro.label) {
+            Ok(()) => self.set_status(&format!("Pomodoro finished: {}", pomodoro.label), false),
+            Err(e) => self.set_status(&format!("Pomodoro finished, but {}", e), true),
+        }
+    }
+
+    fn expire_status(&mut self, now: Instant) {
+        if self
+            .status_message
+            .as_ref()
+            .and_then(|m| m.expires)
+            .is_some_and(|expires| now >= expires)
+        {
+            self.status_message = None;
+        }
+    }
+
     /// Perform a task action that changes status
     fn change_task_status(&mut self, new_status: &str) {
+        if !self.check_not_syncing() {
+            return;
+        }
         let task = match self.selected_task() {
             Some(t) => t.clone(),
             None => {
@@ -458,17 +2452,24 @@ impl App {
 
         let result = (|| -> Result<(), rstask_core::error::RstaskError> {
             let include_resolved = task.status == STATUS_RESOLVED;
-            let mut ts = TaskSet::load(&self.conf.repo, &self.conf.ids_file, include_resolved)?;
-            let mut t = ts
+            let mut ts = TaskSet::load_for_update(&self.conf, include_resolved)?;
+            let before = ts
                 .get_by_uuid(&task.uuid)
                 .ok_or_else(|| rstask_core::error::RstaskError::TaskNotFound(task.uuid.clone()))?
                 .clone();
+            let mut t = before.clone();
             t.status = new_status.to_string();
             t.write_pending = true;
             if new_status == STATUS_RESOLVED {
                 t.resolved = Some(Utc::now());
             }
-            ts.must_update_task(t)?;
+            ts.must_update_task(t.clone())?;
+            let paused = if self.conf.preferences.single_active_task && new_status == STATUS_ACTIVE
+            {
+                ts.pause_active_except(&[&t.uuid])?
+            } else {
+                Vec::new()
+            };
             ts.save_pending_changes()?;
 
             let verb = match new_status {
@@ -478,7 +2479,11 @@ impl App {
                 _ if task.status == STATUS_RESOLVED => "Reopened",
                 _ => "Updated",
             };
-            git_commit(&self.conf.repo, &format!("{} {}", verb, task.summary), true)?;
+            commit_changes(&self.conf, &format!("{} {}", verb, task.summary), true)?;
+            self.conf.events.emit_change(&before, &t);
+            for (before, after) in paused {
+                self.conf.events.emit_change(&before, &after);
+            }
             Ok(())
         })();
 
@@ -493,8 +2498,9 @@ impl App {
                     _ if task.status == STATUS_RESOLVED => "Reopened",
                     _ => "Updated",
                 };
-                self.set_status(&format!("{}: {}", verb, task.summary), false);
-                let _ = self.reload_tasks();
+                let message = format!("{}: {}", verb, task.summary);
+                self.set_status(&message, false);
+                self.reload_after_change(&message);
             }
             Err(e) => {
                 self.set_status(&format!("Error: {}", e), true);
@@ -503,7 +2509,23 @@ impl App {
     }
 
     /// Cycle priority of selected task
+    /// Switch to the next theme and remember it in the config file
+    fn cycle_theme(&mut self) {
+        let theme = self.conf.preferences.tui_theme.next();
+        self.conf.preferences.tui_theme = theme;
+        match Preferences::save_setting("tui_theme", theme.as_str()) {
+            Ok(()) => self.set_status(&format!("Theme: {}", theme.as_str()), false),
+            Err(e) => self.set_status(
+                &format!("Theme: {} (not saved: {})", theme.as_str(), e),
+                true,
+            ),
+        }
+    }
+
     fn cycle_priority(&mut self) {
+        if !self.check_not_syncing() {
+            return;
+        }
         let task = match self.selected_task() {
             Some(t) => t.clone(),
             None => {
@@ -522,30 +2544,33 @@ impl App {
 
         let result = (|| -> Result<(), rstask_core::error::RstaskError> {
             let include_resolved = task.status == STATUS_RESOLVED;
-            let mut ts = TaskSet::load(&self.conf.repo, &self.conf.ids_file, include_resolved)?;
-            let mut t = ts
+            let mut ts = TaskSet::load_for_update(&self.conf, include_resolved)?;
+            let before = ts
                 .get_by_uuid(&task.uuid)
                 .ok_or_else(|| rstask_core::error::RstaskError::TaskNotFound(task.uuid.clone()))?
                 .clone();
+            let mut t = before.clone();
             t.priority = new_priority.to_string();
             t.write_pending = true;
-            ts.must_update_task(t)?;
+            ts.must_update_task(t.clone())?;
             ts.save_pending_changes()?;
-            git_commit(
-                &self.conf.repo,
+            commit_changes(
+                &self.conf,
                 &format!("Changed priority of {} to {}", task.summary, new_priority),
                 true,
             )?;
+            self.conf.events.emit_change(&before, &t);
             Ok(())
         })();
 
         match result {
             Ok(()) => {
-                self.set_status(
-                    &format!("Priority: {} -> {}", task.priority, new_priority),
-                    false,
+                let message = format!(
+                    "Priority of {}: {} -> {}",
+                    task.summary, task.priority, new_priority
                 );
-                let _ = self.reload_tasks();
+                self.set_status(&message, false);
+                self.reload_after_change(&message);
             }
             Err(e) => {
                 self.set_status(&format!("Error: {}", e), true);
@@ -553,13 +2578,105 @@ impl App {
         }
     }
 
-    /// Sync with remote git repository (pull + push), then reload tasks
+    /// Carry out a command sent through the control socket, answering with
+    /// the status message it produced
+    #[cfg(unix)]
+    fn handle_control(&mut self, cmd: ControlCommand) -> Result<String, String> {
+        self.status_message = None;
+        match cmd {
+            ControlCommand::Reload => match self.reload_tasks() {
+                Ok(()) => self.set_status("Tasks reloaded", false),
+                Err(e) => self.set_status(&format!("Reload error: {}", e), true),
+            },
+            ControlCommand::Filter(query) => {
+                parse_query(&split_words(&query)).map_err(|e| e.to_string())?;
+                self.filter_text = query;
+                self.apply_filter();
+                self.set_status(
+                    &format!("Filter: {} tasks", self.filtered_indices.len()),
+                    false,
+                );
+            }
+            ControlCommand::Select(key) => {
+                let matches = |t: &Task| t.uuid == key || key.parse() == Ok(t.id) && t.id != 0;
+                // The task may be resolved and not loaded yet
+                if !self.resolved_loaded && !self.all_tasks.iter().any(matches) {
+                    self.resolved_loaded = true;
+                    self.reload_tasks().map_err(|e| e.to_string())?;
+                }
+                let idx = self
+                    .all_tasks
+                    .iter()
+                    .position(matches)
+                    .ok_or_else(|| format!("no task {}", key))?;
+                self.select_task(idx);
+            }
+            ControlCommand::Sync => self.sync(),
+        }
+
+        match &self.status_message {
+            Some(msg) if msg.is_error => Err(msg.text.clone()),
+            Some(msg) => Ok(msg.text.clone()),
+            None => Ok(String::new()),
+        }
+    }
+
+    /// Start syncing with the remote git repository (pull + push) on a
+    /// background thread, so that the list stays usable meanwhile.
+    /// `poll_sync` picks up the result.
     fn sync(&mut self) {
-        self.set_status("Syncing...", false);
+        if self.sync_job.is_some() {
+            self.set_status("A sync is already running", false);
+            return;
+        }
+
         let repo_path = self.conf.repo.to_str().unwrap().to_string();
-        match cmd_sync(&repo_path, true) {
-            Ok(summary) => match self.reload_tasks() {
-                Ok(()) => self.set_status(&format!("Synced: {}", summary), false),
+        let lock_timeout = self.conf.repo_settings().lock_timeout;
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = cmd_sync(&repo_path, lock_timeout, true);
+            let _ = tx.send(result.map_err(|e| e.to_string()));
+        });
+        self.sync_job = Some(SyncJob {
+            result: rx,
+            started: Instant::now(),
+        });
+        self.set_status("Syncing...", false);
+    }
+
+    /// Finishes the sync once its thread is done
+    fn poll_sync(&mut self) {
+        let Some(job) = &self.sync_job else {
+            return;
+        };
+        let result = match job.result.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("sync thread stopped".to_string()),
+        };
+        self.sync_job = None;
+        self.finish_sync(result);
+    }
+
+    /// Blocks until a running sync is done, so that quitting doesn't cut
+    /// it off halfway through a merge
+    fn wait_for_sync(&mut self) {
+        if let Some(job) = self.sync_job.take() {
+            let result = job
+                .result
+                .recv()
+                .unwrap_or_else(|_| Err("sync thread stopped".to_string()));
+            self.finish_sync(result);
+        }
+    }
+
+    fn finish_sync(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(summary) => match self.local_state.mark_synced().and(self.reload_tasks()) {
+                Ok(()) => {
+                    self.conf.events.emit(&TaskEvent::Synced);
+                    self.set_status(&format!("Synced: {}", summary), false)
+                }
                 Err(e) => self.set_status(&format!("Synced but reload failed: {}", e), true),
             },
             Err(e) => {
@@ -570,6 +2687,10 @@ impl App {
 
     /// Handle input events
     fn handle_event(&mut self, event: Event) {
+        if let Event::Paste(text) = &event {
+            self.handle_paste(text);
+            return;
+        }
         if let Event::Key(key) = event {
             // Global quit
             if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
@@ -583,6 +2704,12 @@ impl App {
                 return;
             }
 
+            // The dashboard only looks, so it has keys of its own
+            if self.dashboard.is_some() {
+                self.handle_dashboard_input(key);
+                return;
+            }
+
             // URL popup input
             if self.url_popup.is_some() {
                 self.handle_url_popup_input(key);
@@ -595,12 +2722,57 @@ impl App {
                 return;
             }
 
+            // Undo popup input
+            if self.undo_popup.is_some() {
+                self.handle_undo_popup_input(key);
+                return;
+            }
+
+            if self.deps_popup.is_some() {
+                self.handle_deps_popup_input(key);
+                return;
+            }
+
+            if self.jump_input.is_some() {
+                self.handle_jump_input(key);
+                return;
+            }
+
+            if self.delegate_input.is_some() {
+                self.handle_delegate_input(key);
+                return;
+            }
+
             // Add task input mode
             if self.add_input.is_some() {
                 self.handle_add_input(key);
                 return;
             }
 
+            // Command palette input
+            if self.palette.is_some() {
+                self.handle_palette_input(key);
+                return;
+            }
+
+            // Fuzzy finder input
+            if self.finder.is_some() {
+                self.handle_finder_input(key);
+                return;
+            }
+
+            // Due date picker input
+            if self.due_picker.is_some() {
+                self.handle_due_picker_input(key);
+                return;
+            }
+
+            // Modify popup input
+            if self.modify_input.is_some() {
+                self.handle_modify_input(key);
+                return;
+            }
+
             // Context popup input
             if self.context_popup.is_some() {
                 self.handle_context_popup_input(key);
@@ -613,12 +2785,70 @@ impl App {
                 return;
             }
 
+            // The note editor keeps Ctrl+P and Ctrl+F for itself
+            if key.code == KeyCode::Char('p')
+                && key.modifiers.contains(KeyModifiers::CONTROL)
+                && self.view != View::EditNote
+            {
+                self.palette = Some(CommandPalette::new());
+                self.status_message = None;
+                return;
+            }
+            if key.code == KeyCode::Char('f')
+                && key.modifiers.contains(KeyModifiers::CONTROL)
+                && self.view != View::EditNote
+            {
+                self.open_finder();
+                return;
+            }
+
             // View-specific input
             match self.view {
+                View::List if self.sidebar_focused => self.handle_sidebar_input(key),
                 View::List => self.handle_list_input(key),
                 View::Detail => self.handle_detail_input(key),
                 View::EditNote => self.handle_edit_note_input(key),
+                View::Stats => self.handle_stats_input(key),
+            }
+        }
+    }
+
+    /// Bracketed paste: the text goes into whichever input has focus, all at
+    /// once rather than as keys
+    fn handle_paste(&mut self, text: &str) {
+        if self.show_help || self.dashboard.is_some() {
+            return;
+        }
+        if let Some(input) = self.jump_input.as_mut() {
+            input.extend(text.chars().filter(char::is_ascii_digit));
+        } else if let Some(input) = self.delegate_input.as_mut() {
+            paste_line(&mut input.text, &mut input.cursor, text);
+        } else if let Some(form) = self.add_input.as_mut() {
+            let mut cursor = form.cursor;
+            if let Some(field) = form.text_mut() {
+                paste_line(field, &mut cursor, text);
             }
+            form.cursor = cursor;
+        } else if let Some(palette) = self.palette.as_mut() {
+            paste_line(&mut palette.text, &mut palette.cursor, text);
+            palette.selected = 0;
+        } else if let Some(finder) = self.finder.as_mut() {
+            paste_line(&mut finder.text, &mut finder.cursor, text);
+            finder.selected = 0;
+        } else if let Some(input) = self.modify_input.as_mut() {
+            paste_line(&mut input.text, &mut input.cursor, text);
+            input.completion = None;
+        } else if let Some(popup) = self.context_popup.as_mut() {
+            paste_line(&mut popup.text, &mut popup.cursor, text);
+            popup.completion = None;
+        } else if self.filter_active {
+            let mut cursor = self.filter_text.len();
+            paste_line(&mut self.filter_text, &mut cursor, text);
+            self.apply_filter();
+        } else if self.view == View::EditNote
+            && let Some(editor) = self.note_editor.as_mut()
+        {
+            editor.paste(text);
         }
     }
 
@@ -653,38 +2883,49 @@ impl App {
             KeyCode::Char('k') | KeyCode::Up => {
                 self.move_selection(-1);
             }
-            KeyCode::Char('g') | KeyCode::Home => {
-                if !self.filtered_indices.is_empty() {
-                    self.list_state.select(Some(0));
-                }
+            KeyCode::Char('g') | KeyCode::Home if !self.filtered_indices.is_empty() => {
+                self.list_state.select(Some(0));
             }
-            KeyCode::Char('G') | KeyCode::End => {
-                if !self.filtered_indices.is_empty() {
-                    self.list_state
-                        .select(Some(self.filtered_indices.len() - 1));
-                }
+            KeyCode::Char('G') | KeyCode::End if !self.filtered_indices.is_empty() => {
+                self.list_state
+                    .select(Some(self.filtered_indices.len() - 1));
             }
-            KeyCode::Enter => {
-                if self.selected_task().is_some() {
-                    self.view = View::Detail;
-                }
+            KeyCode::Enter if self.status_tab == StatusTab::Templates => {
+                self.add_from_template();
+            }
+            KeyCode::Enter if self.selected_task().is_some() => {
+                self.notes_scroll = 0;
+                self.view = View::Detail;
             }
             KeyCode::Char('/') => {
                 self.filter_active = true;
                 self.status_message = None;
             }
             KeyCode::Char('c') if !key.modifiers.contains(KeyModifiers::SHIFT) => {
-                self.filter_text.clear();
-                self.apply_filter();
-                self.set_status("Filter cleared", false);
+                self.clear_filter();
             }
             KeyCode::Tab => {
-                self.status_tab = self.status_tab.next();
-                self.apply_filter();
+                self.set_status_tab(self.status_tab.next());
             }
             KeyCode::BackTab => {
-                self.status_tab = self.status_tab.prev();
-                self.apply_filter();
+                self.set_status_tab(self.status_tab.prev());
+            }
+            KeyCode::Char('v') => {
+                self.toggle_split_view();
+            }
+            // The notes beside the list, in the split view
+            KeyCode::PageDown | KeyCode::PageUp if self.split_view => {
+                if let Some(offset) = scroll_offset(self.notes_scroll, key.code, self.notes_height)
+                {
+                    self.notes_scroll = offset;
+                }
+            }
+            // Project sidebar
+            KeyCode::Char('b') => {
+                self.toggle_sidebar();
+            }
+            KeyCode::Char('h') | KeyCode::Left if self.show_sidebar => {
+                self.sidebar_focused = true;
             }
             // Actions
             KeyCode::Char('s') if !key.modifiers.contains(KeyModifiers::SHIFT) => {
@@ -693,18 +2934,31 @@ impl App {
             KeyCode::Char('p') if !key.modifiers.contains(KeyModifiers::SHIFT) => {
                 self.change_task_status(STATUS_PAUSED);
             }
-            KeyCode::Char('d') => {
+            KeyCode::Char('d') if !key.modifiers.contains(KeyModifiers::SHIFT) => {
                 self.change_task_status(STATUS_RESOLVED);
             }
+            KeyCode::Char('#') | KeyCode::Char(':') => {
+                self.open_jump_prompt();
+            }
+            KeyCode::Char('@') => {
+                self.open_delegate_input();
+            }
+            KeyCode::Char('L') | KeyCode::Char('l')
+                if key.modifiers.contains(KeyModifiers::SHIFT) =>
+            {
+                self.toggle_activity();
+            }
+            KeyCode::Char('D') | KeyCode::Char('d')
+                if key.modifiers.contains(KeyModifiers::SHIFT) =>
+            {
+                self.open_deps_popup();
+            }
             KeyCode::Char('P') | KeyCode::Char('p')
                 if key.modifiers.contains(KeyModifiers::SHIFT) =>
             {
                 self.cycle_priority();
             }
-            KeyCode::Char('r') => match self.reload_tasks() {
-                Ok(()) => self.set_status("Tasks reloaded", false),
-                Err(e) => self.set_status(&format!("Reload error: {}", e), true),
-            },
+            KeyCode::Char('r') => self.reload(),
             KeyCode::Char('S') | KeyCode::Char('s')
                 if key.modifiers.contains(KeyModifiers::SHIFT) =>
             {
@@ -712,8 +2966,14 @@ impl App {
             }
             // Add task
             KeyCode::Char('a') => {
-                self.add_input = Some(AddTaskInput::new());
-                self.status_message = None;
+                self.open_add_form();
+            }
+            // Modify tags, project, priority and due date
+            KeyCode::Char('m') if !key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.request_modify_task();
+            }
+            KeyCode::Char('t') if !key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.request_due_picker();
             }
             // Remove task
             KeyCode::Char('x') => {
@@ -721,7 +2981,28 @@ impl App {
             }
             // Undo
             KeyCode::Char('u') => {
-                self.confirm_popup = Some(ConfirmPopup::new(ConfirmAction::Undo));
+                self.open_undo_popup();
+            }
+            // Weekly review
+            KeyCode::Char('w') => {
+                self.open_stats();
+            }
+            KeyCode::Char('z') => {
+                self.toggle_pomodoro();
+            }
+            // Copy to the clipboard
+            KeyCode::Char('y') if !key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.yank_task(false);
+            }
+            KeyCode::Char('Y') | KeyCode::Char('y')
+                if key.modifiers.contains(KeyModifiers::SHIFT) =>
+            {
+                self.yank_task(true);
+            }
+            KeyCode::Char('M') | KeyCode::Char('m')
+                if key.modifiers.contains(KeyModifiers::SHIFT) =>
+            {
+                self.convert_to_template();
             }
             // Edit with $EDITOR
             KeyCode::Char('E') | KeyCode::Char('e')
@@ -736,11 +3017,23 @@ impl App {
                 let ctx = self.local_state.get_context().clone();
                 self.context_popup = Some(ContextPopup::new(&ctx));
             }
+            // Theme
+            KeyCode::Char('T') | KeyCode::Char('t')
+                if key.modifiers.contains(KeyModifiers::SHIFT) =>
+            {
+                self.cycle_theme();
+            }
             _ => {}
         }
     }
 
     fn handle_detail_input(&mut self, key: KeyEvent) {
+        if let Some(offset) = scroll_offset(self.notes_scroll, key.code, self.notes_height) {
+            // Drawing clamps the offset to the length of the notes
+            self.notes_scroll = offset;
+            return;
+        }
+
         match key.code {
             KeyCode::Esc | KeyCode::Char('q') | KeyCode::Backspace => {
                 self.view = View::List;
@@ -750,17 +3043,38 @@ impl App {
             }
             // Enter edit mode for notes
             KeyCode::Char('e') if !key.modifiers.contains(KeyModifiers::SHIFT) => {
-                if let Some(task) = self.selected_task() {
-                    let editor = NoteEditor::new(&task.notes, &task.uuid);
-                    self.note_editor = Some(editor);
-                    self.view = View::EditNote;
-                    self.set_status("Editing notes | Ctrl+S: save | Esc: cancel", false);
-                }
+                self.request_edit_notes();
+            }
+            KeyCode::Char('m') => {
+                self.request_modify_task();
+            }
+            KeyCode::Char('t') => {
+                self.request_due_picker();
             }
             // Open URLs found in task
             KeyCode::Char('o') => {
                 self.open_task_urls();
             }
+            KeyCode::Char('y') if !key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.yank_task(false);
+            }
+            KeyCode::Char('Y') | KeyCode::Char('y')
+                if key.modifiers.contains(KeyModifiers::SHIFT) =>
+            {
+                self.yank_task(true);
+            }
+            KeyCode::Char('z') => {
+                self.toggle_pomodoro();
+            }
+            // The task's file, for debugging
+            KeyCode::Char('f') if !key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.open_task_file(false);
+            }
+            KeyCode::Char('F') | KeyCode::Char('f')
+                if key.modifiers.contains(KeyModifiers::SHIFT) =>
+            {
+                self.open_task_file(true);
+            }
             // Edit with $EDITOR
             KeyCode::Char('E') | KeyCode::Char('e')
                 if key.modifiers.contains(KeyModifiers::SHIFT) =>
@@ -774,12 +3088,28 @@ impl App {
             KeyCode::Char('p') if !key.modifiers.contains(KeyModifiers::SHIFT) => {
                 self.change_task_status(STATUS_PAUSED);
             }
-            KeyCode::Char('d') => {
+            KeyCode::Char('d') if !key.modifiers.contains(KeyModifiers::SHIFT) => {
                 self.change_task_status(STATUS_RESOLVED);
                 if self.selected_task().is_none() {
                     self.view = View::List;
                 }
             }
+            KeyCode::Char('#') | KeyCode::Char(':') => {
+                self.open_jump_prompt();
+            }
+            KeyCode::Char('@') => {
+                self.open_delegate_input();
+            }
+            KeyCode::Char('L') | KeyCode::Char('l')
+                if key.modifiers.contains(KeyModifiers::SHIFT) =>
+            {
+                self.toggle_activity();
+            }
+            KeyCode::Char('D') | KeyCode::Char('d')
+                if key.modifiers.contains(KeyModifiers::SHIFT) =>
+            {
+                self.open_deps_popup();
+            }
             KeyCode::Char('P') | KeyCode::Char('p')
                 if key.modifiers.contains(KeyModifiers::SHIFT) =>
             {
@@ -817,6 +3147,29 @@ impl App {
             return;
         }
 
+        // Ctrl+Z and Ctrl+Y to undo and redo
+        if key.modifiers.contains(KeyModifiers::CONTROL)
+            && matches!(key.code, KeyCode::Char('z') | KeyCode::Char('y'))
+        {
+            let undo = key.code == KeyCode::Char('z');
+            let done = if undo { editor.undo() } else { editor.redo() };
+            if !done {
+                let msg = if undo {
+                    "Nothing to undo"
+                } else {
+                    "Nothing to redo"
+                };
+                self.set_status(msg, false);
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char(_) | KeyCode::Enter | KeyCode::Tab => editor.record(EditKind::Insert),
+            KeyCode::Backspace | KeyCode::Delete => editor.record(EditKind::Delete),
+            _ => editor.last_edit = None,
+        }
+
         match key.code {
             KeyCode::Char(c) => {
                 editor.lines[editor.cursor_row].insert(editor.cursor_col, c);
@@ -858,6 +3211,9 @@ impl App {
                     editor.lines[editor.cursor_row].push_str(&next_line);
                 }
             }
+            KeyCode::Left | KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                editor.move_word(key.code == KeyCode::Right);
+            }
             KeyCode::Left => {
                 if editor.cursor_col > 0 {
                     // Move to previous char boundary
@@ -883,24 +3239,10 @@ impl App {
                     editor.cursor_col = 0;
                 }
             }
-            KeyCode::Up => {
-                if editor.cursor_row > 0 {
-                    editor.cursor_row -= 1;
-                    editor.clamp_cursor_col();
-                }
-            }
-            KeyCode::Down => {
-                if editor.cursor_row + 1 < editor.lines.len() {
-                    editor.cursor_row += 1;
-                    editor.clamp_cursor_col();
-                }
-            }
-            KeyCode::Home => {
-                editor.cursor_col = 0;
-            }
-            KeyCode::End => {
-                editor.cursor_col = editor.lines[editor.cursor_row].len();
-            }
+            KeyCode::Up => editor.move_vertical(false),
+            KeyCode::Down => editor.move_vertical(true),
+            KeyCode::Home => editor.move_to_row_edge(false),
+            KeyCode::End => editor.move_to_row_edge(true),
             KeyCode::Tab => {
                 // Insert 4 spaces
                 editor.lines[editor.cursor_row].insert_str(editor.cursor_col, "    ");
@@ -912,234 +3254,946 @@ impl App {
 
     /// Save the edited note back to the task
     fn save_note(&mut self) {
+        if !self.check_not_syncing() {
+            return;
+        }
         let (note_text, task_uuid) = match &self.note_editor {
             Some(editor) => (editor.to_string(), editor.task_uuid.clone()),
             None => return,
         };
 
         let result = (|| -> Result<String, rstask_core::error::RstaskError> {
-            let mut ts = TaskSet::load(&self.conf.repo, &self.conf.ids_file, true)?;
-            let mut task = ts
-                .get_by_uuid(&task_uuid)
-                .ok_or_else(|| rstask_core::error::RstaskError::TaskNotFound(task_uuid.clone()))?
+            let mut ts = TaskSet::load_for_update(&self.conf, true)?;
+            let before = ts
+                .get_by_uuid(&task_uuid)
+                .ok_or_else(|| rstask_core::error::RstaskError::TaskNotFound(task_uuid.clone()))?
+                .clone();
+            let summary = before.summary.clone();
+            let mut task = before.clone();
+            task.notes = note_text;
+            task.write_pending = true;
+            ts.must_update_task(task.clone())?;
+            ts.save_pending_changes()?;
+            commit_changes(&self.conf, &format!("Updated notes for {}", summary), true)?;
+            self.conf.events.emit_change(&before, &task);
+            Ok(summary)
+        })();
+
+        match result {
+            Ok(summary) => {
+                self.note_editor = None;
+                self.view = View::Detail;
+                let message = format!("Notes saved for {}", summary);
+                self.set_status(&message, false);
+                self.reload_after_change(&message);
+            }
+            Err(e) => {
+                self.set_status(&format!("Save failed: {}", e), true);
+            }
+        }
+    }
+
+    fn move_selection(&mut self, delta: i32) {
+        if self.filtered_indices.is_empty() {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0) as i32;
+        let new = (current + delta).clamp(0, self.filtered_indices.len() as i32 - 1) as usize;
+        self.list_state.select(Some(new));
+        self.notes_scroll = 0;
+    }
+
+    /// Copies the selected task's summary to the clipboard, or with
+    /// `markdown` a block with its details and notes
+    fn yank_task(&mut self, markdown: bool) {
+        let task = match self.selected_task() {
+            Some(t) => t.clone(),
+            None => {
+                self.set_status("No task selected", true);
+                return;
+            }
+        };
+        let text = if markdown {
+            task_markdown(&task)
+        } else {
+            task.summary.clone()
+        };
+
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(e) => {
+                    self.set_status(&format!("Clipboard unavailable: {}", e), true);
+                    return;
+                }
+            }
+        }
+        let result = self.clipboard.as_mut().map(|c| c.set_text(text));
+        match result {
+            Some(Err(e)) => self.set_status(&format!("Failed to copy: {}", e), true),
+            _ if markdown => {
+                self.set_status(&format!("Copied {} as markdown", task.summary), false)
+            }
+            _ => self.set_status(&format!("Copied {}", task.summary), false),
+        }
+    }
+
+    /// Switches to the stats view, first loading resolved tasks so there's
+    /// something to count
+    fn open_stats(&mut self) {
+        if !self.resolved_loaded {
+            self.resolved_loaded = true;
+            if let Err(e) = self.reload_tasks() {
+                self.set_status(&format!("Error: {}", e), true);
+                return;
+            }
+        }
+        self.view = View::Stats;
+    }
+
+    fn handle_stats_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('w') => self.view = View::List,
+            KeyCode::Char('?') => self.show_help = true,
+            KeyCode::Char('r') => self.reload(),
+            _ => {}
+        }
+    }
+
+    fn open_deps_popup(&mut self) {
+        let Some(task) = self.selected_task() else {
+            self.set_status("No task selected", true);
+            return;
+        };
+        let uuid = task.uuid.clone();
+        // Finished blockers are resolved tasks, which may not be loaded yet
+        let missing = task
+            .dependencies
+            .iter()
+            .any(|dep| !self.all_tasks.iter().any(|t| &t.uuid == dep));
+        if missing && !self.resolved_loaded {
+            self.resolved_loaded = true;
+            if let Err(e) = self.reload_tasks() {
+                self.set_status(&format!("Reload error: {}", e), true);
+                return;
+            }
+        }
+
+        let Some(task) = self.all_tasks.iter().find(|t| t.uuid == uuid) else {
+            return;
+        };
+        let lines = dependency_tree(task, &self.all_tasks);
+        if lines.len() == 1 {
+            self.set_status("No dependencies", false);
+            return;
+        }
+        self.deps_popup = Some(DepsPopup::new(lines));
+    }
+
+    fn handle_deps_popup_input(&mut self, key: KeyEvent) {
+        let popup = match self.deps_popup.as_mut() {
+            Some(p) => p,
+            None => return,
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.deps_popup = None,
+            KeyCode::Char('j') | KeyCode::Down => popup.move_selection(true),
+            KeyCode::Char('k') | KeyCode::Up => popup.move_selection(false),
+            KeyCode::Enter => {
+                let uuid = popup.lines[popup.selected].uuid.clone();
+                self.deps_popup = None;
+                if let Some(idx) =
+                    uuid.and_then(|u| self.all_tasks.iter().position(|t| t.uuid == u))
+                {
+                    self.select_task(idx);
+                    self.view = View::Detail;
+                    self.notes_scroll = 0;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn open_task_urls(&mut self) {
+        let task = match self.selected_task() {
+            Some(t) => t.clone(),
+            None => return,
+        };
+
+        let urls = task_links(&task);
+
+        if urls.is_empty() {
+            self.set_status("No URLs found in task", true);
+            return;
+        }
+
+        if urls.len() == 1 {
+            self.open_url(&urls[0]);
+            return;
+        }
+
+        // Multiple URLs — show selection popup, where a number opens that link
+        self.url_popup = Some(UrlPopup::new(urls));
+    }
+
+    fn open_url(&mut self, url: &str) {
+        match open_browser(url) {
+            Ok(()) => self.set_status(&format!("Opened {}", url), false),
+            Err(e) => self.set_status(&format!("Failed to open URL: {}", e), true),
+        }
+    }
+
+    fn handle_url_popup_input(&mut self, key: KeyEvent) {
+        let popup = match self.url_popup.as_mut() {
+            Some(p) => p,
+            None => return,
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.url_popup = None;
+            }
+            KeyCode::Char('j') | KeyCode::Down if popup.cursor + 1 < popup.urls.len() => {
+                popup.cursor += 1;
+            }
+            KeyCode::Char('k') | KeyCode::Up if popup.cursor > 0 => {
+                popup.cursor -= 1;
+            }
+            KeyCode::Char(' ') => {
+                popup.toggle_current();
+            }
+            KeyCode::Char('a') => {
+                popup.toggle_all();
+            }
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = c as usize - '1' as usize;
+                if let Some(url) = popup.urls.get(index).cloned() {
+                    self.url_popup = None;
+                    self.open_url(&url);
+                }
+            }
+            KeyCode::Enter => {
+                // Open checked URLs, or the currently highlighted one if none checked
+                let urls_to_open: Vec<String> = if popup.any_checked() {
+                    popup.checked_urls().iter().map(|s| s.to_string()).collect()
+                } else {
+                    vec![popup.urls[popup.cursor].clone()]
+                };
+
+                let count = urls_to_open.len();
+                let mut errors = Vec::new();
+                for url in &urls_to_open {
+                    if let Err(e) = open_browser(url) {
+                        errors.push(format!("{}", e));
+                    }
+                }
+
+                self.url_popup = None;
+                if errors.is_empty() {
+                    self.set_status(
+                        &format!("Opened {} URL{}", count, if count == 1 { "" } else { "s" }),
+                        false,
+                    );
+                } else {
+                    self.set_status(
+                        &format!("Failed to open some URLs: {}", errors.join(", ")),
+                        true,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_confirm_popup_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                let action = self.confirm_popup.take().unwrap().action;
+                self.execute_confirmed_action(action);
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.confirm_popup = None;
+                self.set_status("Cancelled", false);
+            }
+            _ => {}
+        }
+    }
+
+    fn execute_confirmed_action(&mut self, action: ConfirmAction) {
+        match action {
+            ConfirmAction::RemoveTask { uuid, summary } => {
+                self.do_remove_task(&uuid, &summary);
+            }
+        }
+    }
+
+    fn project_names(&self) -> Vec<String> {
+        self.projects.iter().map(|p| p.name.clone()).collect()
+    }
+
+    fn open_add_form(&mut self) {
+        self.add_input = Some(AddTaskForm::new(
+            self.local_state.get_context(),
+            self.project_names(),
+            self.tags.clone(),
+        ));
+        self.status_message = None;
+    }
+
+    fn handle_add_input(&mut self, key: KeyEvent) {
+        let form = match self.add_input.as_mut() {
+            Some(f) => f,
+            None => return,
+        };
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+
+        match key.code {
+            KeyCode::Esc => {
+                self.add_input = None;
+                self.set_status("Add cancelled", false);
+            }
+            KeyCode::Enter => {
+                if form.summary.trim().is_empty() {
+                    form.field = AddField::Summary;
+                    form.cursor = form.summary.len();
+                    self.set_status("A summary is needed", true);
+                } else {
+                    let form = self.add_input.take().unwrap();
+                    self.do_add_task(form);
+                }
+            }
+            KeyCode::BackTab | KeyCode::Up => form.focus(-1),
+            KeyCode::Tab if shift => form.focus(-1),
+            KeyCode::Tab | KeyCode::Down => form.focus(1),
+            // Accepting a completion, as in a shell
+            KeyCode::Right
+                if form.text().is_some_and(|t| form.cursor == t.len()) && form.complete() => {}
+            _ => match form.field {
+                AddField::Priority => match key.code {
+                    KeyCode::Left => form.priority = form.priority.saturating_sub(1),
+                    KeyCode::Right => form.priority = (form.priority + 1).min(PRIORITIES.len() - 1),
+                    KeyCode::Char(c @ '0'..='3') => form.priority = c as usize - '0' as usize,
+                    _ => {}
+                },
+                AddField::Due => {
+                    let today = Local::now().date_naive();
+                    match key.code {
+                        KeyCode::Left => form.move_due(-1),
+                        KeyCode::Right => form.move_due(1),
+                        KeyCode::Char('t') => form.due = Some(today),
+                        KeyCode::Char('o') => form.due = today.succ_opt(),
+                        KeyCode::Char('w') => form.due = today.checked_add_days(Days::new(7)),
+                        KeyCode::Char('x') | KeyCode::Backspace | KeyCode::Delete => {
+                            form.due = None
+                        }
+                        _ => {}
+                    }
+                }
+                // A task can't be both started and resolved
+                AddField::Start => {
+                    if key.code == KeyCode::Char(' ') {
+                        form.start_immediately = !form.start_immediately;
+                        form.resolve_immediately &= !form.start_immediately;
+                    }
+                }
+                AddField::Log => {
+                    if key.code == KeyCode::Char(' ') {
+                        form.resolve_immediately = !form.resolve_immediately;
+                        form.start_immediately &= !form.resolve_immediately;
+                    }
+                }
+                _ => {
+                    let mut cursor = form.cursor;
+                    if let Some(text) = form.text_mut() {
+                        edit_line(text, &mut cursor, key);
+                    }
+                    form.cursor = cursor;
+                }
+            },
+        }
+    }
+
+    fn handle_modify_input(&mut self, key: KeyEvent) {
+        let projects = self.project_names();
+        let input = match self.modify_input.as_mut() {
+            Some(i) => i,
+            None => return,
+        };
+        if handle_completion(
+            &mut input.completion,
+            &mut input.text,
+            &mut input.cursor,
+            key,
+            &self.tags,
+            &projects,
+        ) {
+            return;
+        }
+
+        match key.code {
+            KeyCode::Esc => {
+                self.modify_input = None;
+                self.set_status("Modify cancelled", false);
+            }
+            KeyCode::Enter => match input.to_modification() {
+                Ok((query, clear_due)) => {
+                    let uuid = input.task_uuid.clone();
+                    self.modify_input = None;
+                    self.do_modify_task(&uuid, &query, clear_due);
+                }
+                // Keep the popup open so the text can be fixed
+                Err(e) => self.set_status(&format!("Parse error: {}", e), true),
+            },
+            _ => {
+                edit_line(&mut input.text, &mut input.cursor, key);
+            }
+        }
+    }
+
+    fn request_modify_task(&mut self) {
+        match self.selected_task() {
+            Some(task) => {
+                self.modify_input = Some(ModifyInput::new(task));
+                self.status_message = None;
+            }
+            None => self.set_status("No task selected", true),
+        }
+    }
+
+    fn do_modify_task(&mut self, uuid: &str, query: &Query, clear_due: bool) {
+        self.update_task(
+            uuid,
+            |t| {
+                t.modify(query);
+                if clear_due {
+                    t.due = None;
+                }
+            },
+            |t| format!("Modified {}", t.summary),
+        );
+    }
+
+    /// Applies `change` to a task, then saves, commits with the message
+    /// `describe` gives and reports it in the status bar
+    fn update_task(
+        &mut self,
+        uuid: &str,
+        change: impl FnOnce(&mut Task),
+        describe: impl FnOnce(&Task) -> String,
+    ) {
+        if !self.check_not_syncing() {
+            return;
+        }
+        let result = (|| -> Result<String, rstask_core::error::RstaskError> {
+            let mut ts = TaskSet::load_for_update(&self.conf, true)?;
+            let before = ts
+                .get_by_uuid(uuid)
+                .ok_or_else(|| rstask_core::error::RstaskError::TaskNotFound(uuid.to_string()))?
                 .clone();
-            let summary = task.summary.clone();
-            task.notes = note_text;
-            task.write_pending = true;
-            ts.must_update_task(task)?;
+            let mut t = before.clone();
+            change(&mut t);
+            t.write_pending = true;
+            ts.must_update_task(t.clone())?;
             ts.save_pending_changes()?;
-            git_commit(
-                &self.conf.repo,
-                &format!("Updated notes for {}", summary),
-                true,
-            )?;
-            Ok(summary)
+            let message = describe(&t);
+            commit_changes(&self.conf, &message, true)?;
+            self.conf.events.emit_change(&before, &t);
+            Ok(message)
         })();
 
         match result {
-            Ok(summary) => {
-                self.note_editor = None;
-                self.view = View::Detail;
-                self.set_status(&format!("Notes saved for {}", summary), false);
-                let _ = self.reload_tasks();
+            Ok(message) => {
+                self.set_status(&message, false);
+                self.reload_after_change(&message);
             }
             Err(e) => {
-                self.set_status(&format!("Save failed: {}", e), true);
+                self.set_status(&format!("Error: {}", e), true);
             }
         }
     }
 
-    fn move_selection(&mut self, delta: i32) {
-        if self.filtered_indices.is_empty() {
-            return;
+    fn open_delegate_input(&mut self) {
+        match self.selected_task() {
+            Some(task) => {
+                let text = task.delegated_to.clone();
+                self.delegate_input = Some(DelegateInput {
+                    cursor: text.len(),
+                    text,
+                    task_uuid: task.uuid.clone(),
+                });
+                self.status_message = None;
+            }
+            None => self.set_status("No task selected", true),
         }
-        let current = self.list_state.selected().unwrap_or(0) as i32;
-        let new = (current + delta).clamp(0, self.filtered_indices.len() as i32 - 1) as usize;
-        self.list_state.select(Some(new));
     }
 
-    fn open_task_urls(&mut self) {
-        let task = match self.selected_task() {
-            Some(t) => t.clone(),
-            None => return,
+    fn handle_delegate_input(&mut self, key: KeyEvent) {
+        let Some(input) = self.delegate_input.as_mut() else {
+            return;
         };
+        match key.code {
+            KeyCode::Esc => {
+                self.delegate_input = None;
+            }
+            KeyCode::Enter => {
+                let input = self.delegate_input.take().unwrap();
+                let name = input.text.trim().trim_start_matches('@').to_string();
+                self.update_task(
+                    &input.task_uuid,
+                    |t| t.delegated_to = name,
+                    |t| {
+                        if t.delegated_to.is_empty() {
+                            format!("Cleared delegate of {}", t.summary)
+                        } else {
+                            format!("Delegated {} to {}", t.summary, t.delegated_to)
+                        }
+                    },
+                );
+            }
+            _ => {
+                edit_line(&mut input.text, &mut input.cursor, key);
+            }
+        }
+    }
 
-        let text = format!("{} {}", task.summary, task.notes);
-        let urls = extract_urls(&text);
+    /// Everyone tasks are delegated to, for the delegate popup
+    fn delegate_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .all_tasks
+            .iter()
+            .map(|t| t.delegated_to.as_str())
+            .filter(|name| !name.is_empty())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
 
-        if urls.is_empty() {
-            self.set_status("No URLs found in task", true);
-            return;
+    fn request_due_picker(&mut self) {
+        match self.selected_task() {
+            Some(task) => {
+                self.due_picker = Some(DuePicker::new(task));
+                self.status_message = None;
+            }
+            None => self.set_status("No task selected", true),
         }
+    }
 
-        if urls.len() == 1 {
-            match open_browser(&urls[0]) {
-                Ok(()) => self.set_status(&format!("Opened {}", urls[0]), false),
-                Err(e) => self.set_status(&format!("Failed to open URL: {}", e), true),
-            }
+    /// Shows the dashboard's current view from the top of the list
+    fn show_dashboard_view(&mut self) {
+        let Some(dashboard) = &self.dashboard else {
             return;
+        };
+        self.filter_text = dashboard.query().to_string();
+        self.project_filter = None;
+        self.status_tab = StatusTab::All;
+        self.view = View::List;
+        self.apply_filter();
+        if !self.filtered_indices.is_empty() {
+            self.list_state.select(Some(0));
         }
-
-        // Multiple URLs — show selection popup
-        self.url_popup = Some(UrlPopup::new(urls));
     }
 
-    fn handle_url_popup_input(&mut self, key: KeyEvent) {
-        let popup = match self.url_popup.as_mut() {
-            Some(p) => p,
-            None => return,
+    /// Moves to the next view and syncs when they're due
+    fn dashboard_tick(&mut self, now: Instant) {
+        let Some(dashboard) = self.dashboard.as_mut() else {
+            return;
         };
+        let (next_view, sync) = dashboard.due(now);
+        if sync {
+            dashboard.synced_at = now;
+        }
+        if next_view {
+            dashboard.advance(1, now);
+        }
+
+        if sync {
+            self.sync();
+        }
+        if next_view {
+            self.show_dashboard_view();
+        }
+    }
 
+    fn handle_dashboard_input(&mut self, key: KeyEvent) {
+        let now = Instant::now();
         match key.code {
-            KeyCode::Esc | KeyCode::Char('q') => {
-                self.url_popup = None;
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Esc if self.view == View::List => self.should_quit = true,
+            KeyCode::Esc | KeyCode::Backspace => self.view = View::List,
+            KeyCode::Enter if self.selected_task().is_some() => {
+                self.notes_scroll = 0;
+                self.view = View::Detail;
             }
-            KeyCode::Char('j') | KeyCode::Down => {
-                if popup.cursor + 1 < popup.urls.len() {
-                    popup.cursor += 1;
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Tab => {
+                if let Some(dashboard) = self.dashboard.as_mut() {
+                    dashboard.advance(1, now);
                 }
+                self.show_dashboard_view();
             }
-            KeyCode::Char('k') | KeyCode::Up => {
-                if popup.cursor > 0 {
-                    popup.cursor -= 1;
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::BackTab => {
+                if let Some(dashboard) = self.dashboard.as_mut() {
+                    dashboard.advance(-1, now);
                 }
+                self.show_dashboard_view();
             }
-            KeyCode::Char(' ') => {
-                popup.toggle_current();
+            KeyCode::Char('r') => self.reload(),
+            code if self.view == View::Detail => {
+                if let Some(offset) = scroll_offset(self.notes_scroll, code, self.notes_height) {
+                    self.notes_scroll = offset;
+                }
             }
-            KeyCode::Char('a') => {
-                popup.toggle_all();
+            KeyCode::Char('j') | KeyCode::Down => self.move_selection(1),
+            KeyCode::Char('k') | KeyCode::Up => self.move_selection(-1),
+            KeyCode::Char('g') | KeyCode::Home if !self.filtered_indices.is_empty() => {
+                self.list_state.select(Some(0));
             }
-            KeyCode::Enter => {
-                // Open checked URLs, or the currently highlighted one if none checked
-                let urls_to_open: Vec<String> = if popup.any_checked() {
-                    popup.checked_urls().iter().map(|s| s.to_string()).collect()
-                } else {
-                    vec![popup.urls[popup.cursor].clone()]
-                };
-
-                let count = urls_to_open.len();
-                let mut errors = Vec::new();
-                for url in &urls_to_open {
-                    if let Err(e) = open_browser(url) {
-                        errors.push(format!("{}", e));
-                    }
-                }
-
-                self.url_popup = None;
-                if errors.is_empty() {
-                    self.set_status(
-                        &format!("Opened {} URL{}", count, if count == 1 { "" } else { "s" }),
-                        false,
-                    );
-                } else {
-                    self.set_status(
-                        &format!("Failed to open some URLs: {}", errors.join(", ")),
-                        true,
-                    );
-                }
+            KeyCode::Char('G') | KeyCode::End if !self.filtered_indices.is_empty() => {
+                self.list_state
+                    .select(Some(self.filtered_indices.len() - 1));
             }
             _ => {}
         }
     }
 
-    fn handle_confirm_popup_input(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Char('y') | KeyCode::Char('Y') => {
-                let action = self.confirm_popup.take().unwrap().action;
-                self.execute_confirmed_action(action);
-            }
-            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-                self.confirm_popup = None;
-                self.set_status("Cancelled", false);
-            }
-            _ => {}
+    fn clear_filter(&mut self) {
+        self.filter_text.clear();
+        self.project_filter = None;
+        self.apply_filter();
+        self.set_status("Filter cleared", false);
+    }
+
+    fn reload(&mut self) {
+        match self.reload_tasks() {
+            Ok(()) => self.set_status("Tasks reloaded", false),
+            Err(e) => self.set_status(&format!("Reload error: {}", e), true),
         }
     }
 
-    fn execute_confirmed_action(&mut self, action: ConfirmAction) {
-        match action {
-            ConfirmAction::RemoveTask { uuid, summary } => {
-                self.do_remove_task(&uuid, &summary);
+    fn request_edit_notes(&mut self) {
+        if let Some(task) = self.selected_task() {
+            let editor = NoteEditor::new(&task.notes, &task.uuid);
+            self.note_editor = Some(editor);
+            self.view = View::EditNote;
+            self.set_status("Editing notes | Ctrl+S: save | Esc: cancel", false);
+        }
+    }
+
+    fn handle_palette_input(&mut self, key: KeyEvent) {
+        let Some(palette) = self.palette.as_mut() else {
+            return;
+        };
+        let count = palette.matches().len();
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Esc => {
+                self.palette = None;
+            }
+            KeyCode::Enter => {
+                let action = palette.matches().get(palette.selected).map(|e| e.action);
+                self.palette = None;
+                if let Some(action) = action {
+                    self.run_palette_action(action);
+                }
             }
-            ConfirmAction::Undo => {
-                self.do_undo();
+            KeyCode::Up => {
+                palette.selected = palette.selected.saturating_sub(1);
+            }
+            KeyCode::Char('p' | 'k') if ctrl => {
+                palette.selected = palette.selected.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                palette.selected = (palette.selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Char('n' | 'j') if ctrl => {
+                palette.selected = (palette.selected + 1).min(count.saturating_sub(1));
+            }
+            _ if ctrl => {}
+            _ => {
+                if edit_line(&mut palette.text, &mut palette.cursor, key) {
+                    palette.selected = 0;
+                }
             }
         }
     }
 
-    fn handle_add_input(&mut self, key: KeyEvent) {
-        let input = match self.add_input.as_mut() {
-            Some(i) => i,
-            None => return,
+    fn open_finder(&mut self) {
+        self.finder = Some(FuzzyFinder::new(&self.all_tasks));
+        self.status_message = None;
+    }
+
+    fn handle_finder_input(&mut self, key: KeyEvent) {
+        let Some(finder) = self.finder.as_mut() else {
+            return;
         };
+        let count = finder.matches().len();
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
 
         match key.code {
             KeyCode::Esc => {
-                self.add_input = None;
-                self.set_status("Add cancelled", false);
+                self.finder = None;
             }
             KeyCode::Enter => {
-                if input.text.trim().is_empty() {
-                    self.add_input = None;
-                    self.set_status("Add cancelled (empty)", false);
-                } else {
-                    let text = input.text.clone();
-                    let resolve = input.resolve_immediately;
-                    self.add_input = None;
-                    self.do_add_task(&text, resolve);
+                let target = finder
+                    .matches()
+                    .get(finder.selected)
+                    .map(|e| e.target.clone());
+                self.finder = None;
+                if let Some(target) = target {
+                    self.go_to_find_target(target);
                 }
             }
-            KeyCode::Tab => {
-                // Toggle resolve-immediately checkbox
-                input.resolve_immediately = !input.resolve_immediately;
+            KeyCode::Up => {
+                finder.selected = finder.selected.saturating_sub(1);
+            }
+            KeyCode::Char('p' | 'k') if ctrl => {
+                finder.selected = finder.selected.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                finder.selected = (finder.selected + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Char('n' | 'j') if ctrl => {
+                finder.selected = (finder.selected + 1).min(count.saturating_sub(1));
+            }
+            _ if ctrl => {}
+            _ => {
+                if edit_line(&mut finder.text, &mut finder.cursor, key) {
+                    finder.selected = 0;
+                }
+            }
+        }
+    }
+
+    fn open_jump_prompt(&mut self) {
+        self.jump_input = Some(String::new());
+        self.status_message = None;
+    }
+
+    fn handle_jump_input(&mut self, key: KeyEvent) {
+        let Some(text) = self.jump_input.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.jump_input = None,
+            KeyCode::Enter => {
+                let text = self.jump_input.take().unwrap_or_default();
+                if !text.is_empty() {
+                    self.jump_to_id(&text);
+                }
             }
             KeyCode::Backspace => {
-                if input.cursor > 0 {
-                    let prev = input.text[..input.cursor]
-                        .char_indices()
-                        .last()
-                        .map(|(i, _)| i)
-                        .unwrap_or(0);
-                    input.text.remove(prev);
-                    input.cursor = prev;
+                text.pop();
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() => text.push(c),
+            _ => {}
+        }
+    }
+
+    /// Selects the task with the given ID, switching tabs and clearing the
+    /// filters if it isn't listed
+    fn jump_to_id(&mut self, text: &str) {
+        let Some(idx) = text
+            .parse::<i32>()
+            .ok()
+            .filter(|&id| id > 0)
+            .and_then(|id| self.all_tasks.iter().position(|t| t.id == id))
+        else {
+            self.set_status(&format!("No open task with ID {}", text), true);
+            return;
+        };
+        self.sidebar_focused = false;
+        self.notes_scroll = 0;
+        if self.view != View::Detail {
+            self.view = View::List;
+        }
+        self.select_task(idx);
+    }
+
+    fn go_to_find_target(&mut self, target: FindTarget) {
+        self.view = View::List;
+        self.sidebar_focused = false;
+        match target {
+            FindTarget::Task(uuid) => {
+                if let Some(idx) = self.all_tasks.iter().position(|t| t.uuid == uuid) {
+                    self.select_task(idx);
                 }
             }
-            KeyCode::Left => {
-                if input.cursor > 0 {
-                    input.cursor = input.text[..input.cursor]
-                        .char_indices()
-                        .last()
-                        .map(|(i, _)| i)
-                        .unwrap_or(0);
+            FindTarget::Project(project) => {
+                self.project_filter = Some(project.clone());
+                self.list_state.select(Some(0));
+                self.apply_filter();
+                self.set_status(&format!("Project: {}", project), false);
+            }
+            FindTarget::Tag(tag) => {
+                self.filter_text = format!("+{}", tag);
+                self.list_state.select(Some(0));
+                self.apply_filter();
+                self.set_status(
+                    &format!("Filter: {} tasks", self.filtered_indices.len()),
+                    false,
+                );
+            }
+        }
+    }
+
+    fn run_palette_action(&mut self, action: PaletteAction) {
+        match action {
+            PaletteAction::AddTask => {
+                self.open_add_form();
+            }
+            PaletteAction::ShowDetail => {
+                if self.selected_task().is_some() {
+                    self.notes_scroll = 0;
+                    self.view = View::Detail;
                 }
             }
-            KeyCode::Right => {
-                if input.cursor < input.text.len() {
-                    let rest = &input.text[input.cursor..];
-                    let next_len = rest.chars().next().map(|c| c.len_utf8()).unwrap_or(0);
-                    input.cursor += next_len;
+            PaletteAction::ModifyTask => self.request_modify_task(),
+            PaletteAction::PickDue => self.request_due_picker(),
+            PaletteAction::StartTask => self.change_task_status(STATUS_ACTIVE),
+            PaletteAction::PauseTask => self.change_task_status(STATUS_PAUSED),
+            PaletteAction::DoneTask => {
+                self.change_task_status(STATUS_RESOLVED);
+                if self.selected_task().is_none() {
+                    self.view = View::List;
                 }
             }
-            KeyCode::Home => {
-                input.cursor = 0;
+            PaletteAction::CyclePriority => self.cycle_priority(),
+            PaletteAction::RemoveTask => self.request_remove_task(),
+            PaletteAction::EditNotes => self.request_edit_notes(),
+            PaletteAction::EditInEditor => self.request_editor(),
+            PaletteAction::OpenUrls => self.open_task_urls(),
+            PaletteAction::ShowDependencies => self.open_deps_popup(),
+            PaletteAction::YankSummary => self.yank_task(false),
+            PaletteAction::YankMarkdown => self.yank_task(true),
+            PaletteAction::Filter => {
+                self.view = View::List;
+                self.sidebar_focused = false;
+                self.filter_active = true;
             }
-            KeyCode::End => {
-                input.cursor = input.text.len();
+            PaletteAction::Find => self.open_finder(),
+            PaletteAction::GoToId => self.open_jump_prompt(),
+            PaletteAction::Delegate => self.open_delegate_input(),
+            PaletteAction::ShowDelegated => {
+                self.view = View::List;
+                self.set_status_tab(StatusTab::Delegated);
+            }
+            PaletteAction::ClearFilter => self.clear_filter(),
+            PaletteAction::NextStatusTab => {
+                self.set_status_tab(self.status_tab.next());
+            }
+            PaletteAction::ShowResolved => {
+                self.view = View::List;
+                self.set_status_tab(StatusTab::Resolved);
+            }
+            PaletteAction::ShowStats => self.open_stats(),
+            PaletteAction::Pomodoro => self.toggle_pomodoro(),
+            PaletteAction::ShowTemplates => {
+                self.view = View::List;
+                self.set_status_tab(StatusTab::Templates);
+            }
+            PaletteAction::AddFromTemplate => self.add_from_template(),
+            PaletteAction::ConvertToTemplate => self.convert_to_template(),
+            PaletteAction::ToggleSidebar => {
+                self.view = View::List;
+                self.toggle_sidebar();
+            }
+            PaletteAction::ToggleSplitView => {
+                self.view = View::List;
+                self.toggle_split_view();
+            }
+            PaletteAction::ToggleActivity => self.toggle_activity(),
+            PaletteAction::SetContext => {
+                let ctx = self.local_state.get_context().clone();
+                self.context_popup = Some(ContextPopup::new(&ctx));
+            }
+            PaletteAction::ClearContext => self.do_set_context(""),
+            PaletteAction::Reload => self.reload(),
+            PaletteAction::Sync => self.sync(),
+            PaletteAction::Undo => {
+                self.open_undo_popup();
+            }
+            PaletteAction::CycleTheme => self.cycle_theme(),
+            PaletteAction::Help => self.show_help = true,
+            PaletteAction::Quit => self.should_quit = true,
+        }
+    }
+
+    fn handle_due_picker_input(&mut self, key: KeyEvent) {
+        let picker = match self.due_picker.as_mut() {
+            Some(p) => p,
+            None => return,
+        };
+        let today = Local::now().date_naive();
+
+        let chosen = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.due_picker = None;
+                self.set_status("Due date unchanged", false);
+                return;
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                picker.move_days(-1);
+                return;
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                picker.move_days(1);
+                return;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                picker.move_days(-7);
+                return;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                picker.move_days(7);
+                return;
+            }
+            KeyCode::PageUp | KeyCode::Char('<') => {
+                picker.move_months(-1);
+                return;
             }
-            KeyCode::Char(c) => {
-                input.text.insert(input.cursor, c);
-                input.cursor += c.len_utf8();
+            KeyCode::PageDown | KeyCode::Char('>') => {
+                picker.move_months(1);
+                return;
             }
-            _ => {}
-        }
+            KeyCode::Enter => Some(picker.cursor),
+            KeyCode::Char('t') => Some(today),
+            KeyCode::Char('o') => today.succ_opt(),
+            KeyCode::Char('w') => today.checked_add_days(Days::new(7)),
+            KeyCode::Char('x') => None,
+            _ => return,
+        };
+
+        let uuid = picker.task_uuid.clone();
+        self.due_picker = None;
+        self.update_task(
+            &uuid,
+            |t| t.due = chosen.map(|d| local_midnight(d).with_timezone(&Utc)),
+            |t| match chosen {
+                Some(date) => format!("Set due date of {} to {}", t.summary, date),
+                None => format!("Cleared due date of {}", t.summary),
+            },
+        );
     }
 
     fn handle_context_popup_input(&mut self, key: KeyEvent) {
+        let projects = self.project_names();
         let popup = match self.context_popup.as_mut() {
             Some(p) => p,
             None => return,
         };
+        if handle_completion(
+            &mut popup.completion,
+            &mut popup.text,
+            &mut popup.cursor,
+            key,
+            &self.tags,
+            &projects,
+        ) {
+            return;
+        }
 
         match key.code {
             KeyCode::Esc => {
@@ -1151,32 +4205,26 @@ impl App {
                 self.context_popup = None;
                 self.do_set_context(&text);
             }
-            KeyCode::Backspace => {
-                if popup.cursor > 0 {
-                    let prev = popup.text[..popup.cursor]
-                        .char_indices()
-                        .last()
-                        .map(|(i, _)| i)
-                        .unwrap_or(0);
-                    popup.text.remove(prev);
-                    popup.cursor = prev;
-                }
+            KeyCode::Backspace if popup.cursor > 0 => {
+                let prev = popup.text[..popup.cursor]
+                    .char_indices()
+                    .last()
+                    .map(|(i, _)| i)
+                    .unwrap_or(0);
+                popup.text.remove(prev);
+                popup.cursor = prev;
             }
-            KeyCode::Left => {
-                if popup.cursor > 0 {
-                    popup.cursor = popup.text[..popup.cursor]
-                        .char_indices()
-                        .last()
-                        .map(|(i, _)| i)
-                        .unwrap_or(0);
-                }
+            KeyCode::Left if popup.cursor > 0 => {
+                popup.cursor = popup.text[..popup.cursor]
+                    .char_indices()
+                    .last()
+                    .map(|(i, _)| i)
+                    .unwrap_or(0);
             }
-            KeyCode::Right => {
-                if popup.cursor < popup.text.len() {
-                    let rest = &popup.text[popup.cursor..];
-                    let next_len = rest.chars().next().map(|c| c.len_utf8()).unwrap_or(0);
-                    popup.cursor += next_len;
-                }
+            KeyCode::Right if popup.cursor < popup.text.len() => {
+                let rest = &popup.text[popup.cursor..];
+                let next_len = rest.chars().next().map(|c| c.len_utf8()).unwrap_or(0);
+                popup.cursor += next_len;
             }
             KeyCode::Home => {
                 popup.cursor = 0;
@@ -1209,17 +4257,21 @@ impl App {
 
     /// Actually remove a task after confirmation
     fn do_remove_task(&mut self, uuid: &str, summary: &str) {
+        if !self.check_not_syncing() {
+            return;
+        }
         let result = (|| -> Result<(), rstask_core::error::RstaskError> {
-            let mut ts = TaskSet::load(&self.conf.repo, &self.conf.ids_file, true)?;
+            let mut ts = TaskSet::load_for_update(&self.conf, true)?;
             ts.delete_task(uuid)?;
-            git_commit(&self.conf.repo, &format!("Removed {}", summary), true)?;
+            commit_changes(&self.conf, &format!("Removed {}", summary), true)?;
             Ok(())
         })();
 
         match result {
             Ok(()) => {
-                self.set_status(&format!("Removed: {}", summary), false);
-                let _ = self.reload_tasks();
+                let message = format!("Removed: {}", summary);
+                self.set_status(&message, false);
+                self.reload_after_change(&message);
                 // If we were in detail view, go back to list
                 if self.view == View::Detail {
                     self.view = View::List;
@@ -1231,75 +4283,192 @@ impl App {
         }
     }
 
-    /// Undo last git commit
-    fn do_undo(&mut self) {
-        match git_reset(&self.conf.repo) {
-            Ok(()) => {
-                self.set_status("Undone: last commit reverted", false);
-                let _ = self.reload_tasks();
+    /// Reloads after one of the TUI's own changes, remembering the commit it
+    /// made, if any, so that the change can be undone later
+    fn reload_after_change(&mut self, description: &str) {
+        if let Some(head) = head_commit(&self.conf.repo)
+            && self.known_head.as_ref() != Some(&head)
+        {
+            self.changes.push(SessionChange {
+                description: description.to_string(),
+                applied: head,
+                undone_by: None,
+            });
+        }
+        let _ = self.reload_tasks();
+    }
+
+    fn open_undo_popup(&mut self) {
+        if self.changes.is_empty() {
+            self.set_status("Nothing changed in this session yet", false);
+        } else {
+            self.undo_popup = Some(UndoPopup { selected: 0 });
+            self.status_message = None;
+        }
+    }
+
+    fn handle_undo_popup_input(&mut self, key: KeyEvent) {
+        let popup = match self.undo_popup.as_mut() {
+            Some(p) => p,
+            None => return,
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('u') => {
+                self.undo_popup = None;
             }
-            Err(e) => {
-                self.set_status(&format!("Undo failed: {}", e), true);
+            KeyCode::Char('j') | KeyCode::Down if popup.selected + 1 < self.changes.len() => {
+                popup.selected += 1;
+            }
+            KeyCode::Char('k') | KeyCode::Up if popup.selected > 0 => {
+                popup.selected -= 1;
             }
+            // Stays open, so that several changes can be undone in a row
+            KeyCode::Enter => {
+                let index = self.changes.len() - 1 - popup.selected;
+                self.toggle_change(index);
+            }
+            _ => {}
         }
     }
 
-    /// Add a new task from the input text
-    fn do_add_task(&mut self, text: &str, resolve: bool) {
-        let tokens: Vec<String> = text.split_whitespace().map(|s| s.to_string()).collect();
-        let query = match parse_query(&tokens) {
-            Ok(q) => q,
+    /// Undoes a change from this session, or redoes it if it's undone, by
+    /// reverting whichever commit last applied or reversed it. History is
+    /// only ever added to, so this is safe after syncing too.
+    fn toggle_change(&mut self, index: usize) {
+        if !self.check_not_syncing() {
+            return;
+        }
+        let change = &self.changes[index];
+        let (commit, verb) = change.next_revert();
+        let message = format!("{}: {}", verb, change.description);
+        match git_revert(&self.conf.repo, commit, &message) {
+            Ok(new_commit) => {
+                self.changes[index].reverted(new_commit);
+                self.set_status(&message, false);
+                let _ = self.reload_tasks();
+            }
             Err(e) => {
-                self.set_status(&format!("Parse error: {}", e), true);
-                return;
+                self.set_status(&format!("{} failed: {}", verb, e), true);
             }
-        };
+        }
+    }
 
-        if query.text.is_empty() {
-            self.set_status("No task summary provided", true);
+    /// Add a new task from the input text
+    fn do_add_task(&mut self, form: AddTaskForm) {
+        if !self.check_not_syncing() {
             return;
         }
-
-        // Merge with context
-        let ctx = self.local_state.get_context().clone();
-        let merged = query.merge(&ctx);
+        let resolve = form.resolve_immediately;
+        let start = form.start_immediately && !resolve;
+        let verb = if resolve {
+            "Logged"
+        } else if start {
+            "Added and started"
+        } else {
+            "Added"
+        };
 
         let result = (|| -> Result<String, rstask_core::error::RstaskError> {
-            let mut ts = TaskSet::load(&self.conf.repo, &self.conf.ids_file, false)?;
+            let mut ts = TaskSet::load_for_update(&self.conf, false)?;
             let task = Task {
-                summary: merged.text.clone(),
-                tags: merged.tags.clone(),
-                project: merged.project.clone(),
-                priority: if merged.priority.is_empty() {
-                    PRIORITY_NORMAL.to_string()
-                } else {
-                    merged.priority.clone()
-                },
+                summary: form.summary.trim().to_string(),
+                tags: form.tag_list(),
+                project: form.project.trim().to_string(),
+                priority: PRIORITIES[form.priority].to_string(),
                 status: if resolve {
                     STATUS_RESOLVED.to_string()
+                } else if start {
+                    STATUS_ACTIVE.to_string()
                 } else {
                     STATUS_PENDING.to_string()
                 },
                 resolved: if resolve { Some(Utc::now()) } else { None },
-                due: merged.due,
-                notes: merged.note.clone(),
+                due: form.due.map(|d| local_midnight(d).with_timezone(&Utc)),
+                notes: form.notes.trim().to_string(),
                 created: Utc::now(),
                 write_pending: true,
                 ..Default::default()
             };
             let summary = task.summary.clone();
-            ts.must_load_task(task)?;
+            let task = ts.must_load_task(task)?;
+            let paused = if self.conf.preferences.single_active_task && start {
+                ts.pause_active_except(&[&task.uuid])?
+            } else {
+                Vec::new()
+            };
             ts.save_pending_changes()?;
-            let verb = if resolve { "Logged" } else { "Added" };
-            git_commit(&self.conf.repo, &format!("{} {}", verb, summary), true)?;
+            commit_changes(&self.conf, &format!("{} {}", verb, summary), true)?;
+            self.conf.events.emit(&TaskEvent::TaskAdded(task));
+            for (before, after) in paused {
+                self.conf.events.emit_change(&before, &after);
+            }
             Ok(summary)
         })();
 
         match result {
             Ok(summary) => {
-                let verb = if resolve { "Logged" } else { "Added" };
-                self.set_status(&format!("{}: {}", verb, summary), false);
-                let _ = self.reload_tasks();
+                let message = format!("{}: {}", verb, summary);
+                self.set_status(&message, false);
+                self.reload_after_change(&message);
+            }
+            Err(e) => {
+                self.set_status(&format!("Add failed: {}", e), true);
+            }
+        }
+    }
+
+    /// Adds a pending task copied from the selected template, like
+    /// `rstask add template:N`
+    fn add_from_template(&mut self) {
+        if !self.check_not_syncing() {
+            return;
+        }
+        let template = match self.selected_task() {
+            Some(t) if is_template(t) => t.clone(),
+            Some(_) => {
+                self.set_status("Select a template on the Templates tab", true);
+                return;
+            }
+            None => {
+                self.set_status("No template selected", true);
+                return;
+            }
+        };
+
+        let ctx = self.local_state.get_context().clone();
+        let result = (|| -> Result<i32, rstask_core::error::RstaskError> {
+            let mut ts = TaskSet::load_for_update(&self.conf, false)?;
+            let mut task = Task {
+                write_pending: true,
+                status: STATUS_PENDING.to_string(),
+                summary: template.summary.clone(),
+                tags: template.tags.clone(),
+                project: template.project.clone(),
+                priority: template.priority.clone(),
+                due: template.due,
+                notes: template.notes.clone(),
+                created: Utc::now(),
+                ..Default::default()
+            };
+            task.modify(&ctx);
+            let task = ts.must_load_task(task)?;
+            ts.save_pending_changes()?;
+            commit_changes(
+                &self.conf,
+                &format!("Added {}: {}", task.id, task.summary),
+                true,
+            )?;
+            let id = task.id;
+            self.conf.events.emit(&TaskEvent::TaskAdded(task));
+            Ok(id)
+        })();
+
+        match result {
+            Ok(id) => {
+                let message = format!("Added {} from template: {}", id, template.summary);
+                self.set_status(&message, false);
+                self.reload_after_change(&message);
             }
             Err(e) => {
                 self.set_status(&format!("Add failed: {}", e), true);
@@ -1307,8 +4476,77 @@ impl App {
         }
     }
 
+    /// Turns the selected open task into a template, like `rstask template N`
+    fn convert_to_template(&mut self) {
+        if !self.check_not_syncing() {
+            return;
+        }
+        let task = match self.selected_task() {
+            Some(t) if HIDDEN_STATUSES.contains(&t.status.as_str()) => {
+                self.set_status("Only open tasks can become templates", true);
+                return;
+            }
+            Some(t) => t.clone(),
+            None => {
+                self.set_status("No task selected", true);
+                return;
+            }
+        };
+
+        let result = (|| -> Result<(), rstask_core::error::RstaskError> {
+            let mut ts = TaskSet::load_for_update(&self.conf, false)?;
+            let mut template = ts
+                .get_by_uuid(&task.uuid)
+                .ok_or_else(|| rstask_core::error::RstaskError::TaskNotFound(task.uuid.clone()))?
+                .clone();
+            template.status = STATUS_TEMPLATE.to_string();
+            template.write_pending = true;
+            ts.must_update_task(template)?;
+            ts.save_pending_changes()?;
+            commit_changes(
+                &self.conf,
+                &format!("Changed {} to Template", task.summary),
+                true,
+            )?;
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => {
+                let message = format!("Template: {}", task.summary);
+                self.set_status(&message, false);
+                self.reload_after_change(&message);
+                if self.view == View::Detail {
+                    self.view = View::List;
+                }
+            }
+            Err(e) => {
+                self.set_status(&format!("Error: {}", e), true);
+            }
+        }
+    }
+
     /// Request to open selected task in $EDITOR
     fn request_editor(&mut self) {
+        if !self.check_not_syncing() {
+            return;
+        }
+        let task = match self.selected_task() {
+            Some(t) => t.clone(),
+            None => {
+                self.set_status("No task selected", true);
+                return;
+            }
+        };
+        self.editor_request = Some(EditorRequest::Task(task.uuid.clone()));
+    }
+
+    /// Shows the path of the selected task's file and opens it, in $EDITOR
+    /// with `editor` or else with the system opener
+    fn open_task_file(&mut self, editor: bool) {
+        if editor && !self.check_not_syncing() {
+            return;
+        }
         let task = match self.selected_task() {
             Some(t) => t.clone(),
             None => {
@@ -1316,24 +4554,49 @@ impl App {
                 return;
             }
         };
-        self.editor_request = Some(task.uuid.clone());
+        let path = match task.file_path(&self.conf.repo) {
+            Some(p) => p,
+            None => {
+                self.set_status("Task file not found", true);
+                return;
+            }
+        };
+
+        if editor {
+            self.editor_request = Some(EditorRequest::File {
+                path,
+                summary: task.summary,
+            });
+            return;
+        }
+        match open_browser(&path.to_string_lossy()) {
+            Ok(()) => self.set_status(&format!("Opened {}", path.display()), false),
+            Err(e) => self.set_status(&format!("{}: {}", path.display(), e), true),
+        }
     }
 
     /// Actually run the external editor (called from main loop with terminal suspended)
     fn run_external_editor(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         let uuid = match self.editor_request.take() {
-            Some(u) => u,
+            Some(EditorRequest::Task(uuid)) => uuid,
+            Some(EditorRequest::File { path, summary }) => {
+                return self.edit_task_file(&path, &summary);
+            }
             None => return Ok(()),
         };
 
-        let mut ts = TaskSet::load(&self.conf.repo, &self.conf.ids_file, true)?;
+        let mut ts = TaskSet::load_for_update(&self.conf, true)?;
         let task = ts
             .get_by_uuid(&uuid)
             .ok_or_else(|| rstask_core::error::RstaskError::TaskNotFound(uuid.clone()))?
             .clone();
 
         let markdown = task_to_markdown(&task)?;
-        let edited = edit_string(&markdown)?;
+        let edited = edit_string(
+            self.conf.preferences.editor.as_deref(),
+            &markdown,
+            &make_temp_filename(task.id, &task.summary, "md"),
+        )?;
 
         if edited.trim() == markdown.trim() {
             self.set_status("No changes made", false);
@@ -1343,11 +4606,42 @@ impl App {
         let mut updated = task_from_markdown(&edited, &task.uuid, &task.status, task.id)?;
         updated.write_pending = true;
         let summary = updated.summary.clone();
-        ts.must_update_task(updated)?;
+        ts.must_update_task(updated.clone())?;
         ts.save_pending_changes()?;
-        git_commit(&self.conf.repo, &format!("Edited {}", summary), true)?;
-        self.set_status(&format!("Saved: {}", summary), false);
-        self.reload_tasks()?;
+        commit_changes(&self.conf, &format!("Edited {}", summary), true)?;
+        self.conf.events.emit_change(&task, &updated);
+        let message = format!("Saved: {}", summary);
+        self.set_status(&message, false);
+        self.reload_after_change(&message);
+        Ok(())
+    }
+
+    /// Edits a task file as it is on disk. The edit is committed if the
+    /// file still loads, and undone otherwise.
+    fn edit_task_file(
+        &mut self,
+        path: &std::path::Path,
+        summary: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let original = std::fs::read_to_string(path)?;
+        edit_file(self.conf.preferences.editor.as_deref(), path)?;
+        let edited = std::fs::read_to_string(path)?;
+        if edited == original {
+            self.set_status("No changes made", false);
+            return Ok(());
+        }
+
+        let rel_path = path.strip_prefix(&self.conf.repo).unwrap_or(path);
+        if let Err(e) = check_task_file(&rel_path.to_string_lossy(), &edited) {
+            std::fs::write(path, original)?;
+            self.set_status(&format!("Not saved, the file doesn't load: {}", e), true);
+            return Ok(());
+        }
+
+        commit_changes(&self.conf, &format!("Edited file of {}", summary), true)?;
+        let message = format!("Saved {}", rel_path.display());
+        self.set_status(&message, false);
+        self.reload_after_change(&message);
         Ok(())
     }
 
@@ -1370,8 +4664,7 @@ impl App {
             return;
         }
 
-        let tokens: Vec<String> = text.split_whitespace().map(|s| s.to_string()).collect();
-        let query = match parse_query(&tokens) {
+        let query = match parse_query(&split_words(text)) {
             Ok(q) => q,
             Err(e) => {
                 self.set_status(&format!("Parse error: {}", e), true);
@@ -1398,6 +4691,10 @@ impl App {
 // -- Rendering --
 
 fn ui(f: &mut Frame, app: &mut App) {
+    if f.area().width < MIN_WIDTH || f.area().height < MIN_HEIGHT {
+        draw_too_small(f);
+        return;
+    }
     let term_width = f.area().width as usize;
 
     // Compute help hint text so we can determine its height
@@ -1420,10 +4717,42 @@ fn ui(f: &mut Frame, app: &mut App) {
 
     draw_header(f, app, chunks[0]);
 
+    let main = if app.activity.is_some() && app.view != View::EditNote {
+        let panes = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(ACTIVITY_HEIGHT)])
+            .split(chunks[1]);
+        draw_activity(f, app, panes[1]);
+        panes[0]
+    } else {
+        chunks[1]
+    };
+
     match app.view {
-        View::List => draw_list(f, app, chunks[1]),
-        View::Detail => draw_detail(f, app, chunks[1]),
-        View::EditNote => draw_edit_note(f, app, chunks[1]),
+        View::List => {
+            let mut area = main;
+            if app.show_sidebar {
+                let panes = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Length(28), Constraint::Min(20)])
+                    .split(area);
+                draw_sidebar(f, app, panes[0]);
+                area = panes[1];
+            }
+            if app.split_view && term_width >= SPLIT_MIN_WIDTH as usize {
+                let panes = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(area);
+                draw_list(f, app, panes[0]);
+                draw_detail(f, app, panes[1]);
+            } else {
+                draw_list(f, app, area);
+            }
+        }
+        View::Detail => draw_detail(f, app, main),
+        View::EditNote => draw_edit_note(f, app, main),
+        View::Stats => draw_stats(f, app, main),
     }
 
     draw_status_bar(f, app, chunks[2]);
@@ -1441,13 +4770,102 @@ fn ui(f: &mut Frame, app: &mut App) {
         draw_confirm_popup(f, app);
     }
 
+    if let Some(ref popup) = app.undo_popup {
+        draw_undo_popup(f, app, popup);
+    }
+
+    if let Some(ref popup) = app.deps_popup {
+        draw_deps_popup(f, popup);
+    }
+
     if app.add_input.is_some() {
         draw_add_input(f, app);
     }
 
+    if app.modify_input.is_some() {
+        draw_modify_input(f, app);
+    }
+
+    if let Some(ref picker) = app.due_picker {
+        draw_due_picker(f, picker);
+    }
+
     if app.context_popup.is_some() {
         draw_context_popup(f, app);
     }
+
+    if let Some(ref palette) = app.palette {
+        draw_palette(f, palette);
+    }
+
+    if let Some(ref finder) = app.finder {
+        draw_finder(f, finder);
+    }
+
+    if let Some(ref text) = app.jump_input {
+        draw_jump_prompt(f, text);
+    }
+
+    if let Some(ref input) = app.delegate_input {
+        draw_delegate_input(f, input, &app.delegate_names());
+    }
+
+    apply_theme(f.buffer_mut(), app.conf.preferences.tui_theme);
+}
+
+/// Restyles the finished frame for the accessible themes, so the drawing code
+/// only deals with the default colours. Backgrounds become reverse video.
+fn apply_theme(buf: &mut Buffer, theme: TuiTheme) {
+    match theme {
+        TuiTheme::Default => return,
+        TuiTheme::Light => {
+            for cell in buf.content.iter_mut() {
+                cell.fg = light_color(cell.fg);
+                cell.bg = light_color(cell.bg);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    for cell in buf.content.iter_mut() {
+        if cell.bg != Color::Reset {
+            cell.bg = Color::Reset;
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.fg = match theme {
+            TuiTheme::Monochrome => Color::Reset,
+            _ => high_contrast_color(cell.fg),
+        };
+    }
+}
+
+/// Swaps the colours that get lost on a light background for darker ones.
+/// Exact `#rrggbb` colours are left as they are
+fn light_color(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+        Color::Gray => Color::DarkGray,
+        Color::Yellow | Color::LightYellow => Color::Indexed(136),
+        Color::Cyan | Color::LightCyan => Color::Indexed(31),
+        Color::Green | Color::LightGreen => Color::Indexed(28),
+        Color::Indexed(236) => Color::Indexed(254),
+        other => other,
+    }
+}
+
+fn high_contrast_color(color: Color) -> Color {
+    match color {
+        Color::Red => Color::LightRed,
+        Color::Green => Color::LightGreen,
+        Color::Yellow => Color::LightYellow,
+        Color::Blue => Color::LightBlue,
+        Color::Magenta => Color::LightMagenta,
+        Color::Cyan => Color::LightCyan,
+        Color::Reset => Color::Reset,
+        _ => Color::White,
+    }
 }
 
 fn draw_header(f: &mut Frame, app: &App, area: Rect) {
@@ -1465,9 +4883,11 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect) {
         StatusTab::Pending,
         StatusTab::Active,
         StatusTab::Paused,
+        StatusTab::Delegated,
         StatusTab::Resolved,
+        StatusTab::Templates,
     ];
-    let compact = width < 50;
+    let compact = width < 80;
     let tab_spans: Vec<Span> = tabs
         .iter()
         .map(|tab| {
@@ -1477,7 +4897,9 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect) {
                     StatusTab::Pending => "Pend",
                     StatusTab::Active => "Act",
                     StatusTab::Paused => "Pau",
+                    StatusTab::Delegated => "Dlg",
                     StatusTab::Resolved => "Res",
+                    StatusTab::Templates => "Tmpl",
                 }
             } else {
                 tab.label()
@@ -1514,6 +4936,66 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect) {
         ));
     }
 
+    if let Some(project) = &app.project_filter {
+        tab_line.push(Span::styled(
+            "  project:",
+            Style::default().fg(Color::DarkGray),
+        ));
+        tab_line.push(Span::styled(
+            project.as_str(),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    // Whether a sync would push or pull anything, as of the last fetch
+    match app.ahead_behind {
+        Some((0, 0)) => {
+            tab_line.push(Span::styled(
+                "  synced",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        Some((ahead, behind)) => {
+            tab_line.push(Span::raw("  "));
+            if ahead > 0 {
+                tab_line.push(Span::styled(
+                    format!("↑{} ", ahead),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            if behind > 0 {
+                tab_line.push(Span::styled(
+                    format!("↓{}", behind),
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+        }
+        None => {}
+    }
+
+    if let Some((task, since, others)) = app.current_work() {
+        let mut work = format!(
+            "  > {} {}",
+            task.id,
+            format_elapsed((Utc::now() - since).num_seconds())
+        );
+        if others > 0 {
+            work.push_str(&format!(" +{}", others));
+        }
+        tab_line.push(Span::styled(work, Style::default().fg(app.colors.active)));
+    }
+    if let Some(pomodoro) = &app.pomodoro {
+        let left = pomodoro.ends.saturating_duration_since(Instant::now());
+        tab_line.push(Span::styled(
+            format!("  pomodoro {}", format_elapsed(left.as_secs() as i64)),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+
     let tabs_widget = Paragraph::new(Line::from(tab_line));
     f.render_widget(tabs_widget, chunks[0]);
 
@@ -1529,6 +5011,25 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect) {
                     .add_modifier(Modifier::SLOW_BLINK),
             ),
         ])
+    } else if let Some(dashboard) = &app.dashboard {
+        Line::from(vec![
+            Span::styled(
+                format!(
+                    " view {}/{}: ",
+                    dashboard.current + 1,
+                    dashboard.views.len()
+                ),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(
+                if app.filter_text.is_empty() {
+                    "open tasks"
+                } else {
+                    app.filter_text.as_str()
+                },
+                Style::default().fg(Color::Yellow),
+            ),
+        ])
     } else if !app.filter_text.is_empty() {
         Line::from(vec![
             Span::styled(" filter: ", Style::default().fg(Color::DarkGray)),
@@ -1538,27 +5039,57 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect) {
         Line::from("")
     };
 
-    let filter_widget =
-        Paragraph::new(filter_content).block(Block::default().borders(Borders::BOTTOM));
+    let filter_widget = Paragraph::new(filter_content).block(
+        Block::default()
+            .borders(Borders::BOTTOM)
+            .border_style(Style::default().fg(app.colors.border)),
+    );
     f.render_widget(filter_widget, chunks[1]);
 }
 
-fn priority_color(priority: &str) -> Color {
+fn priority_color(priority: &str, colors: &ThemeColors) -> Color {
     match priority {
-        PRIORITY_CRITICAL => Color::Red,
-        PRIORITY_HIGH => Color::Yellow,
-        PRIORITY_NORMAL => Color::White,
-        PRIORITY_LOW => Color::DarkGray,
+        PRIORITY_CRITICAL => colors.priorities[0],
+        PRIORITY_HIGH => colors.priorities[1],
+        PRIORITY_NORMAL => colors.priorities[2],
+        PRIORITY_LOW => colors.priorities[3],
         _ => Color::White,
     }
 }
 
-fn status_color(status: &str) -> Color {
+/// Red when overdue, yellow inside the reminder lead time for its priority
+fn due_color(task: &Task, prefs: &Preferences) -> Color {
+    let now = Utc::now();
+    if task.due.is_some_and(|due| due < now) {
+        Color::Red
+    } else if task.is_due_soon(prefs, now) {
+        Color::Yellow
+    } else {
+        Color::Gray
+    }
+}
+
+/// Spells out what the due colour means, for themes that can't rely on it
+fn due_label(task: &Task, prefs: &Preferences) -> &'static str {
+    if matches!(prefs.tui_theme, TuiTheme::Default | TuiTheme::Light) {
+        return "";
+    }
+    let now = Utc::now();
+    if task.due.is_some_and(|due| due < now) {
+        " (overdue)"
+    } else if task.is_due_soon(prefs, now) {
+        " (soon)"
+    } else {
+        ""
+    }
+}
+
+fn status_color(status: &str, colors: &ThemeColors) -> Color {
     match status {
-        STATUS_ACTIVE => Color::Green,
-        STATUS_PAUSED => Color::Yellow,
-        STATUS_PENDING => Color::Blue,
-        STATUS_RESOLVED => Color::DarkGray,
+        STATUS_ACTIVE => colors.active,
+        STATUS_PAUSED => colors.paused,
+        STATUS_PENDING => colors.pending,
+        STATUS_RESOLVED => colors.resolved,
         _ => Color::White,
     }
 }
@@ -1573,38 +5104,200 @@ fn status_indicator(status: &str) -> &str {
     }
 }
 
+/// Where each week starts in a list of resolved tasks sorted by when they
+/// were resolved, with the heading to show above it, like `show-resolved`
+fn week_headings(resolved: &[Option<DateTime<Utc>>]) -> Vec<(usize, String)> {
+    let mut headings = Vec::new();
+    let mut last_week = None;
+    for (i, when) in resolved.iter().enumerate() {
+        let Some(when) = when else { continue };
+        let date = when.with_timezone(&Local).date_naive();
+        let week = date.iso_week();
+        if last_week != Some(week) {
+            let monday = date - Days::new(date.weekday().num_days_from_monday().into());
+            headings.push((
+                i,
+                format!(
+                    "Week {}, starting {}",
+                    week.week(),
+                    monday.format("%a %-d %b %Y")
+                ),
+            ));
+            last_week = Some(week);
+        }
+    }
+    headings
+}
+
+/// How many of the oldest open tasks the stats view lists
+const STATS_OLDEST: usize = 8;
+
+/// Figures for the stats view, worked out from the loaded tasks
+#[derive(Debug)]
+struct TaskStats {
+    /// Number of tasks with each status, leaving out statuses nobody uses
+    by_status: Vec<(&'static str, usize)>,
+    /// Tasks resolved in each week, oldest first, ending with this week
+    resolved_per_week: Vec<u64>,
+    overdue: usize,
+    /// Due within the next seven days
+    due_next_week: usize,
+    /// The open tasks created longest ago, with their age in days
+    oldest: Vec<(i64, String)>,
+}
+
+impl TaskStats {
+    fn new(tasks: &[Task], now: DateTime<Utc>, weeks: usize) -> Self {
+        let by_status = ALL_STATUSES
+            .iter()
+            .map(|&status| (status, tasks.iter().filter(|t| t.status == status).count()))
+            .filter(|&(_, count)| count > 0)
+            .collect();
+
+        // Weeks start on Monday, like the Resolved tab's headings
+        let today = now.with_timezone(&Local).date_naive();
+        let this_monday = today - Days::new(today.weekday().num_days_from_monday().into());
+        let mut resolved_per_week = vec![0; weeks];
+        for when in tasks.iter().filter_map(|t| t.resolved) {
+            let date = when.with_timezone(&Local).date_naive();
+            let monday = date - Days::new(date.weekday().num_days_from_monday().into());
+            let ago = (this_monday - monday).num_days() / 7;
+            if (0..weeks as i64).contains(&ago) {
+                resolved_per_week[weeks - 1 - ago as usize] += 1;
+            }
+        }
+
+        let open: Vec<&Task> = tasks
+            .iter()
+            .filter(|t| !HIDDEN_STATUSES.contains(&t.status.as_str()))
+            .collect();
+        let overdue = open
+            .iter()
+            .filter(|t| t.due.is_some_and(|due| due < now))
+            .count();
+        let due_next_week = open
+            .iter()
+            .filter(|t| {
+                t.due
+                    .is_some_and(|due| due >= now && due < now + Days::new(7))
+            })
+            .count();
+
+        let mut by_age = open;
+        by_age.sort_by_key(|t| t.created);
+        let oldest = by_age
+            .iter()
+            .take(STATS_OLDEST)
+            .map(|t| ((now - t.created).num_days(), task_label(t)))
+            .collect();
+
+        TaskStats {
+            by_status,
+            resolved_per_week,
+            overdue,
+            due_next_week,
+            oldest,
+        }
+    }
+}
+
+/// Columns of the task list: ID, status, priority, summary, project, tags
+/// and due date
+const LIST_COLUMNS: usize = 7;
+const LIST_SUMMARY: usize = 3;
+const NOTES_MARKER: &str = " [notes]";
+
+/// The text of each column of a task's row. Resolved tasks show when they
+/// were resolved in place of the ID they no longer have.
+fn list_cells(task: &Task, prefs: &Preferences) -> [String; LIST_COLUMNS] {
+    let id = if task.status == STATUS_RESOLVED {
+        task.resolved
+            .map(|dt| dt.format("%b %-d").to_string())
+            .unwrap_or_default()
+    } else {
+        task.id.to_string()
+    };
+    let mut summary = task.summary.clone();
+    if !task.notes.is_empty() {
+        summary.push_str(NOTES_MARKER);
+    }
+    let mut tags = if task.delegated_to.is_empty() {
+        Vec::new()
+    } else {
+        vec![format!("@{}", task.delegated_to)]
+    };
+    tags.extend(task.tags.iter().map(|t| format!("+{}", t)));
+    let tags = tags.join(" ");
+    let due = task.due_date_str(prefs.date_style);
+    let due = if due.is_empty() {
+        due
+    } else {
+        format!("{}{}", due, due_label(task, prefs))
+    };
+    [
+        id,
+        status_indicator(&task.status).to_string(),
+        task.priority.clone(),
+        summary,
+        task.project.clone(),
+        tags,
+        due,
+    ]
+}
+
+/// Widths for the list columns within `budget`, narrowing the widest first
+/// like the CLI table. Columns that are empty in every row take no space.
+fn list_column_widths(rows: &[[String; LIST_COLUMNS]], budget: usize) -> [usize; LIST_COLUMNS] {
+    let mut widths = [0; LIST_COLUMNS];
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.width());
+        }
+    }
+    let gaps = widths.iter().filter(|&&w| w > 0).count().saturating_sub(1);
+    fit_widths(&mut widths, budget.saturating_sub(gaps));
+    widths
+}
+
+/// Pads `text` to `width` columns, or cuts it short with an ellipsis
+fn fit_cell(text: &str, width: usize) -> String {
+    let text_width = text.width();
+    if text_width <= width {
+        return format!("{}{}", text, " ".repeat(width - text_width));
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let mut fitted = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        fitted.push(c);
+        used += w;
+    }
+    fitted.push('…');
+    format!("{}{}", fitted, " ".repeat(width - used - 1))
+}
+
 fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
-    let items: Vec<ListItem> = app
+    let rows: Vec<_> = app
         .filtered_indices
         .iter()
-        .map(|&idx| {
-            let task = &app.all_tasks[idx];
-            let pri_color = priority_color(&task.priority);
-            let st_color = status_color(&task.status);
-
-            let mut spans = vec![
-                Span::styled(
-                    if task.status == STATUS_RESOLVED {
-                        match task.resolved {
-                            Some(dt) => format!("{} ", dt.format("%b %-d")),
-                            None => "    ".to_string(),
-                        }
-                    } else {
-                        format!("{:>3} ", task.id)
-                    },
-                    Style::default().fg(Color::DarkGray),
-                ),
-                Span::styled(
-                    format!("{} ", status_indicator(&task.status)),
-                    Style::default().fg(st_color).add_modifier(Modifier::BOLD),
-                ),
-                Span::styled(
-                    format!("{} ", task.priority),
-                    Style::default().fg(pri_color).add_modifier(Modifier::BOLD),
-                ),
-            ];
+        .map(|&idx| list_cells(&app.all_tasks[idx], &app.conf.preferences))
+        .collect();
+    // Borders and the highlight symbol take four columns
+    let widths = list_column_widths(&rows, (area.width as usize).saturating_sub(4));
 
-            // Summary
+    let mut items: Vec<ListItem> = app
+        .filtered_indices
+        .iter()
+        .zip(&rows)
+        .map(|(&idx, cells)| {
+            let task = &app.all_tasks[idx];
             let summary_style = if task.status == STATUS_ACTIVE {
                 Style::default()
                     .fg(Color::White)
@@ -1612,50 +5305,93 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
             } else {
                 Style::default().fg(Color::White)
             };
-            spans.push(Span::styled(&task.summary, summary_style));
-
-            // Project
-            if !task.project.is_empty() {
-                spans.push(Span::styled(
-                    format!("  [{}]", task.project),
-                    Style::default().fg(Color::Cyan),
-                ));
-            }
-
-            // Tags
-            if !task.tags.is_empty() {
-                spans.push(Span::styled(
-                    format!("  +{}", task.tags.join(" +")),
-                    Style::default().fg(Color::Magenta),
-                ));
-            }
+            let styles = [
+                Style::default().fg(Color::DarkGray),
+                Style::default()
+                    .fg(status_color(&task.status, &app.colors))
+                    .add_modifier(Modifier::BOLD),
+                Style::default()
+                    .fg(priority_color(&task.priority, &app.colors))
+                    .add_modifier(Modifier::BOLD),
+                summary_style,
+                Style::default().fg(Color::Cyan),
+                Style::default().fg(Color::Magenta),
+                Style::default().fg(due_color(task, &app.conf.preferences)),
+            ];
 
-            // Due date
-            let due_str = task.parse_due_date_to_str();
-            if !due_str.is_empty() {
-                let due_color = if task.due.is_some() && task.due.unwrap() < Utc::now() {
-                    Color::Red
+            let matched = summary_style.bg(app.colors.search_match).fg(Color::Black);
+            let mut spans = Vec::new();
+            for (col, (cell, &width)) in cells.iter().zip(&widths).enumerate() {
+                if width == 0 {
+                    continue;
+                }
+                if !spans.is_empty() {
+                    spans.push(Span::raw(" "));
+                }
+                if col == 0 && cell.width() <= width {
+                    spans.push(Span::styled(format!("{:>width$}", cell), styles[col]));
+                } else if col == LIST_SUMMARY
+                    && !task.notes.is_empty()
+                    && width > NOTES_MARKER.len()
+                {
+                    // Cut the summary rather than the notes marker
+                    let fitted = fit_cell(&task.summary, width - NOTES_MARKER.len());
+                    let summary = fitted.trim_end();
+                    let padding = width - NOTES_MARKER.len() - summary.width();
+                    spans.extend(highlight_matches(
+                        summary,
+                        &app.search_text,
+                        styles[col],
+                        matched,
+                    ));
+                    spans.push(Span::styled(
+                        NOTES_MARKER,
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                    spans.push(Span::raw(" ".repeat(padding)));
+                } else if col == LIST_SUMMARY {
+                    spans.extend(highlight_matches(
+                        &fit_cell(cell, width),
+                        &app.search_text,
+                        styles[col],
+                        matched,
+                    ));
                 } else {
-                    Color::Yellow
-                };
-                spans.push(Span::styled(
-                    format!("  due:{}", due_str),
-                    Style::default().fg(due_color),
-                ));
-            }
-
-            // Notes indicator
-            if !task.notes.is_empty() {
-                spans.push(Span::styled(
-                    " [notes]",
-                    Style::default().fg(Color::DarkGray),
-                ));
+                    spans.push(Span::styled(fit_cell(cell, width), styles[col]));
+                }
             }
 
             ListItem::new(Line::from(spans))
         })
         .collect();
 
+    // Week headings go between the rows, so the selection moves down past
+    // the headings above it
+    let mut selected = app.list_state.selected();
+    if app.status_tab == StatusTab::Resolved {
+        let resolved: Vec<_> = app
+            .filtered_indices
+            .iter()
+            .map(|&i| app.all_tasks[i].resolved)
+            .collect();
+        for (n, (row, heading)) in week_headings(&resolved).into_iter().enumerate() {
+            items.insert(
+                row + n,
+                ListItem::new(Line::from(Span::styled(
+                    format!("  {}", heading),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ))),
+            );
+            if let Some(sel) = selected.as_mut()
+                && *sel >= row + n
+            {
+                *sel += 1;
+            }
+        }
+    }
+
     let task_count = app.filtered_indices.len();
     let total_count = app.all_tasks.len();
     let title = if task_count == total_count {
@@ -1665,47 +5401,238 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
     };
 
     let list = List::new(items)
-        .block(Block::default().title(title).borders(Borders::ALL))
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(app.colors.border)),
+        )
         .highlight_style(
             Style::default()
-                .bg(Color::Indexed(236))
+                .bg(app.colors.selection)
                 .add_modifier(Modifier::BOLD),
         )
         .highlight_symbol("> ");
 
-    f.render_stateful_widget(list, area, &mut app.list_state);
+    let mut state = ListState::default()
+        .with_offset(app.list_state.offset())
+        .with_selected(selected);
+    f.render_stateful_widget(list, area, &mut state);
+    *app.list_state.offset_mut() = state.offset();
+}
+
+fn draw_sidebar(f: &mut Frame, app: &mut App, area: Rect) {
+    let current_project = app.selected_task().map(|t| t.project.clone());
+    let open_total: usize = app.projects.iter().map(|p| p.open).sum();
+
+    let mut items = vec![ListItem::new(Line::from(vec![
+        Span::styled(
+            if app.project_filter.is_none() {
+                "* "
+            } else {
+                "  "
+            },
+            Style::default().fg(Color::Yellow),
+        ),
+        Span::styled("All projects", Style::default().fg(Color::White)),
+        Span::styled(
+            format!(" {}", open_total),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]))];
+
+    for project in &app.projects {
+        let filtered = app.project_filter.as_deref() == Some(project.name.as_str());
+        // The project of the task selected in the list stands out
+        let name_style = if current_project.as_deref() == Some(project.name.as_str()) {
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+        } else if project.open == 0 {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default().fg(Color::Cyan)
+        };
+
+        let mut spans = vec![
+            Span::styled(
+                if filtered { "* " } else { "  " },
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::styled(project.name.as_str(), name_style),
+            Span::styled(
+                format!(" {}", project.open),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ];
+        if project.active > 0 {
+            spans.push(Span::styled(
+                format!(" {}>", project.active),
+                Style::default().fg(Color::Green),
+            ));
+        }
+        items.push(ListItem::new(Line::from(spans)));
+    }
+
+    let border_style = if app.sidebar_focused {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default().fg(app.colors.border)
+    };
+    let mut list = List::new(items).block(
+        Block::default()
+            .title(" Projects ")
+            .borders(Borders::ALL)
+            .border_style(border_style),
+    );
+    if app.sidebar_focused {
+        list = list.highlight_style(
+            Style::default()
+                .bg(app.colors.selection)
+                .add_modifier(Modifier::BOLD),
+        );
+    }
+
+    f.render_stateful_widget(list, area, &mut app.sidebar_state);
+}
+
+fn draw_stats(f: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .title(" Stats ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.colors.border));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(9),
+            Constraint::Length(6),
+            Constraint::Min(3),
+        ])
+        .split(inner);
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[0]);
+
+    // One bar per column, up to a year
+    let weeks = (rows[1].width as usize).saturating_sub(2).clamp(1, 52);
+    let stats = TaskStats::new(&app.all_tasks, Utc::now(), weeks);
+    let heading = |text: &str| {
+        Line::from(Span::styled(
+            text.to_string(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))
+    };
+    let count_line = |label: &str, count: usize, color: Color| {
+        Line::from(vec![
+            Span::styled(
+                format!("  {:<10}", label),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(format!("{:>6}", count), Style::default().fg(color)),
+        ])
+    };
+
+    let mut status_lines = vec![heading("By status")];
+    for &(status, count) in &stats.by_status {
+        status_lines.push(count_line(status, count, status_color(status, &app.colors)));
+    }
+    f.render_widget(Paragraph::new(status_lines), top[0]);
+
+    let due_lines = vec![
+        heading("Due dates"),
+        count_line(
+            "overdue",
+            stats.overdue,
+            if stats.overdue > 0 {
+                Color::Red
+            } else {
+                Color::Gray
+            },
+        ),
+        count_line("next 7 days", stats.due_next_week, Color::Yellow),
+    ];
+    f.render_widget(Paragraph::new(due_lines), top[1]);
+
+    let this_week = stats.resolved_per_week.last().copied().unwrap_or(0);
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .title(format!(
+                    " Resolved per week, last {} weeks ({} this week) ",
+                    weeks, this_week
+                ))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray)),
+        )
+        .data(&stats.resolved_per_week)
+        .style(Style::default().fg(Color::Green));
+    f.render_widget(sparkline, rows[1]);
+
+    let mut oldest_lines = vec![heading("Oldest open tasks")];
+    let text_width = (rows[2].width as usize).saturating_sub(10);
+    for (age, label) in &stats.oldest {
+        oldest_lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {:>5}d ", age),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(
+                fit_cell(label, text_width),
+                Style::default().fg(Color::White),
+            ),
+        ]));
+    }
+    f.render_widget(Paragraph::new(oldest_lines), rows[2]);
 }
 
 fn draw_detail(f: &mut Frame, app: &mut App, area: Rect) {
     let task = match app.selected_task() {
         Some(t) => t.clone(),
         None => {
-            let msg = Paragraph::new("No task selected")
-                .block(Block::default().title(" Detail ").borders(Borders::ALL));
+            let msg = Paragraph::new("No task selected").block(
+                Block::default()
+                    .title(" Detail ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(app.colors.border)),
+            );
             f.render_widget(msg, area);
             return;
         }
     };
 
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(10), Constraint::Min(3)])
-        .split(area);
+    // Links are numbered as `o` then a number opens them
+    let mut links = Vec::new();
+    let summary = number_links(&task.summary, &mut links);
+    let notes = number_links(&task.notes, &mut links);
 
     // Metadata section
-    let pri_color = priority_color(&task.priority);
-    let st_color = status_color(&task.status);
+    let summary_style = Style::default()
+        .fg(Color::White)
+        .add_modifier(Modifier::BOLD);
+    let pri_color = priority_color(&task.priority, &app.colors);
+    let st_color = status_color(&task.status, &app.colors);
 
     let mut meta_lines = vec![
-        Line::from(vec![
-            Span::styled("  Summary: ", Style::default().fg(Color::DarkGray)),
-            Span::styled(
-                &task.summary,
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::BOLD),
-            ),
-        ]),
+        Line::from(
+            vec![Span::styled(
+                "  Summary: ",
+                Style::default().fg(Color::DarkGray),
+            )]
+            .into_iter()
+            .chain(highlight_matches(
+                &summary,
+                &app.search_text,
+                summary_style,
+                summary_style.bg(app.colors.search_match).fg(Color::Black),
+            ))
+            .collect::<Vec<_>>(),
+        ),
         Line::from(vec![
             Span::styled("       ID: ", Style::default().fg(Color::DarkGray)),
             Span::styled(task.id.to_string(), Style::default().fg(Color::White)),
@@ -1760,29 +5687,58 @@ fn draw_detail(f: &mut Frame, app: &mut App, area: Rect) {
     ];
 
     if let Some(due) = task.due {
-        let due_color = if due < Utc::now() {
-            Color::Red
-        } else {
-            Color::Yellow
-        };
+        let due_color = due_color(&task, &app.conf.preferences);
         meta_lines.push(Line::from(vec![
             Span::styled("      Due: ", Style::default().fg(Color::DarkGray)),
             Span::styled(
-                due.format("%Y-%m-%d %H:%M").to_string(),
+                format!(
+                    "{}{}",
+                    due.format("%Y-%m-%d %H:%M"),
+                    due_label(&task, &app.conf.preferences)
+                ),
                 Style::default().fg(due_color),
             ),
         ]));
     }
 
+    if !task.delegated_to.is_empty() {
+        meta_lines.push(Line::from(vec![
+            Span::styled("Delegated: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                format!("@{}", task.delegated_to),
+                Style::default().fg(Color::Magenta),
+            ),
+        ]));
+    }
+
+    if let Some(estimate) = task.estimate {
+        meta_lines.push(Line::from(vec![
+            Span::styled(" Estimate: ", Style::default().fg(Color::DarkGray)),
+            Span::styled(format_minutes(estimate), Style::default().fg(Color::White)),
+        ]));
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length((meta_lines.len() as u16 + 2).max(10)),
+            Constraint::Min(3),
+        ])
+        .split(area);
+
     let meta = Paragraph::new(meta_lines).block(
         Block::default()
             .title(" Task Detail ")
-            .borders(Borders::ALL),
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(app.colors.border)),
     );
     f.render_widget(meta, chunks[0]);
 
     // Notes section — rendered as markdown
-    let block = Block::default().title(" Notes ").borders(Borders::ALL);
+    let block = Block::default()
+        .title(" Notes ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.colors.border));
     let inner = block.inner(chunks[1]);
     f.render_widget(block, chunks[1]);
 
@@ -1794,16 +5750,127 @@ fn draw_detail(f: &mut Frame, app: &mut App, area: Rect) {
         f.render_widget(empty, inner);
     } else if inner.width > 0 {
         let theme = mdfrier::ratatui::DefaultTheme;
-        let md_lines = app.frier.parse(inner.width, &task.notes, &theme);
+        let md_lines = app.frier.parse(inner.width, &notes, &theme);
         let ratatui_lines: Vec<Line> = md_lines
             .into_iter()
             .map(|md_line| {
                 let (line, _tags) = mdfrier::ratatui::render_line(md_line, &theme);
-                line
+                highlight_line(line, &app.search_text, app.colors.search_match)
             })
             .collect();
-        let preview_widget = Paragraph::new(ratatui_lines).wrap(Wrap { trim: false });
+
+        // The parser wraps to the width, so each line takes one row
+        let height = inner.height as usize;
+        let max_scroll = ratatui_lines.len().saturating_sub(height);
+        app.notes_height = height;
+        app.notes_scroll = app.notes_scroll.min(max_scroll);
+
+        let preview_widget = Paragraph::new(ratatui_lines)
+            .wrap(Wrap { trim: false })
+            .scroll((app.notes_scroll as u16, 0));
         f.render_widget(preview_widget, inner);
+
+        if max_scroll > 0 {
+            let mut state = ScrollbarState::new(max_scroll + 1)
+                .viewport_content_length(height)
+                .position(app.notes_scroll);
+            f.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                    .begin_symbol(None)
+                    .end_symbol(None),
+                chunks[1].inner(ratatui::layout::Margin::new(0, 1)),
+                &mut state,
+            );
+        }
+    }
+}
+
+/// Byte ranges where `needle` occurs in `text`, ignoring case as the filter
+/// does
+fn match_ranges(text: &str, needle: &str) -> Vec<Range<usize>> {
+    let needle = needle.to_lowercase();
+    let mut ranges = Vec::new();
+    if needle.is_empty() {
+        return ranges;
+    }
+    let mut from = 0;
+    for (start, _) in text.char_indices() {
+        if start < from {
+            continue;
+        }
+        let mut rest = needle.as_str();
+        let mut end = start;
+        for c in text[start..].chars() {
+            let lower: String = c.to_lowercase().collect();
+            match rest.strip_prefix(lower.as_str()) {
+                Some(r) => rest = r,
+                None => break,
+            }
+            end += c.len_utf8();
+            if rest.is_empty() {
+                break;
+            }
+        }
+        if rest.is_empty() {
+            ranges.push(start..end);
+            from = end;
+        }
+    }
+    ranges
+}
+
+/// Spans for `text` with the parts matching `needle` in the `matched` style
+fn highlight_matches(text: &str, needle: &str, style: Style, matched: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut copied = 0;
+    for range in match_ranges(text, needle) {
+        if range.start > copied {
+            spans.push(Span::styled(text[copied..range.start].to_string(), style));
+        }
+        spans.push(Span::styled(text[range.clone()].to_string(), matched));
+        copied = range.end;
+    }
+    if copied < text.len() || spans.is_empty() {
+        spans.push(Span::styled(text[copied..].to_string(), style));
+    }
+    spans
+}
+
+/// Highlights `needle` within each span of a rendered line. A match split
+/// across differently styled spans isn't highlighted.
+fn highlight_line(line: Line<'_>, needle: &str, background: Color) -> Line<'static> {
+    let spans: Vec<Span<'static>> = line
+        .spans
+        .into_iter()
+        .flat_map(|span| {
+            highlight_matches(
+                &span.content,
+                needle,
+                span.style,
+                span.style.bg(background).fg(Color::Black),
+            )
+        })
+        .collect();
+    Line {
+        spans,
+        style: line.style,
+        alignment: line.alignment,
+    }
+}
+
+/// Where a scrolling key moves a pane showing `page` lines at a time, or
+/// None if the key doesn't scroll. The result may run past the end; the
+/// caller clamps it to the content.
+fn scroll_offset(offset: usize, key: KeyCode, page: usize) -> Option<usize> {
+    let page = page.max(1);
+    match key {
+        KeyCode::Char('j') | KeyCode::Down => Some(offset.saturating_add(1)),
+        KeyCode::Char('k') | KeyCode::Up => Some(offset.saturating_sub(1)),
+        KeyCode::PageDown | KeyCode::Char(' ') => Some(offset.saturating_add(page)),
+        KeyCode::PageUp => Some(offset.saturating_sub(page)),
+        KeyCode::Char('g') | KeyCode::Home => Some(0),
+        KeyCode::Char('G') | KeyCode::End => Some(usize::MAX),
+        _ => None,
     }
 }
 
@@ -1811,8 +5878,12 @@ fn draw_edit_note(f: &mut Frame, app: &mut App, area: Rect) {
     let task = match app.selected_task() {
         Some(t) => t,
         None => {
-            let msg = Paragraph::new("No task selected")
-                .block(Block::default().title(" Edit Notes ").borders(Borders::ALL));
+            let msg = Paragraph::new("No task selected").block(
+                Block::default()
+                    .title(" Edit Notes ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(app.colors.border)),
+            );
             f.render_widget(msg, area);
             return;
         }
@@ -1870,62 +5941,48 @@ fn draw_editor_pane(f: &mut Frame, editor: &mut NoteEditor, area: Rect) {
     let inner = block.inner(area);
     f.render_widget(block, area);
 
+    // Rows wrap short of the edge so the cursor fits after the last character
+    let gutter_width = format!("{}", editor.lines.len()).len() + 1;
+    editor.wrap_width = (inner.width as usize)
+        .saturating_sub(gutter_width + 2)
+        .max(1);
     let visible_height = inner.height as usize;
     editor.ensure_cursor_visible(visible_height);
 
-    // Build lines with cursor highlight
-    let mut lines: Vec<Line> = Vec::new();
-    let end = (editor.scroll + visible_height).min(editor.lines.len());
-    for row in editor.scroll..end {
-        let line_text = &editor.lines[row];
-        if row == editor.cursor_row {
-            // Show cursor on this line
-            let col = editor.cursor_col.min(line_text.len());
-            let before = &line_text[..col];
-            let cursor_char = if col < line_text.len() {
-                &line_text[col..col
-                    + line_text[col..]
-                        .chars()
-                        .next()
-                        .map(|c| c.len_utf8())
-                        .unwrap_or(1)]
+    // Build rows with cursor highlight, numbering only the first row of a line
+    let rows = editor.visual_rows();
+    let cursor = editor.cursor_visual_row(&rows);
+    let mut numbered_lines: Vec<Line> = Vec::new();
+    for (i, r) in rows
+        .iter()
+        .enumerate()
+        .skip(editor.scroll)
+        .take(visible_height)
+    {
+        let gutter = if r.start == 0 {
+            format!("{:>width$} ", r.line + 1, width = gutter_width)
+        } else {
+            " ".repeat(gutter_width + 1)
+        };
+        let mut spans = vec![Span::styled(gutter, Style::default().fg(Color::DarkGray))];
+        let text = &editor.lines[r.line][r.start..r.end];
+        if i == cursor {
+            let col = editor.cursor_col.clamp(r.start, r.end) - r.start;
+            let char_len = text[col..].chars().next().map_or(0, char::len_utf8);
+            let cursor_char = if char_len > 0 {
+                &text[col..col + char_len]
             } else {
                 " "
             };
-            let after = if col < line_text.len() {
-                let char_len = line_text[col..]
-                    .chars()
-                    .next()
-                    .map(|c| c.len_utf8())
-                    .unwrap_or(0);
-                &line_text[col + char_len..]
-            } else {
-                ""
-            };
-
-            lines.push(Line::from(vec![
-                Span::raw(before.to_string()),
-                Span::styled(
-                    cursor_char.to_string(),
-                    Style::default().bg(Color::White).fg(Color::Black),
-                ),
-                Span::raw(after.to_string()),
-            ]));
+            spans.push(Span::raw(text[..col].to_string()));
+            spans.push(Span::styled(
+                cursor_char.to_string(),
+                Style::default().bg(Color::White).fg(Color::Black),
+            ));
+            spans.push(Span::raw(text[col + char_len..].to_string()));
         } else {
-            lines.push(Line::raw(line_text.to_string()));
+            spans.push(Span::raw(text.to_string()));
         }
-    }
-
-    // Add line numbers gutter
-    let gutter_width = format!("{}", editor.lines.len()).len() + 1;
-    let mut numbered_lines: Vec<Line> = Vec::new();
-    for (i, line) in lines.into_iter().enumerate() {
-        let line_num = editor.scroll + i + 1;
-        let mut spans = vec![Span::styled(
-            format!("{:>width$} ", line_num, width = gutter_width),
-            Style::default().fg(Color::DarkGray),
-        )];
-        spans.extend(line.spans);
         numbered_lines.push(Line::from(spans));
     }
 
@@ -1977,18 +6034,61 @@ fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
         None => (String::new(), Style::default().fg(Color::DarkGray)),
     };
 
-    let bar = Paragraph::new(Span::styled(text, style));
+    let mut spans = Vec::new();
+    if let Some(job) = &app.sync_job {
+        let frame = (job.started.elapsed().as_millis() / 100) as usize % SPINNER.len();
+        spans.push(Span::styled(
+            format!("{} ", SPINNER[frame]),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    spans.push(Span::styled(text, style));
+
+    let bar = Paragraph::new(Line::from(spans));
     f.render_widget(bar, area);
 }
 
 fn build_help_hint(app: &App) -> String {
-    let segments: Vec<&str> = if app.filter_active {
+    let segments: Vec<&str> = if app.dashboard.is_some() {
+        vec![
+            "Dashboard (read-only)",
+            "q: quit",
+            "j/k: move",
+            "Enter: detail",
+            "h/l: previous/next view",
+            "r: reload",
+        ]
+    } else if app.filter_active {
         vec!["Type to filter", "Enter/Esc: confirm"]
+    } else if app.view == View::List && app.sidebar_focused {
+        vec![
+            "?: help",
+            "j/k: move",
+            "Enter: filter by project",
+            "Esc/l: back to tasks",
+            "b: hide projects",
+        ]
     } else {
         match app.view {
+            View::List if app.status_tab == StatusTab::Templates => {
+                vec![
+                    "?: help",
+                    "Ctrl+P: commands",
+                    "q: quit",
+                    "Enter: add task from template",
+                    "m: modify",
+                    "E: editor",
+                    "x: remove",
+                    "u: undo",
+                    "Tab: status",
+                ]
+            }
             View::List => {
                 vec![
                     "?: help",
+                    "Ctrl+P: commands",
+                    "Ctrl+F: find",
+                    "#: go to ID",
                     "q: quit",
                     "/: filter",
                     "Enter: detail",
@@ -1997,10 +6097,18 @@ fn build_help_hint(app: &App) -> String {
                     "p: pause",
                     "d: done",
                     "x: remove",
+                    "m: modify",
+                    "t: due",
+                    "@: delegate",
                     "P: priority",
                     "E: editor",
+                    "M: make template",
                     "u: undo",
                     "C: context",
+                    "T: theme",
+                    "b: projects",
+                    "v: split",
+                    "L: activity",
                     "Tab: status",
                     "r: reload",
                     "S: sync",
@@ -2011,9 +6119,16 @@ fn build_help_hint(app: &App) -> String {
                 vec![
                     "?: help",
                     "Esc: back",
+                    "j/k: scroll",
                     "e: edit",
                     "E: editor",
+                    "m: modify",
+                    "t: due",
                     "o: open URLs",
+                    "f/F: file",
+                    "D: deps",
+                    "@: delegate",
+                    "L: activity",
                     "s: start",
                     "p: pause",
                     "d: done",
@@ -2022,8 +6137,16 @@ fn build_help_hint(app: &App) -> String {
                 ]
             }
             View::EditNote => {
-                vec!["Ctrl+S: save", "Esc: cancel", "arrows: move", "Tab: indent"]
+                vec![
+                    "Ctrl+S: save",
+                    "Esc: cancel",
+                    "arrows: move",
+                    "Ctrl+arrows: word",
+                    "Ctrl+Z/Y: undo/redo",
+                    "Tab: indent",
+                ]
             }
+            View::Stats => vec!["?: help", "Esc: back", "r: reload", "q: quit"],
         }
     };
 
@@ -2037,202 +6160,745 @@ fn build_help_hint(app: &App) -> String {
     result
 }
 
-fn draw_help_hint(f: &mut Frame, hint_text: &str, area: Rect) {
-    let hint_widget = Paragraph::new(Span::styled(
-        hint_text,
-        Style::default().fg(Color::DarkGray),
-    ))
-    .wrap(Wrap { trim: false });
-    f.render_widget(hint_widget, area);
+fn draw_help_hint(f: &mut Frame, hint_text: &str, area: Rect) {
+    let hint_widget = Paragraph::new(Span::styled(
+        hint_text,
+        Style::default().fg(Color::DarkGray),
+    ))
+    .wrap(Wrap { trim: false });
+    f.render_widget(hint_widget, area);
+}
+
+fn draw_url_popup(f: &mut Frame, popup: &UrlPopup) {
+    // Size the popup based on content
+    let max_url_len = popup
+        .urls
+        .iter()
+        .map(|u| u.len())
+        .max()
+        .unwrap_or(20)
+        .min(80);
+    let width = (max_url_len + 13).min((f.area().width as usize).saturating_sub(4)) as u16;
+    // +5 for title, footer, borders, header line, blank line
+    let height = (popup.urls.len() + 6).min((f.area().height as usize).saturating_sub(2)) as u16;
+
+    let area = centered_rect_abs(width, height, f.area());
+    f.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Select URLs to open",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+
+    for (i, url) in popup.urls.iter().enumerate() {
+        let checkbox = if popup.checked[i] { "[x] " } else { "[ ] " };
+        let is_cursor = i == popup.cursor;
+
+        let style = if is_cursor {
+            Style::default()
+                .fg(Color::White)
+                .bg(Color::Indexed(236))
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Cyan)
+        };
+
+        let checkbox_style = if is_cursor {
+            Style::default()
+                .fg(Color::Yellow)
+                .bg(Color::Indexed(236))
+                .add_modifier(Modifier::BOLD)
+        } else if popup.checked[i] {
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{:>2} ", i + 1),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(checkbox.to_string(), checkbox_style),
+            Span::styled(url.clone(), style),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        " 1-9: open | Space: toggle | a: all | Enter: open | Esc: close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let popup_widget = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(" Open URLs ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(popup_widget, area);
+}
+
+fn draw_confirm_popup(f: &mut Frame, app: &App) {
+    let popup = match &app.confirm_popup {
+        Some(p) => p,
+        None => return,
+    };
+
+    let width = (popup.message.len() + 6).clamp(30, 60) as u16;
+    let height = 5;
+    let area = centered_rect_abs(width, height, f.area());
+    f.render_widget(Clear, area);
+
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            &popup.message,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            " y: confirm | n/Esc: cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let widget = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(" Confirm ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red)),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(widget, area);
+}
+
+/// Whether a task is a blueprint rather than work to do, shown only on the
+/// Templates tab
+fn is_template(task: &Task) -> bool {
+    task.status == STATUS_TEMPLATE || task.status == STATUS_RECURRING
+}
+
+fn draw_undo_popup(f: &mut Frame, app: &App, popup: &UndoPopup) {
+    let width = (f.area().width as usize * 70 / 100).clamp(40, 70) as u16;
+    let rows = (f.area().height as usize)
+        .saturating_sub(8)
+        .clamp(3, 14)
+        .min(app.changes.len());
+    let height = rows as u16 + 4;
+    let area = centered_rect_abs(width, height, f.area());
+    f.render_widget(Clear, area);
+
+    let mut lines = vec![Line::from("")];
+    // Keep the selection in view
+    let first = popup.selected.saturating_sub(rows.saturating_sub(1));
+    let text_width = (width as usize).saturating_sub(14);
+    for (i, change) in app.changes.iter().rev().enumerate().skip(first).take(rows) {
+        let undone = change.undone_by.is_some();
+        let style = if i == popup.selected {
+            Style::default().bg(Color::White).fg(Color::Black)
+        } else if undone {
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::CROSSED_OUT)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let description: String = change.description.chars().take(text_width).collect();
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<w$}", description, w = text_width), style),
+            Span::styled(
+                format!("{:>8}  ", if undone { "undone" } else { "" }),
+                style.fg(if i == popup.selected {
+                    Color::Black
+                } else {
+                    Color::Yellow
+                }),
+            ),
+        ]));
+    }
+
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Session changes (Enter: undo/redo, Esc: close) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+    f.render_widget(widget, area);
+}
+
+fn draw_deps_popup(f: &mut Frame, popup: &DepsPopup) {
+    let width = (f.area().width as usize * 70 / 100).clamp(40, 80) as u16;
+    let rows = (f.area().height as usize)
+        .saturating_sub(8)
+        .clamp(3, 20)
+        .min(popup.lines.len());
+    let height = rows as u16 + 2;
+    let area = centered_rect_abs(width, height, f.area());
+    f.render_widget(Clear, area);
+
+    // Keep the selection in view
+    let first = popup.selected.saturating_sub(rows.saturating_sub(1));
+    let inner_width = (width as usize).saturating_sub(2);
+    let lines: Vec<Line> = popup
+        .lines
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .map(|(i, line)| {
+            let style = if i == popup.selected {
+                Style::default().bg(Color::White).fg(Color::Black)
+            } else if line.uuid.is_none() && line.prefix.is_empty() {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else if line.resolved {
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::CROSSED_OUT)
+            } else if i == 0 {
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let text_width = inner_width.saturating_sub(line.prefix.width() + 1);
+            Line::from(vec![
+                Span::styled(
+                    format!(" {}", line.prefix),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(fit_cell(&line.text, text_width), style),
+            ])
+        })
+        .collect();
+
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Dependencies (Enter: go to task, Esc: close) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Green)),
+    );
+    f.render_widget(widget, area);
+}
+
+fn draw_add_input(f: &mut Frame, app: &App) {
+    let form = match &app.add_input {
+        Some(i) => i,
+        None => return,
+    };
+
+    let width = (f.area().width as usize * 70 / 100).clamp(50, 80) as u16;
+    let height = 15;
+    let area = centered_rect_abs(width, height, f.area());
+    f.render_widget(Clear, area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let label = |field: AddField, name: &str| {
+        if form.field == field {
+            Span::styled(
+                format!("  > {:<10}", name),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Span::styled(format!("    {:<10}", name), dim)
+        }
+    };
+    let text_field = |field: AddField, name: &str, text: &str| {
+        let mut spans = vec![label(field, name)];
+        if form.field == field {
+            spans.extend(input_line(text, form.cursor).spans.into_iter().skip(1));
+        } else {
+            spans.push(Span::raw(text.to_string()));
+        }
+        Line::from(spans)
+    };
+
+    let mut priorities = vec![label(AddField::Priority, "Priority")];
+    for (i, priority) in PRIORITIES.iter().enumerate() {
+        let style = if i == form.priority {
+            Style::default()
+                .fg(Color::Black)
+                .bg(priority_color(priority, &app.colors))
+        } else {
+            dim
+        };
+        priorities.push(Span::styled(format!(" {} ", priority), style));
+        priorities.push(Span::raw(" "));
+    }
+
+    let due = match form.due {
+        Some(date) => date.format("%a %-d %b %Y").to_string(),
+        None => "none".to_string(),
+    };
+    let mut due_line = vec![label(AddField::Due, "Due"), Span::raw(due)];
+    if form.field == AddField::Due {
+        due_line.push(Span::styled(
+            "   ←/→: day  t: today  o: tomorrow  w: week  x: clear",
+            dim,
+        ));
+    }
+
+    let completions = form.completions();
+    let completion_line = if completions.is_empty() {
+        Line::from("")
+    } else {
+        let mut spans = vec![Span::styled("    → ", dim)];
+        for name in completions.iter().take(6) {
+            spans.push(Span::styled(
+                format!("{}  ", name),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+        Line::from(spans)
+    };
+
+    let lines = vec![
+        text_field(AddField::Summary, "Summary", &form.summary),
+        text_field(AddField::Project, "Project", &form.project),
+        text_field(AddField::Tags, "Tags", &form.tags),
+        Line::from(priorities),
+        Line::from(due_line),
+        text_field(AddField::Notes, "Notes", &form.notes),
+        Line::from(vec![
+            label(AddField::Start, "Start"),
+            Span::styled(
+                if form.start_immediately {
+                    "[x] start immediately"
+                } else {
+                    "[ ] start immediately"
+                },
+                Style::default().fg(if form.start_immediately {
+                    Color::Green
+                } else {
+                    Color::DarkGray
+                }),
+            ),
+        ]),
+        Line::from(vec![
+            label(AddField::Log, "Log"),
+            Span::styled(
+                if form.resolve_immediately {
+                    "[x] resolve immediately"
+                } else {
+                    "[ ] resolve immediately"
+                },
+                Style::default().fg(if form.resolve_immediately {
+                    Color::Green
+                } else {
+                    Color::DarkGray
+                }),
+            ),
+        ]),
+        Line::from(""),
+        completion_line,
+        Line::from(""),
+        Line::from(Span::styled(
+            " Tab/↓: next field | →: complete | Enter: add | Esc: cancel",
+            dim,
+        )),
+    ];
+
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Add Task ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Green)),
+    );
+    f.render_widget(widget, area);
+}
+
+/// A single-line input with a block cursor
+fn input_line(text: &str, cursor: usize) -> Line<'static> {
+    let col = cursor.min(text.len());
+    let before = &text[..col];
+    let ch_len = text[col..].chars().next().map_or(0, |c| c.len_utf8());
+    let cursor_char = if ch_len > 0 {
+        &text[col..col + ch_len]
+    } else {
+        " "
+    };
+    let after = &text[col + ch_len..];
+
+    Line::from(vec![
+        Span::styled("  > ", Style::default().fg(Color::Yellow)),
+        Span::raw(before.to_string()),
+        Span::styled(
+            cursor_char.to_string(),
+            Style::default().bg(Color::White).fg(Color::Black),
+        ),
+        Span::raw(after.to_string()),
+    ])
 }
 
-fn draw_url_popup(f: &mut Frame, popup: &UrlPopup) {
-    // Size the popup based on content
-    let max_url_len = popup
-        .urls
+fn draw_modify_input(f: &mut Frame, app: &App) {
+    let input = match &app.modify_input {
+        Some(i) => i,
+        None => return,
+    };
+    let summary = app
+        .all_tasks
         .iter()
-        .map(|u| u.len())
-        .max()
-        .unwrap_or(20)
-        .min(80);
-    let width = (max_url_len + 10).min(f.area().width as usize - 4) as u16;
-    // +5 for title, footer, borders, header line, blank line
-    let height = (popup.urls.len() + 6).min(f.area().height as usize - 2) as u16;
+        .find(|t| t.uuid == input.task_uuid)
+        .map_or("", |t| t.summary.as_str());
 
+    let width = (f.area().width as usize * 70 / 100).clamp(40, 80) as u16;
+    let height = 7;
     let area = centered_rect_abs(width, height, f.area());
     f.render_widget(Clear, area);
 
-    let mut lines = vec![
+    let lines = vec![
         Line::from(Span::styled(
-            "Select URLs to open",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
+            format!("  {}", summary),
+            Style::default().fg(Color::White),
         )),
+        input_line(&input.text, input.cursor),
         Line::from(""),
+        Line::from(Span::styled(
+            " Delete a word to remove it | Tab: complete | Enter: apply | Esc: cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
     ];
 
-    for (i, url) in popup.urls.iter().enumerate() {
-        let checkbox = if popup.checked[i] { "[x] " } else { "[ ] " };
-        let is_cursor = i == popup.cursor;
+    let widget = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(" Modify Task (+tag project:X P0-P3 due:date) ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Green)),
+        )
+        .wrap(Wrap { trim: false });
+    f.render_widget(widget, area);
+    if let Some(menu) = &input.completion {
+        draw_completion_menu(f, area, menu);
+    }
+}
 
-        let style = if is_cursor {
-            Style::default()
-                .fg(Color::White)
-                .bg(Color::Indexed(236))
-                .add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(Color::Cyan)
-        };
+fn draw_due_picker(f: &mut Frame, picker: &DuePicker) {
+    let area = centered_rect_abs(36, 15, f.area());
+    f.render_widget(Clear, area);
 
-        let checkbox_style = if is_cursor {
+    let today = Local::now().date_naive();
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("  {}", picker.cursor.format("%B %Y")),
             Style::default()
                 .fg(Color::Yellow)
-                .bg(Color::Indexed(236))
-                .add_modifier(Modifier::BOLD)
-        } else if popup.checked[i] {
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(Color::DarkGray)
-        };
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            "  Mo Tu We Th Fr Sa Su",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
 
-        lines.push(Line::from(vec![
-            Span::styled(checkbox.to_string(), checkbox_style),
-            Span::styled(url.clone(), style),
-        ]));
+    for week in month_grid(picker.cursor) {
+        let mut spans = vec![Span::raw("  ")];
+        for day in week {
+            let Some(day) = day else {
+                spans.push(Span::raw("   "));
+                continue;
+            };
+            let mut style = Style::default().fg(Color::White);
+            if Some(day) == picker.current {
+                style = style.fg(Color::Yellow).add_modifier(Modifier::BOLD);
+            }
+            if day == today {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            if day == picker.cursor {
+                style = style.bg(Color::White).fg(Color::Black);
+            }
+            spans.push(Span::styled(format!("{:>2}", day.day()), style));
+            spans.push(Span::raw(" "));
+        }
+        lines.push(Line::from(spans));
     }
 
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
-        " Space: toggle | a: all | Enter: open | Esc: close",
+        "  t: today  o: tomorrow",
+        Style::default().fg(Color::DarkGray),
+    )));
+    lines.push(Line::from(Span::styled(
+        "  w: next week  x: clear",
+        Style::default().fg(Color::DarkGray),
+    )));
+    lines.push(Line::from(Span::styled(
+        "  </>: month  Enter: set  Esc",
         Style::default().fg(Color::DarkGray),
     )));
 
-    let popup_widget = Paragraph::new(lines)
-        .block(
-            Block::default()
-                .title(" Open URLs ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow)),
-        )
-        .wrap(Wrap { trim: false });
-    f.render_widget(popup_widget, area);
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Due Date ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Green)),
+    );
+    f.render_widget(widget, area);
 }
 
-fn draw_confirm_popup(f: &mut Frame, app: &App) {
-    let popup = match &app.confirm_popup {
-        Some(p) => p,
-        None => return,
-    };
+fn draw_palette(f: &mut Frame, palette: &CommandPalette) {
+    let matches = palette.matches();
+    let width = (f.area().width as usize * 70 / 100).clamp(40, 70) as u16;
+    let rows = (f.area().height as usize).saturating_sub(8).clamp(3, 14);
+    let height = rows as u16 + 5;
+    let area = centered_rect_abs(width, height, f.area());
+    f.render_widget(Clear, area);
 
-    let width = (popup.message.len() + 6).clamp(30, 60) as u16;
-    let height = 5;
+    let mut lines = vec![input_line(&palette.text, palette.cursor), Line::from("")];
+    if matches.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No matching commands",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    // Keep the selection in view
+    let first = palette.selected.saturating_sub(rows - 1);
+    let text_width = (width as usize).saturating_sub(14);
+    for (i, entry) in matches.iter().enumerate().skip(first).take(rows) {
+        let style = if i == palette.selected {
+            Style::default().bg(Color::White).fg(Color::Black)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {:<w$}", entry.description, w = text_width),
+                style,
+            ),
+            Span::styled(
+                format!("{:>8}  ", entry.key),
+                style.fg(if i == palette.selected {
+                    Color::Black
+                } else {
+                    Color::Yellow
+                }),
+            ),
+        ]));
+    }
+
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Commands (Enter: run, Esc: close) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Green)),
+    );
+    f.render_widget(widget, area);
+}
+
+fn draw_finder(f: &mut Frame, finder: &FuzzyFinder) {
+    let matches = finder.matches();
+    let width = (f.area().width as usize * 70 / 100).clamp(40, 90) as u16;
+    let rows = (f.area().height as usize).saturating_sub(8).clamp(3, 14);
+    let height = rows as u16 + 5;
     let area = centered_rect_abs(width, height, f.area());
     f.render_widget(Clear, area);
 
+    let mut lines = vec![input_line(&finder.text, finder.cursor), Line::from("")];
+    if matches.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  Nothing matches",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    // Keep the selection in view
+    let first = finder.selected.saturating_sub(rows - 1);
+    let text_width = (width as usize).saturating_sub(14);
+    for (i, entry) in matches.iter().enumerate().skip(first).take(rows) {
+        let style = if i == finder.selected {
+            Style::default().bg(Color::White).fg(Color::Black)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let kind = match entry.target {
+            FindTarget::Task(_) => "task",
+            FindTarget::Project(_) => "project",
+            FindTarget::Tag(_) => "tag",
+        };
+        let label: String = entry.label.chars().take(text_width).collect();
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<w$}", label, w = text_width), style),
+            Span::styled(
+                format!("{:>8}  ", kind),
+                style.fg(if i == finder.selected {
+                    Color::Black
+                } else {
+                    Color::Cyan
+                }),
+            ),
+        ]));
+    }
+
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Find (Enter: go, Esc: close) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Green)),
+    );
+    f.render_widget(widget, area);
+}
+
+/// Smallest terminal the TUI lays itself out in
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 10;
+
+/// Shown instead of the TUI while the terminal is below the minimum size
+fn draw_too_small(f: &mut Frame) {
+    let area = f.area();
     let lines = vec![
-        Line::from(""),
         Line::from(Span::styled(
-            &popup.message,
+            "Terminal too small",
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
         )),
-        Line::from(""),
+        Line::from(format!("{}x{}", area.width, area.height)),
         Line::from(Span::styled(
-            " y: confirm | n/Esc: cancel",
+            format!("needs {}x{}", MIN_WIDTH, MIN_HEIGHT),
             Style::default().fg(Color::DarkGray),
         )),
     ];
-
-    let widget = Paragraph::new(lines)
-        .block(
-            Block::default()
-                .title(" Confirm ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Red)),
-        )
-        .wrap(Wrap { trim: false });
-    f.render_widget(widget, area);
+    let top = area.height.saturating_sub(lines.len() as u16) / 2;
+    let message = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(
+        message,
+        Rect {
+            y: area.y + top,
+            height: area.height - top,
+            ..area
+        },
+    );
 }
 
-fn draw_add_input(f: &mut Frame, app: &App) {
-    let input = match &app.add_input {
-        Some(i) => i,
-        None => return,
+/// Narrowest terminal the split view shows on
+const SPLIT_MIN_WIDTH: u16 = 100;
+
+/// Rows of the activity pane, borders included
+const ACTIVITY_HEIGHT: u16 = 8;
+/// Most commits the activity pane lists
+const ACTIVITY_LIMIT: usize = 100;
+
+/// The commits made this session, newest first, marking those not pushed yet
+fn draw_activity(f: &mut Frame, app: &App, area: Rect) {
+    let log = app.activity.as_deref().unwrap_or_default();
+    let unpushed = app.ahead_behind.map(|(ahead, _)| ahead);
+    let title = match unpushed {
+        Some(n) => format!(" Activity: {} commits, {} not pushed ", log.len(), n),
+        None => format!(" Activity: {} commits ", log.len()),
     };
 
-    let width = (f.area().width as usize * 70 / 100).clamp(40, 80) as u16;
-    let height = 7;
-    let area = centered_rect_abs(width, height, f.area());
-    f.render_widget(Clear, area);
-
-    let resolve_indicator = if input.resolve_immediately {
-        "[x] Log (resolve immediately)"
+    let lines: Vec<Line> = if log.is_empty() {
+        vec![Line::from(Span::styled(
+            "  No commits yet this session",
+            Style::default().fg(Color::DarkGray),
+        ))]
     } else {
-        "[ ] Log (resolve immediately)"
+        log.iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let pending = unpushed.is_some_and(|n| i < n);
+                let (hash, rest) = entry.split_once(' ').unwrap_or((entry, ""));
+                Line::from(vec![
+                    Span::styled(
+                        if pending { "  * " } else { "    " },
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::styled(format!("{} ", hash), Style::default().fg(Color::DarkGray)),
+                    Span::raw(rest.to_string()),
+                ])
+            })
+            .collect()
     };
 
-    let col = input.cursor.min(input.text.len());
-    let before = &input.text[..col];
-    let cursor_char = if col < input.text.len() {
-        let ch_len = input.text[col..]
-            .chars()
-            .next()
-            .map(|c| c.len_utf8())
-            .unwrap_or(1);
-        &input.text[col..col + ch_len]
-    } else {
-        " "
-    };
-    let after = if col < input.text.len() {
-        let ch_len = input.text[col..]
-            .chars()
-            .next()
-            .map(|c| c.len_utf8())
-            .unwrap_or(0);
-        &input.text[col + ch_len..]
-    } else {
-        ""
-    };
+    let pane = Paragraph::new(lines).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(app.colors.border)),
+    );
+    f.render_widget(pane, area);
+}
+
+fn draw_delegate_input(f: &mut Frame, input: &DelegateInput, known: &[&str]) {
+    let width = 50u16.min(f.area().width.saturating_sub(4));
+    let area = centered_rect_abs(width, 6, f.area());
+    f.render_widget(Clear, area);
 
+    let (before, after) = input.text.split_at(input.cursor);
+    let mut chars = after.chars();
+    let cursor_char = chars.next().map_or(" ".to_string(), String::from);
+    let dim = Style::default().fg(Color::DarkGray);
     let lines = vec![
         Line::from(vec![
-            Span::styled("  > ", Style::default().fg(Color::Yellow)),
+            Span::styled(" @", Style::default().fg(Color::Yellow)),
             Span::raw(before.to_string()),
             Span::styled(
-                cursor_char.to_string(),
+                cursor_char,
                 Style::default().bg(Color::White).fg(Color::Black),
             ),
-            Span::raw(after.to_string()),
+            Span::raw(chars.as_str().to_string()),
         ]),
-        Line::from(""),
         Line::from(Span::styled(
-            format!("  {}", resolve_indicator),
-            Style::default().fg(if input.resolve_immediately {
-                Color::Green
+            if known.is_empty() {
+                String::new()
             } else {
-                Color::DarkGray
-            }),
+                format!(" Known: {}", known.join(", "))
+            },
+            dim,
         )),
         Line::from(""),
         Line::from(Span::styled(
-            " Enter: add | Tab: toggle log | Esc: cancel",
-            Style::default().fg(Color::DarkGray),
+            " Enter: set | empty: clear | Esc: cancel",
+            dim,
         )),
     ];
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Delegate to ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+    f.render_widget(popup, area);
+}
 
-    let widget = Paragraph::new(lines)
-        .block(
-            Block::default()
-                .title(" Add Task (+tag project:X P0-P3 due:date) ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Green)),
-        )
-        .wrap(Wrap { trim: false });
-    f.render_widget(widget, area);
+fn draw_jump_prompt(f: &mut Frame, text: &str) {
+    let width = 30u16.min(f.area().width.saturating_sub(4));
+    let area = centered_rect_abs(width, 3, f.area());
+    f.render_widget(Clear, area);
+
+    let line = Line::from(vec![
+        Span::styled(" # ", Style::default().fg(Color::Yellow)),
+        Span::raw(text.to_string()),
+        Span::styled(" ", Style::default().bg(Color::White).fg(Color::Black)),
+    ]);
+    let prompt = Paragraph::new(line).block(
+        Block::default()
+            .title(" Go to task ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)),
+    );
+    f.render_widget(prompt, area);
 }
 
 fn draw_context_popup(f: &mut Frame, app: &App) {
@@ -2248,7 +6914,7 @@ fn draw_context_popup(f: &mut Frame, app: &App) {
         current
     };
 
-    let width = 60u16.min(f.area().width - 4);
+    let width = 60u16.min(f.area().width.saturating_sub(4));
     let height = 8;
     let area = centered_rect_abs(width, height, f.area());
     f.render_widget(Clear, area);
@@ -2293,7 +6959,7 @@ fn draw_context_popup(f: &mut Frame, app: &App) {
         ]),
         Line::from(""),
         Line::from(Span::styled(
-            " Enter: set | empty/none: clear | Esc: cancel",
+            " Tab: complete | Enter: set | empty/none: clear | Esc: cancel",
             Style::default().fg(Color::DarkGray),
         )),
     ];
@@ -2307,6 +6973,59 @@ fn draw_context_popup(f: &mut Frame, app: &App) {
         )
         .wrap(Wrap { trim: false });
     f.render_widget(widget, area);
+    if let Some(menu) = &popup.completion {
+        draw_completion_menu(f, area, menu);
+    }
+}
+
+/// Draws the completion candidates just below the popup they belong to,
+/// or above it if there's no room
+fn draw_completion_menu(f: &mut Frame, popup: Rect, menu: &CompletionMenu) {
+    let screen = f.area();
+    let rows = menu.candidates.len().min(8);
+    let longest = menu
+        .candidates
+        .iter()
+        .map(|c| c.chars().count())
+        .max()
+        .unwrap_or(0);
+    let width = (longest as u16 + 6).clamp(16, popup.width.saturating_sub(4));
+    let height = rows as u16 + 2;
+    let y = if popup.bottom() + height <= screen.bottom() {
+        popup.bottom()
+    } else {
+        popup.y.saturating_sub(height)
+    };
+    let area = Rect::new(popup.x + 4, y, width, height).intersection(screen);
+    f.render_widget(Clear, area);
+
+    // Keep the selection in view
+    let first = menu.selected.saturating_sub(rows - 1);
+    let lines: Vec<Line> = menu
+        .candidates
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows)
+        .map(|(i, name)| {
+            let style = if i == menu.selected {
+                Style::default().bg(Color::White).fg(Color::Black)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::from(Span::styled(
+                format!(" {:<w$} ", name, w = (width as usize).saturating_sub(4)),
+                style,
+            ))
+        })
+        .collect();
+
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray)),
+    );
+    f.render_widget(widget, area);
 }
 
 fn draw_help_popup(f: &mut Frame) {
@@ -2328,6 +7047,31 @@ fn draw_help_popup(f: &mut Frame) {
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         )]),
+        Line::from(vec![
+            Span::styled("    Ctrl+P    ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Command palette: search and run any action",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    Ctrl+F    ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Find a task, project or tag by fuzzy search",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    @         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Delegate the task to someone, or clear it",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    #         ", Style::default().fg(Color::White)),
+            Span::styled("Go to a task by ID", Style::default().fg(Color::DarkGray)),
+        ]),
         Line::from(vec![
             Span::styled("    j/Down    ", Style::default().fg(Color::White)),
             Span::styled("Move down", Style::default().fg(Color::DarkGray)),
@@ -2378,6 +7122,41 @@ fn draw_help_popup(f: &mut Frame) {
             Span::styled("    Shift+Tab ", Style::default().fg(Color::White)),
             Span::styled("Previous status tab", Style::default().fg(Color::DarkGray)),
         ]),
+        Line::from(vec![
+            Span::styled("    Enter     ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Add a task from the template (Templates tab)",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    b         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Show/hide project sidebar (Enter filters by project)",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    v         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Show/hide the detail beside the list (PgUp/PgDn scroll it)",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    L         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Show/hide the commits made this session",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    h/l       ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Move between sidebar and task list",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "  Actions",
@@ -2388,7 +7167,7 @@ fn draw_help_popup(f: &mut Frame) {
         Line::from(vec![
             Span::styled("    a         ", Style::default().fg(Color::White)),
             Span::styled(
-                "Add new task (Tab to toggle log mode)",
+                "Add a task in a form (Tab moves between fields)",
                 Style::default().fg(Color::DarkGray),
             ),
         ]),
@@ -2417,6 +7196,20 @@ fn draw_help_popup(f: &mut Frame) {
                 Style::default().fg(Color::DarkGray),
             ),
         ]),
+        Line::from(vec![
+            Span::styled("    m         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Modify tags, project, priority and due date",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    t         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Pick a due date from a calendar",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
         Line::from(vec![
             Span::styled("    x         ", Style::default().fg(Color::White)),
             Span::styled(
@@ -2424,10 +7217,31 @@ fn draw_help_popup(f: &mut Frame) {
                 Style::default().fg(Color::DarkGray),
             ),
         ]),
+        Line::from(vec![
+            Span::styled("    M         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Convert task to template",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    z         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Start or stop a pomodoro timer",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    w         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Stats for a weekly review",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
         Line::from(vec![
             Span::styled("    u         ", Style::default().fg(Color::White)),
             Span::styled(
-                "Undo last commit (with confirmation)",
+                "Undo or redo changes from this session",
                 Style::default().fg(Color::DarkGray),
             ),
         ]),
@@ -2435,6 +7249,27 @@ fn draw_help_popup(f: &mut Frame) {
             Span::styled("    E         ", Style::default().fg(Color::White)),
             Span::styled("Edit task in $EDITOR", Style::default().fg(Color::DarkGray)),
         ]),
+        Line::from(vec![
+            Span::styled("    D         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Show the dependency tree",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    y         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Copy summary to clipboard",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    Y         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Copy task with notes as markdown",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
         Line::from(vec![
             Span::styled("    C         ", Style::default().fg(Color::White)),
             Span::styled(
@@ -2442,6 +7277,13 @@ fn draw_help_popup(f: &mut Frame) {
                 Style::default().fg(Color::DarkGray),
             ),
         ]),
+        Line::from(vec![
+            Span::styled("    T         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Cycle theme (default, high contrast, monochrome)",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
         Line::from(vec![
             Span::styled("    r         ", Style::default().fg(Color::White)),
             Span::styled(
@@ -2471,9 +7313,76 @@ fn draw_help_popup(f: &mut Frame) {
             Span::styled("    E         ", Style::default().fg(Color::White)),
             Span::styled("Edit task in $EDITOR", Style::default().fg(Color::DarkGray)),
         ]),
+        Line::from(vec![
+            Span::styled("    m         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Modify tags, project, priority and due date",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    t         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Pick a due date from a calendar",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
         Line::from(vec![
             Span::styled("    o         ", Style::default().fg(Color::White)),
-            Span::styled("Open URLs in browser", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                "Open URLs in browser; then 1-9 opens that link",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    f         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Show the task file and open it",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    F         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Edit the task file in $EDITOR",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    D         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Show the dependency tree",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    y         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Copy summary to clipboard",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    Y         ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Copy task with notes as markdown",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    j/k       ", Style::default().fg(Color::White)),
+            Span::styled("Scroll notes", Style::default().fg(Color::DarkGray)),
+        ]),
+        Line::from(vec![
+            Span::styled("    PgUp/PgDn ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Scroll notes by a page",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("    g/G       ", Style::default().fg(Color::White)),
+            Span::styled("Top/bottom of notes", Style::default().fg(Color::DarkGray)),
         ]),
         Line::from(vec![
             Span::styled("    Esc/q     ", Style::default().fg(Color::White)),
@@ -2494,6 +7403,21 @@ fn draw_help_popup(f: &mut Frame) {
             Span::styled("    Esc       ", Style::default().fg(Color::White)),
             Span::styled("Cancel editing", Style::default().fg(Color::DarkGray)),
         ]),
+        Line::from(vec![
+            Span::styled("    Ctrl+Z/Y  ", Style::default().fg(Color::White)),
+            Span::styled("Undo/redo changes", Style::default().fg(Color::DarkGray)),
+        ]),
+        Line::from(vec![
+            Span::styled("    Ctrl+←/→  ", Style::default().fg(Color::White)),
+            Span::styled("Move by word", Style::default().fg(Color::DarkGray)),
+        ]),
+        Line::from(vec![
+            Span::styled("    Home/End  ", Style::default().fg(Color::White)),
+            Span::styled(
+                "Start/end of the row as shown",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
         Line::from(""),
         Line::from(Span::styled(
             "  Press any key to close",
@@ -2543,15 +7467,24 @@ fn centered_rect_abs(width: u16, height: u16, r: Rect) -> Rect {
 }
 
 /// Entry point for the TUI
-pub fn run_tui(conf: Config) -> Result<(), Box<dyn std::error::Error>> {
+pub fn run_tui(conf: Config, dashboard: bool) -> Result<(), Box<dyn std::error::Error>> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new(conf)?;
+    let mut app = App::new(conf, dashboard)?;
+
+    #[cfg(unix)]
+    let control = match ControlSocket::bind(&app.conf.tui_socket()) {
+        Ok(socket) => Some(socket),
+        Err(e) => {
+            app.set_status(&format!("Control socket unavailable: {}", e), true);
+            None
+        }
+    };
 
     // Main loop
     loop {
@@ -2561,7 +7494,11 @@ pub fn run_tui(conf: Config) -> Result<(), Box<dyn std::error::Error>> {
         if app.editor_request.is_some() {
             // Leave alternate screen and disable raw mode
             disable_raw_mode()?;
-            execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+            execute!(
+                terminal.backend_mut(),
+                DisableBracketedPaste,
+                LeaveAlternateScreen
+            )?;
             terminal.show_cursor()?;
 
             // Run the editor
@@ -2571,25 +7508,785 @@ pub fn run_tui(conf: Config) -> Result<(), Box<dyn std::error::Error>> {
 
             // Re-enter alternate screen and enable raw mode
             enable_raw_mode()?;
-            execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+            execute!(
+                terminal.backend_mut(),
+                EnterAlternateScreen,
+                EnableBracketedPaste
+            )?;
             terminal.clear()?;
             continue;
         }
 
-        if event::poll(std::time::Duration::from_millis(100))? {
+        if event::poll(Duration::from_millis(100))? {
             let ev = event::read()?;
+            // Lay out again at the new size before the next frame is drawn
+            if let Event::Resize(..) = ev {
+                terminal.autoresize()?;
+            }
             app.handle_event(ev);
         }
 
+        #[cfg(unix)]
+        if let Some(control) = &control {
+            control.poll(|cmd| app.handle_control(cmd));
+        }
+
+        let now = Instant::now();
+        app.poll_sync();
+        // A sync reloads when it finishes
+        if app.sync_job.is_none() && app.watcher.poll(now) {
+            app.reload_external();
+        }
+        app.dashboard_tick(now);
+        app.pomodoro_tick(now);
+        app.expire_status(now);
+
         if app.should_quit {
+            if app.sync_job.is_some() {
+                app.set_status("Waiting for the sync to finish...", false);
+                terminal.draw(|f| ui(f, &mut app))?;
+                app.wait_for_sync();
+            }
             break;
         }
     }
 
     // Restore terminal
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        DisableBracketedPaste,
+        LeaveAlternateScreen
+    )?;
     terminal.show_cursor()?;
 
+    app.conf.events.finish(FINISH_LIMIT);
+    auto_sync_if_enabled(&app.conf, SyncTrigger::TuiExit)?;
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn modify(task: &Task, text: &str) -> Result<(Task, bool), String> {
+        let mut input = ModifyInput::new(task);
+        input.text = text.to_string();
+        let (query, clear_due) = input.to_modification()?;
+        let mut modified = task.clone();
+        modified.modify(&query);
+        if clear_due {
+            modified.due = None;
+        }
+        Ok((modified, clear_due))
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("", "Start task").is_some());
+        assert!(fuzzy_score("STRT", "Start task").is_some());
+        assert!(fuzzy_score("sttk", "Start task").is_some());
+        assert!(fuzzy_score("xyz", "Start task").is_none());
+        assert!(fuzzy_score("st", "Start task") > fuzzy_score("st", "Pause task"));
+    }
+
+    #[test]
+    fn test_status_tabs_cycle_through_templates() {
+        let mut tab = StatusTab::All;
+        let mut seen = Vec::new();
+        for _ in 0..7 {
+            seen.push(tab);
+            assert_eq!(tab.next().prev(), tab);
+            tab = tab.next();
+        }
+        assert_eq!(tab, StatusTab::All);
+        assert_eq!(seen[4], StatusTab::Delegated);
+        assert_eq!(seen[6], StatusTab::Templates);
+    }
+
+    #[test]
+    fn test_week_headings() {
+        let at = |day: u32| {
+            Local
+                .with_ymd_and_hms(2025, 10, day, 12, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        // Sunday 19th, Thursday 16th and Monday 13th share a week
+        let resolved = [Some(at(19)), Some(at(16)), Some(at(13)), Some(at(10)), None];
+        assert_eq!(
+            week_headings(&resolved),
+            vec![
+                (0, "Week 42, starting Mon 13 Oct 2025".to_string()),
+                (3, "Week 41, starting Mon 6 Oct 2025".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_session_change_undo_and_redo() {
+        let mut change = SessionChange {
+            description: "Added: Pay rent".to_string(),
+            applied: "a1".to_string(),
+            undone_by: None,
+        };
+        assert_eq!(change.next_revert(), ("a1", "Undo"));
+
+        change.reverted("u1".to_string());
+        assert_eq!(change.next_revert(), ("u1", "Redo"));
+
+        // Redoing reverts the undo, and undoing again reverts the redo
+        change.reverted("r1".to_string());
+        assert_eq!(change.applied, "r1");
+        assert_eq!(change.undone_by, None);
+        assert_eq!(change.next_revert(), ("r1", "Undo"));
+    }
+
+    #[test]
+    fn test_palette_matches() {
+        let mut palette = CommandPalette::new();
+        assert_eq!(palette.matches().len(), PALETTE_ENTRIES.len());
+
+        palette.text = "sync".to_string();
+        assert_eq!(palette.matches()[0].action, PaletteAction::Sync);
+
+        palette.text = "clr ctx".to_string();
+        assert_eq!(palette.matches()[0].action, PaletteAction::ClearContext);
+    }
+
+    #[test]
+    fn test_add_form_defaults_and_completion() {
+        let ctx = Query {
+            project: "home".to_string(),
+            tags: vec!["chores".to_string()],
+            ..Default::default()
+        };
+        let mut form = AddTaskForm::new(
+            &ctx,
+            vec![
+                "home".to_string(),
+                "homework".to_string(),
+                "work".to_string(),
+            ],
+            vec![
+                "bills".to_string(),
+                "birthday".to_string(),
+                "chores".to_string(),
+            ],
+        );
+        assert_eq!(form.project, "home");
+        assert_eq!(form.tag_list(), vec!["chores"]);
+        assert_eq!(PRIORITIES[form.priority], PRIORITY_NORMAL);
+
+        // Tab order wraps around
+        form.focus(-1);
+        assert_eq!(form.field, AddField::Log);
+        form.focus(2);
+        assert_eq!(form.field, AddField::Project);
+        assert_eq!(form.cursor, 4);
+
+        // "home" is a project already but could also become "homework"
+        assert_eq!(form.completions(), vec!["homework"]);
+        form.project = "w".to_string();
+        assert!(form.complete());
+        assert_eq!(form.project, "work");
+
+        form.focus(1);
+        form.tags = "chores +bi".to_string();
+        assert_eq!(form.completions(), vec!["bills", "birthday"]);
+        assert!(!form.complete());
+        form.tags.push('l');
+        assert!(form.complete());
+        assert_eq!(form.tags, "chores +bills");
+        assert_eq!(form.tag_list(), vec!["chores", "bills"]);
+    }
+
+    #[test]
+    fn test_completion_menu() {
+        let tags = vec![
+            "bills".to_string(),
+            "birthday".to_string(),
+            "work".to_string(),
+        ];
+        let projects = vec!["home".to_string(), "website".to_string()];
+        let tab = KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE);
+        let down = KeyEvent::new(KeyCode::Down, KeyModifiers::NONE);
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+
+        // Only tags and projects complete
+        assert!(CompletionMenu::new("call mum", 8, &tags, &projects).is_none());
+        assert!(CompletionMenu::new("+x", 2, &tags, &projects).is_none());
+
+        // A single fit is filled in without a menu
+        let mut menu = None;
+        let mut text = "P1 project:we".to_string();
+        let mut cursor = text.len();
+        assert!(handle_completion(
+            &mut menu,
+            &mut text,
+            &mut cursor,
+            tab,
+            &tags,
+            &projects
+        ));
+        assert!(menu.is_none());
+        assert_eq!(text, "P1 project:website ");
+        assert_eq!(cursor, text.len());
+
+        // Several open the menu, mid-text too
+        let mut text = "-bi P2".to_string();
+        let mut cursor = 3;
+        assert!(handle_completion(
+            &mut menu,
+            &mut text,
+            &mut cursor,
+            tab,
+            &tags,
+            &projects
+        ));
+        assert_eq!(menu.as_ref().unwrap().candidates, vec!["bills", "birthday"]);
+        assert!(handle_completion(
+            &mut menu,
+            &mut text,
+            &mut cursor,
+            down,
+            &tags,
+            &projects
+        ));
+        assert!(handle_completion(
+            &mut menu,
+            &mut text,
+            &mut cursor,
+            enter,
+            &tags,
+            &projects
+        ));
+        assert!(menu.is_none());
+        assert_eq!(text, "-birthday P2");
+        assert_eq!(cursor, 10);
+
+        // Other keys go to the input
+        let key = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE);
+        assert!(!handle_completion(
+            &mut menu,
+            &mut text,
+            &mut cursor,
+            key,
+            &tags,
+            &projects
+        ));
+    }
+
+    #[test]
+    fn test_status_lifetime() {
+        let mut prefs = Preferences::default();
+        assert_eq!(status_lifetime(&prefs, false), Some(Duration::from_secs(5)));
+        assert_eq!(status_lifetime(&prefs, true), Some(Duration::from_secs(15)));
+        prefs.tui_error_seconds = 0;
+        assert_eq!(status_lifetime(&prefs, true), None);
+    }
+
+    #[test]
+    fn test_theme_colors_override_builtin_ones() {
+        let (colors, invalid) = ThemeColors::new(&TuiColors {
+            selection: Some("#d0d0d0".to_string()),
+            p0: Some("light_red".to_string()),
+            active: Some("42".to_string()),
+            border: Some("plaid".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(colors.selection, Color::Rgb(0xd0, 0xd0, 0xd0));
+        assert_eq!(priority_color(PRIORITY_CRITICAL, &colors), Color::LightRed);
+        assert_eq!(priority_color(PRIORITY_HIGH, &colors), Color::Yellow);
+        assert_eq!(status_color(STATUS_ACTIVE, &colors), Color::Indexed(42));
+        assert_eq!(colors.border, Color::Reset);
+        assert_eq!(invalid, vec!["border \"plaid\""]);
+    }
+
+    #[test]
+    fn test_finder_matches_summaries_projects_and_tags() {
+        let tasks = vec![
+            Task {
+                uuid: "a".to_string(),
+                summary: "Renew passport".to_string(),
+                project: "travel".to_string(),
+                tags: vec!["admin".to_string()],
+                ..Default::default()
+            },
+            Task {
+                uuid: "b".to_string(),
+                summary: "Book flights".to_string(),
+                project: "travel".to_string(),
+                ..Default::default()
+            },
+        ];
+        let mut finder = FuzzyFinder::new(&tasks);
+        // Two tasks, one project and one tag
+        assert_eq!(finder.matches().len(), 4);
+
+        finder.text = "rnwpsp".to_string();
+        assert_eq!(
+            finder.matches()[0].target,
+            FindTarget::Task("a".to_string())
+        );
+
+        finder.text = "bk flt".to_string();
+        assert_eq!(
+            finder.matches()[0].target,
+            FindTarget::Task("b".to_string())
+        );
+
+        // The tag matches on its own and as part of the task tagged with it
+        finder.text = "+admn".to_string();
+        let targets: Vec<&FindTarget> = finder.matches().iter().map(|e| &e.target).collect();
+        assert_eq!(targets.len(), 2);
+        assert!(targets.contains(&&FindTarget::Tag("admin".to_string())));
+
+        finder.text = "zzz".to_string();
+        assert!(finder.matches().is_empty());
+    }
+
+    #[test]
+    fn test_dashboard_cycles_and_syncs() {
+        let prefs = Preferences {
+            dashboard_views: vec!["+team".to_string(), "P0".to_string()],
+            dashboard_cycle_seconds: 10,
+            dashboard_sync_minutes: 1,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let mut dashboard = Dashboard::new(&prefs, start);
+        assert_eq!(dashboard.query(), "+team");
+        assert_eq!(
+            dashboard.due(start + Duration::from_secs(5)),
+            (false, false)
+        );
+        assert_eq!(
+            dashboard.due(start + Duration::from_secs(10)),
+            (true, false)
+        );
+        assert_eq!(dashboard.due(start + Duration::from_secs(60)), (true, true));
+
+        dashboard.advance(1, start);
+        assert_eq!(dashboard.query(), "P0");
+        dashboard.advance(1, start);
+        assert_eq!(dashboard.query(), "+team");
+        dashboard.advance(-1, start);
+        assert_eq!(dashboard.query(), "P0");
+
+        // A single view never cycles
+        let single = Dashboard::new(&Preferences::default(), start);
+        assert_eq!(single.query(), "");
+        assert!(!single.due(start + Duration::from_secs(3600)).0);
+    }
+
+    #[test]
+    fn test_scroll_offset() {
+        assert_eq!(scroll_offset(0, KeyCode::Char('j'), 10), Some(1));
+        assert_eq!(scroll_offset(0, KeyCode::Char('k'), 10), Some(0));
+        assert_eq!(scroll_offset(3, KeyCode::PageDown, 10), Some(13));
+        assert_eq!(scroll_offset(3, KeyCode::PageUp, 10), Some(0));
+        assert_eq!(scroll_offset(7, KeyCode::Char('g'), 10), Some(0));
+        assert_eq!(scroll_offset(7, KeyCode::Char('G'), 10), Some(usize::MAX));
+        assert_eq!(
+            scroll_offset(usize::MAX, KeyCode::Down, 10),
+            Some(usize::MAX)
+        );
+        // Before the first draw the page size isn't known yet
+        assert_eq!(scroll_offset(0, KeyCode::PageDown, 0), Some(1));
+        assert_eq!(scroll_offset(0, KeyCode::Char('e'), 10), None);
+    }
+
+    #[test]
+    fn test_month_grid() {
+        // July 2025 starts on a Tuesday and spans five weeks
+        let grid = month_grid(NaiveDate::from_ymd_opt(2025, 7, 17).unwrap());
+        assert_eq!(grid.len(), 5);
+        assert_eq!(grid[0][0], None);
+        assert_eq!(grid[0][1], NaiveDate::from_ymd_opt(2025, 7, 1));
+        assert_eq!(grid[4][3], NaiveDate::from_ymd_opt(2025, 7, 31));
+        assert_eq!(grid[4][4], None);
+
+        // February 2021 fills exactly four weeks
+        let grid = month_grid(NaiveDate::from_ymd_opt(2021, 2, 1).unwrap());
+        assert_eq!(grid.len(), 4);
+        assert!(grid.iter().flatten().all(Option::is_some));
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(0), "0:00");
+        assert_eq!(format_elapsed(25 * 60), "25:00");
+        assert_eq!(format_elapsed(3723), "1:02:03");
+        assert_eq!(format_elapsed(-5), "0:00");
+    }
+
+    #[test]
+    fn test_wrap_points() {
+        assert_eq!(wrap_points("hello world foo", 6), vec![0, 6, 12]);
+        assert_eq!(wrap_points("abcdefgh", 3), vec![0, 3, 6]);
+        assert_eq!(wrap_points("short", 10), vec![0]);
+        assert_eq!(wrap_points("no wrapping at all", 0), vec![0]);
+        assert_eq!(wrap_points("", 4), vec![0]);
+    }
+
+    #[test]
+    fn test_note_editor_moves_by_visual_row_and_word() {
+        let mut editor = NoteEditor::new("hello world foo\nab", "uuid");
+        editor.wrap_width = 6;
+        editor.cursor_col = 2;
+
+        editor.move_vertical(true);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 8));
+        editor.move_vertical(true);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 14));
+        editor.move_vertical(true);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (1, 2));
+        editor.move_vertical(false);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 14));
+
+        editor.move_to_row_edge(false);
+        assert_eq!(editor.cursor_col, 12);
+        editor.move_to_row_edge(true);
+        assert_eq!(editor.cursor_col, 15);
+        editor.cursor_col = 3;
+        editor.move_to_row_edge(true);
+        assert_eq!(editor.cursor_col, 5);
+
+        editor.cursor_col = 0;
+        editor.move_word(true);
+        assert_eq!(editor.cursor_col, 6);
+        editor.move_word(true);
+        editor.move_word(true);
+        assert_eq!(editor.cursor_col, 15);
+        editor.move_word(true);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (1, 0));
+        editor.move_word(false);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 15));
+        editor.move_word(false);
+        assert_eq!(editor.cursor_col, 12);
+    }
+
+    #[test]
+    fn test_note_editor_undo_and_redo() {
+        let mut editor = NoteEditor::new("note", "uuid");
+        assert!(!editor.undo());
+
+        // Typing, even across lines, is one step
+        editor.cursor_col = 4;
+        for c in " and\nmore".chars() {
+            editor.record(EditKind::Insert);
+            if c == '\n' {
+                editor.cursor_row += 1;
+                editor.lines.insert(editor.cursor_row, String::new());
+                editor.cursor_col = 0;
+            } else {
+                editor.lines[editor.cursor_row].insert(editor.cursor_col, c);
+                editor.cursor_col += c.len_utf8();
+            }
+        }
+        assert_eq!(editor.to_string(), "note and\nmore");
+
+        editor.record(EditKind::Delete);
+        editor.lines[1].pop();
+        editor.cursor_col -= 1;
+        assert_eq!(editor.to_string(), "note and\nmor");
+
+        assert!(editor.undo());
+        assert_eq!(editor.to_string(), "note and\nmore");
+        assert!(editor.undo());
+        assert_eq!(editor.to_string(), "note");
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 4));
+        assert!(!editor.undo());
+
+        assert!(editor.redo());
+        assert_eq!(editor.to_string(), "note and\nmore");
+
+        // A new change drops what could be redone
+        editor.record(EditKind::Insert);
+        assert!(!editor.redo());
+    }
+
+    #[test]
+    fn test_paste() {
+        let mut text = String::from("+a ");
+        let mut cursor = 1;
+        paste_line(&mut text, &mut cursor, "one\r\n  two\n\n");
+        assert_eq!(text, "+one twoa ");
+        assert_eq!(cursor, 8);
+
+        let mut editor = NoteEditor::new("start end", "uuid");
+        editor.cursor_col = 6;
+        editor.paste("one\r\ntwo\tthree\n");
+        assert_eq!(editor.to_string(), "start one\ntwo    three\nend");
+        assert_eq!((editor.cursor_row, editor.cursor_col), (2, 0));
+        assert!(editor.undo());
+        assert_eq!(editor.to_string(), "start end");
+    }
+
+    #[test]
+    fn test_number_links() {
+        let mut urls = Vec::new();
+        assert_eq!(
+            number_links("See https://a.example/x", &mut urls),
+            "See https://a.example/x [1]"
+        );
+        assert_eq!(
+            number_links(
+                "[docs](https://b.example/) and <https://a.example/x>, again https://b.example/",
+                &mut urls
+            ),
+            "[docs](https://b.example/) [2] and <https://a.example/x> [1], again https://b.example/ [2]"
+        );
+        assert_eq!(urls, vec!["https://a.example/x", "https://b.example/"]);
+        assert_eq!(number_links("no links", &mut urls), "no links");
+    }
+
+    #[test]
+    fn test_highlight_matches() {
+        assert_eq!(
+            match_ranges("Fix the Login page; login again", "login"),
+            vec![8..13, 20..25]
+        );
+        assert_eq!(match_ranges("ÉTÉ été", "été"), vec![0..5, 6..11]);
+        assert!(match_ranges("anything", "").is_empty());
+
+        let plain = Style::default();
+        let matched = Style::default().bg(Color::Yellow);
+        let spans = highlight_matches("a login b", "LOGIN", plain, matched);
+        let parts: Vec<_> = spans
+            .iter()
+            .map(|s| (s.content.as_ref(), s.style == matched))
+            .collect();
+        assert_eq!(parts, vec![("a ", false), ("login", true), (" b", false)]);
+        assert_eq!(highlight_matches("", "x", plain, matched).len(), 1);
+    }
+
+    #[test]
+    fn test_too_small_message_fits_tiny_terminals() {
+        for (width, height) in [(39, 20), (20, 5), (1, 1)] {
+            let backend = ratatui::backend::TestBackend::new(width, height);
+            let mut terminal = Terminal::new(backend).unwrap();
+            terminal.draw(draw_too_small).unwrap();
+        }
+        let backend = ratatui::backend::TestBackend::new(30, 5);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(draw_too_small).unwrap();
+        let text: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(text.contains("Terminal too small"));
+        assert!(text.contains("30x5"));
+    }
+
+    #[test]
+    fn test_split_words_keeps_quoted_spans() {
+        assert_eq!(
+            split_words(r#"+work  due:"next friday afternoon" P1"#),
+            vec!["+work", "due:next friday afternoon", "P1"]
+        );
+        assert_eq!(split_words(r#"say "" now"#), vec!["say", "", "now"]);
+        assert!(split_words("   ").is_empty());
+    }
+
+    #[test]
+    fn test_task_stats() {
+        // A Wednesday
+        let now = Local
+            .with_ymd_and_hms(2025, 6, 11, 12, 0, 0)
+            .unwrap()
+            .to_utc();
+        let days_ago = |days: u64| now - Days::new(days);
+        let task = |status: &str, created: u64| Task {
+            status: status.to_string(),
+            created: days_ago(created),
+            ..Task::new(format!("{} {}", status, created))
+        };
+
+        let mut tasks = vec![
+            task(STATUS_PENDING, 30),
+            task(STATUS_PENDING, 400),
+            task(STATUS_ACTIVE, 5),
+            task(STATUS_TEMPLATE, 900),
+        ];
+        tasks[0].due = Some(days_ago(1));
+        tasks[2].due = Some(now + chrono::Duration::hours(2));
+        // Monday of this week, Tuesday of last week and two weeks before that
+        for ago in [2, 8, 23] {
+            tasks.push(Task {
+                resolved: Some(days_ago(ago)),
+                ..task(STATUS_RESOLVED, 40)
+            });
+        }
+
+        let stats = TaskStats::new(&tasks, now, 4);
+        assert_eq!(
+            stats.by_status,
+            vec![
+                (STATUS_ACTIVE, 1),
+                (STATUS_PENDING, 2),
+                (STATUS_RESOLVED, 3),
+                (STATUS_TEMPLATE, 1)
+            ]
+        );
+        assert_eq!(stats.resolved_per_week, vec![1, 0, 1, 1]);
+        assert_eq!(stats.overdue, 1);
+        assert_eq!(stats.due_next_week, 1);
+        // Templates and resolved tasks aren't open
+        let ages: Vec<i64> = stats.oldest.iter().map(|(age, _)| *age).collect();
+        assert_eq!(ages, vec![400, 30, 5]);
+    }
+
+    #[test]
+    fn test_dependency_tree() {
+        let mut tasks: Vec<Task> = ["Release", "Fix the build", "Set up CI", "Get access"]
+            .iter()
+            .enumerate()
+            .map(|(i, summary)| Task {
+                id: i as i32 + 1,
+                ..Task::new(summary.to_string())
+            })
+            .collect();
+        // Release waits for the build and CI; both wait for access
+        tasks[0].dependencies = vec![tasks[1].uuid.clone(), tasks[2].uuid.clone()];
+        tasks[1].dependencies = vec![tasks[3].uuid.clone()];
+        tasks[2].dependencies = vec![tasks[3].uuid.clone()];
+        tasks[3].status = STATUS_RESOLVED.to_string();
+
+        let lines = dependency_tree(&tasks[0], &tasks);
+        let rendered: Vec<String> = lines
+            .iter()
+            .map(|l| format!("{}{}", l.prefix, l.text))
+            .collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "1 Release",
+                "Blocked by",
+                "├── 2 Fix the build",
+                "│   └── 4 Get access",
+                "└── 3 Set up CI",
+                "    └── 4 Get access (see above)",
+            ]
+        );
+        assert!(lines[3].resolved);
+
+        let lines = dependency_tree(&tasks[3], &tasks);
+        let rendered: Vec<String> = lines
+            .iter()
+            .map(|l| format!("{}{}", l.prefix, l.text))
+            .collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "4 Get access",
+                "Blocks",
+                "├── 2 Fix the build",
+                "│   └── 1 Release",
+                "└── 3 Set up CI",
+                "    └── 1 Release (see above)",
+            ]
+        );
+
+        // The cursor skips headings
+        let mut popup = DepsPopup::new(lines);
+        assert_eq!(popup.selected, 2);
+        popup.move_selection(false);
+        assert_eq!(popup.selected, 0);
+        popup.move_selection(true);
+        assert_eq!(popup.selected, 2);
+    }
+
+    #[test]
+    fn test_list_columns_fit_the_width() {
+        let prefs = Preferences::default();
+        let mut task = Task {
+            id: 12,
+            project: "website".to_string(),
+            tags: vec!["work".to_string()],
+            notes: "Details".to_string(),
+            ..Task::new("Rewrite the landing page copy".to_string())
+        };
+        let cells = list_cells(&task, &prefs);
+        assert_eq!(cells[0], "12");
+        assert_eq!(cells[LIST_SUMMARY], "Rewrite the landing page copy [notes]");
+        assert_eq!(cells[5], "+work");
+
+        // Wide enough for everything, with no room taken by the empty due column
+        let widths = list_column_widths(std::slice::from_ref(&cells), 80);
+        assert_eq!(widths, [2, 2, 2, 37, 7, 5, 0]);
+
+        // The summary is the widest, so it gives way first
+        let widths = list_column_widths(std::slice::from_ref(&cells), 40);
+        assert_eq!(widths.iter().sum::<usize>() + 5, 40);
+        assert_eq!(widths[LIST_SUMMARY], 17);
+
+        task.notes.clear();
+        assert_eq!(fit_cell(&task.summary, 12), "Rewrite the…");
+        assert_eq!(fit_cell("website", 9), "website  ");
+        assert_eq!(fit_cell("日本語", 4), "日… ");
+
+        task.delegated_to = "alice".to_string();
+        assert_eq!(list_cells(&task, &prefs)[5], "@alice +work");
+    }
+
+    #[test]
+    fn test_task_markdown() {
+        let mut task = Task {
+            uuid: "5d3a4f1e-0000-4000-8000-000000000001".to_string(),
+            tags: vec!["work".to_string(), "urgent".to_string()],
+            priority: PRIORITY_HIGH.to_string(),
+            notes: "Check the logs first\n".to_string(),
+            ..Task::new("Fix the build".to_string())
+        };
+        assert_eq!(
+            task_markdown(&task),
+            "### Fix the build\n\n\
+             - UUID: <urn:uuid:5d3a4f1e-0000-4000-8000-000000000001>\n\
+             - Status: pending\n\
+             - Tags: work, urgent\n\
+             - Priority: P1\n\
+             \n\
+             Check the logs first\n"
+        );
+
+        task.notes.clear();
+        task.project = "ci".to_string();
+        assert!(
+            task_markdown(&task).ends_with("- Project: ci\n- Tags: work, urgent\n- Priority: P1\n")
+        );
+    }
+
+    #[test]
+    fn test_modify_input_applies_edits() {
+        let task = Task {
+            tags: vec!["work".to_string(), "urgent".to_string()],
+            project: "website".to_string(),
+            priority: PRIORITY_HIGH.to_string(),
+            due: Some(Utc::now()),
+            ..Task::new("Ship it".to_string())
+        };
+
+        // The pre-filled text changes nothing
+        let input = ModifyInput::new(&task);
+        assert!(input.text.starts_with("+work +urgent project:website due:"));
+        let (unchanged, _) = modify(&task, &input.text).unwrap();
+        assert!(unchanged.equals(&task));
+
+        // Deleted words are removed, new ones added
+        let (modified, clear_due) = modify(&task, "+urgent +home P0").unwrap();
+        assert_eq!(modified.tags, vec!["urgent", "home"]);
+        assert_eq!(modified.project, "");
+        assert_eq!(modified.priority, PRIORITY_CRITICAL);
+        assert!(clear_due);
+        assert!(modified.due.is_none());
+
+        assert!(modify(&task, "+work some words").is_err());
+    }
+}
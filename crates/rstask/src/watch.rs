@@ -0,0 +1,187 @@
+//! Notices changes made to the task repository outside the TUI
+//!
+//! A command in another terminal, a sync from another machine and a task
+//! file edited by hand all change files in the status directories, whether
+//! or not the change is committed. Those directories are watched with the
+//! platform's file notifications, and so is the repository root, so that a
+//! status directory created later is watched too. `.git` isn't watched: the
+//! TUI's own git commands touch it on every reload.
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rstask_core::constants::ALL_STATUSES;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+/// How long the repository must stay unchanged before reloading, so that a
+/// sync or a bulk command is picked up once, after it finishes
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+pub struct RepoWatcher {
+    repo: PathBuf,
+    /// None if the platform wouldn't watch the repository, in which case no
+    /// changes are reported
+    watcher: Option<RecommendedWatcher>,
+    events: Receiver<notify::Result<Event>>,
+    watched: HashSet<PathBuf>,
+    /// When the latest change not yet reported happened
+    pending: Option<Instant>,
+}
+
+impl RepoWatcher {
+    pub fn new(repo: &Path) -> Self {
+        let (sender, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender)
+            .ok()
+            .and_then(|mut w| w.watch(repo, RecursiveMode::NonRecursive).ok().map(|()| w));
+
+        let mut watcher = RepoWatcher {
+            repo: repo.to_path_buf(),
+            watcher,
+            events,
+            watched: HashSet::new(),
+            pending: None,
+        };
+        watcher.watch_status_dirs();
+        watcher
+    }
+
+    /// Takes the repository as it is now as seen, e.g. after the TUI's own
+    /// changes or a reload
+    pub fn mark_seen(&mut self) {
+        self.take_events();
+        self.pending = None;
+    }
+
+    /// Returns true once the repository has changed and then stayed quiet
+    /// for a moment. The change then counts as seen.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        if self.take_events() {
+            self.pending = Some(now);
+        }
+
+        match self.pending {
+            Some(since) if now.duration_since(since) >= DEBOUNCE => {
+                self.pending = None;
+                true
+            }
+            // Nothing changed, or still changing
+            _ => false,
+        }
+    }
+
+    /// Empties the event queue, returning whether anything in it changed
+    /// tasks
+    fn take_events(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(event) = self.events.try_recv() {
+            // An error can mean events were lost, so reload to be sure
+            let Ok(event) = event else {
+                changed = true;
+                continue;
+            };
+            // Reading tasks opens them
+            if matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+            if matches!(event.kind, EventKind::Remove(_)) {
+                for path in &event.paths {
+                    self.watched.remove(path);
+                }
+            }
+            // Editors' swap files, and .git in the root
+            changed |= event.paths.is_empty() || event.paths.iter().any(|p| !is_hidden(p));
+        }
+        self.watch_status_dirs();
+        changed
+    }
+
+    /// Watches the status directories that exist and aren't watched yet
+    fn watch_status_dirs(&mut self) {
+        let Some(watcher) = &mut self.watcher else {
+            return;
+        };
+        for status in ALL_STATUSES {
+            let dir = self.repo.join(status);
+            if !self.watched.contains(&dir)
+                && dir.is_dir()
+                && watcher.watch(&dir, RecursiveMode::NonRecursive).is_ok()
+            {
+                self.watched.insert(dir);
+            }
+        }
+    }
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Polls at `at` until the change being made shows up; notifications
+    /// arrive from another thread
+    fn wait_for_change(watcher: &mut RepoWatcher, at: Instant) {
+        for _ in 0..200 {
+            assert!(!watcher.poll(at));
+            if watcher.pending.is_some() {
+                // Let the rest of the change's events arrive
+                std::thread::sleep(Duration::from_millis(50));
+                assert!(!watcher.poll(at));
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("change wasn't noticed");
+    }
+
+    #[test]
+    fn test_change_is_reported_once_quiet() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let pending = dir.path().join("pending");
+        std::fs::create_dir_all(&pending).unwrap();
+
+        let mut watcher = RepoWatcher::new(dir.path());
+        let start = Instant::now();
+        assert!(!watcher.poll(start));
+
+        std::fs::write(pending.join("task.md"), "---\n---\n").unwrap();
+        // Waits for the change to settle, then reports it once
+        wait_for_change(&mut watcher, start);
+        assert!(!watcher.poll(start + DEBOUNCE / 2));
+        assert!(watcher.poll(start + DEBOUNCE));
+        assert!(!watcher.poll(start + DEBOUNCE * 2));
+
+        // A task file edited in place without a commit
+        std::fs::write(pending.join("task.md"), "---\nsummary: x\n---\n").unwrap();
+        wait_for_change(&mut watcher, start);
+        assert!(watcher.poll(start + DEBOUNCE));
+
+        // Changes the TUI made itself aren't reported
+        std::fs::remove_file(pending.join("task.md")).unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        watcher.mark_seen();
+        assert!(!watcher.poll(start + DEBOUNCE * 3));
+        assert!(!watcher.poll(start + DEBOUNCE * 5));
+    }
+
+    #[test]
+    fn test_status_directory_created_later_is_watched() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut watcher = RepoWatcher::new(dir.path());
+        let start = Instant::now();
+
+        let paused = dir.path().join("paused");
+        std::fs::create_dir_all(&paused).unwrap();
+        wait_for_change(&mut watcher, start);
+        assert!(watcher.poll(start + DEBOUNCE));
+
+        std::fs::write(paused.join("task.md"), "---\n---\n").unwrap();
+        wait_for_change(&mut watcher, start);
+        assert!(watcher.poll(start + DEBOUNCE));
+    }
+}
@@ -0,0 +1,54 @@
+mod common;
+
+#[test]
+fn test_add_interactive() {
+    let (_repo, cmd) = test_setup!();
+
+    let result = cmd.run(&["add", "seed", "project:website", "+work"]);
+    result.assert_success();
+
+    // Summary, project, tags, priority, due
+    let result = cmd.run_with_stdin(&["add", "-i"], "Write docs\nweb\nwo urgent\np1\ntomorrow\n");
+    result.assert_success();
+
+    let result = cmd.run(&["next", "Write"]);
+    result.assert_success();
+
+    let tasks = result.parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "Write docs");
+    assert_eq!(tasks[0].project, "website");
+    assert_eq!(tasks[0].tags, vec!["urgent", "work"]);
+    assert_eq!(tasks[0].priority, "P1");
+    assert!(tasks[0].due.is_some());
+}
+
+#[test]
+fn test_add_interactive_uses_inline_defaults() {
+    let (_repo, cmd) = test_setup!();
+
+    // Empty answers keep what was given on the command line; bad dates re-prompt
+    let result = cmd.run_with_stdin(&["add", "-i", "Buy milk", "+home"], "\n\n\n\nbogus\n\n");
+    result.assert_success();
+
+    let result = cmd.run(&["next"]);
+    result.assert_success();
+
+    let tasks = result.parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "Buy milk");
+    assert_eq!(tasks[0].tags, vec!["home"]);
+    assert_eq!(tasks[0].priority, "P2");
+    assert!(tasks[0].due.is_none());
+}
+
+#[test]
+fn test_add_interactive_aborts_without_input() {
+    let (_repo, cmd) = test_setup!();
+
+    let result = cmd.run_with_stdin(&["add", "-i"], "");
+    result.assert_failure();
+
+    let result = cmd.run(&["next"]);
+    assert!(result.parse_tasks().is_empty());
+}
@@ -0,0 +1,42 @@
+mod common;
+
+#[test]
+fn test_again_repeats_add() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["last"]).assert_success();
+    cmd.run(&["again"]).assert_failure();
+
+    cmd.run(&["add", "Water plants", "+home", "project:garden"])
+        .assert_success();
+
+    let result = cmd.run(&["last"]);
+    result.assert_success();
+    assert_eq!(
+        result.stdout().trim(),
+        "rstask add 'Water plants' +home project:garden"
+    );
+
+    cmd.run(&["again"]).assert_success();
+
+    let tasks = cmd.run(&["next"]).parse_tasks();
+    assert_eq!(tasks.len(), 2);
+    for task in &tasks {
+        assert_eq!(task.summary, "Water plants");
+        assert_eq!(task.tags, vec!["home"]);
+        assert_eq!(task.project, "garden");
+    }
+}
+
+#[test]
+fn test_non_task_commands_are_not_recorded() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Fix sink"]).assert_success();
+    cmd.run(&["add", "Call plumber"]).assert_success();
+    cmd.run(&["undo"]).assert_success();
+    cmd.run(&["context", "+work"]).assert_success();
+
+    let result = cmd.run(&["last"]);
+    assert_eq!(result.stdout().trim(), "rstask add 'Call plumber'");
+}
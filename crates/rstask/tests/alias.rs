@@ -0,0 +1,53 @@
+mod common;
+
+fn config(aliases: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("rstask")).unwrap();
+    std::fs::write(
+        dir.path().join("rstask").join("config.styx"),
+        format!("aliases {{{}}}\n", aliases),
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_alias_expands_to_command_and_query() {
+    let (_repo, cmd) = test_setup!();
+    let config = config(r#"work "next +work", inbox show-unorganised, add "next +work""#);
+    let env = [("XDG_CONFIG_HOME", config.path().to_str().unwrap())];
+
+    cmd.run(&["add", "Write report", "+work", "P1"])
+        .assert_success();
+    cmd.run(&["add", "Plan offsite", "+work"]).assert_success();
+    cmd.run(&["add", "Buy milk"]).assert_success();
+
+    let tasks = cmd.run_with_env(&["work"], &env).parse_tasks();
+    assert_eq!(tasks.len(), 2);
+
+    let tasks = cmd
+        .run_with_env(&["--compact", "work", "P1"], &env)
+        .parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "Write report");
+
+    let tasks = cmd.run_with_env(&["inbox"], &env).parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "Buy milk");
+
+    // An alias can't take the place of a command
+    cmd.run_with_env(&["add", "Call plumber"], &env)
+        .assert_success();
+    assert_eq!(cmd.run(&["next"]).parse_tasks().len(), 4);
+}
+
+#[test]
+fn test_alias_completions() {
+    let (_repo, cmd) = test_setup!();
+    let config = config("today \"next due:today\", inbox show-unorganised");
+    let env = [("XDG_CONFIG_HOME", config.path().to_str().unwrap())];
+
+    let result = cmd.run_with_env(&["_completions", "aliases"], &env);
+    result.assert_success();
+    assert_eq!(result.stdout(), "inbox\ntoday\n");
+}
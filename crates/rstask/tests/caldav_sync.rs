@@ -0,0 +1,10 @@
+mod common;
+
+#[test]
+fn test_caldav_sync_requires_url() {
+    let (_repo, cmd) = test_setup!();
+
+    let result = cmd.run(&["caldav-sync"]);
+    result.assert_failure();
+    assert!(result.stderr().contains("caldav_url is not set"));
+}
@@ -0,0 +1,26 @@
+mod common;
+
+#[test]
+fn test_columns_flag_accepts_known_columns() {
+    let (_repo, cmd) = test_setup!();
+
+    let result = cmd.run(&["add", "one", "+one"]);
+    result.assert_success();
+
+    let result = cmd.run(&["--columns", "id,urgency,age,summary", "next"]);
+    result.assert_success();
+
+    // Column selection only affects the table; JSON output is unchanged
+    let tasks = result.parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "one");
+}
+
+#[test]
+fn test_columns_flag_rejects_unknown_column() {
+    let (_repo, cmd) = test_setup!();
+
+    let result = cmd.run(&["--columns", "id,bogus", "next"]);
+    result.assert_failure();
+    assert!(result.stderr().contains("unknown column: bogus"));
+}
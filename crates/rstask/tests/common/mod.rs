@@ -1,8 +1,11 @@
 #![allow(dead_code)]
 
-use rstask_core::task::Task;
+use rstask_core::config::repo_state_dir;
+use rstask_core::task::{Task, WriteFormat};
+use rstask_core::taskset::{LoadOptions, TaskSet};
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Output};
+use std::process::{Command, Output, Stdio};
 use tempfile::TempDir;
 
 pub struct TestRepo {
@@ -24,6 +27,11 @@ impl TestRepo {
 
         assert!(status.success(), "git init failed");
 
+        Self::configure(dir, path)
+    }
+
+    /// Sets the git identity that commits in tests are made with
+    fn configure(dir: TempDir, path: PathBuf) -> Self {
         // Set git identity for the test repo
         let status = Command::new("git")
             .args(["config", "user.name", "Test User"])
@@ -47,6 +55,164 @@ impl TestRepo {
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    /// Runs git in the repository, panicking if it fails, and returns stdout
+    pub fn git(&self, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&self.path)
+            .output()
+            .expect("Failed to run git");
+        assert!(
+            output.status.success(),
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    /// Writes a task file directly, without the binary and without committing.
+    /// An empty UUID gets a new one; the written task is returned.
+    pub fn write_task(&self, mut task: Task) -> Task {
+        if task.uuid.is_empty() {
+            task.uuid = rstask_core::util::must_get_uuid4_string();
+        }
+        task.save_to_disk(&self.path, WriteFormat::default())
+            .expect("Failed to write task");
+        task
+    }
+
+    /// Stages and commits everything, as a hand edit would
+    pub fn commit_all(&self, message: &str) {
+        self.git(&["add", "-A"]);
+        self.git(&["commit", "--no-gpg-sign", "--no-verify", "-m", message]);
+    }
+
+    /// Where rstask keeps the IDs of this repository's tasks
+    pub fn ids_file(&self) -> PathBuf {
+        repo_state_dir(&state_home(&self.path), &self.path).join("ids.bin")
+    }
+
+    /// Reads every task from disk, including resolved ones
+    pub fn tasks(&self) -> Vec<Task> {
+        TaskSet::load_with(&self.path, &self.ids_file(), &LoadOptions::new(true))
+            .expect("Failed to load tasks")
+            .all_tasks()
+            .to_vec()
+    }
+
+    pub fn task_by_summary(&self, summary: &str) -> Option<Task> {
+        self.tasks().into_iter().find(|t| t.summary == summary)
+    }
+
+    pub fn commit_count(&self) -> usize {
+        let has_head = Command::new("git")
+            .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+            .current_dir(&self.path)
+            .output()
+            .expect("Failed to run git")
+            .status
+            .success();
+        if !has_head {
+            return 0;
+        }
+        self.git(&["rev-list", "--count", "HEAD"])
+            .trim()
+            .parse()
+            .unwrap()
+    }
+
+    /// Commit subjects, newest first
+    pub fn commit_messages(&self) -> Vec<String> {
+        if self.commit_count() == 0 {
+            return Vec::new();
+        }
+        self.git(&["log", "--format=%s"])
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn assert_last_commit(&self, message: &str) {
+        let messages = self.commit_messages();
+        assert_eq!(
+            messages.first().map(String::as_str),
+            Some(message),
+            "commits: {:?}",
+            messages
+        );
+    }
+
+    /// Asserts that nothing is left uncommitted
+    pub fn assert_clean(&self) {
+        let status = self.git(&["status", "--porcelain"]);
+        assert!(status.trim().is_empty(), "uncommitted changes:\n{}", status);
+    }
+
+    /// Makes `remote` this repository's origin and pushes the current branch
+    /// to it, so that `rstask sync` has something to pull from
+    pub fn push_to(&self, remote: &TestRemote) {
+        self.git(&["remote", "add", "origin", remote.url()]);
+        self.git(&["push", "--quiet", "-u", "origin", "HEAD"]);
+    }
+}
+
+/// A bare repository standing in for a shared remote
+pub struct TestRemote {
+    pub dir: TempDir,
+}
+
+impl TestRemote {
+    pub fn new() -> Self {
+        let dir = TempDir::new().expect("Failed to create temp directory");
+        let status = Command::new("git")
+            .args(["init", "--bare", "--quiet"])
+            .current_dir(dir.path())
+            .status()
+            .expect("Failed to initialize bare repo");
+        assert!(status.success(), "git init --bare failed");
+        TestRemote { dir }
+    }
+
+    pub fn url(&self) -> &str {
+        self.dir.path().to_str().unwrap()
+    }
+
+    /// Runs git in the bare repository, panicking if it fails
+    pub fn git(&self, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(self.dir.path())
+            .output()
+            .expect("Failed to run git");
+        assert!(
+            output.status.success(),
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    /// Clones the remote as another machine would
+    pub fn clone_repo(&self) -> TestRepo {
+        let dir = TempDir::new().expect("Failed to create temp directory");
+        let path = dir.path().to_path_buf();
+        let status = Command::new("git")
+            .args(["clone", "--quiet", self.url(), "."])
+            .current_dir(&path)
+            .status()
+            .expect("Failed to clone");
+        assert!(status.success(), "git clone failed");
+        TestRepo::configure(dir, path)
+    }
+}
+
+/// Keeps rstask's state for a test repository out of the real home
+/// directory, inside .git where it doesn't show up as a change
+fn state_home(repo: &Path) -> PathBuf {
+    repo.join(".git").join("xdg-state")
 }
 
 pub struct TestCmd {
@@ -89,10 +255,48 @@ impl TestCmd {
     }
 
     pub fn run(&self, args: &[&str]) -> TestResult {
+        self.run_with_env(args, &[])
+    }
+
+    pub fn run_with_env(&self, args: &[&str], env: &[(&str, &str)]) -> TestResult {
+        let output = self
+            .command(args, env)
+            .output()
+            .expect("Failed to execute command");
+
+        TestResult { output }
+    }
+
+    pub fn run_with_stdin(&self, args: &[&str], input: &str) -> TestResult {
+        let mut child = self
+            .command(args, &[])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute command");
+
+        child
+            .stdin
+            .take()
+            .expect("Failed to open stdin")
+            .write_all(input.as_bytes())
+            .expect("Failed to write stdin");
+
+        let output = child
+            .wait_with_output()
+            .expect("Failed to wait for command");
+
+        TestResult { output }
+    }
+
+    fn command(&self, args: &[&str], env: &[(&str, &str)]) -> Command {
         let mut cmd = Command::new(&self.binary_path);
         cmd.args(args)
             .env("RSTASK_GIT_REPO", &self.repo_path)
-            .env("XDG_CONFIG_HOME", "/nonexistent"); // Prevent loading user's config
+            .env("XDG_CONFIG_HOME", "/nonexistent") // Prevent loading user's config
+            .env("XDG_STATE_HOME", state_home(&self.repo_path))
+            .env_remove("VISUAL");
 
         if let Some(ctx) = &self.context {
             cmd.env("RSTASK_CONTEXT", ctx);
@@ -100,9 +304,11 @@ impl TestCmd {
             cmd.env("RSTASK_CONTEXT", "");
         }
 
-        let output = cmd.output().expect("Failed to execute command");
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
 
-        TestResult { output }
+        cmd
     }
 }
 
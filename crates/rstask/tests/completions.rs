@@ -0,0 +1,27 @@
+mod common;
+
+#[test]
+fn test_completions_tasks_and_due() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Write report", "project:work"])
+        .assert_success();
+    cmd.run(&["add", "Buy milk"]).assert_success();
+
+    let result = cmd.run(&["_completions", "tasks"]);
+    result.assert_success();
+    assert_eq!(result.stdout(), "1\tWrite report\n2\tBuy milk\n");
+
+    let result = cmd.run(&["_completions", "due"]);
+    result.assert_success();
+    let stdout = result.stdout();
+    let keywords: Vec<&str> = stdout.lines().collect();
+    assert!(keywords.contains(&"tomorrow"));
+    assert!(keywords.contains(&"next-friday"));
+
+    for shell in ["bash", "zsh", "fish", "powershell", "nushell"] {
+        let result = cmd.run(&["completions", shell]);
+        result.assert_success();
+        assert!(result.stdout().contains("_completions"));
+    }
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use std::fs;
+
+#[test]
+fn test_csv_round_trip_updates_tasks() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Pay rent", "+home", "due:tomorrow"])
+        .assert_success();
+    cmd.run(&[
+        "add",
+        "Prune roses",
+        "project:garden",
+        "/",
+        "Before the frost, see \"pruning.md\"",
+    ])
+    .assert_success();
+
+    let result = cmd.run(&["export-csv"]);
+    result.assert_success();
+    let csv = result.stdout();
+    assert!(csv.starts_with("uuid,id,status,summary,project,priority,tags,due,"));
+    assert!(csv.contains("\"Before the frost, see \"\"pruning.md\"\"\""));
+
+    // Exported and imported again as it is, nothing changes
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("tasks.csv");
+    fs::write(&file, &csv).unwrap();
+    let result = cmd.run(&["import-csv", file.to_str().unwrap()]);
+    result.assert_success();
+    assert!(result.stdout().contains("Nothing to import"));
+
+    // Triage in a spreadsheet: a new priority and tags, and the date cleared
+    let edited = csv.replace(",P2,home,", ",P1,home bills,");
+    let edited = edited.replacen(
+        &format!(
+            ",{},",
+            repo.task_by_summary("Pay rent")
+                .unwrap()
+                .due
+                .unwrap()
+                .to_rfc3339()
+        ),
+        ",,",
+        1,
+    );
+    fs::write(&file, edited).unwrap();
+    let result = cmd.run(&["import-csv", file.to_str().unwrap()]);
+    result.assert_success();
+    assert!(result.stdout().contains("0 added, 1 updated"));
+    repo.assert_last_commit("Imported CSV: 0 added, 1 updated");
+    repo.assert_clean();
+
+    let task = repo.task_by_summary("Pay rent").unwrap();
+    assert_eq!(task.priority, "P1");
+    assert_eq!(task.tags, vec!["bills", "home"]);
+    assert!(task.due.is_none());
+}
+
+#[test]
+fn test_import_csv_from_another_tool() {
+    let (repo, cmd) = test_setup!();
+
+    let result = cmd.run_with_stdin(
+        &["import-csv", "-"],
+        "Title,Labels,Due Date,Assignee\n\
+         Renew passport,admin,2030-06-01,Sam\n\
+         \"Book dentist, then the optician\",,,\n",
+    );
+    result.assert_success();
+    assert!(result.stdout().contains("2 added, 0 updated"));
+    assert!(result.stderr().contains("assignee"));
+
+    let task = repo.task_by_summary("Renew passport").unwrap();
+    assert_eq!(task.tags, vec!["admin"]);
+    assert!(task.due.is_some());
+    assert!(
+        repo.task_by_summary("Book dentist, then the optician")
+            .is_some()
+    );
+
+    let tasks = cmd.run(&["next"]).parse_tasks();
+    assert_eq!(tasks.len(), 2);
+}
+
+#[test]
+fn test_import_csv_writes_nothing_when_a_row_is_wrong() {
+    let (repo, cmd) = test_setup!();
+
+    let result = cmd.run_with_stdin(
+        &["import-csv", "-"],
+        "summary,status\nRenew passport,pending\nBook dentist,finished\n",
+    );
+    result.assert_failure();
+    assert!(result.stderr().contains("line 3: unknown status finished"));
+    assert!(repo.task_by_summary("Renew passport").is_none());
+
+    let result = cmd.run_with_stdin(&["import-csv", "-"], "project\ngarden\n");
+    result.assert_failure();
+    assert!(result.stderr().contains("no summary column"));
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::fs;
+
+#[test]
+fn test_delegate_hides_task_from_next() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Review budget"]).assert_success();
+    cmd.run(&["add", "Book venue"]).assert_success();
+    cmd.run(&["delegate", "2", "@alice"]).assert_success();
+
+    let tasks = cmd.run(&["next"]).parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "Review budget");
+
+    let tasks = cmd.run(&["next", "+delegated"]).parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "Book venue");
+    assert_eq!(tasks[0].delegated_to, "alice");
+
+    // Clearing the delegate brings it back
+    cmd.run(&["delegate", "2"]).assert_success();
+    let tasks = cmd.run(&["next"]).parse_tasks();
+    assert_eq!(tasks.len(), 2);
+}
+
+#[test]
+fn test_show_delegated_by_person() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Review budget"]).assert_success();
+    cmd.run(&["add", "Book venue"]).assert_success();
+    cmd.run(&["add", "Print flyers"]).assert_success();
+    cmd.run(&["delegate", "2", "alice"]).assert_success();
+    cmd.run(&["delegate", "3", "bob"]).assert_success();
+
+    let tasks = cmd.run(&["show-delegated"]).parse_tasks();
+    assert_eq!(tasks.len(), 2);
+
+    let tasks = cmd.run(&["show-delegated", "Alice"]).parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "Book venue");
+
+    let tasks = cmd.run(&["show-open", "-delegated"]).parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "Review budget");
+}
+
+#[test]
+fn test_next_keeps_delegated_when_configured() {
+    let (_repo, cmd) = test_setup!();
+
+    let config_home = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_home.path().join("rstask")).unwrap();
+    fs::write(
+        config_home.path().join("rstask").join("config.styx"),
+        "next_hides_delegated false\n",
+    )
+    .unwrap();
+    let env = [("XDG_CONFIG_HOME", config_home.path().to_str().unwrap())];
+
+    cmd.run_with_env(&["add", "Book venue"], &env)
+        .assert_success();
+    cmd.run_with_env(&["delegate", "1", "alice"], &env)
+        .assert_success();
+
+    let tasks = cmd.run_with_env(&["next"], &env).parse_tasks();
+    assert_eq!(tasks.len(), 1);
+}
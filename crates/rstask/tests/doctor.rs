@@ -0,0 +1,58 @@
+mod common;
+
+#[test]
+fn test_doctor_clean_repo() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Fix the build"]).assert_success();
+
+    let result = cmd.run(&["doctor"]);
+    result.assert_success();
+    assert!(result.stdout().contains("No problems found"));
+}
+
+#[test]
+fn test_doctor_reports_and_fixes() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Fix the build"]).assert_success();
+    let task = repo.task_by_summary("Fix the build").unwrap();
+    let path = repo
+        .path()
+        .join("pending")
+        .join(format!("{}.md", task.uuid));
+
+    // A hand edit adding a dependency on a task that was never created
+    let data = std::fs::read_to_string(&path).unwrap().replacen(
+        "---\n",
+        "---\ndependencies: [9a8b7c6d-0000-4000-8000-000000000000]\n",
+        1,
+    );
+    std::fs::write(&path, data).unwrap();
+    std::fs::write(repo.path().join("pending").join("scratch.txt"), "").unwrap();
+    repo.commit_all("Hand edit");
+
+    let result = cmd.run(&["doctor"]);
+    result.assert_failure();
+    let stdout = result.stdout();
+    assert!(stdout.contains(&format!(
+        "pending/{}.md: depends on 9a8b7c6d-0000-4000-8000-000000000000, which isn't a task (fixable)",
+        task.uuid
+    )));
+    assert!(stdout.contains("pending/scratch.txt: not a task file\n"));
+    assert!(result.stderr().contains("1 can be fixed"));
+
+    let result = cmd.run(&["doctor", "--fix"]);
+    result.assert_failure();
+    assert!(result.stdout().contains("Fixed 1 problem"));
+    repo.assert_last_commit("Fixed 1 problem found by doctor");
+    assert!(
+        repo.task_by_summary("Fix the build")
+            .unwrap()
+            .dependencies
+            .is_empty()
+    );
+
+    std::fs::remove_file(repo.path().join("pending").join("scratch.txt")).unwrap();
+    cmd.run(&["doctor"]).assert_success();
+}
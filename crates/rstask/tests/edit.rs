@@ -0,0 +1,127 @@
+mod common;
+
+use std::path::Path;
+
+/// Writes an executable shell script to use as $EDITOR
+fn write_editor(dir: &Path, body: &str) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join("editor.sh");
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+fn pending_task_file(repo: &Path) -> std::path::PathBuf {
+    std::fs::read_dir(repo.join("pending"))
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .find(|p| p.extension().is_some_and(|ext| ext == "md"))
+        .expect("pending task file")
+}
+
+#[test]
+fn test_edit_task() {
+    let (repo, cmd) = test_setup!();
+
+    let result = cmd.run(&["add", "one"]);
+    result.assert_success();
+
+    let editor = write_editor(
+        repo.path(),
+        r#"sed -i 's/summary: one/summary: edited/' "$1""#,
+    );
+    let result = cmd.run_with_env(&["edit", "1"], &[("EDITOR", &editor)]);
+    result.assert_success();
+
+    let result = cmd.run(&["next"]);
+    result.assert_success();
+    let tasks = result.parse_tasks();
+    assert_eq!(tasks[0].summary, "edited");
+}
+
+#[test]
+fn test_edit_aborts_on_concurrent_modification() {
+    let (repo, cmd) = test_setup!();
+
+    let result = cmd.run(&["add", "one"]);
+    result.assert_success();
+
+    // Simulate the task changing on disk (e.g. via sync) while the editor is open
+    let task_file = pending_task_file(repo.path());
+    let editor = write_editor(
+        repo.path(),
+        &format!(
+            r#"sed -i 's/summary: one/summary: edited/' "$1"
+sed -i 's/summary: one/summary: synced/' "{}""#,
+            task_file.display()
+        ),
+    );
+    let result = cmd.run_with_env(&["edit", "1"], &[("EDITOR", &editor)]);
+    result.assert_failure();
+    assert!(
+        result
+            .stderr()
+            .contains("modified while it was being edited")
+    );
+
+    let result = cmd.run(&["next"]);
+    result.assert_success();
+    let tasks = result.parse_tasks();
+    assert_eq!(tasks[0].summary, "synced", "newer version must be kept");
+}
+
+#[test]
+fn test_editor_preference_with_arguments() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Fix the header!"]).assert_success();
+
+    // The script sees the flag first, then the file
+    let seen = repo.path().join(".git").join("seen");
+    let editor = write_editor(
+        repo.path(),
+        &format!(
+            r#"echo "$1 $(basename "$2")" > "{}"
+sed -i 's/summary: Fix the header!/summary: edited/' "$2""#,
+            seen.display()
+        ),
+    );
+    let config = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(config.path().join("rstask")).unwrap();
+    std::fs::write(
+        config.path().join("rstask").join("config.styx"),
+        format!("editor \"{} --wait {{file}}\"\n", editor),
+    )
+    .unwrap();
+
+    let result = cmd.run_with_env(
+        &["edit", "1"],
+        &[
+            ("XDG_CONFIG_HOME", config.path().to_str().unwrap()),
+            ("VISUAL", "false"),
+        ],
+    );
+    result.assert_success();
+    assert_eq!(
+        std::fs::read_to_string(&seen).unwrap(),
+        "--wait rstask-1-fix-the-header.md\n"
+    );
+    assert!(repo.task_by_summary("edited").is_some());
+}
+
+#[test]
+fn test_visual_before_editor() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "one"]).assert_success();
+
+    let editor = write_editor(
+        repo.path(),
+        r#"sed -i 's/summary: one/summary: edited/' "$1""#,
+    );
+    let result = cmd.run_with_env(&["edit", "1"], &[("VISUAL", &editor), ("EDITOR", "false")]);
+    result.assert_success();
+    assert!(repo.task_by_summary("edited").is_some());
+}
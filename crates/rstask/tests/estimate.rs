@@ -0,0 +1,33 @@
+mod common;
+
+#[test]
+fn test_estimate_is_set_and_totalled_per_project() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Write report", "project:q3", "estimate:2h"])
+        .assert_success();
+    cmd.run(&["add", "Check figures", "project:q3", "estimate:45m"])
+        .assert_success();
+    cmd.run(&["modify", "1", "estimate:1h30m"]).assert_success();
+
+    let tasks = cmd.run(&["next"]).parse_tasks();
+    let estimates: Vec<Option<u32>> = tasks.iter().map(|t| t.estimate).collect();
+    assert_eq!(estimates, vec![Some(90), Some(45)]);
+
+    let result = cmd.run(&["show-projects"]);
+    result.assert_success();
+    let projects: serde_json::Value = serde_json::from_str(&result.stdout()).unwrap();
+    assert_eq!(projects[0]["estimate"], 135);
+
+    cmd.run(&["modify", "2", "estimate:0"]).assert_success();
+    let tasks = cmd.run(&["next"]).parse_tasks();
+    assert_eq!(tasks[1].estimate, None);
+}
+
+#[test]
+fn test_invalid_estimate_is_rejected() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Write report", "estimate:soon"])
+        .assert_failure();
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use std::fs;
+
+#[test]
+fn test_export_ical_includes_tasks_with_due_dates() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Pay rent", "+home", "due:tomorrow"])
+        .assert_success();
+    cmd.run(&["add", "Someday"]).assert_success();
+    cmd.run(&["add", "Standup", "+work", "due:today"])
+        .assert_success();
+
+    let result = cmd.run(&["export-ical"]);
+    result.assert_success();
+    let ical = result.stdout();
+    assert!(ical.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert_eq!(ical.matches("BEGIN:VTODO").count(), 2);
+    assert!(ical.contains("SUMMARY:Pay rent"));
+    assert!(!ical.contains("Someday"));
+
+    let result = cmd.run(&["export-ical", "--events", "+work"]);
+    result.assert_success();
+    let ical = result.stdout();
+    assert_eq!(ical.matches("BEGIN:VEVENT").count(), 1);
+    assert!(ical.contains("SUMMARY:Standup"));
+}
+
+#[test]
+fn test_ical_export_path_written_on_commit() {
+    let (repo, cmd) = test_setup!();
+
+    let config_home = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_home.path().join("rstask")).unwrap();
+    fs::write(
+        config_home.path().join("rstask").join("config.styx"),
+        "ical_export_path public/tasks.ics\n",
+    )
+    .unwrap();
+    let env = [("XDG_CONFIG_HOME", config_home.path().to_str().unwrap())];
+
+    cmd.run_with_env(
+        &["add", "Pay rent", "due:tomorrow", "/", "door code 1234"],
+        &env,
+    )
+    .assert_success();
+
+    let export = repo.path().join("public").join("tasks.ics");
+    let ical = fs::read_to_string(&export).unwrap();
+    assert!(ical.contains("SUMMARY:Pay rent"));
+    // Notes aren't published unless asked for
+    assert!(!ical.contains("door code"));
+
+    // The file is committed alongside the task
+    let output = std::process::Command::new("git")
+        .args(["ls-files", "public/tasks.ics"])
+        .current_dir(repo.path())
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("tasks.ics"));
+
+    cmd.run_with_env(&["done", "1"], &env).assert_success();
+    let ical = fs::read_to_string(&export).unwrap();
+    assert!(!ical.contains("Pay rent"));
+}
+
+#[test]
+fn test_ical_export_notes_opt_in() {
+    let (repo, cmd) = test_setup!();
+
+    let config_home = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_home.path().join("rstask")).unwrap();
+    fs::write(
+        config_home.path().join("rstask").join("config.styx"),
+        "ical_export_path tasks.ics
+ical_export_notes true
+",
+    )
+    .unwrap();
+    let env = [("XDG_CONFIG_HOME", config_home.path().to_str().unwrap())];
+
+    cmd.run_with_env(
+        &["add", "Pay rent", "due:tomorrow", "/", "door code 1234"],
+        &env,
+    )
+    .assert_success();
+
+    let ical = fs::read_to_string(repo.path().join("tasks.ics")).unwrap();
+    assert!(ical.contains("door code 1234"));
+}
+
+#[test]
+fn test_ical_export_path_outside_repo_is_refused() {
+    let (repo, cmd) = test_setup!();
+
+    let config_home = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_home.path().join("rstask")).unwrap();
+    fs::write(
+        config_home.path().join("rstask").join("config.styx"),
+        "ical_export_path ../tasks.ics
+",
+    )
+    .unwrap();
+    let env = [("XDG_CONFIG_HOME", config_home.path().to_str().unwrap())];
+
+    let result = cmd.run_with_env(&["add", "Pay rent", "due:tomorrow"], &env);
+    result.assert_failure();
+    assert!(result.stderr().contains("ical_export_path"));
+    assert!(!repo.path().parent().unwrap().join("tasks.ics").exists());
+}
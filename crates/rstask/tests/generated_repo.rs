@@ -0,0 +1,23 @@
+mod common;
+
+use rstask_core::constants::*;
+use rstask_core::testing::RepoSpec;
+
+#[test]
+fn test_show_commands_on_generated_repo() {
+    let (repo, cmd) = test_setup!();
+    let tasks = RepoSpec::new(400).seed(11).write(repo.path()).unwrap();
+    let count = |status: &str| tasks.iter().filter(|t| t.status == status).count();
+
+    let result = cmd.run(&["show-active"]);
+    result.assert_success();
+    assert_eq!(result.parse_tasks().len(), count(STATUS_ACTIVE));
+
+    let result = cmd.run(&["show-paused"]);
+    result.assert_success();
+    assert_eq!(result.parse_tasks().len(), count(STATUS_PAUSED));
+
+    let result = cmd.run(&["show-resolved"]);
+    result.assert_success();
+    assert_eq!(result.parse_tasks().len(), count(STATUS_RESOLVED));
+}
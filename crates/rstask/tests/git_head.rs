@@ -0,0 +1,50 @@
+mod common;
+
+#[test]
+fn test_detached_head_is_refused_and_recovered() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "First"]).assert_success();
+    cmd.run(&["git", "checkout", "--detach"]).assert_success();
+
+    let result = cmd.run(&["add", "Second"]);
+    result.assert_failure();
+    assert!(result.stderr().contains("detached HEAD"));
+    assert!(result.stderr().contains("checkout-default"));
+
+    let result = cmd.run(&["git", "checkout-default"]);
+    result.assert_success();
+    assert!(result.stdout().contains("Switched to 'master'"));
+
+    // The uncommitted task is picked up by the next commit
+    cmd.run(&["add", "Third"]).assert_success();
+    let result = cmd.run(&["next"]);
+    assert!(!result.stderr().contains("Warning"));
+    let mut summaries: Vec<String> = result
+        .parse_tasks()
+        .into_iter()
+        .map(|t| t.summary)
+        .collect();
+    summaries.sort();
+    assert_eq!(summaries, vec!["First", "Second", "Third"]);
+
+    let log = cmd.run(&["git", "status", "--porcelain"]);
+    assert!(log.stdout().trim().is_empty());
+}
+
+#[test]
+fn test_warns_on_other_branch() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "First"]).assert_success();
+    cmd.run(&["git", "checkout", "-b", "scratch"])
+        .assert_success();
+
+    let result = cmd.run(&["next"]);
+    result.assert_success();
+    assert!(
+        result
+            .stderr()
+            .contains("on branch 'scratch' rather than 'master'")
+    );
+}
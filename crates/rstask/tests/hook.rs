@@ -0,0 +1,52 @@
+mod common;
+
+const UUID: &str = "9a8b7c6d-0000-4000-8000-000000000000";
+
+#[test]
+fn test_pre_commit_hook_refuses_broken_task_files() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "First"]).assert_success();
+    let result = cmd.run(&["hook", "install", "pre-commit"]);
+    result.assert_success();
+    assert!(result.stdout().contains("pre-commit"));
+
+    // rstask's own commits pass the hook
+    cmd.run(&["add", "Second"]).assert_success();
+
+    // A hand edit with broken YAML is refused
+    let path = repo.path().join("pending").join(format!("{}.md", UUID));
+    std::fs::write(&path, "---\nsummary: [unclosed\n---\n").unwrap();
+    cmd.run(&["git", "add", "."]).assert_success();
+    let result = cmd.run(&["git", "commit", "-m", "Hand edit"]);
+    result.assert_failure();
+    assert!(result.stderr().contains(&format!("pending/{}.md", UUID)));
+
+    // Once fixed, it goes through
+    std::fs::write(
+        &path,
+        "---\nsummary: Fixed\npriority: P2\ncreated: 2024-01-01T00:00:00Z\n---\n",
+    )
+    .unwrap();
+    cmd.run(&["git", "add", "."]).assert_success();
+    cmd.run(&["git", "commit", "-m", "Hand edit"])
+        .assert_success();
+}
+
+#[test]
+fn test_install_leaves_foreign_hooks_alone() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "First"]).assert_success();
+    let hook = repo.path().join(".git/hooks/pre-commit");
+    std::fs::create_dir_all(hook.parent().unwrap()).unwrap();
+    std::fs::write(&hook, "#!/bin/sh\nexit 0\n").unwrap();
+
+    let result = cmd.run(&["hook", "install", "pre-commit"]);
+    result.assert_failure();
+    assert!(result.stderr().contains("already exists"));
+    assert_eq!(
+        std::fs::read_to_string(&hook).unwrap(),
+        "#!/bin/sh\nexit 0\n"
+    );
+}
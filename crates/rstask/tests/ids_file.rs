@@ -0,0 +1,97 @@
+mod common;
+
+fn ids_by_summary(cmd: &common::TestCmd) -> Vec<(String, i32)> {
+    let mut ids: Vec<(String, i32)> = cmd
+        .run(&["next"])
+        .parse_tasks()
+        .into_iter()
+        .map(|t| (t.summary, t.id))
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn test_damaged_ids_file_keeps_ids_from_backup() {
+    let (repo, cmd) = test_setup!();
+
+    for summary in ["One", "Two", "Three", "Four"] {
+        cmd.run(&["add", summary]).assert_success();
+    }
+    cmd.run(&["done", "2"]).assert_success();
+    cmd.run(&["add", "Five"]).assert_success();
+    let before = ids_by_summary(&cmd);
+
+    let ids_file = repo.ids_file();
+    std::fs::write(&ids_file, b"garbage").unwrap();
+
+    let result = cmd.run(&["next"]);
+    result.assert_success();
+    assert!(result.stderr().contains("restored IDs from its backup"));
+    assert_eq!(ids_by_summary(&cmd), before);
+
+    // Only reported once
+    assert!(!cmd.run(&["next"]).stderr().contains("Warning"));
+}
+
+#[test]
+fn test_damaged_ids_file_and_backup_warn_loudly() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "One"]).assert_success();
+    cmd.run(&["add", "Two"]).assert_success();
+
+    let ids_file = repo.ids_file();
+    let dir = ids_file.parent().unwrap();
+    std::fs::write(dir.join("ids.bin"), b"garbage").unwrap();
+    std::fs::write(dir.join("ids.bin.bak"), b"garbage").unwrap();
+
+    let result = cmd.run(&["next"]);
+    result.assert_success();
+    assert!(result.stderr().contains("Task IDs have been reassigned"));
+    assert!(dir.join("ids.bin.corrupt").exists());
+}
+
+#[test]
+fn test_ids_move_out_of_the_repository() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "One"]).assert_success();
+    cmd.run(&["add", "Two"]).assert_success();
+    cmd.run(&["done", "1"]).assert_success();
+    let before = ids_by_summary(&cmd);
+
+    // Where earlier versions kept them
+    let legacy = repo.path().join(".git/rstask/ids.bin");
+    std::fs::rename(repo.ids_file(), &legacy).unwrap();
+
+    assert_eq!(ids_by_summary(&cmd), before);
+    assert!(!legacy.exists());
+    assert!(repo.ids_file().exists());
+}
+
+#[test]
+fn test_id_grace_period_keeps_released_ids() {
+    let (repo, cmd) = test_setup!();
+
+    let config = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(config.path().join("rstask")).unwrap();
+    std::fs::write(
+        config.path().join("rstask").join("config.styx"),
+        "id_grace_minutes 60\n",
+    )
+    .unwrap();
+    let env = [("XDG_CONFIG_HOME", config.path().to_str().unwrap())];
+
+    for summary in ["One", "Two", "Three"] {
+        cmd.run_with_env(&["add", summary], &env).assert_success();
+    }
+    cmd.run_with_env(&["done", "2"], &env).assert_success();
+    cmd.run_with_env(&["add", "Four"], &env).assert_success();
+    assert_eq!(repo.task_by_summary("Four").unwrap().id, 4);
+
+    // Without the setting, the freed ID goes to the next task
+    cmd.run(&["done", "3"]).assert_success();
+    cmd.run(&["add", "Five"]).assert_success();
+    assert_eq!(repo.task_by_summary("Five").unwrap().id, 2);
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+/// Answers each request with the next (status, body) and returns the request
+/// lines and bodies it received
+fn serve(responses: Vec<(u16, &'static str)>) -> (String, thread::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = thread::spawn(move || {
+        let mut requests = Vec::new();
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = v.trim().parse().unwrap();
+                }
+            }
+            let mut received = vec![0; length];
+            reader.read_exact(&mut received).unwrap();
+            requests.push(format!(
+                "{} {}",
+                request.trim_end(),
+                String::from_utf8_lossy(&received)
+            ));
+
+            write!(
+                stream,
+                "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+        requests
+    });
+
+    (url, handle)
+}
+
+fn config(url: &str, extra: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("rstask")).unwrap();
+    std::fs::write(
+        dir.path().join("rstask").join("config.styx"),
+        format!("jira_url {}\n{}", url, extra),
+    )
+    .unwrap();
+    dir
+}
+
+const SEARCH: &str = r#"{"isLast": true, "issues": [
+    {"key": "OPS-42", "fields": {"summary": "Rotate TLS certificates",
+        "description": "Before they expire", "labels": ["security"],
+        "priority": {"name": "High"}, "duedate": "2030-06-01"}},
+    {"key": "OPS-43", "fields": {"summary": "Update runbook", "priority": {"name": "Medium"}}}
+]}"#;
+
+#[test]
+fn test_jira_import_skips_linked_issues() {
+    let (repo, cmd) = test_setup!();
+    let (url, server) = serve(vec![(200, SEARCH), (200, SEARCH)]);
+    let config = config(&url, "");
+    let env = [
+        ("XDG_CONFIG_HOME", config.path().to_str().unwrap()),
+        ("RSTASK_JIRA_TOKEN", "secret"),
+    ];
+
+    let result = cmd.run_with_env(&["jira", "import", "project = OPS"], &env);
+    result.assert_success();
+    assert!(result.stdout().contains("(OPS-42)"));
+    repo.assert_last_commit("Imported 2 issues from Jira");
+
+    let task = repo.task_by_summary("Rotate TLS certificates").unwrap();
+    assert_eq!(task.jira, "OPS-42");
+    assert_eq!(task.priority, "P1");
+    assert_eq!(task.tags, vec!["security"]);
+    assert!(task.due.is_some());
+
+    let result = cmd.run_with_env(&["jira", "import", "project = OPS"], &env);
+    result.assert_success();
+    assert!(result.stdout().contains("No new issues"));
+
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("GET /rest/api/2/search/jql?jql=project%20%3D%20OPS&"));
+}
+
+#[test]
+fn test_jira_link_and_transition_on_done() {
+    let (repo, cmd) = test_setup!();
+    let (url, server) = serve(vec![
+        (
+            200,
+            r#"{"transitions": [{"id": "11", "name": "Start", "to": {"name": "In Progress"}},
+                {"id": "31", "name": "Close", "to": {"name": "Done"}}]}"#,
+        ),
+        (204, ""),
+    ]);
+    let config = config(&url, "jira_done_transition Done\n");
+    let env = [("XDG_CONFIG_HOME", config.path().to_str().unwrap())];
+
+    cmd.run_with_env(&["add", "Rotate TLS certificates"], &env)
+        .assert_success();
+    cmd.run_with_env(&["jira", "link", "1", "ops-7"], &env)
+        .assert_success();
+    repo.assert_last_commit("Linked Rotate TLS certificates to OPS-7");
+    assert_eq!(
+        repo.task_by_summary("Rotate TLS certificates")
+            .unwrap()
+            .jira,
+        "OPS-7"
+    );
+
+    let result = cmd.run_with_env(&["done", "1"], &env);
+    result.assert_success();
+    assert!(result.stdout().contains("Moved OPS-7 to Done"));
+
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("GET /rest/api/2/issue/OPS-7/transitions"));
+    assert!(requests[1].starts_with("POST /rest/api/2/issue/OPS-7/transitions"));
+    assert!(requests[1].ends_with(r#"{"transition":{"id":"31"}}"#));
+
+    let result = cmd.run_with_env(&["jira", "link", "1", "not a key"], &env);
+    result.assert_failure();
+}
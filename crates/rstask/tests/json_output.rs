@@ -0,0 +1,32 @@
+mod common;
+
+#[test]
+fn test_compact_json_output() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Pay rent", "+home"]).assert_success();
+    cmd.run(&["add", "Write report", "project:work"])
+        .assert_success();
+
+    let pretty = cmd.run(&["next"]);
+    pretty.assert_success();
+    assert!(pretty.stdout().lines().count() > 2);
+
+    let compact = cmd.run(&["--compact", "next"]);
+    compact.assert_success();
+    assert_eq!(compact.stdout().lines().count(), 1);
+
+    let pretty_tasks: serde_json::Value = serde_json::from_str(&pretty.stdout()).unwrap();
+    let compact_tasks: serde_json::Value = serde_json::from_str(&compact.stdout()).unwrap();
+    assert_eq!(pretty_tasks, compact_tasks);
+    assert_eq!(compact_tasks.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_empty_json_output() {
+    let (_repo, cmd) = test_setup!();
+
+    let result = cmd.run(&["--compact", "next"]);
+    result.assert_success();
+    assert_eq!(result.stdout(), "[]\n");
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use std::fs;
+use std::io::Write;
+
+#[test]
+fn test_waits_for_another_instance_then_gives_up() {
+    let (repo, cmd) = test_setup!();
+    cmd.run(&["add", "Fix the build"]).assert_success();
+
+    let config_home = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_home.path().join("rstask")).unwrap();
+    fs::write(
+        config_home.path().join("rstask").join("config.styx"),
+        "lock_timeout_seconds 1\n",
+    )
+    .unwrap();
+    let env = [("XDG_CONFIG_HOME", config_home.path().to_str().unwrap())];
+
+    // Another instance in the middle of saving
+    let mut held = fs::File::create(repo.path().join(".git/rstask/lock")).unwrap();
+    held.lock().unwrap();
+    write!(held, "4242").unwrap();
+
+    let result = cmd.run_with_env(&["add", "Pay rent"], &env);
+    result.assert_failure();
+    assert!(
+        result
+            .stderr()
+            .contains("another rstask instance (pid 4242) is running")
+    );
+    assert!(repo.task_by_summary("Pay rent").is_none());
+
+    // Reading doesn't need the lock
+    cmd.run_with_env(&["next"], &env).assert_success();
+
+    held.unlock().unwrap();
+    cmd.run_with_env(&["add", "Pay rent"], &env)
+        .assert_success();
+    assert!(repo.task_by_summary("Pay rent").is_some());
+}
+
+#[test]
+fn test_concurrent_adds_get_distinct_ids() {
+    let (repo, cmd) = test_setup!();
+
+    let summaries: Vec<String> = (1..=6).map(|n| format!("Task {}", n)).collect();
+    let outputs: Vec<String> = std::thread::scope(|scope| {
+        let runs: Vec<_> = summaries
+            .iter()
+            .map(|summary| {
+                let cmd = &cmd;
+                scope.spawn(move || {
+                    let result = cmd.run(&["add", summary]);
+                    result.assert_success();
+                    result.stdout()
+                })
+            })
+            .collect();
+        runs.into_iter().map(|run| run.join().unwrap()).collect()
+    });
+
+    // Each process reports the ID it handed out, and they must all differ
+    let mut reported: Vec<String> = outputs
+        .iter()
+        .filter_map(|out| out.lines().find(|l| l.starts_with("Added ")))
+        .map(|line| line.split(':').next().unwrap().to_string())
+        .collect();
+    reported.sort();
+    reported.dedup();
+    assert_eq!(reported.len(), 6, "{:?}", outputs);
+
+    let mut ids: Vec<i32> = repo.tasks().iter().map(|t| t.id).collect();
+    ids.sort();
+    assert_eq!(ids, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(repo.commit_count(), 6);
+    repo.assert_clean();
+}
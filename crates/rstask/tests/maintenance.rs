@@ -0,0 +1,60 @@
+mod common;
+
+use rstask_core::local_state::load_ids;
+
+#[test]
+fn test_maintenance_removes_duplicate_files() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Fix the build", "+ci"]).assert_success();
+    let task = repo.task_by_summary("Fix the build").unwrap();
+
+    // A copy left behind by a sync conflict
+    let pending = repo
+        .path()
+        .join("pending")
+        .join(format!("{}.md", task.uuid));
+    let copy = repo
+        .path()
+        .join("resolved")
+        .join(format!("{}.md", task.uuid));
+    std::fs::create_dir_all(copy.parent().unwrap()).unwrap();
+    std::fs::copy(&pending, &copy).unwrap();
+    repo.commit_all("Sync conflict");
+
+    let result = cmd.run(&["maintenance"]);
+    result.assert_success();
+    assert!(result.stdout().contains("Duplicate task files removed: 1"));
+    assert!(pending.exists());
+    assert!(!copy.exists());
+    repo.assert_last_commit("Removed 1 duplicate task file");
+    repo.assert_clean();
+
+    let tasks = cmd.run(&["next"]).parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].tags, vec!["ci"]);
+}
+
+#[test]
+fn test_maintenance_prunes_ids_file() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "One"]).assert_success();
+    cmd.run(&["add", "Two"]).assert_success();
+    let two = repo.task_by_summary("Two").unwrap();
+
+    // Removed on another machine and pulled
+    std::fs::remove_file(repo.path().join("pending").join(format!("{}.md", two.uuid))).unwrap();
+    repo.commit_all("Remove Two");
+
+    let ids_file = repo.ids_file();
+    assert!(load_ids(&ids_file).contains_key(&two.uuid));
+
+    let result = cmd.run(&["maintenance"]);
+    result.assert_success();
+    assert!(result.stdout().contains("Stale IDs file entries pruned: 1"));
+
+    let ids = load_ids(&ids_file);
+    assert_eq!(ids.len(), 1);
+    assert!(!ids.contains_key(&two.uuid));
+}
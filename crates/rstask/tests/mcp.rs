@@ -0,0 +1,82 @@
+mod common;
+
+use serde_json::{Value, json};
+
+fn responses(stdout: &str) -> Vec<Value> {
+    stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn call(id: i64, tool: &str, arguments: Value) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": { "name": tool, "arguments": arguments },
+    })
+    .to_string()
+}
+
+#[test]
+fn test_mcp_session() {
+    let (_repo, cmd) = test_setup!();
+
+    let input = [
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": { "protocolVersion": "2025-03-26", "capabilities": {} },
+        })
+        .to_string(),
+        json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }).to_string(),
+        call(
+            2,
+            "add_task",
+            json!({ "summary": "Pay rent", "project": "home", "tags": ["bills"] }),
+        ),
+        call(
+            3,
+            "annotate",
+            json!({ "id": 1, "note": "landlord prefers transfer" }),
+        ),
+        call(4, "list_tasks", json!({ "filter": "project:home" })),
+        call(5, "resolve_task", json!({ "id": 1 })),
+        call(6, "resolve_task", json!({ "id": 1 })),
+    ]
+    .join("\n");
+
+    let result = cmd.run_with_stdin(&["mcp"], &input);
+    result.assert_success();
+    let responses = responses(&result.stdout());
+
+    // The notification gets no response
+    assert_eq!(responses.len(), 6);
+    assert_eq!(responses[0]["result"]["protocolVersion"], "2025-03-26");
+    assert_eq!(
+        responses[1]["result"]["content"][0]["text"],
+        "Added 1: Pay rent"
+    );
+    assert_eq!(responses[2]["result"]["isError"], false);
+
+    let listed: Vec<Value> = serde_json::from_str(
+        responses[3]["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["tags"], json!(["bills"]));
+    assert_eq!(listed[0]["notes"], "landlord prefers transfer");
+
+    assert_eq!(responses[4]["result"]["isError"], false);
+    assert_eq!(responses[5]["result"]["isError"], true);
+
+    let result = cmd.run(&["show-resolved"]);
+    result.assert_success();
+    let tasks = result.parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "Pay rent");
+}
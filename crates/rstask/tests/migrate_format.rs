@@ -0,0 +1,71 @@
+mod common;
+
+use std::path::Path;
+
+fn files_with_extension(dir: &Path, ext: &str) -> usize {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().extension().is_some_and(|e| e == ext))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+#[test]
+fn test_migrate_format_round_trip() {
+    let (repo, cmd) = test_setup!();
+    let config = tempfile::tempdir().unwrap();
+    let env = [("XDG_CONFIG_HOME", config.path().to_str().unwrap())];
+
+    cmd.run(&["add", "Fix the build", "/", "Check the CI logs"])
+        .assert_success();
+    cmd.run(&["add", "Pay rent"]).assert_success();
+    cmd.run(&["done", "2"]).assert_success();
+
+    let result = cmd.run_with_env(&["migrate-format", "yml"], &env);
+    result.assert_success();
+    assert!(result.stdout().contains("Converted 2 task files to yml"));
+    repo.assert_last_commit("Converted 2 task files to yml");
+    repo.assert_clean();
+    assert_eq!(files_with_extension(&repo.path().join("pending"), "yml"), 1);
+    assert_eq!(
+        files_with_extension(&repo.path().join("resolved"), "yml"),
+        1
+    );
+    assert_eq!(files_with_extension(&repo.path().join("pending"), "md"), 0);
+    assert_eq!(files_with_extension(&repo.path().join("resolved"), "md"), 0);
+
+    let config_text =
+        std::fs::read_to_string(config.path().join("rstask").join("config.styx")).unwrap();
+    assert!(config_text.contains("task_format yml"));
+
+    // New changes keep the saved format
+    cmd.run_with_env(&["add", "Water plants"], &env)
+        .assert_success();
+    assert_eq!(files_with_extension(&repo.path().join("pending"), "yml"), 2);
+
+    let task = repo.task_by_summary("Fix the build").unwrap();
+    assert_eq!(task.notes.trim(), "Check the CI logs");
+
+    let result = cmd.run_with_env(&["migrate-format", "md"], &env);
+    result.assert_success();
+    assert!(result.stdout().contains("Converted 3 task files to md"));
+    assert_eq!(files_with_extension(&repo.path().join("pending"), "yml"), 0);
+    assert_eq!(files_with_extension(&repo.path().join("pending"), "md"), 2);
+    let task = repo.task_by_summary("Fix the build").unwrap();
+    assert_eq!(task.notes.trim(), "Check the CI logs");
+
+    // Nothing left to convert
+    let result = cmd.run_with_env(&["migrate-format", "md"], &env);
+    result.assert_success();
+    assert!(result.stdout().contains("Converted 0 task files to md"));
+}
+
+#[test]
+fn test_migrate_format_rejects_unknown_format() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["migrate-format", "json"]).assert_failure();
+}
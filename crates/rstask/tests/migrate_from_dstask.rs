@@ -0,0 +1,212 @@
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+const OPEN_UUID: &str = "0a1b2c3d-4e5f-4a6b-8c7d-8e9f0a1b2c3d";
+const RESOLVED_UUID: &str = "1b2c3d4e-5f6a-4b7c-9d8e-9f0a1b2c3d4e";
+
+/// A repository as dstask leaves it: YAML task files, and gob-encoded IDs
+/// and context under .git/dstask
+fn dstask_repo(path: &Path) {
+    Command::new("git")
+        .args(["init", "--quiet"])
+        .arg(path)
+        .status()
+        .unwrap();
+    for dir in ["pending", "resolved", ".git/dstask"] {
+        std::fs::create_dir_all(path.join(dir)).unwrap();
+    }
+
+    std::fs::write(
+        path.join("pending").join(format!("{}.yml", OPEN_UUID)),
+        "summary: Renew passport\n\
+         notes: Photos are in the drawer\n\
+         tags:\n\
+         - admin\n\
+         project: home\n\
+         priority: P1\n\
+         delegatedto: \"\"\n\
+         subtasks: []\n\
+         dependencies: []\n\
+         created: 2024-05-01T09:30:00.123456789+02:00\n\
+         resolved: 0001-01-01T00:00:00Z\n\
+         due: 0001-01-01T00:00:00Z\n",
+    )
+    .unwrap();
+    std::fs::write(
+        path.join("resolved").join(format!("{}.yml", RESOLVED_UUID)),
+        "summary: Book dentist\n\
+         notes: \"\"\n\
+         tags: []\n\
+         project: \"\"\n\
+         priority: P2\n\
+         delegatedto: \"\"\n\
+         subtasks: []\n\
+         dependencies: []\n\
+         created: 2024-04-01T08:00:00Z\n\
+         resolved: 2024-04-03T17:00:00Z\n\
+         due: 0001-01-01T00:00:00Z\n",
+    )
+    .unwrap();
+
+    // map[string]int{OPEN_UUID: 7}
+    let mut ids = vec![
+        0x0e, 0xff, 0x81, 0x04, 0x01, 0x02, 0xff, 0x82, 0x00, 0x01, 0x0c, 0x01, 0x04, 0x00, 0x00,
+        0x2a, 0xff, 0x82, 0x00, 0x01, 0x24,
+    ];
+    ids.extend_from_slice(OPEN_UUID.as_bytes());
+    ids.push(0x0e);
+    std::fs::write(path.join(".git/dstask/ids.bin"), ids).unwrap();
+
+    // State{Context: Query{Tags: []string{"admin"}}}, with Query cut down
+    // to Cmd and Tags
+    let mut state = vec![
+        0x20, 0xff, 0x81, 0x03, 0x01, 0x01, 0x05, b'S', b't', b'a', b't', b'e', 0x01, 0xff, 0x82,
+        0x00, 0x01, 0x01, 0x01, 0x07, b'C', b'o', b'n', b't', b'e', b'x', b't', 0x01, 0xff, 0x84,
+        0x00, 0x00, 0x00,
+    ];
+    state.extend_from_slice(&[
+        0x25, 0xff, 0x83, 0x03, 0x01, 0x01, 0x05, b'Q', b'u', b'e', b'r', b'y', 0x01, 0xff, 0x84,
+        0x00, 0x01, 0x02, 0x01, 0x03, b'C', b'm', b'd', 0x01, 0x0c, 0x00, 0x01, 0x04, b'T', b'a',
+        b'g', b's', 0x01, 0xff, 0x86, 0x00, 0x00, 0x00,
+    ]);
+    state.extend_from_slice(&[
+        0x16, 0xff, 0x85, 0x02, 0x01, 0x01, 0x08, b'[', b']', b's', b't', b'r', b'i', b'n', b'g',
+        0x01, 0xff, 0x86, 0x00, 0x01, 0x0c, 0x00, 0x00,
+    ]);
+    state.extend_from_slice(&[
+        0x0d, 0xff, 0x82, 0x01, 0x02, 0x01, 0x05, b'a', b'd', b'm', b'i', b'n', 0x00, 0x00,
+    ]);
+    std::fs::write(path.join(".git/dstask/state.bin"), state).unwrap();
+}
+
+#[test]
+fn test_migrate_from_dstask() {
+    let (repo, cmd) = test_setup!();
+    let dstask = tempfile::tempdir().unwrap();
+    dstask_repo(dstask.path());
+
+    cmd.run(&["add", "Water plants"]).assert_success();
+
+    let result = cmd.run(&["migrate-from-dstask", dstask.path().to_str().unwrap()]);
+    result.assert_success();
+    assert!(result.stdout().contains("Imported 2 tasks"));
+    assert!(result.stdout().contains("1 keeping their dstask IDs"));
+    assert!(result.stdout().contains("Context set to +admin"));
+    repo.assert_last_commit("Imported 2 tasks from dstask");
+    repo.assert_clean();
+
+    let task = repo.task_by_summary("Renew passport").unwrap();
+    assert_eq!(task.uuid, OPEN_UUID);
+    assert_eq!(task.notes.trim(), "Photos are in the drawer");
+    assert!(
+        repo.path()
+            .join("pending")
+            .join(format!("{}.md", OPEN_UUID))
+            .exists()
+    );
+    let resolved = repo.task_by_summary("Book dentist").unwrap();
+    assert_eq!(resolved.status, "resolved");
+    assert!(resolved.resolved.is_some());
+
+    // The ID dstask showed, and the context limits next to +admin
+    let result = cmd.run(&["next"]);
+    result.assert_success();
+    let tasks = result.parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, 7);
+
+    let result = cmd.run(&["migrate-from-dstask", dstask.path().to_str().unwrap()]);
+    result.assert_success();
+    assert!(result.stdout().contains("Imported 0 tasks"));
+    assert!(result.stdout().contains("Skipped 2"));
+}
+
+#[test]
+fn test_migrate_from_dstask_imports_nothing_from_a_broken_repo() {
+    let (repo, cmd) = test_setup!();
+    let dstask = tempfile::tempdir().unwrap();
+    dstask_repo(dstask.path());
+    std::fs::write(
+        dstask
+            .path()
+            .join("pending")
+            .join("2c3d4e5f-6a7b-4c8d-8e9f-0a1b2c3d4e5f.yml"),
+        "summary: [unclosed\n",
+    )
+    .unwrap();
+
+    let result = cmd.run(&["migrate-from-dstask", dstask.path().to_str().unwrap()]);
+    result.assert_failure();
+    assert!(result.stderr().contains("1 task file(s)"));
+    assert!(repo.task_by_summary("Renew passport").is_none());
+
+    let result = cmd.run(&[
+        "migrate-from-dstask",
+        repo.path().join("nowhere").to_str().unwrap(),
+    ]);
+    result.assert_failure();
+    assert!(result.stderr().contains("isn't a dstask repository"));
+}
+
+#[test]
+fn test_dstask_compat_writes_dstask_files() {
+    let (repo, cmd) = test_setup!();
+    let config = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(config.path().join("rstask")).unwrap();
+    std::fs::write(
+        config.path().join("rstask").join("config.styx"),
+        "dstask_compat true\n",
+    )
+    .unwrap();
+    let env = [("XDG_CONFIG_HOME", config.path().to_str().unwrap())];
+
+    cmd.run_with_env(&["add", "Renew passport", "+admin"], &env)
+        .assert_success();
+
+    let task = repo.task_by_summary("Renew passport").unwrap();
+    let data = std::fs::read_to_string(
+        repo.path()
+            .join("pending")
+            .join(format!("{}.yml", task.uuid)),
+    )
+    .unwrap();
+    assert!(data.starts_with("summary: Renew passport\nnotes: \"\"\ntags:\n- admin\n"));
+    assert!(data.contains("resolved: 0001-01-01T00:00:00Z\n"));
+    assert!(!data.contains("status:"));
+}
+
+#[test]
+fn test_dstask_compat_refuses_fields_dstask_drops() {
+    let (repo, cmd) = test_setup!();
+    let config = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(config.path().join("rstask")).unwrap();
+    std::fs::write(
+        config.path().join("rstask").join("config.styx"),
+        "dstask_compat true\n",
+    )
+    .unwrap();
+    let env = [("XDG_CONFIG_HOME", config.path().to_str().unwrap())];
+
+    cmd.run_with_env(&["add", "Renew passport"], &env)
+        .assert_success();
+    let commits = repo.commit_count();
+
+    for args in [
+        &["add", "Book photo", "estimate:30m"][..],
+        &["modify", "1", "estimate:1h"],
+        &["modify", "1", "schedule:every:week"],
+        &["jira", "link", "1", "ADMIN-7"],
+        &["template", "Weekly review", "schedule:every:monday"],
+        &["template", "1", "schedule:every:monday"],
+        &["tick"],
+    ] {
+        let result = cmd.run_with_env(args, &env);
+        result.assert_failure();
+        assert!(result.stderr().contains("dstask_compat"), "{:?}", args);
+    }
+    assert_eq!(repo.commit_count(), commits);
+    assert!(repo.task_by_summary("Book photo").is_none());
+    assert!(repo.task_by_summary("Weekly review").is_none());
+}
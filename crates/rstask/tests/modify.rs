@@ -69,3 +69,27 @@ fn test_modify_tasks_in_context() {
         "tags should have been modified"
     );
 }
+
+#[test]
+fn test_modify_and_done_with_id_ranges_and_lists() {
+    let (repo, cmd) = test_setup!();
+
+    for summary in ["one", "two", "three", "four", "five"] {
+        cmd.run(&["add", summary]).assert_success();
+    }
+
+    cmd.run(&["modify", "1,4", "+urgent"]).assert_success();
+    assert_eq!(repo.task_by_summary("one").unwrap().tags, vec!["urgent"]);
+    assert_eq!(repo.task_by_summary("four").unwrap().tags, vec!["urgent"]);
+    assert!(repo.task_by_summary("two").unwrap().tags.is_empty());
+
+    cmd.run(&["done", "2-4"]).assert_success();
+    repo.assert_last_commit("Resolved 3 tasks");
+    let open: Vec<String> = cmd
+        .run(&["next"])
+        .parse_tasks()
+        .into_iter()
+        .map(|t| t.summary)
+        .collect();
+    assert_eq!(open, vec!["one", "five"]);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+#[test]
+fn test_add_notes_new_project_and_tags() {
+    let (_repo, cmd) = test_setup!();
+
+    let result = cmd.run(&["add", "one", "project:website", "+work"]);
+    result.assert_success();
+    assert!(result.stderr().contains("creating new project 'website'"));
+    assert!(result.stderr().contains("creating new tag 'work'"));
+
+    let result = cmd.run(&["add", "two", "project:website", "+work"]);
+    result.assert_success();
+    assert!(!result.stderr().contains("creating new"));
+
+    let result = cmd.run(&["add", "three", "project:webiste", "+work"]);
+    result.assert_success();
+    assert!(result.stderr().contains("creating new project 'webiste'"));
+    assert!(!result.stderr().contains("creating new tag"));
+}
+
+#[test]
+fn test_resolved_tasks_count_as_known() {
+    let (_repo, cmd) = test_setup!();
+
+    let result = cmd.run(&["add", "one", "+archive"]);
+    result.assert_success();
+    let result = cmd.run(&["done", "1"]);
+    result.assert_success();
+
+    let result = cmd.run(&["add", "two", "+archive"]);
+    result.assert_success();
+    assert!(!result.stderr().contains("creating new"));
+
+    // Known from an open task, from resolved ones only and from neither
+    let id = cmd.run(&["next"]).parse_tasks()[0].id.to_string();
+    cmd.run(&["done", &id]).assert_success();
+    cmd.run(&["add", "three", "project:website"])
+        .assert_success();
+    let result = cmd.run(&["add", "four", "project:website", "+archive", "+fresh"]);
+    result.assert_success();
+    assert!(!result.stderr().contains("creating new project"));
+    assert!(!result.stderr().contains("creating new tag 'archive'"));
+    assert!(result.stderr().contains("creating new tag 'fresh'"));
+}
+
+#[test]
+fn test_no_new_refuses_new_taxonomy() {
+    let (_repo, cmd) = test_setup!();
+
+    let result = cmd.run(&["add", "one", "+work"]);
+    result.assert_success();
+
+    let result = cmd.run(&["add", "--no-new", "two", "+wrok"]);
+    result.assert_failure();
+    assert!(
+        result
+            .stderr()
+            .contains("refusing to create new tag 'wrok'")
+    );
+
+    let result = cmd.run(&["modify", "--no-new", "1", "project:webiste"]);
+    result.assert_failure();
+    assert!(result.stderr().contains("project 'webiste'"));
+
+    let result = cmd.run(&["modify", "--no-new", "1", "+work", "P1"]);
+    result.assert_success();
+
+    let result = cmd.run(&["next"]);
+    result.assert_success();
+    let tasks = result.parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].project, "");
+    assert_eq!(tasks[0].priority, "P1");
+}
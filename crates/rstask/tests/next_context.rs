@@ -96,3 +96,38 @@ fn test_context_from_env_var() {
     let tasks = result.parse_tasks();
     assert_eq!(tasks[0].summary, "two", "project:beta is on-disk context");
 }
+
+#[test]
+fn test_text_negation_in_queries_and_context() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Weekly sync meeting"]).assert_success();
+    cmd.run(&["add", "Write report"]).assert_success();
+    cmd.run(&["add", "Review report", "/", "before the weekly sync"])
+        .assert_success();
+
+    let summaries = |args: &[&str]| -> Vec<String> {
+        let mut summaries: Vec<String> = cmd
+            .run(args)
+            .parse_tasks()
+            .into_iter()
+            .map(|t| t.summary)
+            .collect();
+        summaries.sort();
+        summaries
+    };
+
+    // Notes count too
+    assert_eq!(summaries(&["next", "not:Sync"]), vec!["Write report"]);
+    assert_eq!(
+        summaries(&["next", "-\"write\""]),
+        vec!["Review report", "Weekly sync meeting"]
+    );
+
+    cmd.run(&["context", "not:meeting"]).assert_success();
+    assert_eq!(summaries(&["next"]), vec!["Review report", "Write report"]);
+    assert_eq!(
+        summaries(&["next", "report", "not:review"]),
+        vec!["Write report"]
+    );
+}
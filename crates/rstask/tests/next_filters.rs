@@ -75,3 +75,30 @@ fn test_next_project_filter() {
     let tasks = result.parse_tasks();
     assert_eq!(tasks[0].summary, "one");
 }
+
+#[test]
+fn test_next_count_and_quiet() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "+work", "Write report", "P1"])
+        .assert_success();
+    cmd.run(&["add", "+work", "Plan offsite"]).assert_success();
+    cmd.run(&["add", "Buy milk"]).assert_success();
+
+    let result = cmd.run(&["--count", "next", "+work"]);
+    result.assert_success();
+    assert_eq!(result.stdout(), "2\n");
+
+    let result = cmd.run(&["show-open", "-q"]);
+    result.assert_success();
+    assert_eq!(
+        result.stdout(),
+        "1 Write report\n2 Plan offsite\n3 Buy milk\n"
+    );
+
+    let result = cmd.run(&["--count", "show-resolved"]);
+    result.assert_success();
+    assert_eq!(result.stdout(), "0\n");
+
+    cmd.run(&["--count", "--quiet", "next"]).assert_failure();
+}
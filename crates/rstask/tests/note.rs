@@ -0,0 +1,84 @@
+mod common;
+
+#[test]
+fn test_note_appends_without_editor() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Fix the build", "/", "Broken since Monday"])
+        .assert_success();
+    // No editor is set up, so this would fail if one were opened
+    let result = cmd.run_with_env(
+        &["note", "1", "Blame", "+ci", "project:infra"],
+        &[("EDITOR", "false")],
+    );
+    result.assert_success();
+    repo.assert_last_commit("Added note to Fix the build");
+
+    let task = repo.task_by_summary("Fix the build").unwrap();
+    assert_eq!(task.notes, "Broken since Monday\nBlame +ci project:infra");
+    assert!(task.tags.is_empty());
+}
+
+#[test]
+fn test_note_clear() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Fix the build", "/", "Old notes"])
+        .assert_success();
+    cmd.run(&["note", "1", "--clear"]).assert_success();
+    repo.assert_last_commit("Cleared notes of Fix the build");
+    assert_eq!(repo.task_by_summary("Fix the build").unwrap().notes, "");
+
+    let result = cmd.run(&["note", "1", "--clear"]);
+    result.assert_success();
+    assert!(result.stdout().contains("has no notes"));
+
+    cmd.run(&["note", "--clear", "1", "Fresh", "start"])
+        .assert_success();
+    assert_eq!(
+        repo.task_by_summary("Fix the build").unwrap().notes,
+        "Fresh start"
+    );
+}
+
+/// Writes `config` as the config file in `config_home`
+fn write_config(config_home: &tempfile::TempDir, config: &str) {
+    std::fs::create_dir_all(config_home.path().join("rstask")).unwrap();
+    std::fs::write(
+        config_home.path().join("rstask").join("config.styx"),
+        config,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_unreadable_config_with_notes_key_is_an_error() {
+    let (repo, cmd) = test_setup!();
+    let config_home = tempfile::TempDir::new().unwrap();
+    write_config(
+        &config_home,
+        "notes_key_file ~/notes.key\nsync_frequency bogus\n",
+    );
+    let env = [("XDG_CONFIG_HOME", config_home.path().to_str().unwrap())];
+
+    // Falling back to the defaults would commit the notes in plain text
+    let result = cmd.run_with_env(&["add", "Secret", "/", "the notes"], &env);
+    result.assert_failure();
+    assert!(result.stderr().contains("which sets notes_key_file"));
+    assert!(repo.task_by_summary("Secret").is_none());
+}
+
+#[test]
+fn test_unreadable_config_mentioning_notes_key_in_a_comment_falls_back() {
+    let (repo, cmd) = test_setup!();
+    let config_home = tempfile::TempDir::new().unwrap();
+    write_config(
+        &config_home,
+        "// notes_key_file ~/notes.key\nsync_frequency bogus\n",
+    );
+    let env = [("XDG_CONFIG_HOME", config_home.path().to_str().unwrap())];
+
+    cmd.run_with_env(&["add", "Plain", "/", "the notes"], &env)
+        .assert_success();
+    assert!(repo.task_by_summary("Plain").is_some());
+}
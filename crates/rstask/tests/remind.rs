@@ -0,0 +1,46 @@
+mod common;
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+#[test]
+fn test_remind_notifies_due_tasks_once() {
+    let (repo, cmd) = test_setup!();
+
+    // Deliver notifications through a script that appends the body to a file
+    let out = repo.path().join("notified.txt");
+    let script = repo.path().join("notify.sh");
+    fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\necho \"$RSTASK_NOTIFY_BODY\" >> {}\n",
+            out.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let config_home = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_home.path().join("rstask")).unwrap();
+    fs::write(
+        config_home.path().join("rstask").join("config.styx"),
+        format!(
+            "notification_backends (command)\nnotify_command {}\nremind_horizon_minutes 2880\n",
+            script.display()
+        ),
+    )
+    .unwrap();
+    let env = [("XDG_CONFIG_HOME", config_home.path().to_str().unwrap())];
+
+    cmd.run(&["add", "Pay rent", "due:today"]).assert_success();
+    cmd.run(&["add", "Renew passport", "due:2099-01-01"])
+        .assert_success();
+    cmd.run(&["add", "Someday"]).assert_success();
+
+    cmd.run_with_env(&["remind"], &env).assert_success();
+    assert_eq!(fs::read_to_string(&out).unwrap(), "1: Pay rent\n");
+
+    // The task is snoozed, so a second run stays quiet
+    cmd.run_with_env(&["remind"], &env).assert_success();
+    assert_eq!(fs::read_to_string(&out).unwrap(), "1: Pay rent\n");
+}
@@ -0,0 +1,41 @@
+mod common;
+
+#[test]
+fn test_repo_file_overrides_preferences() {
+    let (repo, cmd) = test_setup!();
+
+    std::fs::write(
+        repo.path().join(".rstask.toml"),
+        "default_project = \"website\"\nbulk_commit_strategy = \"single\"\n",
+    )
+    .unwrap();
+    repo.commit_all("Add repository settings");
+
+    cmd.run(&["add", "Fix header"]).assert_success();
+    cmd.run(&["add", "Fix footer", "project:blog"])
+        .assert_success();
+    assert_eq!(
+        repo.task_by_summary("Fix header").unwrap().project,
+        "website"
+    );
+    assert_eq!(repo.task_by_summary("Fix footer").unwrap().project, "blog");
+
+    cmd.run(&["modify", "1", "2", "+urgent"]).assert_success();
+    repo.assert_last_commit("Modified 2 tasks");
+}
+
+#[test]
+fn test_invalid_repo_file_is_ignored() {
+    let (repo, cmd) = test_setup!();
+
+    std::fs::write(
+        repo.path().join(".rstask.toml"),
+        "sync_frequency = \"never\"\n",
+    )
+    .unwrap();
+
+    let result = cmd.run(&["add", "Fix header"]);
+    result.assert_success();
+    assert!(result.stderr().contains("Warning: ignoring"));
+    assert_eq!(repo.task_by_summary("Fix header").unwrap().project, "");
+}
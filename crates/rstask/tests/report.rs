@@ -0,0 +1,41 @@
+mod common;
+
+#[test]
+fn test_report_md_summarises_the_week() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Mend fence", "project:garden"])
+        .assert_success();
+    cmd.run(&["add", "Ship release", "+work", "P1"])
+        .assert_success();
+    cmd.run(&["add", "Water plants"]).assert_success();
+    cmd.run(&["done", "1"]).assert_success();
+    cmd.run(&["start", "2"]).assert_success();
+
+    let result = cmd.run(&["report", "md"]);
+    result.assert_success();
+    let report = result.stdout();
+    assert!(report.starts_with("# Status report: "));
+    assert!(report.contains("## Done (1)\n\n### garden\n\n- Mend fence\n"));
+    assert!(report.contains("## Still open (1)\n\n- Ship release (active, P1)\n"));
+    assert!(report.contains("## New (2)\n\n- Ship release (active, P1)\n- Water plants\n"));
+}
+
+#[test]
+fn test_report_md_respects_context_and_since() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Ship release", "+work"]).assert_success();
+    cmd.run(&["add", "Water plants"]).assert_success();
+    cmd.run(&["context", "+work"]).assert_success();
+
+    let result = cmd.run(&["report", "md", "--since", "3d"]);
+    result.assert_success();
+    let report = result.stdout();
+    assert!(report.contains("Context: `+work`"));
+    assert!(report.contains("- Ship release"));
+    assert!(!report.contains("Water plants"));
+
+    let result = cmd.run(&["report", "md", "--since", "whenever"]);
+    result.assert_failure();
+}
@@ -0,0 +1,46 @@
+mod common;
+
+fn summaries(section: &serde_json::Value) -> Vec<&str> {
+    section
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["summary"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_review_lists_overdue_and_unorganised() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Renew passport", "+admin", "due:yesterday"])
+        .assert_success();
+    cmd.run(&["add", "Call plumber"]).assert_success();
+    cmd.run(&["add", "Plan trip", "+travel", "due:eom"])
+        .assert_success();
+
+    let result = cmd.run(&["review"]);
+    result.assert_success();
+    let review: serde_json::Value = serde_json::from_str(&result.stdout()).unwrap();
+
+    assert_eq!(summaries(&review["overdue"]), vec!["Renew passport"]);
+    assert_eq!(summaries(&review["unorganised"]), vec!["Call plumber"]);
+    // Everything was just added
+    assert!(summaries(&review["stale"]).is_empty());
+}
+
+#[test]
+fn test_review_filter_narrows_overdue() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Renew passport", "+admin", "due:yesterday"])
+        .assert_success();
+    cmd.run(&["add", "Book flights", "+travel", "due:yesterday"])
+        .assert_success();
+
+    let result = cmd.run(&["review", "+travel"]);
+    result.assert_success();
+    let review: serde_json::Value = serde_json::from_str(&result.stdout()).unwrap();
+
+    assert_eq!(summaries(&review["overdue"]), vec!["Book flights"]);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use rstask_core::task::{SubTask, Task};
+
+#[test]
+fn test_show_includes_related_information() {
+    let (repo, cmd) = test_setup!();
+
+    let dependency = repo.write_task(Task::new("Order parts".to_string()));
+    let main = repo.write_task(Task {
+        dependencies: vec![dependency.uuid.clone()],
+        subtasks: vec![
+            SubTask {
+                summary: "Measure".to_string(),
+                resolved: true,
+            },
+            SubTask {
+                summary: "Cut".to_string(),
+                resolved: false,
+            },
+        ],
+        notes: "Plans at https://example.com/plans".to_string(),
+        ..Task::new("Build shelf".to_string())
+    });
+    repo.write_task(Task {
+        dependencies: vec![main.uuid.clone()],
+        ..Task::new("Paint shelf".to_string())
+    });
+    repo.commit_all("Import");
+
+    let id = cmd
+        .run(&["next"])
+        .parse_tasks()
+        .into_iter()
+        .find(|t| t.summary == "Build shelf")
+        .unwrap()
+        .id;
+    cmd.run(&["modify", &id.to_string(), "+diy"])
+        .assert_success();
+
+    let result = cmd.run(&["show", &id.to_string()]);
+    result.assert_success();
+    let out = result.stdout();
+
+    let blocked_by = out.find("Blocked by:").unwrap();
+    assert!(out[blocked_by..].contains("pending  Order parts"));
+    assert!(!out.contains("Depends on:"));
+    let blocks = out.find("Blocks:").unwrap();
+    assert!(out[blocks..].contains("Paint shelf"));
+    assert!(out.contains("Subtasks (1/2 done):"));
+    assert!(out.contains("[x] Measure"));
+    assert!(out.contains("[ ] Cut"));
+    assert!(out.contains("Links:"));
+    assert!(out.contains("https://example.com/plans"));
+
+    // Newest change first
+    let history = &out[out.find("History:").unwrap()..];
+    let modified = history.find("Modified Build shelf").unwrap();
+    let imported = history.find("Import").unwrap();
+    assert!(modified < imported);
+}
+
+#[test]
+fn test_show_blocked_lists_tasks_waiting_on_open_ones() {
+    let (repo, cmd) = test_setup!();
+
+    let parts = repo.write_task(Task::new("Order parts".to_string()));
+    let mut wood = Task::new("Buy wood".to_string());
+    wood.status = "resolved".to_string();
+    wood.resolved = Some(chrono::Utc::now());
+    let wood = repo.write_task(wood);
+    repo.write_task(Task {
+        dependencies: vec![parts.uuid.clone(), wood.uuid.clone()],
+        ..Task::new("Build shelf".to_string())
+    });
+    repo.write_task(Task {
+        dependencies: vec![wood.uuid.clone()],
+        ..Task::new("Sand planks".to_string())
+    });
+    repo.commit_all("Import");
+
+    let blocked = cmd.run(&["show-blocked"]).parse_tasks();
+    assert_eq!(blocked.len(), 1);
+    assert_eq!(blocked[0].summary, "Build shelf");
+
+    let result = cmd.run(&["show", &blocked[0].id.to_string()]);
+    result.assert_success();
+    let out = result.stdout();
+    let blocked_by = out.find("Blocked by:").unwrap();
+    let depends = out.find("Depends on:").unwrap();
+    assert!(out[blocked_by..depends].contains("Order parts"));
+    assert!(out[depends..].contains("resolved Buy wood"));
+
+    // Resolving the last open dependency unblocks the task
+    let parts_id = cmd
+        .run(&["next"])
+        .parse_tasks()
+        .into_iter()
+        .find(|t| t.summary == "Order parts")
+        .unwrap()
+        .id;
+    cmd.run(&["done", &parts_id.to_string()]).assert_success();
+    assert!(cmd.run(&["show-blocked"]).parse_tasks().is_empty());
+}
@@ -25,3 +25,34 @@ fn test_show_active() {
     let tasks = result.parse_tasks();
     assert!(tasks.is_empty(), "no tasks should be active");
 }
+
+#[test]
+fn test_single_active_task_pauses_others() {
+    let (repo, cmd) = test_setup!();
+
+    let config = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(config.path().join("rstask")).unwrap();
+    std::fs::write(
+        config.path().join("rstask").join("config.styx"),
+        "single_active_task true\n",
+    )
+    .unwrap();
+    let env = [("XDG_CONFIG_HOME", config.path().to_str().unwrap())];
+
+    cmd.run(&["add", "one"]).assert_success();
+    cmd.run(&["add", "two"]).assert_success();
+    cmd.run_with_env(&["start", "1"], &env).assert_success();
+
+    let result = cmd.run_with_env(&["start", "2"], &env);
+    result.assert_success();
+    assert!(result.stdout().contains("Paused 1: one"));
+    repo.assert_last_commit("Started 1 task");
+
+    let tasks = cmd.run(&["show-active"]).parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "two");
+    assert_eq!(repo.task_by_summary("one").unwrap().status, "paused");
+
+    cmd.run_with_env(&["start", "1", "2"], &env)
+        .assert_failure();
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use rstask_core::task::Task;
+
+fn summaries(result: &common::TestResult) -> Vec<String> {
+    result
+        .parse_tasks()
+        .into_iter()
+        .map(|t| t.summary)
+        .collect()
+}
+
+fn task_modified_days_ago(summary: &str, days: i64) -> Task {
+    Task {
+        modified: Some(Utc::now() - Duration::days(days)),
+        created: Utc::now() - Duration::days(days),
+        ..Task::new(summary.to_string())
+    }
+}
+
+#[test]
+fn test_show_stale_lists_oldest_first() {
+    let (repo, cmd) = test_setup!();
+
+    repo.write_task(task_modified_days_ago("Sort out garage", 45));
+    repo.write_task(task_modified_days_ago("Learn Welsh", 90));
+    repo.commit_all("Add tasks");
+    cmd.run(&["add", "Water plants"]).assert_success();
+
+    let result = cmd.run(&["show-stale"]);
+    result.assert_success();
+    assert_eq!(summaries(&result), vec!["Learn Welsh", "Sort out garage"]);
+
+    let result = cmd.run(&["show-stale", "--days", "60"]);
+    result.assert_success();
+    assert_eq!(summaries(&result), vec!["Learn Welsh"]);
+}
+
+#[test]
+fn test_show_stale_prefers_modified_over_git_history() {
+    let (_repo, cmd) = test_setup!();
+
+    // Committed long ago but saved with today's `modified`, so not stale
+    let long_ago = (Utc::now() - Duration::days(90)).to_rfc3339();
+    cmd.run_with_env(
+        &["add", "Learn Welsh"],
+        &[("GIT_COMMITTER_DATE", &long_ago)],
+    )
+    .assert_success();
+
+    let result = cmd.run(&["show-stale"]);
+    result.assert_success();
+    assert!(summaries(&result).is_empty());
+}
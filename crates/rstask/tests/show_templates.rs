@@ -19,3 +19,31 @@ fn test_show_templates() {
     let tasks = result.parse_tasks();
     assert_eq!(tasks[0].summary, "template1", "should be a template");
 }
+
+#[test]
+fn test_add_from_template_fills_placeholders() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&[
+        "template",
+        "Release {{version}}",
+        "+release",
+        "/",
+        "Tag {{ version }} and tell {{list}}",
+    ])
+    .assert_success();
+
+    let result = cmd.run_with_stdin(&["add", "template:1", "--var", "version=2.1"], "users@\n");
+    result.assert_success();
+    let task = repo.task_by_summary("Release 2.1").unwrap();
+    assert_eq!(task.notes, "Tag 2.1 and tell users@");
+    assert_eq!(task.tags, vec!["release"]);
+    assert_eq!(
+        repo.task_by_summary("Release {{version}}").unwrap().status,
+        "template"
+    );
+
+    let result = cmd.run_with_stdin(&["add", "template:1"], "");
+    result.assert_failure();
+    assert!(result.stderr().contains("--var version="));
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use rstask_core::task::Task;
+
+fn summaries(result: &common::TestResult) -> Vec<String> {
+    result
+        .parse_tasks()
+        .into_iter()
+        .map(|t| t.summary)
+        .collect()
+}
+
+fn task_modified_days_ago(summary: &str, days: i64) -> Task {
+    Task {
+        modified: Some(Utc::now() - Duration::days(days)),
+        ..Task::new(summary.to_string())
+    }
+}
+
+#[test]
+fn test_modify_updates_modified() {
+    let (repo, cmd) = test_setup!();
+
+    let task = repo.write_task(task_modified_days_ago("Learn Welsh", 30));
+    repo.commit_all("Add task");
+    let before = task.modified.unwrap();
+
+    cmd.run(&["modify", "1", "+languages"]).assert_success();
+
+    let after = repo.task_by_summary("Learn Welsh").unwrap();
+    assert!(after.modified.unwrap() > before);
+    assert_eq!(after.created, task.created);
+}
+
+#[test]
+fn test_sort_by_modified() {
+    let (repo, cmd) = test_setup!();
+
+    repo.write_task(task_modified_days_ago("Sort out garage", 10));
+    repo.write_task(task_modified_days_ago("Learn Welsh", 30));
+    repo.commit_all("Add tasks");
+    cmd.run(&["add", "Water plants", "P1"]).assert_success();
+
+    let result = cmd.run(&["--sort", "modified", "next"]);
+    result.assert_success();
+    assert_eq!(
+        summaries(&result),
+        vec!["Learn Welsh", "Sort out garage", "Water plants"]
+    );
+
+    // The default puts the higher priority first
+    let result = cmd.run(&["next"]);
+    result.assert_success();
+    assert_eq!(summaries(&result)[0], "Water plants");
+}
+
+#[test]
+fn test_sort_unknown_field() {
+    let (_repo, cmd) = test_setup!();
+
+    let result = cmd.run(&["--sort", "colour", "next"]);
+    result.assert_failure();
+    assert!(result.stderr().contains("unknown sort: colour"));
+}
+
+#[test]
+fn test_sort_by_due_and_limit() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Renew passport", "due:30d"])
+        .assert_success();
+    cmd.run(&["add", "Water plants"]).assert_success();
+    cmd.run(&["add", "Pay rent", "due:tomorrow", "P3"])
+        .assert_success();
+
+    let result = cmd.run(&["--sort", "due", "next"]);
+    result.assert_success();
+    assert_eq!(
+        summaries(&result),
+        vec!["Pay rent", "Renew passport", "Water plants"]
+    );
+
+    // Tasks without a due date stay last
+    let result = cmd.run(&["--sort", "due:desc", "show-open"]);
+    result.assert_success();
+    assert_eq!(
+        summaries(&result),
+        vec!["Renew passport", "Pay rent", "Water plants"]
+    );
+
+    let result = cmd.run(&["--sort", "due", "--limit", "2", "next"]);
+    result.assert_success();
+    assert_eq!(summaries(&result), vec!["Pay rent", "Renew passport"]);
+}
+
+#[test]
+fn test_sort_by_urgency() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Tidy desk", "P3"]).assert_success();
+    cmd.run(&["add", "Fix outage", "P0"]).assert_success();
+
+    let result = cmd.run(&["--sort", "urgency", "--limit", "1", "next"]);
+    result.assert_success();
+    assert_eq!(summaries(&result), vec!["Fix outage"]);
+
+    let result = cmd.run(&["--sort", "urgency:asc", "next"]);
+    result.assert_success();
+    assert_eq!(summaries(&result), vec!["Tidy desk", "Fix outage"]);
+}
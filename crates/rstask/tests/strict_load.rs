@@ -0,0 +1,44 @@
+mod common;
+
+fn break_task_file(repo: &common::TestRepo, summary: &str) -> String {
+    let task = repo.task_by_summary(summary).unwrap();
+    let rel_path = format!("pending/{}.md", task.uuid);
+    std::fs::write(
+        repo.path().join(&rel_path),
+        "---\nsummary: [unclosed\n---\n",
+    )
+    .unwrap();
+    rel_path
+}
+
+#[test]
+fn test_broken_file_is_skipped_with_a_count() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Fix the build"]).assert_success();
+    cmd.run(&["add", "Pay rent"]).assert_success();
+    let rel_path = break_task_file(&repo, "Pay rent");
+
+    let result = cmd.run(&["next"]);
+    result.assert_success();
+    assert_eq!(result.parse_tasks().len(), 1);
+    let stderr = result.stderr();
+    assert!(stderr.contains(&format!("Warning: error loading task {}:", rel_path)));
+    assert!(stderr.contains("skipped 1 task file(s)"));
+}
+
+#[test]
+fn test_strict_fails_on_broken_file() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Fix the build"]).assert_success();
+    cmd.run(&["add", "Pay rent"]).assert_success();
+    let rel_path = break_task_file(&repo, "Pay rent");
+
+    let result = cmd.run(&["--strict", "next"]);
+    result.assert_failure();
+    let stderr = result.stderr();
+    assert!(stderr.contains("1 task file(s) failed to load"));
+    assert!(stderr.contains(&rel_path));
+    assert!(result.stdout().is_empty());
+}
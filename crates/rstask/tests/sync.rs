@@ -0,0 +1,138 @@
+mod common;
+
+use common::{TestCmd, TestRemote};
+use rstask_core::task::Task;
+
+#[test]
+fn test_sync_shares_tasks_between_clones() {
+    let (laptop, laptop_cmd) = test_setup!();
+    let remote = TestRemote::new();
+
+    laptop_cmd
+        .run(&["add", "Pay rent", "+home"])
+        .assert_success();
+    laptop.push_to(&remote);
+
+    let desktop = remote.clone_repo();
+    let desktop_cmd = TestCmd::new(&desktop);
+    assert!(desktop.task_by_summary("Pay rent").is_some());
+
+    // Changes on both sides meet through the remote
+    desktop_cmd.run(&["add", "Buy milk"]).assert_success();
+    desktop_cmd.run(&["sync"]).assert_success();
+    laptop_cmd.run(&["done", "1"]).assert_success();
+    laptop_cmd.run(&["sync"]).assert_success();
+    desktop_cmd.run(&["sync"]).assert_success();
+
+    for repo in [&laptop, &desktop] {
+        let rent = repo.task_by_summary("Pay rent").unwrap();
+        assert_eq!(rent.status, "resolved");
+        assert!(repo.task_by_summary("Buy milk").is_some());
+        repo.assert_clean();
+    }
+    assert_eq!(laptop.commit_count(), desktop.commit_count());
+}
+
+#[test]
+fn test_tasks_written_on_disk_are_loaded_and_committed() {
+    let (repo, cmd) = test_setup!();
+
+    let task = repo.write_task(Task::new("Written by another tool".to_string()));
+    repo.commit_all("Import");
+    assert_eq!(repo.commit_count(), 1);
+
+    cmd.run(&["modify", "1", "+imported"]).assert_success();
+    repo.assert_last_commit("Modified Written by another tool");
+    repo.assert_clean();
+
+    let loaded = repo.task_by_summary("Written by another tool").unwrap();
+    assert_eq!(loaded.uuid, task.uuid);
+    assert_eq!(loaded.tags, vec!["imported"]);
+}
+
+#[test]
+fn test_sync_into_repository_without_commits() {
+    let (laptop, laptop_cmd) = test_setup!();
+    let remote = TestRemote::new();
+
+    laptop_cmd.run(&["add", "Pay rent"]).assert_success();
+    // The remote uses a different default branch name than the new repo
+    laptop.git(&["branch", "-M", "trunk"]);
+    laptop.push_to(&remote);
+    remote.git(&["symbolic-ref", "HEAD", "refs/heads/trunk"]);
+
+    let (desktop, desktop_cmd) = test_setup!();
+    assert_eq!(desktop.commit_count(), 0);
+    desktop.git(&["remote", "add", "origin", remote.url()]);
+    desktop_cmd.run(&["sync"]).assert_success();
+    assert!(desktop.task_by_summary("Pay rent").is_some());
+
+    desktop_cmd.run(&["add", "Buy milk"]).assert_success();
+    desktop_cmd.run(&["sync"]).assert_success();
+    laptop_cmd.run(&["sync"]).assert_success();
+    assert!(laptop.task_by_summary("Buy milk").is_some());
+}
+
+#[test]
+fn test_sync_before_anything_is_committed() {
+    let (repo, cmd) = test_setup!();
+    let remote = TestRemote::new();
+    repo.git(&["remote", "add", "origin", remote.url()]);
+
+    // Neither side has commits yet
+    cmd.run(&["sync"]).assert_success();
+    assert_eq!(repo.commit_count(), 0);
+}
+
+#[test]
+fn test_sync_with_other_remote_names() {
+    let (repo, cmd) = test_setup!();
+    let remote = TestRemote::new();
+    repo.git(&["remote", "add", "upstream", remote.url()]);
+
+    cmd.run(&["add", "Pay rent"]).assert_success();
+    cmd.run(&["sync"]).assert_success();
+    assert!(remote.clone_repo().task_by_summary("Pay rent").is_some());
+
+    // With several remotes and no upstream, the choice is left to the user
+    let (other, other_cmd) = test_setup!();
+    let backup = TestRemote::new();
+    other.git(&["remote", "add", "primary", remote.url()]);
+    other.git(&["remote", "add", "backup", backup.url()]);
+    let result = other_cmd.run(&["sync"]);
+    result.assert_failure();
+    assert!(result.stderr().contains("remote.pushDefault"));
+
+    other.git(&["config", "remote.pushDefault", "primary"]);
+    other_cmd.run(&["sync"]).assert_success();
+    assert!(other.task_by_summary("Pay rent").is_some());
+}
+
+#[test]
+fn test_sync_follows_renamed_remote_branch() {
+    let (laptop, laptop_cmd) = test_setup!();
+    let remote = TestRemote::new();
+
+    laptop_cmd.run(&["add", "Pay rent"]).assert_success();
+    laptop.push_to(&remote);
+    let old = laptop.git(&["branch", "--show-current"]).trim().to_string();
+
+    // Another machine renames the shared branch
+    let desktop = remote.clone_repo();
+    desktop.git(&["branch", "-m", "renamed"]);
+    desktop.git(&["push", "--quiet", "-u", "origin", "renamed"]);
+    remote.git(&["symbolic-ref", "HEAD", "refs/heads/renamed"]);
+    desktop.git(&["push", "--quiet", "origin", "--delete", &old]);
+
+    laptop_cmd.run(&["add", "Buy milk"]).assert_success();
+    laptop_cmd.run(&["sync"]).assert_success();
+    assert_eq!(
+        laptop
+            .git(&["rev-parse", "--abbrev-ref", "@{upstream}"])
+            .trim(),
+        "origin/renamed"
+    );
+
+    TestCmd::new(&desktop).run(&["sync"]).assert_success();
+    assert!(desktop.task_by_summary("Buy milk").is_some());
+}
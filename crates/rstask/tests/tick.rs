@@ -0,0 +1,89 @@
+mod common;
+
+use std::path::Path;
+
+/// Pretends the template was last copied long ago, so that its schedule has
+/// come round since
+fn backdate_templates(repo: &Path) {
+    for entry in std::fs::read_dir(repo.join("template")).unwrap() {
+        let path = entry.unwrap().path();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let content: Vec<String> = content
+            .lines()
+            .map(|line| {
+                if line.starts_with("last_instantiated:") {
+                    "last_instantiated: 2020-01-01T00:00:00Z".to_string()
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+        std::fs::write(&path, content.join("\n") + "\n").unwrap();
+    }
+}
+
+#[test]
+fn test_tick_adds_scheduled_tasks_once() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["template", "Water plants", "+home", "schedule:every:day"])
+        .assert_success();
+    let result = cmd.run(&["tick"]);
+    result.assert_success();
+    assert!(result.stdout().contains("No scheduled templates are due"));
+
+    backdate_templates(repo.path());
+    let result = cmd.run(&["tick"]);
+    result.assert_success();
+    assert!(result.stdout().contains("Water plants"));
+    repo.assert_last_commit("Added Water plants from its schedule");
+
+    let result = cmd.run(&["tick"]);
+    result.assert_success();
+    assert!(result.stdout().contains("No scheduled templates are due"));
+
+    let tasks = cmd.run(&["next"]).parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "Water plants");
+    assert_eq!(tasks[0].tags, vec!["home"]);
+
+    cmd.run(&["template", "Bad", "schedule:every:fortnight"])
+        .assert_failure();
+}
+
+#[test]
+fn test_next_ticks_first_unless_disabled() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["template", "Standup notes", "schedule:0 9 * * *"])
+        .assert_success();
+    cmd.run(&["template", "Weekly review"]).assert_success();
+    backdate_templates(repo.path());
+
+    let config = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(config.path().join("rstask")).unwrap();
+    std::fs::write(
+        config.path().join("rstask").join("config.styx"),
+        "tick_before_next false\n",
+    )
+    .unwrap();
+    let env = [("XDG_CONFIG_HOME", config.path().to_str().unwrap())];
+    let result = cmd.run_with_env(&["next"], &env);
+    result.assert_success();
+    assert!(result.parse_tasks().is_empty());
+
+    let result = cmd.run(&["next"]);
+    result.assert_success();
+    assert!(result.stderr().contains("Standup notes from its schedule"));
+    let tasks = result.parse_tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].summary, "Standup notes");
+
+    cmd.run(&["modify", "1", "schedule:none"]).assert_success();
+    assert!(
+        repo.task_by_summary("Standup notes")
+            .unwrap()
+            .schedule
+            .is_empty()
+    );
+}
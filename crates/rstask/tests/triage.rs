@@ -0,0 +1,60 @@
+mod common;
+
+#[test]
+fn test_triage_files_unorganised_tasks() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Fix login page", "project:website", "+bug"])
+        .assert_success();
+    cmd.run(&["add", "Renew passport"]).assert_success();
+    cmd.run(&["add", "Update footer"]).assert_success();
+    cmd.run(&["add", "Call plumber"]).assert_success();
+
+    // Completes "webs" and "bu", asks again after a typo, then skips, marks
+    // done and quits
+    let result = cmd.run_with_stdin(
+        &["triage"],
+        "nonsense\np1 +travel due:2030-01-01\nproject:webs +bu\n",
+    );
+    result.assert_success();
+    assert!(result.stderr().contains("not understood: nonsense"));
+    assert!(result.stdout().contains("Triaged 2 of 3 tasks"));
+    repo.assert_last_commit("Modified Update footer");
+
+    let passport = repo.task_by_summary("Renew passport").unwrap();
+    assert_eq!(passport.priority, "P1");
+    assert_eq!(passport.tags, vec!["travel"]);
+    assert!(passport.due.is_some());
+    let footer = repo.task_by_summary("Update footer").unwrap();
+    assert_eq!(footer.project, "website");
+    assert_eq!(footer.tags, vec!["bug"]);
+
+    let result = cmd.run_with_stdin(&["triage"], "d\n");
+    result.assert_success();
+    assert!(result.stdout().contains("Triaged 1 of 1 tasks"));
+    assert_eq!(
+        repo.task_by_summary("Call plumber").unwrap().status,
+        "resolved"
+    );
+
+    let result = cmd.run_with_stdin(&["triage"], "");
+    result.assert_success();
+    assert!(result.stdout().contains("Nothing to triage"));
+}
+
+#[test]
+fn test_triage_skip_and_quit() {
+    let (repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "First"]).assert_success();
+    cmd.run(&["add", "Second"]).assert_success();
+
+    let result = cmd.run_with_stdin(&["triage"], "\nq\n");
+    result.assert_success();
+    assert!(result.stdout().contains("Triaged 0 of 2 tasks"));
+    repo.assert_last_commit("Added 2: Second");
+
+    cmd.run_with_stdin(&["triage", "--stale", "--days", "0"], "s\n")
+        .assert_success();
+    assert_eq!(repo.task_by_summary("First").unwrap().status, "active");
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use std::fs;
+
+fn usage_counts(stdout: &str) -> Vec<(String, u64)> {
+    let entries: Vec<serde_json::Value> = serde_json::from_str(stdout).unwrap();
+    entries
+        .iter()
+        .map(|e| {
+            (
+                e["command"].as_str().unwrap().to_string(),
+                e["count"].as_u64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_usage_is_off_by_default() {
+    let (_repo, cmd) = test_setup!();
+
+    cmd.run(&["add", "Pay rent"]).assert_success();
+    cmd.run(&["next"]).assert_success();
+
+    let result = cmd.run(&["usage"]);
+    result.assert_success();
+    assert!(result.stdout().contains("usage_stats true"));
+}
+
+#[test]
+fn test_usage_counts_commands_when_enabled() {
+    let (_repo, cmd) = test_setup!();
+
+    let config_home = tempfile::TempDir::new().unwrap();
+    fs::create_dir_all(config_home.path().join("rstask")).unwrap();
+    fs::write(
+        config_home.path().join("rstask").join("config.styx"),
+        "usage_stats true\n",
+    )
+    .unwrap();
+    let env = [("XDG_CONFIG_HOME", config_home.path().to_str().unwrap())];
+
+    cmd.run_with_env(&["add", "Pay rent"], &env)
+        .assert_success();
+    cmd.run_with_env(&["add", "Water plants"], &env)
+        .assert_success();
+    cmd.run_with_env(&[], &env).assert_success();
+    cmd.run_with_env(&["next"], &env).assert_success();
+    cmd.run_with_env(&["done", "1"], &env).assert_success();
+    // Failed commands aren't counted
+    cmd.run_with_env(&["done", "99"], &env).assert_failure();
+
+    let result = cmd.run_with_env(&["usage"], &env);
+    result.assert_success();
+    assert_eq!(
+        usage_counts(&result.stdout()),
+        vec![
+            ("add".to_string(), 2),
+            ("next".to_string(), 2),
+            ("done".to_string(), 1),
+        ]
+    );
+
+    // Viewing or clearing the report isn't counted either
+    cmd.run_with_env(&["usage", "reset"], &env).assert_success();
+    let result = cmd.run_with_env(&["usage"], &env);
+    assert!(result.stdout().contains("No commands counted yet"));
+}